@@ -12,7 +12,8 @@ mod source;
 
 pub use package_id::PackageId;
 pub use report::{
-    Count, CounterBlock, DependencyKind, PackageInfo, QuickReportEntry,
-    QuickSafetyReport, ReportEntry, SafetyReport, UnsafeInfo,
+    Count, CounterBlock, DependencyKind, FfiCount, PackageInfo,
+    QuickReportEntry, QuickSafetyReport, ReportEntry, SafetyReport,
+    ScanWarning, UnsafeFinding, UnsafeInfo, UnsafeKind,
 };
-pub use source::Source;
+pub use source::{Source, SourceKind};
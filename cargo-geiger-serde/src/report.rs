@@ -1,7 +1,9 @@
-use crate::PackageId;
+use crate::{PackageId, SourceKind};
+use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
     ops::{Add, AddAssign},
     path::PathBuf,
 };
@@ -43,6 +45,16 @@ pub struct QuickReportEntry {
     pub package: PackageInfo,
     /// Whether this package forbids the use of `unsafe`
     pub forbids_unsafe: bool,
+    /// Whether this package denies the use of `unsafe` with
+    /// `#![deny(unsafe_code)]`, which unlike `forbid` can be overridden, when
+    /// it doesn't forbid it
+    #[serde(default)]
+    pub denies_unsafe: bool,
+    /// Whether this package forbids the use of `unsafe` only under a
+    /// condition, with `#![cfg_attr(<condition>, forbid(unsafe_code))]` or
+    /// `deny`, when it doesn't unconditionally
+    #[serde(default)]
+    pub conditionally_forbids_unsafe: bool,
 }
 
 /// Report generated from scanning for packages that forbid the use of `unsafe`
@@ -54,6 +66,13 @@ pub struct QuickSafetyReport {
     /// Packages that were not scanned successfully
     #[serde(serialize_with = "set_serde::serialize")]
     pub packages_without_metrics: HashSet<PackageId>,
+    /// Packages left out of the scan with `--ignore`
+    #[serde(
+        default,
+        serialize_with = "set_serde::serialize",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub ignored_packages: HashSet<PackageId>,
 }
 
 /// Entry of the report generated from scanning for the use of `unsafe`
@@ -71,8 +90,29 @@ pub struct SafetyReport {
     pub packages: HashMap<PackageId, ReportEntry>,
     #[serde(serialize_with = "set_serde::serialize")]
     pub packages_without_metrics: HashSet<PackageId>,
+    /// Packages left out of the scan with `--ignore`, which are neither
+    /// counted nor reported as without metrics
+    #[serde(
+        default,
+        serialize_with = "set_serde::serialize",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub ignored_packages: HashSet<PackageId>,
     #[serde(serialize_with = "set_serde::serialize")]
     pub used_but_not_scanned_files: HashSet<PathBuf>,
+    /// The files which failed to be scanned, or which were only scanned by
+    /// decoding them lossily, recorded with `--lossy`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scan_warnings: Vec<ScanWarning>,
+}
+
+/// A file which failed to be scanned, or was only partially read, and why
+#[derive(
+    Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize,
+)]
+pub struct ScanWarning {
+    pub path: PathBuf,
+    pub message: String,
 }
 
 /// Unsafety usage in a package
@@ -84,6 +124,127 @@ pub struct UnsafeInfo {
     pub unused: CounterBlock,
     /// Whether this package forbids the use of `unsafe`
     pub forbids_unsafe: bool,
+    /// Whether this package denies the use of `unsafe` with
+    /// `#![deny(unsafe_code)]`, which unlike `forbid` can be overridden, when
+    /// it doesn't forbid it
+    #[serde(default)]
+    pub denies_unsafe: bool,
+    /// Whether this package forbids the use of `unsafe` only under a
+    /// condition, with `#![cfg_attr(<condition>, forbid(unsafe_code))]` or
+    /// `deny`, when it doesn't unconditionally
+    #[serde(default)]
+    pub conditionally_forbids_unsafe: bool,
+    /// The FFI surface of this package, which is counted apart from its
+    /// unsafe code
+    #[serde(default)]
+    pub ffi: FfiCount,
+    /// The earliest version in which the used unsafe code of this package is
+    /// the same as in the scanned version, when known from `--history`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsafe_introduced_at: Option<Version>,
+    /// The crates.io download count of this package, when known from a
+    /// `--downloads` snapshot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downloads: Option<u64>,
+    /// Whether this package publishes a security policy, when known from
+    /// `--check-security-policy`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_security_policy: Option<bool>,
+    /// Whether the source of this package doesn't match its checksum, when
+    /// listed in the file of `--verify-checksums`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum_mismatch: Option<bool>,
+    /// The ids of the RustSec advisories affecting this version of the
+    /// package, when known from `--audit`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub advisories: Option<Vec<String>>,
+    /// The unsafe items in the files used by the project, sorted by file and
+    /// position, when asked for with `--include-locations`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsafe_findings: Option<Vec<UnsafeFinding>>,
+    /// The number of calls to each high-risk API made by the unsafe code in
+    /// the files used by the project, leaving out the APIs which aren't
+    /// called, when asked for with `--dangerous-apis`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dangerous_api_calls: Option<BTreeMap<String, u64>>,
+    /// Unsafe usage statistics for the macro-expanded code of the library of
+    /// this package, or of its first binary, when scanned with `--expand`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expanded: Option<CounterBlock>,
+    /// Where this package comes from, vendored crates being told apart from
+    /// the crates of their registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_kind: Option<SourceKind>,
+    /// The name of the registry this package comes from in the cargo
+    /// configuration, such as `crates.io`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// The number of findings of each detector enabled with `--detector` in
+    /// the files used by the build, by the name of the detector
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detector_findings: Option<BTreeMap<String, u64>>,
+    /// The percentage of the crates of a `--percentiles` snapshot of about
+    /// the same size with a lower share of unsafe items, when the snapshot
+    /// has such crates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsafe_density_percentile: Option<u8>,
+    /// The number of lines of the files used by the build, blank and comment
+    /// lines included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines_of_code: Option<u64>,
+}
+
+impl UnsafeInfo {
+    /// The unsafe expressions used by the build per thousand lines of code,
+    /// unless no lines of code were counted
+    pub fn unsafe_per_kloc(&self) -> Option<f64> {
+        self.lines_of_code
+            .filter(|lines_of_code| *lines_of_code > 0)
+            .map(|lines_of_code| {
+                self.used.exprs.unsafe_ as f64 * 1000.0 / lines_of_code as f64
+            })
+    }
+}
+
+/// An unsafe item in a source file. Lines and columns are 1-based, the end
+/// being the last character of the item.
+#[derive(
+    Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize,
+)]
+pub struct UnsafeFinding {
+    /// The path of the file, relative to the workspace root when the file is
+    /// in it
+    pub file: String,
+    pub kind: UnsafeKind,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// The kind of unsafe item found in a file
+#[derive(
+    Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize,
+)]
+pub enum UnsafeKind {
+    Function,
+    Block,
+    Impl,
+    Trait,
+    Method,
+}
+
+impl fmt::Display for UnsafeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            UnsafeKind::Function => "function",
+            UnsafeKind::Block => "block",
+            UnsafeKind::Impl => "impl",
+            UnsafeKind::Trait => "trait",
+            UnsafeKind::Method => "method",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 /// Kind of dependency for a package
@@ -134,6 +295,34 @@ impl AddAssign for Count {
     }
 }
 
+/// Number of items of the FFI surface: the items declared in `extern` blocks,
+/// and the functions with an `extern` ABI or exported with `#[no_mangle]` or
+/// `#[export_name]`
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FfiCount {
+    /// Number of FFI items in code used by the project
+    pub used: u64,
+    /// Number of FFI items in code not used by the project
+    pub unused: u64,
+}
+
+impl Add for FfiCount {
+    type Output = FfiCount;
+
+    fn add(self, other: FfiCount) -> FfiCount {
+        FfiCount {
+            used: self.used + other.used,
+            unused: self.unused + other.unused,
+        }
+    }
+}
+
+impl AddAssign for FfiCount {
+    fn add_assign(&mut self, rhs: FfiCount) {
+        *self = self.clone() + rhs;
+    }
+}
+
 /// Unsafe usage metrics collection.
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CounterBlock {
@@ -152,6 +341,15 @@ impl CounterBlock {
             || self.item_traits.unsafe_ > 0
             || self.methods.unsafe_ > 0
     }
+
+    /// The number of unsafe functions, expressions, impls, traits and methods
+    pub fn total_unsafe(&self) -> u64 {
+        self.functions.unsafe_
+            + self.exprs.unsafe_
+            + self.item_impls.unsafe_
+            + self.item_traits.unsafe_
+            + self.methods.unsafe_
+    }
 }
 
 impl Add for CounterBlock {
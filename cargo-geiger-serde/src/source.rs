@@ -1,6 +1,13 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use url::Url;
 
+/// The URLs of the index of crates.io, with the git and the sparse protocol
+const CRATES_IO_INDEX_URLS: [&str; 2] = [
+    "https://github.com/rust-lang/crates.io-index",
+    "https://index.crates.io/",
+];
+
 /// Source of a package (where it is fetched from)
 #[derive(
     Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
@@ -10,3 +17,77 @@ pub enum Source {
     Registry { name: String, url: Url },
     Path(Url),
 }
+
+impl Source {
+    pub fn is_crates_io(&self) -> bool {
+        match self {
+            Source::Registry { url, .. } => {
+                CRATES_IO_INDEX_URLS.contains(&url.as_str())
+            }
+            _ => false,
+        }
+    }
+
+    /// The kind of this source. A vendored crate can't be told apart from its
+    /// source alone, and is of the kind of its registry.
+    pub fn kind(&self) -> SourceKind {
+        match self {
+            Source::Git { .. } => SourceKind::Git,
+            Source::Path(_) => SourceKind::Path,
+            Source::Registry { .. } if self.is_crates_io() => {
+                SourceKind::CratesIo
+            }
+            Source::Registry { .. } => SourceKind::Registry,
+        }
+    }
+}
+
+/// The source as in the package ID specs of cargo, such as
+/// `registry+https://github.com/rust-lang/crates.io-index`, with the
+/// revision of a git source after a `#`
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Git { url, rev } => write!(f, "git+{}#{}", url, rev),
+            Source::Registry { url, .. } => write!(f, "registry+{}", url),
+            Source::Path(url) => write!(f, "path+{}", url),
+        }
+    }
+}
+
+/// The kind of source of a package. The variants are declared from the
+/// least to the most reviewed by others, crates.io being last.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Eq,
+    Hash,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+)]
+pub enum SourceKind {
+    Git,
+    Path,
+    /// A crate of a registry, copied into the workspace by `cargo vendor`
+    Vendored,
+    /// An alternate registry
+    Registry,
+    CratesIo,
+}
+
+impl fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SourceKind::Git => "git",
+            SourceKind::Path => "path",
+            SourceKind::Vendored => "vendored",
+            SourceKind::Registry => "registry",
+            SourceKind::CratesIo => "crates.io",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,352 @@
+use cargo::core::GitReference;
+use cargo::sources::git::GitRemote;
+use cargo::util::IntoUrl;
+use cargo::{CliError, Config};
+use cargo_metadata::semver::{Version, VersionReq};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use walkdir::WalkDir;
+
+/// The repository of the `RustSec` advisory database, fetched when there is no
+/// local copy of it
+const ADVISORY_DB_URL: &str = "https://github.com/rustsec/advisory-db";
+
+/// The directory in the cargo home of the copy of the advisory database kept
+/// by cargo-audit, which is read instead of fetching the database
+const ADVISORY_DB_DIRECTORY: &str = "advisory-db";
+
+/// The advisories of the `RustSec` database which affect each crate, as read
+/// with `--audit`
+#[derive(Debug, Default)]
+pub struct Advisories {
+    crate_advisories: HashMap<String, Vec<Advisory>>,
+}
+
+impl Advisories {
+    /// The ids of the advisories affecting the given version of a crate, in
+    /// the order of their ids
+    pub fn get(&self, name: &str, version: &Version) -> Vec<String> {
+        let mut advisory_ids = self
+            .crate_advisories
+            .get(name)
+            .map(|advisories| {
+                advisories
+                    .iter()
+                    .filter(|advisory| advisory.affects(version))
+                    .map(|advisory| advisory.id.clone())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        advisory_ids.sort();
+        advisory_ids
+    }
+}
+
+/// An advisory of the database, with the versions of the crate it doesn't
+/// affect
+#[derive(Debug, Eq, PartialEq)]
+struct Advisory {
+    id: String,
+    patched: Vec<VersionReq>,
+    unaffected: Vec<VersionReq>,
+}
+
+impl Advisory {
+    fn affects(&self, version: &Version) -> bool {
+        !self
+            .patched
+            .iter()
+            .chain(&self.unaffected)
+            .any(|version_req| version_req.matches(version))
+    }
+}
+
+/// The front matter of an advisory file
+#[derive(Deserialize)]
+struct AdvisoryFile {
+    advisory: AdvisoryMetadata,
+    #[serde(default)]
+    versions: AdvisoryVersions,
+}
+
+#[derive(Deserialize)]
+struct AdvisoryMetadata {
+    id: String,
+    package: String,
+    informational: Option<String>,
+    withdrawn: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct AdvisoryVersions {
+    #[serde(default)]
+    patched: Vec<VersionReq>,
+    #[serde(default)]
+    unaffected: Vec<VersionReq>,
+}
+
+#[derive(Debug)]
+pub struct AdvisoryParseError {
+    pub message: String,
+}
+
+impl Error for AdvisoryParseError {}
+
+impl fmt::Display for AdvisoryParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// The annotation of a crate in the tree, such as
+/// ` (advisories: RUSTSEC-2021-0001)`
+pub fn format_advisories(advisory_ids: &[String]) -> String {
+    match advisory_ids {
+        [] => String::new(),
+        _ => format!(" (advisories: {})", advisory_ids.join(", ")),
+    }
+}
+
+/// Read the advisory database, or return `None` without `--audit`.
+///
+/// The database is read from the directory given with `--advisory-db`, or
+/// else from the copy kept by cargo-audit in the cargo home. Without either,
+/// it is fetched into a temporary directory, unless the network is disabled
+/// with `--offline`.
+pub fn read_advisories(
+    audit: bool,
+    advisory_db: &Option<PathBuf>,
+    config: &Config,
+) -> Result<Option<Advisories>, CliError> {
+    if !audit {
+        return Ok(None);
+    }
+    if let Some(advisory_db) = advisory_db {
+        return read_advisory_db(advisory_db).map(Some);
+    }
+    let cargo_home_advisory_db =
+        config.home().as_path_unlocked().join(ADVISORY_DB_DIRECTORY);
+    if cargo_home_advisory_db.is_dir() {
+        return read_advisory_db(&cargo_home_advisory_db).map(Some);
+    }
+    if config.offline() {
+        eprintln!(
+            "No advisory database found at {}, and `--offline` is set, \
+            `--advisory-db` gives a local copy of it",
+            cargo_home_advisory_db.display()
+        );
+        return Err(CliError::code(1));
+    }
+    let temp_dir = fetch_advisory_db(config)?;
+    read_advisory_db(&temp_dir.path().join("checkout")).map(Some)
+}
+
+fn fetch_advisory_db(config: &Config) -> Result<TempDir, CliError> {
+    let url = ADVISORY_DB_URL
+        .into_url()
+        .map_err(|e| CliError::new(e, 1))?;
+    let temp_dir = TempDir::new().map_err(|e| {
+        eprintln!("Failed to create a directory to fetch the advisories into");
+        anyhow::Error::from(e)
+    })?;
+    let (git_database, revision) = GitRemote::new(&url)
+        .checkout(
+            &temp_dir.path().join("database"),
+            None,
+            &GitReference::DefaultBranch,
+            None,
+            config,
+        )
+        .map_err(|e| {
+            eprintln!("Failed to fetch the advisory database: {}", url);
+            CliError::new(e, 1)
+        })?;
+    git_database
+        .copy_to(revision, &temp_dir.path().join("checkout"), config)
+        .map_err(|e| {
+            eprintln!("Failed to check out the advisory database: {}", url);
+            CliError::new(e, 1)
+        })?;
+    Ok(temp_dir)
+}
+
+/// Read the advisories of the `crates` directory of the database, leaving
+/// out the withdrawn and informational ones except for those about unsound
+/// code, which is what the unsafe code of a crate may be
+fn read_advisory_db(advisory_db: &Path) -> Result<Advisories, CliError> {
+    let crates_directory = advisory_db.join("crates");
+    if !crates_directory.is_dir() {
+        eprintln!("No advisory database found at: {}", advisory_db.display());
+        return Err(CliError::code(1));
+    }
+    let mut advisories = Advisories::default();
+    for entry in WalkDir::new(&crates_directory)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|extension| extension == "md")
+        })
+    {
+        let advisory_content =
+            fs::read_to_string(entry.path()).map_err(|e| {
+                eprintln!(
+                    "Failed to read advisory: {}",
+                    entry.path().display()
+                );
+                anyhow::Error::from(e)
+            })?;
+        let parsed_advisory =
+            parse_advisory(&advisory_content).map_err(|e| {
+                eprintln!(
+                    "Failed to parse advisory: {}",
+                    entry.path().display()
+                );
+                CliError::new(anyhow::Error::from(e), 1)
+            })?;
+        if let Some((package, advisory)) = parsed_advisory {
+            advisories
+                .crate_advisories
+                .entry(package)
+                .or_default()
+                .push(advisory);
+        }
+    }
+    Ok(advisories)
+}
+
+/// The crate and advisory of the Toml front matter of an advisory, between
+/// `` ```toml `` and `` ``` `` lines, or `None` if the advisory is withdrawn
+/// or only informational
+fn parse_advisory(
+    advisory_content: &str,
+) -> Result<Option<(String, Advisory)>, AdvisoryParseError> {
+    let front_matter = advisory_content
+        .trim_start()
+        .strip_prefix("```toml")
+        .and_then(|rest| rest.split_once("\n```"))
+        .map(|(front_matter, _)| front_matter)
+        .ok_or_else(|| AdvisoryParseError {
+            message: String::from("No ```toml front matter"),
+        })?;
+    let advisory_file = toml_edit::easy::from_str::<AdvisoryFile>(front_matter)
+        .map_err(|e| AdvisoryParseError {
+            message: e.to_string(),
+        })?;
+    let metadata = advisory_file.advisory;
+    let is_reported = metadata.withdrawn.is_none()
+        && matches!(metadata.informational.as_deref(), None | Some("unsound"));
+    if !is_reported {
+        return Ok(None);
+    }
+    Ok(Some((
+        metadata.package,
+        Advisory {
+            id: metadata.id,
+            patched: advisory_file.versions.patched,
+            unaffected: advisory_file.versions.unaffected,
+        },
+    )))
+}
+
+#[cfg(test)]
+mod advisory_tests {
+    use super::*;
+
+    use rstest::*;
+
+    const ADVISORY: &str = r#"```toml
+[advisory]
+id = "RUSTSEC-2020-0001"
+package = "foo"
+date = "2020-01-01"
+
+[versions]
+patched = [">= 1.2.0"]
+unaffected = ["< 0.9.0"]
+```
+
+# Memory corruption in foo
+"#;
+
+    #[rstest(
+        input_version,
+        expected_advisory_ids,
+        case("1.1.0", vec!["RUSTSEC-2020-0001"]),
+        case("1.2.0", vec![]),
+        case("0.8.0", vec![])
+    )]
+    fn advisories_get_test(
+        input_version: &str,
+        expected_advisory_ids: Vec<&str>,
+    ) {
+        let (package, advisory) = parse_advisory(ADVISORY).unwrap().unwrap();
+        let mut advisories = Advisories::default();
+        advisories.crate_advisories.insert(package, vec![advisory]);
+
+        assert_eq!(
+            advisories.get("foo", &Version::parse(input_version).unwrap()),
+            expected_advisory_ids
+        );
+        assert!(advisories
+            .get("bar", &Version::parse(input_version).unwrap())
+            .is_empty());
+    }
+
+    #[rstest(
+        input_extra_metadata,
+        expected_is_reported,
+        case("", true),
+        case("informational = \"unsound\"\n", true),
+        case("informational = \"unmaintained\"\n", false),
+        case("withdrawn = \"2020-02-01\"\n", false)
+    )]
+    fn parse_advisory_test(
+        input_extra_metadata: &str,
+        expected_is_reported: bool,
+    ) {
+        let advisory_content = ADVISORY.replace(
+            "date = \"2020-01-01\"\n",
+            &format!("date = \"2020-01-01\"\n{}", input_extra_metadata),
+        );
+
+        assert_eq!(
+            parse_advisory(&advisory_content).unwrap().is_some(),
+            expected_is_reported
+        );
+    }
+
+    #[rstest]
+    fn parse_advisory_test_invalid() {
+        assert!(parse_advisory("# No front matter").is_err());
+    }
+
+    #[rstest(
+        input_advisory_ids,
+        expected_annotation,
+        case(vec![], ""),
+        case(
+            vec!["RUSTSEC-2020-0001", "RUSTSEC-2021-0002"],
+            " (advisories: RUSTSEC-2020-0001, RUSTSEC-2021-0002)"
+        )
+    )]
+    fn format_advisories_test(
+        input_advisory_ids: Vec<&str>,
+        expected_annotation: &str,
+    ) {
+        let advisory_ids = input_advisory_ids
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        assert_eq!(format_advisories(&advisory_ids), expected_annotation);
+    }
+}
@@ -0,0 +1,210 @@
+use cargo::CliError;
+use cargo_metadata::semver::Version;
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// A crate of the allowlist, every version of it unless a version is given
+#[derive(Debug, Eq, PartialEq)]
+struct AllowlistEntry {
+    name: String,
+    version: Option<Version>,
+}
+
+impl FromStr for AllowlistEntry {
+    type Err = AllowlistParseError;
+
+    /// Parse `<name>` or `<name>:<version>`, such as `libc:0.2.132`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_error = || AllowlistParseError {
+            entry: s.to_string(),
+        };
+        let (name, version) = match s.trim().split_once(':') {
+            Some((name, version)) => (
+                name.trim(),
+                Some(
+                    Version::parse(version.trim())
+                        .map_err(|_| parse_error())?,
+                ),
+            ),
+            None => (s.trim(), None),
+        };
+        if name.is_empty() {
+            return Err(parse_error());
+        }
+        Ok(AllowlistEntry {
+            name: name.to_string(),
+            version,
+        })
+    }
+}
+
+/// The crates read from the file given with `--allowlist`, which are
+/// displayed as not using unsafe code and don't fail `--deny-unsafe`,
+/// whatever unsafe code they use
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Allowlist {
+    entries: Vec<AllowlistEntry>,
+}
+
+impl Allowlist {
+    pub fn contains(&self, name: &str, version: &Version) -> bool {
+        self.entries.iter().any(|entry| {
+            entry.name == name
+                && entry.version.as_ref().is_none_or(|v| v == version)
+        })
+    }
+
+    /// A crate on each line, empty lines and lines starting with `#` are
+    /// ignored
+    pub fn from_lines(s: &str) -> Result<Self, AllowlistParseError> {
+        let entries = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(AllowlistEntry::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Allowlist { entries })
+    }
+
+    /// A `crates` array of entries, such as `crates = ["libc:0.2.132"]`
+    pub fn from_toml(s: &str) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct AllowlistFile {
+            crates: Vec<String>,
+        }
+
+        let allowlist_file = toml_edit::easy::from_str::<AllowlistFile>(s)?;
+        let entries = allowlist_file
+            .crates
+            .iter()
+            .map(|entry| AllowlistEntry::from_str(entry))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Allowlist { entries })
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct AllowlistParseError {
+    pub entry: String,
+}
+
+impl Error for AllowlistParseError {}
+
+impl fmt::Display for AllowlistParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse allowlist entry `{}`, expected `<name>` or `<name>:<version>`",
+            self.entry
+        )
+    }
+}
+
+/// Read the file given with `--allowlist`, or `None` if no file is given. A
+/// file with the `.toml` extension is read as TOML, any other file as a
+/// crate on each line.
+pub fn read_allowlist_file(
+    allowlist_path: &Option<PathBuf>,
+) -> Result<Option<Allowlist>, CliError> {
+    match allowlist_path {
+        Some(allowlist_path) => read_allowlist(allowlist_path).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn read_allowlist(allowlist_path: &Path) -> Result<Allowlist, CliError> {
+    let allowlist_content =
+        fs::read_to_string(allowlist_path).map_err(|e| {
+            eprintln!(
+                "Failed to read allowlist from file: {}",
+                allowlist_path.display()
+            );
+            anyhow::Error::from(e)
+        })?;
+    let allowlist = match allowlist_path.extension() {
+        Some(extension) if extension == "toml" => {
+            Allowlist::from_toml(&allowlist_content)
+        }
+        _ => Allowlist::from_lines(&allowlist_content).map_err(Into::into),
+    };
+    allowlist.map_err(|e| {
+        eprintln!(
+            "Failed to parse allowlist from file: {}",
+            allowlist_path.display()
+        );
+        CliError::new(e, 1)
+    })
+}
+
+#[cfg(test)]
+mod allowlist_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest(
+        input_name,
+        input_version,
+        expected_contains,
+        case("libc", "0.2.132", true),
+        case("libc", "0.2.100", true),
+        case("winapi", "0.3.9", true),
+        case("winapi", "0.3.8", false),
+        case("foo", "0.1.0", false)
+    )]
+    fn allowlist_from_lines_test(
+        input_name: &str,
+        input_version: &str,
+        expected_contains: bool,
+    ) {
+        let allowlist =
+            Allowlist::from_lines("# audited\nlibc\n\n  winapi:0.3.9 \n")
+                .unwrap();
+
+        assert_eq!(
+            allowlist
+                .contains(input_name, &Version::parse(input_version).unwrap()),
+            expected_contains
+        );
+    }
+
+    #[rstest]
+    fn allowlist_from_lines_test_invalid_version() {
+        assert_eq!(
+            Allowlist::from_lines("libc\nwinapi:latest\n"),
+            Err(AllowlistParseError {
+                entry: String::from("winapi:latest")
+            })
+        );
+    }
+
+    #[rstest]
+    fn allowlist_from_toml_test() {
+        let allowlist =
+            Allowlist::from_toml("crates = [\"libc\", \"winapi:0.3.9\"]\n")
+                .unwrap();
+
+        assert_eq!(
+            allowlist,
+            Allowlist::from_lines("libc\nwinapi:0.3.9").unwrap()
+        );
+        assert!(Allowlist::from_toml("crates = \"libc\"\n").is_err());
+    }
+
+    #[rstest]
+    fn read_allowlist_file_test() {
+        let allowlist_directory = tempfile::tempdir().unwrap();
+        let allowlist_path = allowlist_directory.path().join("allowlist.toml");
+        fs::write(&allowlist_path, "crates = [\"libc\"]\n").unwrap();
+
+        let allowlist =
+            read_allowlist_file(&Some(allowlist_path)).unwrap().unwrap();
+
+        assert!(allowlist.contains("libc", &Version::new(0, 2, 132)));
+        assert!(read_allowlist_file(&None).unwrap().is_none());
+    }
+}
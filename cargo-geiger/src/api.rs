@@ -0,0 +1,207 @@
+use crate::args::{Args, FeaturesArgs, Verbosity};
+use crate::cli::{get_cargo_metadata, get_krates, get_workspace};
+use crate::explain::{find_package, CrateSpec};
+use crate::graph::build_graph;
+use crate::mapping::{CargoMetadataParameters, QueryResolve};
+use crate::scan::{scan_to_report, ScanReport};
+
+use cargo::util::important_paths;
+use cargo::{CliError, Config};
+use cargo_metadata::{Metadata, PackageId};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+/// What to scan, for tools calling `scan_workspace` instead of running the
+/// command line. The fields not set are the defaults of the command line.
+#[derive(Clone, Debug, Default)]
+pub struct ScanOptions {
+    pub features_args: FeaturesArgs,
+    /// Count unsafe usage in tests, as with `--include-tests`
+    pub include_tests: bool,
+    /// The number of threads scanning the files, or the number of logical
+    /// CPUs if not given
+    pub jobs: Option<NonZeroUsize>,
+    /// The `Cargo.toml` of the package, or the one of the current directory if
+    /// not given
+    pub manifest_path: Option<PathBuf>,
+    /// Run without accessing the network, as with `--offline`
+    pub offline: bool,
+    /// The package of the resolved dependency graph to scan from, as with
+    /// `--root`
+    pub root: Option<CrateSpec>,
+}
+
+impl From<&ScanOptions> for Args {
+    /// The command line arguments of the options, without any output, not
+    /// even the progress of cargo
+    fn from(scan_options: &ScanOptions) -> Self {
+        Args {
+            features_args: scan_options.features_args.clone(),
+            include_tests: scan_options.include_tests,
+            jobs: scan_options.jobs,
+            manifest_path: scan_options.manifest_path.clone(),
+            offline: scan_options.offline,
+            quiet: true,
+            root: scan_options.root.clone(),
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        }
+    }
+}
+
+/// Build the dependency graph of the package given by the options, and scan
+/// it for unsafe usage, returning the report which the Json output is made of.
+///
+/// ```no_run
+/// # use cargo_geiger::api::{scan_workspace, ScanOptions};
+/// let scan_report = scan_workspace(&ScanOptions::default()).unwrap();
+/// for entry in scan_report.report.packages.values() {
+///     println!("{}: {:?}", entry.package.id.name, entry.unsafety.used);
+/// }
+/// ```
+pub fn scan_workspace(
+    scan_options: &ScanOptions,
+) -> Result<ScanReport, CliError> {
+    let args = Args::from(scan_options);
+    let mut config = Config::default()?;
+    args.update_config(&mut config)?;
+    scan_workspace_with_args(&args, &config)
+}
+
+/// Like `scan_workspace`, but with any command line arguments, the output
+/// format aside
+pub fn scan_workspace_with_args(
+    args: &Args,
+    config: &Config,
+) -> Result<ScanReport, CliError> {
+    let cargo_metadata = get_cargo_metadata(args, config)?;
+    let krates = get_krates(&cargo_metadata)?;
+
+    let cargo_metadata_parameters = CargoMetadataParameters {
+        metadata: &cargo_metadata,
+        krates: &krates,
+    };
+
+    let workspace = get_workspace(config, args.manifest_path.clone())?;
+    let cargo_metadata_root_package_id =
+        get_root_package_id(args, config, &cargo_metadata)?;
+    let global_rustc = config.load_global_rustc(Some(&workspace))?;
+
+    let graph = build_graph(
+        args,
+        &cargo_metadata_parameters,
+        &global_rustc.host,
+        &global_rustc.path,
+        cargo_metadata_root_package_id.clone(),
+    )?;
+
+    let query_resolve_root_package_id = args.package.as_ref().map_or(
+        cargo_metadata_root_package_id.clone(),
+        |package_query| {
+            krates
+                .query_resolve(package_query)
+                .map_or(cargo_metadata_root_package_id, |package_id| package_id)
+        },
+    );
+
+    scan_to_report(
+        args,
+        &cargo_metadata_parameters,
+        config,
+        &graph,
+        query_resolve_root_package_id,
+        &workspace,
+    )
+}
+
+/// The package given with `--root`, or else the root package of the
+/// workspace, which a virtual manifest doesn't have
+pub fn get_root_package_id(
+    args: &Args,
+    config: &Config,
+    cargo_metadata: &Metadata,
+) -> Result<PackageId, CliError> {
+    if let Some(crate_spec) = &args.root {
+        find_package(crate_spec, cargo_metadata)
+            .map(|package| package.id.clone())
+    } else if let Some(cargo_metadata_root_package) =
+        cargo_metadata.root_package()
+    {
+        Ok(cargo_metadata_root_package.id.clone())
+    } else {
+        eprintln!(
+            "manifest path `{}` is a virtual manifest, but this command requires running against an actual package in this workspace",
+            match args.manifest_path.clone() {
+                Some(path) => path,
+                None => important_paths::find_root_manifest_for_wd(config.cwd())?,
+            }.as_os_str().to_str().unwrap()
+        );
+
+        Err(CliError::code(1))
+    }
+}
+
+/// The members of the workspace scanned with `--workspace`, in the order of
+/// their names, without the ones given with `--exclude`
+pub fn get_workspace_member_ids(
+    args: &Args,
+    cargo_metadata: &Metadata,
+) -> Result<Vec<PackageId>, CliError> {
+    let exclude = &args.workspace_args.exclude;
+    if let Some(unknown_member) = exclude.iter().find(|name| {
+        !cargo_metadata
+            .workspace_members
+            .iter()
+            .any(|package_id| &cargo_metadata[package_id].name == *name)
+    }) {
+        eprintln!("No workspace member to exclude named: {}", unknown_member);
+        return Err(CliError::code(1));
+    }
+
+    let mut member_packages = cargo_metadata
+        .workspace_members
+        .iter()
+        .map(|package_id| &cargo_metadata[package_id])
+        .filter(|package| !exclude.contains(&package.name))
+        .collect::<Vec<_>>();
+    if member_packages.is_empty() {
+        eprintln!("All workspace members are excluded from `--workspace`");
+        return Err(CliError::code(1));
+    }
+    member_packages.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(member_packages
+        .into_iter()
+        .map(|package| package.id.clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod api_tests {
+    use super::*;
+
+    use rstest::*;
+    use std::str::FromStr;
+
+    #[rstest]
+    fn args_from_scan_options_test() {
+        let scan_options = ScanOptions {
+            features_args: FeaturesArgs {
+                all_features: true,
+                ..Default::default()
+            },
+            include_tests: true,
+            manifest_path: Some(PathBuf::from("foo/Cargo.toml")),
+            root: Some(CrateSpec::from_str("bar:0.1.0").unwrap()),
+            ..Default::default()
+        };
+
+        let args = Args::from(&scan_options);
+
+        assert!(args.features_args.all_features);
+        assert!(args.include_tests);
+        assert_eq!(args.manifest_path, scan_options.manifest_path);
+        assert_eq!(args.root, scan_options.root);
+        assert!(args.quiet);
+        assert_eq!(args.verbosity, Verbosity::Quiet);
+    }
+}
@@ -1,9 +1,36 @@
 use crate::args::Verbosity::{Normal, Quiet, Verbose};
-use crate::format::print_config::OutputFormat;
+use crate::badge::parse_badge_args;
+use crate::compare::{parse_compare_args, CompareSpec};
+use crate::completions::{parse_generate_args, GenerateCommand};
+use crate::explain::CrateSpec;
+use crate::format::json::JsonVersion;
+use crate::format::print_config::{
+    ColorScheme, CrateSort, EmittedReport, OutputFormat,
+};
+use crate::format::summary::SummaryFormat;
+use crate::format::{Charset, Symbols};
+use crate::gate::ExitCodeMode;
+use crate::graph::extra_deps::ExtraDeps;
+use crate::inspect::parse_inspect_args;
+use crate::license::LicenseExpression;
+use crate::limits::UnsafeLimits;
+use crate::log_sink::LogSink;
+use crate::merge::FacadeMerge;
+use crate::policy::CategoryPolicy;
+use crate::readme::ReadmeMarkersError;
+use crate::registry::PackageSpec;
+use crate::scan::SafetyDocsForbidOnlyError;
+use crate::scan_log::{parse_history_args, HistoryCommand};
+use crate::score::CategoryWeights;
+use crate::source_kind::{GroupBy, SourceFilter};
+use crate::source_map::SourceMapEntry;
 
 use cargo::core::shell::ColorChoice;
 use cargo::{CliResult, Config};
+use geiger::detector::BuiltInDetector;
 use pico_args::Arguments;
+use std::ffi::OsString;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 /// Constant `&str` containing help text
@@ -12,82 +39,904 @@ pub const HELP: &str =
 
 USAGE:
     cargo geiger [OPTIONS]
+    cargo geiger compare <CRATE> <OLD_VERSION> <NEW_VERSION> [OPTIONS]
+    cargo geiger history <record|show> [OPTIONS]
+    cargo geiger badge [OPTIONS]
+    cargo geiger inspect <CRATE> [OPTIONS]
+    cargo geiger completions <SHELL>
+    cargo geiger manpage
 
 OPTIONS:
     -p, --package <SPEC>          Package to be used as the root of the tree.
+        --root <NAME[:VERSION]>   Package of the resolved dependency graph to
+                                  scan from instead of the root package, such
+                                  as a member of a virtual workspace. Fails
+                                  unless exactly one package matches.
+        --workspace               Scan every member of the workspace in a
+                                  single build, and print one report of the
+                                  crates they depend on, a shared dependency
+                                  counted once, in place of the tree. Works
+                                  with a virtual manifest.
+        --exclude <MEMBER>        Leave the named member out of `--workspace`.
+                                  May be given multiple times.
+        --member-summary          Print a table of each member and its
+                                  dependencies below the `--workspace` report,
+                                  or on stderr with the machine-readable
+                                  output formats.
         --features <FEATURES>     Space-separated list of features to activate.
         --all-features            Activate all available features.
         --no-default-features     Do not activate the `default` feature.
-        --target <TARGET>         Set the target triple.
+        --compare-features        Scan with the default features, and again with
+                                  the features selected by the options above
+                                  (or all features, if none are selected), and
+                                  print the difference in unsafe usage.
+        --feature-matrix          Scan every combination of the features of
+                                  the package, each without the default
+                                  features, and print the worst case unsafe
+                                  usage of each crate, with the features it was
+                                  found with. Runs a full build for each
+                                  combination, up to 256 of them.
+        --feature-set <FEATURES>  Space-separated list of features to scan with
+                                  `--feature-matrix`, in place of every
+                                  combination. Can be given multiple times. The
+                                  default features are only enabled if
+                                  `default` is listed.
+        --feature-report          Print only, for each feature activated by
+                                  cargo for a crate which enables optional
+                                  dependencies, the crates no longer depended
+                                  on with the feature disabled, and their
+                                  unsafe code used by the build, to find the
+                                  features pulling in unsafe-heavy crates.
+        --target <TARGET>         Set the target triple. The build is for
+                                  the target, and crates only depended on by
+                                  other platforms are left out.
+        --profile <PROFILE>       Build profile to scan with: dev, release
+                                  [default: dev]. The profile decides
+                                  `debug_assertions`, so code behind
+                                  `#[cfg(debug_assertions)]` is only used by
+                                  dev builds, and unsafe usage of release and
+                                  dev builds can differ.
         --all-targets             Return dependencies for all targets. By
                                   default only the host target is matched.
         --manifest-path <PATH>    Path to Cargo.toml.
+        --git <URL>               Clone the git repository into a temporary
+                                  directory, and scan the package at its root
+                                  in place of `--manifest-path`. The hash of
+                                  the commit scanned is printed on stderr. The
+                                  clone is removed afterwards.
+        --rev <SHA>               Commit to check out with `--git`.
+        --branch <BRANCH>         Branch to check out with `--git`.
+        --tag <TAG>               Tag to check out with `--git`. Without
+                                  `--rev`, `--branch` or `--tag`, the default
+                                  branch is checked out.
+        --package-spec <NAME[@VERSION]>
+                                  Download the package from crates.io into a
+                                  temporary directory, and scan it with its
+                                  dependencies in place of `--manifest-path`,
+                                  without a project depending on it. Without
+                                  a version, the latest one is scanned.
     -i, --invert                  Invert the tree direction.
         --no-indent               Display the dependencies as a list (rather
                                   than a tree).
         --prefix-depth            Display the dependencies as a list (rather
                                   than a tree), but prefixed with the depth.
+        --depth-column            Display the dependencies as a list (rather
+                                  than a tree), with the depth in a column of
+                                  fixed width, e.g. \"  2 | \".
+        --sort <ORDER>            Order the crates by one of:
+                                  Flattening the tree into a list of each
+                                  crate once, ties ordered by name:
+                                    name          by name
+                                    unsafe        least used unsafe code first
+                                    unsafe-desc   most used unsafe code first
+                                  Keeping the tree, ordering the dependencies
+                                  of each crate, also in the Ratio and
+                                  GitHubMarkdown tables:
+                                    unsafe-count  most used unsafe code first
+                                    unsafe-ratio  highest share of used code
+                                                  being unsafe first
+                                    total-unsafe  most unsafe code found
+                                                  first, used or not
+                                    ffi           most FFI items used first
     -a, --all                     Don't truncate dependencies that have already
                                   been displayed.
-    --format <FORMAT>             Format string used for printing dependencies
-                                  [default: {p}].
-    --output-format               Output format for the report: Ascii, GitHubMarkdown,
-                                  Json, Utf8, Ratio [default: Utf8]
+    --format <FORMAT>             Format string used for printing dependencies,
+                                  where {p} is the package name and version,
+                                  {v} the version, {l} the license, {r} the
+                                  repository, {s} the source, such as a git
+                                  repository or a path, and {fn}, {expr},
+                                  {impl} and {trait} the unsafe functions,
+                                  expressions, impls and traits used by the
+                                  build and in total, e.g. \"2/3\", and {D}
+                                  the unsafe expressions used per thousand
+                                  lines, e.g. \"12.5\", or \"n/a\" without
+                                  lines [default: {p}].
+    --format-file <PATH>          Path to a file containing the format string,
+                                  with trailing newlines trimmed. `--format`
+                                  takes precedence.
+    --check-format                Print the chunks the format is built from,
+                                  e.g. \"Package, Raw(\"-\"), License\", or
+                                  the error in it, without building or
+                                  scanning anything.
+    --output-format <FORMAT>      Output format for the report: Ascii, Csv,
+                                  CycloneDx, Dot, GitHubActions,
+                                  GitHubMarkdown, Html, Json, JsonLines,
+                                  Mermaid, Plain, PrComment, Utf8, Ratio,
+                                  Sarif, ShieldsIo, Toml [default: Utf8].
+                                  Plain prints the tree as a header and a line
+                                  for each crate of tab separated columns:
+                                  depth, name, version, used unsafe, total
+                                  unsafe and status, without colors, symbols
+                                  or totals.
+                                  Toml prints the data of the Json report,
+                                  with a [[crate]] table for each crate.
+                                  Html prints a standalone page with a table
+                                  of the crates and their totals, the
+                                  dependency tree, and a collapsible section
+                                  for each crate with the unsafe code of each
+                                  of its files, colored with inline styles.
+                                  ShieldsIo prints the total unsafe code used
+                                  by the build as a shields.io endpoint badge,
+                                  colored brightgreen for 0, yellow up to 99,
+                                  orange up to 999 and red above.
+                                  Json prints a report with a
+                                  `schema_version`, whose field names are
+                                  kept stable, ending with a `summary` of the
+                                  totals of all crates, counting each crate
+                                  once.
+                                  JsonLines prints the same data as a line of
+                                  Json for each crate, with a `record` of
+                                  crate, and a last line with a `record` of
+                                  summary, to be read a line at a time.
+                                  Sarif prints a SARIF 2.1.0 log with a result
+                                  for each crate using unsafe code, and for
+                                  each of its unsafe functions, blocks, impls,
+                                  traits and methods in the files used by the
+                                  build, for code scanning tools such as
+                                  GitHub's.
+                                  CycloneDx prints a CycloneDX 1.4 bill of
+                                  materials with a component for each crate,
+                                  whose properties are its unsafe counts and
+                                  whether it forbids unsafe code, with a
+                                  package URL for the crates from crates.io.
+                                  Dot prints a Graphviz digraph of the
+                                  dependency graph, whose nodes are labeled
+                                  with the used/total unsafe code of their
+                                  crate and filled green if it forbids unsafe
+                                  code, yellow if it denies it and red if it
+                                  uses it. Build and dev dependencies are
+                                  dashed and dotted edges.
+                                  Mermaid prints the same graph as a Mermaid
+                                  flowchart in a fenced block, which GitHub
+                                  renders in issues, pull requests and wikis.
+                                  GitHubActions prints a `::warning` workflow
+                                  command for each unsafe item of the
+                                  workspace members, which GitHub shows inline
+                                  in pull requests, and a `::notice` with the
+                                  totals.
+                                  PrComment prints a Markdown fragment with
+                                  the totals of each category, to post as a
+                                  pull request comment, with the difference
+                                  to the `--baseline` if one is given.
+                                  Ascii and Utf8 print the same table, Ascii
+                                  with `--charset ascii --symbols ascii`.
+                                  Every format is written in the same order
+                                  in each run: the crates, the dependencies
+                                  of each crate and the files are ordered by
+                                  name, version and source, unless `--sort`
+                                  or `--order-file` is given.
+        --charset <CHARSET>       Characters the tree is drawn with: ascii,
+                                  utf8 [default: utf8, or ascii with
+                                  `--output-format Ascii`].
+        --symbols <SYMBOLS>       Symbols marking the status of each crate:
+                                  emoji, ascii, such as `:)` and `!`, or none
+                                  [default: emoji where the terminal supports
+                                  them, else ascii]. `--forbid-only` keeps
+                                  the ascii symbols with none.
+        --json-version <VERSION>  Version of the Json output: 1, the shape
+                                  from before it was versioned, or 2
+                                  [default: 2]. Reports of both versions are
+                                  read as a `--baseline`. The `--forbid-only`
+                                  report is not versioned.
+        --summary                 Print only the summary, without the tree,
+                                  such as for the logs of a CI job. The
+                                  summary is text, or Json with
+                                  `--summary-format json`.
+        --summary-top <N>         With `--summary`, also list the N crates
+                                  using the most unsafe code.
+        --summary-format <FORMAT> Format of the summary below the tree: table,
+                                  text, json [default: table]. The json
+                                  summary is a single line starting with
+                                  `cargo-geiger-summary: `, to find it among
+                                  the lines of the tree.
+        --hide-zeros              Print `-` in place of `0/0` for unsafe
+                                  categories without any unsafe code in the
+                                  tree and `--group-by-source` output. The
+                                  Json output and the Ratio output format are
+                                  not affected.
+        --max-depth <N>           Print only the crates at most N levels below
+                                  the root, such as the crate given to
+                                  `--invert`, adding the unsafe code of the
+                                  crates left out to the row of the crate
+                                  above them. 0 prints only the root.
+        --depth <N>               Same as `--max-depth`, as in `cargo tree`.
+        --min-unsafe <N>          Leave crates using less than N unsafe items
+                                  in total out of the tree, still counting
+                                  them in the totals. With `--invert`, the
+                                  printed crate is compared, not the root.
+                                  [default: 0]
+        --ffi                     Add an FFI column to the table, before the
+                                  dependency: the items declared in `extern`
+                                  blocks, and the functions with an `extern`
+                                  ABI or exported with `#[no_mangle]` or
+                                  `#[export_name]`, used by the build and
+                                  found in total, e.g. \"1/3\". They are
+                                  counted apart from the unsafe code, and are
+                                  in the `ffi` field of the Json output.
+        --min-ffi <N>             Leave crates using less than N FFI items out
+                                  of the tree, like `--min-unsafe`.
+                                  [default: 0]
+        --workspace-only          Print only the workspace members, and the
+                                  root of the tree, such as the crate given to
+                                  `--invert`. The other crates are still
+                                  counted in the totals, but don't fail
+                                  `--deny-unsafe` and the other gates.
+        --only-unsafe             Print only the crates using unsafe code, as
+                                  a flat list, followed by a line with the
+                                  number of crates scanned and using unsafe
+                                  code instead of the totals.
+        --only-source <KINDS>     Only report crates from the comma-separated
+                                  kinds of source, among `crates.io`,
+                                  `registry`, `git`, `path` and `vendored`,
+                                  such as `git,path`. A vendored crate is one
+                                  copied by `cargo vendor`.
+        --categories              Print below the tree, for each unsafe
+                                  category, the unsafe code used by the build
+                                  and found in all crates, and in each crate
+                                  using unsafe code of the category, most used
+                                  first. With `--output-format Json`, the
+                                  breakdown is in a `categories` field.
+        --dangerous-apis          Print below the tree, for each crate, the
+                                  calls made by its unsafe code used by the
+                                  build to high-risk APIs, such as
+                                  `mem::transmute`, `ptr::copy_nonoverlapping`,
+                                  `.assume_init`, `.get_unchecked` and casts to
+                                  raw pointers. With `--output-format Json`,
+                                  they are in the `dangerous_api_calls` field
+                                  of each crate.
+        --dangerous-api <API>     Also look for calls to API, a path such as
+                                  `ptr::read` or a method such as `.set_len`,
+                                  implying `--dangerous-apis`. Can be given
+                                  several times.
+        --print-digest            Instead of the report, print a SHA-256 of
+                                  the unsafe counts and forbid status of each
+                                  crate, which only changes when they do. Paths
+                                  are left out, so the digest doesn't depend on
+                                  where the scanned code is checked out.
+        --explain <NAME[@VERSION]>
+                                  Instead of the report, print how the status
+                                  of a single crate was determined: whether its
+                                  entry points forbid or deny unsafe code, and
+                                  the unsafe usage of each of its files. Only
+                                  the files of that crate are scanned.
+        --why <NAME[@VERSION]>    Print only the dependency paths from the
+                                  root to a crate, such as one using unsafe
+                                  code, with the unsafe code used by the
+                                  build/found of each crate along them. At
+                                  most 100 paths are printed.
+        --interactive             Explore the tree at a prompt instead of
+                                  printing it: expand and collapse crates, sort
+                                  them by their unsafe code, filter them by
+                                  name, and list the files of a crate using
+                                  unsafe code. Type `h` at the prompt for the
+                                  commands.
+        --aligned                 Print a header and a line for each crate,
+                                  sorted by name and version, with its status
+                                  and its unsafe code used by the build and
+                                  found in each category, padded to the width
+                                  of each column without colors or symbols, so
+                                  that reports of separate runs can be diffed
+                                  line by line. The widths are those of the
+                                  widest field in each column, so a longer
+                                  crate name or count realigns every line.
+        --folded                  Print a line in the folded stack format of
+                                  flamegraph.pl for each crate, such as
+                                  `root;tokio;mio 42`: the dependency chain
+                                  from the root crate, and the unsafe code used
+                                  by the build in the crate. A crate depended
+                                  on by several crates is printed once, with
+                                  one of its shortest chains.
+        --heatmap                 Print crates as rows and the unsafe
+                                  categories as columns, most unsafe first, with
+                                  each cell shaded relative to its column.
+                                  Plain numbers are printed with the Ascii and
+                                  GitHubMarkdown output formats, or without
+                                  color.
+        --group-by-source         Group crates by where they come from: each
+                                  git repository, path dependencies, vendored
+                                  crates, each alternate registry and
+                                  crates.io, and print the unsafe usage of
+                                  each group. Groups not from crates.io are
+                                  printed first.
+        --group-by <KEY>          Group crates by `source`, the same as
+                                  `--group-by-source`.
+        --group-by-license        Group crates by the license in their
+                                  manifest, and print the number of crates and
+                                  the unsafe usage of each group. Crates
+                                  without a license are grouped as unknown.
+                                  With `--output-format Json`, the groups are
+                                  printed as Json.
+        --order-file <PATH>       Path to a file with a crate name on each
+                                  line. The `--heatmap` places the listed
+                                  crates first, in the order of the file,
+                                  followed by the crates not listed in
+                                  alphabetical order.
+        --collapse-workspace      Sum the unsafe usage of all workspace members
+                                  into a single \"workspace\" row of the
+                                  `--heatmap` or `--group-by-source` output,
+                                  keeping dependencies outside the workspace
+                                  itemized.
+        --merge <FACADE=IMPL>     Count the unsafe usage of an implementation
+                                  crate as part of the facade crate which
+                                  re-exports it, such as `rand=rand_core`, in
+                                  the `--heatmap` or `--group-by-source`
+                                  output. This is an opinionated view, the Json
+                                  output keeps the numbers of each crate. Can
+                                  be given multiple times.
+        --downloads               Annotate crates with their crates.io download
+                                  count, read from a snapshot, to tell unsafe
+                                  code in widely used crates apart from unsafe
+                                  code in obscure ones. Download counts are
+                                  never fetched.
+        --downloads-file <PATH>   The snapshot read by `--downloads`, with a
+                                  line `<name> <downloads>` for each crate.
+                                  Defaults to the snapshot of approximate
+                                  counts bundled with cargo-geiger.
+        --percentiles             Annotate crates using unsafe code with their
+                                  share of unsafe items, the share of the
+                                  crates of a snapshot of the unsafe
+                                  statistics of popular crates, of the same
+                                  order of magnitude of items, with a lower
+                                  one, and their median, such as `above 95% of
+                                  similar crates`. The snapshot is never
+                                  fetched.
+        --percentiles-file <PATH> The snapshot read by `--percentiles`, with a
+                                  line `<name> <items> <unsafe items>` for
+                                  each crate. Defaults to the snapshot bundled
+                                  with cargo-geiger.
+        --check-security-policy   Annotate crates with whether they publish a
+                                  security policy, such as a SECURITY.md or
+                                  security.txt, in their sources or in their
+                                  GitHub or GitLab repository. Repositories
+                                  are looked up over the network, once for all
+                                  crates sharing one, unless `--offline` is
+                                  given.
+        --audit                   Annotate crates with the RustSec advisories
+                                  affecting their version, leaving out the
+                                  withdrawn and informational ones, except for
+                                  those about unsound code. The ids are also
+                                  in the `advisories` field of the Json output.
+        --advisory-db <PATH>      The directory of a local copy of the advisory
+                                  database read by `--audit`. Defaults to the
+                                  copy kept by cargo-audit in the cargo home,
+                                  or else the database is fetched, unless
+                                  `--offline` is given.
+        --sort-by-unpopular-unsafe
+                                  Order the `--heatmap` by unsafe usage weighted
+                                  down by the download count, so that unsafe
+                                  code in rarely downloaded crates comes first.
     --update-readme               Writes output to ./README.md. Looks for a Safety
                                   Report section, replaces if found, adds if not.
                                   Throws an error if no README.md exists.
+        --bundle <DIR>            Write an audit bundle to the directory instead
+                                  of printing the report: report.json (the Json
+                                  report, of `--json-version`), args.txt (the
+                                  arguments used), Cargo.lock and
+                                  scanned-files.txt (the .rs files used by the
+                                  build).
+        --force                   Allow `--bundle` to write to a non-empty
+                                  directory, replacing only the files of the
+                                  bundle. The current directory, the workspace
+                                  root and their parents are never written to.
         --readme-path <PATH>      Path of README.md file to be written to.
         --section-name <NAME>     The section name in the README.md to be written
                                   to.
+    --update-file <PATH>          Writes output to a section of the file, as
+                                  `--update-readme` does for README.md, such as
+                                  SECURITY.md or a page of an mdBook.
+        --begin-marker <MARKER>   With `--update-readme` or `--update-file`, the
+                                  line that starts the section, instead of a
+                                  Safety Report header. Requires
+                                  `--end-marker`.
+        --end-marker <MARKER>     The line that ends the section started by
+                                  `--begin-marker`.
     -v, --verbose                 Use verbose output (-vv very verbose/build.rs
                                   output).
     -q, --quiet                   No output printed to stdout other than the
-                                  tree.
-        --color <WHEN>            Coloring: auto, always, never.
+                                  tree, and no progress of the scan, the
+                                  crates scanned out of all of them and the
+                                  last one, printed to stderr. The progress
+                                  is only printed when stderr is a terminal.
+        --color <WHEN>            Coloring: auto, always, never. Auto colors
+                                  unless `NO_COLOR` is set or stdout is not a
+                                  terminal. Without `--color`,
+                                  `CARGO_TERM_COLOR` is read as for cargo.
+        --color-scheme <SCHEME>   Colors of the crate statuses: default, which
+                                  is green, yellow and red, or colorblind,
+                                  which is blue, sky blue and orange, for red-
+                                  green color blindness [default: default].
+        --tee <PATH>              Also write the printed report to the file,
+                                  with colors stripped from the file copy.
+        --output-file <PATH>      Write the report to the file without colors,
+                                  instead of to stdout. Build diagnostics are
+                                  still printed on stderr. Also `--output`.
+        --also-emit <FORMAT=PATH> Also write the report in a report format to
+                                  the file, from the same scan: Csv,
+                                  CycloneDx, Dot, GitHubActions, Html, Json,
+                                  JsonLines, Mermaid, PrComment (Markdown),
+                                  Sarif, ShieldsIo or Toml. Can be given
+                                  several times.
+        --metrics-file <PATH>     Also write gauges of the unsafe code used,
+                                  of the crates by status and of the unsafe
+                                  code of each top-level dependency to the
+                                  file, in the Prometheus text format.
+        --relative-paths          Write the paths under the workspace root
+                                  relative to it in the reports, and the path
+                                  sources of crates as `file:///` URLs from
+                                  the workspace root, so that they are the
+                                  same on every machine. Other paths are kept.
+        --pager                   Pipe the report through `$PAGER` (default:
+                                  less) when printing to a terminal.
+        --log-sink <SINK>         Also write a record for each crate to the
+                                  system log: syslog, journald. The fields are
+                                  crate, version, count (the unsafe items used
+                                  by the build) and severity: warning with
+                                  unsafe usage, notice if unsafe is allowed,
+                                  info if denied, debug if forbidden. The
+                                  records come from the same scan. Requires
+                                  the `log-sink` cargo feature.
         --frozen                  Require Cargo.lock and cache are up to date.
-        --locked                  Require Cargo.lock is up to date.
-        --offline                 Run without accessing the network.
+                                  Implies `--locked` and `--offline`.
+        --locked                  Require Cargo.lock is up to date, failing
+                                  instead of writing to it.
+        --offline                 Run without accessing the network, failing
+                                  if a source isn't downloaded yet. Also given
+                                  to the cargo commands run by cargo-geiger.
     -Z \"<FLAG>...\"                Unstable (nightly-only) flags to Cargo.
         --include-tests           Count unsafe usage in tests.
+        --detector <NAME>         Also run a detector on each scanned file,
+                                  after the unsafe counter, and add its
+                                  findings to the entries of the Json output:
+                                  panic-in-unsafe (panicking macros and calls
+                                  of `unwrap` and `expect` in unsafe code).
+                                  Can be given several times.
+        --include-locations       Add the file, line and column span and kind
+                                  of each unsafe item used by a crate to its
+                                  entry of the Json output.
+        --show-snippets           Print below the tree each unsafe item used by
+                                  a workspace crate, with the source around
+                                  it, as in the diagnostics of the compiler.
+    -j, --jobs <N>                Number of threads scanning the files of the
+                                  crates [default: the number of logical
+                                  CPUs].
+        --no-partial-results      Fail if a file of any crate can't be
+                                  scanned, instead of counting the unsafe
+                                  usage of the files which could be.
+        --lossy                   Scan the files which aren't valid UTF-8 with
+                                  the invalid bytes replaced, and list the
+                                  files which couldn't be scanned, or only
+                                  lossily, in the report: in a Scan warnings
+                                  section of the table, and in the
+                                  `scan_warnings` field of the Json output.
+        --expand                  Scan the macro-expanded code of every crate
+                                  as well, and report its unsafe usage along
+                                  with that of the sources, to find the unsafe
+                                  code generated by macros. Needs a nightly
+                                  toolchain, installed with rustup, and builds
+                                  the dependencies of each crate.
+        --no-cache                Scan every crate, without reading or
+                                  writing the cache of the scan results of
+                                  crates.io packages. The cache is kept in
+                                  `--cache-dir`, or else in
+                                  `$CARGO_GEIGER_CACHE_DIR` or
+                                  `$GEIGER_CACHE_DIR`, or else in the
+                                  cargo-geiger directory of `$XDG_CACHE_HOME`
+                                  or `~/.cache`.
+        --refresh                 Scan every crate, and replace the entries of
+                                  the crates.io packages in the cache.
+        --cache-dir <PATH>        Keep the cache in this directory, which may
+                                  be shared by concurrent runs, for instance
+                                  by the jobs of a CI runner.
+        --watch                   Scan again each time a source file or
+                                  manifest of a workspace member changes,
+                                  until interrupted. The unchanged workspace
+                                  members are read from the cache, as the
+                                  crates.io packages are.
+        --scan-examples           Also build the examples of the workspace
+                                  members, and print their unsafe usage below
+                                  the tree, apart from the crates' own.
+        --scan-benches            Also build the benches of the workspace
+                                  members, and print their unsafe usage below
+                                  the tree, apart from the crates' own.
+        --target-breakdown        Print the unsafe usage of each crate using
+                                  unsafe code below the tree, broken down by
+                                  the kind of its targets: lib, bins,
+                                  examples, tests, benches and build script.
+                                  The build scripts are only scanned for it,
+                                  and not counted in the rest of the report.
+        --source-map <NAME=PATH>  Scan the crate from a local directory, such
+                                  as a locally modified fork, in place of its
+                                  source. The rest of the dependency graph is
+                                  unchanged, and the build still decides which
+                                  files of the crate are used, by their path
+                                  relative to the crate root. May be given
+                                  multiple times.
         --build-dependencies      Also analyze build dependencies.
         --dev-dependencies        Also analyze dev dependencies.
         --all-dependencies        Analyze all dependencies, including build and
                                   dev.
+        --kind <KIND>             The dependencies to analyze: normal, dev,
+                                  build or all. The normal dependencies are
+                                  always analyzed, dev and build add the dev
+                                  or build dependencies to them [default:
+                                  normal]. Takes precedence over the three
+                                  flags above.
+        --no-dev-deps             Leave the dev dependencies out of the graph,
+                                  e.g. to analyze the build dependencies of
+                                  `--all-dependencies` only.
+        --no-build-deps           Leave the build dependencies, and the crates
+                                  only they depend upon, out of the tree and
+                                  the totals, e.g. to analyze the dev
+                                  dependencies of `--all-dependencies` only.
+                                  The dev dependencies are not affected.
+        --include-build-deps      Print and count the build dependencies
+                                  analyzed with `--build-dependencies` or
+                                  `--all-dependencies` [default]. Takes
+                                  precedence over `--no-build-deps`.
+        --license-filter <EXPR>   Only report crates whose license matches the
+                                  SPDX style expression, e.g. \"GPL-* OR
+                                  LGPL-*\". Supported are license identifiers,
+                                  optionally ending in a `*` wildcard, `AND`,
+                                  `OR`, `WITH` and parentheses. Crates with a
+                                  missing or unparseable license are included,
+                                  with a warning.
         --forbid-only             Don't build or clean anything, only scan
                                   entry point .rs source files for.
                                   forbid(unsafe_code) flags. This is
                                   significantly faster than the default
                                   scanning mode. TODO: Add ability to combine
                                   this with a whitelist for use in CI.
+        --baseline <PATH>         Path to a report previously written with
+                                  `--output-format Json`, or Toml for a .toml
+                                  file, to compare against. The PrComment
+                                  output shows the difference to it.
+        --baseline-diff           Instead of the report, print for each crate
+                                  whose unsafe usage differs from the
+                                  `--baseline` the change in each category,
+                                  e.g. \"expressions -2, impls +2 (net 0)\".
+        --save-baseline <PATH>    Instead of the report, write it to the path
+                                  as Json, or as Toml for a .toml file, to be
+                                  used later with `--baseline` or `--diff`.
+        --diff <PATH>             Instead of the report, compare the used
+                                  unsafe code of each crate with a report
+                                  previously written with `--output-format
+                                  Json` or `--save-baseline`, and print the
+                                  crates added, removed and changed. The
+                                  crates whose used unsafe code went up are
+                                  highlighted, and with `--deny-unsafe` only
+                                  they fail the run. With `--output-format
+                                  Json` or GitHubMarkdown, the difference is
+                                  printed in that format.
+        --diff-baseline <PATH>    Same as `--diff`.
+        --deny-new-crates         Fail if a crate, matched by name and version,
+                                  is not present in the `--baseline`.
+        --allow-new <NAME>        Don't fail `--deny-new-crates` for the named
+                                  crate. May be given multiple times.
+        --write-lockfile          Instead of the report, write the used unsafe
+                                  code of each crate, by category, to the
+                                  `--lockfile` to be checked in, accepting it.
+        --verify-lockfile         Instead of the report, print the crates
+                                  using more unsafe code in a category than
+                                  recorded in the `--lockfile`, and fail if
+                                  there are any.
+        --lockfile <PATH>         The lockfile of `--write-lockfile` and
+                                  `--verify-lockfile` [default: geiger.lock].
+        --verify-checksums <PATH> Path to a file with a line
+                                  `<name> <version> <sha256>` for each crate,
+                                  giving the expected checksum of its source
+                                  files. Crates whose source doesn't match are
+                                  reported as mismatched, and marked in the
+                                  tree and the `checksum_mismatch` field of
+                                  the Json report. The checksums of
+                                  crates not in the file are printed in the
+                                  same format, and can be copied into it.
+        --deny-checksum-mismatch  Fail if a crate's source doesn't match its
+                                  checksum in `--verify-checksums`.
+        --warn-duplicate-versions Print the crates present at more than one
+                                  version, with the crates requiring each
+                                  version.
+        --deny-duplicate-versions Like `--warn-duplicate-versions`, but fail if
+                                  any crate is present at more than one
+                                  version.
+        --allow-duplicate <NAME>  Don't report the named crate for being
+                                  present at more than one version. May be
+                                  given multiple times.
+        --history <PATH>          Path to a report of an earlier scan, written
+                                  with `--output-format Json`. May be given
+                                  multiple times. Crates using unsafe code are
+                                  annotated with the earliest version known to
+                                  use the same unsafe code, or `unknown`.
+        --history-store <PATH>    Path to the store of `history record` and
+                                  `history show` [default:
+                                  geiger-history.jsonl at the root of the
+                                  workspace].
+        --require-safety-docs     Fail if any `unsafe` block in a workspace
+                                  crate is not preceded by a `// SAFETY:`
+                                  comment. Not available with
+                                  `--forbid-only`.
+        --max-score <N>           Fail if the weighted unsafe score of a crate,
+                                  counting only unsafe code used by the build,
+                                  is above N. The crates above it are printed
+                                  with their score. Not checked with
+                                  `--forbid-only`.
+        --score-weights <WEIGHTS> Weights of the unsafe categories in the
+                                  score, as a comma separated list of
+                                  `<category>=<weight>`, e.g.
+                                  \"impls=0.5,expressions=3\". Categories are
+                                  functions, expressions, impls, traits and
+                                  methods, each weighing 1 unless given.
+        --fail-threshold <RATIO>  Fail if the share of unsafe code in the
+                                  table footer, all categories added together,
+                                  is above RATIO, e.g. 0.25. The ratio is the
+                                  complement of the safe percentages shown by
+                                  `--output-format Ratio`. A RATIO of 0 fails
+                                  on any unsafe code. Only checked with the
+                                  table output.
+        --category-policy <POLICY>
+                                  Tiers of the unsafe categories, as a comma
+                                  separated list of `<category>=<tier>`, e.g.
+                                  \"impls=warn,traits=ignore\". Fail if a crate
+                                  uses unsafe code of a category in the error
+                                  tier, and report crates using unsafe code of
+                                  a category in the warn tier on stderr.
+                                  Categories not listed are in the error tier.
+                                  The send-sync-impls category, such as in
+                                  \"impls=warn,send-sync-impls=error\", is the
+                                  `unsafe impl`s of Send and Sync, which are
+                                  also impls, the stricter tier applying. It
+                                  is in no tier unless listed.
+                                  Not checked with `--forbid-only`.
+        --max-unsafe <LIMITS>     Fail if a crate uses more unsafe code of a
+                                  category than its limit, given as a comma
+                                  separated list of `<category>=<max>`, e.g.
+                                  \"functions=0,expressions=10\". Categories
+                                  not listed are unlimited. The crates above
+                                  a limit are printed on stderr. Not checked
+                                  with `--forbid-only`.
+        --max-total-unsafe <LIMITS>
+                                  Like `--max-unsafe`, but for the unsafe code
+                                  of all crates added together, counting each
+                                  crate once.
+        --deny-warnings           Fail with exit code 2 if the scan is
+                                  incomplete, with any output format. Warnings
+                                  are raised for crates without metrics, and
+                                  for .rs files used by the build which were
+                                  never scanned. Without this flag, only the
+                                  tree output fails on warnings, with exit
+                                  code 1.
+        --deny-unsafe             Fail with exit code 3 if any crate uses
+                                  unsafe code, with any output format. The
+                                  crates using it are printed on stderr. Not
+                                  checked with `--forbid-only`.
+        --allowlist <PATH>        Path to a list of audited crates, a `<name>`
+                                  or `<name>:<version>` on each line, or in the
+                                  `crates` array of a .toml file. They are
+                                  displayed as allowing unsafe code without
+                                  using it, and don't fail `--deny-unsafe`.
+        --audits <PATH>           Path to a .toml file of manual reviews of
+                                  crates, an `[[audit]]` table with the
+                                  `crate`, `auditor` and optional `version`,
+                                  `date` and `note` of each, or the
+                                  audits.toml of cargo-vet. Crates are
+                                  annotated with their auditor, and crates
+                                  using unsafe code without an audit as not
+                                  audited.
+        --require-audits          Fail with exit code 3 if a crate uses unsafe
+                                  code without an audit in `--audits`. The
+                                  crates are printed on stderr. Allowlisted
+                                  crates don't need an audit.
+        --ignore <NAME>           Leave the named crate out of the scan. It is
+                                  still printed in the tree, marked as
+                                  skipped, without counting its unsafe code.
+                                  May be given multiple times.
+        --ignore-path <GLOB>      Leave the files matching the pattern out of
+                                  the scan, relative to the root of their
+                                  package, such as \"vendor/**\" or
+                                  \"src/generated/*.rs\". May be given
+                                  multiple times.
+        --exit-zero               Report which of the checks above would have
+                                  failed the run, but always exit with status
+                                  0.
+        --exit-code-mode <MODE>   What the exit status of a scan tells: with
+                                  `gates`, the default, whether the checks
+                                  above passed. With `detection`, what the
+                                  scan found, the checks being only reported:
+                                  0 if no unsafe usage was found and every
+                                  crate forbids unsafe code, 1 on errors, 2 if
+                                  unsafe usage was found, 3 if none was found
+                                  but a crate doesn't forbid unsafe code.
     -h, --help                    Prints help information.
     -V, --version                 Prints version information.
+
+COMPARE:
+    `compare` downloads both versions of the crate from crates.io and scans
+    each, then prints the unsafe code found in the crate and used by its
+    dependencies side by side, the files of the crate which use unsafe code
+    in the new version but used none in the old, and the difference for each
+    dependency.
+
+HISTORY:
+    `history record` scans the package and appends the totals and the unsafe
+    code of each crate, with the time and the commit checked out, to the
+    store of `--history-store`. `history show` prints a row for each recorded
+    scan, with a bar of the unsafe code used by the build, to follow its
+    trend over time.
+
+BADGE:
+    `badge` scans the package and writes an SVG badge of the unsafe code used
+    by the build/found in all crates, to the file of `--output-file`, or else
+    to geiger-badge.svg. It is green if every crate forbids unsafe code, red
+    if any crate uses unsafe code, and gray otherwise.
+
+INSPECT:
+    `inspect` scans only the files of the crate, given as `<name>` or
+    `<name>@<version>`, and prints the unsafe code of each of its modules,
+    the modules with the most unsafe code first, with their share of the
+    unsafe code of the crate.
+
+COMPLETIONS:
+    `completions` prints the completion script of the options and
+    subcommands of cargo-geiger for the shell: bash, fish, powershell or zsh.
+    It completes the `cargo-geiger` command. `manpage` prints the man page of
+    cargo-geiger, in roff. Both are made from this help text.
+
+CONFIGURATION:
+    Defaults for the options above are read from a `geiger.toml` next to the
+    manifest, or else from the `[package.metadata.geiger]` table of the
+    manifest. Each key is an option without its leading `--`, such as
+    `output-format = \"Json\"` or `include-tests = true`, with an array for
+    options given several times. Options on the command line take precedence.
 ";
 
-#[derive(Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Args {
+    pub advisory_db: Option<PathBuf>,
+    pub aligned: bool,
     pub all: bool,
+    pub allowlist: Option<PathBuf>,
+    pub also_emit: Vec<EmittedReport>,
+    pub audit: bool,
+    pub audits: Option<PathBuf>,
+    pub badge: bool,
+    pub baseline_args: BaselineArgs,
+    pub bundle_args: BundleArgs,
+    pub cache_dir: Option<PathBuf>,
+    pub categories: bool,
+    pub category_policy: Option<CategoryPolicy>,
+    pub charset: Option<Charset>,
+    pub check_format: bool,
+    pub check_security_policy: bool,
+    pub checksum_args: ChecksumArgs,
+    pub collapse_workspace: bool,
     pub color: Option<String>,
+    pub color_scheme: ColorScheme,
+    pub compare: Option<CompareSpec>,
+    pub compare_features: bool,
+    pub dangerous_api: Vec<String>,
+    pub dangerous_apis: bool,
+    pub deny_unsafe: bool,
+    pub deny_warnings: bool,
     pub deps_args: DepsArgs,
+    pub depth_column: bool,
+    pub detectors: Vec<BuiltInDetector>,
+    pub diff: Option<PathBuf>,
+    pub downloads: bool,
+    pub downloads_file: Option<PathBuf>,
+    pub duplicate_args: DuplicateArgs,
+    pub exit_code_mode: ExitCodeMode,
+    pub exit_zero: bool,
+    pub expand: bool,
+    pub explain: Option<CrateSpec>,
+    pub fail_threshold: Option<f32>,
+    pub feature_matrix_args: FeatureMatrixArgs,
+    pub feature_report: bool,
     pub features_args: FeaturesArgs,
+    pub ffi: bool,
+    pub folded: bool,
     pub forbid_only: bool,
-    pub format: String,
+    pub format: Option<String>,
+    pub format_file: Option<PathBuf>,
     pub frozen: bool,
+    pub generate_command: Option<GenerateCommand>,
+    pub git_args: GitArgs,
+    pub group_by: Option<GroupBy>,
+    pub group_by_license: bool,
+    pub group_by_source: bool,
+    pub heatmap: bool,
     pub help: bool,
+    pub hide_zeros: bool,
+    pub history: Vec<PathBuf>,
+    pub history_command: Option<HistoryCommand>,
+    pub history_store: Option<PathBuf>,
+    pub ignore: Vec<String>,
+    pub ignore_path: Vec<glob::Pattern>,
+    pub include_build_deps: bool,
+    pub include_locations: bool,
     pub include_tests: bool,
+    pub inspect: Option<CrateSpec>,
+    pub interactive: bool,
     pub invert: bool,
+    pub jobs: Option<NonZeroUsize>,
+    pub json_version: JsonVersion,
+    pub license_filter: Option<LicenseExpression>,
+    pub local_targets_args: LocalTargetsArgs,
     pub locked: bool,
+    pub lockfile_args: LockfileArgs,
+    pub log_sink: Option<LogSink>,
+    pub lossy: bool,
     pub manifest_path: Option<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub max_score: Option<f64>,
+    pub max_total_unsafe: Option<UnsafeLimits>,
+    pub max_unsafe: Option<UnsafeLimits>,
+    pub merge: Vec<FacadeMerge>,
+    pub metrics_file: Option<PathBuf>,
+    pub min_ffi: u64,
+    pub min_unsafe: u64,
+    pub no_build_deps: bool,
+    pub no_cache: bool,
     pub no_indent: bool,
+    pub no_partial_results: bool,
     pub offline: bool,
+    pub only_source: Option<SourceFilter>,
+    pub only_unsafe: bool,
+    pub order_file: Option<PathBuf>,
+    pub output_file: Option<PathBuf>,
     pub output_format: OutputFormat,
     pub package: Option<String>,
+    pub package_spec: Option<PackageSpec>,
+    pub pager: bool,
+    pub percentiles: bool,
+    pub percentiles_file: Option<PathBuf>,
     pub prefix_depth: bool,
+    pub print_digest: bool,
+    pub profile: Profile,
     pub quiet: bool,
     pub readme_args: ReadmeArgs,
+    pub refresh: bool,
+    pub relative_paths: bool,
+    pub require_audits: bool,
+    pub require_safety_docs: bool,
+    pub root: Option<CrateSpec>,
+    pub score_weights: CategoryWeights,
+    pub show_snippets: bool,
+    pub sort: Option<CrateSort>,
+    pub sort_by_unpopular_unsafe: bool,
+    pub source_map: Vec<SourceMapEntry>,
+    pub summary: bool,
+    pub summary_format: SummaryFormat,
+    pub summary_top: Option<usize>,
+    pub symbols: Option<Symbols>,
     pub target_args: TargetArgs,
+    pub target_breakdown: bool,
+    pub tee: Option<PathBuf>,
     pub unstable_flags: Vec<String>,
     pub verbosity: Verbosity,
     pub version: bool,
+    pub watch: bool,
+    pub why: Option<CrateSpec>,
+    pub workspace_args: WorkspaceArgs,
+    pub workspace_only: bool,
 }
 
 impl Args {
@@ -102,13 +951,91 @@ impl Args {
         mut raw_args: Arguments,
     ) -> Result<Args, Box<dyn std::error::Error>> {
         let mut args = Args {
+            advisory_db: raw_args.opt_value_from_str("--advisory-db")?,
+            aligned: raw_args.contains("--aligned"),
             all: raw_args.contains(["-a", "--all"]),
+            allowlist: raw_args.opt_value_from_str("--allowlist")?,
+            also_emit: raw_args.values_from_str("--also-emit")?,
+            audit: raw_args.contains("--audit"),
+            audits: raw_args.opt_value_from_str("--audits")?,
+            // Parsed last, from the arguments left after the options.
+            badge: false,
+            baseline_args: BaselineArgs {
+                allow_new: raw_args.values_from_str("--allow-new")?,
+                baseline: raw_args.opt_value_from_str("--baseline")?,
+                baseline_diff: raw_args.contains("--baseline-diff"),
+                deny_new_crates: raw_args.contains("--deny-new-crates"),
+                save_baseline: raw_args
+                    .opt_value_from_str("--save-baseline")?,
+            },
+            bundle_args: BundleArgs {
+                bundle_path: raw_args.opt_value_from_str("--bundle")?,
+                force: raw_args.contains("--force"),
+            },
+            cache_dir: raw_args.opt_value_from_str("--cache-dir")?,
+            categories: raw_args.contains("--categories"),
+            category_policy: raw_args
+                .opt_value_from_str("--category-policy")?,
+            charset: raw_args.opt_value_from_str("--charset")?,
+            check_format: raw_args.contains("--check-format"),
+            check_security_policy: raw_args.contains("--check-security-policy"),
+            checksum_args: ChecksumArgs {
+                deny_checksum_mismatch: raw_args
+                    .contains("--deny-checksum-mismatch"),
+                verify_checksums: raw_args
+                    .opt_value_from_str("--verify-checksums")?,
+            },
+            collapse_workspace: raw_args.contains("--collapse-workspace"),
             color: raw_args.opt_value_from_str("--color")?,
+            color_scheme: raw_args
+                .opt_value_from_str("--color-scheme")?
+                .unwrap_or_default(),
+            // Parsed last, from the arguments left after the options.
+            compare: None,
+            compare_features: raw_args.contains("--compare-features"),
+            dangerous_api: raw_args.values_from_str("--dangerous-api")?,
+            dangerous_apis: raw_args.contains("--dangerous-apis"),
+            deny_unsafe: raw_args.contains("--deny-unsafe"),
+            deny_warnings: raw_args.contains("--deny-warnings"),
             deps_args: DepsArgs {
                 all_deps: raw_args.contains("--all-dependencies"),
                 build_deps: raw_args.contains("--build-dependencies"),
                 dev_deps: raw_args.contains("--dev-dependencies"),
+                kind: raw_args.opt_value_from_str("--kind")?,
+                no_dev_deps: raw_args.contains("--no-dev-deps"),
+            },
+            depth_column: raw_args.contains("--depth-column"),
+            detectors: raw_args.values_from_str("--detector")?,
+            diff: match raw_args.opt_value_from_str("--diff")? {
+                Some(diff) => Some(diff),
+                None => raw_args.opt_value_from_str("--diff-baseline")?,
+            },
+            downloads: raw_args.contains("--downloads"),
+            downloads_file: raw_args.opt_value_from_str("--downloads-file")?,
+            duplicate_args: DuplicateArgs {
+                allow_duplicate: raw_args
+                    .values_from_str("--allow-duplicate")?,
+                deny_duplicate_versions: raw_args
+                    .contains("--deny-duplicate-versions"),
+                warn_duplicate_versions: raw_args
+                    .contains("--warn-duplicate-versions"),
             },
+            exit_code_mode: raw_args
+                .opt_value_from_str("--exit-code-mode")?
+                .unwrap_or_default(),
+            exit_zero: raw_args.contains("--exit-zero"),
+            expand: raw_args.contains("--expand"),
+            explain: raw_args.opt_value_from_str("--explain")?,
+            fail_threshold: raw_args.opt_value_from_str("--fail-threshold")?,
+            feature_matrix_args: FeatureMatrixArgs {
+                feature_matrix: raw_args.contains("--feature-matrix"),
+                feature_sets: raw_args
+                    .values_from_str("--feature-set")?
+                    .into_iter()
+                    .map(|raw_features| parse_features(Some(raw_features)))
+                    .collect(),
+            },
+            feature_report: raw_args.contains("--feature-report"),
             features_args: FeaturesArgs {
                 all_features: raw_args.contains("--all-features"),
                 features: parse_features(
@@ -116,30 +1043,127 @@ impl Args {
                 ),
                 no_default_features: raw_args.contains("--no-default-features"),
             },
+            ffi: raw_args.contains("--ffi"),
+            folded: raw_args.contains("--folded"),
             forbid_only: raw_args.contains(["-f", "--forbid-only"]),
-            format: raw_args
-                .opt_value_from_str("--format")?
-                .unwrap_or_else(|| "{p}".to_string()),
+            format: raw_args.opt_value_from_str("--format")?,
+            format_file: raw_args.opt_value_from_str("--format-file")?,
             frozen: raw_args.contains("--frozen"),
+            // Parsed last, from the arguments left after the options.
+            generate_command: None,
+            git_args: GitArgs {
+                branch: raw_args.opt_value_from_str("--branch")?,
+                git: raw_args.opt_value_from_str("--git")?,
+                rev: raw_args.opt_value_from_str("--rev")?,
+                tag: raw_args.opt_value_from_str("--tag")?,
+            },
+            group_by: raw_args.opt_value_from_str("--group-by")?,
+            group_by_license: raw_args.contains("--group-by-license"),
+            group_by_source: raw_args.contains("--group-by-source"),
+            heatmap: raw_args.contains("--heatmap"),
             help: raw_args.contains(["-h", "--help"]),
+            hide_zeros: raw_args.contains("--hide-zeros"),
+            history: raw_args.values_from_str("--history")?,
+            // Parsed last, from the arguments left after the options.
+            history_command: None,
+            history_store: raw_args.opt_value_from_str("--history-store")?,
+            ignore: raw_args.values_from_str("--ignore")?,
+            ignore_path: raw_args.values_from_str("--ignore-path")?,
+            include_build_deps: raw_args.contains("--include-build-deps"),
+            include_locations: raw_args.contains("--include-locations"),
             include_tests: raw_args.contains("--include-tests"),
+            inspect: None,
+            interactive: raw_args.contains("--interactive"),
             invert: raw_args.contains(["-i", "--invert"]),
+            jobs: raw_args.opt_value_from_str(["-j", "--jobs"])?,
+            json_version: raw_args
+                .opt_value_from_str("--json-version")?
+                .unwrap_or_default(),
+            license_filter: raw_args.opt_value_from_str("--license-filter")?,
+            local_targets_args: LocalTargetsArgs {
+                scan_benches: raw_args.contains("--scan-benches"),
+                scan_examples: raw_args.contains("--scan-examples"),
+            },
             locked: raw_args.contains("--locked"),
+            lockfile_args: LockfileArgs {
+                lockfile: raw_args.opt_value_from_str("--lockfile")?,
+                verify_lockfile: raw_args.contains("--verify-lockfile"),
+                write_lockfile: raw_args.contains("--write-lockfile"),
+            },
+            log_sink: raw_args.opt_value_from_str("--log-sink")?,
+            lossy: raw_args.contains("--lossy"),
             manifest_path: raw_args.opt_value_from_str("--manifest-path")?,
+            max_depth: match raw_args.opt_value_from_str("--max-depth")? {
+                Some(max_depth) => Some(max_depth),
+                None => raw_args.opt_value_from_str("--depth")?,
+            },
+            max_score: raw_args.opt_value_from_str("--max-score")?,
+            max_total_unsafe: raw_args
+                .opt_value_from_str("--max-total-unsafe")?,
+            max_unsafe: raw_args.opt_value_from_str("--max-unsafe")?,
+            merge: raw_args.values_from_str("--merge")?,
+            metrics_file: raw_args.opt_value_from_str("--metrics-file")?,
+            min_ffi: raw_args.opt_value_from_str("--min-ffi")?.unwrap_or(0),
+            min_unsafe: raw_args
+                .opt_value_from_str("--min-unsafe")?
+                .unwrap_or(0),
+            no_build_deps: raw_args.contains("--no-build-deps"),
             no_indent: raw_args.contains("--no-indent"),
+            no_cache: raw_args.contains("--no-cache"),
+            no_partial_results: raw_args.contains("--no-partial-results"),
             offline: raw_args.contains("--offline"),
+            only_source: raw_args.opt_value_from_str("--only-source")?,
+            only_unsafe: raw_args.contains("--only-unsafe"),
+            order_file: raw_args.opt_value_from_str("--order-file")?,
+            output_file: match raw_args.opt_value_from_str("--output-file")? {
+                Some(output_file) => Some(output_file),
+                None => raw_args.opt_value_from_str("--output")?,
+            },
             package: raw_args.opt_value_from_str(["-p", "--package"])?,
+            package_spec: raw_args.opt_value_from_str("--package-spec")?,
+            pager: raw_args.contains("--pager"),
+            percentiles: raw_args.contains("--percentiles"),
+            percentiles_file: raw_args
+                .opt_value_from_str("--percentiles-file")?,
             prefix_depth: raw_args.contains("--prefix-depth"),
+            print_digest: raw_args.contains("--print-digest"),
+            profile: raw_args
+                .opt_value_from_str("--profile")?
+                .unwrap_or_default(),
             quiet: raw_args.contains(["-q", "--quiet"]),
             readme_args: ReadmeArgs {
+                begin_marker: raw_args.opt_value_from_str("--begin-marker")?,
+                end_marker: raw_args.opt_value_from_str("--end-marker")?,
                 readme_path: raw_args.opt_value_from_str("--readme-path")?,
                 section_name: raw_args.opt_value_from_str("--section-name")?,
+                update_file: raw_args.opt_value_from_str("--update-file")?,
                 update_readme: raw_args.contains("--update-readme"),
             },
+            refresh: raw_args.contains("--refresh"),
+            relative_paths: raw_args.contains("--relative-paths"),
+            require_audits: raw_args.contains("--require-audits"),
+            require_safety_docs: raw_args.contains("--require-safety-docs"),
+            root: raw_args.opt_value_from_str("--root")?,
+            score_weights: raw_args
+                .opt_value_from_str("--score-weights")?
+                .unwrap_or_default(),
+            show_snippets: raw_args.contains("--show-snippets"),
+            sort: raw_args.opt_value_from_str("--sort")?,
+            sort_by_unpopular_unsafe: raw_args
+                .contains("--sort-by-unpopular-unsafe"),
+            source_map: raw_args.values_from_str("--source-map")?,
+            summary: raw_args.contains("--summary"),
+            summary_format: raw_args
+                .opt_value_from_str("--summary-format")?
+                .unwrap_or_default(),
+            summary_top: raw_args.opt_value_from_str("--summary-top")?,
+            symbols: raw_args.opt_value_from_str("--symbols")?,
+            tee: raw_args.opt_value_from_str("--tee")?,
             target_args: TargetArgs {
                 all_targets: raw_args.contains("--all-targets"),
                 target: raw_args.opt_value_from_str("--target")?,
             },
+            target_breakdown: raw_args.contains("--target-breakdown"),
             unstable_flags: raw_args
                 .opt_value_from_str("-Z")?
                 .map(|s: String| s.split(' ').map(|s| s.to_owned()).collect())
@@ -154,17 +1178,41 @@ impl Args {
                 (false, true) => Normal,
                 (true, _) => Verbose,
             },
+            watch: raw_args.contains("--watch"),
+            why: raw_args.opt_value_from_str("--why")?,
+            workspace_args: WorkspaceArgs {
+                exclude: raw_args.values_from_str("--exclude")?,
+                member_summary: raw_args.contains("--member-summary"),
+                workspace: raw_args.contains("--workspace"),
+            },
+            workspace_only: raw_args.contains("--workspace-only"),
             output_format: raw_args
                 .opt_value_from_str("--output-format")?
                 .unwrap_or(OutputFormat::Utf8),
         };
+        let positional_args = positional_args(&raw_args.finish());
+        args.badge = parse_badge_args(&positional_args)?;
+        args.compare = parse_compare_args(&positional_args)?;
+        args.generate_command = parse_generate_args(&positional_args)?;
+        args.history_command = parse_history_args(&positional_args)?;
+        args.inspect = parse_inspect_args(&positional_args)?;
 
-        if args.readme_args.update_readme
+        if args.readme_args.begin_marker.is_some()
+            != args.readme_args.end_marker.is_some()
+        {
+            return Err(Box::new(ReadmeMarkersError));
+        }
+
+        if args.require_safety_docs && args.forbid_only {
+            return Err(Box::new(SafetyDocsForbidOnlyError));
+        }
+
+        if args.readme_args.updates_file()
             && args.output_format != OutputFormat::GitHubMarkdown
         {
             eprintln!(
-                "OutputFormat has been specified as {:?}, but the `--update-readme` flag has also been provided. \
-                To ensure the report written to the README.md is correct, a reduced charset will be used.",
+                "OutputFormat has been specified as {:?}, but the `--update-readme` or `--update-file` flag has also been provided. \
+                To ensure the report written to the file is correct, a reduced charset will be used.",
                 args.output_format
             );
             args.output_format = OutputFormat::GitHubMarkdown
@@ -173,6 +1221,26 @@ impl Args {
         Ok(args)
     }
 
+    /// Whether crates are grouped by where they come from, with either
+    /// `--group-by-source` or `--group-by source`
+    pub fn groups_by_source(&self) -> bool {
+        self.group_by_source || self.group_by == Some(GroupBy::Source)
+    }
+
+    /// The `--frozen`, `--locked` and `--offline` flags, given to the cargo
+    /// commands run besides the cargo library, such as `cargo metadata`
+    pub fn cargo_lock_args(&self) -> Vec<String> {
+        [
+            (self.frozen, "--frozen"),
+            (self.locked, "--locked"),
+            (self.offline, "--offline"),
+        ]
+        .iter()
+        .filter(|(is_set, _)| *is_set)
+        .map(|(_, flag)| String::from(*flag))
+        .collect()
+    }
+
     /// Update `cargo::util::Config` with values from `Args` struct, and set the shell
     /// colour choice
     /// ```
@@ -214,34 +1282,150 @@ impl Args {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
+pub struct BaselineArgs {
+    pub allow_new: Vec<String>,
+    pub baseline: Option<PathBuf>,
+    pub baseline_diff: bool,
+    pub deny_new_crates: bool,
+    pub save_baseline: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BundleArgs {
+    pub bundle_path: Option<PathBuf>,
+    pub force: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ChecksumArgs {
+    pub deny_checksum_mismatch: bool,
+    pub verify_checksums: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct DepsArgs {
     pub all_deps: bool,
     pub build_deps: bool,
     pub dev_deps: bool,
+    pub kind: Option<ExtraDeps>,
+    pub no_dev_deps: bool,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
+pub struct DuplicateArgs {
+    pub allow_duplicate: Vec<String>,
+    pub deny_duplicate_versions: bool,
+    pub warn_duplicate_versions: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FeatureMatrixArgs {
+    pub feature_matrix: bool,
+    pub feature_sets: Vec<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct FeaturesArgs {
     pub all_features: bool,
     pub features: Vec<String>,
     pub no_default_features: bool,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
+pub struct LocalTargetsArgs {
+    pub scan_benches: bool,
+    pub scan_examples: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LockfileArgs {
+    pub lockfile: Option<PathBuf>,
+    pub verify_lockfile: bool,
+    pub write_lockfile: bool,
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct TargetArgs {
     pub all_targets: bool,
     pub target: Option<String>,
 }
 
-#[derive(Debug, Default)]
+/// The members scanned together with `--workspace`
+#[derive(Clone, Debug, Default)]
+pub struct WorkspaceArgs {
+    pub exclude: Vec<String>,
+    pub member_summary: bool,
+    pub workspace: bool,
+}
+
+/// The build profile to scan with. Code behind `#[cfg(debug_assertions)]` is
+/// only used by `dev` builds, so the unsafe used by a `release` build can
+/// differ.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Profile {
+    #[default]
+    Dev,
+    Release,
+}
+
+impl Profile {
+    /// The name of the profile, as known to cargo
+    pub fn name(&self) -> &'static str {
+        match self {
+            Profile::Dev => "dev",
+            Profile::Release => "release",
+        }
+    }
+}
+
+impl std::str::FromStr for Profile {
+    type Err = ProfileParseError;
+    fn from_str(s: &str) -> Result<Self, ProfileParseError> {
+        match s {
+            "dev" => Ok(Self::Dev),
+            "release" => Ok(Self::Release),
+            _ => Err(ProfileParseError),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ProfileParseError;
+impl std::fmt::Display for ProfileParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "matching profile not found, expected dev or release")
+    }
+}
+impl std::error::Error for ProfileParseError {}
+
+#[derive(Clone, Debug, Default)]
+pub struct GitArgs {
+    pub branch: Option<String>,
+    pub git: Option<String>,
+    pub rev: Option<String>,
+    pub tag: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct ReadmeArgs {
+    pub begin_marker: Option<String>,
+    pub end_marker: Option<String>,
     pub readme_path: Option<PathBuf>,
     pub section_name: Option<String>,
+    pub update_file: Option<PathBuf>,
     pub update_readme: bool,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+impl ReadmeArgs {
+    /// Whether the report is written into a section of a file, with either
+    /// `--update-readme` or `--update-file`
+    pub fn updates_file(&self) -> bool {
+        self.update_readme || self.update_file.is_some()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Verbosity {
     Verbose,
     Normal,
@@ -265,10 +1449,24 @@ fn parse_features(raw_features: Option<String>) -> Vec<String> {
         .collect::<Vec<String>>()
 }
 
+/// The arguments left after the options which name a subcommand and its
+/// arguments, without the `geiger` passed first by cargo. Any unknown option
+/// is ignored, as it is without a subcommand.
+fn positional_args(free_args: &[OsString]) -> Vec<String> {
+    free_args
+        .iter()
+        .map(|free_arg| free_arg.to_string_lossy().into_owned())
+        .filter(|free_arg| !free_arg.starts_with('-'))
+        .skip_while(|free_arg| free_arg == "geiger")
+        .collect()
+}
+
 #[cfg(test)]
 pub mod args_tests {
     use super::*;
 
+    use crate::completions::CompletionShell;
+
     use cargo::core::shell::ColorChoice;
     use cargo::core::Verbosity as CargoCoreVerbosity;
     use rstest::*;
@@ -297,6 +1495,12 @@ pub mod args_tests {
             OutputFormat::Ascii,
             Quiet
         ),
+        case(
+            vec![OsString::from("--output-format"), OsString::from("Csv")],
+            false,
+            OutputFormat::Csv,
+            Quiet
+        ),
         case(
             vec![OsString::from("-v")],
             false,
@@ -320,6 +1524,12 @@ pub mod args_tests {
             false,
             OutputFormat::GitHubMarkdown,
             Quiet
+        ),
+        case(
+            vec![OsString::from("--update-file"), OsString::from("SECURITY.md")],
+            false,
+            OutputFormat::GitHubMarkdown,
+            Quiet
         )
     )]
     fn parse_args_test(
@@ -370,6 +1580,290 @@ pub mod args_tests {
         assert_eq!(parse_features(input_raw_features), expected_features);
     }
 
+    #[rstest(
+        input_argument_vector,
+        expected_profile_option,
+        case(vec![], Some(Profile::Dev)),
+        case(
+            vec![OsString::from("--profile"), OsString::from("release")],
+            Some(Profile::Release)
+        ),
+        case(
+            vec![OsString::from("--profile"), OsString::from("bench")],
+            None
+        )
+    )]
+    fn parse_args_test_profile(
+        input_argument_vector: Vec<OsString>,
+        expected_profile_option: Option<Profile>,
+    ) {
+        let args_result =
+            Args::parse_args(Arguments::from_vec(input_argument_vector));
+
+        assert_eq!(
+            args_result.ok().map(|a| a.profile),
+            expected_profile_option
+        );
+    }
+
+    #[rstest(
+        input_argument_vector,
+        expected_jobs_option,
+        case(vec![], Some(None)),
+        case(
+            vec![OsString::from("--jobs"), OsString::from("4")],
+            Some(NonZeroUsize::new(4))
+        ),
+        case(vec![OsString::from("-j"), OsString::from("1")], Some(NonZeroUsize::new(1))),
+        case(vec![OsString::from("--jobs"), OsString::from("0")], None)
+    )]
+    fn parse_args_test_jobs(
+        input_argument_vector: Vec<OsString>,
+        expected_jobs_option: Option<Option<NonZeroUsize>>,
+    ) {
+        let args_result =
+            Args::parse_args(Arguments::from_vec(input_argument_vector));
+
+        assert_eq!(args_result.ok().map(|a| a.jobs), expected_jobs_option);
+    }
+
+    #[rstest(
+        input_argument_vector,
+        expected_max_depth,
+        case(vec![], None),
+        case(vec!["--max-depth", "2"], Some(2)),
+        case(vec!["--depth", "1"], Some(1))
+    )]
+    fn parse_args_test_max_depth(
+        input_argument_vector: Vec<&str>,
+        expected_max_depth: Option<usize>,
+    ) {
+        let args = Args::parse_args(Arguments::from_vec(
+            input_argument_vector
+                .into_iter()
+                .map(OsString::from)
+                .collect(),
+        ))
+        .unwrap();
+
+        assert_eq!(args.max_depth, expected_max_depth);
+    }
+
+    #[rstest(
+        input_argument_vector,
+        expected_output_file,
+        expected_also_emit,
+        case(vec![], None, vec![]),
+        case(vec!["--output", "report.json"], Some("report.json"), vec![]),
+        case(
+            vec![
+                "--output-file",
+                "report.json",
+                "--output-format",
+                "Json",
+                "--also-emit",
+                "PrComment=report.md",
+                "--also-emit",
+                "Sarif=report.sarif"
+            ],
+            Some("report.json"),
+            vec![
+                (OutputFormat::PrComment, "report.md"),
+                (OutputFormat::Sarif, "report.sarif")
+            ]
+        )
+    )]
+    fn parse_args_test_output_file(
+        input_argument_vector: Vec<&str>,
+        expected_output_file: Option<&str>,
+        expected_also_emit: Vec<(OutputFormat, &str)>,
+    ) {
+        let args = Args::parse_args(Arguments::from_vec(
+            input_argument_vector
+                .into_iter()
+                .map(OsString::from)
+                .collect(),
+        ))
+        .unwrap();
+
+        assert_eq!(args.output_file, expected_output_file.map(PathBuf::from));
+        assert_eq!(
+            args.also_emit,
+            expected_also_emit
+                .into_iter()
+                .map(|(output_format, path)| EmittedReport {
+                    output_format,
+                    path: PathBuf::from(path),
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest(
+        input_argument_vector,
+        expected_markers,
+        case(vec![], Some((None, None))),
+        case(
+            vec!["--begin-marker", "<!-- begin -->", "--end-marker", "<!-- end -->"],
+            Some((Some("<!-- begin -->"), Some("<!-- end -->")))
+        ),
+        case(vec!["--begin-marker", "<!-- begin -->"], None),
+        case(vec!["--end-marker", "<!-- end -->"], None)
+    )]
+    fn parse_args_test_markers(
+        input_argument_vector: Vec<&str>,
+        expected_markers: Option<(Option<&str>, Option<&str>)>,
+    ) {
+        let args_result = Args::parse_args(Arguments::from_vec(
+            input_argument_vector
+                .into_iter()
+                .map(OsString::from)
+                .collect(),
+        ));
+
+        assert_eq!(
+            args_result.ok().map(|args| (
+                args.readme_args.begin_marker,
+                args.readme_args.end_marker
+            )),
+            expected_markers.map(|(begin_marker, end_marker)| (
+                begin_marker.map(String::from),
+                end_marker.map(String::from)
+            ))
+        );
+    }
+
+    #[rstest(
+        input_argument_vector,
+        expected_require_safety_docs,
+        case(vec![], Some(false)),
+        case(vec!["--require-safety-docs"], Some(true)),
+        case(vec!["--require-safety-docs", "--forbid-only"], None)
+    )]
+    fn parse_args_test_require_safety_docs(
+        input_argument_vector: Vec<&str>,
+        expected_require_safety_docs: Option<bool>,
+    ) {
+        let args_result = Args::parse_args(Arguments::from_vec(
+            input_argument_vector
+                .into_iter()
+                .map(OsString::from)
+                .collect(),
+        ));
+
+        assert_eq!(
+            args_result.ok().map(|args| args.require_safety_docs),
+            expected_require_safety_docs
+        );
+    }
+
+    #[rstest(
+        input_argument_vector,
+        expected_workspace,
+        expected_exclude,
+        case(vec![], false, vec![]),
+        case(vec!["--workspace-only"], false, vec![]),
+        case(
+            vec!["--workspace", "--exclude", "foo", "--exclude", "bar"],
+            true,
+            vec!["foo", "bar"]
+        )
+    )]
+    fn parse_args_test_workspace(
+        input_argument_vector: Vec<&str>,
+        expected_workspace: bool,
+        expected_exclude: Vec<&str>,
+    ) {
+        let args = Args::parse_args(Arguments::from_vec(
+            input_argument_vector
+                .into_iter()
+                .map(OsString::from)
+                .collect(),
+        ))
+        .unwrap();
+
+        assert_eq!(args.workspace_args.workspace, expected_workspace);
+        assert_eq!(args.workspace_args.exclude, expected_exclude);
+    }
+
+    #[rstest(
+        input_argument_vector,
+        expected_compare_name,
+        expected_history_command,
+        case(vec!["geiger", "--all"], None, None),
+        case(
+            vec!["geiger", "compare", "--all", "serde", "1.0.0", "1.0.1"],
+            Some("serde"),
+            None
+        ),
+        case(
+            vec!["history", "--all", "record"],
+            None,
+            Some(HistoryCommand::Record)
+        ),
+        case(vec!["geiger", "badge", "--all"], None, None),
+        case(vec!["geiger", "inspect", "--all", "tokio"], None, None),
+        case(vec!["geiger", "completions", "--all", "zsh"], None, None)
+    )]
+    fn parse_args_test_subcommand(
+        input_argument_vector: Vec<&str>,
+        expected_compare_name: Option<&str>,
+        expected_history_command: Option<HistoryCommand>,
+    ) {
+        let expected_badge = input_argument_vector.contains(&"badge");
+        let expected_inspect = input_argument_vector
+            .contains(&"inspect")
+            .then(|| String::from("tokio"));
+        let expected_generate_command = input_argument_vector
+            .contains(&"completions")
+            .then_some(GenerateCommand::Completions(CompletionShell::Zsh));
+        let args = Args::parse_args(Arguments::from_vec(
+            input_argument_vector
+                .into_iter()
+                .map(OsString::from)
+                .collect(),
+        ))
+        .unwrap();
+
+        assert!(args.all);
+        assert_eq!(
+            args.compare.map(|compare_spec| compare_spec.name),
+            expected_compare_name.map(String::from)
+        );
+        assert_eq!(args.history_command, expected_history_command);
+        assert_eq!(args.badge, expected_badge);
+        assert_eq!(
+            args.inspect.map(|crate_spec| crate_spec.name),
+            expected_inspect
+        );
+        assert_eq!(args.generate_command, expected_generate_command);
+    }
+
+    #[rstest(
+        input_frozen,
+        input_locked,
+        input_offline,
+        expected_cargo_lock_args,
+        case(false, false, false, vec![]),
+        case(false, true, false, vec!["--locked"]),
+        case(true, false, true, vec!["--frozen", "--offline"])
+    )]
+    fn cargo_lock_args_test(
+        input_frozen: bool,
+        input_locked: bool,
+        input_offline: bool,
+        expected_cargo_lock_args: Vec<&str>,
+    ) {
+        let args = Args {
+            frozen: input_frozen,
+            locked: input_locked,
+            offline: input_offline,
+            ..Default::default()
+        };
+
+        assert_eq!(args.cargo_lock_args(), expected_cargo_lock_args);
+    }
+
     #[rstest(
         input_quiet,
         input_verbosity,
@@ -0,0 +1,344 @@
+use cargo::CliError;
+use cargo_metadata::semver::Version;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub struct UnauditedUnsafeError {
+    pub crate_count: u64,
+}
+
+impl Error for UnauditedUnsafeError {}
+
+impl fmt::Display for UnauditedUnsafeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Found {} crates using unsafe code without an audit",
+            self.crate_count
+        )
+    }
+}
+
+/// A manual review of a crate, of a single version or of every version of it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CrateAudit {
+    pub auditor: String,
+    pub date: Option<String>,
+    pub note: Option<String>,
+    pub version: Option<Version>,
+}
+
+/// The audits read from the file given with `--audits`, by crate name
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Audits {
+    crate_audits: HashMap<String, Vec<CrateAudit>>,
+}
+
+impl Audits {
+    /// The audit of the version of the crate, an audit of that version being
+    /// preferred to one of every version
+    pub fn get(&self, name: &str, version: &Version) -> Option<&CrateAudit> {
+        let crate_audits = self.crate_audits.get(name)?;
+        crate_audits
+            .iter()
+            .find(|audit| audit.version.as_ref() == Some(version))
+            .or_else(|| {
+                crate_audits.iter().find(|audit| audit.version.is_none())
+            })
+    }
+
+    /// An `[[audit]]` table for each audit, such as
+    ///
+    /// ```toml
+    /// [[audit]]
+    /// crate = "libc"
+    /// version = "0.2.132"
+    /// auditor = "Jane Doe"
+    /// date = "2022-09-01"
+    /// note = "Reviewed the FFI bindings"
+    /// ```
+    ///
+    /// in which only `crate` and `auditor` are required, or the `audits.toml`
+    /// of cargo-vet, whose audits of a delta are of the version audited to.
+    pub fn from_toml(s: &str) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct AuditEntry {
+            #[serde(rename = "crate")]
+            name: String,
+            version: Option<Version>,
+            auditor: String,
+            date: Option<String>,
+            note: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum VetAuditors {
+            One(String),
+            Many(Vec<String>),
+        }
+
+        #[derive(Deserialize)]
+        struct VetAuditEntry {
+            who: Option<VetAuditors>,
+            version: Option<Version>,
+            delta: Option<String>,
+            notes: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        struct AuditsFile {
+            #[serde(default)]
+            audit: Vec<AuditEntry>,
+            /// The audits of cargo-vet, by crate name
+            #[serde(default)]
+            audits: BTreeMap<String, Vec<VetAuditEntry>>,
+        }
+
+        let audits_file = toml_edit::easy::from_str::<AuditsFile>(s)?;
+        let mut audits = Audits::default();
+        for entry in audits_file.audit {
+            audits.insert(
+                entry.name,
+                CrateAudit {
+                    auditor: entry.auditor,
+                    date: entry.date,
+                    note: entry.note,
+                    version: entry.version,
+                },
+            );
+        }
+        for (name, entries) in audits_file.audits {
+            for entry in entries {
+                let version = match (entry.version, entry.delta) {
+                    (Some(version), _) => version,
+                    (None, Some(delta)) => parse_delta_target(&delta)
+                        .ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "Failed to parse the delta `{}` of an audit of {}, expected `<version> -> <version>`",
+                                delta,
+                                name
+                            )
+                        })?,
+                    (None, None) => anyhow::bail!(
+                        "An audit of {} has neither a `version` nor a `delta`",
+                        name
+                    ),
+                };
+                let auditor = match entry.who {
+                    Some(VetAuditors::One(who)) => who,
+                    Some(VetAuditors::Many(who)) => who.join(", "),
+                    None => String::from("unknown"),
+                };
+                audits.insert(
+                    name.clone(),
+                    CrateAudit {
+                        auditor,
+                        date: None,
+                        note: entry.notes,
+                        version: Some(version),
+                    },
+                );
+            }
+        }
+        Ok(audits)
+    }
+
+    fn insert(&mut self, name: String, crate_audit: CrateAudit) {
+        self.crate_audits.entry(name).or_default().push(crate_audit);
+    }
+}
+
+/// The annotation of a crate in the tree, such as
+/// ` (audited by Jane Doe on 2022-09-01)`. Only crates using unsafe code are
+/// annotated when they have no audit, as ` (not audited)`.
+pub fn format_audit(
+    crate_audit: Option<&CrateAudit>,
+    uses_unsafe: bool,
+) -> String {
+    match crate_audit {
+        Some(CrateAudit {
+            auditor,
+            date: Some(date),
+            ..
+        }) => format!(" (audited by {} on {})", auditor, date),
+        Some(CrateAudit { auditor, .. }) => {
+            format!(" (audited by {})", auditor)
+        }
+        None if uses_unsafe => String::from(" (not audited)"),
+        None => String::new(),
+    }
+}
+
+/// Read the file given with `--audits`, or `None` if no file is given
+pub fn read_audits_file(
+    audits_path: &Option<PathBuf>,
+) -> Result<Option<Audits>, CliError> {
+    match audits_path {
+        Some(audits_path) => read_audits(audits_path).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn read_audits(audits_path: &Path) -> Result<Audits, CliError> {
+    let audits_content = fs::read_to_string(audits_path).map_err(|e| {
+        eprintln!("Failed to read audits from file: {}", audits_path.display());
+        anyhow::Error::from(e)
+    })?;
+    Audits::from_toml(&audits_content).map_err(|e| {
+        eprintln!(
+            "Failed to parse audits from file: {}",
+            audits_path.display()
+        );
+        CliError::new(e, 1)
+    })
+}
+
+/// The version audited to by a cargo-vet delta, such as the `1.1.0` of
+/// `1.0.0 -> 1.1.0`
+fn parse_delta_target(delta: &str) -> Option<Version> {
+    let (_, target) = delta.split_once("->")?;
+    Version::parse(target.trim()).ok()
+}
+
+#[cfg(test)]
+mod audits_tests {
+    use super::*;
+
+    use rstest::*;
+
+    const AUDITS_TOML: &str = r#"
+[[audit]]
+crate = "libc"
+version = "0.2.132"
+auditor = "Jane Doe"
+date = "2022-09-01"
+note = "Reviewed the FFI bindings"
+
+[[audit]]
+crate = "libc"
+auditor = "John Doe"
+"#;
+
+    const VET_AUDITS_TOML: &str = r#"
+[criteria.safe-to-deploy]
+description = "Safe to deploy"
+
+[[audits.memchr]]
+who = "Jane Doe <jane@example.com>"
+criteria = "safe-to-deploy"
+version = "2.5.0"
+notes = "No unsafe code outside of the SIMD routines"
+
+[[audits.memchr]]
+who = ["Jane Doe", "John Doe"]
+criteria = "safe-to-deploy"
+delta = "2.5.0 -> 2.6.0"
+"#;
+
+    #[rstest(
+        input_version,
+        expected_auditor,
+        case(Version::new(0, 2, 132), Some("Jane Doe")),
+        case(Version::new(0, 2, 133), Some("John Doe"))
+    )]
+    fn audits_from_toml_test(
+        input_version: Version,
+        expected_auditor: Option<&str>,
+    ) {
+        let audits = Audits::from_toml(AUDITS_TOML).unwrap();
+
+        assert_eq!(
+            audits
+                .get("libc", &input_version)
+                .map(|audit| audit.auditor.as_str()),
+            expected_auditor
+        );
+        assert_eq!(audits.get("memchr", &input_version), None);
+    }
+
+    #[rstest]
+    fn audits_from_toml_test_vet() {
+        let audits = Audits::from_toml(VET_AUDITS_TOML).unwrap();
+
+        assert_eq!(
+            audits.get("memchr", &Version::new(2, 5, 0)),
+            Some(&CrateAudit {
+                auditor: String::from("Jane Doe <jane@example.com>"),
+                date: None,
+                note: Some(String::from(
+                    "No unsafe code outside of the SIMD routines"
+                )),
+                version: Some(Version::new(2, 5, 0)),
+            })
+        );
+        assert_eq!(
+            audits
+                .get("memchr", &Version::new(2, 6, 0))
+                .map(|audit| audit.auditor.as_str()),
+            Some("Jane Doe, John Doe")
+        );
+        assert_eq!(audits.get("memchr", &Version::new(2, 7, 0)), None);
+        assert!(Audits::from_toml(
+            "[[audits.memchr]]\ncriteria = \"safe-to-run\"\n"
+        )
+        .is_err());
+    }
+
+    #[rstest(
+        input_crate_audit,
+        input_uses_unsafe,
+        expected_annotation,
+        case(
+            Some(CrateAudit {
+                auditor: String::from("Jane Doe"),
+                date: Some(String::from("2022-09-01")),
+                note: None,
+                version: None,
+            }),
+            true,
+            " (audited by Jane Doe on 2022-09-01)"
+        ),
+        case(
+            Some(CrateAudit {
+                auditor: String::from("Jane Doe"),
+                date: None,
+                note: None,
+                version: None,
+            }),
+            false,
+            " (audited by Jane Doe)"
+        ),
+        case(None, true, " (not audited)"),
+        case(None, false, "")
+    )]
+    fn format_audit_test(
+        input_crate_audit: Option<CrateAudit>,
+        input_uses_unsafe: bool,
+        expected_annotation: &str,
+    ) {
+        assert_eq!(
+            format_audit(input_crate_audit.as_ref(), input_uses_unsafe),
+            expected_annotation
+        );
+    }
+
+    #[rstest(
+        input_delta,
+        expected_version,
+        case("2.5.0 -> 2.6.0", Some(Version::new(2, 6, 0))),
+        case("2.5.0->2.6.0", Some(Version::new(2, 6, 0))),
+        case("2.6.0", None)
+    )]
+    fn parse_delta_target_test(
+        input_delta: &str,
+        expected_version: Option<Version>,
+    ) {
+        assert_eq!(parse_delta_target(input_delta), expected_version);
+    }
+}
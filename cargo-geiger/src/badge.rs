@@ -0,0 +1,285 @@
+use crate::format::CrateDetectionStatus;
+
+use cargo::CliError;
+use cargo_geiger_serde::SafetyReport;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// The file written by `badge`, unless `--output-file` is given
+pub const DEFAULT_BADGE_PATH: &str = "geiger-badge.svg";
+
+/// The approximate width of a character of 11px Verdana, which the badge is
+/// sized for, as browsers don't measure the text of an SVG image
+const CHARACTER_WIDTH: usize = 7;
+
+/// The space on each side of the label and the message
+const TEXT_PADDING: usize = 6;
+
+const LABEL: &str = "unsafe";
+
+const LABEL_COLOR: &str = "#555";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BadgeArgsError;
+
+impl Error for BadgeArgsError {}
+
+impl fmt::Display for BadgeArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected `badge`, with the path of the badge given with \
+            `--output-file`"
+        )
+    }
+}
+
+/// The status of all crates of the report, colored as in the tree: green if
+/// every crate forbids unsafe code, red if any crate uses unsafe code, and
+/// neutral otherwise
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BadgeStatus {
+    ForbidsUnsafe,
+    NoneDetected,
+    UnsafeDetected,
+}
+
+impl BadgeStatus {
+    fn color(self) -> &'static str {
+        match self {
+            BadgeStatus::ForbidsUnsafe => "#4c1",
+            BadgeStatus::NoneDetected => "#9f9f9f",
+            BadgeStatus::UnsafeDetected => "#e05d44",
+        }
+    }
+}
+
+/// The badge written by `badge`, with the unsafe code used by the build and
+/// found in all crates of the report as its message, such as `3/120`
+#[derive(Debug, Eq, PartialEq)]
+pub struct Badge {
+    pub status: BadgeStatus,
+    pub used: u64,
+    pub found: u64,
+}
+
+impl Badge {
+    pub fn from_report(report: &SafetyReport) -> Self {
+        let mut badge = Badge {
+            status: BadgeStatus::ForbidsUnsafe,
+            used: 0,
+            found: 0,
+        };
+        for entry in report.packages.values() {
+            let used = entry.unsafety.used.total_unsafe();
+            badge.used += used;
+            badge.found += used + entry.unsafety.unused.total_unsafe();
+            badge.status = match (
+                badge.status,
+                CrateDetectionStatus::from(&entry.unsafety),
+            ) {
+                (_, CrateDetectionStatus::UnsafeDetected)
+                | (BadgeStatus::UnsafeDetected, _) => {
+                    BadgeStatus::UnsafeDetected
+                }
+                (
+                    BadgeStatus::ForbidsUnsafe,
+                    CrateDetectionStatus::NoneDetectedForbidsUnsafe,
+                ) => BadgeStatus::ForbidsUnsafe,
+                _ => BadgeStatus::NoneDetected,
+            };
+        }
+        badge
+    }
+
+    pub fn message(&self) -> String {
+        format!("{}/{}", self.used, self.found)
+    }
+
+    /// The badge in the flat style of shields.io
+    pub fn to_svg(&self) -> String {
+        let message = self.message();
+        let label_width = text_width(LABEL);
+        let message_width = text_width(&message);
+        let width = label_width + message_width;
+        let title = format!(
+            "{}: {} used by the build/found",
+            LABEL,
+            escape_xml(&message)
+        );
+
+        [
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="20" role="img" aria-label="{}">"#,
+                width, title
+            ),
+            format!("<title>{}</title>", title),
+            String::from(
+                r##"<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>"##,
+            ),
+            format!(
+                r##"<clipPath id="r"><rect width="{}" height="20" rx="3" fill="#fff"/></clipPath>"##,
+                width
+            ),
+            format!(
+                r#"<g clip-path="url(#r)"><rect width="{}" height="20" fill="{}"/><rect x="{}" width="{}" height="20" fill="{}"/><rect width="{}" height="20" fill="url(#s)"/></g>"#,
+                label_width,
+                LABEL_COLOR,
+                label_width,
+                message_width,
+                self.status.color(),
+                width
+            ),
+            format!(
+                r##"<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{}" y="14">{}</text><text x="{}" y="14">{}</text></g>"##,
+                label_width / 2,
+                LABEL,
+                label_width + message_width / 2,
+                escape_xml(&message)
+            ),
+            String::from("</svg>"),
+        ]
+        .join("\n")
+    }
+}
+
+/// Whether the positional arguments are of the `badge` subcommand
+pub fn parse_badge_args(
+    positional_args: &[String],
+) -> Result<bool, BadgeArgsError> {
+    match positional_args {
+        [subcommand] if subcommand == "badge" => Ok(true),
+        [subcommand, ..] if subcommand == "badge" => Err(BadgeArgsError),
+        _ => Ok(false),
+    }
+}
+
+pub fn write_badge(badge_path: &Path, badge: &Badge) -> Result<(), CliError> {
+    fs::write(badge_path, badge.to_svg() + "\n").map_err(|e| {
+        eprintln!("Failed to write badge to file: {}", badge_path.display());
+        CliError::new(anyhow::Error::from(e), 1)
+    })
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn text_width(text: &str) -> usize {
+    text.chars().count() * CHARACTER_WIDTH + 2 * TEXT_PADDING
+}
+
+#[cfg(test)]
+mod badge_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::{PackageInfo, UnsafeInfo};
+    use rstest::*;
+
+    /// A report with a crate for each of the used and unused unsafe
+    /// expressions, and whether the crate forbids unsafe code
+    fn create_report(crates: &[(u64, u64, bool)]) -> SafetyReport {
+        create_safety_report(
+            crates
+                .iter()
+                .enumerate()
+                .map(|(index, (used_exprs, unused_exprs, forbids_unsafe))| {
+                    let mut unsafety = UnsafeInfo {
+                        forbids_unsafe: *forbids_unsafe,
+                        ..create_unsafe_info(*used_exprs)
+                    };
+                    unsafety.unused.exprs.unsafe_ = *unused_exprs;
+                    let package_id =
+                        create_path_package_id(&format!("crate{}", index));
+                    (PackageInfo::new(package_id), unsafety)
+                })
+                .collect(),
+        )
+    }
+
+    #[rstest(
+        input_crates,
+        expected_badge,
+        case(
+            vec![],
+            Badge { status: BadgeStatus::ForbidsUnsafe, used: 0, found: 0 }
+        ),
+        case(
+            vec![(0, 0, true), (0, 0, true)],
+            Badge { status: BadgeStatus::ForbidsUnsafe, used: 0, found: 0 }
+        ),
+        case(
+            vec![(0, 0, true), (0, 4, false)],
+            Badge { status: BadgeStatus::NoneDetected, used: 0, found: 4 }
+        ),
+        case(
+            vec![(2, 1, false), (0, 0, true)],
+            Badge { status: BadgeStatus::UnsafeDetected, used: 2, found: 3 }
+        )
+    )]
+    fn badge_from_report_test(
+        input_crates: Vec<(u64, u64, bool)>,
+        expected_badge: Badge,
+    ) {
+        assert_eq!(
+            Badge::from_report(&create_report(&input_crates)),
+            expected_badge
+        );
+    }
+
+    #[rstest(
+        input_positional_args,
+        expected_badge,
+        case(vec![], Ok(false)),
+        case(vec!["history", "show"], Ok(false)),
+        case(vec!["badge"], Ok(true)),
+        case(vec!["badge", "geiger-badge.svg"], Err(BadgeArgsError))
+    )]
+    fn parse_badge_args_test(
+        input_positional_args: Vec<&str>,
+        expected_badge: Result<bool, BadgeArgsError>,
+    ) {
+        let positional_args = input_positional_args
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        assert_eq!(parse_badge_args(&positional_args), expected_badge);
+    }
+
+    #[rstest]
+    fn badge_to_svg_test() {
+        let svg = Badge {
+            status: BadgeStatus::UnsafeDetected,
+            used: 3,
+            found: 120,
+        }
+        .to_svg();
+
+        // "unsafe" is 6 and "3/120" 5 characters wide.
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="101" height="20" role="img" aria-label="unsafe: 3/120 used by the build/found">"#
+        ));
+        assert!(svg.contains(
+            r##"<rect x="54" width="47" height="20" fill="#e05d44"/>"##
+        ));
+        assert!(svg.contains(
+            r#"<text x="27" y="14">unsafe</text><text x="77" y="14">3/120</text>"#
+        ));
+        assert!(svg.ends_with("</svg>"));
+    }
+
+    #[rstest]
+    fn escape_xml_test() {
+        assert_eq!(escape_xml(r#"<a & "b">"#), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+}
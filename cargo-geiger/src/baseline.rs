@@ -0,0 +1,315 @@
+use crate::args::BaselineArgs;
+use crate::format::json::{
+    create_json_line, safety_report_from_json, JsonVersion,
+};
+use crate::format::toml::{create_toml_lines, safety_report_from_toml};
+use crate::graph::Graph;
+use crate::mapping::{CargoMetadataParameters, ToCargoGeigerPackageId};
+
+use cargo::CliError;
+use cargo_geiger_serde::{PackageId, SafetyReport};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct NewCratesError {
+    pub new_crate_count: usize,
+}
+
+impl Error for NewCratesError {}
+
+impl fmt::Display for NewCratesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Found {} crates which are not present in the baseline",
+            self.new_crate_count
+        )
+    }
+}
+
+/// Read a report previously written with `--output-format Json`, or with
+/// `--output-format Toml` for a file with the `.toml` extension, to be used as
+/// the baseline for a scan
+pub fn read_baseline(baseline_path: &Path) -> Result<SafetyReport, CliError> {
+    let baseline_content = fs::read_to_string(baseline_path).map_err(|e| {
+        eprintln!(
+            "Failed to read baseline from file: {}",
+            baseline_path.display()
+        );
+        CliError::new(anyhow::Error::from(e), 1)
+    })?;
+
+    let (baseline, output_format) = match baseline_path.extension() {
+        Some(extension) if extension == "toml" => {
+            (safety_report_from_toml(&baseline_content), "Toml")
+        }
+        _ => (safety_report_from_json(&baseline_content), "Json"),
+    };
+    baseline.map_err(|e| {
+        eprintln!(
+            "Failed to parse baseline file: {}, expected a report created with `--output-format {}`",
+            baseline_path.display(),
+            output_format
+        );
+        CliError::new(e, 1)
+    })
+}
+
+/// Write the report of the scan to the path of `--save-baseline`, in the
+/// format `read_baseline` expects for its extension
+pub fn write_baseline(
+    baseline_path: &Path,
+    report: &SafetyReport,
+) -> Result<(), CliError> {
+    let baseline_content = match baseline_path.extension() {
+        Some(extension) if extension == "toml" => {
+            create_toml_lines(report).join("\n")
+        }
+        _ => create_json_line(report, false, JsonVersion::default()),
+    };
+    fs::write(baseline_path, baseline_content).map_err(|e| {
+        eprintln!(
+            "Failed to write baseline to file: {}",
+            baseline_path.display()
+        );
+        CliError::new(anyhow::Error::from(e), 1)
+    })
+}
+
+/// When `--deny-new-crates` is set, the gate fails if the dependency graph
+/// contains a crate whose name and version are not present in the
+/// `--baseline`, unless the crate name has been allowed with `--allow-new`
+pub fn check_new_crates(
+    baseline_args: &BaselineArgs,
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    graph: &Graph,
+) -> Result<Option<NewCratesError>, CliError> {
+    if !baseline_args.deny_new_crates {
+        return Ok(None);
+    }
+
+    let baseline_path = match &baseline_args.baseline {
+        Some(baseline_path) => baseline_path,
+        None => {
+            eprintln!("`--deny-new-crates` requires a `--baseline` report");
+            return Err(CliError::code(1));
+        }
+    };
+    let baseline = read_baseline(baseline_path)?;
+
+    let current_package_ids = graph
+        .graph
+        .node_weights()
+        .filter_map(|package_id| {
+            package_id
+                .to_cargo_geiger_package_id(cargo_metadata_parameters.metadata)
+        })
+        .collect::<Vec<PackageId>>();
+
+    let new_crates = find_new_crates(
+        &baseline,
+        &current_package_ids,
+        &baseline_args.allow_new,
+    );
+
+    if new_crates.is_empty() {
+        return Ok(None);
+    }
+
+    eprintln!("Crates not present in the baseline:");
+    for new_crate in &new_crates {
+        eprintln!("    {} {}", new_crate.name, new_crate.version);
+    }
+
+    Ok(Some(NewCratesError {
+        new_crate_count: new_crates.len(),
+    }))
+}
+
+/// Crates are matched by name and version only, the source is ignored so
+/// that a baseline stays valid when it is moved between machines
+fn find_new_crates(
+    baseline: &SafetyReport,
+    current_package_ids: &[PackageId],
+    allow_new: &[String],
+) -> Vec<PackageId> {
+    let baseline_crates = baseline
+        .packages
+        .keys()
+        .chain(baseline.packages_without_metrics.iter())
+        .map(|package_id| (&package_id.name, &package_id.version))
+        .collect::<HashSet<_>>();
+
+    let mut new_crates = current_package_ids
+        .iter()
+        .filter(|package_id| {
+            !baseline_crates.contains(&(&package_id.name, &package_id.version))
+                && !allow_new.contains(&package_id.name)
+        })
+        .cloned()
+        .collect::<Vec<PackageId>>();
+
+    new_crates.sort_by(|a, b| {
+        a.name.cmp(&b.name).then_with(|| a.version.cmp(&b.version))
+    });
+    new_crates.dedup_by(|a, b| a.name == b.name && a.version == b.version);
+    new_crates
+}
+
+#[cfg(test)]
+mod baseline_tests {
+    use super::*;
+
+    use crate::format::toml::create_toml_lines;
+    use crate::lib_tests::{create_registry_package_id, create_safety_report};
+
+    use cargo_geiger_serde::{PackageInfo, UnsafeInfo};
+    use rstest::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[rstest(
+        input_current_crates,
+        input_allow_new,
+        expected_new_crates,
+        case(
+            vec![("foo", "1.0.0"), ("bar", "0.2.0")],
+            vec![],
+            vec![]
+        ),
+        case(
+            vec![("foo", "1.0.0"), ("bar", "0.3.0"), ("baz", "0.1.0")],
+            vec![],
+            vec![("bar", "0.3.0"), ("baz", "0.1.0")]
+        ),
+        case(
+            vec![("foo", "1.0.0"), ("bar", "0.3.0"), ("baz", "0.1.0")],
+            vec![String::from("baz")],
+            vec![("bar", "0.3.0")]
+        )
+    )]
+    fn find_new_crates_test(
+        input_current_crates: Vec<(&str, &str)>,
+        input_allow_new: Vec<String>,
+        expected_new_crates: Vec<(&str, &str)>,
+    ) {
+        let baseline =
+            create_baseline(&[("foo", "1.0.0")], &[("bar", "0.2.0")]);
+        let current_package_ids = input_current_crates
+            .iter()
+            .map(|(name, version)| create_registry_package_id(name, version))
+            .collect::<Vec<PackageId>>();
+
+        let new_crates =
+            find_new_crates(&baseline, &current_package_ids, &input_allow_new);
+
+        assert_eq!(
+            new_crates
+                .iter()
+                .map(|p| (p.name.as_str(), p.version.to_string()))
+                .collect::<Vec<(&str, String)>>(),
+            expected_new_crates
+                .iter()
+                .map(|(name, version)| (*name, version.to_string()))
+                .collect::<Vec<(&str, String)>>()
+        );
+    }
+
+    #[rstest]
+    fn read_baseline_test() {
+        let baseline =
+            create_baseline(&[("foo", "1.0.0")], &[("bar", "0.2.0")]);
+        let mut baseline_file = NamedTempFile::new().unwrap();
+        write!(
+            baseline_file,
+            "{}",
+            serde_json::to_string(&baseline).unwrap()
+        )
+        .unwrap();
+
+        let read_baseline_result = read_baseline(baseline_file.path());
+
+        assert!(read_baseline_result.is_ok());
+        assert_eq!(read_baseline_result.unwrap(), baseline);
+    }
+
+    #[rstest]
+    fn read_baseline_test_toml() {
+        let baseline =
+            create_baseline(&[("foo", "1.0.0")], &[("bar", "0.2.0")]);
+        let mut baseline_file =
+            tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+        write!(baseline_file, "{}", create_toml_lines(&baseline).join("\n"))
+            .unwrap();
+
+        let read_baseline_result = read_baseline(baseline_file.path());
+
+        assert_eq!(read_baseline_result.unwrap(), baseline);
+    }
+
+    #[rstest(input_suffix, case(".json"), case(".toml"))]
+    fn write_baseline_test(input_suffix: &str) {
+        let baseline =
+            create_baseline(&[("foo", "1.0.0")], &[("bar", "0.2.0")]);
+        let baseline_file = tempfile::Builder::new()
+            .suffix(input_suffix)
+            .tempfile()
+            .unwrap();
+
+        write_baseline(baseline_file.path(), &baseline).unwrap();
+
+        assert_eq!(read_baseline(baseline_file.path()).unwrap(), baseline);
+    }
+
+    #[rstest]
+    fn read_baseline_test_invalid_content() {
+        let mut baseline_file = NamedTempFile::new().unwrap();
+        write!(baseline_file, "not a report").unwrap();
+
+        assert_eq!(
+            read_baseline(baseline_file.path()).unwrap_err().exit_code,
+            1
+        );
+    }
+
+    #[rstest]
+    fn read_baseline_test_missing_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        assert_eq!(
+            read_baseline(&temp_dir.path().join("missing.json"))
+                .unwrap_err()
+                .exit_code,
+            1
+        );
+    }
+
+    fn create_baseline(
+        packages: &[(&str, &str)],
+        packages_without_metrics: &[(&str, &str)],
+    ) -> SafetyReport {
+        let mut baseline = create_safety_report(
+            packages
+                .iter()
+                .map(|(name, version)| {
+                    (
+                        PackageInfo::new(create_registry_package_id(
+                            name, version,
+                        )),
+                        UnsafeInfo::default(),
+                    )
+                })
+                .collect(),
+        );
+        for (name, version) in packages_without_metrics {
+            baseline
+                .packages_without_metrics
+                .insert(create_registry_package_id(name, version));
+        }
+        baseline
+    }
+}
@@ -0,0 +1,458 @@
+//! An audit bundle is a directory containing everything needed to review, or
+//! reproduce, a scan at a later point in time:
+//!
+//! ```text
+//! <dir>/
+//!     report.json         The report, as written by `--output-format Json`
+//!                         with the same `--json-version` and `--categories`
+//!     args.txt            The command line, and the arguments as parsed
+//!     Cargo.lock          The lock file the scan was resolved against
+//!     scanned-files.txt   The .rs files used by the build, one per line
+//! ```
+
+use crate::args::{Args, BundleArgs};
+use crate::format::json::create_json_line;
+use crate::scan::ScanReport;
+
+use cargo::{CliError, CliResult};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const BUNDLE_ARGS_FILENAME: &str = "args.txt";
+pub const BUNDLE_CARGO_LOCK_FILENAME: &str = "Cargo.lock";
+pub const BUNDLE_REPORT_FILENAME: &str = "report.json";
+pub const BUNDLE_SCANNED_FILES_FILENAME: &str = "scanned-files.txt";
+
+/// The files written to a bundle. Only these are replaced in an existing
+/// bundle directory, any other file in it is kept.
+const BUNDLE_FILENAMES: [&str; 4] = [
+    BUNDLE_ARGS_FILENAME,
+    BUNDLE_CARGO_LOCK_FILENAME,
+    BUNDLE_REPORT_FILENAME,
+    BUNDLE_SCANNED_FILES_FILENAME,
+];
+
+/// Make sure the bundle directory exists and is empty, unless `--force` has
+/// been provided. This is checked before scanning, to fail early. The
+/// current directory, the workspace root and their parents are refused, as
+/// the bundle directory is replaced when the bundle is written.
+pub fn prepare_bundle_directory(
+    bundle_args: &BundleArgs,
+    workspace_root: &Path,
+) -> CliResult {
+    let bundle_path = match &bundle_args.bundle_path {
+        Some(bundle_path) => bundle_path,
+        None => return Ok(()),
+    };
+
+    if contains_protected_directory(bundle_path, workspace_root) {
+        eprintln!(
+            "Bundle directory: {} is the current directory, the workspace root, or a parent of either. Choose a directory of its own.",
+            bundle_path.display()
+        );
+        return Err(CliError::code(1));
+    }
+
+    let is_non_empty = match fs::read_dir(bundle_path) {
+        Ok(mut entries) => entries.next().is_some(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => false,
+        Err(e) => {
+            eprintln!(
+                "Failed to read bundle directory: {}",
+                bundle_path.display()
+            );
+            return Err(anyhow::Error::from(e).into());
+        }
+    };
+
+    if is_non_empty && !bundle_args.force {
+        eprintln!(
+            "Bundle directory: {} is not empty. Use `--force` to overwrite its contents.",
+            bundle_path.display()
+        );
+        return Err(CliError::code(1));
+    }
+
+    fs::create_dir_all(bundle_path).map_err(|e| {
+        eprintln!(
+            "Failed to create bundle directory: {}",
+            bundle_path.display()
+        );
+        anyhow::Error::from(e)
+    })?;
+
+    Ok(())
+}
+
+/// Whether the bundle directory is the current directory or the workspace
+/// root, or one of their parents
+fn contains_protected_directory(
+    bundle_path: &Path,
+    workspace_root: &Path,
+) -> bool {
+    // A directory which doesn't exist yet can't contain another one.
+    let canonical_bundle_path = match bundle_path.canonicalize() {
+        Ok(canonical_bundle_path) => canonical_bundle_path,
+        Err(_) => return false,
+    };
+    env::current_dir()
+        .into_iter()
+        .chain(Some(workspace_root.to_path_buf()))
+        .filter_map(|path| path.canonicalize().ok())
+        .any(|path| path.starts_with(&canonical_bundle_path))
+}
+
+/// Write the audit bundle into the directory given by `--bundle`. The files
+/// are written to a temporary directory next to it, which then takes its
+/// place, so that a failed write never leaves a partial bundle behind.
+pub fn write_bundle(
+    args: &Args,
+    bundle_path: &Path,
+    cargo_lock_path: &Path,
+    scan_report: &ScanReport,
+) -> CliResult {
+    let parent_path = match bundle_path.parent() {
+        Some(parent_path) if !parent_path.as_os_str().is_empty() => parent_path,
+        _ => Path::new("."),
+    };
+    let temp_dir = tempfile::Builder::new()
+        .prefix(".geiger-bundle")
+        .tempdir_in(parent_path)
+        .map_err(|e| {
+            eprintln!(
+                "Failed to create temporary bundle directory in: {}",
+                parent_path.display()
+            );
+            anyhow::Error::from(e)
+        })?;
+
+    write_bundle_files(args, temp_dir.path(), cargo_lock_path, scan_report)?;
+
+    replace_bundle_directory(temp_dir.path(), bundle_path, parent_path)
+        .map_err(|e| {
+            eprintln!(
+                "Failed to move the bundle into place: {}",
+                bundle_path.display()
+            );
+            anyhow::Error::from(e)
+        })?;
+
+    eprintln!("Wrote audit bundle to: {}", bundle_path.display());
+
+    Ok(())
+}
+
+/// Move the new bundle into place. An existing bundle directory is moved
+/// aside first, and only removed once the new bundle is in place and the
+/// files of the directory which are not part of a bundle have been moved
+/// over to it.
+fn replace_bundle_directory(
+    new_bundle_path: &Path,
+    bundle_path: &Path,
+    parent_path: &Path,
+) -> io::Result<()> {
+    let old_dir = tempfile::Builder::new()
+        .prefix(".geiger-bundle-old")
+        .tempdir_in(parent_path)?;
+    let old_bundle_path = old_dir.path().join("bundle");
+    match fs::rename(bundle_path, &old_bundle_path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return fs::rename(new_bundle_path, bundle_path)
+        }
+        result => result?,
+    }
+    if let Err(e) = fs::rename(new_bundle_path, bundle_path) {
+        fs::rename(&old_bundle_path, bundle_path)?;
+        return Err(e);
+    }
+
+    let moved_over = fs::read_dir(&old_bundle_path).and_then(|entries| {
+        for entry in entries {
+            let entry = entry?;
+            if !BUNDLE_FILENAMES
+                .iter()
+                .any(|file_name| entry.file_name() == *file_name)
+            {
+                fs::rename(entry.path(), bundle_path.join(entry.file_name()))?;
+            }
+        }
+        Ok(())
+    });
+    if moved_over.is_err() {
+        eprintln!(
+            "WARNING: The previous contents of the bundle directory are kept in: {}",
+            old_dir.into_path().display()
+        );
+    }
+    moved_over
+}
+
+fn write_bundle_files(
+    args: &Args,
+    bundle_path: &Path,
+    cargo_lock_path: &Path,
+    scan_report: &ScanReport,
+) -> CliResult {
+    write_bundle_file(
+        bundle_path,
+        BUNDLE_REPORT_FILENAME,
+        &create_json_line(
+            &scan_report.report,
+            args.categories,
+            args.json_version,
+        ),
+    )?;
+
+    write_bundle_file(
+        bundle_path,
+        BUNDLE_ARGS_FILENAME,
+        &construct_args_content(
+            env::args_os().map(|arg| arg.to_string_lossy().into_owned()),
+            args,
+        ),
+    )?;
+
+    if cargo_lock_path.exists() {
+        fs::copy(
+            cargo_lock_path,
+            bundle_path.join(BUNDLE_CARGO_LOCK_FILENAME),
+        )
+        .map_err(|e| {
+            eprintln!(
+                "Failed to copy lock file: {}",
+                cargo_lock_path.display()
+            );
+            anyhow::Error::from(e)
+        })?;
+    } else {
+        eprintln!(
+            "WARNING: No lock file found at: {}, it is left out of the bundle",
+            cargo_lock_path.display()
+        );
+    }
+
+    write_bundle_file(
+        bundle_path,
+        BUNDLE_SCANNED_FILES_FILENAME,
+        &construct_scanned_files_content(&scan_report.rs_files_used),
+    )
+}
+
+fn construct_args_content<I: Iterator<Item = String>>(
+    command_line: I,
+    args: &Args,
+) -> String {
+    format!(
+        "Command line: {}\n\nParsed arguments:\n{:#?}\n",
+        command_line.collect::<Vec<String>>().join(" "),
+        args
+    )
+}
+
+fn construct_scanned_files_content<'a, I: IntoIterator<Item = &'a PathBuf>>(
+    rs_files_used: I,
+) -> String {
+    let mut paths = rs_files_used
+        .into_iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<String>>();
+    paths.sort();
+
+    paths.iter().map(|path| format!("{}\n", path)).collect()
+}
+
+fn write_bundle_file(
+    bundle_path: &Path,
+    file_name: &str,
+    content: &str,
+) -> CliResult {
+    let file_path = bundle_path.join(file_name);
+    fs::write(&file_path, content).map_err(|e| {
+        eprintln!("Failed to write bundle file: {}", file_path.display());
+        anyhow::Error::from(e)
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod bundle_tests {
+    use super::*;
+
+    use crate::format::json::{safety_report_from_json, JsonVersion};
+
+    use cargo_geiger_serde::SafetyReport;
+    use rstest::*;
+    use tempfile::tempdir;
+
+    #[rstest(
+        input_create_file,
+        input_force,
+        expected_is_ok,
+        case(false, false, true),
+        case(true, false, false),
+        case(true, true, true)
+    )]
+    fn prepare_bundle_directory_test(
+        input_create_file: bool,
+        input_force: bool,
+        expected_is_ok: bool,
+    ) {
+        let temp_dir = tempdir().unwrap();
+        if input_create_file {
+            fs::write(temp_dir.path().join("existing.txt"), "").unwrap();
+        }
+        let bundle_args = BundleArgs {
+            bundle_path: Some(temp_dir.path().to_path_buf()),
+            force: input_force,
+        };
+
+        assert_eq!(
+            prepare_bundle_directory(&bundle_args, tempdir().unwrap().path())
+                .is_ok(),
+            expected_is_ok
+        );
+    }
+
+    #[rstest(input_bundle_path, case(""), case("workspace"))]
+    fn prepare_bundle_directory_test_protected_directory(
+        input_bundle_path: &str,
+    ) {
+        let temp_dir = tempdir().unwrap();
+        let workspace_root = temp_dir.path().join("workspace");
+        fs::create_dir(&workspace_root).unwrap();
+        let bundle_args = BundleArgs {
+            bundle_path: Some(temp_dir.path().join(input_bundle_path)),
+            force: true,
+        };
+
+        assert_eq!(
+            prepare_bundle_directory(&bundle_args, &workspace_root)
+                .unwrap_err()
+                .exit_code,
+            1
+        );
+    }
+
+    #[rstest]
+    fn prepare_bundle_directory_test_creates_directory() {
+        let temp_dir = tempdir().unwrap();
+        let bundle_path = temp_dir.path().join("nested").join("bundle");
+        let bundle_args = BundleArgs {
+            bundle_path: Some(bundle_path.clone()),
+            force: false,
+        };
+
+        assert!(prepare_bundle_directory(
+            &bundle_args,
+            &temp_dir.path().join("workspace")
+        )
+        .is_ok());
+        assert!(bundle_path.is_dir());
+    }
+
+    #[rstest]
+    fn write_bundle_test_replaces_bundle_directory() {
+        let temp_dir = tempdir().unwrap();
+        let bundle_path = temp_dir.path().join("bundle");
+        fs::create_dir(&bundle_path).unwrap();
+        fs::write(bundle_path.join("existing.txt"), "").unwrap();
+        fs::write(bundle_path.join(BUNDLE_CARGO_LOCK_FILENAME), "").unwrap();
+        let scan_report = ScanReport {
+            report: Default::default(),
+            rs_files_used: vec![PathBuf::from("/a/lib.rs")]
+                .into_iter()
+                .collect(),
+        };
+
+        assert!(write_bundle(
+            &Args::default(),
+            &bundle_path,
+            &temp_dir.path().join("Cargo.lock"),
+            &scan_report
+        )
+        .is_ok());
+
+        let mut file_names = fs::read_dir(&bundle_path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        file_names.sort();
+        assert_eq!(
+            file_names,
+            vec![
+                BUNDLE_ARGS_FILENAME,
+                "existing.txt",
+                BUNDLE_REPORT_FILENAME,
+                BUNDLE_SCANNED_FILES_FILENAME
+            ]
+        );
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[rstest(
+        input_json_version,
+        expected_schema_version,
+        case(JsonVersion::V1, serde_json::Value::Null),
+        case(JsonVersion::V2, serde_json::Value::from(2))
+    )]
+    fn write_bundle_test_json_version(
+        input_json_version: JsonVersion,
+        expected_schema_version: serde_json::Value,
+    ) {
+        let temp_dir = tempdir().unwrap();
+        let bundle_path = temp_dir.path().join("bundle");
+        let args = Args {
+            json_version: input_json_version,
+            ..Default::default()
+        };
+
+        write_bundle(
+            &args,
+            &bundle_path,
+            &temp_dir.path().join("Cargo.lock"),
+            &ScanReport {
+                report: SafetyReport::default(),
+                rs_files_used: Default::default(),
+            },
+        )
+        .unwrap();
+
+        let report_json =
+            fs::read_to_string(bundle_path.join(BUNDLE_REPORT_FILENAME))
+                .unwrap();
+        let json_value =
+            serde_json::from_str::<serde_json::Value>(&report_json).unwrap();
+        assert_eq!(json_value["schema_version"], expected_schema_version);
+        assert_eq!(
+            safety_report_from_json(&report_json).unwrap(),
+            SafetyReport::default()
+        );
+    }
+
+    #[rstest]
+    fn construct_scanned_files_content_test() {
+        let rs_files_used =
+            vec![PathBuf::from("/b/lib.rs"), PathBuf::from("/a/main.rs")];
+
+        assert_eq!(
+            construct_scanned_files_content(&rs_files_used),
+            "/a/main.rs\n/b/lib.rs\n"
+        );
+    }
+
+    #[rstest]
+    fn construct_args_content_test() {
+        let args = Args::default();
+        let args_content = construct_args_content(
+            vec![String::from("cargo-geiger"), String::from("geiger")]
+                .into_iter(),
+            &args,
+        );
+
+        assert!(args_content.starts_with(
+            "Command line: cargo-geiger geiger\n\nParsed arguments:\n"
+        ));
+    }
+}
@@ -0,0 +1,516 @@
+use crate::args::ChecksumArgs;
+use crate::graph::Graph;
+
+use cargo::CliError;
+use cargo_metadata::semver::Version;
+use cargo_util::Sha256;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use walkdir::{DirEntry, WalkDir};
+
+/// Written by cargo when it extracts a registry crate, so it is not part of
+/// the crate's source
+const CARGO_OK_FILE_NAME: &str = ".cargo-ok";
+
+/// Directories in the root of a crate which are not part of its source, and
+/// are only present for path and git dependencies
+const IGNORED_ROOT_DIRECTORIES: [&str; 2] = [".git", "target"];
+
+/// Files marking a directory below the crate root as not part of its source:
+/// the manifest of a nested package, such as a vendored crate or another
+/// workspace member, and the tag cargo writes in its build directories
+const IGNORED_DIRECTORY_MARKERS: [&str; 2] = ["Cargo.toml", "CACHEDIR.TAG"];
+
+#[derive(Debug)]
+pub struct ChecksumMismatchError {
+    pub mismatched_crate_count: usize,
+}
+
+impl Error for ChecksumMismatchError {}
+
+impl fmt::Display for ChecksumMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Found {} crates whose source doesn't match the expected checksum",
+            self.mismatched_crate_count
+        )
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ChecksumFileParseError {
+    pub line_number: usize,
+}
+
+impl Error for ChecksumFileParseError {}
+
+impl fmt::Display for ChecksumFileParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse line {}, expected `<name> <version> <sha256>`",
+            self.line_number
+        )
+    }
+}
+
+/// The result of comparing a crate's source against the checksum file
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChecksumStatus {
+    Matched,
+    Mismatched {
+        expected: String,
+    },
+    /// The crate is not listed in the checksum file
+    Unlisted,
+}
+
+/// The status of the source of each crate of the dependency graph, compared
+/// against the file given with `--verify-checksums`
+#[derive(Debug, Default)]
+pub struct ChecksumStatuses {
+    crate_statuses: HashMap<(String, Version), ChecksumStatus>,
+}
+
+impl ChecksumStatuses {
+    pub fn get(
+        &self,
+        name: &str,
+        version: &Version,
+    ) -> Option<&ChecksumStatus> {
+        self.crate_statuses
+            .get(&(name.to_string(), version.clone()))
+    }
+
+    /// Whether the source of the crate doesn't match its checksum, or `None`
+    /// if it isn't listed in the file
+    pub fn is_mismatched(&self, name: &str, version: &Version) -> Option<bool> {
+        match self.get(name, version)? {
+            ChecksumStatus::Matched => Some(false),
+            ChecksumStatus::Mismatched { .. } => Some(true),
+            ChecksumStatus::Unlisted => None,
+        }
+    }
+
+    pub fn mismatched_crate_count(&self) -> u64 {
+        self.crate_statuses
+            .values()
+            .filter(|status| {
+                matches!(status, ChecksumStatus::Mismatched { .. })
+            })
+            .count() as u64
+    }
+}
+
+/// The annotation of a crate in the tree whose source doesn't match its
+/// checksum, and which is left unannotated otherwise
+pub fn format_checksum_mismatch(is_mismatched: Option<bool>) -> String {
+    match is_mismatched {
+        Some(true) => String::from(" (CHECKSUM MISMATCH)"),
+        _ => String::new(),
+    }
+}
+
+/// When `--verify-checksums` is set, the gate fails on a mismatch if
+/// `--deny-checksum-mismatch` is set too
+pub fn check_checksums(
+    checksum_args: &ChecksumArgs,
+    mismatched_crate_count: u64,
+) -> Option<ChecksumMismatchError> {
+    (checksum_args.deny_checksum_mismatch && mismatched_crate_count > 0)
+        .then_some(ChecksumMismatchError {
+            mismatched_crate_count: mismatched_crate_count as usize,
+        })
+}
+
+/// When `--verify-checksums` is set, compute the checksum of the source of
+/// every crate in the dependency graph and compare it against the file, or
+/// return `None` without it. Mismatched crates, and the checksums of unlisted
+/// crates, are printed.
+pub fn find_checksum_statuses(
+    checksum_args: &ChecksumArgs,
+    cargo_metadata: &cargo_metadata::Metadata,
+    graph: &Graph,
+) -> Result<Option<ChecksumStatuses>, CliError> {
+    let checksums_path = match &checksum_args.verify_checksums {
+        Some(checksums_path) => checksums_path,
+        None if checksum_args.deny_checksum_mismatch => {
+            eprintln!(
+                "`--deny-checksum-mismatch` requires a `--verify-checksums` file"
+            );
+            return Err(CliError::code(1));
+        }
+        None => return Ok(None),
+    };
+    let expected_checksums = read_checksums(checksums_path)?;
+
+    let mut packages = graph
+        .graph
+        .node_weights()
+        .map(|package_id| &cargo_metadata[package_id])
+        .collect::<Vec<&cargo_metadata::Package>>();
+    packages.sort_by(|a, b| {
+        a.name
+            .cmp(&b.name)
+            .then_with(|| a.version.cmp(&b.version))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    packages.dedup_by(|a, b| a.id == b.id);
+
+    let mut crate_statuses = HashMap::new();
+    let mut mismatched_lines = vec![];
+    let mut unlisted_lines = vec![];
+    for package in packages {
+        let package_root = package.manifest_path.parent().unwrap();
+        let checksum = compute_source_checksum(package_root.as_std_path())
+            .map_err(|e| {
+                eprintln!(
+                    "Failed to compute the checksum of package: {}",
+                    package.id
+                );
+                CliError::new(e, 1)
+            })?;
+
+        let checksum_status = checksum_status(
+            &expected_checksums,
+            &package.name,
+            &package.version,
+            &checksum,
+        );
+        match &checksum_status {
+            ChecksumStatus::Matched => {}
+            ChecksumStatus::Mismatched { expected } => {
+                mismatched_lines.push(format!(
+                    "    {} {} (expected {}, found {}) at {}",
+                    package.name,
+                    package.version,
+                    expected,
+                    checksum,
+                    package_root
+                ));
+            }
+            ChecksumStatus::Unlisted => {
+                unlisted_lines.push(format!(
+                    "    {} {} {}",
+                    package.name, package.version, checksum
+                ));
+            }
+        }
+        crate_statuses.insert(
+            (package.name.clone(), package.version.clone()),
+            checksum_status,
+        );
+    }
+
+    if !unlisted_lines.is_empty() {
+        eprintln!("Crates without an expected checksum:");
+        for unlisted_line in &unlisted_lines {
+            eprintln!("{}", unlisted_line);
+        }
+    }
+
+    if !mismatched_lines.is_empty() {
+        eprintln!(
+            "CHECKSUM MISMATCH, the source of these crates has been modified:"
+        );
+        for mismatched_line in &mismatched_lines {
+            eprintln!("{}", mismatched_line);
+        }
+    }
+
+    Ok(Some(ChecksumStatuses { crate_statuses }))
+}
+
+/// The SHA-256 of the files of the crate, ordered by their path relative to
+/// the crate root. The directories of nested packages and of builds are left
+/// out, so that the checksum of a workspace root only covers its own
+/// sources. Each file contributes its path with `/` separators, a NUL byte, its
+/// length as a little-endian `u64` and its content, so that moving bytes
+/// between files changes the checksum.
+pub fn compute_source_checksum(
+    package_root: &Path,
+) -> Result<String, anyhow::Error> {
+    let mut sha256 = Sha256::new();
+
+    let walker = WalkDir::new(package_root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| !is_ignored_entry(entry));
+
+    for entry in walker {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative_path = entry
+            .path()
+            .strip_prefix(package_root)
+            .unwrap()
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let content = fs::read(entry.path())?;
+
+        sha256
+            .update(relative_path.as_bytes())
+            .update(&[0])
+            .update(&(content.len() as u64).to_le_bytes())
+            .update(&content);
+    }
+
+    Ok(sha256.finish_hex())
+}
+
+fn checksum_status(
+    expected_checksums: &HashMap<(String, Version), String>,
+    name: &str,
+    version: &Version,
+    checksum: &str,
+) -> ChecksumStatus {
+    match expected_checksums.get(&(name.to_string(), version.clone())) {
+        Some(expected) if expected.eq_ignore_ascii_case(checksum) => {
+            ChecksumStatus::Matched
+        }
+        Some(expected) => ChecksumStatus::Mismatched {
+            expected: expected.clone(),
+        },
+        None => ChecksumStatus::Unlisted,
+    }
+}
+
+fn is_ignored_entry(entry: &DirEntry) -> bool {
+    let file_name = entry.file_name().to_string_lossy();
+    let is_dir = entry.file_type().is_dir();
+    match entry.depth() {
+        0 => false,
+        1 if !is_dir => file_name == CARGO_OK_FILE_NAME,
+        1 if IGNORED_ROOT_DIRECTORIES.contains(&file_name.as_ref()) => true,
+        _ => {
+            is_dir
+                && IGNORED_DIRECTORY_MARKERS
+                    .iter()
+                    .any(|marker| entry.path().join(marker).exists())
+        }
+    }
+}
+
+/// Empty lines and lines starting with `#` are ignored
+fn parse_checksums(
+    checksums_content: &str,
+) -> Result<HashMap<(String, Version), String>, ChecksumFileParseError> {
+    let mut expected_checksums = HashMap::new();
+    for (index, line) in checksums_content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parse_error = ChecksumFileParseError {
+            line_number: index + 1,
+        };
+        let fields = line.split_whitespace().collect::<Vec<&str>>();
+        match fields.as_slice() {
+            [name, version, checksum]
+                if checksum.chars().all(|c| c.is_ascii_hexdigit()) =>
+            {
+                let version =
+                    Version::parse(version).map_err(|_| parse_error)?;
+                expected_checksums
+                    .insert((name.to_string(), version), checksum.to_string());
+            }
+            _ => return Err(parse_error),
+        }
+    }
+    Ok(expected_checksums)
+}
+
+fn read_checksums(
+    checksums_path: &Path,
+) -> Result<HashMap<(String, Version), String>, CliError> {
+    let checksums_content =
+        fs::read_to_string(checksums_path).map_err(|e| {
+            eprintln!(
+                "Failed to read checksums from file: {}",
+                checksums_path.display()
+            );
+            anyhow::Error::from(e)
+        })?;
+
+    parse_checksums(&checksums_content).map_err(|e| {
+        eprintln!(
+            "Failed to parse checksum file: {}",
+            checksums_path.display()
+        );
+        CliError::new(anyhow::Error::from(e), 1)
+    })
+}
+
+#[cfg(test)]
+mod checksum_tests {
+    use super::*;
+
+    use rstest::*;
+    use tempfile::tempdir;
+
+    #[rstest(
+        input_checksum,
+        expected_checksum_status,
+        case("abc123", ChecksumStatus::Matched),
+        case("ABC123", ChecksumStatus::Matched),
+        case(
+            "def456",
+            ChecksumStatus::Mismatched {
+                expected: String::from("abc123")
+            }
+        )
+    )]
+    fn checksum_status_test(
+        input_checksum: &str,
+        expected_checksum_status: ChecksumStatus,
+    ) {
+        let expected_checksums =
+            parse_checksums("# comment\n\nfoo 1.0.0 abc123\n").unwrap();
+
+        assert_eq!(
+            checksum_status(
+                &expected_checksums,
+                "foo",
+                &Version::new(1, 0, 0),
+                input_checksum
+            ),
+            expected_checksum_status
+        );
+        assert_eq!(
+            checksum_status(
+                &expected_checksums,
+                "foo",
+                &Version::new(1, 1, 0),
+                input_checksum
+            ),
+            ChecksumStatus::Unlisted
+        );
+    }
+
+    #[rstest(
+        input_deny_checksum_mismatch,
+        input_mismatched_crate_count,
+        expected_mismatched_crate_count,
+        case(false, 2, None),
+        case(true, 0, None),
+        case(true, 2, Some(2))
+    )]
+    fn check_checksums_test(
+        input_deny_checksum_mismatch: bool,
+        input_mismatched_crate_count: u64,
+        expected_mismatched_crate_count: Option<usize>,
+    ) {
+        let checksum_args = ChecksumArgs {
+            deny_checksum_mismatch: input_deny_checksum_mismatch,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_checksums(&checksum_args, input_mismatched_crate_count)
+                .map(|error| error.mismatched_crate_count),
+            expected_mismatched_crate_count
+        );
+    }
+
+    #[rstest]
+    fn checksum_statuses_test() {
+        let version = Version::new(1, 0, 0);
+        let checksum_statuses = ChecksumStatuses {
+            crate_statuses: vec![
+                (
+                    (String::from("foo"), version.clone()),
+                    ChecksumStatus::Matched,
+                ),
+                (
+                    (String::from("bar"), version.clone()),
+                    ChecksumStatus::Mismatched {
+                        expected: String::from("abc123"),
+                    },
+                ),
+                (
+                    (String::from("baz"), version.clone()),
+                    ChecksumStatus::Unlisted,
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        assert_eq!(
+            checksum_statuses.is_mismatched("foo", &version),
+            Some(false)
+        );
+        assert_eq!(
+            checksum_statuses.is_mismatched("bar", &version),
+            Some(true)
+        );
+        assert_eq!(checksum_statuses.is_mismatched("baz", &version), None);
+        assert_eq!(checksum_statuses.is_mismatched("qux", &version), None);
+        assert_eq!(checksum_statuses.mismatched_crate_count(), 1);
+        assert_eq!(
+            format_checksum_mismatch(Some(true)),
+            " (CHECKSUM MISMATCH)"
+        );
+        assert_eq!(format_checksum_mismatch(Some(false)), "");
+    }
+
+    #[rstest(
+        input_checksums_content,
+        expected_line_number,
+        case("foo 1.0.0", 1),
+        case("foo 1.0.0 abc123\nbar one 123abc", 2),
+        case("foo 1.0.0 not-hex", 1),
+        case("foo 1.0.0 abc123 extra", 1)
+    )]
+    fn parse_checksums_test_invalid(
+        input_checksums_content: &str,
+        expected_line_number: usize,
+    ) {
+        assert_eq!(
+            parse_checksums(input_checksums_content),
+            Err(ChecksumFileParseError {
+                line_number: expected_line_number
+            })
+        );
+    }
+
+    #[rstest]
+    fn compute_source_checksum_test() {
+        let package_root = tempdir().unwrap();
+        fs::create_dir(package_root.path().join("src")).unwrap();
+        fs::write(package_root.path().join("src/lib.rs"), "fn foo() {}")
+            .unwrap();
+        let checksum = compute_source_checksum(package_root.path()).unwrap();
+
+        // Files written by cargo, or by a build, don't change the checksum.
+        fs::write(package_root.path().join(CARGO_OK_FILE_NAME), "ok").unwrap();
+        fs::create_dir(package_root.path().join("target")).unwrap();
+        fs::write(package_root.path().join("target/foo"), "").unwrap();
+        // Neither do nested packages, nor build directories elsewhere.
+        fs::create_dir_all(package_root.path().join("vendor/bar")).unwrap();
+        fs::write(package_root.path().join("vendor/bar/Cargo.toml"), "")
+            .unwrap();
+        fs::create_dir_all(package_root.path().join("build/out")).unwrap();
+        fs::write(package_root.path().join("build/CACHEDIR.TAG"), "").unwrap();
+        fs::write(package_root.path().join("build/out/foo"), "").unwrap();
+        assert_eq!(
+            compute_source_checksum(package_root.path()).unwrap(),
+            checksum
+        );
+
+        fs::write(package_root.path().join("src/lib.rs"), "fn bar() {}")
+            .unwrap();
+        assert_ne!(
+            compute_source_checksum(package_root.path()).unwrap(),
+            checksum
+        );
+    }
+}
@@ -7,12 +7,13 @@
 // using rustc? Is it implementing a compiler plugin?
 
 use crate::args::Args;
+use crate::format::print_config::build_format_pattern;
 
 // TODO: Consider making this a lib.rs (again) and expose a full API, excluding
 // only the terminal output..? That API would be dependent on cargo.
 use cargo::core::Workspace;
 use cargo::util::{important_paths, CargoResult};
-use cargo::Config;
+use cargo::{CliError, Config};
 use cargo_metadata::{CargoOpt, Metadata, MetadataCommand};
 use cargo_platform::Cfg;
 use krates::Builder as KratesBuilder;
@@ -47,7 +48,28 @@ pub fn get_cargo_metadata(
         metadata_command.features(metadata_command_features);
     }
 
-    Ok(metadata_command.exec()?)
+    let mut other_options = args.cargo_lock_args();
+    let is_locked = !other_options.is_empty();
+    // Leaves out the crates only depended on by other platforms, which are
+    // otherwise scanned although they are never built.
+    if let (false, Some(target)) =
+        (args.target_args.all_targets, &args.target_args.target)
+    {
+        other_options.push(String::from("--filter-platform"));
+        other_options.push(target.clone());
+    }
+    metadata_command.other_options(other_options);
+
+    metadata_command.exec().map_err(|e| {
+        if is_locked {
+            eprintln!(
+                "Failed to read the dependency graph. With `--frozen`, \
+                `--locked` or `--offline`, Cargo.lock must be up to date and \
+                every source must already be downloaded, with `cargo fetch`."
+            );
+        }
+        e.into()
+    })
 }
 
 /// TODO: Write proper documentation for this.
@@ -75,6 +97,12 @@ pub fn get_cfgs(
     ))
 }
 
+/// The chunks the format given with `--format` or `--format-file` is built
+/// from, for `--check-format`, or the error the scan would fail with
+pub fn check_format(args: &Args) -> Result<String, CliError> {
+    Ok(build_format_pattern(args)?.describe_chunks())
+}
+
 pub fn get_krates(cargo_metadata: &Metadata) -> CargoResult<Krates> {
     Ok(KratesBuilder::new()
         .build_with_metadata(cargo_metadata.clone(), |_| ())?)
@@ -96,6 +124,8 @@ pub fn get_workspace(
 #[cfg(test)]
 mod cli_tests {
     use super::*;
+
+    use crate::args::TargetArgs;
     use rstest::*;
 
     #[rstest]
@@ -108,6 +138,40 @@ mod cli_tests {
         assert!(cargo_metadata_result.is_ok());
     }
 
+    #[rstest(
+        input_all_targets,
+        input_target,
+        expected_has_winapi,
+        case(false, Some("x86_64-unknown-linux-gnu"), false),
+        case(false, Some("x86_64-pc-windows-msvc"), true),
+        case(true, Some("x86_64-unknown-linux-gnu"), true),
+        case(false, None, true)
+    )]
+    fn get_cargo_metadata_target_test(
+        input_all_targets: bool,
+        input_target: Option<&str>,
+        expected_has_winapi: bool,
+    ) {
+        let args = Args {
+            target_args: TargetArgs {
+                all_targets: input_all_targets,
+                target: input_target.map(String::from),
+            },
+            ..Default::default()
+        };
+        let config = Config::default().unwrap();
+
+        let cargo_metadata = get_cargo_metadata(&args, &config).unwrap();
+
+        assert_eq!(
+            cargo_metadata
+                .packages
+                .iter()
+                .any(|package| package.name == "winapi"),
+            expected_has_winapi
+        );
+    }
+
     #[rstest]
     fn get_cfgs_test() {
         let config = Config::default().unwrap();
@@ -0,0 +1,421 @@
+use crate::diff::{construct_diff_lines, diff_reports};
+use crate::format::table::UNSAFE_CATEGORY_NAMES;
+use crate::registry::PackageSpec;
+
+use cargo_geiger_serde::{CounterBlock, SafetyReport};
+use cargo_metadata::semver::Version;
+use geiger::find::find_unsafe_in_file;
+use geiger::IncludeTests;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// The crate of crates.io and its two versions given with
+/// `cargo geiger compare <CRATE> <OLD_VERSION> <NEW_VERSION>`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompareSpec {
+    pub name: String,
+    pub old_version: Version,
+    pub new_version: Version,
+}
+
+impl CompareSpec {
+    /// The package spec downloading exactly the version of the crate
+    pub fn package_spec(&self, version: &Version) -> PackageSpec {
+        PackageSpec {
+            name: self.name.clone(),
+            version_req: Some(format!("={}", version)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CompareArgsError;
+
+impl Error for CompareArgsError {}
+
+impl fmt::Display for CompareArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected `compare <CRATE> <OLD_VERSION> <NEW_VERSION>`, with \
+            complete versions such as 1.0.120"
+        )
+    }
+}
+
+/// The unsafe code of a side of the comparison
+pub struct CompareSide<'a> {
+    pub report: &'a SafetyReport,
+    /// The unsafe code found in each file of the crate, see
+    /// `unsafe_file_counts`
+    pub unsafe_file_counts: &'a BTreeMap<PathBuf, u64>,
+}
+
+/// The `compare` subcommand from the positional arguments, or `None` if they
+/// are not of the subcommand
+pub fn parse_compare_args(
+    positional_args: &[String],
+) -> Result<Option<CompareSpec>, CompareArgsError> {
+    match positional_args {
+        [subcommand, ..] if subcommand != "compare" => Ok(None),
+        [] => Ok(None),
+        [_, name, old_version, new_version] => Ok(Some(CompareSpec {
+            name: name.clone(),
+            old_version: Version::parse(old_version)
+                .map_err(|_| CompareArgsError)?,
+            new_version: Version::parse(new_version)
+                .map_err(|_| CompareArgsError)?,
+        })),
+        _ => Err(CompareArgsError),
+    }
+}
+
+/// The unsafe code found in each `.rs` file of the package, by its path
+/// relative to the root of the package, so that the files of two versions
+/// can be matched. A file which fails to be scanned is left out.
+pub fn unsafe_file_counts(
+    package_path: &Path,
+    include_tests: IncludeTests,
+) -> BTreeMap<PathBuf, u64> {
+    WalkDir::new(package_path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_type().is_file()
+                && entry.path().extension().is_some_and(|ext| ext == "rs")
+        })
+        .filter_map(|entry| {
+            match find_unsafe_in_file(entry.path(), include_tests) {
+                Ok(rs_file_metrics) => Some((
+                    entry.path().strip_prefix(package_path).unwrap().to_owned(),
+                    rs_file_metrics.counters.total_unsafe(),
+                )),
+                Err(e) => {
+                    eprintln!(
+                        "Failed to scan file: {}, {}",
+                        entry.path().display(),
+                        e
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// The files using unsafe code in the new version which used none, or didn't
+/// exist, in the old version, with their unsafe code
+pub fn newly_unsafe_files(
+    old_unsafe_file_counts: &BTreeMap<PathBuf, u64>,
+    new_unsafe_file_counts: &BTreeMap<PathBuf, u64>,
+) -> Vec<(PathBuf, u64)> {
+    new_unsafe_file_counts
+        .iter()
+        .filter(|(path, unsafe_count)| {
+            **unsafe_count > 0
+                && old_unsafe_file_counts.get(*path).is_none_or(|c| *c == 0)
+        })
+        .map(|(path, unsafe_count)| (path.clone(), *unsafe_count))
+        .collect()
+}
+
+/// The lines printed by `compare`: the unsafe code found in the crate and the
+/// unsafe code used by its dependencies side by side, the files which became
+/// unsafe, and the difference for each dependency
+pub fn construct_compare_lines(
+    compare_spec: &CompareSpec,
+    old: &CompareSide,
+    new: &CompareSide,
+) -> Vec<String> {
+    let old_label = compare_spec.old_version.to_string();
+    let new_label = compare_spec.new_version.to_string();
+    let old_metrics = compare_metrics(
+        &compare_spec.name,
+        &compare_spec.old_version,
+        old.report,
+    );
+    let new_metrics = compare_metrics(
+        &compare_spec.name,
+        &compare_spec.new_version,
+        new.report,
+    );
+
+    let mut compare_lines = vec![
+        String::new(),
+        format!(
+            "Unsafe usage of {}: {} -> {}",
+            compare_spec.name, old_label, new_label
+        ),
+        String::new(),
+        format_compare_row("", &old_label, &new_label, "delta"),
+    ];
+    for ((metric, old_value), (_, new_value)) in
+        old_metrics.iter().zip(&new_metrics)
+    {
+        compare_lines.push(format_compare_row(
+            metric,
+            &old_value.to_string(),
+            &new_value.to_string(),
+            &format!("{:+}", *new_value as i64 - *old_value as i64),
+        ));
+    }
+
+    compare_lines.push(String::new());
+    compare_lines.push(format!("Newly unsafe files in {}:", new_label));
+    let newly_unsafe_files =
+        newly_unsafe_files(old.unsafe_file_counts, new.unsafe_file_counts);
+    if newly_unsafe_files.is_empty() {
+        compare_lines.push(String::from("    None"));
+    }
+    for (path, unsafe_count) in newly_unsafe_files {
+        compare_lines.push(format!("    {} {}", path.display(), unsafe_count));
+    }
+
+    // The crate itself is compared above, in the diff it would be a removed
+    // and an added crate.
+    let dependency_diffs = diff_reports(old.report, new.report)
+        .into_iter()
+        .filter(|crate_diff| crate_diff.name != compare_spec.name)
+        .collect::<Vec<_>>();
+    compare_lines.extend(construct_diff_lines(
+        &old_label,
+        &new_label,
+        &dependency_diffs,
+    ));
+
+    compare_lines
+}
+
+/// The unsafe code found in the crate for each category, then the number of
+/// dependencies, of those using unsafe code, and the unsafe code they use
+fn compare_metrics(
+    name: &str,
+    version: &Version,
+    report: &SafetyReport,
+) -> Vec<(&'static str, u64)> {
+    let mut crate_found = CounterBlock::default();
+    let mut dependency_count = 0;
+    let mut unsafe_dependency_count = 0;
+    let mut dependency_used = 0;
+    for entry in report.packages.values() {
+        if entry.package.id.name == name && entry.package.id.version == *version
+        {
+            crate_found =
+                entry.unsafety.used.clone() + entry.unsafety.unused.clone();
+            continue;
+        }
+        dependency_count += 1;
+        let used = entry.unsafety.used.total_unsafe();
+        if used > 0 {
+            unsafe_dependency_count += 1;
+        }
+        dependency_used += used;
+    }
+
+    let mut metrics = UNSAFE_CATEGORY_NAMES
+        .iter()
+        .copied()
+        .zip([
+            crate_found.functions.unsafe_,
+            crate_found.exprs.unsafe_,
+            crate_found.item_impls.unsafe_,
+            crate_found.item_traits.unsafe_,
+            crate_found.methods.unsafe_,
+        ])
+        .collect::<Vec<_>>();
+    metrics.push(("dependencies", dependency_count));
+    metrics.push(("dependencies using unsafe", unsafe_dependency_count));
+    metrics.push(("unsafe used by dependencies", dependency_used));
+    metrics
+}
+
+fn format_compare_row(
+    metric: &str,
+    old_value: &str,
+    new_value: &str,
+    delta: &str,
+) -> String {
+    format!(
+        "{: <28}{: >12}{: >12}{: >8}",
+        metric, old_value, new_value, delta
+    )
+}
+
+#[cfg(test)]
+mod compare_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::{PackageId, PackageInfo};
+    use rstest::*;
+    use std::fs;
+
+    fn compare_spec() -> CompareSpec {
+        CompareSpec {
+            name: String::from("foo"),
+            old_version: Version::new(0, 1, 0),
+            new_version: Version::new(0, 2, 0),
+        }
+    }
+
+    fn create_report(crates: &[(&str, Version, u64)]) -> SafetyReport {
+        create_safety_report(
+            crates
+                .iter()
+                .map(|(name, version, used_exprs)| {
+                    let package_id = PackageId {
+                        version: version.clone(),
+                        ..create_path_package_id(name)
+                    };
+                    (
+                        PackageInfo::new(package_id),
+                        create_unsafe_info(*used_exprs),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[rstest(
+        input_positional_args,
+        expected_compare_spec,
+        case(vec![], Ok(None)),
+        case(vec!["history", "show"], Ok(None)),
+        case(vec!["compare", "foo", "0.1.0", "0.2.0"], Ok(Some(compare_spec()))),
+        case(vec!["compare", "foo", "0.1.0"], Err(CompareArgsError)),
+        case(vec!["compare", "foo", "0.1", "0.2.0"], Err(CompareArgsError))
+    )]
+    fn parse_compare_args_test(
+        input_positional_args: Vec<&str>,
+        expected_compare_spec: Result<Option<CompareSpec>, CompareArgsError>,
+    ) {
+        let positional_args = input_positional_args
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        assert_eq!(parse_compare_args(&positional_args), expected_compare_spec);
+    }
+
+    #[rstest]
+    fn compare_spec_package_spec_test() {
+        assert_eq!(
+            compare_spec().package_spec(&Version::new(0, 1, 0)),
+            PackageSpec {
+                name: String::from("foo"),
+                version_req: Some(String::from("=0.1.0")),
+            }
+        );
+    }
+
+    #[rstest]
+    fn unsafe_file_counts_test() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::create_dir(temp_dir.path().join("src")).unwrap();
+        fs::write(
+            temp_dir.path().join("src").join("lib.rs"),
+            "pub fn f() { unsafe { std::hint::unreachable_unchecked() } }",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("src").join("safe.rs"), "fn g() {}")
+            .unwrap();
+        fs::write(temp_dir.path().join("README.md"), "unsafe").unwrap();
+
+        assert_eq!(
+            unsafe_file_counts(temp_dir.path(), IncludeTests::Yes),
+            BTreeMap::from([
+                (PathBuf::from("src/lib.rs"), 1),
+                (PathBuf::from("src/safe.rs"), 0),
+            ])
+        );
+    }
+
+    #[rstest]
+    fn newly_unsafe_files_test() {
+        let old_unsafe_file_counts = BTreeMap::from([
+            (PathBuf::from("src/lib.rs"), 2),
+            (PathBuf::from("src/safe.rs"), 0),
+        ]);
+        let new_unsafe_file_counts = BTreeMap::from([
+            (PathBuf::from("src/ffi.rs"), 3),
+            (PathBuf::from("src/lib.rs"), 4),
+            (PathBuf::from("src/safe.rs"), 1),
+            (PathBuf::from("src/util.rs"), 0),
+        ]);
+
+        assert_eq!(
+            newly_unsafe_files(
+                &old_unsafe_file_counts,
+                &new_unsafe_file_counts
+            ),
+            vec![
+                (PathBuf::from("src/ffi.rs"), 3),
+                (PathBuf::from("src/safe.rs"), 1),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn construct_compare_lines_test() {
+        let old_report = create_report(&[
+            ("foo", Version::new(0, 1, 0), 1),
+            ("bar", Version::new(1, 0, 0), 2),
+        ]);
+        let new_report = create_report(&[
+            ("foo", Version::new(0, 2, 0), 4),
+            ("bar", Version::new(1, 0, 0), 2),
+            ("baz", Version::new(0, 3, 0), 5),
+        ]);
+        let old_unsafe_file_counts =
+            BTreeMap::from([(PathBuf::from("src/lib.rs"), 1)]);
+        let new_unsafe_file_counts = BTreeMap::from([
+            (PathBuf::from("src/ffi.rs"), 3),
+            (PathBuf::from("src/lib.rs"), 1),
+        ]);
+
+        let compare_lines = construct_compare_lines(
+            &compare_spec(),
+            &CompareSide {
+                report: &old_report,
+                unsafe_file_counts: &old_unsafe_file_counts,
+            },
+            &CompareSide {
+                report: &new_report,
+                unsafe_file_counts: &new_unsafe_file_counts,
+            },
+        );
+
+        assert_eq!(
+            compare_lines[..15],
+            [
+                "",
+                "Unsafe usage of foo: 0.1.0 -> 0.2.0",
+                "",
+                "                                   0.1.0       0.2.0   delta",
+                "functions                              0           0      +0",
+                "expressions                            1           4      +3",
+                "impls                                  0           0      +0",
+                "traits                                 0           0      +0",
+                "methods                                0           0      +0",
+                "dependencies                           1           2      +1",
+                "dependencies using unsafe              1           2      +1",
+                "unsafe used by dependencies            2           7      +5",
+                "",
+                "Newly unsafe files in 0.2.0:",
+                "    src/ffi.rs 3",
+            ]
+        );
+        assert_eq!(
+            compare_lines[15..18],
+            ["", "Unsafe usage difference: 0.1.0 -> 0.2.0", ""]
+        );
+        assert!(compare_lines[19].ends_with(" baz 0.3.0 (only in 0.2.0)"));
+        assert!(!compare_lines.iter().any(|line| line.contains(" foo ")));
+    }
+}
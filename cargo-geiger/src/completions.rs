@@ -0,0 +1,543 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The column of the help text at which the descriptions of the options start
+const DESCRIPTION_COLUMN: usize = 34;
+
+/// The command the completion scripts complete. Cargo runs it for
+/// `cargo geiger`, but the shells complete the command which is typed.
+const COMMAND_NAME: &str = "cargo-geiger";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GenerateCommand {
+    Completions(CompletionShell),
+    Manpage,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CompletionShell {
+    Bash,
+    Fish,
+    PowerShell,
+    Zsh,
+}
+
+impl FromStr for CompletionShell {
+    type Err = GenerateArgsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(CompletionShell::Bash),
+            "fish" => Ok(CompletionShell::Fish),
+            "powershell" => Ok(CompletionShell::PowerShell),
+            "zsh" => Ok(CompletionShell::Zsh),
+            _ => Err(GenerateArgsError),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GenerateArgsError;
+
+impl Error for GenerateArgsError {}
+
+impl fmt::Display for GenerateArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected `completions <bash|fish|powershell|zsh>` or `manpage`"
+        )
+    }
+}
+
+/// The `completions` or `manpage` subcommand from the positional arguments,
+/// or `None` if they are of neither
+pub fn parse_generate_args(
+    positional_args: &[String],
+) -> Result<Option<GenerateCommand>, GenerateArgsError> {
+    match positional_args {
+        [subcommand, shell] if subcommand == "completions" => shell
+            .parse()
+            .map(|shell| Some(GenerateCommand::Completions(shell))),
+        [subcommand] if subcommand == "manpage" => {
+            Ok(Some(GenerateCommand::Manpage))
+        }
+        [subcommand, ..]
+            if subcommand == "completions" || subcommand == "manpage" =>
+        {
+            Err(GenerateArgsError)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// An option of the OPTIONS section of the help text
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HelpOption {
+    /// Such as `-p` and `--package`
+    pub names: Vec<String>,
+    /// Such as `<SPEC>`, for the options taking a value
+    pub value: Option<String>,
+    /// The lines of the description, joined
+    pub description: String,
+}
+
+impl HelpOption {
+    /// The first sentence of the description
+    fn summary(&self) -> &str {
+        match self.description.find(". ") {
+            Some(end) => &self.description[..end],
+            None => self.description.trim_end_matches('.'),
+        }
+    }
+
+    fn takes_path(&self) -> bool {
+        self.value
+            .as_ref()
+            .is_some_and(|value| value.contains("PATH"))
+    }
+}
+
+/// The options of the help text, in the order they are listed
+pub fn help_options(help: &str) -> Vec<HelpOption> {
+    let mut help_options = Vec::<HelpOption>::new();
+    for line in section_lines(help, "OPTIONS") {
+        // A spec reaching the column is on a line of its own.
+        let (spec, description) = match line.get(..DESCRIPTION_COLUMN) {
+            Some(spec) if spec.ends_with(' ') => {
+                (spec, &line[DESCRIPTION_COLUMN..])
+            }
+            _ => (line, ""),
+        };
+        let description = description.trim();
+        if spec.trim().is_empty() {
+            if let Some(help_option) = help_options.last_mut() {
+                if !help_option.description.is_empty() {
+                    help_option.description.push(' ');
+                }
+                help_option.description.push_str(description);
+            }
+            continue;
+        }
+        let mut names = vec![];
+        let mut value = None;
+        for token in spec.split_whitespace() {
+            match token.trim_end_matches(',') {
+                name if name.starts_with('-') && value.is_none() => {
+                    names.push(name.to_string())
+                }
+                token => {
+                    let value = value.get_or_insert_with(String::new);
+                    if !value.is_empty() {
+                        value.push(' ');
+                    }
+                    value.push_str(token.trim_matches('"'));
+                }
+            }
+        }
+        help_options.push(HelpOption {
+            names,
+            value,
+            description: description.to_string(),
+        });
+    }
+    help_options
+}
+
+/// The subcommands of the USAGE section of the help text, such as `compare`
+pub fn help_subcommands(help: &str) -> Vec<String> {
+    section_lines(help, "USAGE")
+        .filter_map(|line| {
+            let mut words = line.split_whitespace().skip(2);
+            words
+                .next()
+                .filter(|word| !word.starts_with('['))
+                .map(String::from)
+        })
+        .collect()
+}
+
+/// The lines of a section of the help text, such as `OPTIONS`, up to the
+/// blank line ending it
+fn section_lines<'a>(
+    help: &'a str,
+    section: &'a str,
+) -> impl Iterator<Item = &'a str> {
+    help.lines()
+        .skip_while(move |line| line.strip_suffix(':') != Some(section))
+        .skip(1)
+        .take_while(|line| !line.trim().is_empty())
+}
+
+/// The completion script of the options and subcommands of the help text for
+/// the shell
+pub fn create_completion_script(shell: CompletionShell, help: &str) -> String {
+    let help_options = help_options(help);
+    let subcommands = help_subcommands(help);
+    match shell {
+        CompletionShell::Bash => bash_script(&help_options, &subcommands),
+        CompletionShell::Fish => fish_script(&help_options, &subcommands),
+        CompletionShell::PowerShell => {
+            powershell_script(&help_options, &subcommands)
+        }
+        CompletionShell::Zsh => zsh_script(&help_options, &subcommands),
+    }
+}
+
+fn bash_script(help_options: &[HelpOption], subcommands: &[String]) -> String {
+    let words = subcommands
+        .iter()
+        .cloned()
+        .chain(
+            help_options
+                .iter()
+                .flat_map(|help_option| help_option.names.clone()),
+        )
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "_cargo_geiger() {{\n    \
+        local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n    \
+        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n\
+        }}\n\
+        complete -o default -F _cargo_geiger {}\n",
+        words, COMMAND_NAME
+    )
+}
+
+fn fish_script(help_options: &[HelpOption], subcommands: &[String]) -> String {
+    let mut lines = vec![format!(
+        "complete -c {} -n '__fish_use_subcommand' -f -a '{}'",
+        COMMAND_NAME,
+        subcommands.join(" ")
+    )];
+    for help_option in help_options {
+        let mut line = format!("complete -c {}", COMMAND_NAME);
+        for name in &help_option.names {
+            match name.strip_prefix("--") {
+                Some(long) => line.push_str(&format!(" -l {}", long)),
+                None => line.push_str(&format!(" -s {}", &name[1..])),
+            }
+        }
+        if help_option.value.is_some() {
+            line.push_str(if help_option.takes_path() {
+                " -r -F"
+            } else {
+                " -r"
+            });
+        }
+        line.push_str(&format!(
+            " -d '{}'",
+            help_option
+                .summary()
+                .replace('\\', "\\\\")
+                .replace('\'', "\\'")
+        ));
+        lines.push(line);
+    }
+    lines.join("\n") + "\n"
+}
+
+fn powershell_script(
+    help_options: &[HelpOption],
+    subcommands: &[String],
+) -> String {
+    let quote = |text: &str| format!("'{}'", text.replace('\'', "''"));
+    let mut completions = subcommands
+        .iter()
+        .map(|subcommand| {
+            format!(
+                "        @({}, 'ParameterValue', {})",
+                quote(subcommand),
+                quote(subcommand)
+            )
+        })
+        .collect::<Vec<_>>();
+    for help_option in help_options {
+        for name in &help_option.names {
+            completions.push(format!(
+                "        @({}, 'ParameterName', {})",
+                quote(name),
+                quote(help_option.summary())
+            ));
+        }
+    }
+    format!(
+        "Register-ArgumentCompleter -Native -CommandName {} -ScriptBlock {{\n    \
+        param($wordToComplete, $commandAst, $cursorPosition)\n    \
+        @(\n{}\n    \
+        ) | Where-Object {{ $_[0] -like \"$wordToComplete*\" }} | ForEach-Object {{\n        \
+        [System.Management.Automation.CompletionResult]::new($_[0], $_[0], $_[1], $_[2])\n    \
+        }}\n\
+        }}\n",
+        COMMAND_NAME,
+        completions.join(",\n")
+    )
+}
+
+fn zsh_script(help_options: &[HelpOption], subcommands: &[String]) -> String {
+    let mut specs = vec![];
+    for help_option in help_options {
+        let summary = help_option
+            .summary()
+            .replace('\'', "'\\''")
+            .replace('[', "\\[")
+            .replace(']', "\\]")
+            .replace(':', "\\:");
+        let argument = match &help_option.value {
+            Some(value) => format!(
+                ":{}:{}",
+                value
+                    .chars()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+                    .collect::<String>()
+                    .to_lowercase(),
+                if help_option.takes_path() {
+                    "_files"
+                } else {
+                    " "
+                }
+            ),
+            None => String::new(),
+        };
+        for name in &help_option.names {
+            specs.push(format!("'{}[{}]{}'", name, summary, argument));
+        }
+    }
+    specs.push(format!("'1: :({})'", subcommands.join(" ")));
+    format!(
+        "#compdef {}\n\n_cargo-geiger() {{\n    _arguments -s \\\n        {}\n}}\n\n\
+        _cargo-geiger \"$@\"\n",
+        COMMAND_NAME,
+        specs.join(" \\\n        ")
+    )
+}
+
+/// The man page of the help text, in roff. The options are a tagged
+/// paragraph each, and the other sections are kept as their paragraphs.
+pub fn create_manpage(help: &str, version: &str) -> String {
+    let mut lines = vec![
+        format!(".TH CARGO-GEIGER 1 \"\" \"cargo-geiger {}\"", version),
+        String::from(".SH NAME"),
+        format!(
+            "cargo\\-geiger \\- {}",
+            escape_roff(help.lines().next().unwrap_or_default())
+        ),
+        String::from(".SH SYNOPSIS"),
+        String::from(".nf"),
+    ];
+    lines.extend(
+        section_lines(help, "USAGE").map(|line| escape_roff(line.trim())),
+    );
+    lines.push(String::from(".fi"));
+
+    lines.push(String::from(".SH OPTIONS"));
+    for help_option in help_options(help) {
+        let mut tag = help_option
+            .names
+            .iter()
+            .map(|name| format!("\\fB{}\\fR", escape_roff(name)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if let Some(value) = &help_option.value {
+            tag.push_str(&format!(" \\fI{}\\fR", escape_roff(value)));
+        }
+        lines.push(String::from(".TP"));
+        lines.push(tag);
+        lines.push(escape_roff(&help_option.description));
+    }
+
+    let other_sections = help
+        .lines()
+        .filter_map(|line| line.strip_suffix(':'))
+        .filter(|section| {
+            !section.is_empty()
+                && section.chars().all(|c| c.is_ascii_uppercase())
+                && *section != "USAGE"
+                && *section != "OPTIONS"
+        });
+    for section in other_sections {
+        lines.push(format!(".SH {}", section));
+        lines.push(escape_roff(
+            &section_lines(help, section)
+                .map(str::trim)
+                .collect::<Vec<_>>()
+                .join(" "),
+        ));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Backslashes and hyphens are escaped, and a line can't start with a
+/// control character
+fn escape_roff(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    match escaped.starts_with('.') || escaped.starts_with('\'') {
+        true => format!("\\&{}", escaped),
+        false => escaped,
+    }
+}
+
+#[cfg(test)]
+mod completions_tests {
+    use super::*;
+
+    use rstest::*;
+
+    const HELP: &str = "Detects usage of unsafe Rust.
+
+USAGE:
+    cargo geiger [OPTIONS]
+    cargo geiger inspect <CRATE> [OPTIONS]
+
+OPTIONS:
+    -p, --package <SPEC>          Package to be used as the root of the tree.
+        --include-tests           Count unsafe usage in tests. Also in
+                                  benches.
+        --max-total-unsafe <LIMITS>
+                                  Fail if the crates use more unsafe code.
+        --output-file <PATH>      Write the report to the file.
+
+INSPECT:
+    `inspect` scans only the files
+    of the crate.
+";
+
+    #[rstest(
+        input_positional_args,
+        expected_generate_command,
+        case(vec![], Ok(None)),
+        case(vec!["inspect", "tokio"], Ok(None)),
+        case(
+            vec!["completions", "zsh"],
+            Ok(Some(GenerateCommand::Completions(CompletionShell::Zsh)))
+        ),
+        case(vec!["manpage"], Ok(Some(GenerateCommand::Manpage))),
+        case(vec!["completions"], Err(GenerateArgsError)),
+        case(vec!["completions", "tcsh"], Err(GenerateArgsError)),
+        case(vec!["manpage", "extra"], Err(GenerateArgsError))
+    )]
+    fn parse_generate_args_test(
+        input_positional_args: Vec<&str>,
+        expected_generate_command: Result<
+            Option<GenerateCommand>,
+            GenerateArgsError,
+        >,
+    ) {
+        let positional_args = input_positional_args
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            parse_generate_args(&positional_args),
+            expected_generate_command
+        );
+    }
+
+    #[rstest]
+    fn help_options_test() {
+        let help_option =
+            |names: &[&str], value: Option<&str>, description| HelpOption {
+                names: names.iter().map(|name| name.to_string()).collect(),
+                value: value.map(String::from),
+                description: String::from(description),
+            };
+
+        assert_eq!(
+            help_options(HELP),
+            vec![
+                help_option(
+                    &["-p", "--package"],
+                    Some("<SPEC>"),
+                    "Package to be used as the root of the tree."
+                ),
+                help_option(
+                    &["--include-tests"],
+                    None,
+                    "Count unsafe usage in tests. Also in benches."
+                ),
+                help_option(
+                    &["--max-total-unsafe"],
+                    Some("<LIMITS>"),
+                    "Fail if the crates use more unsafe code."
+                ),
+                help_option(
+                    &["--output-file"],
+                    Some("<PATH>"),
+                    "Write the report to the file."
+                ),
+            ]
+        );
+        assert_eq!(help_subcommands(HELP), vec!["inspect"]);
+    }
+
+    #[rstest]
+    fn create_completion_script_test() {
+        assert_eq!(
+            create_completion_script(CompletionShell::Bash, HELP),
+            "_cargo_geiger() {\n    \
+            local cur=\"${COMP_WORDS[COMP_CWORD]}\"\n    \
+            COMPREPLY=($(compgen -W \"inspect -p --package --include-tests \
+            --max-total-unsafe --output-file\" -- \"$cur\"))\n\
+            }\n\
+            complete -o default -F _cargo_geiger cargo-geiger\n"
+        );
+        assert_eq!(
+            create_completion_script(CompletionShell::Fish, HELP),
+            "complete -c cargo-geiger -n '__fish_use_subcommand' -f -a 'inspect'\n\
+            complete -c cargo-geiger -s p -l package -r \
+            -d 'Package to be used as the root of the tree'\n\
+            complete -c cargo-geiger -l include-tests \
+            -d 'Count unsafe usage in tests'\n\
+            complete -c cargo-geiger -l max-total-unsafe -r \
+            -d 'Fail if the crates use more unsafe code'\n\
+            complete -c cargo-geiger -l output-file -r -F \
+            -d 'Write the report to the file'\n"
+        );
+        assert!(
+            create_completion_script(CompletionShell::Zsh, HELP).contains(
+                "'--output-file[Write the report to the file]:path:_files'"
+            )
+        );
+        assert!(create_completion_script(CompletionShell::PowerShell, HELP)
+            .contains("@('--include-tests', 'ParameterName', 'Count unsafe usage in tests')"));
+    }
+
+    #[rstest]
+    fn create_manpage_test() {
+        let manpage = create_manpage(HELP, "0.11.7");
+
+        assert!(manpage.starts_with(
+            ".TH CARGO-GEIGER 1 \"\" \"cargo-geiger 0.11.7\"\n\
+            .SH NAME\n\
+            cargo\\-geiger \\- Detects usage of unsafe Rust.\n\
+            .SH SYNOPSIS\n\
+            .nf\n\
+            cargo geiger [OPTIONS]\n\
+            cargo geiger inspect <CRATE> [OPTIONS]\n\
+            .fi\n\
+            .SH OPTIONS\n\
+            .TP\n\
+            \\fB\\-p\\fR, \\fB\\-\\-package\\fR \\fI<SPEC>\\fR\n\
+            Package to be used as the root of the tree.\n"
+        ));
+        assert!(manpage.ends_with(
+            ".SH INSPECT\n\
+            `inspect` scans only the files of the crate.\n"
+        ));
+    }
+
+    #[rstest(
+        input_text,
+        expected_roff,
+        case("--all", "\\-\\-all"),
+        case("a\\b", "a\\eb"),
+        case(".foo", "\\&.foo"),
+        case("'foo'", "\\&'foo'")
+    )]
+    fn escape_roff_test(input_text: &str, expected_roff: &str) {
+        assert_eq!(escape_roff(input_text), expected_roff);
+    }
+}
@@ -0,0 +1,465 @@
+use crate::args::{Args, HELP};
+
+use pico_args::Arguments;
+use std::error::Error;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::easy::value::Table;
+use toml_edit::easy::Value;
+
+/// The configuration file looked for next to the manifest
+pub const CONFIG_FILE_NAME: &str = "geiger.toml";
+
+#[derive(Debug)]
+pub struct ConfigFileError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl Error for ConfigFileError {}
+
+impl fmt::Display for ConfigFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to read configuration from {}: {}",
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+/// The flags set in the `geiger.toml` next to the manifest, or else in the
+/// `[package.metadata.geiger]` table of the manifest, as command line
+/// arguments. The flags given on the command line are left out, so that the
+/// command line overrides the configuration, replacing the values of a flag
+/// given multiple times rather than adding to them.
+///
+/// The manifest is the one of `--manifest-path`, or else the `Cargo.toml` of
+/// the current directory.
+pub fn read_config_file_arguments(
+    raw_arguments: &[OsString],
+) -> Result<Vec<OsString>, ConfigFileError> {
+    match read_config_table(raw_arguments)? {
+        Some((path, table)) => table_to_arguments(&table, raw_arguments)
+            .map_err(|message| ConfigFileError { path, message }),
+        None => Ok(vec![]),
+    }
+}
+
+/// Parses the command line arguments followed by the ones of the
+/// configuration file. A value of the configuration file that fails to parse,
+/// such as `output-format = "Foo"`, is reported with the path of the file and
+/// its key.
+pub fn parse_args_with_config_file(
+    raw_arguments: Vec<OsString>,
+) -> Result<Args, Box<dyn Error>> {
+    let parse = |arguments: Vec<OsString>| {
+        Args::parse_args(Arguments::from_vec(arguments))
+    };
+    let (path, table) = match read_config_table(&raw_arguments)? {
+        Some(config) => config,
+        None => return parse(raw_arguments),
+    };
+    let config_file_error = |message: String| ConfigFileError {
+        path: path.clone(),
+        message,
+    };
+
+    let config_file_arguments = table_to_arguments(&table, &raw_arguments)
+        .map_err(config_file_error)?;
+    let error =
+        match parse([raw_arguments.clone(), config_file_arguments].concat()) {
+            Ok(args) => return Ok(args),
+            Err(error) => error,
+        };
+
+    // Blame the command line if it fails to parse on its own, or else the
+    // first key of the configuration file that fails to parse with it.
+    parse(raw_arguments.clone())?;
+    for (key, value) in &table {
+        let mut key_table = Table::new();
+        key_table.insert(key.clone(), value.clone());
+        let key_arguments = table_to_arguments(&key_table, &raw_arguments)
+            .map_err(config_file_error)?;
+        if let Err(key_error) =
+            parse([raw_arguments.clone(), key_arguments].concat())
+        {
+            return Err(Box::new(config_file_error(format!(
+                "the value of `{}` is invalid: {}",
+                key, key_error
+            ))));
+        }
+    }
+    Err(Box::new(config_file_error(error.to_string())))
+}
+
+/// The table of flags of the `geiger.toml`, or else of the
+/// `[package.metadata.geiger]` table of the manifest, with the path of the
+/// file it was read from
+fn read_config_table(
+    raw_arguments: &[OsString],
+) -> Result<Option<(PathBuf, Table)>, ConfigFileError> {
+    let manifest_path = manifest_path_argument(raw_arguments)
+        .unwrap_or_else(|| PathBuf::from("Cargo.toml"));
+    let manifest_directory = match manifest_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let config_file_path = manifest_directory.join(CONFIG_FILE_NAME);
+    if config_file_path.is_file() {
+        let table = read_table(&config_file_path)?;
+        return Ok(Some((config_file_path, table)));
+    }
+
+    if manifest_path.is_file() {
+        let manifest = read_table(&manifest_path)?;
+        let geiger_table = manifest
+            .get("package")
+            .and_then(|package| package.get("metadata"))
+            .and_then(|metadata| metadata.get("geiger"));
+        return match geiger_table {
+            Some(Value::Table(geiger_table)) => {
+                Ok(Some((manifest_path, geiger_table.clone())))
+            }
+            Some(_) => Err(ConfigFileError {
+                path: manifest_path,
+                message: String::from(
+                    "`package.metadata.geiger` is not a table",
+                ),
+            }),
+            None => Ok(None),
+        };
+    }
+
+    Ok(None)
+}
+
+/// The value of `--manifest-path`, given as `--manifest-path <PATH>` or
+/// `--manifest-path=<PATH>`
+fn manifest_path_argument(raw_arguments: &[OsString]) -> Option<PathBuf> {
+    let mut raw_arguments = raw_arguments.iter();
+    while let Some(raw_argument) = raw_arguments.next() {
+        if raw_argument == OsStr::new("--manifest-path") {
+            return raw_arguments.next().map(PathBuf::from);
+        }
+        if let Some(manifest_path) = raw_argument
+            .to_str()
+            .and_then(|argument| argument.strip_prefix("--manifest-path="))
+        {
+            return Some(PathBuf::from(manifest_path));
+        }
+    }
+    None
+}
+
+fn read_table(path: &Path) -> Result<Table, ConfigFileError> {
+    let config_file_error = |message: String| ConfigFileError {
+        path: path.to_path_buf(),
+        message,
+    };
+    let content = fs::read_to_string(path)
+        .map_err(|e| config_file_error(e.to_string()))?;
+    toml_edit::easy::from_str::<Table>(&content)
+        .map_err(|e| config_file_error(e.to_string()))
+}
+
+/// Each key is the name of a flag without its leading `--`, such as
+/// `output-format = "Json"`. A `true` boolean is a flag without a value and a
+/// `false` one is left out, and each element of an array is given as a
+/// separate flag, such as the `--feature-set` of `feature-set = ["a", "b"]`.
+/// The keys of the flags already in `raw_arguments` are skipped.
+fn table_to_arguments(
+    table: &Table,
+    raw_arguments: &[OsString],
+) -> Result<Vec<OsString>, String> {
+    let mut arguments = vec![];
+    for (key, value) in table {
+        let flag = format!("--{}", key);
+        if !is_known_flag(&flag) {
+            return Err(format!("unknown flag `{}`", flag));
+        }
+        if is_flag_given(&flag, raw_arguments) {
+            continue;
+        }
+        let values = match value {
+            Value::Array(array) => array.iter().collect::<Vec<_>>(),
+            value => vec![value],
+        };
+        for value in values {
+            match value {
+                Value::Boolean(true) => arguments.push(OsString::from(&flag)),
+                Value::Boolean(false) => {}
+                Value::String(s) => {
+                    arguments.push(OsString::from(&flag));
+                    arguments.push(OsString::from(s));
+                }
+                Value::Integer(i) => {
+                    arguments.push(OsString::from(&flag));
+                    arguments.push(OsString::from(i.to_string()));
+                }
+                Value::Float(f) => {
+                    arguments.push(OsString::from(&flag));
+                    arguments.push(OsString::from(f.to_string()));
+                }
+                _ => {
+                    return Err(format!(
+                        "the value of `{}` is not a string, number or boolean",
+                        key
+                    ))
+                }
+            }
+        }
+    }
+    Ok(arguments)
+}
+
+/// Whether the flag is listed in the help, such as `--output-format`
+fn is_known_flag(flag: &str) -> bool {
+    HELP.split_whitespace()
+        .any(|word| word.trim_end_matches(',') == flag)
+}
+
+/// Whether the flag is given in the arguments, as `--flag`, `--flag=<VALUE>`
+/// or by its short name, such as `-p` for `--package`
+fn is_flag_given(flag: &str, raw_arguments: &[OsString]) -> bool {
+    let short_flag = HELP.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some(short_flag), Some(long_flag))
+                if short_flag.len() == 3 && long_flag == flag =>
+            {
+                short_flag.strip_suffix(',')
+            }
+            _ => None,
+        }
+    });
+    raw_arguments
+        .iter()
+        .filter_map(|raw_argument| raw_argument.to_str())
+        .any(|argument| {
+            argument == flag
+                || argument
+                    .strip_prefix(flag)
+                    .is_some_and(|rest| rest.starts_with('='))
+                || Some(argument) == short_flag
+        })
+}
+
+#[cfg(test)]
+mod config_file_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest(
+        input_raw_arguments,
+        expected_manifest_path,
+        case(vec!["geiger", "--manifest-path", "foo/Cargo.toml"], Some("foo/Cargo.toml")),
+        case(vec!["geiger", "--manifest-path=foo/Cargo.toml"], Some("foo/Cargo.toml")),
+        case(vec!["geiger", "--all"], None)
+    )]
+    fn manifest_path_argument_test(
+        input_raw_arguments: Vec<&str>,
+        expected_manifest_path: Option<&str>,
+    ) {
+        let raw_arguments = input_raw_arguments
+            .into_iter()
+            .map(OsString::from)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            manifest_path_argument(&raw_arguments),
+            expected_manifest_path.map(PathBuf::from)
+        );
+    }
+
+    #[rstest]
+    fn table_to_arguments_test() {
+        let table = toml_edit::easy::from_str::<Table>(
+            "output-format = \"Json\"\n\
+            include-tests = true\n\
+            all = false\n\
+            max-score = 10\n\
+            feature-set = [\"a\", \"b c\"]\n",
+        )
+        .unwrap();
+
+        let mut arguments = table_to_arguments(&table, &[]).unwrap();
+        arguments.sort();
+
+        assert_eq!(
+            arguments,
+            [
+                "--feature-set",
+                "--feature-set",
+                "--include-tests",
+                "--max-score",
+                "--output-format",
+                "10",
+                "Json",
+                "a",
+                "b c",
+            ]
+            .iter()
+            .map(OsString::from)
+            .collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest(
+        input_config,
+        case("output-fromat = \"Json\"\n"),
+        case("[output-format]\nname = \"Json\"\n")
+    )]
+    fn table_to_arguments_test_invalid(input_config: &str) {
+        let table = toml_edit::easy::from_str::<Table>(input_config).unwrap();
+
+        assert!(table_to_arguments(&table, &[]).is_err());
+    }
+
+    #[rstest(
+        input_raw_arguments,
+        expected_arguments,
+        case(vec![], vec!["--include-tests", "--output-format", "Json"]),
+        case(vec!["--output-format", "Ascii"], vec!["--include-tests"]),
+        case(vec!["--output-format=Ascii"], vec!["--include-tests"]),
+        case(
+            vec!["--include-tests-only"],
+            vec!["--include-tests", "--output-format", "Json"]
+        )
+    )]
+    fn table_to_arguments_test_given_on_command_line(
+        input_raw_arguments: Vec<&str>,
+        expected_arguments: Vec<&str>,
+    ) {
+        let table = toml_edit::easy::from_str::<Table>(
+            "include-tests = true\noutput-format = \"Json\"\n",
+        )
+        .unwrap();
+        let raw_arguments = input_raw_arguments
+            .into_iter()
+            .map(OsString::from)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            table_to_arguments(&table, &raw_arguments).unwrap(),
+            expected_arguments
+                .into_iter()
+                .map(OsString::from)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest(
+        input_flag,
+        input_raw_arguments,
+        expected_is_given,
+        case("--package", vec!["-p", "foo"], true),
+        case("--package", vec!["--package-spec", "foo"], false),
+        case("--verbose", vec!["-v"], true),
+        case("--all", vec!["--all-features"], false)
+    )]
+    fn is_flag_given_test(
+        input_flag: &str,
+        input_raw_arguments: Vec<&str>,
+        expected_is_given: bool,
+    ) {
+        let raw_arguments = input_raw_arguments
+            .into_iter()
+            .map(OsString::from)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            is_flag_given(input_flag, &raw_arguments),
+            expected_is_given
+        );
+    }
+
+    #[rstest]
+    fn read_config_file_arguments_test() {
+        let package_directory = tempfile::tempdir().unwrap();
+        let manifest_path = package_directory.path().join("Cargo.toml");
+        fs::write(
+            &manifest_path,
+            "[package]\nname = \"foo\"\n\n\
+            [package.metadata.geiger]\nall = true\n",
+        )
+        .unwrap();
+        let raw_arguments = vec![
+            OsString::from("--manifest-path"),
+            manifest_path.into_os_string(),
+        ];
+
+        assert_eq!(
+            read_config_file_arguments(&raw_arguments).unwrap(),
+            vec![OsString::from("--all")]
+        );
+
+        // The configuration file takes precedence over the manifest.
+        fs::write(
+            package_directory.path().join(CONFIG_FILE_NAME),
+            "include-tests = true\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_config_file_arguments(&raw_arguments).unwrap(),
+            vec![OsString::from("--include-tests")]
+        );
+    }
+
+    #[rstest(
+        input_raw_arguments,
+        input_config,
+        expected_error,
+        case(vec![], "output-format = \"Json\"\n", None),
+        case(
+            vec![],
+            "all = true\noutput-format = \"Foo\"\n",
+            Some("the value of `output-format` is invalid")
+        ),
+        case(vec!["--output-format", "Json"], "output-format = \"Foo\"\n", None),
+        case(vec!["--max-score", "Foo"], "all = true\n", Some("'Foo'"))
+    )]
+    fn parse_args_with_config_file_test(
+        input_raw_arguments: Vec<&str>,
+        input_config: &str,
+        expected_error: Option<&str>,
+    ) {
+        let package_directory = tempfile::tempdir().unwrap();
+        let config_file_path = package_directory.path().join(CONFIG_FILE_NAME);
+        fs::write(&config_file_path, input_config).unwrap();
+        let raw_arguments = [
+            vec![
+                OsString::from("geiger"),
+                OsString::from("--manifest-path"),
+                package_directory.path().join("Cargo.toml").into_os_string(),
+            ],
+            input_raw_arguments
+                .into_iter()
+                .map(OsString::from)
+                .collect(),
+        ]
+        .concat();
+
+        let result = parse_args_with_config_file(raw_arguments);
+
+        match expected_error {
+            None => assert!(result.is_ok()),
+            Some(expected_error) => {
+                let error = result.unwrap_err().to_string();
+                assert!(error.contains(expected_error), "{}", error);
+                let names_config_file =
+                    error.contains(&config_file_path.display().to_string());
+                assert_eq!(
+                    names_config_file,
+                    expected_error.starts_with("the value")
+                );
+            }
+        }
+    }
+}
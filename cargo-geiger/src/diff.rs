@@ -0,0 +1,855 @@
+use crate::args::Args;
+use crate::format::print_config::{OutputFormat, PrintConfig};
+use crate::format::table::{UNSAFE_CATEGORY_NAMES, UNSAFE_COUNTERS_HEADER};
+
+use cargo::CliError;
+use cargo_geiger_serde::{CounterBlock, SafetyReport};
+use cargo_metadata::semver::Version;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct UnsafeRegressionError {
+    pub crate_count: usize,
+}
+
+impl Error for UnsafeRegressionError {}
+
+impl fmt::Display for UnsafeRegressionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Found {} crates using more unsafe code than in the baseline",
+            self.crate_count
+        )
+    }
+}
+
+/// The change in used unsafe code for a single crate between two reports.
+/// A missing side means the crate is not present in that report.
+#[derive(Debug, Eq, PartialEq)]
+pub struct CrateDiff {
+    pub name: String,
+    pub version: Version,
+    pub before: Option<CounterBlock>,
+    pub after: Option<CounterBlock>,
+}
+
+impl CrateDiff {
+    fn has_changed(&self) -> bool {
+        self.before != self.after
+    }
+
+    /// Whether the crate uses more unsafe code than before, which is the case
+    /// for an added crate using any, but never for a removed crate
+    pub fn is_regression(&self) -> bool {
+        self.after.is_some()
+            && total_unsafe(&self.after) > total_unsafe(&self.before)
+    }
+}
+
+/// A crate of the Json diff, with the used unsafe code added up over the
+/// categories in `before`, `after` and `delta`
+#[derive(Serialize)]
+struct JsonCrateDiff<'a> {
+    name: &'a str,
+    version: &'a Version,
+    change: &'static str,
+    before: Option<u64>,
+    after: Option<u64>,
+    delta: i64,
+    categories: BTreeMap<&'static str, i64>,
+    regression: bool,
+}
+
+/// The Json diff output
+#[derive(Serialize)]
+struct JsonDiff<'a> {
+    baseline: &'a str,
+    crates: Vec<JsonCrateDiff<'a>>,
+    regression_count: usize,
+}
+
+/// Match the crates of two reports by name and version, and list the ones
+/// whose used unsafe code differs, sorted by name and version
+pub fn diff_reports(
+    before: &SafetyReport,
+    after: &SafetyReport,
+) -> Vec<CrateDiff> {
+    let mut crate_diffs = BTreeMap::<(String, Version), CrateDiff>::new();
+
+    for (report, is_before) in [(before, true), (after, false)] {
+        for entry in report.packages.values() {
+            let key = (
+                entry.package.id.name.clone(),
+                entry.package.id.version.clone(),
+            );
+            let crate_diff =
+                crate_diffs.entry(key).or_insert_with(|| CrateDiff {
+                    name: entry.package.id.name.clone(),
+                    version: entry.package.id.version.clone(),
+                    before: None,
+                    after: None,
+                });
+            let used = Some(entry.unsafety.used.clone());
+            if is_before {
+                crate_diff.before = used;
+            } else {
+                crate_diff.after = used;
+            }
+        }
+    }
+
+    crate_diffs
+        .into_values()
+        .filter(CrateDiff::has_changed)
+        .collect()
+}
+
+/// Render the differences as a table of per-category deltas, headed by the
+/// labels of the two compared scans
+pub fn construct_diff_lines(
+    before_label: &str,
+    after_label: &str,
+    crate_diffs: &[CrateDiff],
+) -> Vec<String> {
+    let mut diff_lines = vec![
+        String::new(),
+        format!(
+            "Unsafe usage difference: {} -> {}",
+            before_label, after_label
+        ),
+        String::new(),
+    ];
+
+    if crate_diffs.is_empty() {
+        diff_lines.push(String::from("No differences found."));
+        diff_lines.push(String::new());
+        return diff_lines;
+    }
+
+    diff_lines.push(UNSAFE_COUNTERS_HEADER.join(" "));
+
+    let mut total_delta = [0i64; 5];
+    for crate_diff in crate_diffs {
+        let delta = counter_block_delta(&crate_diff.before, &crate_diff.after);
+        for (total, value) in total_delta.iter_mut().zip(delta) {
+            *total += value;
+        }
+        let annotation = match (&crate_diff.before, &crate_diff.after) {
+            (None, Some(_)) => format!(" (only in {})", after_label),
+            (Some(_), None) => format!(" (only in {})", before_label),
+            _ => String::new(),
+        };
+        diff_lines.push(format!(
+            "{} {} {}{}",
+            format_delta_row(&delta),
+            crate_diff.name,
+            crate_diff.version,
+            annotation
+        ));
+    }
+
+    diff_lines.push(String::new());
+    diff_lines.push(format!("{} Total", format_delta_row(&total_delta)));
+    diff_lines.push(String::new());
+
+    diff_lines
+}
+
+/// Render the differences compactly, with a line per crate naming each
+/// category whose count changed, so that changes which cancel out in the
+/// total stay visible. Crates in which only safe code changed are left out.
+pub fn construct_category_diff_lines(
+    before_label: &str,
+    after_label: &str,
+    crate_diffs: &[CrateDiff],
+) -> Vec<String> {
+    let mut diff_lines = vec![
+        String::new(),
+        format!(
+            "Unsafe usage difference by category: {} -> {}",
+            before_label, after_label
+        ),
+        String::new(),
+    ];
+
+    let mut has_differences = false;
+    for crate_diff in crate_diffs {
+        let delta = counter_block_delta(&crate_diff.before, &crate_diff.after);
+        let category_deltas = UNSAFE_CATEGORY_NAMES
+            .iter()
+            .zip(delta)
+            .filter(|(_, value)| *value != 0)
+            .map(|(category, value)| {
+                format!("{} {}", category, format_delta(value))
+            })
+            .collect::<Vec<String>>();
+        if category_deltas.is_empty() {
+            continue;
+        }
+        has_differences = true;
+
+        let annotation = match (&crate_diff.before, &crate_diff.after) {
+            (None, Some(_)) => format!(", only in {}", after_label),
+            (Some(_), None) => format!(", only in {}", before_label),
+            _ => String::new(),
+        };
+        diff_lines.push(format!(
+            "{} {}: {} (net {}{})",
+            crate_diff.name,
+            crate_diff.version,
+            category_deltas.join(", "),
+            format_delta(delta.iter().sum()),
+            annotation
+        ));
+    }
+
+    if !has_differences {
+        diff_lines.push(String::from("No differences found."));
+    }
+    diff_lines.push(String::new());
+
+    diff_lines
+}
+
+/// Render the differences as a line per crate which was added, removed, or
+/// whose used unsafe code changed, followed by the number of regressions. The
+/// regressions are highlighted when `highlight` is set.
+pub fn construct_regression_diff_lines(
+    baseline_label: &str,
+    crate_diffs: &[CrateDiff],
+    highlight: bool,
+) -> Vec<String> {
+    let mut diff_lines = vec![
+        String::new(),
+        format!("Unsafe usage compared to the baseline: {}", baseline_label),
+        String::new(),
+    ];
+
+    for crate_diff in crate_diffs {
+        let (before, after) = (
+            total_unsafe(&crate_diff.before),
+            total_unsafe(&crate_diff.after),
+        );
+        let change = match change_kind(crate_diff) {
+            Some(change_kind) => {
+                format!("{: <7} {}", change_kind, crate_diff_name(crate_diff))
+            }
+            None => continue,
+        };
+        let counts = match (&crate_diff.before, &crate_diff.after) {
+            (Some(_), Some(_)) => format!(
+                "{} -> {} ({})",
+                before,
+                after,
+                format_delta(after as i64 - before as i64)
+            ),
+            (None, _) => after.to_string(),
+            (_, None) => before.to_string(),
+        };
+        let diff_line = format!("{}: {}", change, counts);
+        diff_lines.push(match crate_diff.is_regression() {
+            true if highlight => format!("{} (regression)", diff_line)
+                .red()
+                .bold()
+                .to_string(),
+            true => format!("{} (regression)", diff_line),
+            false => diff_line,
+        });
+    }
+
+    let regression_count = count_regressions(crate_diffs);
+    diff_lines.push(String::new());
+    diff_lines.push(match regression_count {
+        0 => String::from("No regressions found."),
+        _ => format!(
+            "Found {} crates using more unsafe code than in the baseline.",
+            regression_count
+        ),
+    });
+    diff_lines.push(String::new());
+
+    diff_lines
+}
+
+/// Render the differences of `construct_regression_diff_lines` as a Json
+/// document, with the change of each category for each crate
+pub fn construct_json_diff_line(
+    baseline_label: &str,
+    crate_diffs: &[CrateDiff],
+) -> String {
+    let crates = crate_diffs
+        .iter()
+        .filter_map(|crate_diff| {
+            let change = change_kind(crate_diff)?;
+            let delta =
+                counter_block_delta(&crate_diff.before, &crate_diff.after);
+            Some(JsonCrateDiff {
+                name: &crate_diff.name,
+                version: &crate_diff.version,
+                change,
+                before: crate_diff
+                    .before
+                    .as_ref()
+                    .map(|_| total_unsafe(&crate_diff.before)),
+                after: crate_diff
+                    .after
+                    .as_ref()
+                    .map(|_| total_unsafe(&crate_diff.after)),
+                delta: delta.iter().sum(),
+                categories: UNSAFE_CATEGORY_NAMES
+                    .iter()
+                    .copied()
+                    .zip(delta)
+                    .collect(),
+                regression: crate_diff.is_regression(),
+            })
+        })
+        .collect();
+
+    serde_json::to_string(&JsonDiff {
+        baseline: baseline_label,
+        crates,
+        regression_count: count_regressions(crate_diffs),
+    })
+    .unwrap()
+}
+
+/// Render the differences of `construct_regression_diff_lines` as a Markdown
+/// table, to be pasted into a pull request
+pub fn construct_markdown_diff_lines(
+    baseline_label: &str,
+    crate_diffs: &[CrateDiff],
+) -> Vec<String> {
+    let mut diff_lines = vec![
+        format!(
+            "### Unsafe usage compared to the baseline: `{}`",
+            baseline_label
+        ),
+        String::new(),
+    ];
+
+    let mut table_lines = vec![];
+    for crate_diff in crate_diffs {
+        let change = match change_kind(crate_diff) {
+            Some(change) => change,
+            None => continue,
+        };
+        let format_total = |counter_block: &Option<CounterBlock>| {
+            counter_block
+                .as_ref()
+                .map_or_else(String::new, |counter_block| {
+                    counter_block.total_unsafe().to_string()
+                })
+        };
+        let delta = total_unsafe(&crate_diff.after) as i64
+            - total_unsafe(&crate_diff.before) as i64;
+        table_lines.push(format!(
+            "| {} | `{}` | {} | {} | {}{} |",
+            change,
+            crate_diff_name(crate_diff),
+            format_total(&crate_diff.before),
+            format_total(&crate_diff.after),
+            format_delta(delta),
+            if crate_diff.is_regression() {
+                " :warning:"
+            } else {
+                ""
+            }
+        ));
+    }
+
+    if table_lines.is_empty() {
+        diff_lines.push(String::from("No differences found."));
+        return diff_lines;
+    }
+
+    diff_lines
+        .push(String::from("| Change | Crate | Before | After | Delta |"));
+    diff_lines.push(String::from("| --- | --- | --: | --: | --: |"));
+    diff_lines.extend(table_lines);
+    diff_lines.push(String::new());
+    diff_lines.push(match count_regressions(crate_diffs) {
+        0 => String::from("No regressions found."),
+        regression_count => format!(
+            "Found {} crates using more unsafe code than in the baseline.",
+            regression_count
+        ),
+    });
+
+    diff_lines
+}
+
+/// Render the differences to a baseline for `--diff`, in the Json or
+/// `GitHubMarkdown` output format if selected, or else as plain lines
+pub fn construct_baseline_diff_lines(
+    args: &Args,
+    baseline_label: &str,
+    crate_diffs: &[CrateDiff],
+) -> Result<Vec<String>, CliError> {
+    Ok(match args.output_format {
+        OutputFormat::Json => {
+            vec![construct_json_diff_line(baseline_label, crate_diffs)]
+        }
+        OutputFormat::GitHubMarkdown => {
+            construct_markdown_diff_lines(baseline_label, crate_diffs)
+        }
+        _ => construct_regression_diff_lines(
+            baseline_label,
+            crate_diffs,
+            highlight_regressions(args)?,
+        ),
+    })
+}
+
+/// The regressions are highlighted when the report would be colored, as
+/// decided by `--color`, `NO_COLOR` and `--output-file`
+pub fn highlight_regressions(args: &Args) -> Result<bool, CliError> {
+    Ok(PrintConfig::new(args)?.color.is_enabled())
+}
+
+pub fn count_regressions(crate_diffs: &[CrateDiff]) -> usize {
+    crate_diffs
+        .iter()
+        .filter(|crate_diff| crate_diff.is_regression())
+        .count()
+}
+
+/// Whether the crate was added, removed or changed, or `None` if its used
+/// unsafe code adds up to the same total in both reports
+fn change_kind(crate_diff: &CrateDiff) -> Option<&'static str> {
+    match (&crate_diff.before, &crate_diff.after) {
+        (None, Some(_)) => Some("added"),
+        (Some(_), None) => Some("removed"),
+        _ if total_unsafe(&crate_diff.before)
+            == total_unsafe(&crate_diff.after) =>
+        {
+            None
+        }
+        _ => Some("changed"),
+    }
+}
+
+fn crate_diff_name(crate_diff: &CrateDiff) -> String {
+    format!("{} {}", crate_diff.name, crate_diff.version)
+}
+
+/// The unsafe usage of a crate on one side of the diff, none where it is
+/// missing
+fn total_unsafe(counter_block: &Option<CounterBlock>) -> u64 {
+    counter_block.as_ref().map_or(0, CounterBlock::total_unsafe)
+}
+
+fn counter_block_delta(
+    before: &Option<CounterBlock>,
+    after: &Option<CounterBlock>,
+) -> [i64; 5] {
+    let unsafe_columns =
+        |counter_block: &Option<CounterBlock>| match counter_block {
+            Some(c) => [
+                c.functions.unsafe_,
+                c.exprs.unsafe_,
+                c.item_impls.unsafe_,
+                c.item_traits.unsafe_,
+                c.methods.unsafe_,
+            ],
+            None => [0; 5],
+        };
+    let before_columns = unsafe_columns(before);
+    let after_columns = unsafe_columns(after);
+
+    let mut delta = [0i64; 5];
+    for (index, value) in delta.iter_mut().enumerate() {
+        *value = after_columns[index] as i64 - before_columns[index] as i64;
+    }
+    delta
+}
+
+fn format_delta(value: i64) -> String {
+    if value > 0 {
+        format!("+{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_delta_row(delta: &[i64; 5]) -> String {
+    delta
+        .iter()
+        .zip(UNSAFE_COUNTERS_HEADER.iter())
+        .map(|(value, header)| {
+            format!("{: <width$}", format_delta(*value), width = header.len())
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod diff_tests {
+    use super::*;
+
+    use crate::lib_tests::{create_registry_package_id, create_safety_report};
+
+    use cargo_geiger_serde::{Count, PackageInfo, UnsafeInfo};
+    use rstest::*;
+
+    #[rstest]
+    fn diff_reports_test() {
+        let before = create_report(&[
+            ("bar", "1.0.0", 2),
+            ("foo", "0.1.0", 1),
+            ("removed", "0.1.0", 1),
+        ]);
+        let after = create_report(&[
+            ("added", "0.2.0", 3),
+            ("bar", "1.0.0", 2),
+            ("foo", "0.1.0", 4),
+        ]);
+
+        let crate_diffs = diff_reports(&before, &after);
+
+        assert_eq!(
+            crate_diffs
+                .iter()
+                .map(|d| (
+                    d.name.as_str(),
+                    d.before.is_some(),
+                    d.after.is_some()
+                ))
+                .collect::<Vec<(&str, bool, bool)>>(),
+            vec![
+                ("added", false, true),
+                ("foo", true, true),
+                ("removed", true, false)
+            ]
+        );
+    }
+
+    #[rstest]
+    fn construct_diff_lines_test() {
+        let before = create_report(&[("foo", "0.1.0", 1)]);
+        let after =
+            create_report(&[("added", "0.2.0", 3), ("foo", "0.1.0", 4)]);
+
+        let diff_lines = construct_diff_lines(
+            "default features",
+            "all features",
+            &diff_reports(&before, &after),
+        );
+
+        assert_eq!(
+            diff_lines,
+            vec![
+                String::new(),
+                String::from(
+                    "Unsafe usage difference: default features -> all features"
+                ),
+                String::new(),
+                String::from(
+                    "Functions  Expressions  Impls  Traits  Methods  Dependency"
+                ),
+                String::from(
+                    "0          +3           0      0       0        added 0.2.0 (only in all features)"
+                ),
+                String::from(
+                    "0          +3           0      0       0        foo 0.1.0"
+                ),
+                String::new(),
+                String::from(
+                    "0          +6           0      0       0        Total"
+                ),
+                String::new(),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn construct_diff_lines_test_no_differences() {
+        let report = create_report(&[("foo", "0.1.0", 1)]);
+
+        let diff_lines = construct_diff_lines(
+            "before",
+            "after",
+            &diff_reports(&report, &report),
+        );
+
+        assert_eq!(
+            diff_lines,
+            vec![
+                String::new(),
+                String::from("Unsafe usage difference: before -> after"),
+                String::new(),
+                String::from("No differences found."),
+                String::new(),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn construct_category_diff_lines_test() {
+        let before = create_report_with_counts(&[
+            ("foo", "0.1.0", [0, 3, 1, 0, 0]),
+            ("removed", "0.1.0", [1, 0, 0, 0, 0]),
+            ("same", "0.1.0", [0, 1, 0, 0, 0]),
+        ]);
+        let after = create_report_with_counts(&[
+            ("added", "0.2.0", [0, 2, 0, 0, 1]),
+            ("foo", "0.1.0", [0, 1, 3, 0, 0]),
+            ("same", "0.1.0", [0, 1, 0, 0, 0]),
+        ]);
+
+        let diff_lines = construct_category_diff_lines(
+            "baseline",
+            "current scan",
+            &diff_reports(&before, &after),
+        );
+
+        assert_eq!(
+            diff_lines,
+            vec![
+                String::new(),
+                String::from(
+                    "Unsafe usage difference by category: baseline -> current scan"
+                ),
+                String::new(),
+                String::from(
+                    "added 0.2.0: expressions +2, methods +1 (net +3, only in current scan)"
+                ),
+                String::from("foo 0.1.0: expressions -2, impls +2 (net 0)"),
+                String::from(
+                    "removed 0.1.0: functions -1 (net -1, only in baseline)"
+                ),
+                String::new(),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn construct_regression_diff_lines_test() {
+        let before = create_report_with_counts(&[
+            ("decreased", "0.1.0", [0, 3, 0, 0, 0]),
+            ("increased", "0.1.0", [0, 1, 0, 0, 0]),
+            ("removed", "0.1.0", [1, 0, 0, 0, 0]),
+            ("same_total", "0.1.0", [0, 1, 0, 0, 0]),
+        ]);
+        let after = create_report_with_counts(&[
+            ("added", "0.2.0", [0, 2, 0, 0, 0]),
+            ("decreased", "0.1.0", [0, 1, 0, 0, 0]),
+            ("increased", "0.1.0", [0, 1, 2, 0, 0]),
+            ("same_total", "0.1.0", [1, 0, 0, 0, 0]),
+        ]);
+
+        let diff_lines = construct_regression_diff_lines(
+            "baseline.json",
+            &diff_reports(&before, &after),
+            false,
+        );
+
+        assert_eq!(
+            diff_lines,
+            vec![
+                String::new(),
+                String::from(
+                    "Unsafe usage compared to the baseline: baseline.json"
+                ),
+                String::new(),
+                String::from("added   added 0.2.0: 2 (regression)"),
+                String::from("changed decreased 0.1.0: 3 -> 1 (-2)"),
+                String::from("changed increased 0.1.0: 1 -> 3 (+2) (regression)"),
+                String::from("removed removed 0.1.0: 1"),
+                String::new(),
+                String::from(
+                    "Found 2 crates using more unsafe code than in the baseline."
+                ),
+                String::new(),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn construct_json_diff_line_test() {
+        let before = create_report_with_counts(&[
+            ("increased", "0.1.0", [0, 1, 0, 0, 0]),
+            ("removed", "0.1.0", [1, 0, 0, 0, 0]),
+            ("same_total", "0.1.0", [0, 1, 0, 0, 0]),
+        ]);
+        let after = create_report_with_counts(&[
+            ("increased", "0.1.0", [0, 1, 2, 0, 0]),
+            ("same_total", "0.1.0", [1, 0, 0, 0, 0]),
+        ]);
+
+        let json_diff_line = construct_json_diff_line(
+            "baseline.json",
+            &diff_reports(&before, &after),
+        );
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json_diff_line).unwrap(),
+            serde_json::json!({
+                "baseline": "baseline.json",
+                "crates": [
+                    {
+                        "name": "increased",
+                        "version": "0.1.0",
+                        "change": "changed",
+                        "before": 1,
+                        "after": 3,
+                        "delta": 2,
+                        "categories": {
+                            "expressions": 0,
+                            "functions": 0,
+                            "impls": 2,
+                            "methods": 0,
+                            "traits": 0
+                        },
+                        "regression": true
+                    },
+                    {
+                        "name": "removed",
+                        "version": "0.1.0",
+                        "change": "removed",
+                        "before": 1,
+                        "after": null,
+                        "delta": -1,
+                        "categories": {
+                            "expressions": 0,
+                            "functions": -1,
+                            "impls": 0,
+                            "methods": 0,
+                            "traits": 0
+                        },
+                        "regression": false
+                    }
+                ],
+                "regression_count": 1
+            })
+        );
+    }
+
+    #[rstest]
+    fn construct_markdown_diff_lines_test() {
+        let before = create_report(&[
+            ("decreased", "0.1.0", 3),
+            ("removed", "0.1.0", 1),
+        ]);
+        let after =
+            create_report(&[("added", "0.2.0", 2), ("decreased", "0.1.0", 1)]);
+
+        let diff_lines = construct_markdown_diff_lines(
+            "baseline.json",
+            &diff_reports(&before, &after),
+        );
+
+        assert_eq!(
+            diff_lines,
+            vec![
+                String::from(
+                    "### Unsafe usage compared to the baseline: `baseline.json`"
+                ),
+                String::new(),
+                String::from("| Change | Crate | Before | After | Delta |"),
+                String::from("| --- | --- | --: | --: | --: |"),
+                String::from("| added | `added 0.2.0` |  | 2 | +2 :warning: |"),
+                String::from("| changed | `decreased 0.1.0` | 3 | 1 | -2 |"),
+                String::from("| removed | `removed 0.1.0` | 1 |  | -1 |"),
+                String::new(),
+                String::from(
+                    "Found 1 crates using more unsafe code than in the baseline."
+                ),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn construct_markdown_diff_lines_test_no_differences() {
+        let report = create_report(&[("foo", "0.1.0", 1)]);
+
+        assert_eq!(
+            construct_markdown_diff_lines(
+                "baseline.json",
+                &diff_reports(&report, &report)
+            ),
+            vec![
+                String::from(
+                    "### Unsafe usage compared to the baseline: `baseline.json`"
+                ),
+                String::new(),
+                String::from("No differences found."),
+            ]
+        );
+    }
+
+    #[rstest(
+        input_before,
+        input_after,
+        expected_is_regression,
+        case(Some(1), Some(2), true),
+        case(Some(2), Some(1), false),
+        case(None, Some(1), true),
+        case(None, Some(0), false),
+        case(Some(1), None, false)
+    )]
+    fn crate_diff_is_regression_test(
+        input_before: Option<u64>,
+        input_after: Option<u64>,
+        expected_is_regression: bool,
+    ) {
+        let counter_block = |unsafe_exprs: u64| {
+            let mut counter_block = CounterBlock::default();
+            counter_block.exprs.unsafe_ = unsafe_exprs;
+            counter_block
+        };
+        let crate_diff = CrateDiff {
+            name: String::from("foo"),
+            version: Version::new(0, 1, 0),
+            before: input_before.map(counter_block),
+            after: input_after.map(counter_block),
+        };
+
+        assert_eq!(crate_diff.is_regression(), expected_is_regression);
+    }
+
+    fn create_report(crates: &[(&str, &str, u64)]) -> SafetyReport {
+        create_report_with_counts(
+            &crates
+                .iter()
+                .map(|(name, version, unsafe_exprs)| {
+                    (*name, *version, [0, *unsafe_exprs, 0, 0, 0])
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// The unsafe counts are given in the order of the table columns
+    fn create_report_with_counts(
+        crates: &[(&str, &str, [u64; 5])],
+    ) -> SafetyReport {
+        let count = |unsafe_| Count { safe: 0, unsafe_ };
+        create_safety_report(
+            crates
+                .iter()
+                .map(|(name, version, unsafe_counts)| {
+                    (
+                        PackageInfo::new(create_registry_package_id(
+                            name, version,
+                        )),
+                        UnsafeInfo {
+                            used: CounterBlock {
+                                functions: count(unsafe_counts[0]),
+                                exprs: count(unsafe_counts[1]),
+                                item_impls: count(unsafe_counts[2]),
+                                item_traits: count(unsafe_counts[3]),
+                                methods: count(unsafe_counts[4]),
+                            },
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+        )
+    }
+}
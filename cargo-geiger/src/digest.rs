@@ -0,0 +1,136 @@
+use crate::format::summary::format_category_counts;
+
+use cargo_geiger_serde::{PackageId, SafetyReport};
+use cargo_util::Sha256;
+
+/// The SHA-256 of the normalized scan result, which only changes when the
+/// unsafe footprint does: a line for each crate with its counts and whether
+/// it forbids unsafe code, unconditionally or behind a `cfg_attr`, ordered by
+/// crate, and a line for each crate which couldn't be scanned.
+///
+/// Paths and source URLs are left out, so that the digest is the same for
+/// checkouts in different directories, as are download counts and
+/// `--history` versions, which don't come from the scanned code.
+pub fn report_digest(report: &SafetyReport) -> String {
+    let mut entries = report.packages.values().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+    let mut packages_without_metrics =
+        report.packages_without_metrics.iter().collect::<Vec<_>>();
+    packages_without_metrics.sort();
+
+    let mut sha256 = Sha256::new();
+    for entry in entries {
+        let unsafety = &entry.unsafety;
+        sha256.update(
+            format!(
+                "{}: {}, forbids unsafe {}, conditionally forbids unsafe {}, \
+                denies unsafe {}\n",
+                normalized_package_id(&entry.package.id),
+                format_category_counts(&unsafety.used, &unsafety.unused),
+                unsafety.forbids_unsafe,
+                unsafety.conditionally_forbids_unsafe,
+                unsafety.denies_unsafe
+            )
+            .as_bytes(),
+        );
+    }
+    for package_id in packages_without_metrics {
+        sha256.update(
+            format!("{}: without metrics\n", normalized_package_id(package_id))
+                .as_bytes(),
+        );
+    }
+    sha256.finish_hex()
+}
+
+/// The name, version and kind of source of the crate, as crates of the same
+/// name and version from different kinds of sources are different crates
+fn normalized_package_id(package_id: &PackageId) -> String {
+    format!(
+        "{} {} ({})",
+        package_id.name,
+        package_id.version,
+        package_id.source.kind()
+    )
+}
+
+#[cfg(test)]
+mod digest_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_registry_package_id,
+        create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::{PackageInfo, Source, UnsafeInfo};
+    use rstest::*;
+    use url::Url;
+
+    #[rstest]
+    fn report_digest_test() {
+        let digest = report_digest(&create_report("file:///a/foo", 1, None));
+
+        assert_eq!(digest.len(), 64);
+        assert_eq!(
+            report_digest(&create_report("file:///b/foo", 1, Some(200))),
+            digest
+        );
+        assert_ne!(
+            report_digest(&create_report("file:///a/foo", 2, None)),
+            digest
+        );
+    }
+
+    #[rstest]
+    fn report_digest_test_conditionally_forbids_unsafe() {
+        let create_forbidding_report = |forbids_unsafe, conditionally| {
+            let mut report = create_report("file:///a/foo", 0, None);
+            for entry in report.packages.values_mut() {
+                entry.unsafety.forbids_unsafe = forbids_unsafe;
+                entry.unsafety.conditionally_forbids_unsafe = conditionally;
+            }
+            report
+        };
+
+        assert_ne!(
+            report_digest(&create_forbidding_report(true, false)),
+            report_digest(&create_forbidding_report(false, true))
+        );
+        assert_ne!(
+            report_digest(&create_forbidding_report(false, true)),
+            report_digest(&create_forbidding_report(false, false))
+        );
+    }
+
+    #[rstest]
+    fn report_digest_test_source_kind() {
+        let registry_report = create_safety_report(vec![(
+            PackageInfo::new(create_registry_package_id("foo", "0.1.0")),
+            create_unsafe_info(1),
+        )]);
+
+        assert_ne!(
+            report_digest(&registry_report),
+            report_digest(&create_report("file:///a/foo", 1, None))
+        );
+    }
+
+    fn create_report(
+        source_url: &str,
+        unsafe_exprs: u64,
+        downloads: Option<u64>,
+    ) -> SafetyReport {
+        let package_id = PackageId {
+            source: Source::Path(Url::parse(source_url).unwrap()),
+            ..create_path_package_id("foo")
+        };
+        create_safety_report(vec![(
+            PackageInfo::new(package_id),
+            UnsafeInfo {
+                downloads,
+                ..create_unsafe_info(unsafe_exprs)
+            },
+        )])
+    }
+}
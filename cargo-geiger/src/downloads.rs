@@ -0,0 +1,224 @@
+use cargo::CliError;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct DownloadsFileParseError {
+    pub line_number: usize,
+}
+
+impl Error for DownloadsFileParseError {}
+
+impl fmt::Display for DownloadsFileParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse line {}, expected `<name> <downloads>`",
+            self.line_number
+        )
+    }
+}
+
+/// The snapshot read by `--downloads` unless `--downloads-file` is given
+const BUNDLED_SNAPSHOT: &str = include_str!("downloads/snapshot.txt");
+
+/// The crates.io download count of each crate, read from a snapshot with
+/// `--downloads`. The counts are never fetched, so that a scan doesn't depend
+/// on the network.
+#[derive(Debug, Default)]
+pub struct DownloadCounts {
+    crate_downloads: HashMap<String, u64>,
+}
+
+impl DownloadCounts {
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.crate_downloads.get(name).copied()
+    }
+}
+
+impl std::str::FromStr for DownloadCounts {
+    type Err = DownloadsFileParseError;
+
+    /// Empty lines and lines starting with `#` are ignored
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut crate_downloads = HashMap::new();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parse_error = DownloadsFileParseError {
+                line_number: index + 1,
+            };
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            match fields.as_slice() {
+                [name, downloads] => {
+                    let downloads =
+                        downloads.parse().map_err(|_| parse_error)?;
+                    crate_downloads.insert(name.to_string(), downloads);
+                }
+                _ => return Err(parse_error),
+            }
+        }
+        Ok(DownloadCounts { crate_downloads })
+    }
+}
+
+/// A download count shortened to at most four significant characters, such
+/// as `950`, `12.3K` or `100M`
+pub fn format_download_count(downloads: u64) -> String {
+    const SUFFIXES: [(u64, &str); 3] =
+        [(1_000_000_000, "B"), (1_000_000, "M"), (1_000, "K")];
+
+    for (magnitude, suffix) in SUFFIXES {
+        if downloads >= magnitude {
+            let scaled = downloads as f64 / magnitude as f64;
+            return if scaled < 100.0 {
+                format!("{:.1}{}", scaled, suffix)
+                    .replace(&format!(".0{}", suffix), suffix)
+            } else {
+                format!("{:.0}{}", scaled, suffix)
+            };
+        }
+    }
+    downloads.to_string()
+}
+
+/// Read the snapshot given with `--downloads-file`, or else the bundled one,
+/// or return `None` without `--downloads`
+pub fn read_downloads(
+    downloads: bool,
+    downloads_file: &Option<PathBuf>,
+) -> Result<Option<DownloadCounts>, CliError> {
+    if !downloads {
+        return Ok(None);
+    }
+    match downloads_file {
+        Some(downloads_file) => read_downloads_file(downloads_file).map(Some),
+        None => Ok(Some(
+            BUNDLED_SNAPSHOT
+                .parse()
+                .expect("the bundled download counts snapshot is valid"),
+        )),
+    }
+}
+
+/// The unsafe usage of a crate, weighted down by the order of magnitude of its
+/// download count, so that unsafe code in rarely used crates, which fewer
+/// people have vetted, ranks first. A crate without a known download count is
+/// treated as never downloaded.
+pub fn unpopular_unsafe_score(
+    total_unsafe: u64,
+    downloads: Option<u64>,
+) -> f64 {
+    total_unsafe as f64 / (downloads.unwrap_or(0) as f64 + 10.0).log10()
+}
+
+fn read_downloads_file(
+    downloads_path: &Path,
+) -> Result<DownloadCounts, CliError> {
+    let downloads_content =
+        fs::read_to_string(downloads_path).map_err(|e| {
+            eprintln!(
+                "Failed to read download counts from file: {}",
+                downloads_path.display()
+            );
+            anyhow::Error::from(e)
+        })?;
+
+    downloads_content.parse().map_err(|e| {
+        eprintln!(
+            "Failed to parse download counts file: {}",
+            downloads_path.display()
+        );
+        CliError::new(anyhow::Error::from(e), 1)
+    })
+}
+
+#[cfg(test)]
+mod downloads_tests {
+    use super::*;
+
+    use rstest::*;
+    use std::str::FromStr;
+
+    #[rstest(
+        input_downloads,
+        expected_formatted,
+        case(0, "0"),
+        case(950, "950"),
+        case(1_000, "1K"),
+        case(12_345, "12.3K"),
+        case(100_456_789, "100M"),
+        case(2_500_000_000, "2.5B")
+    )]
+    fn format_download_count_test(
+        input_downloads: u64,
+        expected_formatted: &str,
+    ) {
+        assert_eq!(format_download_count(input_downloads), expected_formatted);
+    }
+
+    #[rstest]
+    fn download_counts_from_str_test() {
+        let download_counts =
+            DownloadCounts::from_str("# comment\n\nfoo 200\nbar 100000000\n")
+                .unwrap();
+
+        assert_eq!(download_counts.get("foo"), Some(200));
+        assert_eq!(download_counts.get("bar"), Some(100_000_000));
+        assert_eq!(download_counts.get("baz"), None);
+    }
+
+    #[rstest(
+        input_downloads_content,
+        expected_line_number,
+        case("foo", 1),
+        case("foo 1\nbar many", 2),
+        case("foo 1 2", 1)
+    )]
+    fn download_counts_from_str_test_invalid(
+        input_downloads_content: &str,
+        expected_line_number: usize,
+    ) {
+        assert_eq!(
+            DownloadCounts::from_str(input_downloads_content).unwrap_err(),
+            DownloadsFileParseError {
+                line_number: expected_line_number
+            }
+        );
+    }
+
+    #[rstest]
+    fn read_downloads_test() {
+        assert!(read_downloads(false, &None).unwrap().is_none());
+
+        let download_counts = read_downloads(true, &None).unwrap().unwrap();
+        assert!(download_counts.get("serde").is_some());
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let downloads_file = temp_dir.path().join("downloads.txt");
+        fs::write(&downloads_file, "foo 200\n").unwrap();
+        let download_counts = read_downloads(true, &Some(downloads_file))
+            .unwrap()
+            .unwrap();
+        assert_eq!(download_counts.get("foo"), Some(200));
+        assert_eq!(download_counts.get("serde"), None);
+    }
+
+    #[rstest]
+    fn unpopular_unsafe_score_test() {
+        assert!(
+            unpopular_unsafe_score(10, Some(200))
+                > unpopular_unsafe_score(10, Some(100_000_000))
+        );
+        assert!(
+            unpopular_unsafe_score(10, None)
+                > unpopular_unsafe_score(10, Some(200))
+        );
+        assert_eq!(unpopular_unsafe_score(0, None), 0.0);
+    }
+}
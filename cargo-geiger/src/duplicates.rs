@@ -0,0 +1,186 @@
+use crate::args::DuplicateArgs;
+use crate::graph::Graph;
+
+use cargo_metadata::semver::Version;
+use cargo_metadata::Metadata;
+use petgraph::Direction;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct DuplicateVersionsError {
+    pub duplicate_crate_count: usize,
+}
+
+impl Error for DuplicateVersionsError {}
+
+impl fmt::Display for DuplicateVersionsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Found {} crates present at more than one version",
+            self.duplicate_crate_count
+        )
+    }
+}
+
+/// The versions of each crate in the dependency graph, with the crates which
+/// depend on each version, keyed by crate name
+type CrateVersions = BTreeMap<String, BTreeMap<Version, BTreeSet<String>>>;
+
+/// When `--warn-duplicate-versions` or `--deny-duplicate-versions` is set,
+/// print each crate present at more than one version in the dependency
+/// graph, with the crates through which each version is reached. Crates
+/// allowed with `--allow-duplicate` are skipped. Only the deny variant fails
+/// the gate.
+pub fn check_duplicate_versions(
+    duplicate_args: &DuplicateArgs,
+    metadata: &Metadata,
+    graph: &Graph,
+) -> Option<DuplicateVersionsError> {
+    if !duplicate_args.warn_duplicate_versions
+        && !duplicate_args.deny_duplicate_versions
+    {
+        return None;
+    }
+
+    let mut crate_versions = CrateVersions::new();
+    for node_index in graph.graph.node_indices() {
+        let package = &metadata[&graph.graph[node_index]];
+        let dependents = graph
+            .graph
+            .neighbors_directed(node_index, Direction::Incoming)
+            .map(|dependent_index| {
+                let dependent = &metadata[&graph.graph[dependent_index]];
+                format!("{} {}", dependent.name, dependent.version)
+            });
+        crate_versions
+            .entry(package.name.clone())
+            .or_default()
+            .entry(package.version.clone())
+            .or_default()
+            .extend(dependents);
+    }
+
+    let duplicate_lines = construct_duplicate_lines(
+        &crate_versions,
+        &duplicate_args.allow_duplicate,
+    );
+    if duplicate_lines.is_empty() {
+        return None;
+    }
+
+    eprintln!("WARNING: Crates present at more than one version:");
+    for duplicate_line in &duplicate_lines {
+        eprintln!("{}", duplicate_line);
+    }
+
+    if !duplicate_args.deny_duplicate_versions {
+        return None;
+    }
+
+    Some(DuplicateVersionsError {
+        duplicate_crate_count: duplicate_lines.len(),
+    })
+}
+
+/// A line for each crate with more than one version, ordered by name, each
+/// version followed by its dependents. A version without dependents is the
+/// root of the graph.
+fn construct_duplicate_lines(
+    crate_versions: &CrateVersions,
+    allow_duplicate: &[String],
+) -> Vec<String> {
+    crate_versions
+        .iter()
+        .filter(|(name, versions)| {
+            versions.len() > 1 && !allow_duplicate.contains(name)
+        })
+        .map(|(name, versions)| {
+            let versions = versions
+                .iter()
+                .map(|(version, dependents)| {
+                    if dependents.is_empty() {
+                        format!("{} (root)", version)
+                    } else {
+                        format!(
+                            "{} (required by {})",
+                            version,
+                            dependents
+                                .iter()
+                                .cloned()
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        )
+                    }
+                })
+                .collect::<Vec<String>>();
+            format!("    {}: {}", name, versions.join(", "))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod duplicates_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest(
+        input_allow_duplicate,
+        expected_duplicate_lines,
+        case(
+            vec![],
+            vec![String::from(
+                "    foo: 0.1.0 (required by bar 1.0.0, baz 0.2.0), 0.2.0 (required by root 0.1.0)"
+            )]
+        ),
+        case(vec![String::from("foo")], vec![])
+    )]
+    fn construct_duplicate_lines_test(
+        input_allow_duplicate: Vec<String>,
+        expected_duplicate_lines: Vec<String>,
+    ) {
+        let crate_versions = create_crate_versions(&[
+            ("bar", "1.0.0", &["root 0.1.0"]),
+            ("baz", "0.2.0", &["root 0.1.0"]),
+            ("foo", "0.1.0", &["bar 1.0.0", "baz 0.2.0"]),
+            ("foo", "0.2.0", &["root 0.1.0"]),
+            ("root", "0.1.0", &[]),
+        ]);
+
+        assert_eq!(
+            construct_duplicate_lines(&crate_versions, &input_allow_duplicate),
+            expected_duplicate_lines
+        );
+    }
+
+    #[rstest]
+    fn construct_duplicate_lines_test_root() {
+        let crate_versions = create_crate_versions(&[
+            ("root", "0.1.0", &[]),
+            ("root", "0.2.0", &["foo 1.0.0"]),
+        ]);
+
+        assert_eq!(
+            construct_duplicate_lines(&crate_versions, &[]),
+            vec![String::from(
+                "    root: 0.1.0 (root), 0.2.0 (required by foo 1.0.0)"
+            )]
+        );
+    }
+
+    fn create_crate_versions(
+        crates: &[(&str, &str, &[&str])],
+    ) -> CrateVersions {
+        let mut crate_versions = CrateVersions::new();
+        for (name, version, dependents) in crates {
+            crate_versions.entry(name.to_string()).or_default().insert(
+                Version::parse(version).unwrap(),
+                dependents.iter().map(|d| d.to_string()).collect(),
+            );
+        }
+        crate_versions
+    }
+}
@@ -0,0 +1,220 @@
+use crate::args::FeaturesArgs;
+
+use cargo_geiger_serde::CounterBlock;
+use cargo_metadata::semver::Version;
+use cargo_metadata::{Metadata, Package};
+use geiger::{find_unsafe_in_string, IncludeTests};
+use std::collections::HashMap;
+use std::process::Command;
+
+/// The toolchain the code is expanded with, as `-Zunpretty=expanded` is
+/// unstable
+const NIGHTLY_TOOLCHAIN: &str = "+nightly";
+
+/// The unsafe usage of the macro-expanded code of each crate, found with
+/// `--expand`. A crate is only missing if its code couldn't be expanded or
+/// parsed.
+#[derive(Debug, Default)]
+pub struct ExpandedCounts {
+    crate_counts: HashMap<(String, Version), CounterBlock>,
+}
+
+impl ExpandedCounts {
+    pub fn get(&self, name: &str, version: &Version) -> Option<&CounterBlock> {
+        self.crate_counts.get(&(name.to_string(), version.clone()))
+    }
+}
+
+/// The annotation of a crate in the tree, such as ` (12 unsafe expanded)`
+pub fn format_expanded_counts(counter_block: Option<&CounterBlock>) -> String {
+    match counter_block {
+        Some(counter_block) => {
+            format!(" ({} unsafe expanded)", counter_block.total_unsafe())
+        }
+        None => String::from(" (expansion failed)"),
+    }
+}
+
+/// Expand and scan the code of every crate in the dependency graph, or return
+/// `None` without `--expand`, or if no nightly toolchain is installed.
+///
+/// The features of `--features` and the like are only given to the workspace
+/// members, the dependencies being expanded with the features they are built
+/// with. The `--frozen`, `--locked` and `--offline` flags are given to every
+/// expansion.
+pub fn find_expanded_counts(
+    expand: bool,
+    cargo_lock_args: &[String],
+    features_args: &FeaturesArgs,
+    include_tests: IncludeTests,
+    metadata: &Metadata,
+) -> Option<ExpandedCounts> {
+    if !expand {
+        return None;
+    }
+    if !nightly_toolchain_is_installed() {
+        eprintln!(
+            "WARNING: `--expand` needs a nightly toolchain, installed with \
+            `rustup toolchain install nightly`. The expanded code is not \
+            scanned."
+        );
+        return None;
+    }
+
+    let mut expanded_counts = ExpandedCounts::default();
+    for package in &metadata.packages {
+        let features_args = metadata
+            .workspace_members
+            .contains(&package.id)
+            .then_some(features_args);
+        let expanded_code = match expand_package(
+            cargo_lock_args,
+            features_args,
+            metadata,
+            package,
+        ) {
+            Ok(expanded_code) => expanded_code,
+            Err(e) => {
+                eprintln!(
+                    "WARNING: Failed to expand {} {}: {}",
+                    package.name, package.version, e
+                );
+                continue;
+            }
+        };
+        match find_unsafe_in_string(&expanded_code, include_tests) {
+            Ok(rs_file_metrics) => {
+                expanded_counts.crate_counts.insert(
+                    (package.name.clone(), package.version.clone()),
+                    rs_file_metrics.counters,
+                );
+            }
+            Err(e) => eprintln!(
+                "WARNING: Failed to parse the expanded code of {} {}: {}",
+                package.name, package.version, e
+            ),
+        }
+    }
+    Some(expanded_counts)
+}
+
+/// The code of the library of the package, or of its first binary if it has
+/// no library, with its macros expanded
+fn expand_package(
+    cargo_lock_args: &[String],
+    features_args: Option<&FeaturesArgs>,
+    metadata: &Metadata,
+    package: &Package,
+) -> anyhow::Result<String> {
+    let target_args = expanded_target_args(package).ok_or_else(|| {
+        anyhow::anyhow!("the package has no library or binary target")
+    })?;
+    let mut command = Command::new("cargo");
+    command
+        .arg(NIGHTLY_TOOLCHAIN)
+        .arg("rustc")
+        .arg("--quiet")
+        .arg("--manifest-path")
+        .arg(metadata.workspace_root.join("Cargo.toml"))
+        .arg("--package")
+        .arg(format!("{}@{}", package.name, package.version))
+        .args(target_args)
+        .arg("--profile=check")
+        .args(cargo_lock_args);
+    if let Some(features_args) = features_args {
+        if features_args.all_features {
+            command.arg("--all-features");
+        }
+        if features_args.no_default_features {
+            command.arg("--no-default-features");
+        }
+        if !features_args.features.is_empty() {
+            command
+                .arg("--features")
+                .arg(features_args.features.join(" "));
+        }
+    }
+    let output = command.arg("--").arg("-Zunpretty=expanded").output()?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// The arguments of `cargo rustc` selecting the target which is expanded
+fn expanded_target_args(package: &Package) -> Option<Vec<String>> {
+    if package
+        .targets
+        .iter()
+        .any(|target| target.kind.iter().any(|kind| is_library_kind(kind)))
+    {
+        return Some(vec![String::from("--lib")]);
+    }
+    package
+        .targets
+        .iter()
+        .find(|target| target.kind.iter().any(|kind| kind == "bin"))
+        .map(|target| vec![String::from("--bin"), target.name.clone()])
+}
+
+fn is_library_kind(kind: &str) -> bool {
+    matches!(
+        kind,
+        "lib" | "rlib" | "dylib" | "cdylib" | "staticlib" | "proc-macro"
+    )
+}
+
+fn nightly_toolchain_is_installed() -> bool {
+    Command::new("cargo")
+        .arg(NIGHTLY_TOOLCHAIN)
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+#[cfg(test)]
+mod expand_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest(
+        input_counter_block,
+        expected_annotation,
+        case(Some(CounterBlock::default()), " (0 unsafe expanded)"),
+        case(None, " (expansion failed)")
+    )]
+    fn format_expanded_counts_test(
+        input_counter_block: Option<CounterBlock>,
+        expected_annotation: &str,
+    ) {
+        assert_eq!(
+            format_expanded_counts(input_counter_block.as_ref()),
+            expected_annotation
+        );
+    }
+
+    #[rstest]
+    fn format_expanded_counts_test_total() {
+        let mut counter_block = CounterBlock::default();
+        counter_block.exprs.unsafe_ = 10;
+        counter_block.functions.unsafe_ = 2;
+
+        assert_eq!(
+            format_expanded_counts(Some(&counter_block)),
+            " (12 unsafe expanded)"
+        );
+    }
+
+    #[rstest(
+        input_kind,
+        expected_is_library,
+        case("lib", true),
+        case("proc-macro", true),
+        case("bin", false),
+        case("custom-build", false)
+    )]
+    fn is_library_kind_test(input_kind: &str, expected_is_library: bool) {
+        assert_eq!(is_library_kind(input_kind), expected_is_library);
+    }
+}
@@ -0,0 +1,422 @@
+use crate::format::summary::format_category_counts;
+use crate::format::CrateDetectionStatus;
+use crate::scan::{unsafe_stats, PackageMetrics};
+
+use cargo::CliError;
+use cargo_geiger_serde::CounterBlock;
+use cargo_metadata::semver::Version;
+use cargo_metadata::{Metadata, Package};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The crate given with `--explain` or `--root`, as `<name>`,
+/// `<name>@<version>` or `<name>:<version>`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CrateSpec {
+    pub name: String,
+    pub version: Option<Version>,
+}
+
+impl CrateSpec {
+    fn matches(&self, package: &Package) -> bool {
+        package.name == self.name
+            && self
+                .version
+                .as_ref()
+                .is_none_or(|version| &package.version == version)
+    }
+}
+
+impl fmt::Display for CrateSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{}@{}", self.name, version),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl FromStr for CrateSpec {
+    type Err = CrateSpecParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_error = || CrateSpecParseError {
+            spec: s.to_string(),
+        };
+        let (name, version) = match s.split_once(['@', ':']) {
+            Some((name, version)) => (
+                name,
+                Some(Version::parse(version).map_err(|_| parse_error())?),
+            ),
+            None => (s, None),
+        };
+        if name.is_empty() {
+            return Err(parse_error());
+        }
+        Ok(CrateSpec {
+            name: name.to_string(),
+            version,
+        })
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct CrateSpecParseError {
+    pub spec: String,
+}
+
+impl Error for CrateSpecParseError {}
+
+impl fmt::Display for CrateSpecParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse crate `{}`, expected `<name>`, `<name>@<version>` or `<name>:<version>`",
+            self.spec
+        )
+    }
+}
+
+/// The single crate in the dependency graph matching the spec. Without a
+/// version, the name has to be unique in the graph.
+pub fn find_package<'a>(
+    crate_spec: &CrateSpec,
+    metadata: &'a Metadata,
+) -> Result<&'a Package, CliError> {
+    let packages = metadata
+        .packages
+        .iter()
+        .filter(|package| crate_spec.matches(package))
+        .collect::<Vec<_>>();
+    match packages.as_slice() {
+        [package] => Ok(package),
+        [] => {
+            eprintln!(
+                "No crate matching `{}` found in the dependency graph",
+                crate_spec
+            );
+            Err(CliError::code(1))
+        }
+        _ => {
+            eprintln!(
+                "Multiple crates matching `{}` found in the dependency graph: {}. \
+                Select one with `<name>@<version>`.",
+                crate_spec,
+                packages
+                    .iter()
+                    .map(|package| package.id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            Err(CliError::code(1))
+        }
+    }
+}
+
+/// The breakdown printed with `--explain`: the forbid and deny attributes of
+/// each entry point, the unsafe usage of each file, and how they yield the
+/// status of the crate
+pub fn construct_explanation_lines(
+    package: &Package,
+    package_metrics: &PackageMetrics,
+    rs_files_used: &HashSet<PathBuf>,
+) -> Vec<String> {
+    let package_root = package
+        .manifest_path
+        .parent()
+        .and_then(|root| root.canonicalize().ok());
+    let display_path = |path: &Path| {
+        package_root
+            .as_ref()
+            .and_then(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+
+    let unsafe_info = unsafe_stats(package_metrics, rs_files_used);
+    let crate_detection_status = CrateDetectionStatus::from(&unsafe_info);
+
+    let mut rs_paths_to_metrics = package_metrics
+        .rs_path_to_metrics
+        .iter()
+        .collect::<Vec<_>>();
+    rs_paths_to_metrics.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut explanation_lines = vec![
+        format!(
+            "{} {}: {:?}",
+            package.name, package.version, crate_detection_status
+        ),
+        String::new(),
+        String::from(
+            "Entry points, which all have to forbid or deny unsafe code for \
+            the crate to do so:",
+        ),
+    ];
+    for (path, rs_file_metrics_wrapper) in &rs_paths_to_metrics {
+        if !rs_file_metrics_wrapper.is_crate_entry_point {
+            continue;
+        }
+        let metrics = &rs_file_metrics_wrapper.metrics;
+        explanation_lines.push(format!(
+            "    {}: {}",
+            display_path(path),
+            match (
+                metrics.forbids_unsafe,
+                metrics.denies_unsafe,
+                metrics.conditionally_forbids_unsafe
+            ) {
+                (true, _, _) => "#![forbid(unsafe_code)]",
+                (false, true, _) => "#![deny(unsafe_code)]",
+                (false, false, true) => {
+                    "#![cfg_attr(<condition>, forbid(unsafe_code))]"
+                }
+                (false, false, false) => {
+                    "neither forbids nor denies unsafe code"
+                }
+            }
+        ));
+    }
+
+    explanation_lines.push(String::new());
+    explanation_lines.push(format!(
+        "Unsafe code used by the build/found: {}",
+        format_category_counts(&unsafe_info.used, &unsafe_info.unused)
+    ));
+    explanation_lines.push(String::new());
+    explanation_lines.push(String::from("Files with unsafe code:"));
+    let files_with_unsafe = rs_paths_to_metrics
+        .iter()
+        .filter(|(_, rs_file_metrics_wrapper)| {
+            rs_file_metrics_wrapper.metrics.counters.has_unsafe()
+        })
+        .collect::<Vec<_>>();
+    if files_with_unsafe.is_empty() {
+        explanation_lines.push(String::from("    None"));
+    }
+    for (path, rs_file_metrics_wrapper) in files_with_unsafe {
+        let counters = rs_file_metrics_wrapper.metrics.counters.clone();
+        let (used, unused, used_label) = if rs_files_used.contains(*path) {
+            (counters, CounterBlock::default(), "used by the build")
+        } else {
+            (CounterBlock::default(), counters, "not used by the build")
+        };
+        explanation_lines.push(format!(
+            "    {} ({}): {}",
+            display_path(path),
+            used_label,
+            format_category_counts(&used, &unused)
+        ));
+    }
+
+    explanation_lines.push(String::new());
+    explanation_lines.push(format!(
+        "Status: {}",
+        explain_crate_detection_status(&crate_detection_status)
+    ));
+    explanation_lines.push(String::new());
+    explanation_lines
+}
+
+fn explain_crate_detection_status(
+    crate_detection_status: &CrateDetectionStatus,
+) -> &'static str {
+    match crate_detection_status {
+        CrateDetectionStatus::UnsafeDetected => {
+            "unsafe code used by the build was found."
+        }
+        CrateDetectionStatus::NoneDetectedForbidsUnsafe => {
+            "no unsafe code used by the build was found, and every entry point \
+            forbids unsafe code."
+        }
+        CrateDetectionStatus::NoneDetectedDeniesUnsafe => {
+            "no unsafe code used by the build was found, and every entry point \
+            forbids or denies unsafe code, with at least one only denying it, \
+            which can be overridden within the crate."
+        }
+        CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe => {
+            "no unsafe code used by the build was found, and every entry point \
+            forbids or denies unsafe code, with at least one only doing so \
+            within a `cfg_attr`, which doesn't apply unless its condition \
+            holds."
+        }
+        CrateDetectionStatus::NoneDetectedAllowsUnsafe => {
+            "no unsafe code used by the build was found, but not every entry \
+            point forbids or denies unsafe code."
+        }
+    }
+}
+
+#[cfg(test)]
+mod explain_tests {
+    use super::*;
+
+    use crate::scan::RsFileMetricsWrapper;
+
+    use geiger::RsFileMetrics;
+    use rstest::*;
+
+    #[rstest(
+        input_spec,
+        expected_crate_spec,
+        case(
+            "foo",
+            Ok(CrateSpec {
+                name: String::from("foo"),
+                version: None,
+            })
+        ),
+        case(
+            "foo@1.2.3",
+            Ok(CrateSpec {
+                name: String::from("foo"),
+                version: Some(Version::new(1, 2, 3)),
+            })
+        ),
+        case(
+            "foo:1.2.3",
+            Ok(CrateSpec {
+                name: String::from("foo"),
+                version: Some(Version::new(1, 2, 3)),
+            })
+        ),
+        case(
+            "foo@1.2",
+            Err(CrateSpecParseError {
+                spec: String::from("foo@1.2"),
+            })
+        ),
+        case(
+            "@1.2.3",
+            Err(CrateSpecParseError {
+                spec: String::from("@1.2.3"),
+            })
+        )
+    )]
+    fn crate_spec_from_str_test(
+        input_spec: &str,
+        expected_crate_spec: Result<CrateSpec, CrateSpecParseError>,
+    ) {
+        assert_eq!(CrateSpec::from_str(input_spec), expected_crate_spec);
+    }
+
+    #[rstest(
+        input_spec,
+        expected_package_id,
+        case("bar", Some("bar 0.2.0 (path+file:///bar)")),
+        case("foo:0.1.0", Some("foo 0.1.0 (path+file:///foo-0.1)")),
+        case("foo", None),
+        case("baz", None)
+    )]
+    fn find_package_test(input_spec: &str, expected_package_id: Option<&str>) {
+        let package = |name: &str, version: &str, directory: &str| {
+            serde_json::json!({
+                "name": name,
+                "version": version,
+                "id": format!("{} {} (path+file:///{})", name, version, directory),
+                "dependencies": [],
+                "targets": [],
+                "features": {},
+                "manifest_path": format!("/{}/Cargo.toml", directory)
+            })
+        };
+        let metadata = serde_json::from_value::<Metadata>(serde_json::json!({
+            "packages": [
+                package("foo", "0.1.0", "foo-0.1"),
+                package("foo", "0.2.0", "foo-0.2"),
+                package("bar", "0.2.0", "bar")
+            ],
+            "workspace_members": [],
+            "resolve": null,
+            "target_directory": "/target",
+            "version": 1,
+            "workspace_root": "/"
+        }))
+        .unwrap();
+
+        assert_eq!(
+            find_package(&CrateSpec::from_str(input_spec).unwrap(), &metadata)
+                .ok()
+                .map(|package| package.id.repr.as_str()),
+            expected_package_id
+        );
+    }
+
+    #[rstest]
+    fn construct_explanation_lines_test() {
+        let package = serde_json::from_value::<Package>(serde_json::json!({
+            "name": "foo",
+            "version": "0.1.0",
+            "id": "foo 0.1.0 (path+file:///foo)",
+            "dependencies": [],
+            "targets": [],
+            "features": {},
+            "manifest_path": "/foo/Cargo.toml"
+        }))
+        .unwrap();
+
+        let mut lib_metrics = RsFileMetrics {
+            denies_unsafe: true,
+            ..Default::default()
+        };
+        lib_metrics.counters.exprs.unsafe_ = 2;
+        let mut unused_metrics = RsFileMetrics::default();
+        unused_metrics.counters.functions.unsafe_ = 1;
+
+        let mut package_metrics = PackageMetrics::default();
+        package_metrics.rs_path_to_metrics.insert(
+            PathBuf::from("/elsewhere/lib.rs"),
+            RsFileMetricsWrapper {
+                metrics: lib_metrics,
+                is_crate_entry_point: true,
+            },
+        );
+        package_metrics.rs_path_to_metrics.insert(
+            PathBuf::from("/elsewhere/unused.rs"),
+            RsFileMetricsWrapper {
+                metrics: unused_metrics,
+                is_crate_entry_point: false,
+            },
+        );
+        let rs_files_used = vec![PathBuf::from("/elsewhere/lib.rs")]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            construct_explanation_lines(
+                &package,
+                &package_metrics,
+                &rs_files_used
+            ),
+            vec![
+                String::from("foo 0.1.0: UnsafeDetected"),
+                String::new(),
+                String::from(
+                    "Entry points, which all have to forbid or deny unsafe code for the crate to do so:"
+                ),
+                String::from("    /elsewhere/lib.rs: #![deny(unsafe_code)]"),
+                String::new(),
+                String::from(
+                    "Unsafe code used by the build/found: functions 0/1, expressions 2/2, impls 0/0, traits 0/0, methods 0/0"
+                ),
+                String::new(),
+                String::from("Files with unsafe code:"),
+                String::from(
+                    "    /elsewhere/lib.rs (used by the build): functions 0/0, expressions 2/2, impls 0/0, traits 0/0, methods 0/0"
+                ),
+                String::from(
+                    "    /elsewhere/unused.rs (not used by the build): functions 0/1, expressions 0/0, impls 0/0, traits 0/0, methods 0/0"
+                ),
+                String::new(),
+                String::from(
+                    "Status: unsafe code used by the build was found."
+                ),
+                String::new(),
+            ]
+        );
+    }
+}
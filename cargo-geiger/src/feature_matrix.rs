@@ -0,0 +1,216 @@
+use crate::format::summary::format_category_counts;
+use crate::score::CategoryWeights;
+
+use cargo_geiger_serde::{CounterBlock, SafetyReport};
+use cargo_metadata::semver::Version;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+/// Scanning every combination of more features than this takes too long,
+/// the combinations to scan have to be given with `--feature-set` instead
+pub const MAX_MATRIX_FEATURES: usize = 8;
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct TooManyFeaturesError {
+    pub feature_count: usize,
+}
+
+impl Error for TooManyFeaturesError {}
+
+impl fmt::Display for TooManyFeaturesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The package has {} features, scanning every combination of more \
+            than {} features is not supported, select the combinations to scan \
+            with `--feature-set`",
+            self.feature_count, MAX_MATRIX_FEATURES
+        )
+    }
+}
+
+/// Every combination of the features, starting with no features, with the
+/// features of each combination in the order they are given
+pub fn feature_combinations(
+    features: &[String],
+) -> Result<Vec<Vec<String>>, TooManyFeaturesError> {
+    if features.len() > MAX_MATRIX_FEATURES {
+        return Err(TooManyFeaturesError {
+            feature_count: features.len(),
+        });
+    }
+    Ok((0..1_usize << features.len())
+        .map(|combination| {
+            features
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| combination & (1 << index) != 0)
+                .map(|(_, feature)| feature.clone())
+                .collect()
+        })
+        .collect())
+}
+
+/// The scan of a single crate with the highest weighted unsafe score among
+/// the scans of all feature combinations
+#[derive(Debug, PartialEq)]
+pub struct WorstCaseUnsafe {
+    pub name: String,
+    pub version: Version,
+    pub used: CounterBlock,
+    pub unused: CounterBlock,
+    /// The features of the combination that maximized the unsafe usage
+    pub features: Vec<String>,
+}
+
+/// Match the crates of the reports by name and version, and keep the scan
+/// with the highest weighted unsafe score of each crate. Crates are included
+/// if they are in any of the reports, and on a tie the earlier report wins.
+pub fn worst_case_unsafe(
+    category_weights: &CategoryWeights,
+    feature_reports: &[(Vec<String>, SafetyReport)],
+) -> Vec<WorstCaseUnsafe> {
+    let mut worst_cases = BTreeMap::<(String, Version), WorstCaseUnsafe>::new();
+
+    for (features, report) in feature_reports {
+        for entry in report.packages.values() {
+            let key = (
+                entry.package.id.name.clone(),
+                entry.package.id.version.clone(),
+            );
+            let is_worse = worst_cases.get(&key).is_none_or(|worst_case| {
+                category_weights.weighted_score(&entry.unsafety.used)
+                    > category_weights.weighted_score(&worst_case.used)
+            });
+            if is_worse {
+                worst_cases.insert(
+                    key,
+                    WorstCaseUnsafe {
+                        name: entry.package.id.name.clone(),
+                        version: entry.package.id.version.clone(),
+                        used: entry.unsafety.used.clone(),
+                        unused: entry.unsafety.unused.clone(),
+                        features: features.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    worst_cases.into_values().collect()
+}
+
+/// A line for each crate with its worst case unsafe usage and the features
+/// it was found with, sorted by name and version
+pub fn construct_feature_matrix_lines(
+    combination_count: usize,
+    worst_cases: &[WorstCaseUnsafe],
+) -> Vec<String> {
+    let mut feature_matrix_lines = vec![
+        String::new(),
+        format!(
+            "Worst case unsafe code used by the build/found across {} feature \
+            combinations:",
+            combination_count
+        ),
+        String::new(),
+    ];
+    for worst_case in worst_cases {
+        feature_matrix_lines.push(format!(
+            "{} {}: {} (features: {})",
+            worst_case.name,
+            worst_case.version,
+            format_category_counts(&worst_case.used, &worst_case.unused),
+            describe_features(&worst_case.features)
+        ));
+    }
+    feature_matrix_lines.push(String::new());
+    feature_matrix_lines
+}
+
+fn describe_features(features: &[String]) -> String {
+    if features.is_empty() {
+        String::from("none")
+    } else {
+        format!("\"{}\"", features.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod feature_matrix_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::PackageInfo;
+    use rstest::*;
+
+    #[rstest]
+    fn feature_combinations_test() {
+        let features = vec![String::from("a"), String::from("b")];
+
+        assert_eq!(
+            feature_combinations(&features),
+            Ok(vec![
+                vec![],
+                vec![String::from("a")],
+                vec![String::from("b")],
+                vec![String::from("a"), String::from("b")],
+            ])
+        );
+        assert_eq!(
+            feature_combinations(
+                &(0..9).map(|i| i.to_string()).collect::<Vec<_>>()
+            ),
+            Err(TooManyFeaturesError { feature_count: 9 })
+        );
+    }
+
+    #[rstest]
+    fn worst_case_unsafe_test() {
+        let feature_reports = vec![
+            (vec![], create_report(&[("foo", 1), ("bar", 2)])),
+            (vec![String::from("a")], create_report(&[("foo", 3)])),
+            (
+                vec![String::from("b")],
+                create_report(&[("foo", 3), ("baz", 1)]),
+            ),
+        ];
+
+        let worst_cases =
+            worst_case_unsafe(&CategoryWeights::default(), &feature_reports);
+
+        assert_eq!(
+            worst_cases
+                .iter()
+                .map(|worst_case| (
+                    worst_case.name.as_str(),
+                    worst_case.used.exprs.unsafe_,
+                    worst_case.features.clone()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("bar", 2, vec![]),
+                ("baz", 1, vec![String::from("b")]),
+                ("foo", 3, vec![String::from("a")]),
+            ]
+        );
+    }
+
+    fn create_report(crates: &[(&str, u64)]) -> SafetyReport {
+        create_safety_report(
+            crates
+                .iter()
+                .map(|(name, unsafe_exprs)| {
+                    (
+                        PackageInfo::new(create_path_package_id(name)),
+                        create_unsafe_info(*unsafe_exprs),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
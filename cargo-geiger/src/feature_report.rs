@@ -0,0 +1,474 @@
+use crate::graph::Graph;
+
+use cargo_metadata::semver::Version;
+use cargo_metadata::{Package, PackageId};
+use petgraph::visit::EdgeRef;
+use std::collections::{BTreeSet, HashSet};
+
+/// An active feature of a crate enabling some of its optional dependencies,
+/// with the crates which would be left out of the graph without it
+#[derive(Debug, Eq, PartialEq)]
+pub struct FeatureImpact {
+    pub package_id: PackageId,
+    pub feature: String,
+    /// The optional dependencies no other active feature enables, by the
+    /// name they are depended on with
+    pub optional_dependencies: Vec<String>,
+    /// The crates no longer depended on by any crate of the graph, sorted
+    pub dropped_package_ids: Vec<PackageId>,
+}
+
+/// The impact of disabling each active feature with optional dependencies
+/// of the crates of the graph, ordered by crate and feature. The features it
+/// enables, such as `std` for `default`, and those enabling it, such as
+/// `default` for `std`, are disabled with it.
+pub fn feature_impacts(
+    graph: &Graph,
+    packages: &[Package],
+    root_package_ids: &[PackageId],
+) -> Vec<FeatureImpact> {
+    let mut package_ids = graph.features.keys().collect::<Vec<_>>();
+    package_ids.sort();
+
+    let mut feature_impacts = vec![];
+    for package_id in package_ids {
+        let package =
+            match packages.iter().find(|package| &package.id == package_id) {
+                Some(package) => package,
+                None => continue,
+            };
+        let mut active_features = graph.features[package_id].clone();
+        active_features.sort();
+        let enabled_dependencies =
+            enabled_optional_dependencies(package, &active_features);
+
+        for feature in &active_features {
+            let disabled_features = feature_closure(package, feature);
+            let remaining_features = active_features
+                .iter()
+                .filter(|active_feature| {
+                    !disabled_features.contains(active_feature.as_str())
+                        && !feature_closure(package, active_feature)
+                            .contains(feature.as_str())
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            let optional_dependencies = enabled_dependencies
+                .difference(&enabled_optional_dependencies(
+                    package,
+                    &remaining_features,
+                ))
+                .cloned()
+                .collect::<Vec<_>>();
+            if optional_dependencies.is_empty() {
+                continue;
+            }
+
+            let dependency_names = package
+                .dependencies
+                .iter()
+                .filter(|dependency| {
+                    optional_dependencies
+                        .iter()
+                        .any(|key| key == dependency_key(dependency))
+                })
+                .map(|dependency| dependency.name.as_str())
+                .collect::<HashSet<_>>();
+            feature_impacts.push(FeatureImpact {
+                package_id: package_id.clone(),
+                feature: feature.clone(),
+                optional_dependencies,
+                dropped_package_ids: dropped_package_ids(
+                    graph,
+                    packages,
+                    package_id,
+                    &dependency_names,
+                    root_package_ids,
+                ),
+            });
+        }
+    }
+    feature_impacts
+}
+
+/// A row of the `--feature-report` output
+pub struct FeatureReportRow {
+    pub name: String,
+    pub version: Version,
+    pub feature: String,
+    pub optional_dependencies: Vec<String>,
+    /// The name and version of each crate left out
+    pub dropped_crates: Vec<(String, Version)>,
+    /// The unsafe code used by the build of the crates left out
+    pub dropped_unsafe: u64,
+}
+
+/// The lines printed with `--feature-report`, a line for each feature, the
+/// features dropping the most unsafe code first
+pub fn construct_feature_report_lines(
+    mut rows: Vec<FeatureReportRow>,
+) -> Vec<String> {
+    rows.sort_by(|a, b| {
+        b.dropped_unsafe
+            .cmp(&a.dropped_unsafe)
+            .then(a.name.cmp(&b.name))
+            .then(a.version.cmp(&b.version))
+            .then(a.feature.cmp(&b.feature))
+    });
+
+    let mut feature_report_lines = vec![String::from(
+        "Features enabling optional dependencies, with the unsafe code used \
+        by the build of the crates left out without them:",
+    )];
+    if rows.is_empty() {
+        feature_report_lines.push(String::from("    None"));
+    }
+    for row in rows {
+        let dropped_crates = match row.dropped_crates.as_slice() {
+            [] => String::from(
+                "drops no crate, the dependencies are still used by others",
+            ),
+            dropped_crates => format!(
+                "drops {}",
+                dropped_crates
+                    .iter()
+                    .map(|(name, version)| format!("{} {}", name, version))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        feature_report_lines.push(format!(
+            "    {} {} \"{}\" ({}): {} unsafe, {}",
+            row.name,
+            row.version,
+            row.feature,
+            row.optional_dependencies.join(", "),
+            row.dropped_unsafe,
+            dropped_crates
+        ));
+    }
+    feature_report_lines.push(String::new());
+    feature_report_lines
+}
+
+/// The name a dependency is referred to with in the features of the crate
+fn dependency_key(dependency: &cargo_metadata::Dependency) -> &str {
+    dependency.rename.as_deref().unwrap_or(&dependency.name)
+}
+
+/// The feature and the features of the crate it enables, transitively
+fn feature_closure<'a>(
+    package: &'a Package,
+    feature: &'a str,
+) -> BTreeSet<&'a str> {
+    let mut closure = BTreeSet::new();
+    let mut pending_features = vec![feature];
+    while let Some(feature) = pending_features.pop() {
+        if !closure.insert(feature) {
+            continue;
+        }
+        for enabled in package.features.get(feature).into_iter().flatten() {
+            if package.features.contains_key(enabled.as_str()) {
+                pending_features.push(enabled);
+            }
+        }
+    }
+    closure
+}
+
+/// The optional dependencies the features enable, with `dep:<name>`,
+/// `<name>/<feature>` or the implicit feature of the dependency
+fn enabled_optional_dependencies(
+    package: &Package,
+    features: &[String],
+) -> BTreeSet<String> {
+    let optional_keys = package
+        .dependencies
+        .iter()
+        .filter(|dependency| dependency.optional)
+        .map(dependency_key)
+        .collect::<HashSet<_>>();
+
+    let mut enabled_dependencies = BTreeSet::new();
+    for feature in features {
+        for closure_feature in feature_closure(package, feature) {
+            let enabled = package
+                .features
+                .get(closure_feature)
+                .into_iter()
+                .flatten()
+                // The key of a weak `<name>?/<feature>` keeps the `?`, as it
+                // doesn't enable the dependency.
+                .map(|enabled| match enabled.split_once('/') {
+                    Some((key, _)) => key,
+                    None => enabled.strip_prefix("dep:").unwrap_or(enabled),
+                })
+                .chain(std::iter::once(closure_feature));
+            enabled_dependencies.extend(
+                enabled
+                    .filter(|key| optional_keys.contains(key))
+                    .map(String::from),
+            );
+        }
+    }
+    enabled_dependencies
+}
+
+/// The crates which are no longer reachable from the roots without the
+/// edges from the crate to the dependencies
+fn dropped_package_ids(
+    graph: &Graph,
+    packages: &[Package],
+    package_id: &PackageId,
+    dependency_names: &HashSet<&str>,
+    root_package_ids: &[PackageId],
+) -> Vec<PackageId> {
+    let package_index = graph.nodes[package_id];
+    let removed_edges = graph
+        .graph
+        .edges(package_index)
+        .filter(|edge| {
+            packages
+                .iter()
+                .find(|package| package.id == graph.graph[edge.target()])
+                .is_some_and(|package| {
+                    dependency_names.contains(package.name.as_str())
+                })
+        })
+        .map(|edge| edge.id())
+        .collect::<HashSet<_>>();
+
+    let mut reachable = HashSet::new();
+    let mut pending_indices = root_package_ids
+        .iter()
+        .filter_map(|root_package_id| graph.nodes.get(root_package_id))
+        .copied()
+        .collect::<Vec<_>>();
+    while let Some(index) = pending_indices.pop() {
+        if !reachable.insert(index) {
+            continue;
+        }
+        pending_indices.extend(
+            graph
+                .graph
+                .edges(index)
+                .filter(|edge| !removed_edges.contains(&edge.id()))
+                .map(|edge| edge.target()),
+        );
+    }
+
+    let mut dropped_package_ids = graph
+        .nodes
+        .iter()
+        .filter(|(_, index)| !reachable.contains(*index))
+        .map(|(package_id, _)| package_id.clone())
+        .collect::<Vec<_>>();
+    dropped_package_ids.sort();
+    dropped_package_ids
+}
+
+#[cfg(test)]
+mod feature_report_tests {
+    use super::*;
+
+    use cargo_metadata::DependencyKind;
+    use rstest::*;
+    use std::collections::HashMap;
+
+    fn package_id(name: &str) -> PackageId {
+        PackageId {
+            repr: format!("{} 0.1.0 (path+file:///{})", name, name),
+        }
+    }
+
+    fn create_package(
+        name: &str,
+        dependencies: serde_json::Value,
+        features: serde_json::Value,
+    ) -> Package {
+        serde_json::from_value::<Package>(serde_json::json!({
+            "name": name,
+            "version": "0.1.0",
+            "id": package_id(name).repr,
+            "dependencies": dependencies,
+            "targets": [],
+            "features": features,
+            "manifest_path": format!("/{}/Cargo.toml", name)
+        }))
+        .unwrap()
+    }
+
+    fn create_dependency(name: &str, optional: bool) -> serde_json::Value {
+        serde_json::json!({
+            "name": name,
+            "req": "^0.1",
+            "kind": null,
+            "optional": optional,
+            "uses_default_features": true,
+            "features": []
+        })
+    }
+
+    /// root -> foo, foo -> bar (optional, with `std`, which `default`
+    /// enables), foo -> baz (optional, with `baz`), root -> baz, bar -> qux
+    fn create_packages_and_graph() -> (Vec<Package>, Graph) {
+        let packages = vec![
+            create_package(
+                "root",
+                serde_json::json!([
+                    create_dependency("foo", false),
+                    create_dependency("baz", false)
+                ]),
+                serde_json::json!({}),
+            ),
+            create_package(
+                "foo",
+                serde_json::json!([
+                    create_dependency("bar", true),
+                    create_dependency("baz", true)
+                ]),
+                serde_json::json!({
+                    "default": ["std"],
+                    "std": ["dep:bar"],
+                    "baz": ["dep:baz"]
+                }),
+            ),
+            create_package(
+                "bar",
+                serde_json::json!([create_dependency("qux", false)]),
+                serde_json::json!({}),
+            ),
+            create_package("baz", serde_json::json!([]), serde_json::json!({})),
+            create_package("qux", serde_json::json!([]), serde_json::json!({})),
+        ];
+
+        let mut graph = Graph {
+            graph: Default::default(),
+            nodes: HashMap::new(),
+            features: HashMap::new(),
+        };
+        for package in &packages {
+            let index = graph.graph.add_node(package.id.clone());
+            graph.nodes.insert(package.id.clone(), index);
+        }
+        for (from, to) in [
+            ("root", "foo"),
+            ("root", "baz"),
+            ("foo", "bar"),
+            ("foo", "baz"),
+            ("bar", "qux"),
+        ] {
+            graph.graph.add_edge(
+                graph.nodes[&package_id(from)],
+                graph.nodes[&package_id(to)],
+                DependencyKind::Normal,
+            );
+        }
+        graph.features.insert(
+            package_id("foo"),
+            vec![
+                String::from("std"),
+                String::from("default"),
+                String::from("baz"),
+            ],
+        );
+        (packages, graph)
+    }
+
+    #[rstest]
+    fn feature_impacts_test() {
+        let (packages, graph) = create_packages_and_graph();
+
+        assert_eq!(
+            feature_impacts(&graph, &packages, &[package_id("root")]),
+            vec![
+                FeatureImpact {
+                    package_id: package_id("foo"),
+                    feature: String::from("baz"),
+                    optional_dependencies: vec![String::from("baz")],
+                    dropped_package_ids: vec![],
+                },
+                FeatureImpact {
+                    package_id: package_id("foo"),
+                    feature: String::from("default"),
+                    optional_dependencies: vec![String::from("bar")],
+                    dropped_package_ids: vec![
+                        package_id("bar"),
+                        package_id("qux")
+                    ],
+                },
+                FeatureImpact {
+                    package_id: package_id("foo"),
+                    feature: String::from("std"),
+                    optional_dependencies: vec![String::from("bar")],
+                    dropped_package_ids: vec![
+                        package_id("bar"),
+                        package_id("qux")
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[rstest(
+        input_features,
+        expected_optional_dependencies,
+        case(vec![], vec![]),
+        case(vec!["default"], vec!["bar"]),
+        case(vec!["baz"], vec!["baz"])
+    )]
+    fn enabled_optional_dependencies_test(
+        input_features: Vec<&str>,
+        expected_optional_dependencies: Vec<&str>,
+    ) {
+        let (packages, _) = create_packages_and_graph();
+        let features = input_features
+            .iter()
+            .map(|feature| feature.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            enabled_optional_dependencies(&packages[1], &features),
+            expected_optional_dependencies
+                .iter()
+                .map(|dependency| dependency.to_string())
+                .collect::<BTreeSet<_>>()
+        );
+    }
+
+    #[rstest]
+    fn construct_feature_report_lines_test() {
+        let row =
+            |feature: &str, dropped_crates, dropped_unsafe| FeatureReportRow {
+                name: String::from("foo"),
+                version: Version::new(0, 1, 0),
+                feature: String::from(feature),
+                optional_dependencies: vec![String::from("bar")],
+                dropped_crates,
+                dropped_unsafe,
+            };
+
+        assert_eq!(
+            construct_feature_report_lines(vec![
+                row("baz", vec![], 0),
+                row(
+                    "std",
+                    vec![
+                        (String::from("bar"), Version::new(0, 1, 0)),
+                        (String::from("qux"), Version::new(0, 1, 0)),
+                    ],
+                    12
+                ),
+            ]),
+            vec![
+                "Features enabling optional dependencies, with the unsafe \
+                code used by the build of the crates left out without them:",
+                "    foo 0.1.0 \"std\" (bar): 12 unsafe, drops bar 0.1.0, \
+                qux 0.1.0",
+                "    foo 0.1.0 \"baz\" (bar): 0 unsafe, drops no crate, the \
+                dependencies are still used by others",
+                "",
+            ]
+        );
+    }
+}
@@ -1,25 +1,48 @@
+pub mod aligned;
+pub mod categories;
+pub mod csv;
+pub mod cyclonedx;
+pub mod dot;
 pub mod emoji_symbols;
+pub mod folded;
+pub mod github_actions;
+pub mod heatmap;
+pub mod html;
+pub mod json;
+pub mod json_lines;
+pub mod license_groups;
+pub mod mermaid;
 pub mod pattern;
+pub mod plain;
+pub mod pr_comment;
 pub mod print_config;
+pub mod prometheus;
+pub mod sarif;
+pub mod shields_io;
+pub mod source_groups;
+pub mod summary;
 pub mod table;
+pub mod toml;
+pub mod workspace;
 
 mod display;
 mod parse;
 
+use cargo_geiger_serde::UnsafeInfo;
 use cargo_metadata::DependencyKind;
 use std::fmt;
 use std::str::{self, FromStr};
 
+/// The characters the tree is drawn with, given with `--charset`
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Charset {
     Ascii,
-    GitHubMarkdown,
     Utf8,
 }
 
 impl Default for Charset {
     fn default() -> Self {
-        Charset::Ascii
+        Charset::Utf8
     }
 }
 
@@ -29,6 +52,13 @@ pub enum Chunk {
     Package,
     Raw(String),
     Repository,
+    Source,
+    UnsafeExprs,
+    UnsafeFunctions,
+    UnsafeImpls,
+    UnsafePerKloc,
+    UnsafeTraits,
+    Version,
 }
 
 impl FromStr for Charset {
@@ -38,20 +68,74 @@ impl FromStr for Charset {
         let comparison_string = String::from(s).to_lowercase();
         match comparison_string.as_str() {
             "ascii" => Ok(Charset::Ascii),
-            "githubmarkdown" => Ok(Charset::GitHubMarkdown),
             "utf8" => Ok(Charset::Utf8),
             _ => Err("invalid charset"),
         }
     }
 }
 
+/// The symbols marking the status of each crate, given with `--symbols`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Symbols {
+    Emoji,
+    /// Such as `:)` for a crate forbidding unsafe code and `!` for one using it
+    Ascii,
+    /// The status is only told apart by the colors
+    None,
+}
+
+impl FromStr for Symbols {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Symbols, &'static str> {
+        let comparison_string = String::from(s).to_lowercase();
+        match comparison_string.as_str() {
+            "emoji" => Ok(Symbols::Emoji),
+            "ascii" => Ok(Symbols::Ascii),
+            "none" => Ok(Symbols::None),
+            _ => Err("invalid symbols"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum CrateDetectionStatus {
     NoneDetectedForbidsUnsafe,
+    /// Like `NoneDetectedForbidsUnsafe`, but declared with the weaker
+    /// `#![deny(unsafe_code)]`, which can be overridden within the crate.
+    NoneDetectedDeniesUnsafe,
+    /// Like `NoneDetectedForbidsUnsafe`, but declared within
+    /// `#![cfg_attr(<condition>, ...)]`, so only when the condition holds.
+    NoneDetectedConditionallyForbidsUnsafe,
     NoneDetectedAllowsUnsafe,
     UnsafeDetected,
 }
 
+impl From<&UnsafeInfo> for CrateDetectionStatus {
+    fn from(unsafe_info: &UnsafeInfo) -> Self {
+        match (
+            unsafe_info.forbids_unsafe,
+            unsafe_info.denies_unsafe,
+            unsafe_info.conditionally_forbids_unsafe,
+            unsafe_info.used.has_unsafe(),
+        ) {
+            (_, _, _, true) => CrateDetectionStatus::UnsafeDetected,
+            (true, _, _, false) => {
+                CrateDetectionStatus::NoneDetectedForbidsUnsafe
+            }
+            (false, true, _, false) => {
+                CrateDetectionStatus::NoneDetectedDeniesUnsafe
+            }
+            (false, false, true, false) => {
+                CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe
+            }
+            (false, false, false, false) => {
+                CrateDetectionStatus::NoneDetectedAllowsUnsafe
+            }
+        }
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum RawChunk<'a> {
     Argument(&'a str),
@@ -62,8 +146,10 @@ pub enum RawChunk<'a> {
 #[derive(Clone, Copy)]
 pub enum SymbolKind {
     Lock = 0,
-    QuestionMark = 1,
-    Rads = 2,
+    OpenLock = 1,
+    KeyLock = 2,
+    QuestionMark = 3,
+    Rads = 4,
 }
 
 #[derive(Debug)]
@@ -100,7 +186,7 @@ mod format_tests {
         input_string,
         expected_enum_result,
         case("ascii", Ok(Charset::Ascii)),
-        case("githubmarkdown", Ok(Charset::GitHubMarkdown)),
+        case("githubmarkdown", Err("invalid charset")),
         case("utf8", Ok(Charset::Utf8)),
         case("UTF8", Ok(Charset::Utf8)),
         case("invalid_str", Err("invalid charset"))
@@ -112,6 +198,21 @@ mod format_tests {
         assert_eq!(Charset::from_str(input_string), expected_enum_result);
     }
 
+    #[rstest(
+        input_string,
+        expected_enum_result,
+        case("emoji", Ok(Symbols::Emoji)),
+        case("Ascii", Ok(Symbols::Ascii)),
+        case("none", Ok(Symbols::None)),
+        case("utf8", Err("invalid symbols"))
+    )]
+    fn symbols_from_str_test(
+        input_string: &str,
+        expected_enum_result: Result<Symbols, &'static str>,
+    ) {
+        assert_eq!(Symbols::from_str(input_string), expected_enum_result);
+    }
+
     #[rstest]
     fn get_kind_group_name_test() {
         assert_eq!(
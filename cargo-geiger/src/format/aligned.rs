@@ -0,0 +1,152 @@
+use crate::format::CrateDetectionStatus;
+
+use cargo_geiger_serde::{Count, CounterBlock};
+use cargo_metadata::semver::Version;
+
+const ALIGNED_HEADER: [&str; 8] = [
+    "Crate",
+    "Version",
+    "Status",
+    "Functions",
+    "Expressions",
+    "Impls",
+    "Traits",
+    "Methods",
+];
+
+/// The number of leading columns which are left aligned, the counts after
+/// them are right aligned
+const TEXT_COLUMN_COUNT: usize = 3;
+
+const COLUMN_SEPARATOR: &str = "  ";
+
+/// A crate of the aligned output
+pub struct AlignedRow {
+    pub name: String,
+    pub version: Version,
+    pub status: CrateDetectionStatus,
+    pub used: CounterBlock,
+    pub unused: CounterBlock,
+}
+
+/// A header and a line for each crate, sorted by name and version, with every
+/// field padded to the width of its column, so that an unchanged crate is
+/// printed as the same bytes in each run. The counts are the unsafe code used
+/// by the build and found in total, such as `2/3`.
+///
+/// The column widths are the widest field of each column, not constants, so
+/// a crate with a longer name or count than before realigns the whole output.
+pub fn create_aligned_lines(rows: Vec<AlignedRow>) -> Vec<String> {
+    let mut rows = rows;
+    rows.sort_by(|a, b| {
+        a.name.cmp(&b.name).then_with(|| a.version.cmp(&b.version))
+    });
+
+    let mut fields = vec![ALIGNED_HEADER
+        .iter()
+        .map(|header| header.to_string())
+        .collect::<Vec<String>>()];
+    for row in rows {
+        let count_field = |used: &Count, unused: &Count| {
+            format!("{}/{}", used.unsafe_, used.unsafe_ + unused.unsafe_)
+        };
+        fields.push(vec![
+            row.name,
+            row.version.to_string(),
+            status_label(&row.status).to_string(),
+            count_field(&row.used.functions, &row.unused.functions),
+            count_field(&row.used.exprs, &row.unused.exprs),
+            count_field(&row.used.item_impls, &row.unused.item_impls),
+            count_field(&row.used.item_traits, &row.unused.item_traits),
+            count_field(&row.used.methods, &row.unused.methods),
+        ]);
+    }
+
+    let column_widths = (0..ALIGNED_HEADER.len())
+        .map(|column| {
+            fields
+                .iter()
+                .map(|line_fields| line_fields[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<usize>>();
+
+    fields
+        .iter()
+        .map(|line_fields| {
+            line_fields
+                .iter()
+                .zip(&column_widths)
+                .enumerate()
+                .map(|(column, (field, width))| {
+                    if column < TEXT_COLUMN_COUNT {
+                        format!("{: <width$}", field, width = width)
+                    } else {
+                        format!("{: >width$}", field, width = width)
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(COLUMN_SEPARATOR)
+        })
+        .collect()
+}
+
+pub fn status_label(status: &CrateDetectionStatus) -> &'static str {
+    match status {
+        CrateDetectionStatus::NoneDetectedForbidsUnsafe => "forbids",
+        CrateDetectionStatus::NoneDetectedDeniesUnsafe => "denies",
+        CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe => {
+            "conditionally-forbids"
+        }
+        CrateDetectionStatus::NoneDetectedAllowsUnsafe => "allows",
+        CrateDetectionStatus::UnsafeDetected => "unsafe",
+    }
+}
+
+#[cfg(test)]
+mod aligned_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest]
+    fn create_aligned_lines_test() {
+        let mut tokio_used = CounterBlock::default();
+        tokio_used.exprs.unsafe_ = 120;
+        let mut tokio_unused = CounterBlock::default();
+        tokio_unused.exprs.unsafe_ = 3;
+        tokio_unused.functions.unsafe_ = 1;
+        let rows = vec![
+            AlignedRow {
+                name: String::from("tokio"),
+                version: Version::new(1, 21, 0),
+                status: CrateDetectionStatus::UnsafeDetected,
+                used: tokio_used,
+                unused: tokio_unused,
+            },
+            AlignedRow {
+                name: String::from("bytes"),
+                version: Version::new(1, 2, 1),
+                status: CrateDetectionStatus::NoneDetectedForbidsUnsafe,
+                used: CounterBlock::default(),
+                unused: CounterBlock::default(),
+            },
+        ];
+
+        assert_eq!(
+            create_aligned_lines(rows),
+            vec![
+                String::from(
+                    "Crate  Version  Status   Functions  Expressions  Impls  Traits  Methods"
+                ),
+                String::from(
+                    "bytes  1.2.1    forbids        0/0          0/0    0/0     0/0      0/0"
+                ),
+                String::from(
+                    "tokio  1.21.0   unsafe         0/1      120/123    0/0     0/0      0/0"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use crate::format::table::UNSAFE_CATEGORY_NAMES;
+
+use cargo_geiger_serde::{CounterBlock, PackageId, UnsafeInfo};
+use cargo_metadata::semver::Version;
+use serde::{Deserialize, Serialize};
+
+/// The unsafe code of a category with `--categories`: that of all crates, and
+/// that of each crate using unsafe code of the category
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CategoryBreakdown {
+    pub category: String,
+    pub used: u64,
+    pub found: u64,
+    /// Ordered by the unsafe code used by the build, most first, then by the
+    /// unsafe code found and by name
+    pub crates: Vec<CrateCategoryUsage>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CrateCategoryUsage {
+    pub name: String,
+    pub version: Version,
+    pub used: u64,
+    pub found: u64,
+}
+
+/// A breakdown for each category, in the order of the table columns. A crate
+/// given several times is counted once.
+pub fn category_breakdowns(
+    crates: &[(PackageId, UnsafeInfo)],
+) -> Vec<CategoryBreakdown> {
+    let mut unique_crates = crates.iter().collect::<Vec<_>>();
+    unique_crates.sort_by(|(a, _), (b, _)| a.cmp(b));
+    unique_crates.dedup_by(|(a, _), (b, _)| a == b);
+
+    UNSAFE_CATEGORY_NAMES
+        .iter()
+        .enumerate()
+        .map(|(index, category)| {
+            let mut crate_usages = unique_crates
+                .iter()
+                .map(|(package_id, unsafe_info)| {
+                    let used = category_count(&unsafe_info.used, index);
+                    CrateCategoryUsage {
+                        name: package_id.name.clone(),
+                        version: package_id.version.clone(),
+                        used,
+                        found: used
+                            + category_count(&unsafe_info.unused, index),
+                    }
+                })
+                .filter(|crate_usage| crate_usage.found > 0)
+                .collect::<Vec<_>>();
+            crate_usages.sort_by(|a, b| {
+                b.used
+                    .cmp(&a.used)
+                    .then(b.found.cmp(&a.found))
+                    .then(a.name.cmp(&b.name))
+                    .then(a.version.cmp(&b.version))
+            });
+            CategoryBreakdown {
+                category: category.to_string(),
+                used: crate_usages.iter().map(|usage| usage.used).sum(),
+                found: crate_usages.iter().map(|usage| usage.found).sum(),
+                crates: crate_usages,
+            }
+        })
+        .collect()
+}
+
+/// The lines printed below the tree with `--categories`, a line for each
+/// category with its totals, followed by a line for each of its crates
+pub fn create_categories_lines(
+    category_breakdowns: &[CategoryBreakdown],
+) -> Vec<String> {
+    let mut categories_lines = vec![String::from(
+        "Unsafe code used by the build/found, per category:",
+    )];
+    for category_breakdown in category_breakdowns {
+        categories_lines.push(format!(
+            "    {} {}/{}",
+            category_breakdown.category,
+            category_breakdown.used,
+            category_breakdown.found
+        ));
+        for crate_usage in &category_breakdown.crates {
+            categories_lines.push(format!(
+                "        {} {}: {}/{}",
+                crate_usage.name,
+                crate_usage.version,
+                crate_usage.used,
+                crate_usage.found
+            ));
+        }
+    }
+    categories_lines.push(String::new());
+    categories_lines
+}
+
+/// The unsafe count of the category at the index of `UNSAFE_CATEGORY_NAMES`
+fn category_count(counter_block: &CounterBlock, index: usize) -> u64 {
+    [
+        &counter_block.functions,
+        &counter_block.exprs,
+        &counter_block.item_impls,
+        &counter_block.item_traits,
+        &counter_block.methods,
+    ][index]
+        .unsafe_
+}
+
+#[cfg(test)]
+mod categories_tests {
+    use super::*;
+
+    use cargo_geiger_serde::Source;
+    use rstest::*;
+    use url::Url;
+
+    fn create_crates() -> Vec<(PackageId, UnsafeInfo)> {
+        [("foo", 1, 2), ("bar", 3, 0), ("baz", 0, 0), ("foo", 1, 2)]
+            .iter()
+            .map(|(name, used_exprs, unused_exprs)| {
+                let mut unsafe_info = UnsafeInfo::default();
+                unsafe_info.used.exprs.unsafe_ = *used_exprs;
+                unsafe_info.unused.exprs.unsafe_ = *unused_exprs;
+                (
+                    PackageId {
+                        name: name.to_string(),
+                        version: Version::new(0, 1, 0),
+                        source: Source::Path(
+                            Url::parse("file:///foo").unwrap(),
+                        ),
+                    },
+                    unsafe_info,
+                )
+            })
+            .collect()
+    }
+
+    #[rstest]
+    fn category_breakdowns_test() {
+        let category_breakdowns = category_breakdowns(&create_crates());
+
+        assert_eq!(category_breakdowns.len(), 5);
+        assert_eq!(category_breakdowns[0].crates, vec![]);
+        assert_eq!(
+            category_breakdowns[1],
+            CategoryBreakdown {
+                category: String::from("expressions"),
+                used: 4,
+                found: 6,
+                crates: vec![
+                    CrateCategoryUsage {
+                        name: String::from("bar"),
+                        version: Version::new(0, 1, 0),
+                        used: 3,
+                        found: 3,
+                    },
+                    CrateCategoryUsage {
+                        name: String::from("foo"),
+                        version: Version::new(0, 1, 0),
+                        used: 1,
+                        found: 3,
+                    },
+                ],
+            }
+        );
+    }
+
+    #[rstest]
+    fn create_categories_lines_test() {
+        assert_eq!(
+            create_categories_lines(&category_breakdowns(&create_crates())),
+            vec![
+                "Unsafe code used by the build/found, per category:",
+                "    functions 0/0",
+                "    expressions 4/6",
+                "        bar 0.1.0: 3/3",
+                "        foo 0.1.0: 1/3",
+                "    impls 0/0",
+                "    traits 0/0",
+                "    methods 0/0",
+                "",
+            ]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use crate::format::CrateDetectionStatus;
+
+use cargo_geiger_serde::SafetyReport;
+
+/// The columns of the Csv output, in a fixed order which scripts can rely on
+const CSV_HEADER: [&str; 14] = [
+    "name",
+    "version",
+    "functions_used",
+    "functions_unused",
+    "expressions_used",
+    "expressions_unused",
+    "impls_used",
+    "impls_unused",
+    "traits_used",
+    "traits_unused",
+    "methods_used",
+    "methods_unused",
+    "status",
+    "source",
+];
+
+/// A header row and a row for each crate in the report, ordered by crate,
+/// with the unsafe code used and not used by the build in each category and
+/// the `CrateDetectionStatus` of the crate. With the `source`, the name and
+/// version of a row tell the crate apart from those of other sources.
+pub fn create_csv_lines(report: &SafetyReport) -> Vec<String> {
+    let mut entries = report.packages.values().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+
+    let mut csv_lines = vec![CSV_HEADER.join(",")];
+    for entry in entries {
+        let used = &entry.unsafety.used;
+        let unused = &entry.unsafety.unused;
+        let fields = [
+            entry.package.id.name.clone(),
+            entry.package.id.version.to_string(),
+            used.functions.unsafe_.to_string(),
+            unused.functions.unsafe_.to_string(),
+            used.exprs.unsafe_.to_string(),
+            unused.exprs.unsafe_.to_string(),
+            used.item_impls.unsafe_.to_string(),
+            unused.item_impls.unsafe_.to_string(),
+            used.item_traits.unsafe_.to_string(),
+            unused.item_traits.unsafe_.to_string(),
+            used.methods.unsafe_.to_string(),
+            unused.methods.unsafe_.to_string(),
+            format!("{:?}", CrateDetectionStatus::from(&entry.unsafety)),
+            entry.package.id.source.to_string(),
+        ];
+        csv_lines.push(
+            fields
+                .iter()
+                .map(|field| escape_csv_field(field))
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+    }
+    csv_lines
+}
+
+/// Quote a field containing a separator, a quote or a line break, doubling
+/// its quotes
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod csv_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::{PackageInfo, UnsafeInfo};
+    use rstest::*;
+
+    #[rstest]
+    fn create_csv_lines_test() {
+        let report = create_safety_report(
+            vec![("foo", 3, false), ("bar", 0, true)]
+                .into_iter()
+                .map(|(name, unsafe_exprs, forbids_unsafe)| {
+                    let mut unsafety = UnsafeInfo {
+                        forbids_unsafe,
+                        ..create_unsafe_info(unsafe_exprs)
+                    };
+                    unsafety.unused.functions.unsafe_ = 1;
+                    (PackageInfo::new(create_path_package_id(name)), unsafety)
+                })
+                .collect(),
+        );
+
+        assert_eq!(
+            create_csv_lines(&report),
+            vec![
+                String::from("name,version,functions_used,functions_unused,expressions_used,expressions_unused,impls_used,impls_unused,traits_used,traits_unused,methods_used,methods_unused,status,source"),
+                String::from("bar,0.1.0,0,1,0,0,0,0,0,0,0,0,NoneDetectedForbidsUnsafe,path+file:///bar"),
+                String::from("foo,0.1.0,0,1,3,0,0,0,0,0,0,0,UnsafeDetected,path+file:///foo"),
+            ]
+        );
+    }
+
+    #[rstest(
+        input_field,
+        expected_field,
+        case("foo", "foo"),
+        case("foo,bar", "\"foo,bar\""),
+        case("foo \"bar\"", "\"foo \"\"bar\"\"\"")
+    )]
+    fn escape_csv_field_test(input_field: &str, expected_field: &str) {
+        assert_eq!(escape_csv_field(input_field), expected_field);
+    }
+}
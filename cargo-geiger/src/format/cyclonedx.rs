@@ -0,0 +1,197 @@
+use crate::format::source_groups::SourceGroup;
+use crate::format::table::UNSAFE_CATEGORY_NAMES;
+
+use cargo_geiger_serde::{CounterBlock, ReportEntry, SafetyReport};
+use serde::Serialize;
+
+const BOM_FORMAT: &str = "CycloneDX";
+
+const SPEC_VERSION: &str = "1.4";
+
+/// The namespace of the component properties written by cargo-geiger, as
+/// recommended by the `CycloneDX` property taxonomy
+const PROPERTY_PREFIX: &str = "cargo-geiger";
+
+/// A `CycloneDX` 1.4 bill of materials,
+/// <https://cyclonedx.org/docs/1.4/json/>, with only the properties needed to
+/// carry the unsafe metrics of each component, for the tools merging them with
+/// an SBOM from another tool
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxBom {
+    bom_format: &'static str,
+    spec_version: &'static str,
+    version: u32,
+    metadata: CycloneDxMetadata,
+    components: Vec<CycloneDxComponent>,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDxMetadata {
+    tools: Vec<CycloneDxTool>,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDxTool {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDxComponent {
+    #[serde(rename = "type")]
+    type_: &'static str,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    name: String,
+    version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    purl: Option<String>,
+    properties: Vec<CycloneDxProperty>,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+struct CycloneDxProperty {
+    name: String,
+    value: String,
+}
+
+/// A `CycloneDX` bill of materials with a library component for each crate in
+/// the report, ordered by crate. The properties of a component are whether
+/// the crate forbids unsafe code and its unsafe counts used by the build and
+/// not, such as `cargo-geiger:used:functions`. Only the crates from crates.io
+/// have a package URL, which the crates of an SBOM are matched by.
+pub fn create_cyclonedx_line(report: &SafetyReport) -> String {
+    let mut entries = report.packages.values().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+
+    let components = entries
+        .into_iter()
+        .map(|entry| {
+            let id = &entry.package.id;
+            let purl = (SourceGroup::from_source(&id.source)
+                == SourceGroup::CratesIo)
+                .then(|| format!("pkg:cargo/{}@{}", id.name, id.version));
+            CycloneDxComponent {
+                type_: "library",
+                bom_ref: purl.clone().unwrap_or_else(|| {
+                    format!("{}@{} ({:?})", id.name, id.version, id.source)
+                }),
+                name: id.name.clone(),
+                version: id.version.to_string(),
+                purl,
+                properties: component_properties(entry),
+            }
+        })
+        .collect();
+
+    serde_json::to_string(&CycloneDxBom {
+        bom_format: BOM_FORMAT,
+        spec_version: SPEC_VERSION,
+        version: 1,
+        metadata: CycloneDxMetadata {
+            tools: vec![CycloneDxTool {
+                name: env!("CARGO_PKG_NAME"),
+                version: env!("CARGO_PKG_VERSION"),
+            }],
+        },
+        components,
+    })
+    .unwrap()
+}
+
+fn component_properties(entry: &ReportEntry) -> Vec<CycloneDxProperty> {
+    let property = |name: &str, value: String| CycloneDxProperty {
+        name: format!("{}:{}", PROPERTY_PREFIX, name),
+        value,
+    };
+    let unsafety = &entry.unsafety;
+    let mut properties = vec![
+        property("forbids-unsafe", unsafety.forbids_unsafe.to_string()),
+        property("denies-unsafe", unsafety.denies_unsafe.to_string()),
+    ];
+    for (usage, counter_block) in
+        [("used", &unsafety.used), ("unused", &unsafety.unused)]
+    {
+        for (category, count) in UNSAFE_CATEGORY_NAMES
+            .iter()
+            .zip(unsafe_counts(counter_block))
+        {
+            properties.push(property(
+                &format!("{}:{}", usage, category),
+                count.to_string(),
+            ));
+        }
+    }
+    properties
+}
+
+fn unsafe_counts(counter_block: &CounterBlock) -> [u64; 5] {
+    [
+        counter_block.functions.unsafe_,
+        counter_block.exprs.unsafe_,
+        counter_block.item_impls.unsafe_,
+        counter_block.item_traits.unsafe_,
+        counter_block.methods.unsafe_,
+    ]
+}
+
+#[cfg(test)]
+mod cyclonedx_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_registry_package_id,
+        create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::{PackageInfo, UnsafeInfo};
+    use rstest::*;
+
+    #[rstest]
+    fn create_cyclonedx_line_test() {
+        let report = create_safety_report(vec![
+            (
+                PackageInfo::new(create_registry_package_id("foo", "0.1.0")),
+                create_unsafe_info(3),
+            ),
+            (
+                PackageInfo::new(create_path_package_id("bar")),
+                UnsafeInfo {
+                    forbids_unsafe: true,
+                    ..create_unsafe_info(3)
+                },
+            ),
+        ]);
+
+        let cyclonedx_line = create_cyclonedx_line(&report);
+
+        let bom_value =
+            serde_json::from_str::<serde_json::Value>(&cyclonedx_line).unwrap();
+        assert_eq!(bom_value["bomFormat"], "CycloneDX");
+        assert_eq!(bom_value["specVersion"], "1.4");
+        assert_eq!(bom_value["metadata"]["tools"][0]["name"], "cargo-geiger");
+        let components = bom_value["components"].as_array().unwrap();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0]["name"], "bar");
+        assert!(components[0].get("purl").is_none());
+        assert_eq!(components[1]["name"], "foo");
+        assert_eq!(components[1]["purl"], "pkg:cargo/foo@0.1.0");
+        assert_eq!(components[1]["bom-ref"], "pkg:cargo/foo@0.1.0");
+        assert_eq!(
+            components[0]["properties"][0],
+            serde_json::json!({
+                "name": "cargo-geiger:forbids-unsafe",
+                "value": "true"
+            })
+        );
+        assert_eq!(
+            components[1]["properties"][3],
+            serde_json::json!({
+                "name": "cargo-geiger:used:expressions",
+                "value": "3"
+            })
+        );
+        assert_eq!(components[1]["properties"].as_array().unwrap().len(), 12);
+    }
+}
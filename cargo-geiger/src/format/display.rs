@@ -2,13 +2,17 @@ use crate::format::pattern::Pattern;
 use crate::format::Chunk;
 use crate::mapping::{CargoMetadataParameters, GetPackageIdInformation};
 
+use cargo_geiger_serde::{Count, UnsafeInfo};
+use cargo_metadata::semver::Version;
 use cargo_metadata::PackageId;
+use krates::Krates;
 use std::fmt;
 
 pub struct Display<'a> {
     pub cargo_metadata_parameters: &'a CargoMetadataParameters<'a>,
     pub pattern: &'a Pattern,
     pub package: &'a PackageId,
+    pub unsafe_info: Option<&'a UnsafeInfo>,
 }
 
 impl<'a> fmt::Display for Display<'a> {
@@ -36,7 +40,25 @@ impl<'a> fmt::Display for Display<'a> {
                             self.cargo_metadata_parameters.krates,
                         )
                     {
-                        (write!(fmt, "{} {}", package_name, package_version))?
+                        (write!(fmt, "{} {}", package_name, package_version))?;
+                        // A crate sharing its name and version with another
+                        // one, such as a git fork of it, is told apart by its
+                        // source, unless the pattern prints it anyway.
+                        if !self.pattern.chunks.contains(&Chunk::Source)
+                            && has_same_name_and_version(
+                                self.cargo_metadata_parameters.krates,
+                                &package_name,
+                                &package_version,
+                            )
+                        {
+                            if let Some(source) =
+                                self.package.get_package_id_source(
+                                    self.cargo_metadata_parameters.krates,
+                                )
+                            {
+                                (write!(fmt, " ({})", source))?
+                            }
+                        }
                     } else {
                         eprintln!("Failed to format Package: {}", self.package)
                     }
@@ -51,12 +73,95 @@ impl<'a> fmt::Display for Display<'a> {
                         (write!(fmt, "{}", repository))?
                     }
                 }
+                Chunk::Source => {
+                    if let Some(source) = self.package.get_package_id_source(
+                        self.cargo_metadata_parameters.krates,
+                    ) {
+                        (write!(fmt, "{}", source))?
+                    }
+                }
+                Chunk::UnsafeExprs => {
+                    if let Some(unsafe_info) = self.unsafe_info {
+                        fmt.write_str(&format_unsafe_count(
+                            &unsafe_info.used.exprs,
+                            &unsafe_info.unused.exprs,
+                        ))?
+                    }
+                }
+                Chunk::UnsafeFunctions => {
+                    if let Some(unsafe_info) = self.unsafe_info {
+                        fmt.write_str(&format_unsafe_count(
+                            &unsafe_info.used.functions,
+                            &unsafe_info.unused.functions,
+                        ))?
+                    }
+                }
+                Chunk::UnsafeImpls => {
+                    if let Some(unsafe_info) = self.unsafe_info {
+                        fmt.write_str(&format_unsafe_count(
+                            &unsafe_info.used.item_impls,
+                            &unsafe_info.unused.item_impls,
+                        ))?
+                    }
+                }
+                Chunk::UnsafePerKloc => {
+                    if let Some(unsafe_info) = self.unsafe_info {
+                        fmt.write_str(&format_unsafe_per_kloc(unsafe_info))?
+                    }
+                }
+                Chunk::UnsafeTraits => {
+                    if let Some(unsafe_info) = self.unsafe_info {
+                        fmt.write_str(&format_unsafe_count(
+                            &unsafe_info.used.item_traits,
+                            &unsafe_info.unused.item_traits,
+                        ))?
+                    }
+                }
+                Chunk::Version => {
+                    if let Some((_, package_version)) =
+                        self.package.get_package_id_name_and_version(
+                            self.cargo_metadata_parameters.krates,
+                        )
+                    {
+                        (write!(fmt, "{}", package_version))?
+                    } else {
+                        eprintln!("Failed to format Version: {}", self.package)
+                    }
+                }
             }
         }
         Ok(())
     }
 }
 
+/// Whether another crate in the graph has the same name and version
+fn has_same_name_and_version(
+    krates: &Krates,
+    package_name: &str,
+    package_version: &Version,
+) -> bool {
+    krates
+        .krates_by_name(package_name)
+        .filter(|(_, node)| &node.krate.version == package_version)
+        .count()
+        > 1
+}
+
+/// The unsafe count of a category as used by the build and found in total,
+/// such as `2/3`, as in the table columns
+fn format_unsafe_count(used: &Count, unused: &Count) -> String {
+    format!("{}/{}", used.unsafe_, used.unsafe_ + unused.unsafe_)
+}
+
+/// The unsafe expressions used by the build per thousand lines of code, such
+/// as `12.5`, or `n/a` when no lines of code were counted
+fn format_unsafe_per_kloc(unsafe_info: &UnsafeInfo) -> String {
+    match unsafe_info.unsafe_per_kloc() {
+        Some(unsafe_per_kloc) => format!("{:.1}", unsafe_per_kloc),
+        None => String::from("n/a"),
+    }
+}
+
 #[cfg(test)]
 pub mod display_tests {
     use super::*;
@@ -82,6 +187,14 @@ pub mod display_tests {
         case(
             Pattern::new(vec![Chunk::Repository]),
             "https://github.com/rust-secure-code/cargo-geiger"
+        ),
+        case(
+            Pattern::new(vec![Chunk::Source]),
+            concat!("path+", env!("CARGO_MANIFEST_DIR"))
+        ),
+        case(
+            Pattern::new(vec![Chunk::Version]),
+            env!("CARGO_PKG_VERSION")
         )
     )]
     fn display_format_fmt_test(
@@ -107,8 +220,60 @@ pub mod display_tests {
             },
             pattern: &input_pattern,
             package: &package_id,
+            unsafe_info: None,
         };
 
         assert_eq!(format!("{}", display), expected_formatted_string);
     }
+
+    #[rstest(
+        input_used_unsafe,
+        input_unused_unsafe,
+        expected_unsafe_count,
+        case(0, 0, "0/0"),
+        case(2, 1, "2/3"),
+        case(0, 4, "0/4")
+    )]
+    fn format_unsafe_count_test(
+        input_used_unsafe: u64,
+        input_unused_unsafe: u64,
+        expected_unsafe_count: &str,
+    ) {
+        let used = Count {
+            safe: 5,
+            unsafe_: input_used_unsafe,
+        };
+        let unused = Count {
+            safe: 5,
+            unsafe_: input_unused_unsafe,
+        };
+
+        assert_eq!(format_unsafe_count(&used, &unused), expected_unsafe_count);
+    }
+
+    #[rstest(
+        input_used_unsafe_exprs,
+        input_lines_of_code,
+        expected_unsafe_per_kloc,
+        case(5, Some(400), "12.5"),
+        case(0, Some(1000), "0.0"),
+        case(5, Some(0), "n/a"),
+        case(5, None, "n/a")
+    )]
+    fn format_unsafe_per_kloc_test(
+        input_used_unsafe_exprs: u64,
+        input_lines_of_code: Option<u64>,
+        expected_unsafe_per_kloc: &str,
+    ) {
+        let mut unsafe_info = UnsafeInfo {
+            lines_of_code: input_lines_of_code,
+            ..Default::default()
+        };
+        unsafe_info.used.exprs.unsafe_ = input_used_unsafe_exprs;
+
+        assert_eq!(
+            format_unsafe_per_kloc(&unsafe_info),
+            expected_unsafe_per_kloc
+        );
+    }
 }
@@ -0,0 +1,153 @@
+use crate::format::CrateDetectionStatus;
+
+use cargo_geiger_serde::SafetyReport;
+use std::collections::HashMap;
+
+/// A Graphviz digraph of the dependency graph, with a node for each crate in
+/// the report and an edge to each of its dependencies, dashed for build
+/// dependencies and dotted for dev dependencies. A node is labeled with the
+/// crate and its unsafe code used by the build and found in total, such as
+/// `2/3`, and filled with the color of its status: green if it forbids unsafe
+/// code, yellow if it denies it, red if it uses it. Nodes and edges are
+/// ordered by crate, so that the same graph is written the same way.
+pub fn create_dot_lines(report: &SafetyReport) -> Vec<String> {
+    let mut entries = report.packages.values().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+    let node_indices = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| (&entry.package.id, index))
+        .collect::<HashMap<_, _>>();
+
+    let mut dot_lines = vec![
+        String::from("digraph dependencies {"),
+        String::from("    node [shape=box, style=filled, fillcolor=white];"),
+    ];
+    for entry in &entries {
+        let used = entry.unsafety.used.total_unsafe();
+        let total = used + entry.unsafety.unused.total_unsafe();
+        let status = CrateDetectionStatus::from(&entry.unsafety);
+        dot_lines.push(format!(
+            "    n{} [label=\"{} {}\\n{}/{}\"{}];",
+            node_indices[&entry.package.id],
+            escape_dot(&entry.package.id.name),
+            entry.package.id.version,
+            used,
+            total,
+            status_attributes(&status)
+        ));
+    }
+    for entry in &entries {
+        let package_info = &entry.package;
+        let edge_groups = [
+            (&package_info.dependencies, ""),
+            (&package_info.build_dependencies, " [style=dashed]"),
+            (&package_info.dev_dependencies, " [style=dotted]"),
+        ];
+        for (dependencies, edge_attributes) in edge_groups {
+            let mut dependency_indices = dependencies
+                .iter()
+                .filter_map(|dependency| node_indices.get(dependency).copied())
+                .collect::<Vec<_>>();
+            dependency_indices.sort_unstable();
+            for dependency_index in dependency_indices {
+                dot_lines.push(format!(
+                    "    n{} -> n{}{};",
+                    node_indices[&package_info.id],
+                    dependency_index,
+                    edge_attributes
+                ));
+            }
+        }
+    }
+    dot_lines.push(String::from("}"));
+    dot_lines
+}
+
+/// The fill of a node, following the colors `colorize` uses in a terminal
+fn status_attributes(status: &CrateDetectionStatus) -> &'static str {
+    match status {
+        CrateDetectionStatus::NoneDetectedForbidsUnsafe => {
+            ", fillcolor=palegreen"
+        }
+        CrateDetectionStatus::NoneDetectedDeniesUnsafe => {
+            ", fillcolor=lightgoldenrod"
+        }
+        CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe => {
+            ", fillcolor=lightcyan"
+        }
+        CrateDetectionStatus::NoneDetectedAllowsUnsafe => "",
+        CrateDetectionStatus::UnsafeDetected => {
+            ", fillcolor=lightcoral, penwidth=2"
+        }
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod dot_tests {
+    use super::*;
+
+    use crate::lib_tests::{create_path_package_id, create_safety_report};
+
+    use cargo_geiger_serde::{DependencyKind, PackageInfo, UnsafeInfo};
+    use rstest::*;
+
+    #[rstest]
+    fn create_dot_lines_test() {
+        let mut root = PackageInfo::new(create_path_package_id("root"));
+        root.add_dependency(
+            create_path_package_id("bar"),
+            DependencyKind::Normal,
+        );
+        root.add_dependency(
+            create_path_package_id("baz"),
+            DependencyKind::Build,
+        );
+        root.add_dependency(
+            create_path_package_id("missing"),
+            DependencyKind::Normal,
+        );
+        let mut bar_unsafety = UnsafeInfo::default();
+        bar_unsafety.used.exprs.unsafe_ = 2;
+        bar_unsafety.unused.functions.unsafe_ = 1;
+        let baz_unsafety = UnsafeInfo {
+            forbids_unsafe: true,
+            ..Default::default()
+        };
+
+        let report = create_safety_report(vec![
+            (root, UnsafeInfo::default()),
+            (
+                PackageInfo::new(create_path_package_id("bar")),
+                bar_unsafety,
+            ),
+            (
+                PackageInfo::new(create_path_package_id("baz")),
+                baz_unsafety,
+            ),
+        ]);
+
+        assert_eq!(
+            create_dot_lines(&report),
+            vec![
+                "digraph dependencies {",
+                "    node [shape=box, style=filled, fillcolor=white];",
+                "    n0 [label=\"bar 0.1.0\\n2/3\", fillcolor=lightcoral, penwidth=2];",
+                "    n1 [label=\"baz 0.1.0\\n0/0\", fillcolor=palegreen];",
+                "    n2 [label=\"root 0.1.0\\n0/0\"];",
+                "    n2 -> n0;",
+                "    n2 -> n1 [style=dashed];",
+                "}",
+            ]
+        );
+    }
+
+    #[rstest]
+    fn escape_dot_test() {
+        assert_eq!(escape_dot("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
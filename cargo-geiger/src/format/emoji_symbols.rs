@@ -1,52 +1,80 @@
-use crate::format::print_config::{colorize, OutputFormat};
-use crate::format::{CrateDetectionStatus, SymbolKind};
+use crate::format::print_config::{
+    colorize, ColorScheme, ColorWhen, OutputFormat,
+};
+use crate::format::{CrateDetectionStatus, SymbolKind, Symbols};
 
 use colored::ColoredString;
-use terminal_supports_emoji::{Stream, supports_emoji};
 
 pub struct EmojiSymbols {
-    emojis: [&'static str; 3],
-    fallbacks: [ColoredString; 3],
-    output_format: OutputFormat,
+    emojis: [&'static str; 5],
+    fallbacks: [ColoredString; 5],
+    symbols: Symbols,
 }
 
 impl EmojiSymbols {
     pub fn emoji(&self, kind: SymbolKind) -> Box<dyn std::fmt::Display> {
         let idx = kind as usize;
-        if self.will_output_emoji() {
-            Box::new(self.emojis[idx])
-        } else {
-            Box::new(self.fallbacks[idx].clone())
+        match self.symbols {
+            Symbols::Emoji => Box::new(self.emojis[idx]),
+            Symbols::Ascii => Box::new(self.fallbacks[idx].clone()),
+            Symbols::None => Box::new(""),
         }
     }
 
-    pub fn new(output_format: OutputFormat) -> EmojiSymbols {
+    pub fn new(
+        color: ColorWhen,
+        color_scheme: ColorScheme,
+        output_format: OutputFormat,
+        symbols: Symbols,
+    ) -> EmojiSymbols {
         Self {
-            emojis: ["🔒", "❓", "☢️"],
+            emojis: ["🔒", "🔓", "🔐", "❓", "☢️"],
             fallbacks: [
                 colorize(
+                    color,
+                    color_scheme,
                     &CrateDetectionStatus::NoneDetectedForbidsUnsafe,
                     output_format,
                     String::from(":)"),
                 ),
                 colorize(
+                    color,
+                    color_scheme,
+                    &CrateDetectionStatus::NoneDetectedDeniesUnsafe,
+                    output_format,
+                    String::from(":|"),
+                ),
+                colorize(
+                    color,
+                    color_scheme,
+                    &CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe,
+                    output_format,
+                    String::from(":/"),
+                ),
+                colorize(
+                    color,
+                    color_scheme,
                     &CrateDetectionStatus::NoneDetectedAllowsUnsafe,
                     output_format,
                     String::from("?"),
                 ),
                 colorize(
+                    color,
+                    color_scheme,
                     &CrateDetectionStatus::UnsafeDetected,
                     output_format,
                     String::from("!"),
                 ),
             ],
-            output_format,
+            symbols,
         }
     }
 
     pub fn will_output_emoji(&self) -> bool {
-        (self.output_format == OutputFormat::Utf8
-            && supports_emoji(Stream::Stdout))
-            || self.output_format == OutputFormat::GitHubMarkdown
+        self.symbols == Symbols::Emoji
+    }
+
+    pub fn will_output_symbols(&self) -> bool {
+        self.symbols != Symbols::None
     }
 }
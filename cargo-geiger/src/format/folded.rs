@@ -0,0 +1,55 @@
+use cargo_geiger_serde::CounterBlock;
+
+/// A crate of the folded output, with the dependency chain leading to it from
+/// the root crate
+pub struct FoldedRow {
+    /// The crate names from the root crate to this crate
+    pub stack: Vec<String>,
+    pub used: CounterBlock,
+}
+
+/// A line in the folded stack format of `flamegraph.pl` for each crate, such
+/// as `root;tokio;mio 42`, where the value is the unsafe code used by the
+/// build in that crate alone. Lines are sorted by their stack.
+pub fn create_folded_lines(rows: Vec<FoldedRow>) -> Vec<String> {
+    let mut rows = rows;
+    rows.sort_by(|a, b| a.stack.cmp(&b.stack));
+    rows.into_iter()
+        .map(|row| {
+            format!("{} {}", row.stack.join(";"), row.used.total_unsafe())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod folded_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest]
+    fn create_folded_lines_test() {
+        let mut mio_used = CounterBlock::default();
+        mio_used.exprs.unsafe_ = 40;
+        mio_used.functions.unsafe_ = 2;
+        let rows = vec![
+            FoldedRow {
+                stack: vec![
+                    String::from("root"),
+                    String::from("tokio"),
+                    String::from("mio"),
+                ],
+                used: mio_used,
+            },
+            FoldedRow {
+                stack: vec![String::from("root")],
+                used: CounterBlock::default(),
+            },
+        ];
+
+        assert_eq!(
+            create_folded_lines(rows),
+            vec![String::from("root 0"), String::from("root;tokio;mio 42")]
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use crate::format::json::ReportSummary;
+use crate::format::sarif::UnsafeLocations;
+
+use cargo_geiger_serde::{PackageId, SafetyReport};
+use std::collections::HashSet;
+
+/// GitHub Actions workflow commands, a `::warning` annotation for each unsafe
+/// item of the workspace members in the files used by the build, which
+/// GitHub shows inline in the files of a pull request, followed by a
+/// `::notice` with the totals of all crates. The unsafe code of dependencies
+/// is only counted in the notice, as their files aren't in the repository.
+pub fn create_github_actions_lines(
+    report: &SafetyReport,
+    unsafe_locations: &UnsafeLocations,
+    workspace_member_ids: &HashSet<PackageId>,
+) -> Vec<String> {
+    let mut member_entries = report
+        .packages
+        .values()
+        .filter(|entry| workspace_member_ids.contains(&entry.package.id))
+        .collect::<Vec<_>>();
+    member_entries.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+
+    let mut github_actions_lines = vec![];
+    for entry in member_entries {
+        for (uri, unsafe_location) in unsafe_locations
+            .get(&entry.package.id)
+            .into_iter()
+            .flatten()
+        {
+            github_actions_lines.push(format!(
+                "::warning file={},line={},col={},endLine={},endColumn={},\
+                title={}::{}",
+                escape_property(uri),
+                unsafe_location.line,
+                unsafe_location.column,
+                unsafe_location.end_line,
+                unsafe_location.end_column,
+                escape_property(&format!("Unsafe {}", unsafe_location.kind)),
+                escape_data(&format!(
+                    "Unsafe {} in {} {}",
+                    unsafe_location.kind,
+                    entry.package.id.name,
+                    entry.package.id.version
+                ))
+            ));
+        }
+    }
+
+    let report_summary = ReportSummary::from_report(report);
+    github_actions_lines.push(format!(
+        "::notice title=cargo-geiger::{}",
+        escape_data(&format!(
+            "{} unsafe items used by the build, in {} of {} crates",
+            report_summary.used.total_unsafe(),
+            report_summary.crates_using_unsafe,
+            report_summary.crates_scanned
+        ))
+    ));
+    github_actions_lines
+}
+
+/// The message of a workflow command ends at the end of the line, which is
+/// written as an escape instead
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// The properties of a workflow command are also separated by commas, and
+/// end at the `::` before the message
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod github_actions_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::PackageInfo;
+    use geiger::{UnsafeKind, UnsafeLocation};
+    use rstest::*;
+    use std::collections::HashMap;
+
+    #[rstest]
+    fn create_github_actions_lines_test() {
+        let report = create_safety_report(
+            vec!["member", "dependency"]
+                .into_iter()
+                .map(|name| {
+                    (
+                        PackageInfo::new(create_path_package_id(name)),
+                        create_unsafe_info(1),
+                    )
+                })
+                .collect(),
+        );
+        let unsafe_location = UnsafeLocation {
+            line: 3,
+            column: 5,
+            end_line: 4,
+            end_column: 6,
+            kind: UnsafeKind::Block,
+        };
+        let unsafe_locations = HashMap::from([
+            (
+                create_path_package_id("member"),
+                vec![(String::from("src/lib.rs"), unsafe_location)],
+            ),
+            (
+                create_path_package_id("dependency"),
+                vec![(
+                    String::from("file:///dependency/lib.rs"),
+                    unsafe_location,
+                )],
+            ),
+        ]);
+        let workspace_member_ids =
+            HashSet::from([create_path_package_id("member")]);
+
+        assert_eq!(
+            create_github_actions_lines(
+                &report,
+                &unsafe_locations,
+                &workspace_member_ids
+            ),
+            vec![
+                "::warning file=src/lib.rs,line=3,col=5,endLine=4,endColumn=6,title=Unsafe block::Unsafe block in member 0.1.0",
+                "::notice title=cargo-geiger::2 unsafe items used by the build, in 2 of 2 crates",
+            ]
+        );
+    }
+
+    #[rstest(
+        input_text,
+        expected_data,
+        expected_property,
+        case("src/lib.rs", "src/lib.rs", "src/lib.rs"),
+        case("a,b:c", "a,b:c", "a%2Cb%3Ac"),
+        case("100%\nnext", "100%25%0Anext", "100%25%0Anext")
+    )]
+    fn escape_test(
+        input_text: &str,
+        expected_data: &str,
+        expected_property: &str,
+    ) {
+        assert_eq!(escape_data(input_text), expected_data);
+        assert_eq!(escape_property(input_text), expected_property);
+    }
+}
@@ -0,0 +1,307 @@
+use crate::downloads::{format_download_count, unpopular_unsafe_score};
+use crate::format::table::UNSAFE_COUNTERS_HEADER;
+use crate::order::CrateOrder;
+
+use cargo_geiger_serde::CounterBlock;
+
+/// Block characters of increasing intensity, used to shade a cell by its
+/// magnitude relative to the largest value in its column.
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+const MINIMUM_CELL_WIDTH: usize = 8;
+
+const DOWNLOADS_HEADER: &str = "Downloads";
+
+/// A crate to be placed in the heatmap
+pub struct HeatmapRow {
+    /// The name matched against the `--order-file`
+    pub crate_name: String,
+    /// The download count of the crate, if known from `--downloads`
+    pub downloads: Option<u64>,
+    pub name: String,
+    pub used: CounterBlock,
+}
+
+/// Renders one row per crate and one column per unsafe category, with the
+/// crates containing the most unsafe code first. When `shade` is set, each
+/// cell is prefixed with a block character whose intensity is relative to
+/// the column maximum, otherwise only the numbers are printed.
+///
+/// With `show_downloads` a column with the download count of each crate is
+/// added, and with `sort_by_unpopular_unsafe` the crates are ordered by their
+/// unsafe usage weighted down by their download count instead.
+///
+/// A `crate_order` takes precedence over both: the crates it lists come first
+/// in its order, followed by the other crates in alphabetical order.
+pub fn create_heatmap_lines(
+    mut rows: Vec<HeatmapRow>,
+    shade: bool,
+    show_downloads: bool,
+    sort_by_unpopular_unsafe: bool,
+    crate_order: Option<&CrateOrder>,
+) -> Vec<String> {
+    rows.sort_by(|a, b| {
+        let ordering = if let Some(crate_order) = crate_order {
+            let position = |row: &HeatmapRow| {
+                crate_order.position(&row.crate_name).unwrap_or(usize::MAX)
+            };
+            position(a).cmp(&position(b))
+        } else if sort_by_unpopular_unsafe {
+            unpopular_unsafe_score(b.used.total_unsafe(), b.downloads)
+                .total_cmp(&unpopular_unsafe_score(
+                    a.used.total_unsafe(),
+                    a.downloads,
+                ))
+        } else {
+            b.used.total_unsafe().cmp(&a.used.total_unsafe())
+        };
+        ordering.then_with(|| a.name.cmp(&b.name))
+    });
+
+    let column_maximums =
+        rows.iter()
+            .fold([0; 5], |mut column_maximums: [u64; 5], row| {
+                for (maximum, value) in
+                    column_maximums.iter_mut().zip(unsafe_columns(&row.used))
+                {
+                    *maximum = (*maximum).max(value);
+                }
+                column_maximums
+            });
+
+    let column_widths = UNSAFE_COUNTERS_HEADER
+        .iter()
+        .map(|header| header.len().max(MINIMUM_CELL_WIDTH))
+        .collect::<Vec<usize>>();
+
+    let mut header_cells = UNSAFE_COUNTERS_HEADER
+        .iter()
+        .zip(&column_widths)
+        .map(|(header, width)| format!("{: <width$}", header, width = width))
+        .collect::<Vec<String>>();
+    if show_downloads {
+        // Placed in front of the last header, which names the crates.
+        header_cells
+            .insert(header_cells.len() - 1, DOWNLOADS_HEADER.to_string());
+    }
+    let mut heatmap_lines = vec![header_cells.join(" ")];
+
+    for row in &rows {
+        let mut cells = unsafe_columns(&row.used)
+            .iter()
+            .zip(&column_maximums)
+            .zip(&column_widths)
+            .map(|((value, maximum), width)| {
+                let cell = if shade {
+                    let shade_character = shade_for(*value, *maximum);
+                    format!("{}{} {}", shade_character, shade_character, value)
+                } else {
+                    value.to_string()
+                };
+                format!("{: <width$}", cell, width = width)
+            })
+            .collect::<Vec<String>>();
+        if show_downloads {
+            cells.push(format!(
+                "{: <width$}",
+                row.downloads
+                    .map_or(String::from("unknown"), format_download_count),
+                width = DOWNLOADS_HEADER.len()
+            ));
+        }
+        heatmap_lines.push(format!("{} {}", cells.join(" "), row.name));
+    }
+
+    heatmap_lines
+}
+
+fn shade_for(value: u64, maximum: u64) -> char {
+    if value == 0 || maximum == 0 {
+        return SHADES[0];
+    }
+    let levels = (SHADES.len() - 1) as u64;
+    // Round up, so that any non zero value is visible.
+    let level = (value * levels - 1) / maximum + 1;
+    SHADES[level as usize]
+}
+
+fn unsafe_columns(counters: &CounterBlock) -> [u64; 5] {
+    [
+        counters.functions.unsafe_,
+        counters.exprs.unsafe_,
+        counters.item_impls.unsafe_,
+        counters.item_traits.unsafe_,
+        counters.methods.unsafe_,
+    ]
+}
+
+#[cfg(test)]
+mod heatmap_tests {
+    use super::*;
+
+    use cargo_geiger_serde::Count;
+    use rstest::*;
+
+    #[rstest(
+        input_value,
+        input_maximum,
+        expected_shade,
+        case(0, 0, ' '),
+        case(0, 10, ' '),
+        case(1, 10, '░'),
+        case(5, 10, '▒'),
+        case(6, 10, '▓'),
+        case(10, 10, '█')
+    )]
+    fn shade_for_test(
+        input_value: u64,
+        input_maximum: u64,
+        expected_shade: char,
+    ) {
+        assert_eq!(shade_for(input_value, input_maximum), expected_shade);
+    }
+
+    #[rstest(
+        input_shade,
+        expected_heatmap_lines,
+        case(
+            false,
+            vec![
+                String::from(
+                    "Functions  Expressions  Impls    Traits   Methods  Dependency"
+                ),
+                String::from(
+                    "4          8            0        0        0        bar 0.2.0"
+                ),
+                String::from(
+                    "1          2            0        0        1        foo 0.1.0"
+                ),
+            ]
+        ),
+        case(
+            true,
+            vec![
+                String::from(
+                    "Functions  Expressions  Impls    Traits   Methods  Dependency"
+                ),
+                String::from(
+                    "██ 4       ██ 8            0        0        0     bar 0.2.0"
+                ),
+                String::from(
+                    "░░ 1       ░░ 2            0        0     ██ 1     foo 0.1.0"
+                ),
+            ]
+        )
+    )]
+    fn create_heatmap_lines_test(
+        input_shade: bool,
+        expected_heatmap_lines: Vec<String>,
+    ) {
+        let rows = vec![
+            create_heatmap_row("foo 0.1.0", [1, 2, 0, 0, 1], None),
+            create_heatmap_row("bar 0.2.0", [4, 8, 0, 0, 0], None),
+        ];
+
+        assert_eq!(
+            create_heatmap_lines(rows, input_shade, false, false, None),
+            expected_heatmap_lines
+        );
+    }
+
+    #[rstest(
+        input_sort_by_unpopular_unsafe,
+        expected_heatmap_lines,
+        case(
+            false,
+            vec![
+                String::from(
+                    "Functions  Expressions  Impls    Traits   Methods  Downloads Dependency"
+                ),
+                String::from(
+                    "4          8            0        0        0        100M      bar 0.2.0"
+                ),
+                String::from(
+                    "1          2            0        0        1        unknown   foo 0.1.0"
+                ),
+            ]
+        ),
+        case(
+            true,
+            vec![
+                String::from(
+                    "Functions  Expressions  Impls    Traits   Methods  Downloads Dependency"
+                ),
+                String::from(
+                    "1          2            0        0        1        unknown   foo 0.1.0"
+                ),
+                String::from(
+                    "4          8            0        0        0        100M      bar 0.2.0"
+                ),
+            ]
+        )
+    )]
+    fn create_heatmap_lines_test_downloads(
+        input_sort_by_unpopular_unsafe: bool,
+        expected_heatmap_lines: Vec<String>,
+    ) {
+        let rows = vec![
+            create_heatmap_row("foo 0.1.0", [1, 2, 0, 0, 1], None),
+            create_heatmap_row("bar 0.2.0", [4, 8, 0, 0, 0], Some(100_000_000)),
+        ];
+
+        assert_eq!(
+            create_heatmap_lines(
+                rows,
+                false,
+                true,
+                input_sort_by_unpopular_unsafe,
+                None
+            ),
+            expected_heatmap_lines
+        );
+    }
+
+    #[rstest]
+    fn create_heatmap_lines_test_crate_order() {
+        let rows = vec![
+            create_heatmap_row("foo 0.1.0", [1, 2, 0, 0, 1], None),
+            create_heatmap_row("bar 0.2.0", [4, 8, 0, 0, 0], None),
+            create_heatmap_row("baz 0.3.0", [0, 0, 0, 0, 0], None),
+            create_heatmap_row("qux 0.4.0", [9, 9, 9, 9, 9], None),
+        ];
+        let crate_order = CrateOrder::from("baz\nfoo\n");
+
+        assert_eq!(
+            create_heatmap_lines(rows, false, false, false, Some(&crate_order))
+                .iter()
+                .skip(1)
+                .map(|line| line.split_whitespace().rev().nth(1).unwrap())
+                .collect::<Vec<_>>(),
+            vec!["baz", "foo", "bar", "qux"]
+        );
+    }
+
+    fn create_heatmap_row(
+        name: &str,
+        unsafe_counts: [u64; 5],
+        downloads: Option<u64>,
+    ) -> HeatmapRow {
+        HeatmapRow {
+            crate_name: name.split(' ').next().unwrap().to_string(),
+            downloads,
+            name: String::from(name),
+            used: create_counter_block(unsafe_counts),
+        }
+    }
+
+    fn create_counter_block(unsafe_counts: [u64; 5]) -> CounterBlock {
+        let count = |unsafe_| Count { safe: 0, unsafe_ };
+        CounterBlock {
+            functions: count(unsafe_counts[0]),
+            exprs: count(unsafe_counts[1]),
+            item_impls: count(unsafe_counts[2]),
+            item_traits: count(unsafe_counts[3]),
+            methods: count(unsafe_counts[4]),
+        }
+    }
+}
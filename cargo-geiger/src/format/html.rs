@@ -0,0 +1,492 @@
+use crate::format::aligned::status_label;
+use crate::format::CrateDetectionStatus;
+
+use cargo_geiger_serde::{Count, CounterBlock, PackageId, SafetyReport};
+use std::collections::{HashMap, HashSet};
+
+/// The files of each crate used by the build, as displayed in the report,
+/// with the unsafe code found in each, sorted by file
+pub type CrateFiles = HashMap<PackageId, Vec<(String, CounterBlock)>>;
+
+const HTML_HEADER: [&str; 8] = [
+    "Crate",
+    "Version",
+    "Status",
+    "Functions",
+    "Expressions",
+    "Impls",
+    "Traits",
+    "Methods",
+];
+
+const HTML_FILE_HEADER: [&str; 6] = [
+    "File",
+    "Functions",
+    "Expressions",
+    "Impls",
+    "Traits",
+    "Methods",
+];
+
+/// A standalone HTML document, with a summary table of all crates, the
+/// dependency tree from `root_package_id`, and a collapsible section for each
+/// crate listing the unsafe code found in each of its files. It is colored
+/// with inline styles instead of escape codes, and links to nothing outside
+/// of itself, so it can be kept as a build artifact.
+pub fn create_html_lines(
+    report: &SafetyReport,
+    root_package_id: Option<&PackageId>,
+    crate_files: &CrateFiles,
+) -> Vec<String> {
+    let mut entries = report.packages.values().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+
+    let mut html_lines = vec![
+        String::from("<!DOCTYPE html>"),
+        String::from("<html>"),
+        String::from("<head>"),
+        String::from("<meta charset=\"utf-8\">"),
+        String::from("<title>cargo-geiger report</title>"),
+        String::from("</head>"),
+        String::from("<body>"),
+        String::from("<h1>cargo-geiger report</h1>"),
+        String::from("<h2>Summary</h2>"),
+    ];
+    html_lines.append(&mut create_html_table_lines(report));
+
+    if let Some(root_package_id) = root_package_id {
+        html_lines.push(String::from("<h2>Dependency tree</h2>"));
+        html_lines.push(String::from("<ul>"));
+        append_tree_lines(
+            report,
+            root_package_id,
+            1,
+            &mut HashSet::new(),
+            &mut html_lines,
+        );
+        html_lines.push(String::from("</ul>"));
+    }
+
+    html_lines.push(String::from("<h2>Crates</h2>"));
+    for entry in entries {
+        append_crate_section_lines(
+            &entry.package.id,
+            &entry.unsafety.used,
+            &entry.unsafety.unused,
+            &CrateDetectionStatus::from(&entry.unsafety),
+            crate_files.get(&entry.package.id),
+            &mut html_lines,
+        );
+    }
+
+    html_lines.push(String::from("</body>"));
+    html_lines.push(String::from("</html>"));
+    html_lines
+}
+
+/// A `<table>` with a row for each crate in the report, ordered by crate,
+/// and a footer row with the totals of all crates. The counts are the unsafe
+/// code used by the build and found in total, such as `2/3`.
+fn create_html_table_lines(report: &SafetyReport) -> Vec<String> {
+    let mut entries = report.packages.values().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+
+    let mut html_lines = vec![
+        String::from("<table>"),
+        String::from("  <thead>"),
+        format!(
+            "    <tr>{}</tr>",
+            HTML_HEADER
+                .iter()
+                .map(|header| format!("<th>{}</th>", header))
+                .collect::<String>()
+        ),
+        String::from("  </thead>"),
+        String::from("  <tbody>"),
+    ];
+    let mut total_used = CounterBlock::default();
+    let mut total_unused = CounterBlock::default();
+    for entry in entries {
+        let status = CrateDetectionStatus::from(&entry.unsafety);
+        let mut fields = vec![
+            format!(
+                "<a href=\"#{}\">{}</a>",
+                crate_anchor(&entry.package.id),
+                escape_html(&entry.package.id.name)
+            ),
+            entry.package.id.version.to_string(),
+            status_label(&status).to_string(),
+        ];
+        fields.append(&mut count_fields(
+            &entry.unsafety.used,
+            &entry.unsafety.unused,
+        ));
+        html_lines.push(format!(
+            "    <tr style=\"{}\">{}</tr>",
+            status_style(&status),
+            cells("td", &fields)
+        ));
+        total_used += entry.unsafety.used.clone();
+        total_unused += entry.unsafety.unused.clone();
+    }
+    html_lines.push(String::from("  </tbody>"));
+
+    let mut total_fields =
+        vec![String::from("Total"), String::new(), String::new()];
+    total_fields.append(&mut count_fields(&total_used, &total_unused));
+    html_lines.push(String::from("  <tfoot>"));
+    html_lines.push(format!("    <tr>{}</tr>", cells("th", &total_fields)));
+    html_lines.push(String::from("  </tfoot>"));
+    html_lines.push(String::from("</table>"));
+    html_lines
+}
+
+/// A `<li>` for the crate, followed by a nested list of its dependencies. A
+/// crate already listed is marked with `(*)` instead of listing its
+/// dependencies again, and a crate without metrics has no counts.
+fn append_tree_lines(
+    report: &SafetyReport,
+    package_id: &PackageId,
+    depth: usize,
+    visited_package_ids: &mut HashSet<PackageId>,
+    html_lines: &mut Vec<String>,
+) {
+    let indent = "  ".repeat(depth);
+    let name =
+        format!("{} {}", escape_html(&package_id.name), package_id.version);
+    let entry = match report.packages.get(package_id) {
+        Some(entry) => entry,
+        None => {
+            html_lines
+                .push(format!("{}<li>{} (no metrics)</li>", indent, name));
+            return;
+        }
+    };
+
+    let status = CrateDetectionStatus::from(&entry.unsafety);
+    let used_unsafe = entry.unsafety.used.total_unsafe();
+    let label = format!(
+        "<a href=\"#{}\">{}</a> {}/{}",
+        crate_anchor(package_id),
+        name,
+        used_unsafe,
+        used_unsafe + entry.unsafety.unused.total_unsafe()
+    );
+    let mut dependencies = entry
+        .package
+        .dependencies
+        .iter()
+        .chain(&entry.package.build_dependencies)
+        .chain(&entry.package.dev_dependencies)
+        .collect::<Vec<_>>();
+    dependencies.sort();
+    dependencies.dedup();
+
+    if dependencies.is_empty() {
+        html_lines.push(format!(
+            "{}<li style=\"{}\">{}</li>",
+            indent,
+            status_style(&status),
+            label
+        ));
+        return;
+    }
+    if !visited_package_ids.insert(package_id.clone()) {
+        html_lines.push(format!(
+            "{}<li style=\"{}\">{} (*)</li>",
+            indent,
+            status_style(&status),
+            label
+        ));
+        return;
+    }
+
+    html_lines.push(format!(
+        "{}<li style=\"{}\">{}",
+        indent,
+        status_style(&status),
+        label
+    ));
+    html_lines.push(format!("{}<ul>", indent));
+    for dependency in dependencies {
+        append_tree_lines(
+            report,
+            dependency,
+            depth + 1,
+            visited_package_ids,
+            html_lines,
+        );
+    }
+    html_lines.push(format!("{}</ul>", indent));
+    html_lines.push(format!("{}</li>", indent));
+}
+
+/// A `<details>` section for the crate, linked to from the summary table and
+/// the tree, with a row for each of its files
+fn append_crate_section_lines(
+    package_id: &PackageId,
+    used: &CounterBlock,
+    unused: &CounterBlock,
+    status: &CrateDetectionStatus,
+    files: Option<&Vec<(String, CounterBlock)>>,
+    html_lines: &mut Vec<String>,
+) {
+    let used_unsafe = used.total_unsafe();
+    html_lines.push(format!("<details id=\"{}\">", crate_anchor(package_id)));
+    html_lines.push(format!(
+        "  <summary style=\"{}\">{} {}: {}/{} unsafe, {}</summary>",
+        status_style(status),
+        escape_html(&package_id.name),
+        package_id.version,
+        used_unsafe,
+        used_unsafe + unused.total_unsafe(),
+        status_label(status)
+    ));
+    match files {
+        Some(files) if !files.is_empty() => {
+            html_lines.push(String::from("  <table>"));
+            html_lines.push(format!(
+                "    <tr>{}</tr>",
+                HTML_FILE_HEADER
+                    .iter()
+                    .map(|header| format!("<th>{}</th>", header))
+                    .collect::<String>()
+            ));
+            for (path, counters) in files {
+                let mut fields = vec![escape_html(path)];
+                fields.extend(
+                    unsafe_columns(counters).iter().map(u64::to_string),
+                );
+                html_lines
+                    .push(format!("    <tr>{}</tr>", cells("td", &fields)));
+            }
+            html_lines.push(String::from("  </table>"));
+        }
+        _ => html_lines.push(String::from("  <p>No scanned files.</p>")),
+    }
+    html_lines.push(String::from("</details>"));
+}
+
+/// The `id` of the section of the crate, such as `crate-foo-0.1.0`
+fn crate_anchor(package_id: &PackageId) -> String {
+    escape_html(&format!("crate-{}-{}", package_id.name, package_id.version))
+}
+
+fn cells(tag: &str, fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("<{}>{}</{}>", tag, field, tag))
+        .collect()
+}
+
+fn count_fields(used: &CounterBlock, unused: &CounterBlock) -> Vec<String> {
+    let count_field = |used: &Count, unused: &Count| {
+        format!("{}/{}", used.unsafe_, used.unsafe_ + unused.unsafe_)
+    };
+    vec![
+        count_field(&used.functions, &unused.functions),
+        count_field(&used.exprs, &unused.exprs),
+        count_field(&used.item_impls, &unused.item_impls),
+        count_field(&used.item_traits, &unused.item_traits),
+        count_field(&used.methods, &unused.methods),
+    ]
+}
+
+fn unsafe_columns(counter_block: &CounterBlock) -> [u64; 5] {
+    [
+        counter_block.functions.unsafe_,
+        counter_block.exprs.unsafe_,
+        counter_block.item_impls.unsafe_,
+        counter_block.item_traits.unsafe_,
+        counter_block.methods.unsafe_,
+    ]
+}
+
+/// The inline equivalent of the colors `colorize` uses in a terminal
+fn status_style(status: &CrateDetectionStatus) -> &'static str {
+    match status {
+        CrateDetectionStatus::NoneDetectedForbidsUnsafe => "color: green",
+        CrateDetectionStatus::NoneDetectedDeniesUnsafe => "color: goldenrod",
+        CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe => {
+            "color: darkcyan"
+        }
+        CrateDetectionStatus::NoneDetectedAllowsUnsafe => "",
+        CrateDetectionStatus::UnsafeDetected => "color: red; font-weight: bold",
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod html_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::{PackageInfo, UnsafeInfo};
+    use rstest::*;
+
+    #[rstest]
+    fn create_html_lines_test() {
+        let report = create_report();
+        let foo_id = create_path_package_id("foo");
+        let crate_files = vec![(
+            foo_id.clone(),
+            vec![(String::from("src/lib.rs"), {
+                let mut counters = CounterBlock::default();
+                counters.exprs.unsafe_ = 3;
+                counters
+            })],
+        )]
+        .into_iter()
+        .collect::<CrateFiles>();
+
+        let html_lines =
+            create_html_lines(&report, Some(&foo_id), &crate_files);
+
+        assert_eq!(html_lines.first().unwrap(), "<!DOCTYPE html>");
+        assert_eq!(html_lines.last().unwrap(), "</html>");
+        let tree_start = html_lines
+            .iter()
+            .position(|line| line == "<h2>Dependency tree</h2>")
+            .unwrap();
+        assert_eq!(
+            html_lines[tree_start + 1..tree_start + 7],
+            [
+                "<ul>",
+                "  <li style=\"color: red; font-weight: bold\"><a href=\"#crate-foo-0.1.0\">foo 0.1.0</a> 3/4",
+                "  <ul>",
+                "    <li style=\"color: green\"><a href=\"#crate-bar-0.1.0\">bar 0.1.0</a> 0/1</li>",
+                "  </ul>",
+                "  </li>",
+            ]
+        );
+        let foo_section_start = html_lines
+            .iter()
+            .position(|line| line == "<details id=\"crate-foo-0.1.0\">")
+            .unwrap();
+        assert_eq!(
+            html_lines[foo_section_start + 1..foo_section_start + 6],
+            [
+                "  <summary style=\"color: red; font-weight: bold\">foo 0.1.0: 3/4 unsafe, unsafe</summary>",
+                "  <table>",
+                "    <tr><th>File</th><th>Functions</th><th>Expressions</th><th>Impls</th><th>Traits</th><th>Methods</th></tr>",
+                "    <tr><td>src/lib.rs</td><td>0</td><td>3</td><td>0</td><td>0</td><td>0</td></tr>",
+                "  </table>",
+            ]
+        );
+        assert!(
+            html_lines.contains(&String::from("  <p>No scanned files.</p>"))
+        );
+        assert!(html_lines.iter().all(|line| !line.contains('\x1B')));
+    }
+
+    #[rstest]
+    fn create_html_table_lines_test() {
+        let html_lines = create_html_table_lines(&create_report());
+
+        assert_eq!(html_lines.first().unwrap(), "<table>");
+        assert_eq!(html_lines.last().unwrap(), "</table>");
+        assert_eq!(
+            html_lines[5],
+            "    <tr style=\"color: green\"><td><a href=\"#crate-bar-0.1.0\">bar</a></td><td>0.1.0</td><td>forbids</td><td>0/1</td><td>0/0</td><td>0/0</td><td>0/0</td><td>0/0</td></tr>"
+        );
+        assert_eq!(
+            html_lines[6],
+            "    <tr style=\"color: red; font-weight: bold\"><td><a href=\"#crate-foo-0.1.0\">foo</a></td><td>0.1.0</td><td>unsafe</td><td>0/1</td><td>3/3</td><td>0/0</td><td>0/0</td><td>0/0</td></tr>"
+        );
+        assert_eq!(
+            html_lines[9],
+            "    <tr><th>Total</th><th></th><th></th><th>0/2</th><th>3/3</th><th>0/0</th><th>0/0</th><th>0/0</th></tr>"
+        );
+    }
+
+    #[rstest]
+    fn append_tree_lines_test_repeated_crate() {
+        let mut report = create_report();
+        let foo_id = create_path_package_id("foo");
+        let bar_id = create_path_package_id("bar");
+        // A cycle through a dev dependency is listed once.
+        report
+            .packages
+            .get_mut(&bar_id)
+            .unwrap()
+            .package
+            .dev_dependencies
+            .insert(foo_id.clone());
+        report
+            .packages_without_metrics
+            .insert(create_path_package_id("baz"));
+        report
+            .packages
+            .get_mut(&bar_id)
+            .unwrap()
+            .package
+            .dependencies
+            .insert(create_path_package_id("baz"));
+
+        let mut html_lines = vec![];
+        append_tree_lines(
+            &report,
+            &foo_id,
+            0,
+            &mut HashSet::new(),
+            &mut html_lines,
+        );
+
+        assert_eq!(
+            html_lines,
+            [
+                "<li style=\"color: red; font-weight: bold\"><a href=\"#crate-foo-0.1.0\">foo 0.1.0</a> 3/4",
+                "<ul>",
+                "  <li style=\"color: green\"><a href=\"#crate-bar-0.1.0\">bar 0.1.0</a> 0/1",
+                "  <ul>",
+                "    <li>baz 0.1.0 (no metrics)</li>",
+                "    <li style=\"color: red; font-weight: bold\"><a href=\"#crate-foo-0.1.0\">foo 0.1.0</a> 3/4 (*)</li>",
+                "  </ul>",
+                "  </li>",
+                "</ul>",
+                "</li>",
+            ]
+        );
+    }
+
+    #[rstest(
+        input_text,
+        expected_text,
+        case("foo", "foo"),
+        case("<foo & \"bar\">", "&lt;foo &amp; &quot;bar&quot;&gt;")
+    )]
+    fn escape_html_test(input_text: &str, expected_text: &str) {
+        assert_eq!(escape_html(input_text), expected_text);
+    }
+
+    /// `foo`, using unsafe code and depending on `bar`, which forbids it
+    fn create_report() -> SafetyReport {
+        let mut foo = PackageInfo::new(create_path_package_id("foo"));
+        foo.dependencies.insert(create_path_package_id("bar"));
+        create_safety_report(
+            vec![
+                (foo, 3, false),
+                (PackageInfo::new(create_path_package_id("bar")), 0, true),
+            ]
+            .into_iter()
+            .map(|(package, unsafe_exprs, forbids_unsafe)| {
+                let mut unsafety = UnsafeInfo {
+                    forbids_unsafe,
+                    ..create_unsafe_info(unsafe_exprs)
+                };
+                unsafety.unused.functions.unsafe_ = 1;
+                (package, unsafety)
+            })
+            .collect(),
+        )
+    }
+}
@@ -0,0 +1,257 @@
+pub mod v2;
+
+use crate::format::categories::{category_breakdowns, CategoryBreakdown};
+use crate::format::CrateDetectionStatus;
+
+use cargo_geiger_serde::{CounterBlock, SafetyReport};
+use serde::Serialize;
+use v2::{JsonReport, SCHEMA_VERSION};
+
+/// The shape of the Json output, chosen with `--json-version`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum JsonVersion {
+    /// The fields of the `SafetyReport`, followed by a `summary`
+    V1,
+    /// The versioned model of `v2`, with a `schema_version`
+    #[default]
+    V2,
+}
+
+impl std::str::FromStr for JsonVersion {
+    type Err = JsonVersionParseError;
+    fn from_str(s: &str) -> Result<Self, JsonVersionParseError> {
+        match s {
+            "1" => Ok(Self::V1),
+            "2" => Ok(Self::V2),
+            _ => Err(JsonVersionParseError),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct JsonVersionParseError;
+impl std::fmt::Display for JsonVersionParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "matching Json version not found, expected 1 or 2")
+    }
+}
+impl std::error::Error for JsonVersionParseError {}
+
+/// The totals of all crates in the report, for scripts which would otherwise
+/// sum the entries themselves
+#[derive(Debug, Default, Eq, PartialEq, Serialize)]
+pub struct ReportSummary {
+    pub crates_scanned: u64,
+    pub crates_using_unsafe: u64,
+    pub used: CounterBlock,
+    pub unused: CounterBlock,
+}
+
+impl ReportSummary {
+    /// The report has a single entry for each crate, however often it is
+    /// depended upon in the graph, so a shared dependency is counted once.
+    pub fn from_report(report: &SafetyReport) -> Self {
+        let mut report_summary = ReportSummary::default();
+        for entry in report.packages.values() {
+            report_summary.crates_scanned += 1;
+            if CrateDetectionStatus::from(&entry.unsafety)
+                == CrateDetectionStatus::UnsafeDetected
+            {
+                report_summary.crates_using_unsafe += 1;
+            }
+            report_summary.used += entry.unsafety.used.clone();
+            report_summary.unused += entry.unsafety.unused.clone();
+        }
+        report_summary
+    }
+}
+
+/// The Json output of version 1, the fields of the `SafetyReport` followed by
+/// a `summary`, and the breakdown of `--categories`, which are ignored when the
+/// output is read back as a `SafetyReport`
+#[derive(Serialize)]
+struct JsonReportV1<'a> {
+    #[serde(flatten)]
+    report: &'a SafetyReport,
+    summary: ReportSummary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    categories: Option<Vec<CategoryBreakdown>>,
+}
+
+pub fn create_json_line(
+    report: &SafetyReport,
+    categories: bool,
+    json_version: JsonVersion,
+) -> String {
+    let categories = categories.then(|| {
+        category_breakdowns(
+            &report
+                .packages
+                .values()
+                .map(|entry| (entry.package.id.clone(), entry.unsafety.clone()))
+                .collect::<Vec<_>>(),
+        )
+    });
+    match json_version {
+        JsonVersion::V1 => serde_json::to_string(&JsonReportV1 {
+            report,
+            summary: ReportSummary::from_report(report),
+            categories,
+        }),
+        JsonVersion::V2 => serde_json::to_string(&JsonReport {
+            categories,
+            ..JsonReport::from(report)
+        }),
+    }
+    .unwrap()
+}
+
+/// Read a report written with `--output-format Json`, of any version
+pub fn safety_report_from_json(s: &str) -> anyhow::Result<SafetyReport> {
+    let json_value = serde_json::from_str::<serde_json::Value>(s)?;
+    match json_value.get("schema_version") {
+        None => Ok(serde_json::from_value::<SafetyReport>(json_value)?),
+        Some(schema_version)
+            if schema_version.as_u64() == Some(SCHEMA_VERSION as u64) =>
+        {
+            let json_report = serde_json::from_value::<JsonReport>(json_value)?;
+            Ok(SafetyReport::from(json_report))
+        }
+        Some(schema_version) => Err(anyhow::anyhow!(
+            "unsupported schema_version {}, expected at most {}",
+            schema_version,
+            SCHEMA_VERSION
+        )),
+    }
+}
+
+#[cfg(test)]
+mod json_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::PackageInfo;
+    use rstest::*;
+
+    fn create_report() -> SafetyReport {
+        create_safety_report(
+            vec![("foo", 3, 1), ("bar", 0, 2), ("baz", 4, 0)]
+                .into_iter()
+                .map(|(name, unsafe_exprs, unused_unsafe_functions)| {
+                    let mut unsafety = create_unsafe_info(unsafe_exprs);
+                    unsafety.unused.functions.unsafe_ = unused_unsafe_functions;
+                    (PackageInfo::new(create_path_package_id(name)), unsafety)
+                })
+                .collect(),
+        )
+    }
+
+    #[rstest]
+    fn report_summary_from_report_test() {
+        let mut expected_used = CounterBlock::default();
+        expected_used.exprs.unsafe_ = 7;
+        let mut expected_unused = CounterBlock::default();
+        expected_unused.functions.unsafe_ = 3;
+
+        assert_eq!(
+            ReportSummary::from_report(&create_report()),
+            ReportSummary {
+                crates_scanned: 3,
+                crates_using_unsafe: 2,
+                used: expected_used,
+                unused: expected_unused,
+            }
+        );
+    }
+
+    #[rstest]
+    fn create_json_line_test() {
+        let report = create_report();
+        let json_line = create_json_line(&report, false, JsonVersion::V1);
+
+        let json_value =
+            serde_json::from_str::<serde_json::Value>(&json_line).unwrap();
+        assert_eq!(json_value["summary"]["crates_scanned"], 3);
+        assert_eq!(json_value["summary"]["crates_using_unsafe"], 2);
+        assert_eq!(json_value["summary"]["used"]["exprs"]["unsafe_"], 7);
+        assert!(json_value.get("categories").is_none());
+
+        assert_eq!(
+            serde_json::from_str::<SafetyReport>(&json_line).unwrap(),
+            report
+        );
+    }
+
+    #[rstest(input_json_version, case(JsonVersion::V1), case(JsonVersion::V2))]
+    fn create_json_line_test_categories(input_json_version: JsonVersion) {
+        let report = create_report();
+        let json_line = create_json_line(&report, true, input_json_version);
+
+        let json_value =
+            serde_json::from_str::<serde_json::Value>(&json_line).unwrap();
+        let expressions = &json_value["categories"][1];
+        assert_eq!(expressions["category"], "expressions");
+        assert_eq!(expressions["used"], 7);
+        assert_eq!(expressions["crates"][0]["name"], "baz");
+        assert_eq!(expressions["crates"][0]["used"], 4);
+
+        assert_eq!(safety_report_from_json(&json_line).unwrap(), report);
+    }
+
+    #[rstest]
+    fn create_json_line_test_v2() {
+        let report = create_report();
+        let json_line = create_json_line(&report, false, JsonVersion::V2);
+
+        let json_value =
+            serde_json::from_str::<serde_json::Value>(&json_line).unwrap();
+        assert_eq!(json_value["schema_version"], 2);
+        assert_eq!(json_value["crates"][0]["id"]["name"], "bar");
+        assert_eq!(json_value["crates"][2]["status"], "unsafe_detected");
+        assert_eq!(json_value["summary"]["crates_scanned"], 3);
+        assert_eq!(json_value["summary"]["used"]["expressions"]["unsafe"], 7);
+        assert!(json_value.get("packages").is_none());
+        assert!(json_value.get("categories").is_none());
+
+        assert_eq!(safety_report_from_json(&json_line).unwrap(), report);
+    }
+
+    #[rstest(
+        input_json,
+        expected_is_ok,
+        case("{\"schema_version\": 3, \"crates\": []}", false),
+        case("{\"schema_version\": \"2\"}", false),
+        case("not a report", false),
+        case(
+            "{\"packages\": [], \"packages_without_metrics\": [], \
+            \"used_but_not_scanned_files\": []}",
+            true
+        )
+    )]
+    fn safety_report_from_json_test(input_json: &str, expected_is_ok: bool) {
+        assert_eq!(safety_report_from_json(input_json).is_ok(), expected_is_ok);
+    }
+
+    #[rstest(
+        input_raw_str,
+        expected_json_version_result,
+        case("1", Ok(JsonVersion::V1)),
+        case("2", Ok(JsonVersion::V2)),
+        case("3", Err(JsonVersionParseError))
+    )]
+    fn json_version_from_str_test(
+        input_raw_str: &str,
+        expected_json_version_result: Result<
+            JsonVersion,
+            JsonVersionParseError,
+        >,
+    ) {
+        assert_eq!(
+            input_raw_str.parse::<JsonVersion>(),
+            expected_json_version_result
+        );
+    }
+}
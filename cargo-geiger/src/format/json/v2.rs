@@ -0,0 +1,787 @@
+//! The model of version 2 of the Json output. Its field names and enum values
+//! are kept stable: fields may be added, but a field or value is only renamed
+//! or removed along with an increase of `SCHEMA_VERSION`.
+
+use crate::format::categories::CategoryBreakdown;
+use crate::format::json::ReportSummary;
+use crate::format::CrateDetectionStatus;
+
+use cargo_geiger_serde::{
+    CounterBlock, FfiCount, PackageInfo, ReportEntry, SafetyReport,
+    ScanWarning, SourceKind, UnsafeFinding, UnsafeInfo, UnsafeKind,
+};
+use cargo_metadata::semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use url::Url;
+
+/// The `schema_version` of the reports of this model
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// The report, in which every list is sorted, so that an unchanged report is
+/// written as the same bytes in each run
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JsonReport {
+    pub schema_version: u32,
+    /// The scanned crates, by name, version and source
+    pub crates: Vec<Crate>,
+    /// The crates which couldn't be scanned
+    pub crates_without_metrics: Vec<CrateId>,
+    /// The crates left out of the scan with `--ignore`
+    pub ignored_crates: Vec<CrateId>,
+    pub used_but_not_scanned_files: Vec<PathBuf>,
+    /// The files which failed to be scanned, or were scanned lossily, with
+    /// `--lossy`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scan_warnings: Vec<ScanWarning>,
+    pub summary: Summary,
+    /// The breakdown of `--categories`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<CategoryBreakdown>>,
+}
+
+#[derive(
+    Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize,
+)]
+pub struct CrateId {
+    pub name: String,
+    pub version: Version,
+    pub source: Source,
+}
+
+/// Where a crate is fetched from, told apart by its `kind`: `git`,
+/// `registry` or `path`
+#[derive(
+    Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize,
+)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum Source {
+    Git { url: Url, rev: String },
+    Registry { name: String, url: Url },
+    Path { url: Url },
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Crate {
+    pub id: CrateId,
+    /// Derived from `used` and `unsafe_code_lint`, ignored when the report is
+    /// read
+    pub status: Status,
+    pub unsafe_code_lint: UnsafeCodeLint,
+    /// The unsafe code in the files used by the build
+    pub used: Counts,
+    /// The unsafe code in the other files
+    pub unused: Counts,
+    pub ffi: FfiCounts,
+    pub dependencies: Vec<CrateId>,
+    pub dev_dependencies: Vec<CrateId>,
+    pub build_dependencies: Vec<CrateId>,
+    /// From `--history`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsafe_introduced_at: Option<Version>,
+    /// From `--downloads`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downloads: Option<u64>,
+    /// From `--check-security-policy`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_security_policy: Option<bool>,
+    /// From `--verify-checksums`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum_mismatch: Option<bool>,
+    /// From `--audit`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub advisories: Option<Vec<String>>,
+    /// From `--include-locations`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsafe_findings: Option<Vec<Finding>>,
+    /// From `--dangerous-apis`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dangerous_api_calls: Option<BTreeMap<String, u64>>,
+    /// From `--expand`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expanded: Option<Counts>,
+    /// Where the crate comes from, such as `git` or `vendored`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_kind: Option<SourceKind>,
+    /// The name of the registry of the crate in the cargo configuration, as
+    /// only the URL of its index is part of `id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// From `--detector`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detector_findings: Option<BTreeMap<String, u64>>,
+    /// From `--percentiles`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsafe_density_percentile: Option<u8>,
+    /// The number of lines of the files used by the build
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines_of_code: Option<u64>,
+    /// Derived from `used` and `lines_of_code`, the unsafe expressions per
+    /// thousand lines, null when no lines were counted, ignored when the
+    /// report is read
+    #[serde(default)]
+    pub unsafe_per_kloc: Option<f64>,
+}
+
+/// The status of a crate, as marked in the table output
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// No unsafe code used by the build, `#![forbid(unsafe_code)]` declared
+    ForbidsUnsafe,
+    /// No unsafe code used by the build, `#![deny(unsafe_code)]` declared
+    DeniesUnsafe,
+    /// No unsafe code used by the build, `#![forbid(unsafe_code)]` declared
+    /// only within `#![cfg_attr(<condition>, ...)]`
+    ConditionallyForbidsUnsafe,
+    /// No unsafe code used by the build, nor any lint against it
+    AllowsUnsafe,
+    /// Unsafe code used by the build
+    UnsafeDetected,
+}
+
+impl From<CrateDetectionStatus> for Status {
+    fn from(crate_detection_status: CrateDetectionStatus) -> Self {
+        match crate_detection_status {
+            CrateDetectionStatus::NoneDetectedForbidsUnsafe => {
+                Status::ForbidsUnsafe
+            }
+            CrateDetectionStatus::NoneDetectedDeniesUnsafe => {
+                Status::DeniesUnsafe
+            }
+            CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe => {
+                Status::ConditionallyForbidsUnsafe
+            }
+            CrateDetectionStatus::NoneDetectedAllowsUnsafe => {
+                Status::AllowsUnsafe
+            }
+            CrateDetectionStatus::UnsafeDetected => Status::UnsafeDetected,
+        }
+    }
+}
+
+/// The level of the `unsafe_code` lint declared by all entry points of a
+/// crate
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsafeCodeLint {
+    /// `#![forbid(unsafe_code)]`
+    Forbid,
+    /// `#![deny(unsafe_code)]`, by at least one of them
+    Deny,
+    /// Either of them within `#![cfg_attr(<condition>, ...)]`, by at least
+    /// one of them
+    Conditional,
+    /// Neither, by at least one of them
+    None,
+}
+
+impl UnsafeCodeLint {
+    fn from_unsafe_info(unsafe_info: &UnsafeInfo) -> Self {
+        match (
+            unsafe_info.forbids_unsafe,
+            unsafe_info.denies_unsafe,
+            unsafe_info.conditionally_forbids_unsafe,
+        ) {
+            (true, _, _) => UnsafeCodeLint::Forbid,
+            (false, true, _) => UnsafeCodeLint::Deny,
+            (false, false, true) => UnsafeCodeLint::Conditional,
+            (false, false, false) => UnsafeCodeLint::None,
+        }
+    }
+}
+
+/// The items of each category of unsafe code
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Counts {
+    pub functions: Count,
+    pub expressions: Count,
+    pub impls: Count,
+    pub traits: Count,
+    pub methods: Count,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Count {
+    pub safe: u64,
+    #[serde(rename = "unsafe")]
+    pub unsafe_: u64,
+}
+
+impl From<&CounterBlock> for Counts {
+    fn from(counter_block: &CounterBlock) -> Self {
+        let count = |count: &cargo_geiger_serde::Count| Count {
+            safe: count.safe,
+            unsafe_: count.unsafe_,
+        };
+        Counts {
+            functions: count(&counter_block.functions),
+            expressions: count(&counter_block.exprs),
+            impls: count(&counter_block.item_impls),
+            traits: count(&counter_block.item_traits),
+            methods: count(&counter_block.methods),
+        }
+    }
+}
+
+impl From<Counts> for CounterBlock {
+    fn from(counts: Counts) -> Self {
+        let count = |count: Count| cargo_geiger_serde::Count {
+            safe: count.safe,
+            unsafe_: count.unsafe_,
+        };
+        CounterBlock {
+            functions: count(counts.functions),
+            exprs: count(counts.expressions),
+            item_impls: count(counts.impls),
+            item_traits: count(counts.traits),
+            methods: count(counts.methods),
+        }
+    }
+}
+
+/// The items of the FFI surface, in the files used by the build and in the
+/// other files
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FfiCounts {
+    pub used: u64,
+    pub unused: u64,
+}
+
+/// An unsafe item in a file, of the `kind` `function`, `block`, `impl`,
+/// `trait` or `method`
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Finding {
+    pub file: String,
+    pub kind: FindingKind,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    Function,
+    Block,
+    Impl,
+    Trait,
+    Method,
+}
+
+impl From<&UnsafeFinding> for Finding {
+    fn from(unsafe_finding: &UnsafeFinding) -> Self {
+        Finding {
+            file: unsafe_finding.file.clone(),
+            kind: match unsafe_finding.kind {
+                UnsafeKind::Function => FindingKind::Function,
+                UnsafeKind::Block => FindingKind::Block,
+                UnsafeKind::Impl => FindingKind::Impl,
+                UnsafeKind::Trait => FindingKind::Trait,
+                UnsafeKind::Method => FindingKind::Method,
+            },
+            start_line: unsafe_finding.start_line,
+            start_column: unsafe_finding.start_column,
+            end_line: unsafe_finding.end_line,
+            end_column: unsafe_finding.end_column,
+        }
+    }
+}
+
+impl From<Finding> for UnsafeFinding {
+    fn from(finding: Finding) -> Self {
+        UnsafeFinding {
+            file: finding.file,
+            kind: match finding.kind {
+                FindingKind::Function => UnsafeKind::Function,
+                FindingKind::Block => UnsafeKind::Block,
+                FindingKind::Impl => UnsafeKind::Impl,
+                FindingKind::Trait => UnsafeKind::Trait,
+                FindingKind::Method => UnsafeKind::Method,
+            },
+            start_line: finding.start_line,
+            start_column: finding.start_column,
+            end_line: finding.end_line,
+            end_column: finding.end_column,
+        }
+    }
+}
+
+/// The totals of all crates, as in `ReportSummary`
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Summary {
+    pub crates_scanned: u64,
+    pub crates_using_unsafe: u64,
+    pub used: Counts,
+    pub unused: Counts,
+}
+
+impl From<&SafetyReport> for JsonReport {
+    fn from(report: &SafetyReport) -> Self {
+        let mut crates = report
+            .packages
+            .values()
+            .map(Crate::from)
+            .collect::<Vec<_>>();
+        crates.sort_by(|a, b| a.id.cmp(&b.id));
+        let report_summary = ReportSummary::from_report(report);
+        JsonReport {
+            schema_version: SCHEMA_VERSION,
+            crates,
+            crates_without_metrics: sorted_crate_ids(
+                &report.packages_without_metrics,
+            ),
+            ignored_crates: sorted_crate_ids(&report.ignored_packages),
+            used_but_not_scanned_files: {
+                let mut used_but_not_scanned_files = report
+                    .used_but_not_scanned_files
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                used_but_not_scanned_files.sort();
+                used_but_not_scanned_files
+            },
+            scan_warnings: {
+                let mut scan_warnings = report.scan_warnings.clone();
+                scan_warnings.sort();
+                scan_warnings
+            },
+            summary: Summary {
+                crates_scanned: report_summary.crates_scanned,
+                crates_using_unsafe: report_summary.crates_using_unsafe,
+                used: Counts::from(&report_summary.used),
+                unused: Counts::from(&report_summary.unused),
+            },
+            categories: None,
+        }
+    }
+}
+
+impl From<JsonReport> for SafetyReport {
+    fn from(json_report: JsonReport) -> Self {
+        let crate_ids = |crate_ids: Vec<CrateId>| {
+            crate_ids
+                .into_iter()
+                .map(cargo_geiger_serde::PackageId::from)
+                .collect::<HashSet<_>>()
+        };
+        SafetyReport {
+            packages: json_report
+                .crates
+                .into_iter()
+                .map(|json_crate| {
+                    let report_entry = ReportEntry::from(json_crate);
+                    (report_entry.package.id.clone(), report_entry)
+                })
+                .collect(),
+            packages_without_metrics: crate_ids(
+                json_report.crates_without_metrics,
+            ),
+            ignored_packages: crate_ids(json_report.ignored_crates),
+            used_but_not_scanned_files: json_report
+                .used_but_not_scanned_files
+                .into_iter()
+                .collect(),
+            scan_warnings: json_report.scan_warnings,
+        }
+    }
+}
+
+impl From<&ReportEntry> for Crate {
+    fn from(report_entry: &ReportEntry) -> Self {
+        let unsafety = &report_entry.unsafety;
+        Crate {
+            id: CrateId::from(&report_entry.package.id),
+            status: Status::from(CrateDetectionStatus::from(unsafety)),
+            unsafe_code_lint: UnsafeCodeLint::from_unsafe_info(unsafety),
+            used: Counts::from(&unsafety.used),
+            unused: Counts::from(&unsafety.unused),
+            ffi: FfiCounts {
+                used: unsafety.ffi.used,
+                unused: unsafety.ffi.unused,
+            },
+            dependencies: sorted_crate_ids(&report_entry.package.dependencies),
+            dev_dependencies: sorted_crate_ids(
+                &report_entry.package.dev_dependencies,
+            ),
+            build_dependencies: sorted_crate_ids(
+                &report_entry.package.build_dependencies,
+            ),
+            unsafe_introduced_at: unsafety.unsafe_introduced_at.clone(),
+            downloads: unsafety.downloads,
+            has_security_policy: unsafety.has_security_policy,
+            checksum_mismatch: unsafety.checksum_mismatch,
+            advisories: unsafety.advisories.clone(),
+            unsafe_findings: unsafety.unsafe_findings.as_ref().map(
+                |unsafe_findings| {
+                    unsafe_findings.iter().map(Finding::from).collect()
+                },
+            ),
+            dangerous_api_calls: unsafety.dangerous_api_calls.clone(),
+            expanded: unsafety.expanded.as_ref().map(Counts::from),
+            source_kind: unsafety.source_kind,
+            registry: unsafety.registry.clone(),
+            detector_findings: unsafety.detector_findings.clone(),
+            unsafe_density_percentile: unsafety.unsafe_density_percentile,
+            lines_of_code: unsafety.lines_of_code,
+            unsafe_per_kloc: unsafety.unsafe_per_kloc(),
+        }
+    }
+}
+
+impl From<Crate> for ReportEntry {
+    fn from(json_crate: Crate) -> Self {
+        let crate_ids = |crate_ids: Vec<CrateId>| {
+            crate_ids
+                .into_iter()
+                .map(cargo_geiger_serde::PackageId::from)
+                .collect::<HashSet<_>>()
+        };
+        let (forbids_unsafe, denies_unsafe, conditionally_forbids_unsafe) =
+            match json_crate.unsafe_code_lint {
+                UnsafeCodeLint::Forbid => (true, false, false),
+                UnsafeCodeLint::Deny => (false, true, false),
+                UnsafeCodeLint::Conditional => (false, false, true),
+                UnsafeCodeLint::None => (false, false, false),
+            };
+        ReportEntry {
+            package: PackageInfo {
+                id: cargo_geiger_serde::PackageId::from(json_crate.id),
+                dependencies: crate_ids(json_crate.dependencies),
+                dev_dependencies: crate_ids(json_crate.dev_dependencies),
+                build_dependencies: crate_ids(json_crate.build_dependencies),
+            },
+            unsafety: UnsafeInfo {
+                used: CounterBlock::from(json_crate.used),
+                unused: CounterBlock::from(json_crate.unused),
+                forbids_unsafe,
+                denies_unsafe,
+                conditionally_forbids_unsafe,
+                ffi: FfiCount {
+                    used: json_crate.ffi.used,
+                    unused: json_crate.ffi.unused,
+                },
+                unsafe_introduced_at: json_crate.unsafe_introduced_at,
+                downloads: json_crate.downloads,
+                has_security_policy: json_crate.has_security_policy,
+                checksum_mismatch: json_crate.checksum_mismatch,
+                advisories: json_crate.advisories,
+                unsafe_findings: json_crate.unsafe_findings.map(
+                    |unsafe_findings| {
+                        unsafe_findings
+                            .into_iter()
+                            .map(UnsafeFinding::from)
+                            .collect()
+                    },
+                ),
+                dangerous_api_calls: json_crate.dangerous_api_calls,
+                expanded: json_crate.expanded.map(CounterBlock::from),
+                source_kind: json_crate.source_kind,
+                registry: json_crate.registry,
+                detector_findings: json_crate.detector_findings,
+                unsafe_density_percentile: json_crate.unsafe_density_percentile,
+                lines_of_code: json_crate.lines_of_code,
+            },
+        }
+    }
+}
+
+impl From<&cargo_geiger_serde::PackageId> for CrateId {
+    fn from(package_id: &cargo_geiger_serde::PackageId) -> Self {
+        CrateId {
+            name: package_id.name.clone(),
+            version: package_id.version.clone(),
+            source: match &package_id.source {
+                cargo_geiger_serde::Source::Git { url, rev } => Source::Git {
+                    url: url.clone(),
+                    rev: rev.clone(),
+                },
+                cargo_geiger_serde::Source::Registry { name, url } => {
+                    Source::Registry {
+                        name: name.clone(),
+                        url: url.clone(),
+                    }
+                }
+                cargo_geiger_serde::Source::Path(url) => {
+                    Source::Path { url: url.clone() }
+                }
+            },
+        }
+    }
+}
+
+impl From<CrateId> for cargo_geiger_serde::PackageId {
+    fn from(crate_id: CrateId) -> Self {
+        cargo_geiger_serde::PackageId {
+            name: crate_id.name,
+            version: crate_id.version,
+            source: match crate_id.source {
+                Source::Git { url, rev } => {
+                    cargo_geiger_serde::Source::Git { url, rev }
+                }
+                Source::Registry { name, url } => {
+                    cargo_geiger_serde::Source::Registry { name, url }
+                }
+                Source::Path { url } => cargo_geiger_serde::Source::Path(url),
+            },
+        }
+    }
+}
+
+fn sorted_crate_ids(
+    package_ids: &HashSet<cargo_geiger_serde::PackageId>,
+) -> Vec<CrateId> {
+    let mut crate_ids =
+        package_ids.iter().map(CrateId::from).collect::<Vec<_>>();
+    crate_ids.sort();
+    crate_ids
+}
+
+#[cfg(test)]
+mod v2_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_registry_package_id,
+        create_safety_report,
+    };
+
+    use cargo_geiger_serde::DependencyKind;
+    use rstest::*;
+
+    fn create_report() -> SafetyReport {
+        let foo_id = create_path_package_id("foo");
+        let bar_id = create_registry_package_id("bar", "0.1.0");
+        let baz_id = cargo_geiger_serde::PackageId {
+            source: cargo_geiger_serde::Source::Git {
+                url: Url::parse("https://github.com/foo/baz").unwrap(),
+                rev: String::from("abc123"),
+            },
+            ..create_path_package_id("baz")
+        };
+
+        let mut foo_package = PackageInfo::new(foo_id.clone());
+        foo_package.add_dependency(bar_id.clone(), DependencyKind::Normal);
+        foo_package.add_dependency(baz_id.clone(), DependencyKind::Build);
+        let mut foo_unsafety = UnsafeInfo {
+            forbids_unsafe: true,
+            ..Default::default()
+        };
+        foo_unsafety.used.functions.safe = 4;
+
+        let mut bar_unsafety = UnsafeInfo {
+            ffi: FfiCount { used: 1, unused: 2 },
+            unsafe_introduced_at: Some(Version::new(0, 0, 1)),
+            downloads: Some(1000),
+            has_security_policy: Some(false),
+            advisories: Some(vec![String::from("RUSTSEC-2021-0001")]),
+            unsafe_findings: Some(vec![UnsafeFinding {
+                file: String::from("src/lib.rs"),
+                kind: UnsafeKind::Block,
+                start_line: 1,
+                start_column: 2,
+                end_line: 3,
+                end_column: 4,
+            }]),
+            dangerous_api_calls: Some(BTreeMap::from([(
+                String::from("mem::transmute"),
+                1,
+            )])),
+            lines_of_code: Some(400),
+            ..Default::default()
+        };
+        bar_unsafety.used.exprs.unsafe_ = 2;
+        bar_unsafety.unused.methods.unsafe_ = 1;
+
+        let mut report = create_safety_report(vec![
+            (foo_package, foo_unsafety),
+            (PackageInfo::new(bar_id), bar_unsafety),
+        ]);
+        report.packages_without_metrics.insert(baz_id);
+        report
+            .used_but_not_scanned_files
+            .insert(PathBuf::from("/foo/build.rs"));
+        report
+    }
+
+    #[rstest]
+    fn json_report_round_trip_test() {
+        let report = create_report();
+
+        let json_line =
+            serde_json::to_string(&JsonReport::from(&report)).unwrap();
+        let json_report =
+            serde_json::from_str::<JsonReport>(&json_line).unwrap();
+
+        assert_eq!(json_report, JsonReport::from(&report));
+        assert_eq!(SafetyReport::from(json_report), report);
+    }
+
+    #[rstest(
+        input_unsafe_code_lint,
+        case(UnsafeCodeLint::Forbid),
+        case(UnsafeCodeLint::Deny),
+        case(UnsafeCodeLint::Conditional),
+        case(UnsafeCodeLint::None)
+    )]
+    fn unsafe_code_lint_round_trip_test(
+        input_unsafe_code_lint: UnsafeCodeLint,
+    ) {
+        let mut json_crate =
+            Crate::from(create_report().packages.values().next().unwrap());
+        json_crate.unsafe_code_lint = input_unsafe_code_lint;
+
+        let report_entry = ReportEntry::from(json_crate);
+
+        assert_eq!(
+            UnsafeCodeLint::from_unsafe_info(&report_entry.unsafety),
+            input_unsafe_code_lint
+        );
+    }
+
+    /// The fields and values of the schema, which the tests above wouldn't
+    /// notice being renamed
+    #[rstest]
+    fn json_report_schema_test() {
+        let json_value =
+            serde_json::to_value(JsonReport::from(&create_report())).unwrap();
+
+        assert_eq!(
+            json_value,
+            serde_json::json!({
+                "schema_version": 2,
+                "crates": [
+                    {
+                        "id": {
+                            "name": "bar",
+                            "version": "0.1.0",
+                            "source": {
+                                "kind": "registry",
+                                "name": "crates.io",
+                                "url": "https://github.com/rust-lang/crates.io-index"
+                            }
+                        },
+                        "status": "unsafe_detected",
+                        "unsafe_code_lint": "none",
+                        "used": {
+                            "functions": {"safe": 0, "unsafe": 0},
+                            "expressions": {"safe": 0, "unsafe": 2},
+                            "impls": {"safe": 0, "unsafe": 0},
+                            "traits": {"safe": 0, "unsafe": 0},
+                            "methods": {"safe": 0, "unsafe": 0}
+                        },
+                        "unused": {
+                            "functions": {"safe": 0, "unsafe": 0},
+                            "expressions": {"safe": 0, "unsafe": 0},
+                            "impls": {"safe": 0, "unsafe": 0},
+                            "traits": {"safe": 0, "unsafe": 0},
+                            "methods": {"safe": 0, "unsafe": 1}
+                        },
+                        "ffi": {"used": 1, "unused": 2},
+                        "dependencies": [],
+                        "dev_dependencies": [],
+                        "build_dependencies": [],
+                        "unsafe_introduced_at": "0.0.1",
+                        "downloads": 1000,
+                        "has_security_policy": false,
+                        "advisories": ["RUSTSEC-2021-0001"],
+                        "unsafe_findings": [
+                            {
+                                "file": "src/lib.rs",
+                                "kind": "block",
+                                "start_line": 1,
+                                "start_column": 2,
+                                "end_line": 3,
+                                "end_column": 4
+                            }
+                        ],
+                        "dangerous_api_calls": {"mem::transmute": 1},
+                        "lines_of_code": 400,
+                        "unsafe_per_kloc": 5.0
+                    },
+                    {
+                        "id": {
+                            "name": "foo",
+                            "version": "0.1.0",
+                            "source": {"kind": "path", "url": "file:///foo"}
+                        },
+                        "status": "forbids_unsafe",
+                        "unsafe_code_lint": "forbid",
+                        "used": {
+                            "functions": {"safe": 4, "unsafe": 0},
+                            "expressions": {"safe": 0, "unsafe": 0},
+                            "impls": {"safe": 0, "unsafe": 0},
+                            "traits": {"safe": 0, "unsafe": 0},
+                            "methods": {"safe": 0, "unsafe": 0}
+                        },
+                        "unused": {
+                            "functions": {"safe": 0, "unsafe": 0},
+                            "expressions": {"safe": 0, "unsafe": 0},
+                            "impls": {"safe": 0, "unsafe": 0},
+                            "traits": {"safe": 0, "unsafe": 0},
+                            "methods": {"safe": 0, "unsafe": 0}
+                        },
+                        "ffi": {"used": 0, "unused": 0},
+                        "dependencies": [
+                            {
+                                "name": "bar",
+                                "version": "0.1.0",
+                                "source": {
+                                    "kind": "registry",
+                                    "name": "crates.io",
+                                    "url": "https://github.com/rust-lang/crates.io-index"
+                                }
+                            }
+                        ],
+                        "dev_dependencies": [],
+                        "build_dependencies": [
+                            {
+                                "name": "baz",
+                                "version": "0.1.0",
+                                "source": {
+                                    "kind": "git",
+                                    "url": "https://github.com/foo/baz",
+                                    "rev": "abc123"
+                                }
+                            }
+                        ],
+                        "unsafe_per_kloc": null
+                    }
+                ],
+                "crates_without_metrics": [
+                    {
+                        "name": "baz",
+                        "version": "0.1.0",
+                        "source": {
+                            "kind": "git",
+                            "url": "https://github.com/foo/baz",
+                            "rev": "abc123"
+                        }
+                    }
+                ],
+                "ignored_crates": [],
+                "used_but_not_scanned_files": ["/foo/build.rs"],
+                "summary": {
+                    "crates_scanned": 2,
+                    "crates_using_unsafe": 1,
+                    "used": {
+                        "functions": {"safe": 4, "unsafe": 0},
+                        "expressions": {"safe": 0, "unsafe": 2},
+                        "impls": {"safe": 0, "unsafe": 0},
+                        "traits": {"safe": 0, "unsafe": 0},
+                        "methods": {"safe": 0, "unsafe": 0}
+                    },
+                    "unused": {
+                        "functions": {"safe": 0, "unsafe": 0},
+                        "expressions": {"safe": 0, "unsafe": 0},
+                        "impls": {"safe": 0, "unsafe": 0},
+                        "traits": {"safe": 0, "unsafe": 0},
+                        "methods": {"safe": 0, "unsafe": 1}
+                    }
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use crate::format::json::v2::{Crate, CrateId, JsonReport, Summary};
+
+use cargo_geiger_serde::SafetyReport;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// A line of the `JsonLines` output, told apart by its `record`: a `crate`
+/// record for each crate, and a `summary` record last. The fields are those
+/// of the Json output of version 2.
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case", tag = "record")]
+enum JsonLinesRecord<'a> {
+    Crate {
+        schema_version: u32,
+        #[serde(flatten)]
+        json_crate: &'a Crate,
+    },
+    Summary {
+        schema_version: u32,
+        crates_without_metrics: &'a [CrateId],
+        ignored_crates: &'a [CrateId],
+        used_but_not_scanned_files: &'a [PathBuf],
+        #[serde(flatten)]
+        summary: &'a Summary,
+    },
+}
+
+/// A line of Json for each crate, ordered as in the Json output, and a line
+/// with the totals, so that a large report can be read a record at a time
+pub fn create_json_lines(report: &SafetyReport) -> Vec<String> {
+    let json_report = JsonReport::from(report);
+    json_report
+        .crates
+        .iter()
+        .map(|json_crate| JsonLinesRecord::Crate {
+            schema_version: json_report.schema_version,
+            json_crate,
+        })
+        .chain(std::iter::once(JsonLinesRecord::Summary {
+            schema_version: json_report.schema_version,
+            crates_without_metrics: &json_report.crates_without_metrics,
+            ignored_crates: &json_report.ignored_crates,
+            used_but_not_scanned_files: &json_report.used_but_not_scanned_files,
+            summary: &json_report.summary,
+        }))
+        .map(|json_lines_record| {
+            serde_json::to_string(&json_lines_record).unwrap()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod json_lines_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::PackageInfo;
+    use rstest::*;
+
+    #[rstest]
+    fn create_json_lines_test() {
+        let report = create_safety_report(
+            vec![("foo", 2), ("bar", 0)]
+                .into_iter()
+                .map(|(name, used_exprs)| {
+                    (
+                        PackageInfo::new(create_path_package_id(name)),
+                        create_unsafe_info(used_exprs),
+                    )
+                })
+                .collect(),
+        );
+
+        let json_values = create_json_lines(&report)
+            .iter()
+            .map(|json_line| {
+                serde_json::from_str::<serde_json::Value>(json_line).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(json_values.len(), 3);
+        assert_eq!(json_values[0]["record"], "crate");
+        assert_eq!(json_values[0]["schema_version"], 2);
+        assert_eq!(json_values[0]["id"]["name"], "bar");
+        assert_eq!(json_values[1]["id"]["name"], "foo");
+        assert_eq!(json_values[1]["status"], "unsafe_detected");
+        assert_eq!(json_values[1]["used"]["expressions"]["unsafe"], 2);
+        assert_eq!(json_values[2]["record"], "summary");
+        assert_eq!(json_values[2]["crates_scanned"], 2);
+        assert_eq!(json_values[2]["crates_using_unsafe"], 1);
+        assert_eq!(
+            json_values[2]["crates_without_metrics"],
+            serde_json::json!([])
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use crate::format::print_config::OutputFormat;
+use crate::format::table::{table_row, UNSAFE_COUNTERS_HEADER};
+
+use cargo_geiger_serde::CounterBlock;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The license a group of crates is published under, as written in their
+/// manifests, such as `MIT OR Apache-2.0`. The crates without a license are
+/// grouped last.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum LicenseGroup {
+    License(String),
+    Unknown,
+}
+
+impl LicenseGroup {
+    pub fn from_license(license: Option<String>) -> Self {
+        match license {
+            Some(license) => LicenseGroup::License(license),
+            None => LicenseGroup::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for LicenseGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseGroup::License(license) => write!(f, "{}", license),
+            LicenseGroup::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// A crate to be placed in a group
+pub struct LicenseGroupRow {
+    pub license_group: LicenseGroup,
+    pub name: String,
+    pub unused: CounterBlock,
+    pub used: CounterBlock,
+}
+
+/// A group of the Json output, with the summed unsafe usage of its crates
+#[derive(Debug, Eq, PartialEq, Serialize)]
+struct LicenseGroupSummary {
+    license: String,
+    crate_count: u64,
+    total_used_unsafe: u64,
+    crates: Vec<String>,
+    used: CounterBlock,
+    unused: CounterBlock,
+}
+
+/// Renders the summed unsafe usage of each license, followed by its crates,
+/// with the crates without a license last
+pub fn create_license_group_lines(
+    rows: Vec<LicenseGroupRow>,
+    hide_zeros: bool,
+    output_format: OutputFormat,
+) -> Vec<String> {
+    let mut license_group_lines = vec![UNSAFE_COUNTERS_HEADER.join(" ")];
+
+    for (license_group, rows) in group_rows(rows) {
+        let (used, unused) = sum_rows(&rows);
+
+        license_group_lines.push(String::new());
+        license_group_lines.push(format!(
+            "{}  {} ({} crates)",
+            table_row(&used, &unused, hide_zeros, output_format),
+            license_group,
+            rows.len()
+        ));
+        for row in rows {
+            license_group_lines.push(format!(
+                "{}      {}",
+                table_row(&row.used, &row.unused, hide_zeros, output_format),
+                row.name
+            ));
+        }
+    }
+
+    license_group_lines.push(String::new());
+    license_group_lines
+}
+
+/// The groups as a Json object, `{"license_groups": [...]}`, in the order of
+/// the table
+pub fn create_license_group_json_line(rows: Vec<LicenseGroupRow>) -> String {
+    #[derive(Serialize)]
+    struct LicenseGroupsJson {
+        license_groups: Vec<LicenseGroupSummary>,
+    }
+
+    let license_groups = group_rows(rows)
+        .into_iter()
+        .map(|(license_group, rows)| {
+            let (used, unused) = sum_rows(&rows);
+            LicenseGroupSummary {
+                license: license_group.to_string(),
+                crate_count: rows.len() as u64,
+                total_used_unsafe: used.total_unsafe(),
+                crates: rows.into_iter().map(|row| row.name).collect(),
+                used,
+                unused,
+            }
+        })
+        .collect();
+
+    serde_json::to_string(&LicenseGroupsJson { license_groups }).unwrap()
+}
+
+/// The rows of each group, ordered by crate name
+fn group_rows(
+    rows: Vec<LicenseGroupRow>,
+) -> BTreeMap<LicenseGroup, Vec<LicenseGroupRow>> {
+    let mut license_groups =
+        BTreeMap::<LicenseGroup, Vec<LicenseGroupRow>>::new();
+    for row in rows {
+        license_groups
+            .entry(row.license_group.clone())
+            .or_default()
+            .push(row);
+    }
+    for rows in license_groups.values_mut() {
+        rows.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    license_groups
+}
+
+fn sum_rows(rows: &[LicenseGroupRow]) -> (CounterBlock, CounterBlock) {
+    rows.iter().fold(
+        (CounterBlock::default(), CounterBlock::default()),
+        |(used, unused), row| {
+            (used + row.used.clone(), unused + row.unused.clone())
+        },
+    )
+}
+
+#[cfg(test)]
+mod license_groups_tests {
+    use super::*;
+
+    use cargo_geiger_serde::Count;
+    use rstest::*;
+
+    #[rstest(
+        input_license,
+        expected_license_group,
+        case(
+            Some(String::from("MIT")),
+            LicenseGroup::License(String::from("MIT"))
+        ),
+        case(None, LicenseGroup::Unknown)
+    )]
+    fn license_group_from_license_test(
+        input_license: Option<String>,
+        expected_license_group: LicenseGroup,
+    ) {
+        assert_eq!(
+            LicenseGroup::from_license(input_license),
+            expected_license_group
+        );
+    }
+
+    #[rstest]
+    fn create_license_group_lines_test() {
+        let license_group_lines = create_license_group_lines(
+            create_license_group_rows(),
+            false,
+            OutputFormat::Ascii,
+        );
+
+        assert_eq!(
+            license_group_lines,
+            vec![
+                String::from(
+                    "Functions  Expressions  Impls  Traits  Methods  Dependency"
+                ),
+                String::new(),
+                String::from(
+                    "0/0        2/2          0/0    0/0     0/0      Apache-2.0 (1 crates)"
+                ),
+                String::from(
+                    "0/0        2/2          0/0    0/0     0/0          baz 0.1.0"
+                ),
+                String::new(),
+                String::from(
+                    "0/0        4/4          0/0    0/0     0/0      MIT (2 crates)"
+                ),
+                String::from(
+                    "0/0        3/3          0/0    0/0     0/0          bar 0.1.0"
+                ),
+                String::from(
+                    "0/0        1/1          0/0    0/0     0/0          foo 0.1.0"
+                ),
+                String::new(),
+                String::from(
+                    "0/0        0/0          0/0    0/0     0/0      unknown (1 crates)"
+                ),
+                String::from(
+                    "0/0        0/0          0/0    0/0     0/0          qux 0.1.0"
+                ),
+                String::new(),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn create_license_group_json_line_test() {
+        let json_value = serde_json::from_str::<serde_json::Value>(
+            &create_license_group_json_line(create_license_group_rows()),
+        )
+        .unwrap();
+
+        let license_groups = json_value["license_groups"].as_array().unwrap();
+        assert_eq!(license_groups.len(), 3);
+        assert_eq!(license_groups[1]["license"], "MIT");
+        assert_eq!(license_groups[1]["crate_count"], 2);
+        assert_eq!(license_groups[1]["total_used_unsafe"], 4);
+        assert_eq!(license_groups[1]["crates"][0], "bar 0.1.0");
+        assert_eq!(license_groups[2]["license"], "unknown");
+    }
+
+    fn create_license_group_rows() -> Vec<LicenseGroupRow> {
+        vec![
+            create_license_group_row("foo 0.1.0", Some("MIT"), 1),
+            create_license_group_row("qux 0.1.0", None, 0),
+            create_license_group_row("baz 0.1.0", Some("Apache-2.0"), 2),
+            create_license_group_row("bar 0.1.0", Some("MIT"), 3),
+        ]
+    }
+
+    fn create_license_group_row(
+        name: &str,
+        license: Option<&str>,
+        unsafe_exprs: u64,
+    ) -> LicenseGroupRow {
+        LicenseGroupRow {
+            license_group: LicenseGroup::from_license(
+                license.map(String::from),
+            ),
+            name: String::from(name),
+            unused: CounterBlock::default(),
+            used: CounterBlock {
+                exprs: Count {
+                    safe: 0,
+                    unsafe_: unsafe_exprs,
+                },
+                ..Default::default()
+            },
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::format::CrateDetectionStatus;
+
+use cargo_geiger_serde::SafetyReport;
+use std::collections::HashMap;
+
+/// The styles of the classes of the nodes, after the colors `colorize` uses
+/// in a terminal. The crates allowing unsafe code without using it keep the
+/// default style.
+const CLASS_DEFINITIONS: [&str; 4] = [
+    "    classDef forbids fill:#c8f7c5,stroke:#2e7d32",
+    "    classDef denies fill:#fff3b0,stroke:#b8860b",
+    "    classDef conditional fill:#c5f1f7,stroke:#00838f",
+    "    classDef unsafe fill:#f8c4c4,stroke:#c62828,stroke-width:2px",
+];
+
+/// A Mermaid flowchart of the dependency graph in a fenced `mermaid` block,
+/// which GitHub renders in issues, pull requests and wikis. There is a node
+/// for each crate in the report, labeled with the crate and its unsafe code
+/// used by the build and found in total, such as `2/3`, with the class of
+/// its status, and an edge to each of its dependencies, dotted and labeled
+/// for build and dev dependencies. Nodes and edges are ordered by crate.
+pub fn create_mermaid_lines(report: &SafetyReport) -> Vec<String> {
+    let mut entries = report.packages.values().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+    let node_indices = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| (&entry.package.id, index))
+        .collect::<HashMap<_, _>>();
+
+    let mut mermaid_lines =
+        vec![String::from("```mermaid"), String::from("flowchart TD")];
+    mermaid_lines.extend(CLASS_DEFINITIONS.iter().map(|line| line.to_string()));
+    for entry in &entries {
+        let used = entry.unsafety.used.total_unsafe();
+        let total = used + entry.unsafety.unused.total_unsafe();
+        let status = CrateDetectionStatus::from(&entry.unsafety);
+        mermaid_lines.push(format!(
+            "    n{}[\"{} {}<br/>{}/{}\"]{}",
+            node_indices[&entry.package.id],
+            escape_mermaid(&entry.package.id.name),
+            entry.package.id.version,
+            used,
+            total,
+            status_class(&status)
+        ));
+    }
+    for entry in &entries {
+        let package_info = &entry.package;
+        let edge_groups = [
+            (&package_info.dependencies, "-->"),
+            (&package_info.build_dependencies, "-. build .->"),
+            (&package_info.dev_dependencies, "-. dev .->"),
+        ];
+        for (dependencies, arrow) in edge_groups {
+            let mut dependency_indices = dependencies
+                .iter()
+                .filter_map(|dependency| node_indices.get(dependency).copied())
+                .collect::<Vec<_>>();
+            dependency_indices.sort_unstable();
+            for dependency_index in dependency_indices {
+                mermaid_lines.push(format!(
+                    "    n{} {} n{}",
+                    node_indices[&package_info.id], arrow, dependency_index
+                ));
+            }
+        }
+    }
+    mermaid_lines.push(String::from("```"));
+    mermaid_lines
+}
+
+fn status_class(status: &CrateDetectionStatus) -> &'static str {
+    match status {
+        CrateDetectionStatus::NoneDetectedForbidsUnsafe => ":::forbids",
+        CrateDetectionStatus::NoneDetectedDeniesUnsafe => ":::denies",
+        CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe => {
+            ":::conditional"
+        }
+        CrateDetectionStatus::NoneDetectedAllowsUnsafe => "",
+        CrateDetectionStatus::UnsafeDetected => ":::unsafe",
+    }
+}
+
+/// Quotes end a label, and are written as an entity code instead
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod mermaid_tests {
+    use super::*;
+
+    use crate::lib_tests::{create_path_package_id, create_safety_report};
+
+    use cargo_geiger_serde::{DependencyKind, PackageInfo, UnsafeInfo};
+    use rstest::*;
+
+    #[rstest]
+    fn create_mermaid_lines_test() {
+        let mut root = PackageInfo::new(create_path_package_id("root"));
+        root.add_dependency(
+            create_path_package_id("bar"),
+            DependencyKind::Normal,
+        );
+        root.add_dependency(
+            create_path_package_id("baz"),
+            DependencyKind::Development,
+        );
+        let mut bar_unsafety = UnsafeInfo::default();
+        bar_unsafety.used.methods.unsafe_ = 1;
+        let baz_unsafety = UnsafeInfo {
+            denies_unsafe: true,
+            ..Default::default()
+        };
+
+        let report = create_safety_report(vec![
+            (root, UnsafeInfo::default()),
+            (
+                PackageInfo::new(create_path_package_id("bar")),
+                bar_unsafety,
+            ),
+            (
+                PackageInfo::new(create_path_package_id("baz")),
+                baz_unsafety,
+            ),
+        ]);
+
+        let mermaid_lines = create_mermaid_lines(&report);
+
+        assert_eq!(mermaid_lines.first().unwrap(), "```mermaid");
+        assert_eq!(mermaid_lines.last().unwrap(), "```");
+        assert_eq!(
+            mermaid_lines[2 + CLASS_DEFINITIONS.len()..mermaid_lines.len() - 1],
+            [
+                "    n0[\"bar 0.1.0<br/>1/1\"]:::unsafe",
+                "    n1[\"baz 0.1.0<br/>0/0\"]:::denies",
+                "    n2[\"root 0.1.0<br/>0/0\"]",
+                "    n2 --> n0",
+                "    n2 -. dev .-> n1",
+            ]
+        );
+    }
+}
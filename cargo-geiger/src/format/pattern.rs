@@ -4,6 +4,7 @@ use crate::mapping::CargoMetadataParameters;
 
 use super::display::Display;
 
+use cargo_geiger_serde::UnsafeInfo;
 use cargo_metadata::PackageId;
 use std::error::Error;
 
@@ -17,18 +18,32 @@ impl Pattern {
         Pattern { chunks }
     }
 
+    /// The unsafe counts of `{fn}`, `{expr}`, `{impl}` and `{trait}`, and the
+    /// unsafe density of `{D}`, are taken from `unsafe_info`, and left out
+    /// without it
     pub fn display<'a>(
         &'a self,
         cargo_metadata_parameters: &'a CargoMetadataParameters,
         package: &'a PackageId,
+        unsafe_info: Option<&'a UnsafeInfo>,
     ) -> Display<'a> {
         Display {
             cargo_metadata_parameters,
             pattern: self,
             package,
+            unsafe_info,
         }
     }
 
+    /// The chunks of the pattern, such as `Package, Raw("-"), License`
+    pub fn describe_chunks(&self) -> String {
+        self.chunks
+            .iter()
+            .map(|chunk| format!("{:?}", chunk))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
     pub fn try_build(format: &str) -> Result<Pattern, Box<dyn Error>> {
         let mut chunks = vec![];
 
@@ -38,6 +53,13 @@ impl Pattern {
                 RawChunk::Argument("p") => Chunk::Package,
                 RawChunk::Argument("l") => Chunk::License,
                 RawChunk::Argument("r") => Chunk::Repository,
+                RawChunk::Argument("s") => Chunk::Source,
+                RawChunk::Argument("v") => Chunk::Version,
+                RawChunk::Argument("fn") => Chunk::UnsafeFunctions,
+                RawChunk::Argument("expr") => Chunk::UnsafeExprs,
+                RawChunk::Argument("impl") => Chunk::UnsafeImpls,
+                RawChunk::Argument("trait") => Chunk::UnsafeTraits,
+                RawChunk::Argument("D") => Chunk::UnsafePerKloc,
                 RawChunk::Argument(ref a) => {
                     return Err(format!("unsupported pattern `{}`", a).into());
                 }
@@ -61,6 +83,13 @@ mod pattern_tests {
         case("{p}", Pattern::new(vec![Chunk::Package])),
         case("{l}", Pattern::new(vec![Chunk::License])),
         case("{r}", Pattern::new(vec![Chunk::Repository])),
+        case("{s}", Pattern::new(vec![Chunk::Source])),
+        case("{v}", Pattern::new(vec![Chunk::Version])),
+        case("{fn}", Pattern::new(vec![Chunk::UnsafeFunctions])),
+        case("{expr}", Pattern::new(vec![Chunk::UnsafeExprs])),
+        case("{impl}", Pattern::new(vec![Chunk::UnsafeImpls])),
+        case("{trait}", Pattern::new(vec![Chunk::UnsafeTraits])),
+        case("{D}", Pattern::new(vec![Chunk::UnsafePerKloc])),
         case("Text", Pattern::new(vec![Chunk::Raw(String::from("Text"))])),
         case(
             "{p}-{l}-{r}-Text",
@@ -84,4 +113,34 @@ mod pattern_tests {
         assert!(pattern_result.is_ok());
         assert_eq!(pattern_result.unwrap(), expected_pattern);
     }
+
+    #[rstest(
+        input_format_string,
+        expected_chunks_description,
+        case("", ""),
+        case("{p}", "Package"),
+        case("{p}-{l}", "Package, Raw(\"-\"), License"),
+        case("{fn}/{expr}", "UnsafeFunctions, Raw(\"/\"), UnsafeExprs")
+    )]
+    fn pattern_describe_chunks_test(
+        input_format_string: &str,
+        expected_chunks_description: &str,
+    ) {
+        assert_eq!(
+            Pattern::try_build(input_format_string)
+                .unwrap()
+                .describe_chunks(),
+            expected_chunks_description
+        );
+    }
+
+    #[rstest(
+        input_format_string,
+        case("{x}"),
+        case("{p} {version}"),
+        case("{method}")
+    )]
+    fn pattern_try_build_test_unsupported(input_format_string: &str) {
+        assert!(Pattern::try_build(input_format_string).is_err());
+    }
 }
@@ -0,0 +1,111 @@
+use crate::format::aligned::status_label;
+use crate::format::CrateDetectionStatus;
+
+use cargo_geiger_serde::CounterBlock;
+use cargo_metadata::semver::Version;
+
+/// The columns of the Plain output. Scripts read them by position, so they
+/// are kept in this order, and new columns only ever go last.
+const PLAIN_HEADER: [&str; 6] = [
+    "depth",
+    "name",
+    "version",
+    "used_unsafe",
+    "total_unsafe",
+    "status",
+];
+
+const PLAIN_DELIMITER: &str = "\t";
+
+/// A crate of the tree, printed as a line of the Plain output
+pub struct PlainRow<'a> {
+    /// The depth of the crate below the root, empty in the flat list of
+    /// `--only-unsafe`
+    pub depth: &'a str,
+    pub name: &'a str,
+    pub version: Option<&'a Version>,
+    pub status: &'a CrateDetectionStatus,
+    pub used: &'a CounterBlock,
+    pub unused: &'a CounterBlock,
+}
+
+/// The line naming the columns, printed above the crates
+pub fn create_plain_header_line() -> String {
+    PLAIN_HEADER.join(PLAIN_DELIMITER)
+}
+
+/// The fields of the crate separated by tabs, with the unsafe code of every
+/// category added up, as used by the build and found in total
+pub fn create_plain_line(plain_row: &PlainRow) -> String {
+    let used_unsafe = plain_row.used.total_unsafe();
+    [
+        plain_row.depth.to_string(),
+        plain_row.name.to_string(),
+        plain_row
+            .version
+            .map_or_else(String::new, |version| version.to_string()),
+        used_unsafe.to_string(),
+        (used_unsafe + plain_row.unused.total_unsafe()).to_string(),
+        status_label(plain_row.status).to_string(),
+    ]
+    .join(PLAIN_DELIMITER)
+}
+
+#[cfg(test)]
+mod plain_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest]
+    fn create_plain_header_line_test() {
+        assert_eq!(
+            create_plain_header_line(),
+            "depth\tname\tversion\tused_unsafe\ttotal_unsafe\tstatus"
+        );
+    }
+
+    #[rstest(
+        input_depth,
+        input_status,
+        input_used_exprs,
+        expected_plain_line,
+        case(
+            "2",
+            CrateDetectionStatus::UnsafeDetected,
+            3,
+            "2\tfoo\t0.1.0\t4\t9\tunsafe"
+        ),
+        case(
+            "",
+            CrateDetectionStatus::NoneDetectedForbidsUnsafe,
+            0,
+            "\tfoo\t0.1.0\t1\t6\tforbids"
+        )
+    )]
+    fn create_plain_line_test(
+        input_depth: &str,
+        input_status: CrateDetectionStatus,
+        input_used_exprs: u64,
+        expected_plain_line: &str,
+    ) {
+        let mut used = CounterBlock::default();
+        used.exprs.unsafe_ = input_used_exprs;
+        used.functions.unsafe_ = 1;
+        let mut unused = CounterBlock::default();
+        unused.methods.unsafe_ = 5;
+        let version = Version::new(0, 1, 0);
+
+        assert_eq!(
+            create_plain_line(&PlainRow {
+                depth: input_depth,
+                name: "foo",
+                version: Some(&version),
+                status: &input_status,
+                used: &used,
+                unused: &unused,
+            }),
+            expected_plain_line
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use crate::diff::diff_reports;
+use crate::format::json::ReportSummary;
+use crate::format::table::UNSAFE_CATEGORY_NAMES;
+
+use cargo_geiger_serde::{CounterBlock, SafetyReport};
+
+/// The first line of the comment, invisible once rendered, by which a workflow
+/// can find the comment it posted before and update it
+pub const PR_COMMENT_MARKER: &str = "<!-- cargo-geiger -->";
+
+/// A Markdown fragment to post as a pull request comment: a table of the
+/// unsafe code used by the build and found in total in each category, and the
+/// number of crates using unsafe code. With a `--baseline` report, the table
+/// has a column with the difference in used unsafe code, and the crates using
+/// more unsafe code than in the baseline are listed.
+pub fn create_pr_comment_lines(
+    report: &SafetyReport,
+    baseline: Option<&SafetyReport>,
+) -> Vec<String> {
+    let report_summary = ReportSummary::from_report(report);
+    let baseline_summary = baseline.map(ReportSummary::from_report);
+
+    let mut pr_comment_lines = vec![
+        String::from(PR_COMMENT_MARKER),
+        String::from("### cargo-geiger"),
+        String::new(),
+    ];
+    match &baseline_summary {
+        Some(_) => {
+            pr_comment_lines.push(String::from(
+                "| Unsafe | Used by the build | Found | Since the baseline |",
+            ));
+            pr_comment_lines.push(String::from("| --- | --: | --: | --: |"));
+        }
+        None => {
+            pr_comment_lines
+                .push(String::from("| Unsafe | Used by the build | Found |"));
+            pr_comment_lines.push(String::from("| --- | --: | --: |"));
+        }
+    }
+    let used_counts = category_counts(&report_summary.used);
+    let unused_counts = category_counts(&report_summary.unused);
+    let baseline_counts = baseline_summary
+        .as_ref()
+        .map(|baseline_summary| category_counts(&baseline_summary.used));
+    let total = |counts: &[u64; 5]| counts.iter().sum::<u64>();
+    let rows = UNSAFE_CATEGORY_NAMES
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            (
+                name.to_string(),
+                used_counts[index],
+                unused_counts[index],
+                baseline_counts.map(|baseline_counts| baseline_counts[index]),
+            )
+        })
+        .chain(std::iter::once((
+            String::from("**total**"),
+            total(&used_counts),
+            total(&unused_counts),
+            baseline_counts.as_ref().map(total),
+        )));
+    for (name, used, unused, baseline_used) in rows {
+        let mut row = format!("| {} | {} | {} |", name, used, used + unused);
+        if let Some(baseline_used) = baseline_used {
+            row.push_str(&format!(
+                " {} |",
+                format_delta(used as i64 - baseline_used as i64)
+            ));
+        }
+        pr_comment_lines.push(row);
+    }
+    pr_comment_lines.push(String::new());
+    pr_comment_lines.push(format!(
+        "{} of {} crates use unsafe code.",
+        report_summary.crates_using_unsafe, report_summary.crates_scanned
+    ));
+
+    if let Some(baseline) = baseline {
+        let regressions = diff_reports(baseline, report)
+            .into_iter()
+            .filter(|crate_diff| crate_diff.is_regression())
+            .map(|crate_diff| {
+                format!("`{} {}`", crate_diff.name, crate_diff.version)
+            })
+            .collect::<Vec<_>>();
+        pr_comment_lines.push(String::new());
+        pr_comment_lines.push(if regressions.is_empty() {
+            String::from("No crate uses more unsafe code than in the baseline.")
+        } else {
+            format!(
+                ":warning: Using more unsafe code than in the baseline: {}",
+                regressions.join(", ")
+            )
+        });
+    }
+    pr_comment_lines
+}
+
+fn category_counts(counter_block: &CounterBlock) -> [u64; 5] {
+    [
+        counter_block.functions.unsafe_,
+        counter_block.exprs.unsafe_,
+        counter_block.item_impls.unsafe_,
+        counter_block.item_traits.unsafe_,
+        counter_block.methods.unsafe_,
+    ]
+}
+
+fn format_delta(value: i64) -> String {
+    if value > 0 {
+        format!("+{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod pr_comment_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::PackageInfo;
+    use rstest::*;
+
+    fn create_report(crates: &[(&str, u64, u64)]) -> SafetyReport {
+        create_safety_report(
+            crates
+                .iter()
+                .map(|(name, used_exprs, unused_functions)| {
+                    let mut unsafety = create_unsafe_info(*used_exprs);
+                    unsafety.unused.functions.unsafe_ = *unused_functions;
+                    (PackageInfo::new(create_path_package_id(name)), unsafety)
+                })
+                .collect(),
+        )
+    }
+
+    #[rstest]
+    fn create_pr_comment_lines_test() {
+        let report = create_report(&[("bar", 2, 1), ("foo", 0, 0)]);
+
+        assert_eq!(
+            create_pr_comment_lines(&report, None),
+            vec![
+                PR_COMMENT_MARKER,
+                "### cargo-geiger",
+                "",
+                "| Unsafe | Used by the build | Found |",
+                "| --- | --: | --: |",
+                "| functions | 0 | 1 |",
+                "| expressions | 2 | 2 |",
+                "| impls | 0 | 0 |",
+                "| traits | 0 | 0 |",
+                "| methods | 0 | 0 |",
+                "| **total** | 2 | 3 |",
+                "",
+                "1 of 2 crates use unsafe code.",
+            ]
+        );
+    }
+
+    #[rstest(
+        input_baseline_crates,
+        expected_total_row,
+        expected_last_line,
+        case(
+            vec![("bar", 1, 0)],
+            "| **total** | 2 | 3 | +1 |",
+            ":warning: Using more unsafe code than in the baseline: `bar 0.1.0`"
+        ),
+        case(
+            vec![("bar", 3, 0)],
+            "| **total** | 2 | 3 | -1 |",
+            "No crate uses more unsafe code than in the baseline."
+        )
+    )]
+    fn create_pr_comment_lines_test_baseline(
+        input_baseline_crates: Vec<(&str, u64, u64)>,
+        expected_total_row: &str,
+        expected_last_line: &str,
+    ) {
+        let report = create_report(&[("bar", 2, 1)]);
+        let baseline = create_report(&input_baseline_crates);
+
+        let pr_comment_lines =
+            create_pr_comment_lines(&report, Some(&baseline));
+
+        assert_eq!(
+            pr_comment_lines[3],
+            "| Unsafe | Used by the build | Found | Since the baseline |"
+        );
+        assert_eq!(pr_comment_lines[10], expected_total_row);
+        assert_eq!(pr_comment_lines.last().unwrap(), expected_last_line);
+    }
+}
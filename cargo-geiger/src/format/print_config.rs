@@ -1,15 +1,55 @@
+use crate::allowlist::{read_allowlist_file, Allowlist};
 use crate::args::Args;
 use crate::format::pattern::Pattern;
-use crate::format::{CrateDetectionStatus, FormatError};
+use crate::format::{Charset, CrateDetectionStatus, FormatError, Symbols};
+use crate::ignore::IgnoreList;
+use crate::license::LicenseExpression;
+use crate::source_kind::SourceFilter;
 
 use cargo::util::errors::CliError;
+use cargo_metadata::DependencyKind;
 use colored::{ColoredString, Colorize};
+use geiger::detector::BuiltInDetector;
 use geiger::IncludeTests;
 use petgraph::{Direction, EdgeDirection};
+use std::env;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use terminal_supports_emoji::{Stream, supports_emoji};
+
+/// Whether the build dependency edges of the graph are traversed, printed and
+/// counted, which `--no-build-deps` turns off. The dev dependencies are
+/// analyzed or not by the graph alone.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IncludeBuildDeps {
+    Yes,
+    No,
+}
+
+impl IncludeBuildDeps {
+    pub fn allows(self, dependency_kind: DependencyKind) -> bool {
+        self == IncludeBuildDeps::Yes
+            || dependency_kind != DependencyKind::Build
+    }
+}
+
+/// Whether the scan results of packages from crates.io are read from and
+/// written to the on-disk cache
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScanCacheMode {
+    Enabled,
+    /// Written but not read, with `--refresh`
+    Refresh,
+    /// Neither read nor written, with `--no-cache`
+    Disabled,
+}
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Prefix {
     Depth,
+    /// The depth in a column of fixed width, separated from the crate
+    DepthColumn,
     Indent,
     None,
 }
@@ -17,9 +57,41 @@ pub enum Prefix {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum OutputFormat {
     Ascii,
+    /// A row with the unsafe counts of each crate, for spreadsheets
+    Csv,
+    /// A `CycloneDX` bill of materials with the unsafe metrics of each crate as
+    /// the properties of its component
+    CycloneDx,
+    /// A Graphviz digraph of the dependency graph, with the nodes colored by
+    /// the status of their crate
+    Dot,
     Json,
+    /// A line of Json for each crate and a line with the totals
+    JsonLines,
+    /// A GitHub Actions warning annotation for each unsafe item of the
+    /// workspace members, shown inline in pull requests
+    GitHubActions,
     GitHubMarkdown,
+    /// A Mermaid flowchart of the dependency graph, which GitHub renders in
+    /// Markdown
+    Mermaid,
+    /// A standalone page with a summary table, the dependency tree and a
+    /// section for each crate, colored with inline styles
+    Html,
+    /// The tree as a line of tab separated columns for each crate, without
+    /// colors or symbols, for scripts
+    Plain,
+    /// A Markdown fragment with the totals, and the difference to the
+    /// `--baseline`, to post as a pull request comment
+    PrComment,
     Ratio,
+    /// A SARIF log with a result for each crate using unsafe code, for code
+    /// scanning tools
+    Sarif,
+    /// The total used unsafe code as a shields.io endpoint badge
+    ShieldsIo,
+    /// The data of the Json report as a TOML document
+    Toml,
     Utf8,
 }
 
@@ -29,14 +101,48 @@ impl Default for OutputFormat {
     }
 }
 
+impl OutputFormat {
+    /// Whether the output is rendered from the `SafetyReport` for machines or
+    /// documents to read, rather than from the dependency tree
+    pub fn is_machine_readable(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Csv
+                | OutputFormat::CycloneDx
+                | OutputFormat::Dot
+                | OutputFormat::GitHubActions
+                | OutputFormat::Html
+                | OutputFormat::Json
+                | OutputFormat::JsonLines
+                | OutputFormat::Mermaid
+                | OutputFormat::PrComment
+                | OutputFormat::Sarif
+                | OutputFormat::ShieldsIo
+                | OutputFormat::Toml
+        )
+    }
+}
+
 impl std::str::FromStr for OutputFormat {
     type Err = OutputFormatParseError;
     fn from_str(s: &str) -> Result<Self, OutputFormatParseError> {
         match s {
             "Ascii" => Ok(Self::Ascii),
+            "Csv" => Ok(Self::Csv),
+            "CycloneDx" => Ok(Self::CycloneDx),
+            "Dot" => Ok(Self::Dot),
             "Json" => Ok(Self::Json),
+            "JsonLines" => Ok(Self::JsonLines),
+            "GitHubActions" => Ok(Self::GitHubActions),
             "GitHubMarkdown" => Ok(Self::GitHubMarkdown),
+            "Html" => Ok(Self::Html),
+            "Mermaid" => Ok(Self::Mermaid),
+            "Plain" => Ok(Self::Plain),
+            "PrComment" => Ok(Self::PrComment),
             "Ratio" => Ok(Self::Ratio),
+            "Sarif" => Ok(Self::Sarif),
+            "ShieldsIo" => Ok(Self::ShieldsIo),
+            "Toml" => Ok(Self::Toml),
             "Utf8" => Ok(Self::Utf8),
             _ => Err(OutputFormatParseError),
         }
@@ -52,62 +158,423 @@ impl std::fmt::Display for OutputFormatParseError {
 }
 impl std::error::Error for OutputFormatParseError {}
 
+/// A report written to a file with `--also-emit`, in one of the report
+/// formats, from the same scan as the output
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmittedReport {
+    pub output_format: OutputFormat,
+    pub path: PathBuf,
+}
+
+impl std::str::FromStr for EmittedReport {
+    type Err = EmittedReportParseError;
+    fn from_str(s: &str) -> Result<Self, EmittedReportParseError> {
+        let (output_format, path) =
+            s.split_once('=').ok_or(EmittedReportParseError)?;
+        let output_format = output_format
+            .parse::<OutputFormat>()
+            .map_err(|_| EmittedReportParseError)?;
+        if !output_format.is_machine_readable() || path.is_empty() {
+            return Err(EmittedReportParseError);
+        }
+        Ok(EmittedReport {
+            output_format,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EmittedReportParseError;
+impl std::fmt::Display for EmittedReportParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected <FORMAT>=<PATH>, FORMAT being one of the report \
+            formats: Csv, CycloneDx, Dot, GitHubActions, Html, Json, \
+            JsonLines, Mermaid, PrComment, Sarif, ShieldsIo or Toml"
+        )
+    }
+}
+impl std::error::Error for EmittedReportParseError {}
+
+/// The colors `colorize` marks each `CrateDetectionStatus` with
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ColorScheme {
+    /// Green for crates forbidding unsafe code, yellow for crates denying it,
+    /// cyan for crates forbidding it under a `cfg_attr` condition, and bold
+    /// red for crates using it
+    #[default]
+    Default,
+    /// Blue for crates forbidding unsafe code, sky blue for crates denying it,
+    /// bluish green for crates forbidding it under a `cfg_attr` condition,
+    /// and bold orange for crates using it, which stay apart with red-green
+    /// color blindness
+    Colorblind,
+}
+
+impl std::str::FromStr for ColorScheme {
+    type Err = ColorSchemeParseError;
+    fn from_str(s: &str) -> Result<Self, ColorSchemeParseError> {
+        match s {
+            "default" => Ok(Self::Default),
+            "colorblind" => Ok(Self::Colorblind),
+            _ => Err(ColorSchemeParseError),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ColorSchemeParseError;
+impl std::fmt::Display for ColorSchemeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "matching color scheme not found, expected default or colorblind"
+        )
+    }
+}
+impl std::error::Error for ColorSchemeParseError {}
+
+/// How `--sort` orders the crates of the tree. The name and unsafe orders
+/// print it as a flat list of each crate once instead, ties being broken by
+/// the crate name, while the other orders keep the tree and order the
+/// dependencies of each crate, the most unsafe first.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CrateSort {
+    Name,
+    /// By the total unsafe code used by the build, least first
+    Unsafe,
+    /// By the total unsafe code used by the build, most first
+    UnsafeDesc,
+    /// By the total unsafe code used by the build, keeping the tree
+    UnsafeCount,
+    /// By the share of the code used by the build which is unsafe, keeping
+    /// the tree
+    UnsafeRatio,
+    /// By the total unsafe code found, used by the build or not, keeping the
+    /// tree
+    TotalUnsafe,
+    /// By the FFI items used by the build, keeping the tree
+    Ffi,
+}
+
+impl CrateSort {
+    /// Whether the tree is kept, with the siblings of each level in this
+    /// order, rather than flattened
+    pub fn keeps_tree(self) -> bool {
+        matches!(
+            self,
+            CrateSort::UnsafeCount
+                | CrateSort::UnsafeRatio
+                | CrateSort::TotalUnsafe
+                | CrateSort::Ffi
+        )
+    }
+}
+
+impl std::str::FromStr for CrateSort {
+    type Err = CrateSortParseError;
+    fn from_str(s: &str) -> Result<Self, CrateSortParseError> {
+        match s {
+            "name" => Ok(Self::Name),
+            "unsafe" => Ok(Self::Unsafe),
+            "unsafe-desc" => Ok(Self::UnsafeDesc),
+            "unsafe-count" => Ok(Self::UnsafeCount),
+            "unsafe-ratio" => Ok(Self::UnsafeRatio),
+            "total-unsafe" => Ok(Self::TotalUnsafe),
+            "ffi" => Ok(Self::Ffi),
+            _ => Err(CrateSortParseError),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CrateSortParseError;
+impl std::fmt::Display for CrateSortParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "matching sort order not found, expected name, unsafe, \
+            unsafe-desc, unsafe-count, unsafe-ratio, total-unsafe or ffi"
+        )
+    }
+}
+impl std::error::Error for CrateSortParseError {}
+
+/// When `colorize` marks the crate statuses with colors, set with `--color`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ColorWhen {
+    Always,
+    /// Colors unless `NO_COLOR` is set or stdout is not a terminal
+    #[default]
+    Auto,
+    Never,
+}
+
+impl ColorWhen {
+    pub fn is_enabled(self) -> bool {
+        match self {
+            ColorWhen::Always => true,
+            // Follows colored, which honours `NO_COLOR`, `CLICOLOR_FORCE`
+            // and whether stdout is a terminal, unless overridden for the
+            // pager.
+            ColorWhen::Auto => {
+                colored::control::SHOULD_COLORIZE.should_colorize()
+            }
+            ColorWhen::Never => false,
+        }
+    }
+}
+
+impl std::str::FromStr for ColorWhen {
+    type Err = ColorWhenParseError;
+    fn from_str(s: &str) -> Result<Self, ColorWhenParseError> {
+        match s {
+            "always" => Ok(Self::Always),
+            "auto" => Ok(Self::Auto),
+            "never" => Ok(Self::Never),
+            _ => Err(ColorWhenParseError),
+        }
+    }
+}
+
+/// The coloring given with `--color`, or else with `CARGO_TERM_COLOR` as for
+/// cargo, `Auto` if neither is set
+fn color_when(
+    color: Option<&str>,
+    cargo_term_color: Option<&str>,
+) -> Result<ColorWhen, ColorWhenParseError> {
+    match color.or(cargo_term_color) {
+        Some(color) => color.parse(),
+        None => Ok(ColorWhen::Auto),
+    }
+}
+
+/// The symbols of an output format when `--symbols` isn't given: emoji for
+/// `GitHubMarkdown`, and for `Utf8` where the terminal supports them, else
+/// their ascii fallbacks
+fn default_symbols(
+    output_format: OutputFormat,
+    emoji_supported: bool,
+) -> Symbols {
+    match (output_format, emoji_supported) {
+        (OutputFormat::GitHubMarkdown, _) | (OutputFormat::Utf8, true) => {
+            Symbols::Emoji
+        }
+        _ => Symbols::Ascii,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ColorWhenParseError;
+impl std::fmt::Display for ColorWhenParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "matching color choice not found, expected always, auto or never"
+        )
+    }
+}
+impl std::error::Error for ColorWhenParseError {}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct PrintConfig {
     /// Don't truncate dependencies that have already been displayed.
     pub all: bool,
 
     pub allow_partial_results: bool,
+
+    /// Crates displayed as allowing unsafe code without using it, whatever
+    /// unsafe code they use.
+    pub allowlist: Option<Allowlist>,
+
+    /// The characters the tree is drawn with.
+    pub charset: Charset,
+
+    pub color: ColorWhen,
+    pub color_scheme: ColorScheme,
+
+    /// The detectors run on each scanned file after the unsafe counter.
+    pub detectors: Vec<BuiltInDetector>,
+
     pub direction: EdgeDirection,
 
+    /// Add the FFI column to the table.
+    pub ffi: bool,
+
     // Is anyone using this? This is a carry-over from cargo-tree.
     // TODO: Open a github issue to discuss deprecation.
     pub format: Pattern,
 
+    /// Render the cells of the human readable tables without any unsafe
+    /// code as `-` instead of `0/0`.
+    pub hide_zeros: bool,
+
+    /// The crates and files left out of the scan.
+    pub ignore_list: IgnoreList,
+
+    pub include_build_deps: IncludeBuildDeps,
+
     pub include_tests: IncludeTests,
+
+    /// The number of threads scanning the files, or the number of logical
+    /// CPUs if not given.
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Only crates with a matching license are reported.
+    pub license_filter: Option<LicenseExpression>,
+
+    /// Files which aren't valid UTF-8 are scanned with their invalid
+    /// sequences replaced, and the files which fail to be scanned are
+    /// recorded in the report.
+    pub lossy: bool,
+
+    /// The crates further below the root than this are left out of the tree,
+    /// and their unsafe code is added to the row of their ancestor at this
+    /// depth.
+    pub max_depth: Option<usize>,
+
+    /// Crates using fewer FFI items than this are left out of the tree, but
+    /// still counted in the totals.
+    pub min_ffi: u64,
+
+    /// Crates using less unsafe code than this in total are left out of the
+    /// tree, but still counted in the totals.
+    pub min_unsafe: u64,
+
+    /// Print only the crates using unsafe code, as a flat list, followed by a
+    /// summary line instead of the totals.
+    pub only_unsafe: bool,
+
     pub prefix: Prefix,
     pub output_format: OutputFormat,
+
+    pub scan_cache_mode: ScanCacheMode,
+
+    /// The directory of the cache given with `--cache-dir`, instead of the
+    /// default one
+    pub cache_dir: Option<PathBuf>,
+
+    /// The path packages, such as the workspace members, are also cached for
+    /// the time of a `--watch` session.
+    pub caches_path_packages: bool,
+
+    /// Write the report to this file, without colors, instead of to stdout.
+    pub output_file: Option<PathBuf>,
+
+    /// Print the crates as a flat list in this order, rather than as a tree.
+    pub sort: Option<CrateSort>,
+
+    /// Only crates from these kinds of source are reported.
+    pub source_filter: Option<SourceFilter>,
+
+    /// The symbols marking the status of each crate.
+    pub symbols: Symbols,
+
+    /// Print only the workspace members, and the root of the tree, while
+    /// counting every crate in the totals.
+    pub workspace_only: bool,
 }
 
 impl PrintConfig {
     pub fn new(args: &Args) -> Result<Self, CliError> {
-        // TODO: Add command line flag for this and make it default to false?
-        let allow_partial_results = true;
+        let allow_partial_results = !args.no_partial_results;
+
+        // Escape codes are never wanted in a file.
+        let color = match args.output_file {
+            Some(_) => ColorWhen::Never,
+            None => color_when(
+                args.color.as_deref(),
+                env::var("CARGO_TERM_COLOR").ok().as_deref(),
+            )
+            .map_err(|e| CliError::new(anyhow::Error::new(e), 1))?,
+        };
 
         let direction = match args.invert {
             true => EdgeDirection::Incoming,
             false => EdgeDirection::Outgoing,
         };
 
-        let format = Pattern::try_build(&args.format).map_err(|e| {
-            CliError::new(
-                (FormatError {
-                    message: e.to_string(),
-                })
-                .into(),
-                1,
-            )
-        })?;
+        let format = build_format_pattern(args)?;
+
+        let include_build_deps =
+            match (args.include_build_deps, args.no_build_deps) {
+                (false, true) => IncludeBuildDeps::No,
+                _ => IncludeBuildDeps::Yes,
+            };
 
         let include_tests = match args.include_tests {
             true => IncludeTests::Yes,
             false => IncludeTests::No,
         };
 
-        let prefix = match (args.prefix_depth, args.no_indent) {
-            (true, _) => Prefix::Depth,
-            (false, true) => Prefix::None,
-            (false, false) => Prefix::Indent,
+        // The Ascii and Utf8 output formats are the tables drawn with their
+        // charset, unless `--charset` or `--symbols` is given.
+        let charset = args.charset.unwrap_or(match args.output_format {
+            OutputFormat::Ascii => Charset::Ascii,
+            _ => Charset::Utf8,
+        });
+        let symbols = args.symbols.unwrap_or_else(|| {
+            default_symbols(args.output_format, supports_emoji(Stream::Stdout))
+        });
+
+        // The depth is the first column of the Plain output, whatever the
+        // prefix flags.
+        let prefix = match (
+            args.output_format,
+            args.depth_column,
+            args.prefix_depth,
+            args.no_indent,
+        ) {
+            (OutputFormat::Plain, _, _, _) => Prefix::Depth,
+            (_, true, _, _) => Prefix::DepthColumn,
+            (_, false, true, _) => Prefix::Depth,
+            (_, false, false, true) => Prefix::None,
+            (_, false, false, false) => Prefix::Indent,
+        };
+
+        let scan_cache_mode = match (args.no_cache, args.refresh) {
+            (true, _) => ScanCacheMode::Disabled,
+            (false, true) => ScanCacheMode::Refresh,
+            (false, false) => ScanCacheMode::Enabled,
         };
 
         Ok(PrintConfig {
             all: args.all,
             allow_partial_results,
+            allowlist: read_allowlist_file(&args.allowlist)?,
+            charset,
+            color,
+            color_scheme: args.color_scheme,
+            detectors: args.detectors.clone(),
             direction,
+            ffi: args.ffi,
             format,
+            hide_zeros: args.hide_zeros,
+            ignore_list: IgnoreList {
+                crate_names: args.ignore.clone(),
+                path_patterns: args.ignore_path.clone(),
+            },
+            include_build_deps,
             include_tests,
+            jobs: args.jobs,
+            license_filter: args.license_filter.clone(),
+            lossy: args.lossy,
+            max_depth: args.max_depth,
+            min_ffi: args.min_ffi,
+            min_unsafe: args.min_unsafe,
+            only_unsafe: args.only_unsafe,
+            output_file: args.output_file.clone(),
             output_format: args.output_format,
             prefix,
+            scan_cache_mode,
+            cache_dir: args.cache_dir.clone(),
+            caches_path_packages: args.watch,
+            sort: args.sort,
+            source_filter: args.only_source.clone(),
+            symbols,
+            workspace_only: args.workspace_only,
         })
     }
 }
@@ -117,27 +584,121 @@ impl Default for PrintConfig {
         PrintConfig {
             all: false,
             allow_partial_results: false,
+            allowlist: None,
+            charset: Charset::Utf8,
+            color: ColorWhen::Auto,
+            color_scheme: ColorScheme::Default,
+            detectors: vec![],
             direction: Direction::Outgoing,
+            ffi: false,
             format: Pattern::try_build("p").unwrap(),
+            hide_zeros: false,
+            ignore_list: IgnoreList::default(),
+            include_build_deps: IncludeBuildDeps::Yes,
             include_tests: IncludeTests::Yes,
+            jobs: None,
+            license_filter: None,
+            lossy: false,
+            max_depth: None,
+            min_ffi: 0,
+            min_unsafe: 0,
+            only_unsafe: false,
             prefix: Prefix::Depth,
+            output_file: None,
             output_format: Default::default(),
+            scan_cache_mode: ScanCacheMode::Disabled,
+            cache_dir: None,
+            caches_path_packages: false,
+            sort: None,
+            source_filter: None,
+            symbols: Symbols::Emoji,
+            workspace_only: false,
         }
     }
 }
 
+/// The format string of `--format-file`, without the trailing newlines of the
+/// file
+/// The pattern of `--format`, or of `--format-file`, which defaults to `{p}`
+pub fn build_format_pattern(args: &Args) -> Result<Pattern, CliError> {
+    let format_string = match (&args.format, &args.format_file) {
+        (Some(format), _) => format.clone(),
+        (None, Some(format_file)) => read_format_file(format_file)?,
+        (None, None) => String::from("{p}"),
+    };
+    Pattern::try_build(&format_string).map_err(|e| {
+        let message = match (&args.format, &args.format_file) {
+            (None, Some(format_file)) => {
+                format!("{} (in {})", e, format_file.display())
+            }
+            _ => e.to_string(),
+        };
+        CliError::new((FormatError { message }).into(), 1)
+    })
+}
+
+fn read_format_file(format_file: &Path) -> Result<String, CliError> {
+    let format_file_content = fs::read_to_string(format_file).map_err(|e| {
+        eprintln!(
+            "Failed to read format string from file: {}",
+            format_file.display()
+        );
+        anyhow::Error::from(e)
+    })?;
+    Ok(format_file_content
+        .trim_end_matches(['\n', '\r'])
+        .to_string())
+}
+
 pub fn colorize(
+    color: ColorWhen,
+    color_scheme: ColorScheme,
     crate_detection_status: &CrateDetectionStatus,
     output_format: OutputFormat,
     string: String,
 ) -> ColoredString {
-    match output_format {
-        OutputFormat::GitHubMarkdown => ColoredString::from(string.as_str()),
-        _ => match crate_detection_status {
+    match (output_format, color_scheme) {
+        (
+            OutputFormat::Csv
+            | OutputFormat::CycloneDx
+            | OutputFormat::Dot
+            | OutputFormat::GitHubActions
+            | OutputFormat::GitHubMarkdown
+            | OutputFormat::Html
+            | OutputFormat::JsonLines
+            | OutputFormat::Mermaid
+            | OutputFormat::Plain
+            | OutputFormat::PrComment
+            | OutputFormat::Sarif
+            | OutputFormat::Toml,
+            _,
+        ) => ColoredString::from(string.as_str()),
+        _ if !color.is_enabled() => string.normal(),
+        (_, ColorScheme::Default) => match crate_detection_status {
             CrateDetectionStatus::NoneDetectedForbidsUnsafe => string.green(),
+            CrateDetectionStatus::NoneDetectedDeniesUnsafe => string.yellow(),
+            CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe => {
+                string.cyan()
+            }
             CrateDetectionStatus::NoneDetectedAllowsUnsafe => string.normal(),
             CrateDetectionStatus::UnsafeDetected => string.red().bold(),
         },
+        // The blue, sky blue, bluish green and orange of the Okabe-Ito palette
+        (_, ColorScheme::Colorblind) => match crate_detection_status {
+            CrateDetectionStatus::NoneDetectedForbidsUnsafe => {
+                string.truecolor(0, 114, 178)
+            }
+            CrateDetectionStatus::NoneDetectedDeniesUnsafe => {
+                string.truecolor(86, 180, 233)
+            }
+            CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe => {
+                string.truecolor(0, 158, 115)
+            }
+            CrateDetectionStatus::NoneDetectedAllowsUnsafe => string.normal(),
+            CrateDetectionStatus::UnsafeDetected => {
+                string.truecolor(230, 159, 0).bold()
+            }
+        },
     }
 }
 
@@ -175,6 +736,52 @@ mod print_config_tests {
         );
     }
 
+    #[rstest(
+        input_include_build_deps_bool,
+        input_no_build_deps_bool,
+        expected_include_build_deps,
+        case(false, false, IncludeBuildDeps::Yes),
+        case(false, true, IncludeBuildDeps::No),
+        case(true, false, IncludeBuildDeps::Yes),
+        case(true, true, IncludeBuildDeps::Yes)
+    )]
+    fn print_config_new_test_include_build_deps(
+        input_include_build_deps_bool: bool,
+        input_no_build_deps_bool: bool,
+        expected_include_build_deps: IncludeBuildDeps,
+    ) {
+        let args = Args {
+            include_build_deps: input_include_build_deps_bool,
+            no_build_deps: input_no_build_deps_bool,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            PrintConfig::new(&args).unwrap().include_build_deps,
+            expected_include_build_deps
+        );
+    }
+
+    #[rstest(
+        input_include_build_deps,
+        input_dependency_kind,
+        expected_allows,
+        case(IncludeBuildDeps::Yes, DependencyKind::Build, true),
+        case(IncludeBuildDeps::No, DependencyKind::Build, false),
+        case(IncludeBuildDeps::No, DependencyKind::Development, true),
+        case(IncludeBuildDeps::No, DependencyKind::Normal, true)
+    )]
+    fn include_build_deps_allows_test(
+        input_include_build_deps: IncludeBuildDeps,
+        input_dependency_kind: DependencyKind,
+        expected_allows: bool,
+    ) {
+        assert_eq!(
+            input_include_build_deps.allows(input_dependency_kind),
+            expected_allows
+        );
+    }
+
     #[rstest(
         input_format_string,
         expected_format,
@@ -201,7 +808,7 @@ mod print_config_tests {
         expected_format: Pattern,
     ) {
         let args = Args {
-            format: input_format_string,
+            format: Some(input_format_string),
             ..Default::default()
         };
 
@@ -211,6 +818,56 @@ mod print_config_tests {
         assert_eq!(print_config_result.unwrap().format, expected_format);
     }
 
+    #[rstest(
+        input_format,
+        input_format_file_content,
+        expected_format,
+        case(None, "{p}-{l}\n\n", "{p}-{l}"),
+        case(None, "{p}-{l}\r\n", "{p}-{l}"),
+        case(Some("{r}"), "{p}-{l}\n", "{r}")
+    )]
+    fn print_config_new_test_format_file(
+        input_format: Option<&str>,
+        input_format_file_content: &str,
+        expected_format: &str,
+    ) {
+        let format_file_directory = tempfile::tempdir().unwrap();
+        let format_file = format_file_directory.path().join("format.txt");
+        fs::write(&format_file, input_format_file_content).unwrap();
+        let args = Args {
+            format: input_format.map(String::from),
+            format_file: Some(format_file),
+            ..Default::default()
+        };
+
+        let print_config_result = PrintConfig::new(&args);
+
+        assert_eq!(
+            print_config_result.unwrap().format,
+            Pattern::try_build(expected_format).unwrap()
+        );
+    }
+
+    #[rstest]
+    fn print_config_new_test_format_file_invalid() {
+        let format_file_directory = tempfile::tempdir().unwrap();
+        let format_file = format_file_directory.path().join("format.txt");
+        fs::write(&format_file, "{p}-{x}\n").unwrap();
+        let args = Args {
+            format_file: Some(format_file),
+            ..Default::default()
+        };
+
+        assert!(PrintConfig::new(&args).is_err());
+
+        let args = Args {
+            format_file: Some(format_file_directory.path().join("missing")),
+            ..Default::default()
+        };
+
+        assert!(PrintConfig::new(&args).is_err());
+    }
+
     #[rstest(
         input_include_tests_bool,
         expected_include_tests,
@@ -236,20 +893,25 @@ mod print_config_tests {
     }
 
     #[rstest(
+        input_depth_column_bool,
         input_prefix_depth_bool,
         input_no_indent_bool,
         expected_output_prefix,
-        case(true, false, Prefix::Depth,),
-        case(true, false, Prefix::Depth,),
-        case(false, true, Prefix::None,),
-        case(false, false, Prefix::Indent,)
+        case(false, true, false, Prefix::Depth,),
+        case(false, true, false, Prefix::Depth,),
+        case(false, false, true, Prefix::None,),
+        case(false, false, false, Prefix::Indent,),
+        case(true, false, false, Prefix::DepthColumn,),
+        case(true, true, true, Prefix::DepthColumn,)
     )]
     fn print_config_new_test_prefix(
+        input_depth_column_bool: bool,
         input_prefix_depth_bool: bool,
         input_no_indent_bool: bool,
         expected_output_prefix: Prefix,
     ) {
         let args = Args {
+            depth_column: input_depth_column_bool,
             no_indent: input_no_indent_bool,
             prefix_depth: input_prefix_depth_bool,
             ..Default::default()
@@ -265,8 +927,19 @@ mod print_config_tests {
         input_raw_str,
         expected_output_format_result,
         case("Ascii", Ok(OutputFormat::Ascii)),
+        case("CycloneDx", Ok(OutputFormat::CycloneDx)),
+        case("Dot", Ok(OutputFormat::Dot)),
         case("Json", Ok(OutputFormat::Json)),
+        case("JsonLines", Ok(OutputFormat::JsonLines)),
+        case("GitHubActions", Ok(OutputFormat::GitHubActions)),
         case("GitHubMarkdown", Ok(OutputFormat::GitHubMarkdown)),
+        case("Html", Ok(OutputFormat::Html)),
+        case("Mermaid", Ok(OutputFormat::Mermaid)),
+        case("Plain", Ok(OutputFormat::Plain)),
+        case("PrComment", Ok(OutputFormat::PrComment)),
+        case("Sarif", Ok(OutputFormat::Sarif)),
+        case("ShieldsIo", Ok(OutputFormat::ShieldsIo)),
+        case("Toml", Ok(OutputFormat::Toml)),
         case("Utf8", Ok(OutputFormat::Utf8)),
         case("unknown_variant", Err(OutputFormatParseError))
     )]
@@ -281,6 +954,39 @@ mod print_config_tests {
         assert_eq!(output_format, expected_output_format_result);
     }
 
+    #[rstest(
+        input_raw_str,
+        expected_emitted_report_result,
+        case(
+            "Json=report.json",
+            Ok(EmittedReport {
+                output_format: OutputFormat::Json,
+                path: PathBuf::from("report.json"),
+            })
+        ),
+        case(
+            "PrComment=target/a=b.md",
+            Ok(EmittedReport {
+                output_format: OutputFormat::PrComment,
+                path: PathBuf::from("target/a=b.md"),
+            })
+        ),
+        case("GitHubMarkdown=report.md", Err(EmittedReportParseError)),
+        case("Json=", Err(EmittedReportParseError)),
+        case("report.json", Err(EmittedReportParseError)),
+        case("unknown_variant=report", Err(EmittedReportParseError))
+    )]
+    fn emitted_report_from_str_test(
+        input_raw_str: &str,
+        expected_emitted_report_result: Result<
+            EmittedReport,
+            EmittedReportParseError,
+        >,
+    ) {
+        let emitted_report = EmittedReport::from_str(input_raw_str);
+        assert_eq!(emitted_report, expected_emitted_report_result);
+    }
+
     #[rstest(
         input_crate_detection_status,
         input_output_format,
@@ -290,6 +996,16 @@ mod print_config_tests {
             OutputFormat::Ascii,
             String::from("string_value").green()
         ),
+        case(
+            CrateDetectionStatus::NoneDetectedDeniesUnsafe,
+            OutputFormat::Utf8,
+            String::from("string_value").yellow()
+        ),
+        case(
+            CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe,
+            OutputFormat::Utf8,
+            String::from("string_value").cyan()
+        ),
         case(
             CrateDetectionStatus::NoneDetectedAllowsUnsafe,
             OutputFormat::Utf8,
@@ -314,6 +1030,41 @@ mod print_config_tests {
             CrateDetectionStatus::UnsafeDetected,
             OutputFormat::GitHubMarkdown,
             ColoredString::from("string_value")
+        ),
+        case(
+            CrateDetectionStatus::UnsafeDetected,
+            OutputFormat::Html,
+            ColoredString::from("string_value")
+        ),
+        case(
+            CrateDetectionStatus::UnsafeDetected,
+            OutputFormat::Sarif,
+            ColoredString::from("string_value")
+        ),
+        case(
+            CrateDetectionStatus::UnsafeDetected,
+            OutputFormat::Dot,
+            ColoredString::from("string_value")
+        ),
+        case(
+            CrateDetectionStatus::UnsafeDetected,
+            OutputFormat::Mermaid,
+            ColoredString::from("string_value")
+        ),
+        case(
+            CrateDetectionStatus::UnsafeDetected,
+            OutputFormat::GitHubActions,
+            ColoredString::from("string_value")
+        ),
+        case(
+            CrateDetectionStatus::UnsafeDetected,
+            OutputFormat::PrComment,
+            ColoredString::from("string_value")
+        ),
+        case(
+            CrateDetectionStatus::UnsafeDetected,
+            OutputFormat::Toml,
+            ColoredString::from("string_value")
         )
     )]
     fn colorize_test(
@@ -325,6 +1076,8 @@ mod print_config_tests {
 
         assert_eq!(
             colorize(
+                ColorWhen::Always,
+                ColorScheme::Default,
                 &input_crate_detection_status,
                 input_output_format,
                 string_value
@@ -332,4 +1085,160 @@ mod print_config_tests {
             expected_colored_string
         );
     }
+
+    #[rstest(
+        input_crate_detection_status,
+        input_output_format,
+        expected_colored_string,
+        case(
+            CrateDetectionStatus::NoneDetectedForbidsUnsafe,
+            OutputFormat::Ascii,
+            String::from("string_value").truecolor(0, 114, 178)
+        ),
+        case(
+            CrateDetectionStatus::NoneDetectedAllowsUnsafe,
+            OutputFormat::Utf8,
+            String::from("string_value").normal()
+        ),
+        case(
+            CrateDetectionStatus::UnsafeDetected,
+            OutputFormat::Ascii,
+            String::from("string_value").truecolor(230, 159, 0).bold()
+        ),
+        case(
+            CrateDetectionStatus::UnsafeDetected,
+            OutputFormat::GitHubMarkdown,
+            ColoredString::from("string_value")
+        )
+    )]
+    fn colorize_test_colorblind(
+        input_crate_detection_status: CrateDetectionStatus,
+        input_output_format: OutputFormat,
+        expected_colored_string: ColoredString,
+    ) {
+        let string_value = String::from("string_value");
+
+        assert_eq!(
+            colorize(
+                ColorWhen::Always,
+                ColorScheme::Colorblind,
+                &input_crate_detection_status,
+                input_output_format,
+                string_value
+            ),
+            expected_colored_string
+        );
+    }
+
+    #[rstest(
+        input_crate_detection_status,
+        case(CrateDetectionStatus::NoneDetectedForbidsUnsafe),
+        case(CrateDetectionStatus::NoneDetectedDeniesUnsafe),
+        case(CrateDetectionStatus::NoneDetectedAllowsUnsafe),
+        case(CrateDetectionStatus::UnsafeDetected)
+    )]
+    fn colorize_test_color_never(
+        input_crate_detection_status: CrateDetectionStatus,
+    ) {
+        for color_scheme in [ColorScheme::Default, ColorScheme::Colorblind] {
+            assert_eq!(
+                colorize(
+                    ColorWhen::Never,
+                    color_scheme,
+                    &input_crate_detection_status,
+                    OutputFormat::Utf8,
+                    String::from("string_value")
+                ),
+                String::from("string_value").normal()
+            );
+        }
+    }
+
+    #[rstest]
+    fn print_config_new_test_output_file() {
+        let args = Args {
+            color: Some(String::from("always")),
+            output_file: Some(PathBuf::from("report.txt")),
+            ..Default::default()
+        };
+
+        let print_config = PrintConfig::new(&args).unwrap();
+
+        assert_eq!(print_config.color, ColorWhen::Never);
+        assert_eq!(print_config.output_file, Some(PathBuf::from("report.txt")));
+    }
+
+    #[rstest(
+        input_color,
+        expected_color,
+        case(None, Ok(ColorWhen::Auto)),
+        case(Some("always"), Ok(ColorWhen::Always)),
+        case(Some("auto"), Ok(ColorWhen::Auto)),
+        case(Some("never"), Ok(ColorWhen::Never)),
+        case(Some("sometimes"), Err(()))
+    )]
+    fn print_config_new_test_color(
+        input_color: Option<&str>,
+        expected_color: Result<ColorWhen, ()>,
+    ) {
+        // Covered by `color_when_test` when the environment sets the
+        // coloring, as on some CI services.
+        if env::var_os("CARGO_TERM_COLOR").is_some() {
+            return;
+        }
+        let args = Args {
+            color: input_color.map(String::from),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            PrintConfig::new(&args)
+                .map(|print_config| print_config.color)
+                .map_err(|_| ()),
+            expected_color
+        );
+    }
+
+    #[rstest(
+        input_color,
+        input_cargo_term_color,
+        expected_color,
+        case(None, None, Ok(ColorWhen::Auto)),
+        case(None, Some("never"), Ok(ColorWhen::Never)),
+        case(None, Some("always"), Ok(ColorWhen::Always)),
+        case(Some("always"), Some("never"), Ok(ColorWhen::Always)),
+        case(Some("auto"), Some("never"), Ok(ColorWhen::Auto)),
+        case(None, Some("sometimes"), Err(ColorWhenParseError))
+    )]
+    fn color_when_test(
+        input_color: Option<&str>,
+        input_cargo_term_color: Option<&str>,
+        expected_color: Result<ColorWhen, ColorWhenParseError>,
+    ) {
+        assert_eq!(
+            color_when(input_color, input_cargo_term_color),
+            expected_color
+        );
+    }
+
+    #[rstest(
+        input_output_format,
+        input_emoji_supported,
+        expected_symbols,
+        case(OutputFormat::Utf8, true, Symbols::Emoji),
+        case(OutputFormat::Utf8, false, Symbols::Ascii),
+        case(OutputFormat::GitHubMarkdown, false, Symbols::Emoji),
+        case(OutputFormat::Ascii, true, Symbols::Ascii),
+        case(OutputFormat::Ratio, true, Symbols::Ascii)
+    )]
+    fn default_symbols_test(
+        input_output_format: OutputFormat,
+        input_emoji_supported: bool,
+        expected_symbols: Symbols,
+    ) {
+        assert_eq!(
+            default_symbols(input_output_format, input_emoji_supported),
+            expected_symbols
+        );
+    }
 }
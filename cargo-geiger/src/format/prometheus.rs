@@ -0,0 +1,280 @@
+use crate::format::CrateDetectionStatus;
+
+use cargo_geiger_serde::{CounterBlock, PackageId, SafetyReport};
+use std::collections::{BTreeMap, HashSet};
+
+/// The label of each detection status in the `status` label of
+/// `cargo_geiger_crates`
+const STATUS_LABELS: [(CrateDetectionStatus, &str); 5] = [
+    (CrateDetectionStatus::NoneDetectedForbidsUnsafe, "forbids"),
+    (CrateDetectionStatus::NoneDetectedDeniesUnsafe, "denies"),
+    (
+        CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe,
+        "conditionally_forbids",
+    ),
+    (CrateDetectionStatus::NoneDetectedAllowsUnsafe, "allows"),
+    (CrateDetectionStatus::UnsafeDetected, "unsafe"),
+];
+
+/// Gauges of the report in the Prometheus text exposition format, written with
+/// `--metrics-file`:
+///
+/// * `cargo_geiger_unsafe_used` and `cargo_geiger_unsafe_unused`, the unsafe
+///   code of all crates by kind, such as `kind="exprs"`
+/// * `cargo_geiger_crates`, the number of crates of each detection status
+/// * `cargo_geiger_dependency_unsafe_used`, the unsafe code used by the build
+///   of each top-level dependency, counting the crates it pulls in
+///
+/// The top-level dependencies are those of the given roots, the root package
+/// or the workspace members. Samples are ordered, so that the same report is
+/// written the same way.
+pub fn create_prometheus_lines(
+    report: &SafetyReport,
+    roots: &HashSet<PackageId>,
+) -> Vec<String> {
+    let mut used = CounterBlock::default();
+    let mut unused = CounterBlock::default();
+    let mut status_counts = STATUS_LABELS.map(|_| 0_u64);
+    for entry in report.packages.values() {
+        used += entry.unsafety.used.clone();
+        unused += entry.unsafety.unused.clone();
+        let status = CrateDetectionStatus::from(&entry.unsafety);
+        if let Some(index) = STATUS_LABELS
+            .iter()
+            .position(|(status_label, _)| *status_label == status)
+        {
+            status_counts[index] += 1;
+        }
+    }
+
+    let mut prometheus_lines = vec![];
+    for (name, help, counter_block) in [
+        (
+            "cargo_geiger_unsafe_used",
+            "Unsafe code used by the build, by kind",
+            &used,
+        ),
+        (
+            "cargo_geiger_unsafe_unused",
+            "Unsafe code not used by the build, by kind",
+            &unused,
+        ),
+    ] {
+        push_header(&mut prometheus_lines, name, help);
+        for (kind, count) in unsafe_kinds(counter_block) {
+            prometheus_lines
+                .push(format!("{}{{kind=\"{}\"}} {}", name, kind, count));
+        }
+    }
+
+    push_header(
+        &mut prometheus_lines,
+        "cargo_geiger_crates",
+        "Crates by detection status",
+    );
+    for ((_, status_label), count) in STATUS_LABELS.iter().zip(status_counts) {
+        prometheus_lines.push(format!(
+            "cargo_geiger_crates{{status=\"{}\"}} {}",
+            status_label, count
+        ));
+    }
+
+    push_header(
+        &mut prometheus_lines,
+        "cargo_geiger_dependency_unsafe_used",
+        "Unsafe code used by the build of each top-level dependency and the \
+        crates it depends on",
+    );
+    for (dependency, total) in top_level_dependency_totals(report, roots) {
+        prometheus_lines.push(format!(
+            "cargo_geiger_dependency_unsafe_used{{dependency=\"{}\",version=\"{}\"}} {}",
+            escape_label_value(&dependency.name),
+            dependency.version,
+            total
+        ));
+    }
+    prometheus_lines
+}
+
+fn push_header(prometheus_lines: &mut Vec<String>, name: &str, help: &str) {
+    prometheus_lines.push(format!("# HELP {} {}", name, help));
+    prometheus_lines.push(format!("# TYPE {} gauge", name));
+}
+
+fn unsafe_kinds(counter_block: &CounterBlock) -> [(&'static str, u64); 5] {
+    [
+        ("functions", counter_block.functions.unsafe_),
+        ("exprs", counter_block.exprs.unsafe_),
+        ("item_impls", counter_block.item_impls.unsafe_),
+        ("item_traits", counter_block.item_traits.unsafe_),
+        ("methods", counter_block.methods.unsafe_),
+    ]
+}
+
+/// The total unsafe code used by each dependency of the roots which is not a
+/// root itself, and by the normal and build dependencies it pulls in, each
+/// crate counted once
+fn top_level_dependency_totals<'a>(
+    report: &'a SafetyReport,
+    roots: &HashSet<PackageId>,
+) -> BTreeMap<&'a PackageId, u64> {
+    report
+        .packages
+        .values()
+        .filter(|entry| roots.contains(&entry.package.id))
+        .flat_map(|entry| {
+            entry
+                .package
+                .dependencies
+                .iter()
+                .chain(&entry.package.build_dependencies)
+        })
+        .filter(|dependency| !roots.contains(dependency))
+        .filter_map(|dependency| report.packages.get_key_value(dependency))
+        .map(|(dependency, _)| {
+            (
+                dependency,
+                total_unsafe_with_dependencies(report, dependency),
+            )
+        })
+        .collect()
+}
+
+fn total_unsafe_with_dependencies(
+    report: &SafetyReport,
+    package_id: &PackageId,
+) -> u64 {
+    let mut visited = HashSet::new();
+    let mut pending = vec![package_id];
+    let mut total = 0;
+    while let Some(package_id) = pending.pop() {
+        if !visited.insert(package_id) {
+            continue;
+        }
+        if let Some(entry) = report.packages.get(package_id) {
+            total += entry.unsafety.used.total_unsafe();
+            pending.extend(
+                entry
+                    .package
+                    .dependencies
+                    .iter()
+                    .chain(&entry.package.build_dependencies),
+            );
+        }
+    }
+    total
+}
+
+/// Backslashes, quotes and line feeds are escaped in label values
+fn escape_label_value(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod prometheus_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::{DependencyKind, PackageInfo, UnsafeInfo};
+    use rstest::*;
+
+    #[rstest]
+    fn create_prometheus_lines_test() {
+        let mut root = PackageInfo::new(create_path_package_id("root"));
+        root.add_dependency(
+            create_path_package_id("bar"),
+            DependencyKind::Normal,
+        );
+        root.add_dependency(
+            create_path_package_id("baz"),
+            DependencyKind::Build,
+        );
+        root.add_dependency(
+            create_path_package_id("qux"),
+            DependencyKind::Development,
+        );
+        let mut bar = PackageInfo::new(create_path_package_id("bar"));
+        bar.add_dependency(
+            create_path_package_id("shared"),
+            DependencyKind::Normal,
+        );
+        let mut bar_unsafety = create_unsafe_info(2);
+        bar_unsafety.unused.functions.unsafe_ = 1;
+        let mut baz = PackageInfo::new(create_path_package_id("baz"));
+        baz.add_dependency(
+            create_path_package_id("shared"),
+            DependencyKind::Normal,
+        );
+        let baz_unsafety = UnsafeInfo {
+            forbids_unsafe: true,
+            ..Default::default()
+        };
+        let mut shared_unsafety = UnsafeInfo::default();
+        shared_unsafety.used.functions.unsafe_ = 3;
+        let mut qux_unsafety = UnsafeInfo::default();
+        qux_unsafety.used.methods.unsafe_ = 4;
+        let report = create_safety_report(vec![
+            (root, UnsafeInfo::default()),
+            (bar, bar_unsafety),
+            (baz, baz_unsafety),
+            (
+                PackageInfo::new(create_path_package_id("shared")),
+                shared_unsafety,
+            ),
+            (
+                PackageInfo::new(create_path_package_id("qux")),
+                qux_unsafety,
+            ),
+        ]);
+
+        let roots = vec![create_path_package_id("root")].into_iter().collect();
+
+        assert_eq!(
+            create_prometheus_lines(&report, &roots),
+            vec![
+                "# HELP cargo_geiger_unsafe_used Unsafe code used by the build, by kind",
+                "# TYPE cargo_geiger_unsafe_used gauge",
+                "cargo_geiger_unsafe_used{kind=\"functions\"} 3",
+                "cargo_geiger_unsafe_used{kind=\"exprs\"} 2",
+                "cargo_geiger_unsafe_used{kind=\"item_impls\"} 0",
+                "cargo_geiger_unsafe_used{kind=\"item_traits\"} 0",
+                "cargo_geiger_unsafe_used{kind=\"methods\"} 4",
+                "# HELP cargo_geiger_unsafe_unused Unsafe code not used by the build, by kind",
+                "# TYPE cargo_geiger_unsafe_unused gauge",
+                "cargo_geiger_unsafe_unused{kind=\"functions\"} 1",
+                "cargo_geiger_unsafe_unused{kind=\"exprs\"} 0",
+                "cargo_geiger_unsafe_unused{kind=\"item_impls\"} 0",
+                "cargo_geiger_unsafe_unused{kind=\"item_traits\"} 0",
+                "cargo_geiger_unsafe_unused{kind=\"methods\"} 0",
+                "# HELP cargo_geiger_crates Crates by detection status",
+                "# TYPE cargo_geiger_crates gauge",
+                "cargo_geiger_crates{status=\"forbids\"} 1",
+                "cargo_geiger_crates{status=\"denies\"} 0",
+                "cargo_geiger_crates{status=\"conditionally_forbids\"} 0",
+                "cargo_geiger_crates{status=\"allows\"} 1",
+                "cargo_geiger_crates{status=\"unsafe\"} 3",
+                "# HELP cargo_geiger_dependency_unsafe_used Unsafe code used by the build of each top-level dependency and the crates it depends on",
+                "# TYPE cargo_geiger_dependency_unsafe_used gauge",
+                "cargo_geiger_dependency_unsafe_used{dependency=\"bar\",version=\"0.1.0\"} 5",
+                "cargo_geiger_dependency_unsafe_used{dependency=\"baz\",version=\"0.1.0\"} 3",
+            ]
+        );
+    }
+
+    #[rstest(
+        input_text,
+        expected_label_value,
+        case("foo", "foo"),
+        case("fo\"o", "fo\\\"o"),
+        case("fo\\o", "fo\\\\o"),
+        case("fo\no", "fo\\no")
+    )]
+    fn escape_label_value_test(input_text: &str, expected_label_value: &str) {
+        assert_eq!(escape_label_value(input_text), expected_label_value);
+    }
+}
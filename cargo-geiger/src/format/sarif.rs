@@ -0,0 +1,337 @@
+use crate::format::CrateDetectionStatus;
+
+use cargo_geiger_serde::{Count, PackageId, ReportEntry, SafetyReport};
+use geiger::UnsafeLocation;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use url::Url;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+const SARIF_VERSION: &str = "2.1.0";
+
+/// The file the result of each crate is located in, as the crates using unsafe
+/// code are dependencies of the package rather than lines of its source files
+const RESULT_LOCATION_URI: &str = "Cargo.toml";
+
+/// The unsafe items of each crate in the files used by the build, with the
+/// URI of their file, from `artifact_uri`
+pub type UnsafeLocations = HashMap<PackageId, Vec<(String, UnsafeLocation)>>;
+
+/// A SARIF 2.1.0 log, <https://docs.oasis-open.org/sarif/sarif/v2.1.0/>, with
+/// only the properties needed to be read by code scanning tools, such as the
+/// Security tab of GitHub
+#[derive(Debug, Serialize)]
+struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifRun {
+    tool: SarifTool,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRule {
+    id: String,
+    short_description: SarifMessage,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    level: &'static str,
+    message: SarifMessage,
+    locations: Vec<SarifLocation>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<SarifRegion>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifArtifactLocation {
+    uri: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: usize,
+    start_column: usize,
+}
+
+/// A SARIF log with a rule and a result for each crate in the report using
+/// unsafe code, ordered by crate, followed by a result of the same rule for
+/// each of its unsafe items. The rule of a crate is its name and version, such
+/// as `libc/0.2.132`, and the tool is cargo-geiger itself.
+pub fn create_sarif_line(
+    report: &SafetyReport,
+    unsafe_locations: &UnsafeLocations,
+) -> String {
+    let mut entries = report
+        .packages
+        .values()
+        .filter(|entry| {
+            CrateDetectionStatus::from(&entry.unsafety)
+                == CrateDetectionStatus::UnsafeDetected
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+
+    let rules = entries
+        .iter()
+        .map(|entry| SarifRule {
+            id: rule_id(entry),
+            short_description: SarifMessage {
+                text: format!(
+                    "{} {} uses unsafe code",
+                    entry.package.id.name, entry.package.id.version
+                ),
+            },
+        })
+        .collect();
+    let mut results = vec![];
+    for entry in entries {
+        results.push(SarifResult {
+            rule_id: rule_id(entry),
+            level: "warning",
+            message: SarifMessage {
+                text: result_message(entry),
+            },
+            locations: vec![sarif_location(
+                String::from(RESULT_LOCATION_URI),
+                None,
+            )],
+        });
+        for (uri, unsafe_location) in unsafe_locations
+            .get(&entry.package.id)
+            .into_iter()
+            .flatten()
+        {
+            results.push(SarifResult {
+                rule_id: rule_id(entry),
+                level: "warning",
+                message: SarifMessage {
+                    text: format!(
+                        "Unsafe {} in {} {}",
+                        unsafe_location.kind,
+                        entry.package.id.name,
+                        entry.package.id.version
+                    ),
+                },
+                locations: vec![sarif_location(
+                    uri.clone(),
+                    Some(SarifRegion {
+                        start_line: unsafe_location.line,
+                        start_column: unsafe_location.column,
+                    }),
+                )],
+            });
+        }
+    }
+
+    serde_json::to_string(&SarifLog {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: env!("CARGO_PKG_NAME"),
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: env!("CARGO_PKG_REPOSITORY"),
+                    rules,
+                },
+            },
+            results,
+        }],
+    })
+    .unwrap()
+}
+
+/// The path of the file relative to the workspace root, with `/` separators,
+/// which is how code scanning tools find the files of the repository, or a
+/// `file://` URI for the files outside of it, such as those of dependencies
+/// from crates.io
+pub fn artifact_uri(path: &Path, workspace_root: &Path) -> String {
+    match path.strip_prefix(workspace_root) {
+        Ok(relative_path) => relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => Url::from_file_path(path)
+            .map_or_else(|_| path.display().to_string(), String::from),
+    }
+}
+
+fn sarif_location(uri: String, region: Option<SarifRegion>) -> SarifLocation {
+    SarifLocation {
+        physical_location: SarifPhysicalLocation {
+            artifact_location: SarifArtifactLocation { uri },
+            region,
+        },
+    }
+}
+
+fn rule_id(entry: &ReportEntry) -> String {
+    format!("{}/{}", entry.package.id.name, entry.package.id.version)
+}
+
+/// The unsafe code used by the build and found in total, such as `2/3`, for
+/// each category
+fn result_message(entry: &ReportEntry) -> String {
+    let count_field = |used: &Count, unused: &Count, category: &str| {
+        format!(
+            "{}/{} {}",
+            used.unsafe_,
+            used.unsafe_ + unused.unsafe_,
+            category
+        )
+    };
+    let used = &entry.unsafety.used;
+    let unused = &entry.unsafety.unused;
+    format!(
+        "{} {} uses unsafe code, used by the build/found in total: {}, {}, {}, {}, {}",
+        entry.package.id.name,
+        entry.package.id.version,
+        count_field(&used.functions, &unused.functions, "functions"),
+        count_field(&used.exprs, &unused.exprs, "expressions"),
+        count_field(&used.item_impls, &unused.item_impls, "impls"),
+        count_field(&used.item_traits, &unused.item_traits, "traits"),
+        count_field(&used.methods, &unused.methods, "methods"),
+    )
+}
+
+#[cfg(test)]
+mod sarif_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::PackageInfo;
+    use geiger::UnsafeKind;
+    use rstest::*;
+
+    #[rstest]
+    fn create_sarif_line_test() {
+        let report = create_safety_report(
+            vec![("foo", 3), ("bar", 0)]
+                .into_iter()
+                .map(|(name, unsafe_exprs)| {
+                    let mut unsafety = create_unsafe_info(unsafe_exprs);
+                    unsafety.unused.functions.unsafe_ = 1;
+                    (PackageInfo::new(create_path_package_id(name)), unsafety)
+                })
+                .collect(),
+        );
+
+        let mut unsafe_locations = UnsafeLocations::new();
+        unsafe_locations.insert(
+            report
+                .packages
+                .keys()
+                .find(|package_id| package_id.name == "foo")
+                .unwrap()
+                .clone(),
+            vec![(
+                String::from("foo/src/lib.rs"),
+                UnsafeLocation {
+                    line: 12,
+                    column: 5,
+                    end_line: 14,
+                    end_column: 5,
+                    kind: UnsafeKind::Block,
+                },
+            )],
+        );
+
+        let sarif_line = create_sarif_line(&report, &unsafe_locations);
+
+        assert!(!sarif_line.contains('\x1B'));
+        let sarif_value =
+            serde_json::from_str::<serde_json::Value>(&sarif_line).unwrap();
+        assert_eq!(sarif_value["version"], "2.1.0");
+        let run = &sarif_value["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "cargo-geiger");
+        assert_eq!(run["tool"]["driver"]["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "foo/0.1.0");
+        assert_eq!(run["results"].as_array().unwrap().len(), 2);
+        assert_eq!(run["results"][0]["ruleId"], "foo/0.1.0");
+        assert_eq!(
+            run["results"][0]["message"]["text"],
+            "foo 0.1.0 uses unsafe code, used by the build/found in total: \
+            0/1 functions, 3/3 expressions, 0/0 impls, 0/0 traits, 0/0 methods"
+        );
+        assert_eq!(run["results"][1]["ruleId"], "foo/0.1.0");
+        assert_eq!(
+            run["results"][1]["message"]["text"],
+            "Unsafe block in foo 0.1.0"
+        );
+        let physical_location =
+            &run["results"][1]["locations"][0]["physicalLocation"];
+        assert_eq!(
+            physical_location["artifactLocation"]["uri"],
+            "foo/src/lib.rs"
+        );
+        assert_eq!(physical_location["region"]["startLine"], 12);
+        assert_eq!(physical_location["region"]["startColumn"], 5);
+        assert!(run["results"][0]["locations"][0]["physicalLocation"]
+            .get("region")
+            .is_none());
+    }
+
+    #[rstest(
+        input_path,
+        expected_artifact_uri,
+        case("/workspace/foo/src/lib.rs", "foo/src/lib.rs"),
+        case(
+            "/registry/libc-0.2.132/src/lib.rs",
+            "file:///registry/libc-0.2.132/src/lib.rs"
+        )
+    )]
+    fn artifact_uri_test(input_path: &str, expected_artifact_uri: &str) {
+        assert_eq!(
+            artifact_uri(Path::new(input_path), Path::new("/workspace")),
+            expected_artifact_uri
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use cargo_geiger_serde::SafetyReport;
+use serde::Serialize;
+
+/// The badge color for each range of used unsafe code, as the largest total
+/// which still gets the color. Larger totals are red.
+const COLOR_THRESHOLDS: [(u64, &str); 3] =
+    [(0, "brightgreen"), (99, "yellow"), (999, "orange")];
+
+const HIGHEST_COLOR: &str = "red";
+
+/// The shields.io endpoint badge schema,
+/// <https://shields.io/badges/endpoint-badge>
+#[derive(Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShieldsIoEndpoint {
+    pub schema_version: u8,
+    pub label: String,
+    pub message: String,
+    pub color: String,
+}
+
+impl ShieldsIoEndpoint {
+    /// A badge with the total unsafe code used by the build of all crates in
+    /// the report as its message
+    pub fn from_report(report: &SafetyReport) -> Self {
+        let total_unsafe = report
+            .packages
+            .values()
+            .map(|entry| entry.unsafety.used.total_unsafe())
+            .sum::<u64>();
+
+        let color = COLOR_THRESHOLDS
+            .iter()
+            .find(|(threshold, _)| total_unsafe <= *threshold)
+            .map_or(HIGHEST_COLOR, |(_, color)| color);
+
+        ShieldsIoEndpoint {
+            schema_version: 1,
+            label: String::from("unsafe"),
+            message: total_unsafe.to_string(),
+            color: color.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod shields_io_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::PackageInfo;
+    use rstest::*;
+
+    #[rstest(
+        input_unsafe_exprs,
+        expected_color,
+        case(vec![], "brightgreen"),
+        case(vec![0, 0], "brightgreen"),
+        case(vec![1], "yellow"),
+        case(vec![50, 49], "yellow"),
+        case(vec![50, 50], "orange"),
+        case(vec![1000], "red")
+    )]
+    fn shields_io_endpoint_from_report_test(
+        input_unsafe_exprs: Vec<u64>,
+        expected_color: &str,
+    ) {
+        let report = create_safety_report(
+            input_unsafe_exprs
+                .iter()
+                .enumerate()
+                .map(|(index, unsafe_exprs)| {
+                    let package_id =
+                        create_path_package_id(&format!("crate{}", index));
+                    (
+                        PackageInfo::new(package_id),
+                        create_unsafe_info(*unsafe_exprs),
+                    )
+                })
+                .collect(),
+        );
+
+        let shields_io_endpoint = ShieldsIoEndpoint::from_report(&report);
+
+        assert_eq!(
+            shields_io_endpoint.message,
+            input_unsafe_exprs.iter().sum::<u64>().to_string()
+        );
+        assert_eq!(shields_io_endpoint.color, expected_color);
+    }
+
+    #[rstest]
+    fn shields_io_endpoint_serialize_test() {
+        assert_eq!(
+            serde_json::to_string(&ShieldsIoEndpoint::from_report(
+                &SafetyReport::default()
+            ))
+            .unwrap(),
+            r#"{"schemaVersion":1,"label":"unsafe","message":"0","color":"brightgreen"}"#
+        );
+    }
+}
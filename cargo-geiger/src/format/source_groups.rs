@@ -0,0 +1,209 @@
+use crate::format::print_config::OutputFormat;
+use crate::format::table::{table_row, UNSAFE_COUNTERS_HEADER};
+
+use cargo_geiger_serde::{CounterBlock, Source};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The origin of a group of crates. The variants are declared in the order
+/// the groups are printed in, since crates which bypassed crates.io are the
+/// first ones to review.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum SourceGroup {
+    /// Crates from one git repository, regardless of the revision
+    Git(String),
+    Path,
+    /// Crates of a registry copied into the workspace by `cargo vendor`
+    Vendored,
+    /// Crates from an alternate registry
+    Registry(String),
+    CratesIo,
+}
+
+impl SourceGroup {
+    pub fn from_source(source: &Source) -> Self {
+        match source {
+            Source::Git { url, .. } => SourceGroup::Git(url.to_string()),
+            Source::Path(_) => SourceGroup::Path,
+            Source::Registry { .. } if source.is_crates_io() => {
+                SourceGroup::CratesIo
+            }
+            Source::Registry { url, .. } => {
+                SourceGroup::Registry(url.to_string())
+            }
+        }
+    }
+}
+
+impl fmt::Display for SourceGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceGroup::Git(url) => write!(f, "git {}", url),
+            SourceGroup::Path => write!(f, "path"),
+            SourceGroup::Vendored => write!(f, "vendored"),
+            SourceGroup::Registry(url) => write!(f, "registry {}", url),
+            SourceGroup::CratesIo => write!(f, "crates.io"),
+        }
+    }
+}
+
+/// A crate to be placed in a group
+pub struct SourceGroupRow {
+    pub name: String,
+    pub source_group: SourceGroup,
+    pub unused: CounterBlock,
+    pub used: CounterBlock,
+}
+
+/// Renders the summed unsafe usage of each group, followed by its crates,
+/// with the groups of crates not from crates.io first
+pub fn create_source_group_lines(
+    rows: Vec<SourceGroupRow>,
+    hide_zeros: bool,
+    output_format: OutputFormat,
+) -> Vec<String> {
+    let mut source_groups = BTreeMap::<SourceGroup, Vec<SourceGroupRow>>::new();
+    for row in rows {
+        source_groups
+            .entry(row.source_group.clone())
+            .or_default()
+            .push(row);
+    }
+
+    let mut source_group_lines = vec![UNSAFE_COUNTERS_HEADER.join(" ")];
+
+    for (source_group, mut rows) in source_groups {
+        rows.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let (used, unused) = rows.iter().fold(
+            (CounterBlock::default(), CounterBlock::default()),
+            |(used, unused), row| {
+                (used + row.used.clone(), unused + row.unused.clone())
+            },
+        );
+
+        source_group_lines.push(String::new());
+        source_group_lines.push(format!(
+            "{}  {} ({} crates)",
+            table_row(&used, &unused, hide_zeros, output_format),
+            source_group,
+            rows.len()
+        ));
+        for row in rows {
+            source_group_lines.push(format!(
+                "{}      {}",
+                table_row(&row.used, &row.unused, hide_zeros, output_format),
+                row.name
+            ));
+        }
+    }
+
+    source_group_lines.push(String::new());
+    source_group_lines
+}
+
+#[cfg(test)]
+mod source_groups_tests {
+    use super::*;
+
+    use cargo_geiger_serde::Count;
+    use rstest::*;
+    use url::Url;
+
+    #[rstest(
+        input_source,
+        expected_source_group,
+        case(
+            Source::Registry {
+                name: String::from("crates.io"),
+                url: Url::parse("https://github.com/rust-lang/crates.io-index").unwrap()
+            },
+            SourceGroup::CratesIo
+        ),
+        case(
+            Source::Registry {
+                name: String::from("crates.io"),
+                url: Url::parse("https://example.com/index").unwrap()
+            },
+            SourceGroup::Registry(String::from("https://example.com/index"))
+        ),
+        case(
+            Source::Git {
+                url: Url::parse("https://github.com/foo/bar").unwrap(),
+                rev: String::from("abc")
+            },
+            SourceGroup::Git(String::from("https://github.com/foo/bar"))
+        ),
+        case(
+            Source::Path(Url::parse("file:///foo/bar").unwrap()),
+            SourceGroup::Path
+        )
+    )]
+    fn source_group_from_source_test(
+        input_source: Source,
+        expected_source_group: SourceGroup,
+    ) {
+        assert_eq!(
+            SourceGroup::from_source(&input_source),
+            expected_source_group
+        );
+    }
+
+    #[rstest]
+    fn create_source_group_lines_test() {
+        let rows = vec![
+            create_source_group_row("foo 0.1.0", SourceGroup::CratesIo, 1),
+            create_source_group_row("baz 0.1.0", SourceGroup::Path, 2),
+            create_source_group_row("bar 0.1.0", SourceGroup::CratesIo, 3),
+        ];
+
+        let source_group_lines =
+            create_source_group_lines(rows, false, OutputFormat::Ascii);
+
+        assert_eq!(
+            source_group_lines,
+            vec![
+                String::from(
+                    "Functions  Expressions  Impls  Traits  Methods  Dependency"
+                ),
+                String::new(),
+                String::from(
+                    "0/0        2/2          0/0    0/0     0/0      path (1 crates)"
+                ),
+                String::from(
+                    "0/0        2/2          0/0    0/0     0/0          baz 0.1.0"
+                ),
+                String::new(),
+                String::from(
+                    "0/0        4/4          0/0    0/0     0/0      crates.io (2 crates)"
+                ),
+                String::from(
+                    "0/0        3/3          0/0    0/0     0/0          bar 0.1.0"
+                ),
+                String::from(
+                    "0/0        1/1          0/0    0/0     0/0          foo 0.1.0"
+                ),
+                String::new(),
+            ]
+        );
+    }
+
+    fn create_source_group_row(
+        name: &str,
+        source_group: SourceGroup,
+        unsafe_exprs: u64,
+    ) -> SourceGroupRow {
+        SourceGroupRow {
+            name: String::from(name),
+            source_group,
+            unused: CounterBlock::default(),
+            used: CounterBlock {
+                exprs: Count {
+                    safe: 0,
+                    unsafe_: unsafe_exprs,
+                },
+                ..Default::default()
+            },
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use crate::format::table::UNSAFE_CATEGORY_NAMES;
+use crate::format::CrateDetectionStatus;
+
+use cargo_geiger_serde::{Count, CounterBlock};
+use cargo_metadata::semver::Version;
+use serde::Serialize;
+
+/// Starts the line of the Json summary, so that a script can find it among
+/// the lines of the tree
+pub const JSON_SUMMARY_PREFIX: &str = "cargo-geiger-summary: ";
+
+/// How the summary below the tree is rendered, independently of the output
+/// format of the tree itself
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SummaryFormat {
+    /// A row aligned with the columns of the tree
+    #[default]
+    Table,
+    /// A sentence for each of the unsafe counts and the crates
+    Text,
+    /// A single uncolored line of Json after `JSON_SUMMARY_PREFIX`
+    Json,
+}
+
+impl std::str::FromStr for SummaryFormat {
+    type Err = SummaryFormatParseError;
+    fn from_str(s: &str) -> Result<Self, SummaryFormatParseError> {
+        match s {
+            "table" => Ok(Self::Table),
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(SummaryFormatParseError),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SummaryFormatParseError;
+impl std::fmt::Display for SummaryFormatParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "matching summary format not found, expected text, table or json"
+        )
+    }
+}
+impl std::error::Error for SummaryFormatParseError {}
+
+/// The number of crates in the tree by what was detected in them
+#[derive(Debug, Default, Eq, PartialEq, Serialize)]
+pub struct CrateCounts {
+    pub forbids_unsafe: u64,
+    pub denies_unsafe: u64,
+    pub conditionally_forbids_unsafe: u64,
+    pub allows_unsafe: u64,
+    pub unsafe_detected: u64,
+}
+
+impl CrateCounts {
+    /// Count a crate under its status
+    pub fn count(&mut self, crate_detection_status: CrateDetectionStatus) {
+        match crate_detection_status {
+            CrateDetectionStatus::NoneDetectedForbidsUnsafe => {
+                self.forbids_unsafe += 1
+            }
+            CrateDetectionStatus::NoneDetectedDeniesUnsafe => {
+                self.denies_unsafe += 1
+            }
+            CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe => {
+                self.conditionally_forbids_unsafe += 1
+            }
+            CrateDetectionStatus::NoneDetectedAllowsUnsafe => {
+                self.allows_unsafe += 1
+            }
+            CrateDetectionStatus::UnsafeDetected => self.unsafe_detected += 1,
+        }
+    }
+
+    fn total(&self) -> u64 {
+        self.forbids_unsafe
+            + self.denies_unsafe
+            + self.conditionally_forbids_unsafe
+            + self.allows_unsafe
+            + self.unsafe_detected
+    }
+}
+
+/// The totals of all crates in the tree
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct Summary {
+    pub used: CounterBlock,
+    pub unused: CounterBlock,
+    pub crates: CrateCounts,
+}
+
+impl Summary {
+    pub fn json_line(&self) -> String {
+        format!(
+            "{}{}",
+            JSON_SUMMARY_PREFIX,
+            serde_json::to_string(self).unwrap()
+        )
+    }
+
+    pub fn text_lines(&self) -> Vec<String> {
+        vec![
+            format!(
+                "Unsafe code used by the build/found: {}",
+                format_category_counts(&self.used, &self.unused)
+            ),
+            format!(
+                "Crates: {} with unsafe usage, {} forbid unsafe, {} deny \
+                unsafe, {} forbid unsafe conditionally, {} allow unsafe \
+                without using it ({} total)",
+                self.crates.unsafe_detected,
+                self.crates.forbids_unsafe,
+                self.crates.denies_unsafe,
+                self.crates.conditionally_forbids_unsafe,
+                self.crates.allows_unsafe,
+                self.crates.total()
+            ),
+        ]
+    }
+}
+
+/// The unsafe code used by the build and the total unsafe code found for each
+/// category, such as `functions 1/3, expressions 4/4, ...`
+pub fn format_category_counts(
+    used: &CounterBlock,
+    unused: &CounterBlock,
+) -> String {
+    UNSAFE_CATEGORY_NAMES
+        .iter()
+        .zip(category_counts(used))
+        .zip(category_counts(unused))
+        .map(|((name, used), unused)| {
+            format!("{} {}/{}", name, used, used + unused)
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// A crate which may be listed by `--summary-top`
+pub struct TopCrateRow {
+    pub name: String,
+    pub version: Version,
+    pub used: CounterBlock,
+    pub unused: CounterBlock,
+}
+
+/// The lines printed below the summary with `--summary-top`, the crates using
+/// the most unsafe code, most used by the build first, then most found, then
+/// by name. The crates without unsafe code are left out.
+pub fn create_top_crate_lines(
+    rows: Vec<TopCrateRow>,
+    top_count: usize,
+) -> Vec<String> {
+    let total = |counter_block: &CounterBlock| {
+        category_counts(counter_block).iter().sum::<u64>()
+    };
+    let mut rows = rows
+        .into_iter()
+        .map(|row| {
+            let used = total(&row.used);
+            (used, used + total(&row.unused), row)
+        })
+        .filter(|(_, found, _)| *found > 0)
+        .collect::<Vec<_>>();
+    rows.sort_by(|(a_used, a_found, a), (b_used, b_found, b)| {
+        b_used
+            .cmp(a_used)
+            .then(b_found.cmp(a_found))
+            .then(a.name.cmp(&b.name))
+            .then(a.version.cmp(&b.version))
+    });
+
+    let mut top_crate_lines = vec![String::from(
+        "Crates using the most unsafe code, used by the build/found:",
+    )];
+    if rows.is_empty() {
+        top_crate_lines.push(String::from("    None"));
+    }
+    top_crate_lines.extend(rows.into_iter().take(top_count).enumerate().map(
+        |(index, (used, found, row))| {
+            format!(
+                "    {}. {} {}: {}/{}",
+                index + 1,
+                row.name,
+                row.version,
+                used,
+                found
+            )
+        },
+    ));
+    top_crate_lines
+}
+
+fn category_counts(counter_block: &CounterBlock) -> [u64; 5] {
+    let unsafe_count = |count: &Count| count.unsafe_;
+    [
+        unsafe_count(&counter_block.functions),
+        unsafe_count(&counter_block.exprs),
+        unsafe_count(&counter_block.item_impls),
+        unsafe_count(&counter_block.item_traits),
+        unsafe_count(&counter_block.methods),
+    ]
+}
+
+#[cfg(test)]
+mod summary_tests {
+    use super::*;
+
+    use rstest::*;
+    use std::str::FromStr;
+
+    #[rstest(
+        input_summary_format,
+        expected_summary_format,
+        case("table", Ok(SummaryFormat::Table)),
+        case("text", Ok(SummaryFormat::Text)),
+        case("json", Ok(SummaryFormat::Json)),
+        case("Json", Err(SummaryFormatParseError))
+    )]
+    fn summary_format_from_str_test(
+        input_summary_format: &str,
+        expected_summary_format: Result<SummaryFormat, SummaryFormatParseError>,
+    ) {
+        assert_eq!(
+            SummaryFormat::from_str(input_summary_format),
+            expected_summary_format
+        );
+    }
+
+    #[rstest]
+    fn summary_text_lines_test() {
+        assert_eq!(
+            create_summary().text_lines(),
+            vec![
+                String::from(
+                    "Unsafe code used by the build/found: functions 1/3, expressions 4/4, impls 0/0, traits 0/0, methods 0/0"
+                ),
+                String::from(
+                    "Crates: 2 with unsafe usage, 1 forbid unsafe, 0 deny unsafe, 1 forbid unsafe conditionally, 3 allow unsafe without using it (7 total)"
+                ),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn summary_json_line_test() {
+        let json_line = create_summary().json_line();
+        let json = json_line.strip_prefix(JSON_SUMMARY_PREFIX).unwrap();
+        let value = serde_json::from_str::<serde_json::Value>(json).unwrap();
+
+        assert_eq!(value["used"]["exprs"]["unsafe_"], 4);
+        assert_eq!(value["unused"]["functions"]["unsafe_"], 2);
+        assert_eq!(value["crates"]["unsafe_detected"], 2);
+        assert!(!json.contains('\n'));
+    }
+
+    #[rstest]
+    fn crate_counts_count_test() {
+        let mut crate_counts = CrateCounts::default();
+        for crate_detection_status in [
+            CrateDetectionStatus::UnsafeDetected,
+            CrateDetectionStatus::NoneDetectedDeniesUnsafe,
+            CrateDetectionStatus::UnsafeDetected,
+        ] {
+            crate_counts.count(crate_detection_status);
+        }
+
+        assert_eq!(
+            crate_counts,
+            CrateCounts {
+                denies_unsafe: 1,
+                unsafe_detected: 2,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[rstest(
+        input_top_count,
+        expected_top_crate_lines,
+        case(
+            2,
+            vec![
+                "Crates using the most unsafe code, used by the build/found:",
+                "    1. tokio 1.21.0: 120/124",
+                "    2. bytes 1.2.1: 3/3",
+            ]
+        ),
+        case(
+            5,
+            vec![
+                "Crates using the most unsafe code, used by the build/found:",
+                "    1. tokio 1.21.0: 120/124",
+                "    2. bytes 1.2.1: 3/3",
+                "    3. libc 0.2.0: 3/3",
+                "    4. memchr 2.5.0: 0/7",
+            ]
+        )
+    )]
+    fn create_top_crate_lines_test(
+        input_top_count: usize,
+        expected_top_crate_lines: Vec<&str>,
+    ) {
+        let row = |name: &str, version: Version, used: u64, unused: u64| {
+            let mut row = TopCrateRow {
+                name: String::from(name),
+                version,
+                used: CounterBlock::default(),
+                unused: CounterBlock::default(),
+            };
+            row.used.exprs.unsafe_ = used;
+            row.unused.functions.unsafe_ = unused;
+            row
+        };
+        let rows = vec![
+            row("memchr", Version::new(2, 5, 0), 0, 7),
+            row("libc", Version::new(0, 2, 0), 3, 0),
+            row("serde", Version::new(1, 0, 0), 0, 0),
+            row("tokio", Version::new(1, 21, 0), 120, 4),
+            row("bytes", Version::new(1, 2, 1), 3, 0),
+        ];
+
+        assert_eq!(
+            create_top_crate_lines(rows, input_top_count),
+            expected_top_crate_lines
+        );
+    }
+
+    #[rstest]
+    fn create_top_crate_lines_test_none() {
+        assert_eq!(
+            create_top_crate_lines(vec![], 3),
+            vec![
+                "Crates using the most unsafe code, used by the build/found:",
+                "    None",
+            ]
+        );
+    }
+
+    fn create_summary() -> Summary {
+        let mut used = CounterBlock::default();
+        used.functions.unsafe_ = 1;
+        used.exprs.unsafe_ = 4;
+        let mut unused = CounterBlock::default();
+        unused.functions.unsafe_ = 2;
+        Summary {
+            used,
+            unused,
+            crates: CrateCounts {
+                forbids_unsafe: 1,
+                denies_unsafe: 0,
+                conditionally_forbids_unsafe: 1,
+                allows_unsafe: 3,
+                unsafe_detected: 2,
+            },
+        }
+    }
+}
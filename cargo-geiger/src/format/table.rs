@@ -1,11 +1,22 @@
 mod handle_text_tree_line;
 mod total_package_counts;
 
+use crate::advisory::Advisories;
+use crate::audits::Audits;
+use crate::checksum::ChecksumStatuses;
+use crate::downloads::DownloadCounts;
+use crate::expand::ExpandedCounts;
 use crate::format::emoji_symbols::EmojiSymbols;
-use crate::format::print_config::{colorize, OutputFormat, PrintConfig};
+use crate::format::print_config::{
+    colorize, ColorScheme, ColorWhen, OutputFormat, PrintConfig,
+};
+use crate::format::summary::{CrateCounts, Summary, SummaryFormat};
 use crate::format::CrateDetectionStatus;
+use crate::history::UnsafeHistory;
 use crate::mapping::CargoMetadataParameters;
+use crate::percentiles::EcosystemStats;
 use crate::scan::{GeigerContext, ScanResult};
+use crate::security_policy::SecurityPolicies;
 use crate::tree::TextTreeLine;
 
 use handle_text_tree_line::{
@@ -14,7 +25,8 @@ use handle_text_tree_line::{
 };
 use total_package_counts::TotalPackageCounts;
 
-use cargo_geiger_serde::{Count, CounterBlock};
+use cargo_geiger_serde::{Count, CounterBlock, FfiCount};
+use cargo_metadata::PackageId;
 use colored::ColoredString;
 use std::collections::HashSet;
 use std::path::PathBuf;
@@ -31,6 +43,25 @@ pub const UNSAFE_COUNTERS_HEADER: [&str; 6] = [
     "Dependency",
 ];
 
+/// The header of the column added with `--ffi`, before the dependency
+pub const FFI_HEADER: &str = "FFI ";
+
+/// The headers of the table, with the FFI column when it is added
+pub fn table_header(ffi: bool) -> Vec<&'static str> {
+    let mut header = UNSAFE_COUNTERS_HEADER.to_vec();
+    if ffi {
+        header.insert(header.len() - 1, FFI_HEADER);
+    }
+    header
+}
+
+/// The unsafe categories of the table columns, as named in flags and compact
+/// output
+pub const UNSAFE_CATEGORY_NAMES: [&str; 5] =
+    ["functions", "expressions", "impls", "traits", "methods"];
+
+/// The checks of the gates, such as `--require-safety-docs`, are run by the
+/// caller, so their counts are left at 0 in the result
 pub fn create_table_from_text_tree_lines(
     cargo_metadata_parameters: &CargoMetadataParameters,
     table_parameters: &TableParameters,
@@ -40,30 +71,43 @@ pub fn create_table_from_text_tree_lines(
     let mut total_package_counts = TotalPackageCounts::new();
     let mut warning_count = 0;
     let mut visited_package_ids = HashSet::new();
-    let emoji_symbols =
-        EmojiSymbols::new(table_parameters.print_config.output_format);
+    let emoji_symbols = EmojiSymbols::new(
+        table_parameters.print_config.color,
+        table_parameters.print_config.color_scheme,
+        table_parameters.print_config.output_format,
+        table_parameters.print_config.symbols,
+    );
     let mut handle_package_parameters = HandlePackageParameters {
         total_package_counts: &mut total_package_counts,
         visited_package_ids: &mut visited_package_ids,
         warning_count: &mut warning_count,
     };
 
+    let is_plain =
+        table_parameters.print_config.output_format == OutputFormat::Plain;
+
     for table_line in text_tree_lines.into_iter().filter_map(|text_tree_line| {
         match text_tree_line {
+            // The group headers would break the columns of the Plain output.
+            TextTreeLine::ExtraDepsGroup { .. } if is_plain => None,
             TextTreeLine::ExtraDepsGroup {
                 kind: dep_kind,
                 tree_vines,
             } => text_tree_line_extra_deps_group_to_table_line_string(
-                dep_kind, tree_vines,
+                dep_kind,
+                table_parameters.print_config.ffi,
+                tree_vines,
             ),
             TextTreeLine::Package {
                 id: package_id,
+                pruned_package_ids,
                 tree_vines,
             } => text_tree_line_package_to_table_line_string(
                 cargo_metadata_parameters,
                 &emoji_symbols,
                 &mut handle_package_parameters,
                 package_id,
+                &pruned_package_ids,
                 table_parameters,
                 tree_vines,
             ),
@@ -72,42 +116,154 @@ pub fn create_table_from_text_tree_lines(
         table_lines.push(table_line);
     }
 
-    table_lines.push(String::new());
+    if !is_plain {
+        table_lines.push(String::new());
+    }
     let total_detection_status =
         total_package_counts.get_total_detection_status();
 
-    table_lines.push(format!(
-        "{}",
-        table_footer(
-            total_package_counts.total_counter_block,
-            total_package_counts.total_unused_counter_block,
-            table_parameters.print_config.output_format,
-            total_detection_status
-        )
-    ));
+    let over_fail_threshold_ratio =
+        table_parameters.fail_threshold.and_then(|fail_threshold| {
+            unsafe_ratio_over_threshold(
+                fail_threshold,
+                &total_package_counts.total_counter_block,
+                &total_package_counts.total_unused_counter_block,
+            )
+        });
+
+    match table_parameters.summary_format {
+        SummaryFormat::Table if is_plain => {}
+        SummaryFormat::Table if table_parameters.print_config.only_unsafe => {
+            table_lines.push(only_unsafe_summary_line(&total_package_counts))
+        }
+        SummaryFormat::Table => {
+            let print_config = table_parameters.print_config;
+            let ffi_footer = match print_config.ffi {
+                true => colorize(
+                    print_config.color,
+                    print_config.color_scheme,
+                    &total_detection_status,
+                    print_config.output_format,
+                    ffi_cell(
+                        &total_package_counts.total_ffi,
+                        print_config.hide_zeros,
+                    ),
+                )
+                .to_string(),
+                false => String::new(),
+            };
+            table_lines.push(format!(
+                "{}{}",
+                table_footer(
+                    print_config.color,
+                    print_config.color_scheme,
+                    total_package_counts.total_counter_block,
+                    total_package_counts.total_unused_counter_block,
+                    print_config.hide_zeros,
+                    print_config.output_format,
+                    total_detection_status
+                ),
+                ffi_footer
+            ))
+        }
+        summary_format => {
+            let summary = Summary {
+                used: total_package_counts.total_counter_block,
+                unused: total_package_counts.total_unused_counter_block,
+                crates: CrateCounts {
+                    forbids_unsafe: total_package_counts
+                        .none_detected_forbids_unsafe
+                        as u64,
+                    denies_unsafe: total_package_counts
+                        .none_detected_denies_unsafe
+                        as u64,
+                    conditionally_forbids_unsafe: total_package_counts
+                        .none_detected_conditionally_forbids_unsafe
+                        as u64,
+                    allows_unsafe: total_package_counts
+                        .none_detected_allows_unsafe
+                        as u64,
+                    unsafe_detected: total_package_counts.unsafe_detected
+                        as u64,
+                },
+            };
+            if summary_format == SummaryFormat::Json {
+                table_lines.push(summary.json_line());
+            } else {
+                table_lines.append(&mut summary.text_lines());
+            }
+        }
+    }
 
-    table_lines.push(String::new());
+    if !is_plain {
+        table_lines.push(String::new());
+    }
 
     ScanResult {
+        category_policy_error_count: 0,
+        checksum_mismatch_count: 0,
+        detection_outcome: None,
+        over_fail_threshold_ratio,
+        over_max_score_count: 0,
         scan_output_lines: table_lines,
+        unaudited_crate_count: 0,
+        undocumented_unsafe_count: 0,
+        unsafe_crate_count: 0,
+        unsafe_limit_violation_count: 0,
         warning_count,
     }
 }
 
+/// The line printed instead of the totals with `--only-unsafe`
+fn only_unsafe_summary_line(
+    total_package_counts: &TotalPackageCounts,
+) -> String {
+    let crates_scanned = total_package_counts.none_detected_forbids_unsafe
+        + total_package_counts.none_detected_denies_unsafe
+        + total_package_counts.none_detected_conditionally_forbids_unsafe
+        + total_package_counts.none_detected_allows_unsafe
+        + total_package_counts.unsafe_detected;
+    match total_package_counts.unsafe_detected {
+        0 => format!(
+            "No unsafe code found in the {} crates scanned",
+            crates_scanned
+        ),
+        unsafe_detected => format!(
+            "Scanned {} crates, {} use unsafe code",
+            crates_scanned, unsafe_detected
+        ),
+    }
+}
+
 pub struct TableParameters<'a> {
+    pub advisories: Option<&'a Advisories>,
+    pub audits: Option<&'a Audits>,
+    pub checksum_statuses: Option<&'a ChecksumStatuses>,
+    pub download_counts: Option<&'a DownloadCounts>,
+    pub ecosystem_stats: Option<&'a EcosystemStats>,
+    pub expanded_counts: Option<&'a ExpandedCounts>,
+    pub fail_threshold: Option<f32>,
     pub geiger_context: &'a GeigerContext,
     pub print_config: &'a PrintConfig,
+    /// Printed with `--workspace-only`, even if it isn't a workspace member
+    pub root_package_id: &'a PackageId,
     pub rs_files_used: &'a HashSet<PathBuf>,
+    pub security_policies: Option<&'a SecurityPolicies>,
+    pub summary_format: SummaryFormat,
+    pub unsafe_history: Option<&'a UnsafeHistory>,
 }
 
 fn table_footer_unsafe_counts(
+    color: ColorWhen,
+    color_scheme: ColorScheme,
     used: CounterBlock,
     not_used: CounterBlock,
+    hide_zeros: bool,
     output_format: OutputFormat,
     status: CrateDetectionStatus,
 ) -> ColoredString {
     let fmt = |used: &Count, not_used: &Count| {
-        format!("{}/{}", used.unsafe_, used.unsafe_ + not_used.unsafe_)
+        unsafe_count_cell(used, not_used, hide_zeros)
     };
     let output = format!(
         "{: <10} {: <12} {: <6} {: <7} {: <7}",
@@ -117,10 +273,51 @@ fn table_footer_unsafe_counts(
         fmt(&used.item_traits, &not_used.item_traits),
         fmt(&used.methods, &not_used.methods),
     );
-    colorize(&status, output_format, output)
+    colorize(color, color_scheme, &status, output_format, output)
+}
+
+/// The percentage of safe code among the code counted in `used` and
+/// `not_used`, as displayed by `--output-format Ratio`
+fn safe_percentage(used: &Count, not_used: &Count) -> f32 {
+    let total = used.safe + used.unsafe_ + not_used.unsafe_ + not_used.safe;
+    if total == 0 {
+        100.0
+    } else {
+        (100.00 * (used.safe + not_used.safe) as f32) / (total as f32)
+    }
+}
+
+/// The share of unsafe code among the code of every category, the complement
+/// of the safe percentage of the categories added together, when it is above
+/// `fail_threshold`
+pub fn unsafe_ratio_over_threshold(
+    fail_threshold: f32,
+    used: &CounterBlock,
+    not_used: &CounterBlock,
+) -> Option<f32> {
+    let categories = |counter_block: &CounterBlock| {
+        [
+            counter_block.functions.clone(),
+            counter_block.exprs.clone(),
+            counter_block.item_impls.clone(),
+            counter_block.item_traits.clone(),
+            counter_block.methods.clone(),
+        ]
+        .iter()
+        .fold(Count::default(), |total, count| total + count.clone())
+    };
+    let (used, not_used) = (categories(used), categories(not_used));
+    let unsafe_ratio = 1.0 - safe_percentage(&used, &not_used) / 100.0;
+    // A single unsafe item among many can round to a ratio of 0, which still
+    // fails a threshold of 0.
+    let has_unsafe = used.unsafe_ + not_used.unsafe_ > 0;
+    (unsafe_ratio > fail_threshold || (has_unsafe && fail_threshold <= 0.0))
+        .then_some(unsafe_ratio)
 }
 
 fn table_footer_safe_ratio(
+    color: ColorWhen,
+    color_scheme: ColorScheme,
     used: CounterBlock,
     not_used: CounterBlock,
     output_format: OutputFormat,
@@ -131,16 +328,7 @@ fn table_footer_safe_ratio(
             "{:>5}/{:<}={:.2}%",
             (used.safe + not_used.safe),
             (used.safe + used.unsafe_ + not_used.unsafe_ + not_used.safe),
-            if used.safe + used.unsafe_ + not_used.unsafe_ + not_used.safe == 0
-            {
-                100.0
-            } else {
-                (100.00 * (used.safe + not_used.safe) as f32)
-                    / ((used.safe
-                        + used.unsafe_
-                        + not_used.unsafe_
-                        + not_used.safe) as f32)
-            }
+            safe_percentage(used, not_used)
         )
     };
     let output = format!(
@@ -151,26 +339,45 @@ fn table_footer_safe_ratio(
         fmt(&used.item_traits, &not_used.item_traits),
         fmt(&used.methods, &not_used.methods),
     );
-    colorize(&status, output_format, output)
+    colorize(color, color_scheme, &status, output_format, output)
 }
 
 fn table_footer(
+    color: ColorWhen,
+    color_scheme: ColorScheme,
     used: CounterBlock,
     not_used: CounterBlock,
+    hide_zeros: bool,
     output_format: OutputFormat,
     status: CrateDetectionStatus,
 ) -> ColoredString {
     match output_format {
-        OutputFormat::Ratio => {
-            table_footer_safe_ratio(used, not_used, output_format, status)
-        }
-        _ => table_footer_unsafe_counts(used, not_used, output_format, status),
+        OutputFormat::Ratio => table_footer_safe_ratio(
+            color,
+            color_scheme,
+            used,
+            not_used,
+            output_format,
+            status,
+        ),
+        _ => table_footer_unsafe_counts(
+            color,
+            color_scheme,
+            used,
+            not_used,
+            hide_zeros,
+            output_format,
+            status,
+        ),
     }
 }
 
-fn table_row(
+/// With `hide_zeros`, the cells of categories without any unsafe code are
+/// rendered as `-`. The safe ratio is always rendered in full.
+pub fn table_row(
     used: &CounterBlock,
     not_used: &CounterBlock,
+    hide_zeros: bool,
     output_format: OutputFormat,
 ) -> String {
     match output_format {
@@ -212,7 +419,7 @@ fn table_row(
         }
         _ => {
             let fmt = |used: &Count, not_used: &Count| {
-                format!("{}/{}", used.unsafe_, used.unsafe_ + not_used.unsafe_)
+                unsafe_count_cell(used, not_used, hide_zeros)
             };
             format!(
                 "{: <10} {: <12} {: <6} {: <7} {: <7}",
@@ -226,9 +433,37 @@ fn table_row(
     }
 }
 
-fn table_row_empty() -> String {
-    let headers_but_last =
-        &UNSAFE_COUNTERS_HEADER[..UNSAFE_COUNTERS_HEADER.len() - 1];
+/// The cell of the FFI column, appended to a table row, with the FFI items
+/// used by the build and found in total, such as ` 1/3 `. With `hide_zeros`,
+/// a crate without any is rendered as `-`.
+pub fn ffi_cell(ffi: &FfiCount, hide_zeros: bool) -> String {
+    let found = ffi.used + ffi.unused;
+    let cell = if hide_zeros && found == 0 {
+        String::from("-")
+    } else {
+        format!("{}/{}", ffi.used, found)
+    };
+    format!(" {: <4}", cell)
+}
+
+/// The unsafe code used by the build and the total unsafe code found, such as
+/// `1/3`
+fn unsafe_count_cell(
+    used: &Count,
+    not_used: &Count,
+    hide_zeros: bool,
+) -> String {
+    let total = used.unsafe_ + not_used.unsafe_;
+    if hide_zeros && total == 0 {
+        String::from("-")
+    } else {
+        format!("{}/{}", used.unsafe_, total)
+    }
+}
+
+fn table_row_empty(ffi: bool) -> String {
+    let header = table_header(ffi);
+    let headers_but_last = &header[..header.len() - 1];
     let n = headers_but_last
         .iter()
         .map(|s| s.len())
@@ -247,7 +482,7 @@ mod table_tests {
 
     use geiger::RsFileMetrics;
     use rstest::*;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
     use std::path::Path;
 
     #[rstest(
@@ -279,12 +514,17 @@ mod table_tests {
 
         for crate_detection_status in [
             CrateDetectionStatus::NoneDetectedForbidsUnsafe,
+            CrateDetectionStatus::NoneDetectedDeniesUnsafe,
+            CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe,
             CrateDetectionStatus::NoneDetectedAllowsUnsafe,
             CrateDetectionStatus::UnsafeDetected,
         ] {
             let table_footer = table_footer(
+                ColorWhen::Always,
+                ColorScheme::Default,
                 used_counter_block.clone(),
                 not_used_counter_block.clone(),
+                false,
                 input_output_format,
                 crate_detection_status.clone(),
             );
@@ -292,6 +532,8 @@ mod table_tests {
             assert_eq!(
                 table_footer,
                 colorize(
+                    ColorWhen::Always,
+                    ColorScheme::Default,
                     &crate_detection_status,
                     input_output_format,
                     expected_line.clone()
@@ -300,6 +542,74 @@ mod table_tests {
         }
     }
 
+    #[rstest(
+        input_fail_threshold,
+        expected_over_threshold,
+        case(0.0, true),
+        case(0.5, true),
+        case(0.6, false),
+        case(1.0, false)
+    )]
+    fn unsafe_ratio_over_threshold_test(
+        input_fail_threshold: f32,
+        expected_over_threshold: bool,
+    ) {
+        let unsafe_ratio = unsafe_ratio_over_threshold(
+            input_fail_threshold,
+            &create_counter_block(),
+            &create_counter_block(),
+        );
+
+        // 60 unsafe items among 110, the complement of the 50/110 safe items
+        // of the categories of the Ratio footer added together
+        assert_eq!(unsafe_ratio.is_some(), expected_over_threshold);
+        if let Some(unsafe_ratio) = unsafe_ratio {
+            assert!((unsafe_ratio - 60.0 / 110.0).abs() < 1e-6);
+        }
+    }
+
+    #[rstest]
+    fn unsafe_ratio_over_threshold_test_zero_threshold() {
+        let mut used = CounterBlock::default();
+        used.exprs.safe = 100_000_000;
+        used.exprs.unsafe_ = 1;
+
+        assert!(unsafe_ratio_over_threshold(
+            0.0,
+            &used,
+            &CounterBlock::default()
+        )
+        .is_some());
+        used.exprs.unsafe_ = 0;
+        assert_eq!(
+            unsafe_ratio_over_threshold(0.0, &used, &CounterBlock::default()),
+            None
+        );
+    }
+
+    #[rstest(
+        input_unsafe_detected,
+        expected_line,
+        case(0, "No unsafe code found in the 3 crates scanned"),
+        case(2, "Scanned 5 crates, 2 use unsafe code")
+    )]
+    fn only_unsafe_summary_line_test(
+        input_unsafe_detected: i32,
+        expected_line: &str,
+    ) {
+        let total_package_counts = TotalPackageCounts {
+            none_detected_forbids_unsafe: 1,
+            none_detected_allows_unsafe: 2,
+            unsafe_detected: input_unsafe_detected,
+            ..TotalPackageCounts::new()
+        };
+
+        assert_eq!(
+            only_unsafe_summary_line(&total_package_counts),
+            expected_line
+        );
+    }
+
     #[rstest]
     fn table_row_test() {
         let mut rs_path_to_metrics =
@@ -330,38 +640,103 @@ mod table_tests {
         .collect();
         let unsafety = unsafe_stats(&package_metrics, &rs_files_used);
 
-        let table_row =
-            table_row(&unsafety.used, &unsafety.unused, OutputFormat::Ascii);
+        let table_row = table_row(
+            &unsafety.used,
+            &unsafety.unused,
+            false,
+            OutputFormat::Ascii,
+        );
         assert_eq!(table_row, "4/6        8/12         12/18  16/24   20/30  ");
     }
 
+    #[rstest(
+        input_hide_zeros,
+        expected_table_row,
+        case(false, "1/3        0/0          0/0    0/0     0/0    "),
+        case(true, "1/3        -            -      -       -      ")
+    )]
+    fn table_row_hide_zeros_test(
+        input_hide_zeros: bool,
+        expected_table_row: &str,
+    ) {
+        let mut used = CounterBlock::default();
+        used.functions.unsafe_ = 1;
+        let mut not_used = CounterBlock::default();
+        not_used.functions.unsafe_ = 2;
+
+        assert_eq!(
+            table_row(&used, &not_used, input_hide_zeros, OutputFormat::Ascii),
+            expected_table_row
+        );
+    }
+
     #[rstest]
     fn table_row_empty_test() {
-        let empty_table_row = table_row_empty();
+        let empty_table_row = table_row_empty(false);
         assert_eq!(empty_table_row.len(), 55);
+        assert_eq!(table_row_empty(true).len(), 60);
+    }
+
+    #[rstest(
+        input_ffi,
+        input_hide_zeros,
+        expected_ffi_cell,
+        case(FfiCount { used: 1, unused: 2 }, false, " 1/3 "),
+        case(FfiCount::default(), false, " 0/0 "),
+        case(FfiCount::default(), true, " -   ")
+    )]
+    fn ffi_cell_test(
+        input_ffi: FfiCount,
+        input_hide_zeros: bool,
+        expected_ffi_cell: &str,
+    ) {
+        assert_eq!(ffi_cell(&input_ffi, input_hide_zeros), expected_ffi_cell);
+    }
+
+    #[rstest]
+    fn table_header_test() {
+        assert_eq!(table_header(false), UNSAFE_COUNTERS_HEADER.to_vec());
+        assert_eq!(table_header(true)[5..], [FFI_HEADER, "Dependency"]);
     }
 
     #[rstest(
         input_none_detected_forbids_unsafe,
+        input_none_detected_denies_unsafe,
+        input_none_detected_conditionally_forbids_unsafe,
         input_none_detected_allows_unsafe,
         input_unsafe_detected,
         expected_crate_detection_status,
-        case(0, 0, 1, CrateDetectionStatus::UnsafeDetected),
-        case(1, 0, 0, CrateDetectionStatus::NoneDetectedForbidsUnsafe),
-        case(4, 1, 0, CrateDetectionStatus::NoneDetectedAllowsUnsafe)
+        case(0, 0, 0, 0, 1, CrateDetectionStatus::UnsafeDetected),
+        case(1, 0, 0, 0, 0, CrateDetectionStatus::NoneDetectedForbidsUnsafe),
+        case(1, 1, 0, 0, 0, CrateDetectionStatus::NoneDetectedDeniesUnsafe),
+        case(
+            1,
+            1,
+            1,
+            0,
+            0,
+            CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe
+        ),
+        case(4, 0, 1, 1, 0, CrateDetectionStatus::NoneDetectedAllowsUnsafe)
     )]
     fn total_package_counts_get_total_detection_status_tests(
         input_none_detected_forbids_unsafe: i32,
+        input_none_detected_denies_unsafe: i32,
+        input_none_detected_conditionally_forbids_unsafe: i32,
         input_none_detected_allows_unsafe: i32,
         input_unsafe_detected: i32,
         expected_crate_detection_status: CrateDetectionStatus,
     ) {
         let total_detection_status = TotalPackageCounts {
             none_detected_forbids_unsafe: input_none_detected_forbids_unsafe,
+            none_detected_denies_unsafe: input_none_detected_denies_unsafe,
+            none_detected_conditionally_forbids_unsafe:
+                input_none_detected_conditionally_forbids_unsafe,
             none_detected_allows_unsafe: input_none_detected_allows_unsafe,
             unsafe_detected: input_unsafe_detected,
             total_counter_block: CounterBlock::default(),
             total_unused_counter_block: CounterBlock::default(),
+            total_ffi: FfiCount::default(),
         };
 
         assert_eq!(
@@ -377,7 +752,16 @@ mod table_tests {
         RsFileMetricsWrapper {
             metrics: RsFileMetrics {
                 counters: create_counter_block(),
+                lines: 0,
                 forbids_unsafe,
+                denies_unsafe: false,
+                conditionally_forbids_unsafe: false,
+                undocumented_unsafe_blocks: 0,
+                send_sync_impls: 0,
+                ffi_items: 0,
+                unsafe_calls: BTreeMap::new(),
+                unsafe_locations: vec![],
+                detector_findings: BTreeMap::new(),
             },
             is_crate_entry_point,
         }
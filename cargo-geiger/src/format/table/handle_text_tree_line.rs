@@ -1,13 +1,26 @@
+use crate::advisory::format_advisories;
+use crate::audits::format_audit;
+use crate::checksum::format_checksum_mismatch;
+use crate::downloads::format_download_count;
+use crate::expand::format_expanded_counts;
 use crate::format::emoji_symbols::EmojiSymbols;
+use crate::format::plain::{create_plain_line, PlainRow};
 use crate::format::print_config::{colorize, OutputFormat};
 use crate::format::{get_kind_group_name, CrateDetectionStatus, SymbolKind};
-use crate::mapping::CargoMetadataParameters;
+use crate::history::UNKNOWN_VERSION;
+use crate::license::package_matches_license_filter;
+use crate::mapping::{CargoMetadataParameters, GetPackageIdInformation};
+use crate::percentiles::format_density_comparison;
 use crate::scan::unsafe_stats;
+use crate::security_policy::format_security_policy;
+use crate::source_kind::package_matches_source_filter;
+use crate::tree::is_printed_with_workspace_only;
 
 use super::total_package_counts::TotalPackageCounts;
 use super::TableParameters;
-use super::{table_row, table_row_empty};
+use super::{ffi_cell, table_row, table_row_empty};
 
+use cargo_geiger_serde::CounterBlock;
 use cargo_metadata::{DependencyKind, PackageId};
 use colored::ColoredString;
 use std::collections::HashSet;
@@ -21,10 +34,11 @@ pub struct HandlePackageParameters<'a> {
 
 pub fn text_tree_line_extra_deps_group_to_table_line_string(
     dep_kind: DependencyKind,
+    ffi: bool,
     tree_vines: String,
 ) -> Option<String> {
     get_kind_group_name(dep_kind)
-        .map(|name| format!("{}{}{}", table_row_empty(), tree_vines, name,))
+        .map(|name| format!("{}{}{}", table_row_empty(ffi), tree_vines, name,))
 }
 
 pub fn text_tree_line_package_to_table_line_string(
@@ -32,6 +46,7 @@ pub fn text_tree_line_package_to_table_line_string(
     emoji_symbols: &EmojiSymbols,
     handle_package_parameters: &mut HandlePackageParameters,
     package_id: PackageId,
+    pruned_package_ids: &[PackageId],
     table_parameters: &TableParameters,
     tree_vines: String,
 ) -> Option<String> {
@@ -39,12 +54,37 @@ pub fn text_tree_line_package_to_table_line_string(
         .visited_package_ids
         .insert(package_id.clone());
 
+    if !package_matches_license_filter(
+        cargo_metadata_parameters,
+        &table_parameters.print_config.license_filter,
+        &package_id,
+    ) || !package_matches_source_filter(
+        cargo_metadata_parameters,
+        &table_parameters.print_config.source_filter,
+        &package_id,
+    ) {
+        return None;
+    }
+
     let package_metrics = match table_parameters
         .geiger_context
         .package_id_to_metrics
         .get(&package_id)
     {
         Some(m) => m,
+        None if is_ignored(
+            cargo_metadata_parameters,
+            table_parameters,
+            &package_id,
+        ) =>
+        {
+            return skipped_package_line(
+                cargo_metadata_parameters,
+                package_id,
+                table_parameters,
+                tree_vines,
+            );
+        }
         None => {
             *handle_package_parameters.warning_count += package_is_new as u64;
             eprintln!("WARNING: No metrics found for package: {}", package_id);
@@ -60,22 +100,91 @@ pub fn text_tree_line_package_to_table_line_string(
         handle_package_parameters
             .total_package_counts
             .total_unused_counter_block += unsafe_info.unused.clone();
+        handle_package_parameters.total_package_counts.total_ffi +=
+            unsafe_info.ffi.clone();
     }
-    let unsafe_found = unsafe_info.used.has_unsafe();
-    let crate_forbids_unsafe = unsafe_info.forbids_unsafe;
+    // An allowlisted crate is displayed as allowing unsafe code without using
+    // it, the unsafe code it uses is still counted in the totals.
+    let is_allowlisted = is_allowlisted(
+        cargo_metadata_parameters,
+        table_parameters,
+        &package_id,
+    );
+    let unsafe_found = unsafe_info.used.has_unsafe() && !is_allowlisted;
+    let crate_forbids_unsafe = unsafe_info.forbids_unsafe && !is_allowlisted;
+    let crate_denies_unsafe = unsafe_info.denies_unsafe && !is_allowlisted;
+    let crate_conditionally_forbids_unsafe =
+        unsafe_info.conditionally_forbids_unsafe && !is_allowlisted;
     let total_inc = package_is_new as i32;
     let crate_detection_status =
         get_crate_detection_status_and_update_package_counts(
             crate_forbids_unsafe,
+            crate_denies_unsafe,
+            crate_conditionally_forbids_unsafe,
             handle_package_parameters,
             total_inc,
             unsafe_found,
         );
+    let pruned_unsafe = count_pruned_packages(
+        cargo_metadata_parameters,
+        handle_package_parameters,
+        pruned_package_ids,
+        table_parameters,
+    );
+    let mut row_used = unsafe_info.used.clone();
+    row_used += pruned_unsafe.used;
+    let mut row_unused = unsafe_info.unused.clone();
+    row_unused += pruned_unsafe.unused;
+    let row_detection_status = match pruned_unsafe.unsafe_found {
+        true => CrateDetectionStatus::UnsafeDetected,
+        false => crate_detection_status.clone(),
+    };
+    if is_below_min_unsafe(&row_used, table_parameters.print_config.min_unsafe)
+        || unsafe_info.ffi.used < table_parameters.print_config.min_ffi
+    {
+        return None;
+    }
+    if !is_printed_with_workspace_only(
+        table_parameters.print_config.workspace_only,
+        &cargo_metadata_parameters.metadata.workspace_members,
+        table_parameters.root_package_id,
+        &package_id,
+    ) {
+        return None;
+    }
+    // The flat list of `--only-unsafe` has each crate once, without the crates
+    // not using unsafe code.
+    if table_parameters.print_config.only_unsafe
+        && (!package_is_new
+            || row_detection_status != CrateDetectionStatus::UnsafeDetected)
+    {
+        return None;
+    }
+    if table_parameters.print_config.output_format == OutputFormat::Plain {
+        let name_and_version = package_id
+            .get_package_id_name_and_version(cargo_metadata_parameters.krates);
+        return Some(create_plain_line(&PlainRow {
+            depth: tree_vines.trim_end(),
+            name: name_and_version
+                .as_ref()
+                .map_or(package_id.repr.as_str(), |(name, _)| name),
+            version: name_and_version.as_ref().map(|(_, version)| version),
+            status: &row_detection_status,
+            used: &row_used,
+            unused: &row_unused,
+        }));
+    }
 
-    let icon = match crate_detection_status {
+    let icon = match row_detection_status {
         CrateDetectionStatus::NoneDetectedForbidsUnsafe => {
             emoji_symbols.emoji(SymbolKind::Lock)
         }
+        CrateDetectionStatus::NoneDetectedDeniesUnsafe => {
+            emoji_symbols.emoji(SymbolKind::OpenLock)
+        }
+        CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe => {
+            emoji_symbols.emoji(SymbolKind::KeyLock)
+        }
         CrateDetectionStatus::NoneDetectedAllowsUnsafe => {
             emoji_symbols.emoji(SymbolKind::QuestionMark)
         }
@@ -84,29 +193,122 @@ pub fn text_tree_line_package_to_table_line_string(
         }
     };
 
+    let mut package_name = format!(
+        "{}",
+        table_parameters.print_config.format.display(
+            cargo_metadata_parameters,
+            &package_id,
+            Some(&unsafe_info)
+        )
+    );
+    if let (Some(unsafe_history), CrateDetectionStatus::UnsafeDetected) =
+        (table_parameters.unsafe_history, &crate_detection_status)
+    {
+        let unsafe_introduced_at = package_id
+            .get_package_id_name_and_version(cargo_metadata_parameters.krates)
+            .and_then(|(name, version)| {
+                unsafe_history.unsafe_introduced_at(
+                    &name,
+                    &version,
+                    &unsafe_info.used,
+                )
+            });
+        package_name.push_str(&format!(
+            " (unsafe since {})",
+            unsafe_introduced_at
+                .map_or(String::from(UNKNOWN_VERSION), |v| v.to_string())
+        ));
+    }
+    if let Some(download_counts) = table_parameters.download_counts {
+        let downloads = package_id
+            .get_package_id_name_and_version(cargo_metadata_parameters.krates)
+            .and_then(|(name, _)| download_counts.get(&name));
+        package_name.push_str(&format!(
+            " ({} downloads)",
+            downloads.map_or(String::from("unknown"), format_download_count)
+        ));
+    }
+    if let (Some(ecosystem_stats), CrateDetectionStatus::UnsafeDetected) =
+        (table_parameters.ecosystem_stats, &crate_detection_status)
+    {
+        package_name.push_str(&format_density_comparison(
+            ecosystem_stats
+                .compare_counter_blocks(&unsafe_info.used, &unsafe_info.unused),
+        ));
+    }
+    if let Some(security_policies) = table_parameters.security_policies {
+        let has_security_policy = package_id
+            .get_package_id_name_and_version(cargo_metadata_parameters.krates)
+            .and_then(|(name, version)| security_policies.get(&name, &version));
+        package_name.push_str(&format_security_policy(has_security_policy));
+    }
+    if let Some(checksum_statuses) = table_parameters.checksum_statuses {
+        let is_mismatched = package_id
+            .get_package_id_name_and_version(cargo_metadata_parameters.krates)
+            .and_then(|(name, version)| {
+                checksum_statuses.is_mismatched(&name, &version)
+            });
+        package_name.push_str(&format_checksum_mismatch(is_mismatched));
+    }
+    if let Some(advisories) = table_parameters.advisories {
+        let advisory_ids = package_id
+            .get_package_id_name_and_version(cargo_metadata_parameters.krates)
+            .map(|(name, version)| advisories.get(&name, &version))
+            .unwrap_or_default();
+        package_name.push_str(&format_advisories(&advisory_ids));
+    }
+    if let Some(audits) = table_parameters.audits {
+        let crate_audit = package_id
+            .get_package_id_name_and_version(cargo_metadata_parameters.krates)
+            .and_then(|(name, version)| audits.get(&name, &version));
+        package_name.push_str(&format_audit(
+            crate_audit,
+            row_detection_status == CrateDetectionStatus::UnsafeDetected,
+        ));
+    }
+    if let Some(expanded_counts) = table_parameters.expanded_counts {
+        let expanded = package_id
+            .get_package_id_name_and_version(cargo_metadata_parameters.krates)
+            .and_then(|(name, version)| expanded_counts.get(&name, &version));
+        package_name.push_str(&format_expanded_counts(expanded));
+    }
     let package_name = colorize(
-        &crate_detection_status,
+        table_parameters.print_config.color,
+        table_parameters.print_config.color_scheme,
+        &row_detection_status,
         table_parameters.print_config.output_format,
-        format!(
-            "{}",
-            table_parameters
-                .print_config
-                .format
-                .display(cargo_metadata_parameters, &package_id)
-        ),
+        package_name,
     );
     let unsafe_info = colorize(
-        &crate_detection_status,
+        table_parameters.print_config.color,
+        table_parameters.print_config.color_scheme,
+        &row_detection_status,
         table_parameters.print_config.output_format,
-        table_row(
-            &unsafe_info.used,
-            &unsafe_info.unused,
-            table_parameters.print_config.output_format,
-        ),
+        match table_parameters.print_config.ffi {
+            true => format!(
+                "{}{}",
+                table_row(
+                    &row_used,
+                    &row_unused,
+                    table_parameters.print_config.hide_zeros,
+                    table_parameters.print_config.output_format,
+                ),
+                ffi_cell(
+                    &unsafe_info.ffi,
+                    table_parameters.print_config.hide_zeros
+                )
+            ),
+            false => table_row(
+                &row_used,
+                &row_unused,
+                table_parameters.print_config.hide_zeros,
+                table_parameters.print_config.output_format,
+            ),
+        },
     );
 
     Some(construct_package_text_tree_line(
-        crate_detection_status,
+        row_detection_status,
         emoji_symbols,
         icon,
         package_name,
@@ -116,6 +318,163 @@ pub fn text_tree_line_package_to_table_line_string(
     ))
 }
 
+fn is_allowlisted(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    table_parameters: &TableParameters,
+    package_id: &PackageId,
+) -> bool {
+    table_parameters
+        .print_config
+        .allowlist
+        .as_ref()
+        .is_some_and(|allowlist| {
+            package_id
+                .get_package_id_name_and_version(
+                    cargo_metadata_parameters.krates,
+                )
+                .is_some_and(|(name, version)| {
+                    allowlist.contains(&name, &version)
+                })
+        })
+}
+
+fn is_ignored(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    table_parameters: &TableParameters,
+    package_id: &PackageId,
+) -> bool {
+    package_id
+        .get_package_id_name_and_version(cargo_metadata_parameters.krates)
+        .is_some_and(|(name, _)| {
+            table_parameters
+                .print_config
+                .ignore_list
+                .is_ignored_crate(&name)
+        })
+}
+
+/// The row of a crate given with `--ignore`, which stays in the tree without
+/// any unsafe counts. It is left out of the flat list of `--only-unsafe` and
+/// of the plain output, as it has no counts to list.
+fn skipped_package_line(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    package_id: PackageId,
+    table_parameters: &TableParameters,
+    tree_vines: String,
+) -> Option<String> {
+    let print_config = table_parameters.print_config;
+    if print_config.only_unsafe
+        || print_config.output_format == OutputFormat::Plain
+        || !is_printed_with_workspace_only(
+            print_config.workspace_only,
+            &cargo_metadata_parameters.metadata.workspace_members,
+            table_parameters.root_package_id,
+            &package_id,
+        )
+    {
+        return None;
+    }
+    Some(format!(
+        "{}{}{} (skipped)",
+        table_row_empty(print_config.ffi),
+        tree_vines,
+        print_config.format.display(
+            cargo_metadata_parameters,
+            &package_id,
+            None
+        )
+    ))
+}
+
+/// The unsafe code of the crates left out of the tree by `--max-depth`, which
+/// is added to the row of the crate above them
+#[derive(Debug, Default)]
+struct PrunedUnsafe {
+    used: CounterBlock,
+    unused: CounterBlock,
+    /// Whether a crate which isn't allowlisted uses unsafe code
+    unsafe_found: bool,
+}
+
+/// Count the crates left out of the tree in the totals, as if they were
+/// printed, and add up the unsafe code they use. A crate left out below
+/// several crates is counted once in the totals, but in each of their rows.
+fn count_pruned_packages(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    handle_package_parameters: &mut HandlePackageParameters,
+    pruned_package_ids: &[PackageId],
+    table_parameters: &TableParameters,
+) -> PrunedUnsafe {
+    let mut pruned_unsafe = PrunedUnsafe::default();
+    for pruned_package_id in pruned_package_ids {
+        if !package_matches_license_filter(
+            cargo_metadata_parameters,
+            &table_parameters.print_config.license_filter,
+            pruned_package_id,
+        ) || !package_matches_source_filter(
+            cargo_metadata_parameters,
+            &table_parameters.print_config.source_filter,
+            pruned_package_id,
+        ) {
+            continue;
+        }
+        let package_is_new = handle_package_parameters
+            .visited_package_ids
+            .insert(pruned_package_id.clone());
+        let package_metrics = match table_parameters
+            .geiger_context
+            .package_id_to_metrics
+            .get(pruned_package_id)
+        {
+            Some(m) => m,
+            None => {
+                if package_is_new {
+                    *handle_package_parameters.warning_count += 1;
+                    eprintln!(
+                        "WARNING: No metrics found for package: {}",
+                        pruned_package_id
+                    );
+                }
+                continue;
+            }
+        };
+        let unsafe_info =
+            unsafe_stats(package_metrics, table_parameters.rs_files_used);
+        let is_allowlisted = is_allowlisted(
+            cargo_metadata_parameters,
+            table_parameters,
+            pruned_package_id,
+        );
+        let unsafe_found = unsafe_info.used.has_unsafe() && !is_allowlisted;
+        if package_is_new {
+            handle_package_parameters
+                .total_package_counts
+                .total_counter_block += unsafe_info.used.clone();
+            handle_package_parameters
+                .total_package_counts
+                .total_unused_counter_block += unsafe_info.unused.clone();
+            get_crate_detection_status_and_update_package_counts(
+                unsafe_info.forbids_unsafe && !is_allowlisted,
+                unsafe_info.denies_unsafe && !is_allowlisted,
+                unsafe_info.conditionally_forbids_unsafe && !is_allowlisted,
+                handle_package_parameters,
+                1,
+                unsafe_found,
+            );
+        }
+        pruned_unsafe.used += unsafe_info.used;
+        pruned_unsafe.unused += unsafe_info.unused;
+        pruned_unsafe.unsafe_found |= unsafe_found;
+    }
+    pruned_unsafe
+}
+
+/// Whether the crate uses less unsafe code in total than `--min-unsafe`, and is
+/// left out of the tree after being counted
+fn is_below_min_unsafe(used: &CounterBlock, min_unsafe: u64) -> bool {
+    used.total_unsafe() < min_unsafe
+}
+
 fn construct_package_text_tree_line(
     crate_detection_status: CrateDetectionStatus,
     emoji_symbols: &EmojiSymbols,
@@ -165,24 +524,43 @@ fn construct_package_text_tree_line(
 
 fn get_crate_detection_status_and_update_package_counts(
     crate_forbids_unsafe: bool,
+    crate_denies_unsafe: bool,
+    crate_conditionally_forbids_unsafe: bool,
     handle_package_parameters: &mut HandlePackageParameters,
     total_inc: i32,
     unsafe_found: bool,
 ) -> CrateDetectionStatus {
-    match (crate_forbids_unsafe, unsafe_found) {
-        (true, false) => {
+    match (
+        crate_forbids_unsafe,
+        crate_denies_unsafe,
+        crate_conditionally_forbids_unsafe,
+        unsafe_found,
+    ) {
+        (true, _, _, false) => {
             handle_package_parameters
                 .total_package_counts
                 .none_detected_forbids_unsafe += total_inc;
             CrateDetectionStatus::NoneDetectedForbidsUnsafe
         }
-        (false, false) => {
+        (false, true, _, false) => {
+            handle_package_parameters
+                .total_package_counts
+                .none_detected_denies_unsafe += total_inc;
+            CrateDetectionStatus::NoneDetectedDeniesUnsafe
+        }
+        (false, false, true, false) => {
+            handle_package_parameters
+                .total_package_counts
+                .none_detected_conditionally_forbids_unsafe += total_inc;
+            CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe
+        }
+        (false, false, false, false) => {
             handle_package_parameters
                 .total_package_counts
                 .none_detected_allows_unsafe += total_inc;
             CrateDetectionStatus::NoneDetectedAllowsUnsafe
         }
-        (_, true) => {
+        (_, _, _, true) => {
             handle_package_parameters
                 .total_package_counts
                 .unsafe_detected += total_inc;
@@ -196,6 +574,7 @@ mod handle_text_tree_line_tests {
     use super::*;
 
     use crate::format::print_config::PrintConfig;
+    use crate::format::Symbols;
     use colored::Colorize;
     use rstest::*;
 
@@ -204,11 +583,11 @@ mod handle_text_tree_line_tests {
         expected_table_line_option,
         case(
             DependencyKind::Build,
-            Some(format!("{}{}{}", table_row_empty(), "tree_vines", "[build-dependencies]"))
+            Some(format!("{}{}{}", table_row_empty(false), "tree_vines", "[build-dependencies]"))
         ),
         case(
             DependencyKind::Development,
-            Some(format!("{}{}{}", table_row_empty(), "tree_vines", "[dev-dependencies]"))
+            Some(format!("{}{}{}", table_row_empty(false), "tree_vines", "[dev-dependencies]"))
         ),
         case(DependencyKind::Normal, None)
     )]
@@ -220,6 +599,7 @@ mod handle_text_tree_line_tests {
         let actual_table_lines =
             text_tree_line_extra_deps_group_to_table_line_string(
                 input_dep_kind,
+                false,
                 tree_vines,
             );
 
@@ -237,6 +617,12 @@ mod handle_text_tree_line_tests {
             SymbolKind::Lock,
             String::from("unsafe_info  🔒  tree_vinespackage_name")
         ),
+        case(
+            CrateDetectionStatus::NoneDetectedDeniesUnsafe,
+            OutputFormat::GitHubMarkdown,
+            SymbolKind::OpenLock,
+            String::from("unsafe_info  🔓  tree_vinespackage_name")
+        ),
         case(
             CrateDetectionStatus::UnsafeDetected,
             OutputFormat::GitHubMarkdown,
@@ -250,16 +636,34 @@ mod handle_text_tree_line_tests {
         input_symbol_kind: SymbolKind,
         expected_package_text_tree_line: String,
     ) {
-        let emoji_symbols = EmojiSymbols::new(input_output_format);
+        let emoji_symbols = EmojiSymbols::new(
+            Default::default(),
+            Default::default(),
+            input_output_format,
+            Symbols::Emoji,
+        );
         let icon = emoji_symbols.emoji(input_symbol_kind);
         let package_name = String::from("package_name").normal();
         let table_parameters = TableParameters {
+            advisories: None,
+            audits: None,
+            checksum_statuses: None,
+            download_counts: None,
+            ecosystem_stats: None,
+            expanded_counts: None,
+            fail_threshold: None,
             geiger_context: &Default::default(),
             print_config: &PrintConfig {
                 output_format: input_output_format,
                 ..Default::default()
             },
+            root_package_id: &PackageId {
+                repr: String::from("root_package_id"),
+            },
             rs_files_used: &Default::default(),
+            security_policies: None,
+            summary_format: Default::default(),
+            unsafe_history: None,
         };
         let tree_vines = String::from("tree_vines");
         let unsafe_info = ColoredString::from("unsafe_info").normal();
@@ -336,10 +740,13 @@ mod handle_text_tree_line_tests {
         let mut handle_package_parameters = HandlePackageParameters {
             total_package_counts: &mut TotalPackageCounts {
                 none_detected_forbids_unsafe: 0,
+                none_detected_denies_unsafe: 0,
+                none_detected_conditionally_forbids_unsafe: 0,
                 none_detected_allows_unsafe: 0,
                 unsafe_detected: 0,
                 total_counter_block: Default::default(),
                 total_unused_counter_block: Default::default(),
+                total_ffi: Default::default(),
             },
             visited_package_ids: &mut Default::default(),
             warning_count: &mut 0,
@@ -348,6 +755,8 @@ mod handle_text_tree_line_tests {
         let crate_detection_status =
             get_crate_detection_status_and_update_package_counts(
                 input_crate_forbids_unsafe,
+                false,
+                false,
                 &mut handle_package_parameters,
                 input_total_inc,
                 input_unsafe_found,
@@ -376,4 +785,114 @@ mod handle_text_tree_line_tests {
             expected_unsafe_detected
         );
     }
+
+    #[rstest(
+        input_total_inc,
+        expected_none_detected_denies_unsafe,
+        case(1, 1),
+        case(0, 0)
+    )]
+    fn get_crate_detection_status_and_update_package_counts_test_denies_unsafe(
+        input_total_inc: i32,
+        expected_none_detected_denies_unsafe: i32,
+    ) {
+        let mut handle_package_parameters = HandlePackageParameters {
+            total_package_counts: &mut TotalPackageCounts::new(),
+            visited_package_ids: &mut Default::default(),
+            warning_count: &mut 0,
+        };
+
+        let crate_detection_status =
+            get_crate_detection_status_and_update_package_counts(
+                false,
+                true,
+                false,
+                &mut handle_package_parameters,
+                input_total_inc,
+                false,
+            );
+
+        assert_eq!(
+            crate_detection_status,
+            CrateDetectionStatus::NoneDetectedDeniesUnsafe
+        );
+
+        assert_eq!(
+            handle_package_parameters
+                .total_package_counts
+                .none_detected_denies_unsafe,
+            expected_none_detected_denies_unsafe
+        );
+
+        assert_eq!(
+            handle_package_parameters
+                .total_package_counts
+                .none_detected_forbids_unsafe,
+            0
+        );
+    }
+
+    #[rstest(
+        input_unsafe_found,
+        expected_crate_detection_status,
+        expected_none_detected_conditionally_forbids_unsafe,
+        case(
+            false,
+            CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe,
+            1
+        ),
+        case(true, CrateDetectionStatus::UnsafeDetected, 0)
+    )]
+    fn get_crate_detection_status_and_update_package_counts_test_conditionally_forbids_unsafe(
+        input_unsafe_found: bool,
+        expected_crate_detection_status: CrateDetectionStatus,
+        expected_none_detected_conditionally_forbids_unsafe: i32,
+    ) {
+        let mut handle_package_parameters = HandlePackageParameters {
+            total_package_counts: &mut TotalPackageCounts::new(),
+            visited_package_ids: &mut Default::default(),
+            warning_count: &mut 0,
+        };
+
+        let crate_detection_status =
+            get_crate_detection_status_and_update_package_counts(
+                false,
+                false,
+                true,
+                &mut handle_package_parameters,
+                1,
+                input_unsafe_found,
+            );
+
+        assert_eq!(crate_detection_status, expected_crate_detection_status);
+
+        assert_eq!(
+            handle_package_parameters
+                .total_package_counts
+                .none_detected_conditionally_forbids_unsafe,
+            expected_none_detected_conditionally_forbids_unsafe
+        );
+    }
+
+    #[rstest(
+        input_unsafe_exprs,
+        input_min_unsafe,
+        expected_is_below,
+        case(0, 0, false),
+        case(0, 1, true),
+        case(2, 3, true),
+        case(3, 3, false)
+    )]
+    fn is_below_min_unsafe_test(
+        input_unsafe_exprs: u64,
+        input_min_unsafe: u64,
+        expected_is_below: bool,
+    ) {
+        let mut used = CounterBlock::default();
+        used.exprs.unsafe_ = input_unsafe_exprs;
+        assert_eq!(
+            is_below_min_unsafe(&used, input_min_unsafe),
+            expected_is_below
+        );
+    }
 }
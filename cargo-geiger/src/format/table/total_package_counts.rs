@@ -1,34 +1,49 @@
 use crate::format::CrateDetectionStatus;
 
-use cargo_geiger_serde::CounterBlock;
+use cargo_geiger_serde::{CounterBlock, FfiCount};
 
 pub struct TotalPackageCounts {
     pub none_detected_forbids_unsafe: i32,
+    pub none_detected_denies_unsafe: i32,
+    pub none_detected_conditionally_forbids_unsafe: i32,
     pub none_detected_allows_unsafe: i32,
     pub unsafe_detected: i32,
     pub total_counter_block: CounterBlock,
     pub total_unused_counter_block: CounterBlock,
+    /// The FFI items of the crates, printed with `--ffi`
+    pub total_ffi: FfiCount,
 }
 
 impl TotalPackageCounts {
     pub fn new() -> TotalPackageCounts {
         TotalPackageCounts {
             none_detected_forbids_unsafe: 0,
+            none_detected_denies_unsafe: 0,
+            none_detected_conditionally_forbids_unsafe: 0,
             none_detected_allows_unsafe: 0,
             unsafe_detected: 0,
             total_counter_block: CounterBlock::default(),
             total_unused_counter_block: CounterBlock::default(),
+            total_ffi: FfiCount::default(),
         }
     }
 
     pub fn get_total_detection_status(&self) -> CrateDetectionStatus {
         match (
             self.none_detected_forbids_unsafe > 0,
+            self.none_detected_denies_unsafe > 0,
+            self.none_detected_conditionally_forbids_unsafe > 0,
             self.none_detected_allows_unsafe > 0,
             self.unsafe_detected > 0,
         ) {
-            (_, _, true) => CrateDetectionStatus::UnsafeDetected,
-            (true, false, false) => {
+            (_, _, _, _, true) => CrateDetectionStatus::UnsafeDetected,
+            (_, _, true, false, false) => {
+                CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe
+            }
+            (_, true, false, false, false) => {
+                CrateDetectionStatus::NoneDetectedDeniesUnsafe
+            }
+            (true, false, false, false, false) => {
                 CrateDetectionStatus::NoneDetectedForbidsUnsafe
             }
             _ => CrateDetectionStatus::NoneDetectedAllowsUnsafe,
@@ -0,0 +1,194 @@
+use cargo_geiger_serde::{PackageId, ReportEntry, SafetyReport, ScanWarning};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// The `SafetyReport` laid out for TOML, with a `[[crate]]` table for each
+/// entry. Every list is sorted, so that an unchanged report is written as the
+/// same bytes in each run.
+#[derive(Deserialize, Serialize)]
+struct TomlReport {
+    packages_without_metrics: Vec<PackageId>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ignored_packages: Vec<PackageId>,
+    used_but_not_scanned_files: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    scan_warnings: Vec<ScanWarning>,
+    #[serde(rename = "crate")]
+    crates: Vec<ReportEntry>,
+}
+
+impl From<&SafetyReport> for TomlReport {
+    fn from(report: &SafetyReport) -> Self {
+        let mut crates = report.packages.values().cloned().collect::<Vec<_>>();
+        crates.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+        let mut packages_without_metrics = report
+            .packages_without_metrics
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        packages_without_metrics.sort();
+        let mut ignored_packages =
+            report.ignored_packages.iter().cloned().collect::<Vec<_>>();
+        ignored_packages.sort();
+        let mut used_but_not_scanned_files = report
+            .used_but_not_scanned_files
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        used_but_not_scanned_files.sort();
+        let mut scan_warnings = report.scan_warnings.clone();
+        scan_warnings.sort();
+        TomlReport {
+            packages_without_metrics,
+            ignored_packages,
+            used_but_not_scanned_files,
+            scan_warnings,
+            crates,
+        }
+    }
+}
+
+impl From<TomlReport> for SafetyReport {
+    fn from(toml_report: TomlReport) -> Self {
+        SafetyReport {
+            packages: toml_report
+                .crates
+                .into_iter()
+                .map(|entry| (entry.package.id.clone(), entry))
+                .collect(),
+            packages_without_metrics: toml_report
+                .packages_without_metrics
+                .into_iter()
+                .collect(),
+            ignored_packages: toml_report
+                .ignored_packages
+                .into_iter()
+                .collect(),
+            used_but_not_scanned_files: toml_report
+                .used_but_not_scanned_files
+                .into_iter()
+                .collect(),
+            scan_warnings: toml_report.scan_warnings,
+        }
+    }
+}
+
+/// The tables of a crate which are written as `[crate.<key>]` tables instead
+/// of inline tables, so that a change of a count is a change of a short line
+const EXPANDED_TABLE_KEYS: [&str; 4] =
+    ["package", "unsafety", "used", "unused"];
+
+pub fn create_toml_lines(report: &SafetyReport) -> Vec<String> {
+    // The TOML serializer doesn't support enum variants holding a value, such
+    // as `Source::Path`, so the report goes through the Json data model, in
+    // which they are tables.
+    let report_value = serde_json::to_value(TomlReport::from(report)).unwrap();
+    let mut document = toml_edit::ser::to_document(&report_value).unwrap();
+    if let Some(crates) = document.remove("crate") {
+        match crates.into_array_of_tables() {
+            Ok(mut crates) => {
+                crates.iter_mut().for_each(expand_tables);
+                document
+                    .insert("crate", toml_edit::Item::ArrayOfTables(crates));
+            }
+            // An empty array is kept as it is.
+            Err(crates) => {
+                document.insert("crate", crates);
+            }
+        }
+    }
+    document.to_string().lines().map(String::from).collect()
+}
+
+fn expand_tables(table: &mut toml_edit::Table) {
+    for key in EXPANDED_TABLE_KEYS {
+        if let Some(item) = table.remove(key) {
+            match item.into_table() {
+                Ok(mut expanded_table) => {
+                    expand_tables(&mut expanded_table);
+                    table.insert(key, toml_edit::Item::Table(expanded_table));
+                }
+                Err(item) => {
+                    table.insert(key, item);
+                }
+            }
+        }
+    }
+}
+
+/// Read a report written with `--output-format Toml`
+pub fn safety_report_from_toml(s: &str) -> anyhow::Result<SafetyReport> {
+    let toml_report = toml_edit::easy::from_str::<TomlReport>(s)?;
+    Ok(SafetyReport::from(toml_report))
+}
+
+#[cfg(test)]
+mod toml_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::{DependencyKind, PackageInfo, UnsafeInfo};
+    use rstest::*;
+    use semver::Version;
+
+    fn create_report() -> SafetyReport {
+        let mut foo = PackageInfo::new(create_path_package_id("foo"));
+        foo.add_dependency(
+            create_path_package_id("bar"),
+            DependencyKind::Normal,
+        );
+        let mut report = create_safety_report(
+            vec![
+                (foo, 3),
+                (PackageInfo::new(create_path_package_id("bar")), 0),
+            ]
+            .into_iter()
+            .map(|(package, unsafe_exprs)| {
+                (
+                    package,
+                    UnsafeInfo {
+                        unsafe_introduced_at: Some(Version::new(0, 1, 0)),
+                        ..create_unsafe_info(unsafe_exprs)
+                    },
+                )
+            })
+            .collect(),
+        );
+        report
+            .packages_without_metrics
+            .insert(create_path_package_id("baz"));
+        report
+            .used_but_not_scanned_files
+            .insert(PathBuf::from("/baz/src/lib.rs"));
+        report
+    }
+
+    #[rstest]
+    fn create_toml_lines_test() {
+        let toml_lines = create_toml_lines(&create_report());
+
+        assert_eq!(
+            toml_lines
+                .iter()
+                .filter(|line| line.as_str() == "[[crate]]")
+                .count(),
+            2
+        );
+        assert!(toml_lines
+            .iter()
+            .any(|line| line == "exprs = { safe = 0, unsafe_ = 3 }"));
+        assert_eq!(toml_lines, create_toml_lines(&create_report()));
+    }
+
+    #[rstest]
+    fn safety_report_from_toml_test() {
+        let report = create_report();
+        let toml = create_toml_lines(&report).join("\n");
+
+        assert_eq!(safety_report_from_toml(&toml).unwrap(), report);
+        assert!(safety_report_from_toml("crate = 1").is_err());
+    }
+}
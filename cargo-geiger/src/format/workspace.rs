@@ -0,0 +1,183 @@
+use crate::format::json::ReportSummary;
+use crate::format::summary::format_category_counts;
+use crate::format::CrateDetectionStatus;
+
+use cargo_geiger_serde::SafetyReport;
+
+/// The headers of the columns of `--member-summary`, the numbers being right
+/// aligned to them
+const MEMBER_SUMMARY_HEADERS: [&str; 4] =
+    ["Member", "Crates", "Using unsafe", "Unsafe used/found"];
+
+/// A row of `--member-summary`, with the totals of a workspace member and
+/// the crates it depends on
+pub struct MemberSummaryRow {
+    pub name: String,
+    pub summary: ReportSummary,
+}
+
+/// The report of `--workspace`: the members scanned, the unsafe usage of
+/// each crate using unsafe code, and the totals of all crates, each counted
+/// once however many members depend on it
+pub fn create_workspace_lines(
+    member_names: &[String],
+    report: &SafetyReport,
+) -> Vec<String> {
+    let mut entries = report
+        .packages
+        .values()
+        .filter(|entry| {
+            CrateDetectionStatus::from(&entry.unsafety)
+                == CrateDetectionStatus::UnsafeDetected
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+
+    let mut workspace_lines = vec![
+        format!("Workspace members scanned: {}", member_names.join(", ")),
+        String::new(),
+    ];
+    if !entries.is_empty() {
+        workspace_lines.push(String::from("Crates using unsafe code:"));
+        for entry in entries {
+            workspace_lines.push(format!(
+                "    {} {}: {}",
+                entry.package.id.name,
+                entry.package.id.version,
+                format_category_counts(
+                    &entry.unsafety.used,
+                    &entry.unsafety.unused
+                )
+            ));
+        }
+        workspace_lines.push(String::new());
+    }
+
+    let report_summary = ReportSummary::from_report(report);
+    workspace_lines.push(format!(
+        "Unsafe code used by the build/found: {}",
+        format_category_counts(&report_summary.used, &report_summary.unused)
+    ));
+    workspace_lines.push(format!(
+        "Crates: {} scanned, {} using unsafe code",
+        report_summary.crates_scanned, report_summary.crates_using_unsafe
+    ));
+    workspace_lines
+}
+
+/// A table with a row for each member, in the order given, with the number
+/// of crates in its dependency graph, itself included, and their unsafe code
+/// used by the build and found in total
+pub fn create_member_summary_lines(rows: &[MemberSummaryRow]) -> Vec<String> {
+    let name_width = rows
+        .iter()
+        .map(|row| row.name.len())
+        .chain(std::iter::once(MEMBER_SUMMARY_HEADERS[0].len()))
+        .max()
+        .unwrap();
+    let [_, crates_header, using_unsafe_header, unsafe_header] =
+        MEMBER_SUMMARY_HEADERS;
+    let format_row = |cells: [&str; 4]| {
+        format!(
+            "{:<name_width$}  {:>crates_width$}  {:>using_unsafe_width$}  \
+            {:>unsafe_width$}",
+            cells[0],
+            cells[1],
+            cells[2],
+            cells[3],
+            name_width = name_width,
+            crates_width = crates_header.len(),
+            using_unsafe_width = using_unsafe_header.len(),
+            unsafe_width = unsafe_header.len()
+        )
+    };
+
+    let mut member_summary_lines = vec![format_row(MEMBER_SUMMARY_HEADERS)];
+    for row in rows {
+        let used = row.summary.used.total_unsafe();
+        let total = used + row.summary.unused.total_unsafe();
+        member_summary_lines.push(format_row([
+            &row.name,
+            &row.summary.crates_scanned.to_string(),
+            &row.summary.crates_using_unsafe.to_string(),
+            &format!("{}/{}", used, total),
+        ]));
+    }
+    member_summary_lines
+}
+
+#[cfg(test)]
+mod workspace_tests {
+    use super::*;
+
+    use crate::lib_tests::{create_path_package_id, create_safety_report};
+
+    use cargo_geiger_serde::{CounterBlock, PackageInfo, UnsafeInfo};
+    use rstest::*;
+
+    #[rstest]
+    fn create_workspace_lines_test() {
+        let mut bar_unsafety = UnsafeInfo::default();
+        bar_unsafety.used.exprs.unsafe_ = 2;
+        bar_unsafety.unused.functions.unsafe_ = 1;
+
+        let report = create_safety_report(vec![
+            (
+                PackageInfo::new(create_path_package_id("member")),
+                UnsafeInfo::default(),
+            ),
+            (
+                PackageInfo::new(create_path_package_id("bar")),
+                bar_unsafety,
+            ),
+        ]);
+
+        assert_eq!(
+            create_workspace_lines(&[String::from("member 0.1.0")], &report),
+            vec![
+                "Workspace members scanned: member 0.1.0",
+                "",
+                "Crates using unsafe code:",
+                "    bar 0.1.0: functions 0/1, expressions 2/2, impls 0/0, traits 0/0, methods 0/0",
+                "",
+                "Unsafe code used by the build/found: functions 0/1, expressions 2/2, impls 0/0, traits 0/0, methods 0/0",
+                "Crates: 2 scanned, 1 using unsafe code",
+            ]
+        );
+    }
+
+    #[rstest]
+    fn create_member_summary_lines_test() {
+        let mut used = CounterBlock::default();
+        used.methods.unsafe_ = 3;
+        let mut unused = CounterBlock::default();
+        unused.exprs.unsafe_ = 4;
+        let rows = [
+            MemberSummaryRow {
+                name: String::from("a-long-member 0.1.0"),
+                summary: ReportSummary {
+                    crates_scanned: 12,
+                    crates_using_unsafe: 2,
+                    used,
+                    unused,
+                },
+            },
+            MemberSummaryRow {
+                name: String::from("b 0.2.0"),
+                summary: ReportSummary {
+                    crates_scanned: 1,
+                    ..Default::default()
+                },
+            },
+        ];
+
+        assert_eq!(
+            create_member_summary_lines(&rows),
+            vec![
+                "Member               Crates  Using unsafe  Unsafe used/found",
+                "a-long-member 0.1.0      12             2                3/7",
+                "b 0.2.0                   1             0                0/0",
+            ]
+        );
+    }
+}
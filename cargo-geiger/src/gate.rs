@@ -0,0 +1,304 @@
+use crate::format::CrateDetectionStatus;
+
+use cargo::{CliError, CliResult};
+use cargo_geiger_serde::SafetyReport;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The exit code of gates on unsafe usage, and of configuration errors
+pub const DEFAULT_EXIT_CODE: i32 = 1;
+
+/// The exit code of the `--deny-warnings` gate, which tells an incomplete scan
+/// apart from a failure of the other gates
+pub const WARNINGS_EXIT_CODE: i32 = 2;
+
+/// The exit code of the `--deny-unsafe` gate, which tells unsafe code being
+/// found apart from the tool failing
+pub const UNSAFE_EXIT_CODE: i32 = 3;
+
+/// What the exit code tells, with `--exit-code-mode`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ExitCodeMode {
+    /// Whether the gates passed, each failed gate having its exit code
+    #[default]
+    Gates,
+    /// What the scan detected, as a `DetectionOutcome`, regardless of the
+    /// gates
+    Detection,
+}
+
+impl FromStr for ExitCodeMode {
+    type Err = ExitCodeModeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gates" => Ok(ExitCodeMode::Gates),
+            "detection" => Ok(ExitCodeMode::Detection),
+            _ => Err(ExitCodeModeParseError),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExitCodeModeParseError;
+
+impl Error for ExitCodeModeParseError {}
+
+impl fmt::Display for ExitCodeModeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "matching exit code mode not found, expected gates or detection"
+        )
+    }
+}
+
+/// The outcome of a scan over all of its crates, declared from the best to
+/// the worst
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum DetectionOutcome {
+    /// No unsafe usage, and every crate forbids unsafe code with
+    /// `#![forbid(unsafe_code)]`
+    NoneDetectedForbidsUnsafe,
+    /// No unsafe usage, but a crate allows unsafe code, or only denies it
+    NoneDetectedAllowsUnsafe,
+    /// A crate uses unsafe code in the files used by the build
+    UnsafeDetected,
+}
+
+impl DetectionOutcome {
+    /// The outcome of the worst crate of the report. The crates without
+    /// metrics aren't counted.
+    pub fn from_report(report: &SafetyReport) -> Self {
+        report
+            .packages
+            .values()
+            .map(|entry| match CrateDetectionStatus::from(&entry.unsafety) {
+                CrateDetectionStatus::NoneDetectedForbidsUnsafe => {
+                    DetectionOutcome::NoneDetectedForbidsUnsafe
+                }
+                CrateDetectionStatus::UnsafeDetected => {
+                    DetectionOutcome::UnsafeDetected
+                }
+                _ => DetectionOutcome::NoneDetectedAllowsUnsafe,
+            })
+            .max()
+            .unwrap_or(DetectionOutcome::NoneDetectedForbidsUnsafe)
+    }
+
+    /// The exit code with `--exit-code-mode detection`. Errors preventing the
+    /// scan exit with `DEFAULT_EXIT_CODE`, 1.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            DetectionOutcome::NoneDetectedForbidsUnsafe => 0,
+            DetectionOutcome::UnsafeDetected => 2,
+            DetectionOutcome::NoneDetectedAllowsUnsafe => 3,
+        }
+    }
+}
+
+pub struct FailedGate {
+    pub error: anyhow::Error,
+    pub exit_code: i32,
+}
+
+impl FailedGate {
+    pub fn new<E>(error: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        FailedGate {
+            error: anyhow::Error::new(error),
+            exit_code: DEFAULT_EXIT_CODE,
+        }
+    }
+
+    pub fn with_exit_code(self, exit_code: i32) -> Self {
+        FailedGate { exit_code, ..self }
+    }
+}
+
+/// Decide the exit code from the gates which failed during the run, which is
+/// the one of the first failed gate. With `--exit-zero` the failed gates are
+/// only reported, and the run succeeds.
+///
+/// Errors which prevent the scan itself, such as an unreadable `--baseline`,
+/// are not gates and are returned before this point.
+pub fn determine_exit_code(
+    exit_zero: bool,
+    failed_gates: Vec<FailedGate>,
+) -> CliResult {
+    let mut failed_gates = failed_gates.into_iter();
+    let first_failed_gate = match failed_gates.next() {
+        Some(failed_gate) => failed_gate,
+        None => return Ok(()),
+    };
+
+    if exit_zero {
+        for failed_gate in
+            std::iter::once(first_failed_gate).chain(failed_gates)
+        {
+            eprintln!("WARNING: Gate would have failed: {}", failed_gate.error);
+        }
+        eprintln!("Exiting with status 0, since `--exit-zero` is set");
+        return Ok(());
+    }
+
+    // Only one error is printed on exit, so print the others here.
+    for failed_gate in failed_gates {
+        eprintln!("error: {}", failed_gate.error);
+    }
+
+    Err(CliError::new(
+        first_failed_gate.error,
+        first_failed_gate.exit_code,
+    ))
+}
+
+/// Decide the exit code from what the scan detected, with `--exit-code-mode
+/// detection`. The failed gates are only reported.
+pub fn determine_detection_exit_code(
+    exit_zero: bool,
+    detection_outcome: DetectionOutcome,
+    failed_gates: Vec<FailedGate>,
+) -> CliResult {
+    for failed_gate in failed_gates {
+        eprintln!("WARNING: Gate failed: {}", failed_gate.error);
+    }
+    match detection_outcome.exit_code() {
+        0 => Ok(()),
+        _ if exit_zero => {
+            eprintln!("Exiting with status 0, since `--exit-zero` is set");
+            Ok(())
+        }
+        exit_code => Err(CliError::code(exit_code)),
+    }
+}
+
+#[cfg(test)]
+mod gate_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+    use crate::scan::FoundWarningsError;
+
+    use cargo_geiger_serde::{PackageInfo, UnsafeInfo};
+    use rstest::*;
+
+    #[rstest(
+        input_exit_zero,
+        input_failed_gate_count,
+        expected_is_ok,
+        case(false, 0, true),
+        case(true, 0, true),
+        case(false, 1, false),
+        case(false, 2, false),
+        case(true, 2, true)
+    )]
+    fn determine_exit_code_test(
+        input_exit_zero: bool,
+        input_failed_gate_count: u64,
+        expected_is_ok: bool,
+    ) {
+        let failed_gates = (0..input_failed_gate_count)
+            .map(|warning_count| {
+                FailedGate::new(FoundWarningsError { warning_count })
+            })
+            .collect();
+
+        assert_eq!(
+            determine_exit_code(input_exit_zero, failed_gates).is_ok(),
+            expected_is_ok
+        );
+    }
+
+    #[rstest]
+    fn determine_exit_code_test_uses_first_exit_code() {
+        let failed_gates = vec![
+            FailedGate::new(FoundWarningsError { warning_count: 1 })
+                .with_exit_code(WARNINGS_EXIT_CODE),
+            FailedGate::new(FoundWarningsError { warning_count: 2 }),
+        ];
+
+        let cli_error = determine_exit_code(false, failed_gates).unwrap_err();
+
+        assert_eq!(cli_error.exit_code, WARNINGS_EXIT_CODE);
+    }
+
+    #[rstest(
+        input_crates,
+        expected_detection_outcome,
+        case(vec![], DetectionOutcome::NoneDetectedForbidsUnsafe),
+        case(
+            vec![(true, false, 0), (true, false, 0)],
+            DetectionOutcome::NoneDetectedForbidsUnsafe
+        ),
+        case(
+            vec![(true, false, 0), (false, true, 0)],
+            DetectionOutcome::NoneDetectedAllowsUnsafe
+        ),
+        case(
+            vec![(true, false, 0), (false, false, 1)],
+            DetectionOutcome::UnsafeDetected
+        )
+    )]
+    fn detection_outcome_from_report_test(
+        input_crates: Vec<(bool, bool, u64)>,
+        expected_detection_outcome: DetectionOutcome,
+    ) {
+        let report = create_safety_report(
+            input_crates
+                .into_iter()
+                .enumerate()
+                .map(
+                    |(index, (forbids_unsafe, denies_unsafe, unsafe_exprs))| {
+                        let package_id =
+                            create_path_package_id(&format!("crate{}", index));
+                        let unsafety = UnsafeInfo {
+                            forbids_unsafe,
+                            denies_unsafe,
+                            ..create_unsafe_info(unsafe_exprs)
+                        };
+                        (PackageInfo::new(package_id), unsafety)
+                    },
+                )
+                .collect(),
+        );
+
+        assert_eq!(
+            DetectionOutcome::from_report(&report),
+            expected_detection_outcome
+        );
+    }
+
+    #[rstest(
+        input_detection_outcome,
+        expected_exit_code,
+        case(DetectionOutcome::NoneDetectedForbidsUnsafe, None),
+        case(DetectionOutcome::UnsafeDetected, Some(2)),
+        case(DetectionOutcome::NoneDetectedAllowsUnsafe, Some(3))
+    )]
+    fn determine_detection_exit_code_test(
+        input_detection_outcome: DetectionOutcome,
+        expected_exit_code: Option<i32>,
+    ) {
+        // The failed gates don't change the exit code.
+        let failed_gates =
+            vec![FailedGate::new(FoundWarningsError { warning_count: 1 })
+                .with_exit_code(WARNINGS_EXIT_CODE)];
+
+        assert_eq!(
+            determine_detection_exit_code(
+                false,
+                input_detection_outcome,
+                failed_gates
+            )
+            .err()
+            .map(|cli_error| cli_error.exit_code),
+            expected_exit_code
+        );
+    }
+}
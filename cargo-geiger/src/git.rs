@@ -0,0 +1,167 @@
+use crate::args::GitArgs;
+
+use cargo::core::GitReference;
+use cargo::sources::git::GitRemote;
+use cargo::util::IntoUrl;
+use cargo::{CliError, Config};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// A repository given with `--git`, checked out into a temporary directory
+/// which is removed when this is dropped
+pub struct GitCheckoutDirectory {
+    checkout_path: PathBuf,
+    revision: String,
+    // Only held to remove the directory on drop.
+    _temp_dir: TempDir,
+}
+
+impl GitCheckoutDirectory {
+    /// The manifest at the root of the checkout, which is scanned in place of
+    /// `--manifest-path`
+    pub fn manifest_path(&self) -> PathBuf {
+        self.checkout_path.join("Cargo.toml")
+    }
+
+    /// The hash of the commit checked out, whichever reference selected it
+    pub fn revision(&self) -> &str {
+        &self.revision
+    }
+}
+
+/// Clone the repository given with `--git` at the revision selected by
+/// `--rev`, `--branch` or `--tag`, or at its default branch if none is given.
+/// Returns `None` if no repository is given.
+pub fn checkout_git_repository(
+    git_args: &GitArgs,
+    config: &Config,
+) -> Result<Option<GitCheckoutDirectory>, CliError> {
+    let git_url = match &git_args.git {
+        Some(git_url) => git_url,
+        None if git_args.branch.is_none()
+            && git_args.rev.is_none()
+            && git_args.tag.is_none() =>
+        {
+            return Ok(None)
+        }
+        None => {
+            eprintln!("`--rev`, `--branch` and `--tag` require a `--git` url");
+            return Err(CliError::code(1));
+        }
+    };
+    let git_reference = git_reference(git_args)?;
+
+    let url = git_url.as_str().into_url().map_err(|e| {
+        eprintln!("Invalid git url: {}", git_url);
+        CliError::new(e, 1)
+    })?;
+
+    let temp_dir = TempDir::new().map_err(|e| {
+        eprintln!("Failed to create a directory to clone into");
+        anyhow::Error::from(e)
+    })?;
+    let database_path = temp_dir.path().join("database");
+    let checkout_path = temp_dir.path().join("checkout");
+
+    let git_remote = GitRemote::new(&url);
+    let (git_database, revision) = git_remote
+        .checkout(&database_path, None, &git_reference, None, config)
+        .map_err(|e| {
+            eprintln!(
+                "Failed to clone git repository: {} at {}",
+                git_url,
+                describe_git_reference(&git_reference)
+            );
+            CliError::new(e, 1)
+        })?;
+    git_database
+        .copy_to(revision, &checkout_path, config)
+        .map_err(|e| {
+            eprintln!("Failed to check out revision: {}", revision);
+            CliError::new(e, 1)
+        })?;
+
+    let git_checkout_directory = GitCheckoutDirectory {
+        checkout_path,
+        revision: revision.to_string(),
+        _temp_dir: temp_dir,
+    };
+    if !git_checkout_directory.manifest_path().exists() {
+        eprintln!(
+            "No Cargo.toml found at the root of git repository: {}",
+            git_url
+        );
+        return Err(CliError::code(1));
+    }
+    Ok(Some(git_checkout_directory))
+}
+
+fn describe_git_reference(git_reference: &GitReference) -> String {
+    match git_reference {
+        GitReference::Branch(branch) => format!("branch {}", branch),
+        GitReference::DefaultBranch => String::from("the default branch"),
+        GitReference::Rev(rev) => format!("rev {}", rev),
+        GitReference::Tag(tag) => format!("tag {}", tag),
+    }
+}
+
+fn git_reference(git_args: &GitArgs) -> Result<GitReference, CliError> {
+    match (&git_args.branch, &git_args.rev, &git_args.tag) {
+        (None, None, None) => Ok(GitReference::DefaultBranch),
+        (Some(branch), None, None) => Ok(GitReference::Branch(branch.clone())),
+        (None, Some(rev), None) => Ok(GitReference::Rev(rev.clone())),
+        (None, None, Some(tag)) => Ok(GitReference::Tag(tag.clone())),
+        _ => {
+            eprintln!(
+                "Only one of `--rev`, `--branch` and `--tag` may be given"
+            );
+            Err(CliError::code(1))
+        }
+    }
+}
+
+#[cfg(test)]
+mod git_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest(
+        input_git_args,
+        expected_description,
+        case(GitArgs::default(), Some("the default branch")),
+        case(
+            GitArgs {
+                rev: Some(String::from("abc123")),
+                ..Default::default()
+            },
+            Some("rev abc123")
+        ),
+        case(
+            GitArgs {
+                tag: Some(String::from("v1.0.0")),
+                ..Default::default()
+            },
+            Some("tag v1.0.0")
+        ),
+        case(
+            GitArgs {
+                branch: Some(String::from("main")),
+                tag: Some(String::from("v1.0.0")),
+                ..Default::default()
+            },
+            None
+        )
+    )]
+    fn git_reference_test(
+        input_git_args: GitArgs,
+        expected_description: Option<&str>,
+    ) {
+        assert_eq!(
+            git_reference(&input_git_args)
+                .ok()
+                .map(|r| describe_git_reference(&r)),
+            expected_description.map(String::from)
+        );
+    }
+}
@@ -20,6 +20,9 @@ use std::path::PathBuf;
 pub struct Graph {
     pub graph: petgraph::Graph<PackageId, DependencyKind>,
     pub nodes: HashMap<PackageId, NodeIndex>,
+    /// The features of each crate of the graph activated by cargo's feature
+    /// resolver, for the features given on the command line
+    pub features: HashMap<PackageId, Vec<String>>,
 }
 
 // Almost unmodified compared to the original in cargo-tree, should be fairly
@@ -31,6 +34,24 @@ pub fn build_graph<'a>(
     config_host: &'a str,
     global_rustc_path: &'a PathBuf,
     root_package_id: PackageId,
+) -> CargoResult<Graph> {
+    build_graph_from_roots(
+        args,
+        cargo_metadata_parameters,
+        config_host,
+        global_rustc_path,
+        std::slice::from_ref(&root_package_id),
+    )
+}
+
+/// Like `build_graph`, but with several roots, such as the members scanned
+/// with `--workspace`, whose shared dependencies have a single node
+pub fn build_graph_from_roots<'a>(
+    args: &Args,
+    cargo_metadata_parameters: &'a CargoMetadataParameters,
+    config_host: &'a str,
+    global_rustc_path: &'a PathBuf,
+    root_package_ids: &[PackageId],
 ) -> CargoResult<Graph> {
     let (extra_deps, target) = build_graph_prerequisites(
         config_host,
@@ -42,13 +63,16 @@ pub fn build_graph<'a>(
     let mut graph = Graph {
         graph: petgraph::Graph::new(),
         nodes: HashMap::new(),
+        features: HashMap::new(),
     };
-    graph.nodes.insert(
-        root_package_id.clone(),
-        graph.graph.add_node(root_package_id.clone()),
-    );
+    for root_package_id in root_package_ids {
+        graph.nodes.insert(
+            root_package_id.clone(),
+            graph.graph.add_node(root_package_id.clone()),
+        );
+    }
 
-    let mut pending_packages = vec![root_package_id.clone()];
+    let mut pending_packages = root_package_ids.to_vec();
 
     let graph_configuration = GraphConfiguration {
         target,
@@ -57,7 +81,7 @@ pub fn build_graph<'a>(
     };
 
     while let Some(package_id) = pending_packages.pop() {
-        let is_root_package = package_id == root_package_id;
+        let is_root_package = root_package_ids.contains(&package_id);
         add_package_dependencies_to_graph(
             cargo_metadata_parameters,
             package_id,
@@ -68,6 +92,16 @@ pub fn build_graph<'a>(
         );
     }
 
+    if let Some(resolve) = &cargo_metadata_parameters.metadata.resolve {
+        for node in &resolve.nodes {
+            if graph.nodes.contains_key(&node.id) {
+                graph
+                    .features
+                    .insert(node.id.clone(), node.features.clone());
+            }
+        }
+    }
+
     Ok(graph)
 }
 
@@ -148,7 +182,9 @@ fn build_graph_prerequisites<'a>(
     deps_args: &'a DepsArgs,
     target_args: &'a TargetArgs,
 ) -> (ExtraDeps, Option<&'a str>) {
-    let extra_deps = if deps_args.all_deps {
+    let extra_deps = if let Some(kind) = deps_args.kind {
+        kind
+    } else if deps_args.all_deps {
         ExtraDeps::All
     } else if deps_args.build_deps {
         ExtraDeps::Build
@@ -157,6 +193,10 @@ fn build_graph_prerequisites<'a>(
     } else {
         ExtraDeps::NoMore
     };
+    let extra_deps = match deps_args.no_dev_deps {
+        true => extra_deps.without_dev(),
+        false => extra_deps,
+    };
 
     let target = if target_args.all_targets {
         None
@@ -212,7 +252,8 @@ mod graph_tests {
             DepsArgs {
                 all_deps: true,
                 build_deps: false,
-                dev_deps: false
+                dev_deps: false,
+                ..Default::default()
             },
             ExtraDeps::All
         ),
@@ -220,7 +261,8 @@ mod graph_tests {
             DepsArgs {
                 all_deps: false,
                 build_deps: true,
-                dev_deps: false
+                dev_deps: false,
+                ..Default::default()
             },
             ExtraDeps::Build
         ),
@@ -228,7 +270,8 @@ mod graph_tests {
             DepsArgs {
                 all_deps: false,
                 build_deps: false,
-                dev_deps: true
+                dev_deps: true,
+                ..Default::default()
             },
             ExtraDeps::Dev
         ),
@@ -236,9 +279,26 @@ mod graph_tests {
             DepsArgs {
                 all_deps: false,
                 build_deps: false,
-                dev_deps: false
+                dev_deps: false,
+                ..Default::default()
             },
             ExtraDeps::NoMore
+        ),
+        case(
+            DepsArgs {
+                all_deps: true,
+                kind: Some(ExtraDeps::Dev),
+                ..Default::default()
+            },
+            ExtraDeps::Dev
+        ),
+        case(
+            DepsArgs {
+                all_deps: true,
+                no_dev_deps: true,
+                ..Default::default()
+            },
+            ExtraDeps::Build
         )
     )]
     fn build_graph_prerequisites_extra_deps_test(
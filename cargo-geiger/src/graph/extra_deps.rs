@@ -1,6 +1,9 @@
 use cargo_metadata::DependencyKind;
 
-#[derive(Debug, Eq, PartialEq)]
+/// The dependencies followed when building the graph besides the normal ones,
+/// set with `--kind` or `--build-dependencies`, `--dev-dependencies` and
+/// `--all-dependencies`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ExtraDeps {
     All,
     Build,
@@ -20,7 +23,43 @@ impl ExtraDeps {
             _ => false,
         }
     }
+
+    /// The same dependencies without the dev dependencies, for
+    /// `--no-dev-deps`
+    pub fn without_dev(self) -> Self {
+        match self {
+            ExtraDeps::All => ExtraDeps::Build,
+            ExtraDeps::Dev => ExtraDeps::NoMore,
+            extra_deps => extra_deps,
+        }
+    }
+}
+
+impl std::str::FromStr for ExtraDeps {
+    type Err = ExtraDepsParseError;
+    fn from_str(s: &str) -> Result<Self, ExtraDepsParseError> {
+        match s {
+            "all" => Ok(Self::All),
+            "build" => Ok(Self::Build),
+            "dev" => Ok(Self::Dev),
+            "normal" => Ok(Self::NoMore),
+            _ => Err(ExtraDepsParseError),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExtraDepsParseError;
+impl std::fmt::Display for ExtraDepsParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "matching dependency kind not found, expected normal, dev, build \
+            or all"
+        )
+    }
 }
+impl std::error::Error for ExtraDepsParseError {}
 
 #[cfg(test)]
 mod extra_deps_tests {
@@ -52,4 +91,38 @@ mod extra_deps_tests {
             expected_allows
         );
     }
+
+    #[rstest(
+        input_raw_str,
+        expected_extra_deps_result,
+        case("all", Ok(ExtraDeps::All)),
+        case("build", Ok(ExtraDeps::Build)),
+        case("dev", Ok(ExtraDeps::Dev)),
+        case("normal", Ok(ExtraDeps::NoMore)),
+        case("proc-macro", Err(ExtraDepsParseError))
+    )]
+    fn extra_deps_from_str_test(
+        input_raw_str: &str,
+        expected_extra_deps_result: Result<ExtraDeps, ExtraDepsParseError>,
+    ) {
+        assert_eq!(
+            input_raw_str.parse::<ExtraDeps>(),
+            expected_extra_deps_result
+        );
+    }
+
+    #[rstest(
+        input_extra_deps,
+        expected_extra_deps,
+        case(ExtraDeps::All, ExtraDeps::Build),
+        case(ExtraDeps::Build, ExtraDeps::Build),
+        case(ExtraDeps::Dev, ExtraDeps::NoMore),
+        case(ExtraDeps::NoMore, ExtraDeps::NoMore)
+    )]
+    fn extra_deps_without_dev_test(
+        input_extra_deps: ExtraDeps,
+        expected_extra_deps: ExtraDeps,
+    ) {
+        assert_eq!(input_extra_deps.without_dev(), expected_extra_deps);
+    }
 }
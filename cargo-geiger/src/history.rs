@@ -0,0 +1,151 @@
+use crate::baseline::read_baseline;
+
+use cargo::CliError;
+use cargo_geiger_serde::{CounterBlock, SafetyReport};
+use cargo_metadata::semver::Version;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Printed in place of a version, when the history doesn't tell when the
+/// unsafe usage of a crate appeared
+pub const UNKNOWN_VERSION: &str = "unknown";
+
+/// The used unsafe code of each known version of each crate, collected from
+/// reports previously written with `--output-format Json`
+#[derive(Debug, Default)]
+pub struct UnsafeHistory {
+    crate_versions: HashMap<String, BTreeMap<Version, CounterBlock>>,
+}
+
+impl UnsafeHistory {
+    pub fn from_reports(reports: &[SafetyReport]) -> Self {
+        let mut unsafe_history = UnsafeHistory::default();
+        for report in reports {
+            for entry in report.packages.values() {
+                unsafe_history
+                    .crate_versions
+                    .entry(entry.package.id.name.clone())
+                    .or_default()
+                    .insert(
+                        entry.package.id.version.clone(),
+                        entry.unsafety.used.clone(),
+                    );
+            }
+        }
+        unsafe_history
+    }
+
+    /// The earliest version of the crate with the same used unsafe code as
+    /// the scanned version, without a version in between that differs. This
+    /// is `None` if the history holds no earlier version of the crate.
+    pub fn unsafe_introduced_at(
+        &self,
+        name: &str,
+        version: &Version,
+        used: &CounterBlock,
+    ) -> Option<Version> {
+        let versions = self.crate_versions.get(name)?;
+
+        let mut introduced_at = None;
+        for (history_version, history_used) in
+            versions.range(..version.clone()).rev()
+        {
+            if unsafe_counts(history_used) != unsafe_counts(used) {
+                break;
+            }
+            introduced_at = Some(history_version.clone());
+        }
+
+        // With no earlier version, or only earlier versions with different
+        // counts, the unsafe usage appeared at the first known version after
+        // those, which may be the scanned one.
+        match introduced_at {
+            Some(introduced_at) => Some(introduced_at),
+            None if versions.range(..version.clone()).next().is_some() => {
+                Some(version.clone())
+            }
+            None => None,
+        }
+    }
+}
+
+/// Read the reports given with `--history`, or `None` if no report is given
+pub fn read_history(
+    history_paths: &[PathBuf],
+) -> Result<Option<UnsafeHistory>, CliError> {
+    if history_paths.is_empty() {
+        return Ok(None);
+    }
+    let reports = history_paths
+        .iter()
+        .map(|history_path| read_baseline(history_path))
+        .collect::<Result<Vec<SafetyReport>, CliError>>()?;
+    Ok(Some(UnsafeHistory::from_reports(&reports)))
+}
+
+fn unsafe_counts(counter_block: &CounterBlock) -> [u64; 5] {
+    [
+        counter_block.functions.unsafe_,
+        counter_block.exprs.unsafe_,
+        counter_block.item_impls.unsafe_,
+        counter_block.item_traits.unsafe_,
+        counter_block.methods.unsafe_,
+    ]
+}
+
+#[cfg(test)]
+mod history_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_registry_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::PackageInfo;
+    use rstest::*;
+
+    #[rstest(
+        input_history,
+        input_version,
+        input_unsafe_exprs,
+        expected_introduced_at,
+        case(vec![], "1.0.0", 2, None),
+        case(vec![("0.9.0", 2), ("0.8.0", 2), ("0.7.0", 1)], "1.0.0", 2, Some("0.8.0")),
+        case(vec![("0.9.0", 1)], "1.0.0", 2, Some("1.0.0")),
+        case(vec![("0.9.0", 2), ("0.8.0", 1), ("0.7.0", 2)], "1.0.0", 2, Some("0.9.0")),
+        case(vec![("1.1.0", 2)], "1.0.0", 2, None)
+    )]
+    fn unsafe_introduced_at_test(
+        input_history: Vec<(&str, u64)>,
+        input_version: &str,
+        input_unsafe_exprs: u64,
+        expected_introduced_at: Option<&str>,
+    ) {
+        let reports = input_history
+            .iter()
+            .map(|(version, unsafe_exprs)| {
+                create_safety_report(vec![(
+                    PackageInfo::new(create_registry_package_id(
+                        "foo", version,
+                    )),
+                    create_unsafe_info(*unsafe_exprs),
+                )])
+            })
+            .collect::<Vec<SafetyReport>>();
+        let unsafe_history = UnsafeHistory::from_reports(&reports);
+
+        assert_eq!(
+            unsafe_history.unsafe_introduced_at(
+                "foo",
+                &Version::parse(input_version).unwrap(),
+                &create_unsafe_info(input_unsafe_exprs).used,
+            ),
+            expected_introduced_at.map(|v| Version::parse(v).unwrap())
+        );
+    }
+
+    #[rstest]
+    fn read_history_test_no_paths() {
+        assert!(read_history(&[]).unwrap().is_none());
+    }
+}
@@ -0,0 +1,82 @@
+use glob::{MatchOptions, Pattern};
+use std::path::Path;
+
+/// A `*` doesn't match a `/`, so `src/*.rs` leaves the files of the
+/// subdirectories of `src` in, unlike `src/**/*.rs`.
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// The crates and files given with `--ignore` and `--ignore-path`, which are
+/// left out of the scan. An ignored crate is still in the tree, marked as
+/// skipped, but none of its unsafe code is counted.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IgnoreList {
+    pub crate_names: Vec<String>,
+    pub path_patterns: Vec<Pattern>,
+}
+
+impl IgnoreList {
+    pub fn is_ignored_crate(&self, name: &str) -> bool {
+        self.crate_names.iter().any(|crate_name| crate_name == name)
+    }
+
+    /// Whether the path of the file relative to the root of its package
+    /// matches any of the patterns, such as `vendor/**` or
+    /// `src/generated/*.rs`
+    pub fn is_ignored_path(&self, package_root: &Path, path: &Path) -> bool {
+        let relative_path = match path.strip_prefix(package_root) {
+            Ok(relative_path) => relative_path,
+            Err(_) => return false,
+        };
+        self.path_patterns.iter().any(|path_pattern| {
+            path_pattern.matches_path_with(relative_path, MATCH_OPTIONS)
+        })
+    }
+}
+
+#[cfg(test)]
+mod ignore_tests {
+    use super::*;
+
+    use rstest::*;
+    use std::str::FromStr;
+
+    #[rstest]
+    fn is_ignored_crate_test() {
+        let ignore_list = IgnoreList {
+            crate_names: vec![String::from("libc")],
+            ..Default::default()
+        };
+
+        assert!(ignore_list.is_ignored_crate("libc"));
+        assert!(!ignore_list.is_ignored_crate("libc-print"));
+    }
+
+    #[rstest(
+        input_path,
+        expected_is_ignored,
+        case("/foo/vendor/bar/src/lib.rs", true),
+        case("/foo/src/generated/bindings.rs", true),
+        case("/foo/src/generated/nested/bindings.rs", false),
+        case("/foo/src/lib.rs", false),
+        case("/vendor/src/lib.rs", false)
+    )]
+    fn is_ignored_path_test(input_path: &str, expected_is_ignored: bool) {
+        let ignore_list = IgnoreList {
+            path_patterns: vec![
+                Pattern::from_str("vendor/**").unwrap(),
+                Pattern::from_str("src/generated/*.rs").unwrap(),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ignore_list
+                .is_ignored_path(Path::new("/foo"), Path::new(input_path)),
+            expected_is_ignored
+        );
+    }
+}
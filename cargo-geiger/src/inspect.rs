@@ -0,0 +1,304 @@
+use crate::explain::CrateSpec;
+use crate::scan::PackageMetrics;
+
+use cargo_metadata::Package;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InspectArgsError;
+
+impl Error for InspectArgsError {}
+
+impl fmt::Display for InspectArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected `inspect <CRATE>`, with the crate as `<name>`, \
+            `<name>@<version>` or `<name>:<version>`"
+        )
+    }
+}
+
+/// The unsafe code of a file of the inspected crate
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InspectedFile {
+    /// Relative to the root of the package
+    pub path: PathBuf,
+    pub unsafe_count: u64,
+    pub used_by_build: bool,
+}
+
+/// The crate of the positional arguments of the `inspect` subcommand, or
+/// `None` if they name another subcommand
+pub fn parse_inspect_args(
+    positional_args: &[String],
+) -> Result<Option<CrateSpec>, InspectArgsError> {
+    match positional_args {
+        [subcommand, crate_spec] if subcommand == "inspect" => {
+            crate_spec.parse().map(Some).map_err(|_| InspectArgsError)
+        }
+        [subcommand, ..] if subcommand == "inspect" => Err(InspectArgsError),
+        _ => Ok(None),
+    }
+}
+
+/// The unsafe code of each module of the crate, the modules with the most
+/// unsafe code first, so that it's told whether it is concentrated in a few
+/// of them or spread across the crate
+pub fn construct_inspect_lines(
+    package: &Package,
+    package_metrics: &PackageMetrics,
+    rs_files_used: &HashSet<PathBuf>,
+) -> Vec<String> {
+    let package_root = package
+        .manifest_path
+        .parent()
+        .and_then(|root| root.canonicalize().ok());
+    let inspected_files = package_metrics
+        .rs_path_to_metrics
+        .iter()
+        .map(|(path, rs_file_metrics_wrapper)| InspectedFile {
+            path: package_root
+                .as_ref()
+                .and_then(|root| path.strip_prefix(root).ok())
+                .unwrap_or(path)
+                .to_path_buf(),
+            unsafe_count: rs_file_metrics_wrapper
+                .metrics
+                .counters
+                .total_unsafe(),
+            used_by_build: rs_files_used.contains(path),
+        })
+        .collect::<Vec<_>>();
+    format_inspect_lines(
+        &format!("{} {}", package.name, package.version),
+        inspected_files,
+    )
+}
+
+fn format_inspect_lines(
+    crate_name: &str,
+    mut inspected_files: Vec<InspectedFile>,
+) -> Vec<String> {
+    inspected_files.sort_by(|a, b| {
+        b.unsafe_count
+            .cmp(&a.unsafe_count)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    let found = inspected_files
+        .iter()
+        .map(|inspected_file| inspected_file.unsafe_count)
+        .sum::<u64>();
+    let used = inspected_files
+        .iter()
+        .filter(|inspected_file| inspected_file.used_by_build)
+        .map(|inspected_file| inspected_file.unsafe_count)
+        .sum::<u64>();
+
+    let mut lines = vec![
+        format!(
+            "Unsafe code of {}, per module, used by the build/found: {}/{}",
+            crate_name, used, found
+        ),
+        String::new(),
+    ];
+    let (unsafe_files, safe_files): (Vec<_>, Vec<_>) = inspected_files
+        .into_iter()
+        .partition(|inspected_file| inspected_file.unsafe_count > 0);
+    if unsafe_files.is_empty() {
+        lines.push(String::from("No module uses unsafe code."));
+        lines.push(String::new());
+        return lines;
+    }
+
+    let modules = unsafe_files
+        .iter()
+        .map(|inspected_file| {
+            module_path(&inspected_file.path).unwrap_or_else(|| "-".into())
+        })
+        .collect::<Vec<_>>();
+    let module_width = modules
+        .iter()
+        .map(String::len)
+        .chain(Some("Module".len()))
+        .max()
+        .unwrap_or_default();
+    lines.push(format!(
+        "{:>6}  {:>5}  {:<module_width$}  File",
+        "Unsafe",
+        "Share",
+        "Module",
+        module_width = module_width
+    ));
+    for (inspected_file, module) in unsafe_files.iter().zip(modules) {
+        lines.push(format!(
+            "{:>6}  {:>4}%  {:<module_width$}  {}{}",
+            inspected_file.unsafe_count,
+            inspected_file.unsafe_count * 100 / found,
+            module,
+            inspected_file.path.display(),
+            if inspected_file.used_by_build {
+                ""
+            } else {
+                " (not used by the build)"
+            },
+            module_width = module_width
+        ));
+    }
+    if !safe_files.is_empty() {
+        lines.push(String::new());
+        lines.push(match safe_files.len() {
+            1 => String::from("1 other file uses no unsafe code."),
+            count => format!("{} other files use no unsafe code.", count),
+        });
+    }
+    lines.push(String::new());
+    lines
+}
+
+/// The module of a file under `src`, such as `crate::io::driver` for
+/// `src/io/driver/mod.rs`, going by the layout of the files rather than by
+/// the `mod` items. Other files, such as build scripts and tests, are `None`.
+fn module_path(relative_path: &Path) -> Option<String> {
+    let mut components = relative_path.with_extension("");
+    if components.file_name().is_some_and(|name| name == "mod") {
+        components.pop();
+    }
+    let mut segments =
+        components.components().map(|component| match component {
+            Component::Normal(segment) => Some(segment.to_string_lossy()),
+            _ => None,
+        });
+    if segments.next()?.as_deref() != Some("src") {
+        return None;
+    }
+    let mut module = vec![String::from("crate")];
+    for segment in segments {
+        module.push(segment?.into_owned());
+    }
+    if module.len() == 2 && (module[1] == "lib" || module[1] == "main") {
+        module.truncate(1);
+    }
+    Some(module.join("::"))
+}
+
+#[cfg(test)]
+mod inspect_tests {
+    use super::*;
+
+    use cargo_metadata::semver::Version;
+    use rstest::*;
+
+    #[rstest(
+        input_positional_args,
+        expected_crate_spec,
+        case(vec![], Ok(None)),
+        case(vec!["badge"], Ok(None)),
+        case(
+            vec!["inspect", "tokio"],
+            Ok(Some(CrateSpec { name: String::from("tokio"), version: None }))
+        ),
+        case(
+            vec!["inspect", "tokio@1.20.0"],
+            Ok(Some(CrateSpec {
+                name: String::from("tokio"),
+                version: Some(Version::new(1, 20, 0))
+            }))
+        ),
+        case(vec!["inspect"], Err(InspectArgsError)),
+        case(vec!["inspect", "tokio", "mio"], Err(InspectArgsError)),
+        case(vec!["inspect", "tokio@one"], Err(InspectArgsError))
+    )]
+    fn parse_inspect_args_test(
+        input_positional_args: Vec<&str>,
+        expected_crate_spec: Result<Option<CrateSpec>, InspectArgsError>,
+    ) {
+        let positional_args = input_positional_args
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        assert_eq!(parse_inspect_args(&positional_args), expected_crate_spec);
+    }
+
+    #[rstest(
+        input_path,
+        expected_module,
+        case("src/lib.rs", Some("crate")),
+        case("src/main.rs", Some("crate")),
+        case("src/io.rs", Some("crate::io")),
+        case("src/io/driver/mod.rs", Some("crate::io::driver")),
+        case("src/io/driver/tcp.rs", Some("crate::io::driver::tcp")),
+        case("build.rs", None),
+        case("tests/io.rs", None)
+    )]
+    fn module_path_test(input_path: &str, expected_module: Option<&str>) {
+        assert_eq!(
+            module_path(Path::new(input_path)).as_deref(),
+            expected_module
+        );
+    }
+
+    #[rstest]
+    fn format_inspect_lines_test() {
+        let inspected_files = vec![
+            InspectedFile {
+                path: PathBuf::from("src/lib.rs"),
+                unsafe_count: 1,
+                used_by_build: true,
+            },
+            InspectedFile {
+                path: PathBuf::from("src/io/driver/mod.rs"),
+                unsafe_count: 3,
+                used_by_build: true,
+            },
+            InspectedFile {
+                path: PathBuf::from("src/util.rs"),
+                unsafe_count: 0,
+                used_by_build: true,
+            },
+            InspectedFile {
+                path: PathBuf::from("build.rs"),
+                unsafe_count: 1,
+                used_by_build: false,
+            },
+        ];
+
+        assert_eq!(
+            format_inspect_lines("foo 0.1.0", inspected_files),
+            vec![
+                "Unsafe code of foo 0.1.0, per module, used by the build/found: 4/5",
+                "",
+                "Unsafe  Share  Module             File",
+                "     3    60%  crate::io::driver  src/io/driver/mod.rs",
+                "     1    20%  -                  build.rs (not used by the build)",
+                "     1    20%  crate              src/lib.rs",
+                "",
+                "1 other file uses no unsafe code.",
+                "",
+            ]
+        );
+    }
+
+    #[rstest]
+    fn format_inspect_lines_test_no_unsafe() {
+        let inspected_files = vec![InspectedFile {
+            path: PathBuf::from("src/lib.rs"),
+            unsafe_count: 0,
+            used_by_build: true,
+        }];
+
+        assert_eq!(
+            format_inspect_lines("foo 0.1.0", inspected_files),
+            vec![
+                "Unsafe code of foo 0.1.0, per module, used by the build/found: 0/0",
+                "",
+                "No module uses unsafe code.",
+                "",
+            ]
+        );
+    }
+}
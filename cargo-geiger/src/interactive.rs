@@ -0,0 +1,659 @@
+use crate::format::print_config::{colorize, PrintConfig};
+use crate::format::CrateDetectionStatus;
+
+use cargo_metadata::semver::Version;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// The commands of the prompt, also printed with `h`
+const INTERACTIVE_HELP: [&str; 9] = [
+    "Commands:",
+    "    e <ROW>                 Expand the crate to show its dependencies",
+    "    c <ROW>                 Collapse the crate",
+    "    d <ROW>                 Print the files of the crate using unsafe code",
+    "    s <name|used|found>     Sort the dependencies of each crate",
+    "    f [TEXT]                Only show the crates whose name contains the",
+    "                            text and the paths to them, or all without it",
+    "    <empty line>            Print the tree again",
+    "    q                       Quit",
+];
+
+/// A crate of the tree explored with `--interactive`
+pub struct InteractiveCrate {
+    pub name: String,
+    pub version: Version,
+    /// The status of the crate, with its unsafe code used by the build and
+    /// found in total, or `None` if it wasn't scanned
+    pub unsafe_counts: Option<(CrateDetectionStatus, u64, u64)>,
+    /// The files of the crate using unsafe code, ordered by path
+    pub unsafe_files: Vec<UnsafeFile>,
+}
+
+impl InteractiveCrate {
+    fn used(&self) -> u64 {
+        self.unsafe_counts.as_ref().map_or(0, |(_, used, _)| *used)
+    }
+
+    fn found(&self) -> u64 {
+        self.unsafe_counts
+            .as_ref()
+            .map_or(0, |(_, _, found)| *found)
+    }
+}
+
+/// A file using unsafe code, by its path relative to the root of its package
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnsafeFile {
+    pub path: PathBuf,
+    pub unsafe_count: u64,
+    pub used_by_build: bool,
+}
+
+/// The order of the dependencies of each crate of the tree
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InteractiveSort {
+    Name,
+    /// Most unsafe code used by the build first
+    Used,
+    /// Most unsafe code found first
+    Found,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InteractiveCommand {
+    Collapse(usize),
+    Detail(usize),
+    Expand(usize),
+    /// `None` shows all crates again
+    Filter(Option<String>),
+    Help,
+    Quit,
+    Show,
+    Sort(InteractiveSort),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InteractiveCommandParseError {
+    command: String,
+}
+
+impl Error for InteractiveCommandParseError {}
+
+impl fmt::Display for InteractiveCommandParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown command: {}, type `h` for help", self.command)
+    }
+}
+
+impl FromStr for InteractiveCommand {
+    type Err = InteractiveCommandParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_error = || InteractiveCommandParseError {
+            command: s.trim().to_string(),
+        };
+        let (command, argument) = match s.trim().split_once(' ') {
+            Some((command, argument)) => (command, Some(argument.trim())),
+            None => (s.trim(), None),
+        };
+        let row = || {
+            argument
+                .and_then(|argument| argument.parse::<usize>().ok())
+                .ok_or_else(parse_error)
+        };
+        match command {
+            "" => Ok(InteractiveCommand::Show),
+            "c" | "collapse" => Ok(InteractiveCommand::Collapse(row()?)),
+            "d" | "detail" => Ok(InteractiveCommand::Detail(row()?)),
+            "e" | "expand" => Ok(InteractiveCommand::Expand(row()?)),
+            "f" | "filter" => {
+                Ok(InteractiveCommand::Filter(argument.map(String::from)))
+            }
+            "h" | "help" => Ok(InteractiveCommand::Help),
+            "q" | "quit" => Ok(InteractiveCommand::Quit),
+            "s" | "sort" => match argument {
+                Some("name") => {
+                    Ok(InteractiveCommand::Sort(InteractiveSort::Name))
+                }
+                Some("used") => {
+                    Ok(InteractiveCommand::Sort(InteractiveSort::Used))
+                }
+                Some("found") => {
+                    Ok(InteractiveCommand::Sort(InteractiveSort::Found))
+                }
+                _ => Err(parse_error()),
+            },
+            _ => Err(parse_error()),
+        }
+    }
+}
+
+/// A numbered row of the printed tree
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TreeRow {
+    pub crate_index: usize,
+    pub depth: usize,
+    pub has_dependencies: bool,
+    pub expanded: bool,
+}
+
+/// The dependency tree explored with `--interactive`, with the crates which
+/// are expanded, the order of the dependencies, and the filter by name. A
+/// crate is expanded wherever it is in the tree.
+pub struct InteractiveTree {
+    crates: Vec<InteractiveCrate>,
+    /// The indices of the dependencies of each crate
+    dependencies: Vec<Vec<usize>>,
+    root_index: usize,
+    expanded: HashSet<usize>,
+    filter: Option<String>,
+    sort: InteractiveSort,
+}
+
+impl InteractiveTree {
+    /// Only the root is expanded at first
+    pub fn new(
+        crates: Vec<InteractiveCrate>,
+        dependencies: Vec<Vec<usize>>,
+        root_index: usize,
+    ) -> Self {
+        InteractiveTree {
+            crates,
+            dependencies,
+            root_index,
+            expanded: HashSet::from([root_index]),
+            filter: None,
+            sort: InteractiveSort::Name,
+        }
+    }
+
+    /// The rows of the tree as printed. A crate is not expanded below itself.
+    /// With a filter, the crates leading to a match are expanded, and the
+    /// others are left out.
+    pub fn visible_rows(&self) -> Vec<TreeRow> {
+        let matches = self.filter.as_ref().map(|filter| self.matches(filter));
+        let mut rows = vec![];
+        let mut path = vec![];
+        self.push_rows(self.root_index, 0, &matches, &mut path, &mut rows);
+        rows
+    }
+
+    /// Run the command, and return the lines to print. The rows are numbered
+    /// from 1, as printed by the last command.
+    pub fn apply(
+        &mut self,
+        command: InteractiveCommand,
+        print_config: &PrintConfig,
+    ) -> Vec<String> {
+        let rows = self.visible_rows();
+        let row = |row_number: usize| {
+            row_number
+                .checked_sub(1)
+                .and_then(|index| rows.get(index))
+                .ok_or_else(|| vec![format!("No row {}", row_number)])
+        };
+        let result =
+            match command {
+                InteractiveCommand::Collapse(row_number) => row(row_number)
+                    .map(|row| {
+                        self.expanded.remove(&row.crate_index);
+                        self.render(print_config)
+                    }),
+                InteractiveCommand::Detail(row_number) => row(row_number)
+                    .map(|row| self.detail_lines(row.crate_index)),
+                InteractiveCommand::Expand(row_number) => {
+                    row(row_number).map(|row| {
+                        self.expanded.insert(row.crate_index);
+                        self.render(print_config)
+                    })
+                }
+                InteractiveCommand::Filter(filter) => {
+                    self.filter = filter;
+                    Ok(self.render(print_config))
+                }
+                InteractiveCommand::Help => Ok(INTERACTIVE_HELP
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect()),
+                InteractiveCommand::Quit => Ok(vec![]),
+                InteractiveCommand::Show => Ok(self.render(print_config)),
+                InteractiveCommand::Sort(sort) => {
+                    self.sort = sort;
+                    Ok(self.render(print_config))
+                }
+            };
+        result.unwrap_or_else(|error_lines| error_lines)
+    }
+
+    /// A line for each visible row, below a line with the order and filter
+    pub fn render(&self, print_config: &PrintConfig) -> Vec<String> {
+        let sort = match self.sort {
+            InteractiveSort::Name => "name",
+            InteractiveSort::Used => "unsafe code used by the build",
+            InteractiveSort::Found => "unsafe code found",
+        };
+        let mut lines = vec![match &self.filter {
+            Some(filter) => {
+                format!("Sorted by {}, filtered by: {}", sort, filter)
+            }
+            None => format!("Sorted by {}", sort),
+        }];
+        for (index, row) in self.visible_rows().iter().enumerate() {
+            let marker = match (row.has_dependencies, row.expanded) {
+                (false, _) => "   ",
+                (true, false) => "[+]",
+                (true, true) => "[-]",
+            };
+            lines.push(format!(
+                "{: >4} {}{} {}",
+                index + 1,
+                "  ".repeat(row.depth),
+                marker,
+                self.format_crate(row.crate_index, print_config)
+            ));
+        }
+        lines
+    }
+
+    /// The unsafe code of the crate, and its files using unsafe code
+    pub fn detail_lines(&self, crate_index: usize) -> Vec<String> {
+        let interactive_crate = &self.crates[crate_index];
+        let mut lines = vec![match &interactive_crate.unsafe_counts {
+            Some((_, used, found)) => format!(
+                "{} {}: {}/{} unsafe code used by the build/found",
+                interactive_crate.name, interactive_crate.version, used, found
+            ),
+            None => format!(
+                "{} {}: not scanned",
+                interactive_crate.name, interactive_crate.version
+            ),
+        }];
+        lines.push(String::from("    Files using unsafe code:"));
+        if interactive_crate.unsafe_files.is_empty() {
+            lines.push(String::from("        None"));
+        }
+        for unsafe_file in &interactive_crate.unsafe_files {
+            lines.push(format!(
+                "        {}: {}{}",
+                unsafe_file.path.display(),
+                unsafe_file.unsafe_count,
+                if unsafe_file.used_by_build {
+                    " (used by the build)"
+                } else {
+                    ""
+                }
+            ));
+        }
+        lines
+    }
+
+    fn push_rows(
+        &self,
+        crate_index: usize,
+        depth: usize,
+        matches: &Option<Vec<bool>>,
+        path: &mut Vec<usize>,
+        rows: &mut Vec<TreeRow>,
+    ) {
+        let mut dependencies = self.dependencies[crate_index]
+            .iter()
+            .copied()
+            .filter(|dependency_index| !path.contains(dependency_index))
+            .filter(|dependency_index| {
+                matches
+                    .as_ref()
+                    .is_none_or(|matches| matches[*dependency_index])
+            })
+            .collect::<Vec<_>>();
+        self.sort_crates(&mut dependencies);
+
+        let expanded = !dependencies.is_empty()
+            && (matches.is_some() || self.expanded.contains(&crate_index));
+        rows.push(TreeRow {
+            crate_index,
+            depth,
+            has_dependencies: !dependencies.is_empty(),
+            expanded,
+        });
+        if !expanded {
+            return;
+        }
+        path.push(crate_index);
+        for dependency_index in dependencies {
+            self.push_rows(dependency_index, depth + 1, matches, path, rows);
+        }
+        path.pop();
+    }
+
+    /// Whether each crate, or one of its dependencies, has a name containing
+    /// the filter
+    fn matches(&self, filter: &str) -> Vec<bool> {
+        let mut matches = self
+            .crates
+            .iter()
+            .map(|interactive_crate| interactive_crate.name.contains(filter))
+            .collect::<Vec<_>>();
+        // Spread to the dependents until nothing changes. Only development
+        // dependencies of the root are in the tree, so a cycle always goes
+        // through the root, which is never printed below itself.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (crate_index, dependencies) in
+                self.dependencies.iter().enumerate()
+            {
+                if !matches[crate_index]
+                    && dependencies.iter().any(|index| {
+                        *index != self.root_index && matches[*index]
+                    })
+                {
+                    matches[crate_index] = true;
+                    changed = true;
+                }
+            }
+        }
+        matches
+    }
+
+    fn sort_crates(&self, crate_indices: &mut [usize]) {
+        crate_indices.sort_by(|a, b| {
+            let (a, b) = (&self.crates[*a], &self.crates[*b]);
+            let by_name = a.name.cmp(&b.name).then(a.version.cmp(&b.version));
+            match self.sort {
+                InteractiveSort::Name => by_name,
+                InteractiveSort::Used => b.used().cmp(&a.used()).then(by_name),
+                InteractiveSort::Found => {
+                    b.found().cmp(&a.found()).then(by_name)
+                }
+            }
+        });
+    }
+
+    fn format_crate(
+        &self,
+        crate_index: usize,
+        print_config: &PrintConfig,
+    ) -> String {
+        let interactive_crate = &self.crates[crate_index];
+        match &interactive_crate.unsafe_counts {
+            Some((crate_detection_status, used, found)) => colorize(
+                print_config.color,
+                print_config.color_scheme,
+                crate_detection_status,
+                print_config.output_format,
+                format!(
+                    "{} {} ({}/{})",
+                    interactive_crate.name,
+                    interactive_crate.version,
+                    used,
+                    found
+                ),
+            )
+            .to_string(),
+            None => format!(
+                "{} {} (?)",
+                interactive_crate.name, interactive_crate.version
+            ),
+        }
+    }
+}
+
+/// Print the tree, and run the commands read from the input until `q` or the
+/// end of the input
+pub fn run_interactive<R: BufRead, W: Write>(
+    interactive_tree: &mut InteractiveTree,
+    print_config: &PrintConfig,
+    input: R,
+    mut output: W,
+) -> io::Result<()> {
+    for line in interactive_tree.render(print_config) {
+        writeln!(output, "{}", line)?;
+    }
+    writeln!(output, "Type `h` for help.")?;
+    write!(output, "> ")?;
+    output.flush()?;
+
+    for input_line in input.lines() {
+        let lines = match input_line?.parse::<InteractiveCommand>() {
+            Ok(InteractiveCommand::Quit) => return Ok(()),
+            Ok(command) => interactive_tree.apply(command, print_config),
+            Err(e) => vec![e.to_string()],
+        };
+        for line in lines {
+            writeln!(output, "{}", line)?;
+        }
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+    writeln!(output)
+}
+
+#[cfg(test)]
+mod interactive_tests {
+    use super::*;
+
+    use crate::format::print_config::ColorWhen;
+
+    use rstest::*;
+
+    fn interactive_crate(
+        name: &str,
+        unsafe_counts: Option<(u64, u64)>,
+    ) -> InteractiveCrate {
+        InteractiveCrate {
+            name: String::from(name),
+            version: Version::new(0, 1, 0),
+            unsafe_counts: unsafe_counts.map(|(used, found)| {
+                (CrateDetectionStatus::UnsafeDetected, used, found)
+            }),
+            unsafe_files: vec![],
+        }
+    }
+
+    /// root -> a -> c, root -> b -> c -> root
+    fn create_interactive_tree() -> InteractiveTree {
+        InteractiveTree::new(
+            vec![
+                interactive_crate("root", Some((0, 0))),
+                interactive_crate("b", Some((1, 1))),
+                interactive_crate("a", Some((0, 4))),
+                interactive_crate("c", None),
+            ],
+            vec![vec![1, 2], vec![3], vec![3], vec![0]],
+            0,
+        )
+    }
+
+    fn print_config() -> PrintConfig {
+        PrintConfig {
+            color: ColorWhen::Never,
+            ..Default::default()
+        }
+    }
+
+    #[rstest(
+        input_command,
+        expected_command,
+        case("", Ok(InteractiveCommand::Show)),
+        case("e 3", Ok(InteractiveCommand::Expand(3))),
+        case(" collapse 2 ", Ok(InteractiveCommand::Collapse(2))),
+        case("d 1", Ok(InteractiveCommand::Detail(1))),
+        case("f tok", Ok(InteractiveCommand::Filter(Some(String::from("tok"))))),
+        case("f", Ok(InteractiveCommand::Filter(None))),
+        case("s used", Ok(InteractiveCommand::Sort(InteractiveSort::Used))),
+        case("q", Ok(InteractiveCommand::Quit)),
+        case("s size", Err(InteractiveCommandParseError { command: String::from("s size") })),
+        case("e", Err(InteractiveCommandParseError { command: String::from("e") })),
+        case("x 1", Err(InteractiveCommandParseError { command: String::from("x 1") }))
+    )]
+    fn interactive_command_from_str_test(
+        input_command: &str,
+        expected_command: Result<
+            InteractiveCommand,
+            InteractiveCommandParseError,
+        >,
+    ) {
+        assert_eq!(
+            input_command.parse::<InteractiveCommand>(),
+            expected_command
+        );
+    }
+
+    #[rstest]
+    fn render_test() {
+        let mut interactive_tree = create_interactive_tree();
+
+        assert_eq!(
+            interactive_tree.render(&print_config()),
+            vec![
+                "Sorted by name",
+                "   1 [-] root 0.1.0 (0/0)",
+                "   2   [+] a 0.1.0 (0/4)",
+                "   3   [+] b 0.1.0 (1/1)",
+            ]
+        );
+
+        interactive_tree.apply(InteractiveCommand::Expand(2), &print_config());
+        interactive_tree.apply(InteractiveCommand::Expand(3), &print_config());
+
+        // The root isn't expanded below itself.
+        assert_eq!(
+            interactive_tree.render(&print_config()),
+            vec![
+                "Sorted by name",
+                "   1 [-] root 0.1.0 (0/0)",
+                "   2   [-] a 0.1.0 (0/4)",
+                "   3         c 0.1.0 (?)",
+                "   4   [+] b 0.1.0 (1/1)",
+            ]
+        );
+    }
+
+    #[rstest(
+        input_commands,
+        expected_rows,
+        case(vec![], vec![(0, 0), (2, 1), (1, 1)]),
+        case(
+            vec![InteractiveCommand::Sort(InteractiveSort::Used)],
+            vec![(0, 0), (1, 1), (2, 1)]
+        ),
+        case(
+            vec![InteractiveCommand::Sort(InteractiveSort::Found)],
+            vec![(0, 0), (2, 1), (1, 1)]
+        ),
+        case(vec![InteractiveCommand::Collapse(1)], vec![(0, 0)]),
+        case(
+            vec![InteractiveCommand::Expand(3), InteractiveCommand::Expand(3)],
+            vec![(0, 0), (2, 1), (1, 1), (3, 2)]
+        ),
+        case(
+            vec![InteractiveCommand::Filter(Some(String::from("c")))],
+            vec![(0, 0), (2, 1), (3, 2), (1, 1), (3, 2)]
+        ),
+        case(
+            vec![InteractiveCommand::Filter(Some(String::from("b")))],
+            vec![(0, 0), (1, 1)]
+        ),
+        case(
+            vec![
+                InteractiveCommand::Filter(Some(String::from("b"))),
+                InteractiveCommand::Filter(None)
+            ],
+            vec![(0, 0), (2, 1), (1, 1)]
+        )
+    )]
+    fn visible_rows_test(
+        input_commands: Vec<InteractiveCommand>,
+        expected_rows: Vec<(usize, usize)>,
+    ) {
+        let mut interactive_tree = create_interactive_tree();
+        for command in input_commands {
+            interactive_tree.apply(command, &print_config());
+        }
+
+        assert_eq!(
+            interactive_tree
+                .visible_rows()
+                .iter()
+                .map(|row| (row.crate_index, row.depth))
+                .collect::<Vec<_>>(),
+            expected_rows
+        );
+    }
+
+    #[rstest]
+    fn apply_test_no_row() {
+        let mut interactive_tree = create_interactive_tree();
+
+        assert_eq!(
+            interactive_tree
+                .apply(InteractiveCommand::Detail(9), &print_config()),
+            vec!["No row 9"]
+        );
+        assert_eq!(
+            interactive_tree
+                .apply(InteractiveCommand::Expand(0), &print_config()),
+            vec!["No row 0"]
+        );
+    }
+
+    #[rstest]
+    fn detail_lines_test() {
+        let mut interactive_tree = create_interactive_tree();
+        interactive_tree.crates[2].unsafe_files = vec![
+            UnsafeFile {
+                path: PathBuf::from("src/lib.rs"),
+                unsafe_count: 3,
+                used_by_build: false,
+            },
+            UnsafeFile {
+                path: PathBuf::from("src/sys.rs"),
+                unsafe_count: 1,
+                used_by_build: true,
+            },
+        ];
+
+        assert_eq!(
+            interactive_tree.detail_lines(2),
+            vec![
+                "a 0.1.0: 0/4 unsafe code used by the build/found",
+                "    Files using unsafe code:",
+                "        src/lib.rs: 3",
+                "        src/sys.rs: 1 (used by the build)",
+            ]
+        );
+        assert_eq!(
+            interactive_tree.detail_lines(3),
+            vec![
+                "c 0.1.0: not scanned",
+                "    Files using unsafe code:",
+                "        None",
+            ]
+        );
+    }
+
+    #[rstest]
+    fn run_interactive_test() {
+        let mut interactive_tree = create_interactive_tree();
+        let mut output = Vec::new();
+
+        run_interactive(
+            &mut interactive_tree,
+            &print_config(),
+            "c 1\nz\nq\ne 1\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Sorted by name\n   1 [-] root 0.1.0 (0/0)\n   2   [+] a 0.1.0 (0/4)\n   \
+            3   [+] b 0.1.0 (1/1)\nType `h` for help.\n> Sorted by name\n   \
+            1 [+] root 0.1.0 (0/0)\n> Unknown command: z, type `h` for help\n> "
+        );
+    }
+}
@@ -7,18 +7,104 @@
 #![forbid(unsafe_code)]
 #![deny(warnings)]
 
+/// Known vulnerabilities of crates from the `RustSec` advisory database
+pub mod advisory;
 /// Argument parsing
+/// Crates which are audited and treated as not using unsafe code
+pub mod allowlist;
+/// Scanning a workspace from other tools, without the command line
+pub mod api;
 pub mod args;
+/// Manual reviews of crates, from an audits file or from cargo-vet
+pub mod audits;
+/// The SVG badge of the status of the scan written with `badge`
+pub mod badge;
+/// Comparison of scan results against a previously saved report
+pub mod baseline;
+/// Writing of self-contained audit bundles
+pub mod bundle;
+/// Verification of crate sources against expected checksums
+pub mod checksum;
 /// Bootstrapping functions for structs required by the CLI
 pub mod cli;
+/// Comparison of two versions of a crate of crates.io with `compare`
+pub mod compare;
+/// Shell completions and the man page generated from the help text
+pub mod completions;
+/// Defaults for the command line arguments read from `geiger.toml`
+pub mod config_file;
+/// Comparison of the unsafe usage found by two scans
+pub mod diff;
+/// A digest of the scan result for change detection
+pub mod digest;
+/// Lookup of crate download counts from a snapshot
+pub mod downloads;
+/// Detection of crates present at more than one version
+pub mod duplicates;
+/// Scanning of the macro-expanded code of crates, with `--expand`
+pub mod expand;
+/// Breakdown of how the status of a single crate was determined
+pub mod explain;
+/// Worst case unsafe usage across combinations of features
+pub mod feature_matrix;
+/// Crates and unsafe code left out by disabling the active features
+pub mod feature_report;
+/// Determination of the exit code from the gates which failed
+pub mod gate;
+/// Checkout of git repositories to be scanned
+pub mod git;
 /// Construction of the dependency graph
 pub mod graph;
+/// Lookup of the versions in which the unsafe usage of crates appeared
+pub mod history;
+/// Crates and files left out of the scan
+pub mod ignore;
+/// Breakdown of the unsafe code of a single crate by module, with `inspect`
+pub mod inspect;
+/// Exploration of the dependency tree at a prompt with `--interactive`
+pub mod interactive;
+/// Matching of crate licenses against SPDX style license expressions
+pub mod license;
+/// Per crate and total limits of the used unsafe code of each category
+pub mod limits;
+/// The unsafe code accepted for each crate, recorded in `geiger.lock`
+pub mod lockfile;
+/// Records of the scanned crates for the system log
+pub mod log_sink;
 /// Mapping functionality from `cargo::core` to `cargo_metadata`
 pub mod mapping;
+/// Attribution of the unsafe usage of implementation crates to their facades
+pub mod merge;
+/// Ordering of the flat output by a list of crate names
+pub mod order;
+/// Paging of human readable output
+pub mod pager;
+/// Comparison of the unsafe density of crates to a snapshot of popular crates
+pub mod percentiles;
+/// Tiers of unsafe categories for the category policy gate
+pub mod policy;
 /// Interaction with README.md files
 pub mod readme;
+/// Download of crates.io packages to be scanned
+pub mod registry;
 /// Functions for scanning projects for unsafe code
 pub mod scan;
+/// The log of the scan summaries recorded over time with `history`
+pub mod scan_log;
+/// Weighted scoring of unsafe usage
+pub mod score;
+/// Lookup of the security policies published by crates
+pub mod security_policy;
+/// Kinds of source of crates, for `--only-source` and `--group-by source`
+pub mod source_kind;
+/// Scanning of crates from local directories in place of their source
+pub mod source_map;
+/// Duplication of the printed report into a file
+pub mod tee;
+/// Polling of the files of the workspace members for `--watch`
+pub mod watch;
+/// Dependency paths from the root to a single crate
+pub mod why;
 
 /// Inner display formatting
 mod format;
@@ -27,9 +113,14 @@ mod tree;
 
 #[cfg(test)]
 mod lib_tests {
+    use cargo_geiger_serde::{
+        PackageId, PackageInfo, ReportEntry, SafetyReport, Source, UnsafeInfo,
+    };
     use cargo_metadata::{CargoOpt, Metadata, MetadataCommand};
     use krates::Builder as KratesBuilder;
     use krates::Krates;
+    use semver::Version;
+    use url::Url;
 
     pub fn construct_krates_and_metadata() -> (Krates, Metadata) {
         let metadata = MetadataCommand::new()
@@ -44,4 +135,48 @@ mod lib_tests {
 
         (krates, metadata)
     }
+
+    /// The id of a crate at 0.1.0 in the directory `/<name>`
+    pub fn create_path_package_id(name: &str) -> PackageId {
+        PackageId {
+            name: String::from(name),
+            version: Version::new(0, 1, 0),
+            source: Source::Path(
+                Url::parse(&format!("file:///{}", name)).unwrap(),
+            ),
+        }
+    }
+
+    /// The id of a crate of crates.io
+    pub fn create_registry_package_id(name: &str, version: &str) -> PackageId {
+        PackageId {
+            name: String::from(name),
+            version: Version::parse(version).unwrap(),
+            source: Source::Registry {
+                name: String::from("crates.io"),
+                url: Url::parse("https://github.com/rust-lang/crates.io-index")
+                    .unwrap(),
+            },
+        }
+    }
+
+    /// The unsafe usage of a crate using only unsafe expressions
+    pub fn create_unsafe_info(used_unsafe_exprs: u64) -> UnsafeInfo {
+        let mut unsafe_info = UnsafeInfo::default();
+        unsafe_info.used.exprs.unsafe_ = used_unsafe_exprs;
+        unsafe_info
+    }
+
+    /// A report of the given crates and their unsafe usage
+    pub fn create_safety_report(
+        crates: Vec<(PackageInfo, UnsafeInfo)>,
+    ) -> SafetyReport {
+        let mut safety_report = SafetyReport::default();
+        for (package, unsafety) in crates {
+            safety_report
+                .packages
+                .insert(package.id.clone(), ReportEntry { package, unsafety });
+        }
+        safety_report
+    }
 }
@@ -0,0 +1,397 @@
+//! A subset of SPDX license expressions, used by `--license-filter` to select
+//! crates by their license. Supported are:
+//!
+//! - License identifiers, such as `MIT` or `GPL-3.0-only`, compared ignoring
+//!   case. In a filter, a trailing `*` matches any identifier with that
+//!   prefix, so `GPL-*` matches both `GPL-2.0` and `GPL-3.0-or-later`.
+//! - The `AND` and `OR` operators, where `AND` binds tighter than `OR`.
+//! - Parentheses for grouping.
+//! - `WITH <exception>`, where the exception is accepted but ignored.
+//!
+//! The `/` separator, still used by some crates in place of `OR`, is accepted
+//! in crate licenses. A filter identifier matches a crate if it matches any of
+//! the identifiers in the crate's license expression.
+
+use crate::graph::Graph;
+use crate::mapping::{CargoMetadataParameters, GetPackageIdInformation};
+
+use cargo_metadata::PackageId;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LicenseExpression {
+    And(Box<LicenseExpression>, Box<LicenseExpression>),
+    License(String),
+    Or(Box<LicenseExpression>, Box<LicenseExpression>),
+}
+
+impl LicenseExpression {
+    /// Check whether a crate with the given license identifiers is selected
+    /// by this expression
+    pub fn matches(&self, license_ids: &[String]) -> bool {
+        match self {
+            LicenseExpression::And(left, right) => {
+                left.matches(license_ids) && right.matches(license_ids)
+            }
+            LicenseExpression::License(filter_id) => license_ids
+                .iter()
+                .any(|license_id| license_id_matches(filter_id, license_id)),
+            LicenseExpression::Or(left, right) => {
+                left.matches(license_ids) || right.matches(license_ids)
+            }
+        }
+    }
+
+    /// All license identifiers referenced by this expression
+    pub fn license_ids(&self) -> Vec<String> {
+        match self {
+            LicenseExpression::And(left, right)
+            | LicenseExpression::Or(left, right) => {
+                let mut license_ids = left.license_ids();
+                license_ids.extend(right.license_ids());
+                license_ids
+            }
+            LicenseExpression::License(license_id) => {
+                vec![license_id.clone()]
+            }
+        }
+    }
+}
+
+impl FromStr for LicenseExpression {
+    type Err = LicenseExpressionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            position: 0,
+            tokens: &tokens,
+        };
+        let expression = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(expression),
+            Some(token) => Err(LicenseExpressionParseError {
+                message: format!("unexpected `{}`", token),
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LicenseExpressionParseError {
+    pub message: String,
+}
+
+impl Error for LicenseExpressionParseError {}
+
+impl fmt::Display for LicenseExpressionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to parse license expression: {}", self.message)
+    }
+}
+
+/// Check whether the package should be part of the report. A package with a
+/// missing or unparseable license is kept, since it can't be ruled out, and
+/// is warned about by `print_license_filter_warnings`.
+pub fn package_matches_license_filter(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    license_filter: &Option<LicenseExpression>,
+    package_id: &PackageId,
+) -> bool {
+    match license_filter {
+        Some(license_filter) => license_filter_match(
+            cargo_metadata_parameters,
+            license_filter,
+            package_id,
+        )
+        .unwrap_or(true),
+        None => true,
+    }
+}
+
+/// Print a warning for each package of the graph whose license can't be
+/// checked against `--license-filter`. A package appears several times in
+/// the tree, so this is done once per scan rather than for each of its lines.
+pub fn print_license_filter_warnings(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    license_filter: &Option<LicenseExpression>,
+    graph: &Graph,
+) {
+    let license_filter = match license_filter {
+        Some(license_filter) => license_filter,
+        None => return,
+    };
+    let mut package_ids = graph.graph.node_weights().collect::<Vec<_>>();
+    package_ids.sort();
+    package_ids.dedup();
+    for package_id in package_ids {
+        if let Err(warning) = license_filter_match(
+            cargo_metadata_parameters,
+            license_filter,
+            package_id,
+        ) {
+            eprintln!("WARNING: {}", warning);
+        }
+    }
+}
+
+/// Whether the license of the package matches the filter, or the warning to
+/// print if it has no license or it can't be parsed
+fn license_filter_match(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    license_filter: &LicenseExpression,
+    package_id: &PackageId,
+) -> Result<bool, String> {
+    let license = package_id
+        .get_package_id_licence(cargo_metadata_parameters.krates)
+        .ok_or_else(|| {
+            format!(
+                "No license found for package: {}, including it in the report",
+                package_id
+            )
+        })?;
+
+    let license_expression = parse_crate_license(&license).map_err(|e| {
+        format!(
+            "{}, for package: {}, including it in the report",
+            e, package_id
+        )
+    })?;
+    Ok(license_filter.matches(&license_expression.license_ids()))
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    And,
+    CloseParenthesis,
+    License(String),
+    OpenParenthesis,
+    Or,
+    With,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::And => write!(f, "AND"),
+            Token::CloseParenthesis => write!(f, ")"),
+            Token::License(license_id) => write!(f, "{}", license_id),
+            Token::OpenParenthesis => write!(f, "("),
+            Token::Or => write!(f, "OR"),
+            Token::With => write!(f, "WITH"),
+        }
+    }
+}
+
+struct Parser<'a> {
+    position: usize,
+    tokens: &'a [Token],
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn parse_or(
+        &mut self,
+    ) -> Result<LicenseExpression, LicenseExpressionParseError> {
+        let mut expression = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            expression = LicenseExpression::Or(
+                Box::new(expression),
+                Box::new(self.parse_and()?),
+            );
+        }
+        Ok(expression)
+    }
+
+    fn parse_and(
+        &mut self,
+    ) -> Result<LicenseExpression, LicenseExpressionParseError> {
+        let mut expression = self.parse_primary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            expression = LicenseExpression::And(
+                Box::new(expression),
+                Box::new(self.parse_primary()?),
+            );
+        }
+        Ok(expression)
+    }
+
+    fn parse_primary(
+        &mut self,
+    ) -> Result<LicenseExpression, LicenseExpressionParseError> {
+        match self.next() {
+            Some(Token::OpenParenthesis) => {
+                let expression = self.parse_or()?;
+                match self.next() {
+                    Some(Token::CloseParenthesis) => Ok(expression),
+                    _ => Err(LicenseExpressionParseError {
+                        message: String::from("missing `)`"),
+                    }),
+                }
+            }
+            Some(Token::License(license_id)) => {
+                if self.peek() == Some(&Token::With) {
+                    self.next();
+                    match self.next() {
+                        Some(Token::License(_)) => {}
+                        _ => {
+                            return Err(LicenseExpressionParseError {
+                                message: String::from(
+                                    "expected an exception after `WITH`",
+                                ),
+                            })
+                        }
+                    }
+                }
+                Ok(LicenseExpression::License(license_id.clone()))
+            }
+            Some(token) => Err(LicenseExpressionParseError {
+                message: format!("unexpected `{}`", token),
+            }),
+            None => Err(LicenseExpressionParseError {
+                message: String::from("unexpected end of expression"),
+            }),
+        }
+    }
+}
+
+fn license_id_matches(filter_id: &str, license_id: &str) -> bool {
+    let filter_id = filter_id.to_lowercase();
+    let license_id = license_id.to_lowercase();
+    match filter_id.strip_suffix('*') {
+        Some(prefix) => license_id.starts_with(prefix),
+        None => filter_id == license_id,
+    }
+}
+
+fn parse_crate_license(
+    license: &str,
+) -> Result<LicenseExpression, LicenseExpressionParseError> {
+    license.replace('/', " OR ").parse()
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, LicenseExpressionParseError> {
+    let mut tokens = vec![];
+    let spaced = s.replace('(', " ( ").replace(')', " ) ");
+    for word in spaced.split_whitespace() {
+        let token = match word {
+            "(" => Token::OpenParenthesis,
+            ")" => Token::CloseParenthesis,
+            _ if word.eq_ignore_ascii_case("AND") => Token::And,
+            _ if word.eq_ignore_ascii_case("OR") => Token::Or,
+            _ if word.eq_ignore_ascii_case("WITH") => Token::With,
+            _ if word
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-.+:*".contains(c)) =>
+            {
+                Token::License(String::from(word))
+            }
+            _ => {
+                return Err(LicenseExpressionParseError {
+                    message: format!("invalid license identifier `{}`", word),
+                })
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod license_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest(
+        input_license_filter,
+        input_license,
+        expected_matches,
+        case("MIT", "MIT", true),
+        case("mit", "MIT", true),
+        case("MIT", "Apache-2.0", false),
+        case("MIT", "MIT OR Apache-2.0", true),
+        case("MIT", "Apache-2.0/MIT", true),
+        case("GPL-*", "GPL-3.0-or-later", true),
+        case("GPL-*", "LGPL-2.1", false),
+        case("GPL-* OR LGPL-*", "LGPL-2.1", true),
+        case("MIT AND Apache-2.0", "MIT OR Apache-2.0", true),
+        case("MIT AND Zlib", "MIT OR Apache-2.0", false),
+        case("(MIT OR Zlib) AND Apache-2.0", "Apache-2.0 AND Zlib", true),
+        case(
+            "GPL-2.0-only",
+            "GPL-2.0-only WITH Classpath-exception-2.0",
+            true
+        )
+    )]
+    fn license_expression_matches_test(
+        input_license_filter: &str,
+        input_license: &str,
+        expected_matches: bool,
+    ) {
+        let license_filter =
+            LicenseExpression::from_str(input_license_filter).unwrap();
+        let license_ids =
+            parse_crate_license(input_license).unwrap().license_ids();
+
+        assert_eq!(license_filter.matches(&license_ids), expected_matches);
+    }
+
+    #[rstest(
+        input_license_expression,
+        expected_expression,
+        case(
+            "MIT OR Apache-2.0 AND Zlib",
+            LicenseExpression::Or(
+                Box::new(LicenseExpression::License(String::from("MIT"))),
+                Box::new(LicenseExpression::And(
+                    Box::new(LicenseExpression::License(String::from(
+                        "Apache-2.0"
+                    ))),
+                    Box::new(LicenseExpression::License(String::from(
+                        "Zlib"
+                    )))
+                ))
+            )
+        ),
+        case("(MIT)", LicenseExpression::License(String::from("MIT")))
+    )]
+    fn license_expression_from_str_test(
+        input_license_expression: &str,
+        expected_expression: LicenseExpression,
+    ) {
+        assert_eq!(
+            LicenseExpression::from_str(input_license_expression),
+            Ok(expected_expression)
+        );
+    }
+
+    #[rstest(
+        input_license_expression,
+        case(""),
+        case("MIT OR"),
+        case("(MIT"),
+        case("MIT)"),
+        case("MIT Apache-2.0"),
+        case("MIT WITH"),
+        case("MIT, Apache-2.0")
+    )]
+    fn license_expression_from_str_test_invalid(
+        input_license_expression: &str,
+    ) {
+        assert!(LicenseExpression::from_str(input_license_expression).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+use crate::format::table::UNSAFE_CATEGORY_NAMES;
+
+use cargo_geiger_serde::CounterBlock;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub struct UnsafeLimitError {
+    pub violation_count: u64,
+}
+
+impl Error for UnsafeLimitError {}
+
+impl fmt::Display for UnsafeLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Found {} crates or totals with unsafe usage above the limits",
+            self.violation_count
+        )
+    }
+}
+
+/// The maximum used unsafe count of each category, the categories without a
+/// maximum being unlimited
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct UnsafeLimits {
+    pub functions: Option<u64>,
+    pub exprs: Option<u64>,
+    pub item_impls: Option<u64>,
+    pub item_traits: Option<u64>,
+    pub methods: Option<u64>,
+}
+
+impl UnsafeLimits {
+    /// The categories with used unsafe counts above their maximum, such as
+    /// `functions 3 > 0, impls 2 > 1`
+    fn exceeded_limits(&self, used: &CounterBlock) -> String {
+        let counts = [
+            (self.functions, used.functions.unsafe_),
+            (self.exprs, used.exprs.unsafe_),
+            (self.item_impls, used.item_impls.unsafe_),
+            (self.item_traits, used.item_traits.unsafe_),
+            (self.methods, used.methods.unsafe_),
+        ];
+        UNSAFE_CATEGORY_NAMES
+            .iter()
+            .zip(counts)
+            .filter_map(|(name, (limit, count))| {
+                limit
+                    .filter(|limit| count > *limit)
+                    .map(|limit| format!("{} {} > {}", name, count, limit))
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+impl FromStr for UnsafeLimits {
+    type Err = UnsafeLimitsParseError;
+
+    /// Parse a comma separated list of `<category>=<max>`, such as
+    /// `functions=0,expressions=10`. Categories which are not listed are
+    /// unlimited.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut unsafe_limits = UnsafeLimits::default();
+        for category_limit in s.split(',') {
+            let parse_error = UnsafeLimitsParseError {
+                category_limit: category_limit.to_string(),
+            };
+            let (category, limit) = match category_limit.split_once('=') {
+                Some((category, limit)) => (category, limit),
+                None => return Err(parse_error),
+            };
+            let limit = match limit.trim().parse::<u64>() {
+                Ok(limit) => limit,
+                Err(_) => return Err(parse_error),
+            };
+            let target = match category.trim() {
+                "functions" => &mut unsafe_limits.functions,
+                "expressions" => &mut unsafe_limits.exprs,
+                "impls" => &mut unsafe_limits.item_impls,
+                "traits" => &mut unsafe_limits.item_traits,
+                "methods" => &mut unsafe_limits.methods,
+                _ => return Err(parse_error),
+            };
+            *target = Some(limit);
+        }
+        Ok(unsafe_limits)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct UnsafeLimitsParseError {
+    pub category_limit: String,
+}
+
+impl Error for UnsafeLimitsParseError {}
+
+impl fmt::Display for UnsafeLimitsParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse unsafe limit `{}`, expected `<category>=<max>` with a category of {} and a non-negative integer maximum",
+            self.category_limit,
+            UNSAFE_CATEGORY_NAMES.join(", ")
+        )
+    }
+}
+
+/// A line for each crate with unsafe usage above `crate_limits`, ordered by
+/// name, followed by a line for the unsafe usage of all crates added together
+/// if it is above `total_limits`. A crate in several rows is counted once in
+/// the total.
+pub fn find_unsafe_limit_violations(
+    crate_limits: Option<&UnsafeLimits>,
+    total_limits: Option<&UnsafeLimits>,
+    rows: &[(String, CounterBlock)],
+) -> Vec<String> {
+    let unique_rows = rows
+        .iter()
+        .map(|(name, used)| (name.as_str(), used))
+        .collect::<BTreeMap<&str, &CounterBlock>>();
+
+    let mut violation_lines = vec![];
+    if let Some(crate_limits) = crate_limits {
+        violation_lines.extend(unique_rows.iter().filter_map(
+            |(name, used)| {
+                let exceeded_limits = crate_limits.exceeded_limits(used);
+                (!exceeded_limits.is_empty())
+                    .then(|| format!("    {}: {}", name, exceeded_limits))
+            },
+        ));
+    }
+    if let Some(total_limits) = total_limits {
+        let total_used = unique_rows
+            .values()
+            .fold(CounterBlock::default(), |total_used, used| {
+                total_used + (*used).clone()
+            });
+        let exceeded_limits = total_limits.exceeded_limits(&total_used);
+        if !exceeded_limits.is_empty() {
+            violation_lines.push(format!("    total: {}", exceeded_limits));
+        }
+    }
+    violation_lines
+}
+
+#[cfg(test)]
+mod limits_tests {
+    use super::*;
+
+    use cargo_geiger_serde::Count;
+    use rstest::*;
+
+    #[rstest]
+    fn unsafe_limits_from_str_test() {
+        assert_eq!(
+            UnsafeLimits::from_str("functions=0, impls = 3"),
+            Ok(UnsafeLimits {
+                functions: Some(0),
+                item_impls: Some(3),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[rstest(
+        input_unsafe_limits,
+        case("functions"),
+        case("exprs=1"),
+        case("methods=-1"),
+        case("methods=0.5"),
+        case("")
+    )]
+    fn unsafe_limits_from_str_test_invalid(input_unsafe_limits: &str) {
+        assert!(UnsafeLimits::from_str(input_unsafe_limits).is_err());
+    }
+
+    #[rstest(
+        input_crate_limits,
+        input_total_limits,
+        expected_violation_lines,
+        case(
+            Some("expressions=1,impls=4"),
+            None,
+            vec![String::from("    foo 0.1.0: expressions 2 > 1")]
+        ),
+        case(
+            None,
+            Some("expressions=2"),
+            vec![String::from("    total: expressions 3 > 2")]
+        ),
+        case(Some("expressions=2"), Some("expressions=5"), vec![])
+    )]
+    fn find_unsafe_limit_violations_test(
+        input_crate_limits: Option<&str>,
+        input_total_limits: Option<&str>,
+        expected_violation_lines: Vec<String>,
+    ) {
+        let count = |unsafe_| Count { safe: 0, unsafe_ };
+        let create_counter_block = |exprs| CounterBlock {
+            exprs: count(exprs),
+            item_impls: count(4),
+            ..Default::default()
+        };
+        // The repeated crate is counted once in the total.
+        let rows = vec![
+            (String::from("foo 0.1.0"), create_counter_block(2)),
+            (String::from("bar 0.1.0"), create_counter_block(1)),
+            (String::from("foo 0.1.0"), create_counter_block(2)),
+        ];
+        let crate_limits = input_crate_limits
+            .map(|crate_limits| UnsafeLimits::from_str(crate_limits).unwrap());
+        let total_limits = input_total_limits
+            .map(|total_limits| UnsafeLimits::from_str(total_limits).unwrap());
+
+        assert_eq!(
+            find_unsafe_limit_violations(
+                crate_limits.as_ref(),
+                total_limits.as_ref(),
+                &rows
+            ),
+            expected_violation_lines
+        );
+    }
+}
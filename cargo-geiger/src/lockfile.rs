@@ -0,0 +1,431 @@
+use crate::format::table::UNSAFE_CATEGORY_NAMES;
+
+use cargo::CliError;
+use cargo_geiger_serde::{CounterBlock, SafetyReport};
+use cargo_metadata::semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// The lockfile written by `--write-lockfile` and read by
+/// `--verify-lockfile`, unless `--lockfile` is given
+pub const DEFAULT_LOCKFILE_PATH: &str = "geiger.lock";
+
+const LOCKFILE_HEADER: &str = "\
+# The unsafe code accepted for each crate, written by `cargo geiger --write-lockfile`.
+# `cargo geiger --verify-lockfile` fails if a crate uses more unsafe code than recorded here.
+";
+
+#[derive(Debug)]
+pub struct LockfileDriftError {
+    pub crate_count: usize,
+}
+
+impl Error for LockfileDriftError {}
+
+impl fmt::Display for LockfileDriftError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Found {} crates using more unsafe code than recorded in the lockfile",
+            self.crate_count
+        )
+    }
+}
+
+/// The accepted unsafe code used by the build of a crate, for each category
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LockedCrate {
+    pub name: String,
+    pub version: Version,
+    pub functions: u64,
+    pub expressions: u64,
+    pub impls: u64,
+    pub traits: u64,
+    pub methods: u64,
+}
+
+impl LockedCrate {
+    fn counts(&self) -> [u64; 5] {
+        [
+            self.functions,
+            self.expressions,
+            self.impls,
+            self.traits,
+            self.methods,
+        ]
+    }
+}
+
+/// The crates of the lockfile, sorted by name and version
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "crate")]
+    pub crates: Vec<LockedCrate>,
+}
+
+impl Lockfile {
+    pub fn from_report(report: &SafetyReport) -> Self {
+        let mut crates = report
+            .packages
+            .values()
+            .map(|entry| {
+                let [functions, expressions, impls, traits, methods] =
+                    unsafe_counts(&entry.unsafety.used);
+                LockedCrate {
+                    name: entry.package.id.name.clone(),
+                    version: entry.package.id.version.clone(),
+                    functions,
+                    expressions,
+                    impls,
+                    traits,
+                    methods,
+                }
+            })
+            .collect::<Vec<_>>();
+        crates
+            .sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+        crates.dedup();
+        Lockfile { crates }
+    }
+}
+
+/// A crate of the scan using more unsafe code in a category than recorded
+/// in the lockfile. A crate missing from the lockfile is only a drift if it
+/// uses any unsafe code.
+#[derive(Debug, Eq, PartialEq)]
+pub struct LockfileDrift {
+    pub name: String,
+    pub version: Version,
+    pub locked: Option<[u64; 5]>,
+    pub current: [u64; 5],
+}
+
+/// Match the crates of the report with the lockfile by name and version, and
+/// list the ones exceeding the recorded counts, sorted by name and version
+pub fn find_lockfile_drifts(
+    lockfile: &Lockfile,
+    report: &SafetyReport,
+) -> Vec<LockfileDrift> {
+    let locked_counts = lockfile
+        .crates
+        .iter()
+        .map(|locked_crate| {
+            (
+                (locked_crate.name.as_str(), &locked_crate.version),
+                locked_crate.counts(),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let mut drifts = report
+        .packages
+        .values()
+        .filter_map(|entry| {
+            let name = &entry.package.id.name;
+            let version = &entry.package.id.version;
+            let current = unsafe_counts(&entry.unsafety.used);
+            let locked = locked_counts.get(&(name.as_str(), version)).copied();
+            let exceeds = current
+                .iter()
+                .zip(locked.unwrap_or_default())
+                .any(|(current, locked)| *current > locked);
+            exceeds.then(|| LockfileDrift {
+                name: name.clone(),
+                version: version.clone(),
+                locked,
+                current,
+            })
+        })
+        .collect::<Vec<_>>();
+    drifts.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    drifts.dedup();
+    drifts
+}
+
+/// Render the drifts with a line per crate naming the categories over the
+/// recorded count, or every category used for a crate missing from the
+/// lockfile
+pub fn construct_lockfile_drift_lines(
+    lockfile_label: &str,
+    drifts: &[LockfileDrift],
+) -> Vec<String> {
+    if drifts.is_empty() {
+        return vec![format!(
+            "No crate uses more unsafe code than recorded in {}.",
+            lockfile_label
+        )];
+    }
+
+    let mut drift_lines = vec![
+        format!("Unsafe usage exceeding {}:", lockfile_label),
+        String::new(),
+    ];
+    for drift in drifts {
+        let line = match drift.locked {
+            Some(locked) => {
+                let categories = UNSAFE_CATEGORY_NAMES
+                    .iter()
+                    .zip(drift.current)
+                    .zip(locked)
+                    .filter(|((_, current), locked)| current > locked)
+                    .map(|((category, current), locked)| {
+                        format!("{} {} -> {}", category, locked, current)
+                    })
+                    .collect::<Vec<_>>();
+                format!(
+                    "{} {}: {}",
+                    drift.name,
+                    drift.version,
+                    categories.join(", ")
+                )
+            }
+            None => {
+                let categories = UNSAFE_CATEGORY_NAMES
+                    .iter()
+                    .zip(drift.current)
+                    .filter(|(_, current)| *current > 0)
+                    .map(|(category, current)| {
+                        format!("{} {}", category, current)
+                    })
+                    .collect::<Vec<_>>();
+                format!(
+                    "{} {} (not in {}): {}",
+                    drift.name,
+                    drift.version,
+                    lockfile_label,
+                    categories.join(", ")
+                )
+            }
+        };
+        drift_lines.push(line);
+    }
+    drift_lines.push(String::new());
+    drift_lines.push(String::from(
+        "Run `cargo geiger --write-lockfile` to accept the current unsafe usage.",
+    ));
+    drift_lines
+}
+
+pub fn read_lockfile(lockfile_path: &Path) -> Result<Lockfile, CliError> {
+    let lockfile_content =
+        fs::read_to_string(lockfile_path).map_err(|e| {
+            eprintln!(
+                "Failed to read lockfile from file: {}, write it with `--write-lockfile`",
+                lockfile_path.display()
+            );
+            CliError::new(anyhow::Error::from(e), 1)
+        })?;
+    toml_edit::easy::from_str::<Lockfile>(&lockfile_content).map_err(|e| {
+        eprintln!("Failed to parse lockfile: {}", lockfile_path.display());
+        CliError::new(anyhow::Error::from(e), 1)
+    })
+}
+
+pub fn write_lockfile(
+    lockfile_path: &Path,
+    lockfile: &Lockfile,
+) -> Result<(), CliError> {
+    let mut document = toml_edit::ser::to_document(lockfile)
+        .map_err(|e| CliError::new(anyhow::Error::from(e), 1))?;
+    // Written as a `[[crate]]` table for each crate, rather than an inline
+    // array, so that the changes to the checked in file are one per line.
+    if let Some(crates) = document.remove("crate") {
+        match crates.into_array_of_tables() {
+            Ok(crates) => {
+                document
+                    .insert("crate", toml_edit::Item::ArrayOfTables(crates));
+            }
+            Err(crates) => {
+                document.insert("crate", crates);
+            }
+        }
+    }
+    let lockfile_content = document.to_string();
+    fs::write(
+        lockfile_path,
+        format!("{}\n{}", LOCKFILE_HEADER, lockfile_content),
+    )
+    .map_err(|e| {
+        eprintln!(
+            "Failed to write lockfile to file: {}",
+            lockfile_path.display()
+        );
+        CliError::new(anyhow::Error::from(e), 1)
+    })
+}
+
+fn unsafe_counts(counter_block: &CounterBlock) -> [u64; 5] {
+    [
+        counter_block.functions.unsafe_,
+        counter_block.exprs.unsafe_,
+        counter_block.item_impls.unsafe_,
+        counter_block.item_traits.unsafe_,
+        counter_block.methods.unsafe_,
+    ]
+}
+
+#[cfg(test)]
+mod lockfile_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::{PackageId, PackageInfo};
+    use rstest::*;
+
+    /// A report with a crate for each of the name, version and used unsafe
+    /// expressions and methods
+    fn create_report(crates: &[(&str, &str, u64, u64)]) -> SafetyReport {
+        create_safety_report(
+            crates
+                .iter()
+                .map(|(name, version, exprs, methods)| {
+                    let package_id = PackageId {
+                        version: Version::parse(version).unwrap(),
+                        ..create_path_package_id(name)
+                    };
+                    let mut unsafety = create_unsafe_info(*exprs);
+                    unsafety.used.methods.unsafe_ = *methods;
+                    (PackageInfo::new(package_id), unsafety)
+                })
+                .collect(),
+        )
+    }
+
+    fn create_locked_crate(
+        name: &str,
+        version: &str,
+        expressions: u64,
+        methods: u64,
+    ) -> LockedCrate {
+        LockedCrate {
+            name: name.to_string(),
+            version: Version::parse(version).unwrap(),
+            functions: 0,
+            expressions,
+            impls: 0,
+            traits: 0,
+            methods,
+        }
+    }
+
+    #[rstest]
+    fn lockfile_from_report_test() {
+        let report =
+            create_report(&[("foo", "1.0.0", 3, 1), ("bar", "0.2.0", 0, 0)]);
+
+        assert_eq!(
+            Lockfile::from_report(&report),
+            Lockfile {
+                crates: vec![
+                    create_locked_crate("bar", "0.2.0", 0, 0),
+                    create_locked_crate("foo", "1.0.0", 3, 1),
+                ]
+            }
+        );
+    }
+
+    #[rstest(
+        input_crates,
+        expected_drifts,
+        case(vec![("foo", "1.0.0", 3, 1)], vec![]),
+        case(vec![("foo", "1.0.0", 2, 0)], vec![]),
+        case(
+            vec![("foo", "1.0.0", 2, 2)],
+            vec![LockfileDrift {
+                name: String::from("foo"),
+                version: Version::new(1, 0, 0),
+                locked: Some([0, 3, 0, 0, 1]),
+                current: [0, 2, 0, 0, 2],
+            }]
+        ),
+        case(vec![("bar", "0.1.0", 0, 0)], vec![]),
+        case(
+            vec![("foo", "1.1.0", 3, 1)],
+            vec![LockfileDrift {
+                name: String::from("foo"),
+                version: Version::new(1, 1, 0),
+                locked: None,
+                current: [0, 3, 0, 0, 1],
+            }]
+        )
+    )]
+    fn find_lockfile_drifts_test(
+        input_crates: Vec<(&str, &str, u64, u64)>,
+        expected_drifts: Vec<LockfileDrift>,
+    ) {
+        let lockfile = Lockfile {
+            crates: vec![create_locked_crate("foo", "1.0.0", 3, 1)],
+        };
+
+        assert_eq!(
+            find_lockfile_drifts(&lockfile, &create_report(&input_crates)),
+            expected_drifts
+        );
+    }
+
+    #[rstest]
+    fn construct_lockfile_drift_lines_test() {
+        let drifts = vec![
+            LockfileDrift {
+                name: String::from("bar"),
+                version: Version::new(0, 2, 0),
+                locked: None,
+                current: [2, 4, 0, 0, 0],
+            },
+            LockfileDrift {
+                name: String::from("foo"),
+                version: Version::new(1, 0, 0),
+                locked: Some([0, 3, 0, 0, 1]),
+                current: [0, 5, 0, 0, 1],
+            },
+        ];
+
+        assert_eq!(
+            construct_lockfile_drift_lines("geiger.lock", &drifts),
+            vec![
+                String::from("Unsafe usage exceeding geiger.lock:"),
+                String::new(),
+                String::from(
+                    "bar 0.2.0 (not in geiger.lock): functions 2, expressions 4"
+                ),
+                String::from("foo 1.0.0: expressions 3 -> 5"),
+                String::new(),
+                String::from(
+                    "Run `cargo geiger --write-lockfile` to accept the current unsafe usage."
+                ),
+            ]
+        );
+        assert_eq!(
+            construct_lockfile_drift_lines("geiger.lock", &[]),
+            vec![String::from(
+                "No crate uses more unsafe code than recorded in geiger.lock."
+            )]
+        );
+    }
+
+    #[rstest]
+    fn write_and_read_lockfile_test() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let lockfile_path = temp_dir.path().join(DEFAULT_LOCKFILE_PATH);
+        let lockfile = Lockfile {
+            crates: vec![
+                create_locked_crate("bar", "0.2.0", 0, 0),
+                create_locked_crate("foo", "1.0.0", 3, 1),
+            ],
+        };
+
+        write_lockfile(&lockfile_path, &lockfile).unwrap();
+
+        let lockfile_content = fs::read_to_string(&lockfile_path).unwrap();
+        assert!(lockfile_content.starts_with(LOCKFILE_HEADER));
+        assert!(lockfile_content.contains("[[crate]]"));
+        assert_eq!(read_lockfile(&lockfile_path).unwrap(), lockfile);
+    }
+}
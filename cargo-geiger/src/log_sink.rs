@@ -0,0 +1,292 @@
+use crate::format::CrateDetectionStatus;
+
+use cargo::CliError;
+use cargo_geiger_serde::SafetyReport;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The identifier the records are logged under
+const LOG_IDENTIFIER: &str = "cargo-geiger";
+
+/// The syslog facility of the records, `user`
+const SYSLOG_FACILITY: u8 = 1;
+
+/// The system log a record is written to for each scanned crate with
+/// `--log-sink`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LogSink {
+    /// The syslog socket at `/dev/log`
+    Syslog,
+    /// The native protocol of the systemd journal
+    Journald,
+}
+
+impl LogSink {
+    #[cfg(all(feature = "log-sink", unix))]
+    fn socket_path(self) -> &'static str {
+        match self {
+            LogSink::Syslog => "/dev/log",
+            LogSink::Journald => "/run/systemd/journal/socket",
+        }
+    }
+
+    /// The record as it is written to the log
+    pub fn message(self, log_record: &LogRecord) -> String {
+        match self {
+            LogSink::Syslog => log_record.syslog_message(),
+            LogSink::Journald => log_record.journald_message(),
+        }
+    }
+}
+
+impl FromStr for LogSink {
+    type Err = LogSinkParseError;
+    fn from_str(s: &str) -> Result<Self, LogSinkParseError> {
+        match s {
+            "syslog" => Ok(LogSink::Syslog),
+            "journald" => Ok(LogSink::Journald),
+            _ => Err(LogSinkParseError),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LogSinkParseError;
+impl fmt::Display for LogSinkParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "matching log sink not found, expected syslog or journald"
+        )
+    }
+}
+impl Error for LogSinkParseError {}
+
+/// The syslog severity of a record, decided by what was detected in the crate
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Severity {
+    Warning = 4,
+    Notice = 5,
+    Info = 6,
+    Debug = 7,
+}
+
+impl Severity {
+    fn name(self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Notice => "notice",
+            Severity::Info => "info",
+            Severity::Debug => "debug",
+        }
+    }
+}
+
+impl From<&CrateDetectionStatus> for Severity {
+    fn from(crate_detection_status: &CrateDetectionStatus) -> Self {
+        match crate_detection_status {
+            CrateDetectionStatus::UnsafeDetected => Severity::Warning,
+            CrateDetectionStatus::NoneDetectedAllowsUnsafe => Severity::Notice,
+            CrateDetectionStatus::NoneDetectedDeniesUnsafe
+            | CrateDetectionStatus::NoneDetectedConditionallyForbidsUnsafe => {
+                Severity::Info
+            }
+            CrateDetectionStatus::NoneDetectedForbidsUnsafe => Severity::Debug,
+        }
+    }
+}
+
+/// The finding for a single crate: the crate, its version, the number of unsafe
+/// items used by the build, and the severity
+#[derive(Debug, Eq, PartialEq)]
+pub struct LogRecord {
+    pub crate_name: String,
+    pub version: String,
+    pub count: u64,
+    pub severity: Severity,
+}
+
+impl LogRecord {
+    fn field_text(&self) -> String {
+        format!(
+            "crate={} version={} count={} severity={}",
+            self.crate_name,
+            self.version,
+            self.count,
+            self.severity.name()
+        )
+    }
+
+    /// A syslog message with the fields as `key=value` pairs
+    fn syslog_message(&self) -> String {
+        format!(
+            "<{}>{}: {}",
+            SYSLOG_FACILITY * 8 + self.severity as u8,
+            LOG_IDENTIFIER,
+            self.field_text()
+        )
+    }
+
+    /// A journal entry with the fields as `GEIGER_*` journal fields, along
+    /// with the message as it would be written to syslog
+    fn journald_message(&self) -> String {
+        [
+            format!("MESSAGE={}", self.field_text()),
+            format!("PRIORITY={}", self.severity as u8),
+            format!("SYSLOG_IDENTIFIER={}", LOG_IDENTIFIER),
+            format!("GEIGER_CRATE={}", self.crate_name),
+            format!("GEIGER_VERSION={}", self.version),
+            format!("GEIGER_COUNT={}", self.count),
+            format!("GEIGER_SEVERITY={}", self.severity.name()),
+        ]
+        .iter()
+        .map(|field| format!("{}\n", field))
+        .collect()
+    }
+}
+
+/// A record for each crate in the report, ordered by crate
+pub fn construct_log_records(report: &SafetyReport) -> Vec<LogRecord> {
+    let mut entries = report.packages.values().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+    entries
+        .into_iter()
+        .map(|entry| LogRecord {
+            crate_name: entry.package.id.name.clone(),
+            version: entry.package.id.version.to_string(),
+            count: entry.unsafety.used.total_unsafe(),
+            severity: Severity::from(&CrateDetectionStatus::from(
+                &entry.unsafety,
+            )),
+        })
+        .collect()
+}
+
+/// Write the records to the system log, independently of the printed output
+#[cfg(all(feature = "log-sink", unix))]
+pub fn emit_log_records(
+    log_sink: LogSink,
+    log_records: &[LogRecord],
+) -> Result<(), CliError> {
+    use std::os::unix::net::UnixDatagram;
+
+    let send_log_records = || -> std::io::Result<()> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(log_sink.socket_path())?;
+        for log_record in log_records {
+            socket.send(log_sink.message(log_record).as_bytes())?;
+        }
+        Ok(())
+    };
+    send_log_records().map_err(|e| {
+        eprintln!(
+            "Failed to write to the system log at: {}",
+            log_sink.socket_path()
+        );
+        CliError::new(anyhow::Error::from(e), 1)
+    })
+}
+
+/// Without the `log-sink` feature, or on systems without Unix sockets, there
+/// is no backend to write the records with
+#[cfg(not(all(feature = "log-sink", unix)))]
+pub fn emit_log_records(
+    _log_sink: LogSink,
+    _log_records: &[LogRecord],
+) -> Result<(), CliError> {
+    eprintln!(
+        "`--log-sink` requires cargo-geiger to be built with the `log-sink` \
+        feature on a Unix system"
+    );
+    Err(CliError::code(1))
+}
+
+#[cfg(test)]
+mod log_sink_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::{PackageInfo, UnsafeInfo};
+    use rstest::*;
+
+    #[rstest(
+        input_log_sink,
+        expected_log_sink,
+        case("syslog", Ok(LogSink::Syslog)),
+        case("journald", Ok(LogSink::Journald)),
+        case("journal", Err(LogSinkParseError))
+    )]
+    fn log_sink_from_str_test(
+        input_log_sink: &str,
+        expected_log_sink: Result<LogSink, LogSinkParseError>,
+    ) {
+        assert_eq!(LogSink::from_str(input_log_sink), expected_log_sink);
+    }
+
+    #[rstest]
+    fn construct_log_records_test() {
+        let report = create_safety_report(
+            vec![("foo", 3, false), ("bar", 0, true)]
+                .into_iter()
+                .map(|(name, unsafe_exprs, forbids_unsafe)| {
+                    let unsafety = UnsafeInfo {
+                        forbids_unsafe,
+                        ..create_unsafe_info(unsafe_exprs)
+                    };
+                    (PackageInfo::new(create_path_package_id(name)), unsafety)
+                })
+                .collect(),
+        );
+
+        assert_eq!(
+            construct_log_records(&report),
+            vec![
+                LogRecord {
+                    crate_name: String::from("bar"),
+                    version: String::from("0.1.0"),
+                    count: 0,
+                    severity: Severity::Debug,
+                },
+                LogRecord {
+                    crate_name: String::from("foo"),
+                    version: String::from("0.1.0"),
+                    count: 3,
+                    severity: Severity::Warning,
+                },
+            ]
+        );
+    }
+
+    #[rstest(
+        input_log_sink,
+        expected_message,
+        case(
+            LogSink::Syslog,
+            "<12>cargo-geiger: crate=foo version=0.1.0 count=3 severity=warning"
+        ),
+        case(
+            LogSink::Journald,
+            "MESSAGE=crate=foo version=0.1.0 count=3 severity=warning\n\
+            PRIORITY=4\n\
+            SYSLOG_IDENTIFIER=cargo-geiger\n\
+            GEIGER_CRATE=foo\n\
+            GEIGER_VERSION=0.1.0\n\
+            GEIGER_COUNT=3\n\
+            GEIGER_SEVERITY=warning\n"
+        )
+    )]
+    fn log_sink_message_test(input_log_sink: LogSink, expected_message: &str) {
+        let log_record = LogRecord {
+            crate_name: String::from("foo"),
+            version: String::from("0.1.0"),
+            count: 3,
+            severity: Severity::Warning,
+        };
+
+        assert_eq!(input_log_sink.message(&log_record), expected_message);
+    }
+}
@@ -10,16 +10,73 @@ extern crate cargo;
 extern crate colored;
 extern crate petgraph;
 
-use cargo_geiger::args::{Args, HELP};
-use cargo_geiger::cli::{get_cargo_metadata, get_krates, get_workspace};
-use cargo_geiger::graph::build_graph;
+use cargo_geiger::api::{
+    get_root_package_id, get_workspace_member_ids, scan_workspace_with_args,
+};
+use cargo_geiger::args::{Args, FeaturesArgs, GitArgs, HELP};
+use cargo_geiger::audits::UnauditedUnsafeError;
+use cargo_geiger::badge::{write_badge, Badge, DEFAULT_BADGE_PATH};
+use cargo_geiger::baseline::{check_new_crates, read_baseline, write_baseline};
+use cargo_geiger::bundle::{prepare_bundle_directory, write_bundle};
+use cargo_geiger::checksum::check_checksums;
+use cargo_geiger::cli::{
+    check_format, get_cargo_metadata, get_krates, get_workspace,
+};
+use cargo_geiger::compare::{
+    construct_compare_lines, unsafe_file_counts, CompareSide, CompareSpec,
+};
+use cargo_geiger::completions::{
+    create_completion_script, create_manpage, GenerateCommand,
+};
+use cargo_geiger::config_file::parse_args_with_config_file;
+use cargo_geiger::diff::{
+    construct_baseline_diff_lines, construct_category_diff_lines,
+    construct_diff_lines, count_regressions, diff_reports,
+    UnsafeRegressionError,
+};
+use cargo_geiger::digest::report_digest;
+use cargo_geiger::duplicates::check_duplicate_versions;
+use cargo_geiger::feature_matrix::{
+    construct_feature_matrix_lines, feature_combinations, worst_case_unsafe,
+};
+use cargo_geiger::gate::{
+    determine_detection_exit_code, determine_exit_code, ExitCodeMode,
+    FailedGate, UNSAFE_EXIT_CODE, WARNINGS_EXIT_CODE,
+};
+use cargo_geiger::git::checkout_git_repository;
+use cargo_geiger::graph::{build_graph, build_graph_from_roots, Graph};
+use cargo_geiger::limits::UnsafeLimitError;
+use cargo_geiger::lockfile::{
+    construct_lockfile_drift_lines, find_lockfile_drifts, read_lockfile,
+    write_lockfile, Lockfile, LockfileDriftError, DEFAULT_LOCKFILE_PATH,
+};
 use cargo_geiger::mapping::{CargoMetadataParameters, QueryResolve};
+use cargo_geiger::pager::{
+    configure_colors_for_pager, get_pager_command, print_lines_with_pager,
+};
+use cargo_geiger::policy::CategoryPolicyError;
 use cargo_geiger::readme::create_or_replace_section_in_readme;
-use cargo_geiger::scan::{scan, FoundWarningsError, ScanResult};
+use cargo_geiger::registry::download_registry_package;
+use cargo_geiger::scan::{
+    explain, inspect, remove_watch_entries, scan, scan_to_report,
+    scan_workspace_members, FailThresholdError, FoundWarningsError, ScanReport,
+    ScanResult, UndocumentedUnsafeError, UnsafeDetectedError,
+};
+use cargo_geiger::scan_log::{
+    append_history_record, construct_history_lines, current_commit,
+    current_timestamp, read_history_records, HistoryCommand, HistoryRecord,
+    DEFAULT_HISTORY_STORE,
+};
+use cargo_geiger::score::MaxScoreError;
+use cargo_geiger::tee::TeeWriter;
+use cargo_geiger::watch::{snapshot_workspace, POLL_INTERVAL};
 
 use cargo::core::shell::Shell;
-use cargo::util::important_paths;
 use cargo::{CliError, CliResult, Config};
+use geiger::IncludeTests;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
 
 const VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
 
@@ -32,11 +89,93 @@ fn cli_result_main(args: &Args) -> CliResult {
         println!("{}", HELP);
         return Ok(());
     }
+    if let Some(generate_command) = args.generate_command {
+        match generate_command {
+            GenerateCommand::Completions(shell) => {
+                print!("{}", create_completion_script(shell, HELP))
+            }
+            GenerateCommand::Manpage => print!(
+                "{}",
+                create_manpage(HELP, VERSION.unwrap_or("unknown version"))
+            ),
+        }
+        return Ok(());
+    }
+    if args.check_format {
+        println!("{}", check_format(args)?);
+        return Ok(());
+    }
 
     let mut config = Config::default()?;
     args.update_config(&mut config)?;
 
+    if let Some(compare_spec) = &args.compare {
+        return compare(args, &config, compare_spec);
+    }
+
+    // The checkout is removed when it goes out of scope, after the scan.
+    if let Some(git_checkout_directory) =
+        checkout_git_repository(&args.git_args, &config)?
+    {
+        // On stderr, so that the machine-readable output stays parseable.
+        eprintln!(
+            "Scanning {} at commit {}",
+            args.git_args.git.as_deref().unwrap_or_default(),
+            git_checkout_directory.revision()
+        );
+        let args = Args {
+            git_args: GitArgs::default(),
+            manifest_path: Some(git_checkout_directory.manifest_path()),
+            ..args.clone()
+        };
+        return cli_result_main(&args);
+    }
+
+    // The copy is removed when it goes out of scope, after the scan.
+    if let Some(registry_package_directory) =
+        download_registry_package(&args.package_spec, &config)?
+    {
+        let args = Args {
+            manifest_path: Some(registry_package_directory.manifest_path()),
+            package_spec: None,
+            ..args.clone()
+        };
+        return cli_result_main(&args);
+    }
+
+    if args.badge {
+        return badge(args, &config);
+    }
+
+    if let Some(history_command) = args.history_command {
+        return history(args, &config, history_command);
+    }
+
+    // Decided before scanning, since colors are applied while the report is
+    // being built.
+    // Any styling outside of `colorize`, such as the bold table header, is
+    // left out of the output file too.
+    if args.output_file.is_some() {
+        colored::control::set_override(false);
+    }
+    let pager_command = get_pager_command(args);
+    if pager_command.is_some() {
+        configure_colors_for_pager(config.shell().color_choice());
+    }
+
+    if args.compare_features {
+        return compare_features(args, &config);
+    }
+
+    if args.feature_matrix_args.feature_matrix {
+        return feature_matrix(args, &config);
+    }
+
     let cargo_metadata = get_cargo_metadata(args, &config)?;
+    prepare_bundle_directory(
+        &args.bundle_args,
+        cargo_metadata.workspace_root.as_std_path(),
+    )?;
     let krates = get_krates(&cargo_metadata)?;
 
     let cargo_metadata_parameters = CargoMetadataParameters {
@@ -46,22 +185,71 @@ fn cli_result_main(args: &Args) -> CliResult {
 
     let workspace = get_workspace(&config, args.manifest_path.clone())?;
 
-    let cargo_metadata_root_package_id = if let Some(
-        cargo_metadata_root_package,
-    ) = cargo_metadata.root_package()
-    {
-        cargo_metadata_root_package.id.clone()
-    } else {
-        eprintln!(
-            "manifest path `{}` is a virtual manifest, but this command requires running against an actual package in this workspace",
-            match args.manifest_path.clone() {
-                Some(path) => path,
-                None => important_paths::find_root_manifest_for_wd(config.cwd())?,
-            }.as_os_str().to_str().unwrap()
+    // Doesn't need the dependency graph, which is slow to build.
+    if let Some(crate_spec) = &args.explain {
+        let explanation_lines = explain(
+            args,
+            &cargo_metadata_parameters,
+            &config,
+            crate_spec,
+            &workspace,
+        )?;
+        for explanation_line in explanation_lines {
+            println!("{}", explanation_line);
+        }
+        return Ok(());
+    }
+    if let Some(crate_spec) = &args.inspect {
+        let inspect_lines = inspect(
+            args,
+            &cargo_metadata_parameters,
+            &config,
+            crate_spec,
+            &workspace,
+        )?;
+        for inspect_line in inspect_lines {
+            println!("{}", inspect_line);
+        }
+        return Ok(());
+    }
+
+    if args.workspace_args.workspace {
+        if args.package.is_some() || args.root.is_some() {
+            eprintln!(
+                "`--workspace` scans every member, it can't be combined with \
+                `--package` or `--root`"
+            );
+            return Err(CliError::code(1));
+        }
+        let member_package_ids =
+            get_workspace_member_ids(args, &cargo_metadata)?;
+        let global_rustc = config.load_global_rustc(Some(&workspace))?;
+        let graph = build_graph_from_roots(
+            args,
+            &cargo_metadata_parameters,
+            &global_rustc.host,
+            &global_rustc.path,
+            &member_package_ids,
+        )?;
+        let scan_result = scan_workspace_members(
+            args,
+            &cargo_metadata_parameters,
+            &config,
+            &graph,
+            &member_package_ids,
+            &workspace,
+        )?;
+        return report_scan_result(
+            args,
+            &cargo_metadata_parameters,
+            &graph,
+            pager_command,
+            scan_result,
         );
+    }
 
-        return Err(CliError::code(1));
-    };
+    let cargo_metadata_root_package_id =
+        get_root_package_id(args, &config, &cargo_metadata)?;
 
     let global_rustc = config.load_global_rustc(Some(&workspace))?;
 
@@ -82,10 +270,167 @@ fn cli_result_main(args: &Args) -> CliResult {
         },
     );
 
-    let ScanResult {
-        scan_output_lines,
-        warning_count,
-    } = scan(
+    if let Some(bundle_path) = &args.bundle_args.bundle_path {
+        let scan_report = scan_to_report(
+            args,
+            &cargo_metadata_parameters,
+            &config,
+            &graph,
+            query_resolve_root_package_id,
+            &workspace,
+        )?;
+        return write_bundle(
+            args,
+            bundle_path,
+            &cargo_metadata
+                .workspace_root
+                .join("Cargo.lock")
+                .into_std_path_buf(),
+            &scan_report,
+        );
+    }
+
+    if let Some(baseline_path) = &args.baseline_args.save_baseline {
+        let scan_report = scan_to_report(
+            args,
+            &cargo_metadata_parameters,
+            &config,
+            &graph,
+            query_resolve_root_package_id,
+            &workspace,
+        )?;
+        write_baseline(baseline_path, &scan_report.report)?;
+        eprintln!("Saved baseline to: {}", baseline_path.display());
+        return Ok(());
+    }
+
+    if args.lockfile_args.write_lockfile || args.lockfile_args.verify_lockfile {
+        let lockfile_path = args
+            .lockfile_args
+            .lockfile
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_LOCKFILE_PATH));
+        // The lockfile is read first, so that a missing file fails before the
+        // scan.
+        let lockfile = if args.lockfile_args.write_lockfile {
+            None
+        } else {
+            Some(read_lockfile(&lockfile_path)?)
+        };
+        let scan_report = scan_to_report(
+            args,
+            &cargo_metadata_parameters,
+            &config,
+            &graph,
+            query_resolve_root_package_id,
+            &workspace,
+        )?;
+        let lockfile = match lockfile {
+            Some(lockfile) => lockfile,
+            None => {
+                write_lockfile(
+                    &lockfile_path,
+                    &Lockfile::from_report(&scan_report.report),
+                )?;
+                eprintln!("Wrote lockfile to: {}", lockfile_path.display());
+                return Ok(());
+            }
+        };
+        let drifts = find_lockfile_drifts(&lockfile, &scan_report.report);
+        for drift_line in construct_lockfile_drift_lines(
+            &lockfile_path.display().to_string(),
+            &drifts,
+        ) {
+            println!("{}", drift_line);
+        }
+        let mut failed_gates = vec![];
+        if !drifts.is_empty() {
+            failed_gates.push(
+                FailedGate::new(LockfileDriftError {
+                    crate_count: drifts.len(),
+                })
+                .with_exit_code(UNSAFE_EXIT_CODE),
+            );
+        }
+        return determine_exit_code(args.exit_zero, failed_gates);
+    }
+
+    if args.baseline_args.baseline_diff {
+        let baseline_path = match &args.baseline_args.baseline {
+            Some(baseline_path) => baseline_path,
+            None => {
+                eprintln!("`--baseline-diff` requires a `--baseline` report");
+                return Err(CliError::code(1));
+            }
+        };
+        let baseline = read_baseline(baseline_path)?;
+        let scan_report = scan_to_report(
+            args,
+            &cargo_metadata_parameters,
+            &config,
+            &graph,
+            query_resolve_root_package_id,
+            &workspace,
+        )?;
+        let diff_lines = construct_category_diff_lines(
+            "baseline",
+            "current scan",
+            &diff_reports(&baseline, &scan_report.report),
+        );
+        for diff_line in diff_lines {
+            println!("{}", diff_line);
+        }
+        return Ok(());
+    }
+
+    if let Some(diff_path) = &args.diff {
+        let baseline = read_baseline(diff_path)?;
+        let scan_report = scan_to_report(
+            args,
+            &cargo_metadata_parameters,
+            &config,
+            &graph,
+            query_resolve_root_package_id,
+            &workspace,
+        )?;
+        let crate_diffs = diff_reports(&baseline, &scan_report.report);
+        let diff_lines = construct_baseline_diff_lines(
+            args,
+            &diff_path.display().to_string(),
+            &crate_diffs,
+        )?;
+        for diff_line in diff_lines {
+            println!("{}", diff_line);
+        }
+        // Only the regressions fail `--deny-unsafe`, the unsafe code already
+        // in the baseline is known.
+        let regression_count = count_regressions(&crate_diffs);
+        let mut failed_gates = vec![];
+        if args.deny_unsafe && regression_count > 0 {
+            failed_gates.push(
+                FailedGate::new(UnsafeRegressionError {
+                    crate_count: regression_count,
+                })
+                .with_exit_code(UNSAFE_EXIT_CODE),
+            );
+        }
+        return determine_exit_code(args.exit_zero, failed_gates);
+    }
+
+    if args.print_digest {
+        let scan_report = scan_to_report(
+            args,
+            &cargo_metadata_parameters,
+            &config,
+            &graph,
+            query_resolve_root_package_id,
+            &workspace,
+        )?;
+        println!("{}", report_digest(&scan_report.report));
+        return Ok(());
+    }
+
+    let scan_result = scan(
         args,
         &cargo_metadata_parameters,
         &config,
@@ -93,31 +438,455 @@ fn cli_result_main(args: &Args) -> CliResult {
         query_resolve_root_package_id,
         &workspace,
     )?;
+    report_scan_result(
+        args,
+        &cargo_metadata_parameters,
+        &graph,
+        pager_command,
+        scan_result,
+    )
+}
 
-    if args.readme_args.update_readme {
+/// Print the output of a scan, or write it wherever it was asked to go, and
+/// exit with the code of the gates which failed
+fn report_scan_result(
+    args: &Args,
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    graph: &Graph,
+    pager_command: Option<String>,
+    scan_result: ScanResult,
+) -> CliResult {
+    let ScanResult {
+        category_policy_error_count,
+        checksum_mismatch_count,
+        detection_outcome,
+        over_fail_threshold_ratio,
+        over_max_score_count,
+        scan_output_lines,
+        unaudited_crate_count,
+        undocumented_unsafe_count,
+        unsafe_crate_count,
+        unsafe_limit_violation_count,
+        warning_count,
+    } = scan_result;
+
+    if args.readme_args.updates_file() {
         create_or_replace_section_in_readme(
             &args.readme_args,
             &scan_output_lines,
         )?;
+    } else if let Some(output_file) = &args.output_file {
+        // Nothing is printed to the terminal, the file is the report.
+        write_lines_to_tee(output_file, io::sink(), &scan_output_lines)?;
+        if let Some(tee_path) = &args.tee {
+            write_lines_to_tee(tee_path, io::sink(), &scan_output_lines)?;
+        }
+    } else if let Some(pager_command) = pager_command {
+        print_lines_with_pager(&pager_command, &scan_output_lines);
+        if let Some(tee_path) = &args.tee {
+            write_lines_to_tee(tee_path, io::sink(), &scan_output_lines)?;
+        }
+    } else if let Some(tee_path) = &args.tee {
+        write_lines_to_tee(tee_path, io::stdout().lock(), &scan_output_lines)?;
     } else {
         for scan_output_line in scan_output_lines {
             println!("{}", scan_output_line);
         }
     }
 
+    let mut failed_gates = vec![];
+
+    if let Some(new_crates_error) =
+        check_new_crates(&args.baseline_args, cargo_metadata_parameters, graph)?
+    {
+        failed_gates.push(FailedGate::new(new_crates_error));
+    }
+
+    if let Some(checksum_mismatch_error) =
+        check_checksums(&args.checksum_args, checksum_mismatch_count)
+    {
+        failed_gates.push(FailedGate::new(checksum_mismatch_error));
+    }
+
+    if let Some(duplicate_versions_error) = check_duplicate_versions(
+        &args.duplicate_args,
+        cargo_metadata_parameters.metadata,
+        graph,
+    ) {
+        failed_gates.push(FailedGate::new(duplicate_versions_error));
+    }
+
+    if undocumented_unsafe_count > 0 {
+        failed_gates.push(FailedGate::new(UndocumentedUnsafeError {
+            undocumented_unsafe_count,
+        }));
+    }
+
+    if let Some(max_score) = args.max_score.filter(|_| over_max_score_count > 0)
+    {
+        failed_gates.push(FailedGate::new(MaxScoreError {
+            crate_count: over_max_score_count,
+            max_score,
+        }));
+    }
+
+    if let (Some(fail_threshold), Some(unsafe_ratio)) =
+        (args.fail_threshold, over_fail_threshold_ratio)
+    {
+        failed_gates.push(FailedGate::new(FailThresholdError {
+            fail_threshold,
+            unsafe_ratio,
+        }));
+    }
+
+    if category_policy_error_count > 0 {
+        failed_gates.push(FailedGate::new(CategoryPolicyError {
+            crate_count: category_policy_error_count,
+        }));
+    }
+
+    if unsafe_limit_violation_count > 0 {
+        failed_gates.push(FailedGate::new(UnsafeLimitError {
+            violation_count: unsafe_limit_violation_count,
+        }));
+    }
+
+    if unaudited_crate_count > 0 {
+        failed_gates.push(
+            FailedGate::new(UnauditedUnsafeError {
+                crate_count: unaudited_crate_count,
+            })
+            .with_exit_code(UNSAFE_EXIT_CODE),
+        );
+    }
+
+    if unsafe_crate_count > 0 {
+        failed_gates.push(
+            FailedGate::new(UnsafeDetectedError {
+                crate_count: unsafe_crate_count,
+            })
+            .with_exit_code(UNSAFE_EXIT_CODE),
+        );
+    }
+
     if warning_count > 0 {
-        return Err(CliError::new(
-            anyhow::Error::new(FoundWarningsError { warning_count }),
-            1,
+        let failed_gate = FailedGate::new(FoundWarningsError { warning_count });
+        failed_gates.push(if args.deny_warnings {
+            failed_gate.with_exit_code(WARNINGS_EXIT_CODE)
+        } else {
+            failed_gate
+        });
+    }
+
+    match (args.exit_code_mode, detection_outcome) {
+        (ExitCodeMode::Detection, Some(detection_outcome)) => {
+            determine_detection_exit_code(
+                args.exit_zero,
+                detection_outcome,
+                failed_gates,
+            )
+        }
+        (ExitCodeMode::Detection, None) => {
+            eprintln!(
+                "NOTE: `--exit-code-mode detection` doesn't apply to this \
+                output, the gates decide the exit code."
+            );
+            determine_exit_code(args.exit_zero, failed_gates)
+        }
+        (ExitCodeMode::Gates, _) => {
+            determine_exit_code(args.exit_zero, failed_gates)
+        }
+    }
+}
+
+/// Scan with the default features, and with the selected features, and print
+/// the difference between the two scans
+fn compare_features(args: &Args, config: &Config) -> CliResult {
+    let (compared_features_args, compared_features_label) =
+        match &args.features_args {
+            FeaturesArgs {
+                all_features: false,
+                features,
+                no_default_features: false,
+            } if features.is_empty() => (
+                FeaturesArgs {
+                    all_features: true,
+                    ..Default::default()
+                },
+                String::from("`--all-features`"),
+            ),
+            features_args => {
+                (features_args.clone(), describe_features_args(features_args))
+            }
+        };
+
+    let default_features_report =
+        scan_to_report_with_features(args, config, FeaturesArgs::default())?;
+    let compared_features_report =
+        scan_to_report_with_features(args, config, compared_features_args)?;
+
+    let diff_lines = construct_diff_lines(
+        "default features",
+        &compared_features_label,
+        &diff_reports(
+            &default_features_report.report,
+            &compared_features_report.report,
+        ),
+    );
+    for diff_line in diff_lines {
+        println!("{}", diff_line);
+    }
+
+    Ok(())
+}
+
+/// Download and scan both versions of the crate given with `compare`, and
+/// print the difference between the two scans
+fn compare(
+    args: &Args,
+    config: &Config,
+    compare_spec: &CompareSpec,
+) -> CliResult {
+    let include_tests = match args.include_tests {
+        true => IncludeTests::Yes,
+        false => IncludeTests::No,
+    };
+
+    let mut scans = vec![];
+    for version in [&compare_spec.old_version, &compare_spec.new_version] {
+        // Only `None` without a package spec.
+        let registry_package_directory = download_registry_package(
+            &Some(compare_spec.package_spec(version)),
+            config,
+        )?
+        .unwrap();
+        let scan_report = scan_workspace_with_args(
+            &Args {
+                compare: None,
+                manifest_path: Some(registry_package_directory.manifest_path()),
+                package_spec: None,
+                ..args.clone()
+            },
+            config,
+        )?;
+        scans.push((
+            scan_report.report,
+            unsafe_file_counts(
+                registry_package_directory.package_path(),
+                include_tests,
+            ),
         ));
     }
 
+    let compare_lines = construct_compare_lines(
+        compare_spec,
+        &CompareSide {
+            report: &scans[0].0,
+            unsafe_file_counts: &scans[0].1,
+        },
+        &CompareSide {
+            report: &scans[1].0,
+            unsafe_file_counts: &scans[1].1,
+        },
+    );
+    for compare_line in compare_lines {
+        println!("{}", compare_line);
+    }
+
     Ok(())
 }
 
+/// Scan the package, and write the badge of the scan
+fn badge(args: &Args, config: &Config) -> CliResult {
+    let scan_report = scan_workspace_with_args(args, config)?;
+    let badge_path = args
+        .output_file
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_BADGE_PATH));
+    write_badge(&badge_path, &Badge::from_report(&scan_report.report))?;
+    eprintln!("Wrote badge to: {}", badge_path.display());
+    Ok(())
+}
+
+/// Record a scan in the store of `history`, or print the trend of the
+/// recorded scans
+fn history(
+    args: &Args,
+    config: &Config,
+    history_command: HistoryCommand,
+) -> CliResult {
+    let cargo_metadata = get_cargo_metadata(args, config)?;
+    let workspace_root = cargo_metadata.workspace_root.into_std_path_buf();
+    let history_store_path = args
+        .history_store
+        .clone()
+        .unwrap_or_else(|| workspace_root.join(DEFAULT_HISTORY_STORE));
+
+    match history_command {
+        HistoryCommand::Record => {
+            let scan_report = scan_workspace_with_args(args, config)?;
+            let history_record = HistoryRecord::from_report(
+                &scan_report.report,
+                current_timestamp(),
+                current_commit(&workspace_root),
+            );
+            append_history_record(&history_store_path, &history_record)?;
+            eprintln!("Recorded the scan in: {}", history_store_path.display());
+        }
+        HistoryCommand::Show => {
+            let history_records = read_history_records(&history_store_path)?;
+            for history_line in construct_history_lines(&history_records) {
+                println!("{}", history_line);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Scan, then scan again each time a file of a workspace member changes,
+/// until interrupted. A failed scan or gate is reported without ending the
+/// session.
+fn watch(args: &Args) -> CliResult {
+    if args.help || args.version || args.check_format {
+        return cli_result_main(args);
+    }
+    let mut config = Config::default()?;
+    args.update_config(&mut config)?;
+    let mut cargo_metadata = get_cargo_metadata(args, &config)?;
+    remove_watch_entries(args.cache_dir.as_deref());
+    let mut snapshot = snapshot_workspace(&cargo_metadata);
+    loop {
+        if let Err(CliError {
+            error: Some(error), ..
+        }) = cli_result_main(args)
+        {
+            cargo::display_error(&error, &mut config.shell());
+        }
+        eprintln!(
+            "Watching the workspace members for changes, press Ctrl-C to stop."
+        );
+        let changed_members = loop {
+            thread::sleep(POLL_INTERVAL);
+            let newer_snapshot = snapshot_workspace(&cargo_metadata);
+            let changed_members = snapshot.changed_members(&newer_snapshot);
+            snapshot = newer_snapshot;
+            if !changed_members.is_empty() {
+                break changed_members;
+            }
+        };
+        eprintln!();
+        eprintln!("Changed: {}", changed_members.join(", "));
+        // Members may have been added to or removed from the workspace.
+        if let Ok(newer_cargo_metadata) = get_cargo_metadata(args, &config) {
+            cargo_metadata = newer_cargo_metadata;
+            snapshot = snapshot_workspace(&cargo_metadata);
+        }
+    }
+}
+
+/// Scan each combination of features, and print the worst case unsafe usage
+/// of each crate across the scans
+fn feature_matrix(args: &Args, config: &Config) -> CliResult {
+    let feature_sets = if args.feature_matrix_args.feature_sets.is_empty() {
+        let cargo_metadata = get_cargo_metadata(args, config)?;
+        let root_package_id =
+            get_root_package_id(args, config, &cargo_metadata)?;
+        let features = cargo_metadata[&root_package_id]
+            .features
+            .keys()
+            .cloned()
+            .collect::<Vec<String>>();
+        feature_combinations(&features)
+            .map_err(|e| CliError::new(anyhow::Error::from(e), 1))?
+    } else {
+        args.feature_matrix_args.feature_sets.clone()
+    };
+
+    let mut feature_reports = vec![];
+    for features in feature_sets {
+        let scan_report = scan_to_report_with_features(
+            args,
+            config,
+            FeaturesArgs {
+                all_features: false,
+                features: features.clone(),
+                no_default_features: true,
+            },
+        )?;
+        feature_reports.push((features, scan_report.report));
+    }
+
+    let feature_matrix_lines = construct_feature_matrix_lines(
+        feature_reports.len(),
+        &worst_case_unsafe(&args.score_weights, &feature_reports),
+    );
+    for feature_matrix_line in feature_matrix_lines {
+        println!("{}", feature_matrix_line);
+    }
+
+    Ok(())
+}
+
+fn describe_features_args(features_args: &FeaturesArgs) -> String {
+    let mut options = vec![];
+    if features_args.all_features {
+        options.push(String::from("--all-features"));
+    }
+    if features_args.no_default_features {
+        options.push(String::from("--no-default-features"));
+    }
+    if !features_args.features.is_empty() {
+        options.push(format!(
+            "--features \"{}\"",
+            features_args.features.join(" ")
+        ));
+    }
+    format!("`{}`", options.join(" "))
+}
+
+/// Run the full scan with the feature selection replaced, resolving the
+/// metadata and dependency graph again, since both depend on the features
+fn scan_to_report_with_features(
+    args: &Args,
+    config: &Config,
+    features_args: FeaturesArgs,
+) -> Result<ScanReport, CliError> {
+    let args = Args {
+        features_args,
+        ..args.clone()
+    };
+    scan_workspace_with_args(&args, config)
+}
+
+/// Write the lines to the terminal, and without colors to the `--tee` file
+fn write_lines_to_tee<W: Write>(
+    tee_path: &Path,
+    terminal: W,
+    lines: &[String],
+) -> CliResult {
+    let write_lines = || -> io::Result<()> {
+        let mut tee_writer = TeeWriter::new(tee_path, terminal)?;
+        for line in lines {
+            writeln!(tee_writer, "{}", line)?;
+        }
+        tee_writer.flush()
+    };
+    write_lines().map_err(|e| {
+        eprintln!("Failed to write report to file: {}", tee_path.display());
+        CliError::new(anyhow::Error::from(e), 1)
+    })
+}
+
 fn main() {
-    let args = Args::parse_args(pico_args::Arguments::from_env()).unwrap();
-    if let Err(e) = cli_result_main(&args) {
+    let raw_arguments = std::env::args_os().skip(1).collect::<Vec<_>>();
+    let result = parse_args_with_config_file(raw_arguments)
+        .map_err(|e| CliError::new(anyhow::anyhow!("{}", e), 1))
+        .and_then(|args| match args.watch {
+            true => watch(&args),
+            false => cli_result_main(&args),
+        });
+    if let Err(e) = result {
         let mut shell = Shell::new();
         cargo::exit_with_error(e, &mut shell)
     }
@@ -54,6 +54,14 @@ pub trait GetPackageIdInformation {
         &self,
         krates: &T,
     ) -> Option<String>;
+
+    /// The source of the package, such as
+    /// `registry+https://github.com/rust-lang/crates.io-index`, or the
+    /// directory of a path dependency, such as `path+/home/foo/bar`
+    fn get_package_id_source<T: GetNodeForKid>(
+        &self,
+        krates: &T,
+    ) -> Option<String>;
 }
 
 pub trait GetPackageRoot: GetPackageInformation {
@@ -28,10 +28,12 @@ fn handle_source_repr(source_repr: &str) -> CargoGeigerSerdeSource {
 
     match SourceType::from(source_type) {
         SourceType::Registry => {
+            let url = Url::parse(source_repr_vec.pop().unwrap()).unwrap();
             CargoGeigerSerdeSource::Registry {
-                // It looks like cargo metadata drops this information
-                name: String::from("crates.io"),
-                url: Url::parse(source_repr_vec.pop().unwrap()).unwrap(),
+                // Cargo metadata drops the name of alternate registries,
+                // which are named in the report by `RegistryNames` instead
+                name: registry_name(&url),
+                url,
             }
         }
         SourceType::Git => {
@@ -60,6 +62,18 @@ fn handle_source_repr(source_repr: &str) -> CargoGeigerSerdeSource {
     }
 }
 
+/// `crates.io`, or the host of the index of an alternate registry
+fn registry_name(url: &Url) -> String {
+    let source = CargoGeigerSerdeSource::Registry {
+        name: String::new(),
+        url: url.clone(),
+    };
+    match url.host_str() {
+        Some(host) if !source.is_crates_io() => host.to_string(),
+        _ => String::from("crates.io"),
+    }
+}
+
 fn handle_path_source<T: GetPackageIdRepr>(
     package_id: &T,
 ) -> CargoGeigerSerdeSource {
@@ -89,7 +103,9 @@ enum SourceType {
 impl SourceType {
     fn from(raw: &str) -> Self {
         match raw {
-            "registry" => SourceType::Registry,
+            // Registries using the sparse protocol, such as crates.io with
+            // `sparse+https://index.crates.io/`
+            "registry" | "sparse" => SourceType::Registry,
             "git" => SourceType::Git,
             _ => SourceType::Unrecognised,
         }
@@ -113,6 +129,20 @@ mod geiger_tests {
                 url: Url::parse("https://github.com/rust-lang/crates.io-index").unwrap()
             }
         ),
+        case(
+            "sparse+https://index.crates.io/",
+            CargoGeigerSerdeSource::Registry {
+                name: String::from("crates.io"),
+                url: Url::parse("https://index.crates.io/").unwrap()
+            }
+        ),
+        case(
+            "sparse+https://registry.example.com/index/",
+            CargoGeigerSerdeSource::Registry {
+                name: String::from("registry.example.com"),
+                url: Url::parse("https://registry.example.com/index/").unwrap()
+            }
+        ),
         case(
             "git+https://github.com/rust-itertools/itertools.git?rev=8761fbefb3b209",
             CargoGeigerSerdeSource::Git {
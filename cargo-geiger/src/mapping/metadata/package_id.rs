@@ -30,6 +30,22 @@ impl GetPackageIdInformation for PackageId {
             .get_node_for_kid(self)
             .and_then(|package| package.krate.clone().repository)
     }
+
+    fn get_package_id_source<T: GetNodeForKid>(
+        &self,
+        krates: &T,
+    ) -> Option<String> {
+        krates.get_node_for_kid(self).map(|package| {
+            match (&package.krate.source, package.krate.manifest_path.parent())
+            {
+                (Some(source), _) => source.to_string(),
+                (None, Some(package_directory)) => {
+                    format!("path+{}", package_directory)
+                }
+                (None, None) => String::from("path"),
+            }
+        })
+    }
 }
 
 pub trait GetPackageIdRepr {
@@ -0,0 +1,212 @@
+use cargo_geiger_serde::{PackageId, PackageInfo, UnsafeInfo};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A `--merge` entry, naming a facade crate and the implementation crate it
+/// re-exports, whose unsafe usage is attributed to the facade
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FacadeMerge {
+    pub facade: String,
+    pub implementation: String,
+}
+
+impl FromStr for FacadeMerge {
+    type Err = FacadeMergeParseError;
+
+    /// Parse `<facade>=<implementation>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((facade, implementation))
+                if !facade.trim().is_empty()
+                    && !implementation.trim().is_empty()
+                    && facade.trim() != implementation.trim() =>
+            {
+                Ok(FacadeMerge {
+                    facade: facade.trim().to_string(),
+                    implementation: implementation.trim().to_string(),
+                })
+            }
+            _ => Err(FacadeMergeParseError {
+                entry: s.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct FacadeMergeParseError {
+    pub entry: String,
+}
+
+impl Error for FacadeMergeParseError {}
+
+impl fmt::Display for FacadeMergeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse merge entry `{}`, expected \
+            `<facade>=<implementation>` naming two different crates",
+            self.entry
+        )
+    }
+}
+
+/// The implementation crates merged into each facade crate, such as
+/// `rand_core 0.6.4`
+pub type MergedCrates = BTreeMap<PackageId, Vec<String>>;
+
+/// With `--merge`, add the unsafe usage of each implementation crate to a
+/// facade crate which depends on it directly, and take the implementation
+/// crate out of the scanned crates. An implementation crate without such a
+/// facade in the dependency graph is kept.
+pub fn merge_facades(
+    facade_merges: &[FacadeMerge],
+    package_unsafe_infos: Vec<(PackageInfo, UnsafeInfo)>,
+) -> (Vec<(PackageInfo, UnsafeInfo)>, MergedCrates) {
+    let mut merged_crates = MergedCrates::new();
+    if facade_merges.is_empty() {
+        return (package_unsafe_infos, merged_crates);
+    }
+
+    let mut package_unsafe_infos = package_unsafe_infos;
+    package_unsafe_infos.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+
+    let mut merged_indices = vec![];
+    for (index, (package_info, _)) in package_unsafe_infos.iter().enumerate() {
+        let is_implementation = |facade_merge: &&FacadeMerge| {
+            facade_merge.implementation == package_info.id.name
+        };
+        let facade_index = facade_merges
+            .iter()
+            .filter(is_implementation)
+            .find_map(|facade_merge| {
+                package_unsafe_infos.iter().position(|(facade_info, _)| {
+                    facade_info.id.name == facade_merge.facade
+                        && facade_info.dependencies.contains(&package_info.id)
+                })
+            });
+        if let Some(facade_index) = facade_index {
+            merged_indices.push((index, facade_index));
+        }
+    }
+
+    for (index, facade_index) in &merged_indices {
+        let (package_info, unsafe_info) = package_unsafe_infos[*index].clone();
+        let facade_unsafe_info = &mut package_unsafe_infos[*facade_index].1;
+        facade_unsafe_info.used += unsafe_info.used;
+        facade_unsafe_info.unused += unsafe_info.unused;
+        merged_crates
+            .entry(package_unsafe_infos[*facade_index].0.id.clone())
+            .or_default()
+            .push(format!(
+                "{} {}",
+                package_info.id.name, package_info.id.version
+            ));
+    }
+
+    let package_unsafe_infos = package_unsafe_infos
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| {
+            !merged_indices
+                .iter()
+                .any(|(merged_index, _)| merged_index == index)
+        })
+        .map(|(_, package_unsafe_info)| package_unsafe_info)
+        .collect();
+    (package_unsafe_infos, merged_crates)
+}
+
+/// The name of a scanned crate in the flat outputs, listing the crates merged
+/// into it
+pub fn merged_crate_name(
+    merged_crates: &MergedCrates,
+    package_id: &PackageId,
+) -> String {
+    let crate_name = format!("{} {}", package_id.name, package_id.version);
+    match merged_crates.get(package_id) {
+        Some(implementations) => {
+            format!("{} (merged: {})", crate_name, implementations.join(", "))
+        }
+        None => crate_name,
+    }
+}
+
+#[cfg(test)]
+mod merge_tests {
+    use super::*;
+
+    use crate::lib_tests::{create_path_package_id, create_unsafe_info};
+
+    use cargo_geiger_serde::DependencyKind;
+    use rstest::*;
+
+    #[rstest(
+        input_entry,
+        expected_facade_merge,
+        case(
+            "rand=rand_core",
+            Ok(FacadeMerge {
+                facade: String::from("rand"),
+                implementation: String::from("rand_core"),
+            })
+        ),
+        case(
+            "rand",
+            Err(FacadeMergeParseError {
+                entry: String::from("rand"),
+            })
+        ),
+        case(
+            "rand=rand",
+            Err(FacadeMergeParseError {
+                entry: String::from("rand=rand"),
+            })
+        )
+    )]
+    fn facade_merge_from_str_test(
+        input_entry: &str,
+        expected_facade_merge: Result<FacadeMerge, FacadeMergeParseError>,
+    ) {
+        assert_eq!(FacadeMerge::from_str(input_entry), expected_facade_merge);
+    }
+
+    #[rstest]
+    fn merge_facades_test() {
+        let rand_core = create_path_package_id("rand_core");
+        let mut rand = PackageInfo::new(create_path_package_id("rand"));
+        rand.add_dependency(rand_core.clone(), DependencyKind::Normal);
+        let package_unsafe_infos = vec![
+            (PackageInfo::new(rand_core), create_unsafe_info(3)),
+            (rand.clone(), create_unsafe_info(1)),
+            (
+                PackageInfo::new(create_path_package_id("getrandom")),
+                create_unsafe_info(2),
+            ),
+        ];
+        let facade_merges = vec![
+            FacadeMerge::from_str("rand=rand_core").unwrap(),
+            FacadeMerge::from_str("rand=getrandom").unwrap(),
+        ];
+
+        let (package_unsafe_infos, merged_crates) =
+            merge_facades(&facade_merges, package_unsafe_infos);
+
+        assert_eq!(
+            package_unsafe_infos
+                .iter()
+                .map(|(package_info, unsafe_info)| (
+                    package_info.id.name.as_str(),
+                    unsafe_info.used.exprs.unsafe_
+                ))
+                .collect::<Vec<_>>(),
+            vec![("getrandom", 2), ("rand", 4)]
+        );
+        assert_eq!(
+            merged_crate_name(&merged_crates, &rand.id),
+            "rand 0.1.0 (merged: rand_core 0.1.0)"
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use cargo::CliError;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The order in which crates are placed in the flat output, read from a file
+/// given with `--order-file`
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct CrateOrder {
+    crate_names: Vec<String>,
+}
+
+impl CrateOrder {
+    /// The position of the crate in the order file, or `None` if it is not
+    /// listed
+    pub fn position(&self, crate_name: &str) -> Option<usize> {
+        self.crate_names.iter().position(|name| name == crate_name)
+    }
+
+    /// The crates listed in the order file which are not among `crate_names`,
+    /// in the order of the file
+    pub fn missing_crate_names<'a>(
+        &'a self,
+        crate_names: &HashSet<&str>,
+    ) -> Vec<&'a str> {
+        self.crate_names
+            .iter()
+            .map(String::as_str)
+            .filter(|name| !crate_names.contains(name))
+            .collect()
+    }
+}
+
+impl From<&str> for CrateOrder {
+    /// A crate name on each line, empty lines and lines starting with `#` are
+    /// ignored. Only the first occurrence of a crate name is kept.
+    fn from(s: &str) -> Self {
+        let mut crate_names = Vec::<String>::new();
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty()
+                || line.starts_with('#')
+                || crate_names.iter().any(|name| name == line)
+            {
+                continue;
+            }
+            crate_names.push(line.to_string());
+        }
+        CrateOrder { crate_names }
+    }
+}
+
+/// Read the file given with `--order-file`, or `None` if no file is given
+pub fn read_order_file(
+    order_file_path: &Option<PathBuf>,
+) -> Result<Option<CrateOrder>, CliError> {
+    match order_file_path {
+        Some(order_file_path) => read_crate_order(order_file_path).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn read_crate_order(order_file_path: &Path) -> Result<CrateOrder, CliError> {
+    let order_file_content =
+        fs::read_to_string(order_file_path).map_err(|e| {
+            eprintln!(
+                "Failed to read crate order from file: {}",
+                order_file_path.display()
+            );
+            anyhow::Error::from(e)
+        })?;
+    Ok(CrateOrder::from(order_file_content.as_str()))
+}
+
+#[cfg(test)]
+mod order_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest]
+    fn crate_order_from_str_test() {
+        let crate_order =
+            CrateOrder::from("# highest risk first\nlibc\n\n  foo \nlibc\n");
+
+        assert_eq!(crate_order.position("libc"), Some(0));
+        assert_eq!(crate_order.position("foo"), Some(1));
+        assert_eq!(crate_order.position("bar"), None);
+    }
+
+    #[rstest]
+    fn missing_crate_names_test() {
+        let crate_order = CrateOrder::from("libc\nfoo\nbar\n");
+        let crate_names = vec!["foo", "baz"].into_iter().collect();
+
+        assert_eq!(
+            crate_order.missing_crate_names(&crate_names),
+            vec!["libc", "bar"]
+        );
+    }
+}
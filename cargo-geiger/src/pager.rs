@@ -0,0 +1,123 @@
+use crate::args::Args;
+
+use cargo::core::shell::ColorChoice;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Used when `$PAGER` is not set, mirroring git
+const DEFAULT_PAGER: &str = "less";
+
+/// `less` is told to pass through ANSI colors (`R`), quit if the output fits
+/// on one screen (`F`), and to not clear the screen on exit (`X`)
+const DEFAULT_LESS_OPTIONS: &str = "FRX";
+
+/// The pager command to use when `--pager` is set, or `None` if output should
+/// be printed directly. That is the case for machine readable output, when
+/// writing to a README, an updated file or an output file, when stdout is not
+/// a terminal, or when `$PAGER` has been set to an empty value.
+pub fn get_pager_command(args: &Args) -> Option<String> {
+    if !args.pager
+        || args.output_format.is_machine_readable()
+        || args.readme_args.updates_file()
+        || args.output_file.is_some()
+        || !io::stdout().is_terminal()
+    {
+        return None;
+    }
+    pager_command_from_env(env::var("PAGER").ok())
+}
+
+/// Once the output is piped, colored will no longer detect a terminal. Pagers
+/// like `less -R` render ANSI colors, so force them on unless the user has
+/// asked for something else, or disabled colors with `NO_COLOR`.
+pub fn configure_colors_for_pager(color_choice: ColorChoice) {
+    if color_choice == ColorChoice::CargoAuto && !no_color_from_env() {
+        colored::control::set_override(true);
+    }
+}
+
+/// Whether `NO_COLOR` is set to a non-empty value, following
+/// <https://no-color.org>
+fn no_color_from_env() -> bool {
+    env::var_os("NO_COLOR").is_some_and(|no_color| !no_color.is_empty())
+}
+
+/// Print the lines through the pager, falling back to printing directly if
+/// the pager can't be started
+pub fn print_lines_with_pager(pager_command: &str, lines: &[String]) {
+    let mut words = pager_command.split_whitespace();
+    let mut command = match words.next() {
+        Some(program) => Command::new(program),
+        None => {
+            print_lines(lines);
+            return;
+        }
+    };
+    command.args(words).stdin(Stdio::piped());
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", DEFAULT_LESS_OPTIONS);
+    }
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Failed to start pager `{}`: {}", pager_command, e);
+            print_lines(lines);
+            return;
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        for line in lines {
+            // The pager has been closed by the user, stop writing.
+            if writeln!(stdin, "{}", line).is_err() {
+                break;
+            }
+        }
+    }
+
+    if let Err(e) = child.wait() {
+        eprintln!("Failed to wait for pager `{}`: {}", pager_command, e);
+    }
+}
+
+fn pager_command_from_env(pager_env: Option<String>) -> Option<String> {
+    match pager_env {
+        Some(pager) if pager.trim().is_empty() => None,
+        Some(pager) => Some(pager),
+        None => Some(String::from(DEFAULT_PAGER)),
+    }
+}
+
+fn print_lines(lines: &[String]) {
+    for line in lines {
+        println!("{}", line);
+    }
+}
+
+#[cfg(test)]
+mod pager_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest(
+        input_pager_env,
+        expected_pager_command,
+        case(None, Some(String::from("less"))),
+        case(Some(String::from("")), None),
+        case(Some(String::from("  ")), None),
+        case(Some(String::from("more")), Some(String::from("more"))),
+        case(Some(String::from("less -S")), Some(String::from("less -S")))
+    )]
+    fn pager_command_from_env_test(
+        input_pager_env: Option<String>,
+        expected_pager_command: Option<String>,
+    ) {
+        assert_eq!(
+            pager_command_from_env(input_pager_env),
+            expected_pager_command
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use cargo::CliError;
+use cargo_geiger_serde::{Count, CounterBlock};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct PercentilesFileParseError {
+    pub line_number: usize,
+}
+
+impl Error for PercentilesFileParseError {}
+
+impl fmt::Display for PercentilesFileParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse line {}, expected `<name> <items> <unsafe items>`",
+            self.line_number
+        )
+    }
+}
+
+/// The snapshot read by `--percentiles` unless `--percentiles-file` is given
+const BUNDLED_SNAPSHOT: &str = include_str!("percentiles/snapshot.txt");
+
+/// The unsafe density of the crates of a snapshot of popular crates, read
+/// with `--percentiles`, grouped by size. The statistics are never fetched,
+/// so that a scan doesn't depend on the network.
+#[derive(Debug, Default)]
+pub struct EcosystemStats {
+    /// The sorted unsafe densities of the crates of each size class
+    size_class_densities: BTreeMap<u32, Vec<f64>>,
+}
+
+/// How the unsafe density of a crate compares to the crates of the snapshot
+/// of the same size class
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DensityComparison {
+    /// The share of unsafe items in all the items of the crate
+    pub density: f64,
+    /// The percentage of similar crates with a lower unsafe density
+    pub percentile: u8,
+    /// The median unsafe density of the similar crates
+    pub median_density: f64,
+}
+
+impl EcosystemStats {
+    /// Compare a crate of `items` functions, expressions, impls, traits and
+    /// methods, `unsafe_items` of which are unsafe, to the crates of the
+    /// snapshot having about as many items, or `None` if there are none
+    pub fn compare(
+        &self,
+        items: u64,
+        unsafe_items: u64,
+    ) -> Option<DensityComparison> {
+        if items == 0 {
+            return None;
+        }
+        let densities = self.size_class_densities.get(&size_class(items))?;
+        let density = unsafe_items as f64 / items as f64;
+        let lower_count = densities
+            .iter()
+            .take_while(|other_density| **other_density < density)
+            .count();
+        let middle = densities.len() / 2;
+        let median_density = if densities.len() % 2 == 0 {
+            (densities[middle - 1] + densities[middle]) / 2.0
+        } else {
+            densities[middle]
+        };
+        Some(DensityComparison {
+            density,
+            percentile: (lower_count * 100 / densities.len()) as u8,
+            median_density,
+        })
+    }
+
+    /// Compare a crate by all of its code, used by the build or not, as the
+    /// crates of the snapshot are counted whole
+    pub fn compare_counter_blocks(
+        &self,
+        used: &CounterBlock,
+        unused: &CounterBlock,
+    ) -> Option<DensityComparison> {
+        let mut counter_block = used.clone();
+        counter_block += unused.clone();
+        let counts = [
+            &counter_block.functions,
+            &counter_block.exprs,
+            &counter_block.item_impls,
+            &counter_block.item_traits,
+            &counter_block.methods,
+        ];
+        let total = |count: fn(&Count) -> u64| {
+            counts.iter().map(|c| count(c)).sum::<u64>()
+        };
+        self.compare(
+            total(|count| count.safe + count.unsafe_),
+            total(|count| count.unsafe_),
+        )
+    }
+}
+
+impl std::str::FromStr for EcosystemStats {
+    type Err = PercentilesFileParseError;
+
+    /// Empty lines and lines starting with `#` are ignored, as are the
+    /// crates without any items
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut size_class_densities = BTreeMap::<u32, Vec<f64>>::new();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parse_error = || PercentilesFileParseError {
+                line_number: index + 1,
+            };
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            let (items, unsafe_items) = match fields.as_slice() {
+                [_name, items, unsafe_items] => (
+                    items.parse::<u64>().map_err(|_| parse_error())?,
+                    unsafe_items.parse::<u64>().map_err(|_| parse_error())?,
+                ),
+                _ => return Err(parse_error()),
+            };
+            if unsafe_items > items {
+                return Err(parse_error());
+            }
+            if items > 0 {
+                size_class_densities
+                    .entry(size_class(items))
+                    .or_default()
+                    .push(unsafe_items as f64 / items as f64);
+            }
+        }
+        for densities in size_class_densities.values_mut() {
+            densities.sort_by(f64::total_cmp);
+        }
+        Ok(EcosystemStats {
+            size_class_densities,
+        })
+    }
+}
+
+/// The order of magnitude of the number of items of a crate, crates of the
+/// same class being of similar size
+fn size_class(items: u64) -> u32 {
+    items.max(1).ilog10()
+}
+
+/// The annotation of a crate using unsafe code in the tree, such as
+/// ` (unsafe density 3.1%, above 95% of similar crates, median 0.4%)`
+pub fn format_density_comparison(
+    density_comparison: Option<DensityComparison>,
+) -> String {
+    match density_comparison {
+        Some(density_comparison) => format!(
+            " (unsafe density {:.1}%, above {}% of similar crates, median {:.1}%)",
+            density_comparison.density * 100.0,
+            density_comparison.percentile,
+            density_comparison.median_density * 100.0
+        ),
+        None => String::from(" (unsafe density of similar crates unknown)"),
+    }
+}
+
+/// Read the snapshot given with `--percentiles-file`, or else the bundled
+/// one, or return `None` without `--percentiles`
+pub fn read_percentiles(
+    percentiles: bool,
+    percentiles_file: &Option<PathBuf>,
+) -> Result<Option<EcosystemStats>, CliError> {
+    if !percentiles {
+        return Ok(None);
+    }
+    match percentiles_file {
+        Some(percentiles_file) => {
+            read_percentiles_file(percentiles_file).map(Some)
+        }
+        None => Ok(Some(
+            BUNDLED_SNAPSHOT
+                .parse()
+                .expect("the bundled percentiles snapshot is valid"),
+        )),
+    }
+}
+
+fn read_percentiles_file(
+    percentiles_path: &Path,
+) -> Result<EcosystemStats, CliError> {
+    let percentiles_content =
+        fs::read_to_string(percentiles_path).map_err(|e| {
+            eprintln!(
+                "Failed to read ecosystem statistics from file: {}",
+                percentiles_path.display()
+            );
+            anyhow::Error::from(e)
+        })?;
+
+    percentiles_content.parse().map_err(|e| {
+        eprintln!(
+            "Failed to parse ecosystem statistics file: {}",
+            percentiles_path.display()
+        );
+        CliError::new(anyhow::Error::from(e), 1)
+    })
+}
+
+#[cfg(test)]
+mod percentiles_tests {
+    use super::*;
+
+    use rstest::*;
+    use std::str::FromStr;
+
+    #[rstest]
+    fn read_percentiles_test() {
+        assert!(read_percentiles(false, &None).unwrap().is_none());
+
+        let ecosystem_stats = read_percentiles(true, &None).unwrap().unwrap();
+        assert!(!ecosystem_stats.size_class_densities.is_empty());
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let percentiles_file = temp_dir.path().join("percentiles.txt");
+        fs::write(&percentiles_file, "a 100 0\n").unwrap();
+        let ecosystem_stats = read_percentiles(true, &Some(percentiles_file))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            ecosystem_stats.size_class_densities,
+            BTreeMap::from([(2, vec![0.0])])
+        );
+    }
+
+    fn create_ecosystem_stats() -> EcosystemStats {
+        EcosystemStats::from_str(
+            "# name items unsafe\n\n\
+            a 100 0\nb 200 2\nc 300 9\nd 400 40\n\
+            e 5000 500\nempty 0 0\n",
+        )
+        .unwrap()
+    }
+
+    #[rstest(
+        input_items,
+        input_unsafe_items,
+        expected_percentile,
+        case(500, 0, 0),
+        case(500, 5, 25),
+        case(500, 25, 75),
+        case(500, 500, 100)
+    )]
+    fn ecosystem_stats_compare_test(
+        input_items: u64,
+        input_unsafe_items: u64,
+        expected_percentile: u8,
+    ) {
+        let density_comparison = create_ecosystem_stats()
+            .compare(input_items, input_unsafe_items)
+            .unwrap();
+
+        assert_eq!(density_comparison.percentile, expected_percentile);
+        assert_eq!(density_comparison.median_density, 0.02);
+    }
+
+    #[rstest(input_items, case(0), case(5), case(50_000))]
+    fn ecosystem_stats_compare_test_no_similar_crates(input_items: u64) {
+        assert_eq!(create_ecosystem_stats().compare(input_items, 0), None);
+    }
+
+    #[rstest]
+    fn ecosystem_stats_compare_counter_blocks_test() {
+        let mut used = CounterBlock::default();
+        used.exprs.safe = 4000;
+        used.exprs.unsafe_ = 250;
+        let mut unused = CounterBlock::default();
+        unused.functions.unsafe_ = 750;
+
+        let density_comparison = create_ecosystem_stats()
+            .compare_counter_blocks(&used, &unused)
+            .unwrap();
+
+        assert_eq!(density_comparison.density, 0.2);
+        assert_eq!(density_comparison.percentile, 100);
+        assert_eq!(density_comparison.median_density, 0.1);
+    }
+
+    #[rstest(
+        input_percentiles_content,
+        expected_line_number,
+        case("foo", 1),
+        case("foo 1 0\nbar many 0", 2),
+        case("foo 10 x", 1),
+        case("foo 10 11", 1),
+        case("foo 1 0 2", 1)
+    )]
+    fn ecosystem_stats_from_str_test_invalid(
+        input_percentiles_content: &str,
+        expected_line_number: usize,
+    ) {
+        assert_eq!(
+            EcosystemStats::from_str(input_percentiles_content).unwrap_err(),
+            PercentilesFileParseError {
+                line_number: expected_line_number
+            }
+        );
+    }
+
+    #[rstest]
+    fn format_density_comparison_test() {
+        assert_eq!(
+            format_density_comparison(Some(DensityComparison {
+                density: 0.031,
+                percentile: 95,
+                median_density: 0.004,
+            })),
+            " (unsafe density 3.1%, above 95% of similar crates, median 0.4%)"
+        );
+        assert_eq!(
+            format_density_comparison(None),
+            " (unsafe density of similar crates unknown)"
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use crate::format::table::UNSAFE_CATEGORY_NAMES;
+
+use cargo_geiger_serde::CounterBlock;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub struct CategoryPolicyError {
+    pub crate_count: u64,
+}
+
+impl Error for CategoryPolicyError {}
+
+impl fmt::Display for CategoryPolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Found {} crates with unsafe usage in categories of the error tier",
+            self.crate_count
+        )
+    }
+}
+
+/// How unsafe usage in a category is treated by the `--category-policy` gate
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PolicyTier {
+    /// Fails the gate
+    #[default]
+    Error,
+    /// Is reported, without failing the gate
+    Warn,
+    Ignore,
+}
+
+impl FromStr for PolicyTier {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(PolicyTier::Error),
+            "warn" => Ok(PolicyTier::Warn),
+            "ignore" => Ok(PolicyTier::Ignore),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The tier of each unsafe category. Every category is in the error tier by
+/// default, so that any unsafe usage fails the gate unless told otherwise.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CategoryPolicy {
+    pub functions: PolicyTier,
+    pub exprs: PolicyTier,
+    pub item_impls: PolicyTier,
+    pub item_traits: PolicyTier,
+    pub methods: PolicyTier,
+    /// The `unsafe impl`s of `Send` and `Sync`, which are also in the tier of
+    /// the impls, a crate being in the stricter of the two. Unlike the other
+    /// categories, they are only in a tier if it is given.
+    pub send_sync_impls: Option<PolicyTier>,
+}
+
+impl CategoryPolicy {
+    /// The names of the categories of the tier with unsafe usage, such as
+    /// `expressions 3, impls 1`
+    fn tier_usage(&self, tier: PolicyTier, used: &CounterBlock) -> String {
+        let counts = [
+            (self.functions, used.functions.unsafe_),
+            (self.exprs, used.exprs.unsafe_),
+            (self.item_impls, used.item_impls.unsafe_),
+            (self.item_traits, used.item_traits.unsafe_),
+            (self.methods, used.methods.unsafe_),
+        ];
+        UNSAFE_CATEGORY_NAMES
+            .iter()
+            .zip(counts)
+            .filter(|(_, (category_tier, count))| {
+                *category_tier == tier && *count > 0
+            })
+            .map(|(name, (_, count))| format!("{} {}", name, count))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+impl FromStr for CategoryPolicy {
+    type Err = CategoryPolicyParseError;
+
+    /// Parse a comma separated list of `<category>=<tier>`, such as
+    /// `expressions=error,impls=warn,traits=ignore,send-sync-impls=error`.
+    /// Categories which are not listed stay in the error tier.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut category_policy = CategoryPolicy::default();
+        for category_tier in s.split(',') {
+            let parse_error = CategoryPolicyParseError {
+                category_tier: category_tier.to_string(),
+            };
+            let (category, tier) = match category_tier.split_once('=') {
+                Some((category, tier)) => (category, tier),
+                None => return Err(parse_error),
+            };
+            let tier = match PolicyTier::from_str(tier.trim()) {
+                Ok(tier) => tier,
+                Err(()) => return Err(parse_error),
+            };
+            let target = match category.trim() {
+                "functions" => &mut category_policy.functions,
+                "expressions" => &mut category_policy.exprs,
+                "impls" => &mut category_policy.item_impls,
+                "traits" => &mut category_policy.item_traits,
+                "methods" => &mut category_policy.methods,
+                "send-sync-impls" => {
+                    category_policy.send_sync_impls = Some(tier);
+                    continue;
+                }
+                _ => return Err(parse_error),
+            };
+            *target = tier;
+        }
+        Ok(category_policy)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct CategoryPolicyParseError {
+    pub category_tier: String,
+}
+
+impl Error for CategoryPolicyParseError {}
+
+impl fmt::Display for CategoryPolicyParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse category tier `{}`, expected `<category>=<tier>` with a category of {}, send-sync-impls and a tier of error, warn, ignore",
+            self.category_tier,
+            UNSAFE_CATEGORY_NAMES.join(", ")
+        )
+    }
+}
+
+/// A line for each crate with unsafe usage in the given tier, giving the
+/// categories of the tier it uses, ordered by name
+pub fn find_crates_in_tier(
+    category_policy: &CategoryPolicy,
+    tier: PolicyTier,
+    rows: &[(String, CounterBlock)],
+) -> Vec<String> {
+    let mut tier_lines = rows
+        .iter()
+        .filter_map(|(name, used)| {
+            let tier_usage = category_policy.tier_usage(tier, used);
+            (!tier_usage.is_empty())
+                .then(|| format!("    {}: {}", name, tier_usage))
+        })
+        .collect::<Vec<String>>();
+    tier_lines.sort();
+    tier_lines.dedup();
+    tier_lines
+}
+
+/// A line for each crate with `unsafe impl`s of `Send` or `Sync`, when they
+/// are in the given tier, ordered by name
+pub fn find_send_sync_crates_in_tier(
+    category_policy: &CategoryPolicy,
+    tier: PolicyTier,
+    send_sync_impl_rows: &[(String, u64)],
+) -> Vec<String> {
+    if category_policy.send_sync_impls != Some(tier) {
+        return vec![];
+    }
+    let mut tier_lines = send_sync_impl_rows
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(name, count)| format!("    {}: send-sync impls {}", name, count))
+        .collect::<Vec<String>>();
+    tier_lines.sort();
+    tier_lines.dedup();
+    tier_lines
+}
+
+#[cfg(test)]
+mod policy_tests {
+    use super::*;
+
+    use cargo_geiger_serde::Count;
+    use rstest::*;
+
+    #[rstest]
+    fn category_policy_from_str_test() {
+        assert_eq!(
+            CategoryPolicy::from_str("impls=warn, traits = ignore"),
+            Ok(CategoryPolicy {
+                item_impls: PolicyTier::Warn,
+                item_traits: PolicyTier::Ignore,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            CategoryPolicy::from_str("impls=ignore,send-sync-impls=error"),
+            Ok(CategoryPolicy {
+                item_impls: PolicyTier::Ignore,
+                send_sync_impls: Some(PolicyTier::Error),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[rstest(
+        input_category_policy,
+        case("impls"),
+        case("exprs=warn"),
+        case("impls=fail"),
+        case("")
+    )]
+    fn category_policy_from_str_test_invalid(input_category_policy: &str) {
+        assert!(CategoryPolicy::from_str(input_category_policy).is_err());
+    }
+
+    #[rstest(
+        input_tier,
+        expected_tier_lines,
+        case(
+            PolicyTier::Error,
+            vec![String::from("    foo 0.1.0: functions 1, expressions 2")]
+        ),
+        case(
+            PolicyTier::Warn,
+            vec![String::from("    foo 0.1.0: impls 4")]
+        ),
+        case(PolicyTier::Ignore, vec![])
+    )]
+    fn find_crates_in_tier_test(
+        input_tier: PolicyTier,
+        expected_tier_lines: Vec<String>,
+    ) {
+        let category_policy =
+            CategoryPolicy::from_str("impls=warn,traits=ignore").unwrap();
+        let count = |unsafe_| Count { safe: 0, unsafe_ };
+        let rows = vec![
+            (
+                String::from("foo 0.1.0"),
+                CounterBlock {
+                    functions: count(1),
+                    exprs: count(2),
+                    item_impls: count(4),
+                    item_traits: count(0),
+                    methods: count(0),
+                },
+            ),
+            (String::from("bar 0.1.0"), CounterBlock::default()),
+        ];
+
+        assert_eq!(
+            find_crates_in_tier(&category_policy, input_tier, &rows),
+            expected_tier_lines
+        );
+    }
+
+    #[rstest(
+        input_category_policy,
+        input_tier,
+        expected_tier_lines,
+        case(
+            "impls=ignore,send-sync-impls=error",
+            PolicyTier::Error,
+            vec![String::from("    foo 0.1.0: send-sync impls 2")]
+        ),
+        case("impls=ignore,send-sync-impls=error", PolicyTier::Warn, vec![]),
+        case("impls=error", PolicyTier::Error, vec![])
+    )]
+    fn find_send_sync_crates_in_tier_test(
+        input_category_policy: &str,
+        input_tier: PolicyTier,
+        expected_tier_lines: Vec<String>,
+    ) {
+        let category_policy =
+            CategoryPolicy::from_str(input_category_policy).unwrap();
+        let send_sync_impl_rows = vec![
+            (String::from("foo 0.1.0"), 2),
+            (String::from("bar 0.1.0"), 0),
+        ];
+
+        assert_eq!(
+            find_send_sync_crates_in_tier(
+                &category_policy,
+                input_tier,
+                &send_sync_impl_rows
+            ),
+            expected_tier_lines
+        );
+    }
+}
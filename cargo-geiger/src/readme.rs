@@ -2,6 +2,7 @@ use crate::args::ReadmeArgs;
 
 use cargo::{CliError, CliResult};
 use regex::Regex;
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Error, Write};
 use std::path::{Path, PathBuf};
@@ -12,6 +13,18 @@ pub const README_FILENAME: &str = "README.md";
 const CARGO_GEIGER_SAFETY_REPORT_SECTION_HEADER: &str =
     "## Cargo Geiger Safety Report";
 
+/// Only one of `--begin-marker` and `--end-marker` was given
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ReadmeMarkersError;
+
+impl std::error::Error for ReadmeMarkersError {}
+
+impl fmt::Display for ReadmeMarkersError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected both `--begin-marker` and `--end-marker`")
+    }
+}
+
 /// Taking a `PathBuf` pointing to the README location, and a `&Vec<String>` containing the result
 /// of a scan, either create a section containing the scan result if one does not exist, or replace
 /// the section if it already exists
@@ -24,7 +37,7 @@ pub fn create_or_replace_section_in_readme(
 
     if !readme_path_buf.exists() {
         eprintln!(
-            "File: {} does not exist. To construct a Cargo Geiger Safety Report section, please first create a README.",
+            "File: {} does not exist. To construct a Cargo Geiger Safety Report section, please first create the file.",
             readme_path_buf.to_str().unwrap()
         );
         return CliResult::Err(CliError::code(1));
@@ -39,7 +52,29 @@ pub fn create_or_replace_section_in_readme(
             anyhow::Error::from(e)
         })?;
 
-    update_readme_content(readme_args, &mut readme_content, scan_output_lines);
+    match (&readme_args.begin_marker, &readme_args.end_marker) {
+        (Some(begin_marker), Some(end_marker)) => {
+            if !update_marked_content(
+                begin_marker,
+                end_marker,
+                &mut readme_content,
+                scan_output_lines,
+            ) {
+                eprintln!(
+                    "File: {} has the marker {:?}, but no {:?} after it.",
+                    readme_path_buf.to_str().unwrap(),
+                    begin_marker,
+                    end_marker
+                );
+                return CliResult::Err(CliError::code(1));
+            }
+        }
+        _ => update_readme_content(
+            readme_args,
+            &mut readme_content,
+            scan_output_lines,
+        ),
+    }
 
     write_lines_to_file(&readme_content, &readme_path_buf).map_err(|e| {
         eprintln!(
@@ -101,12 +136,17 @@ fn construct_regex_expression_for_section_header(
     }
 }
 
-/// Returns the `PathBuf` passed in as an argument value if one exists, otherwise
-/// returns the `PathBuf` to a file `README.md` in the current directory
+/// Returns the `PathBuf` passed in as an argument value if one exists, preferring
+/// `--update-file` to `--readme-path`, otherwise returns the `PathBuf` to a file
+/// `README.md` in the current directory
 fn get_readme_path_buf_from_arguments_or_default(
     readme_args: &ReadmeArgs,
 ) -> PathBuf {
-    match &readme_args.readme_path {
+    match readme_args
+        .update_file
+        .as_ref()
+        .or(readme_args.readme_path.as_ref())
+    {
         Some(readme_path) => readme_path.to_path_buf(),
         None => {
             let mut current_dir_path_buf = std::env::current_dir().unwrap();
@@ -127,6 +167,49 @@ fn read_file_contents(path: &Path) -> Result<Vec<String>, Error> {
         .collect::<Vec<String>>())
 }
 
+/// Update the lines between the begin and end markers with a Scan Result, keeping the markers.
+/// When the begin marker isn't present, the markers and the Scan Result are added at the end of
+/// the content. Returns false, leaving the content as it is, if the begin marker is present but
+/// isn't followed by the end marker
+fn update_marked_content(
+    begin_marker: &str,
+    end_marker: &str,
+    content: &mut Vec<String>,
+    scan_result: &[String],
+) -> bool {
+    let mut section = vec![String::from("```")];
+    section.extend(scan_result.iter().cloned());
+    section.push(String::from("```"));
+
+    let begin_line_number = content
+        .iter()
+        .position(|line| line.trim() == begin_marker.trim());
+    match begin_line_number {
+        Some(begin_line_number) => {
+            let end_line_number = content[begin_line_number + 1..]
+                .iter()
+                .position(|line| line.trim() == end_marker.trim())
+                .map(|offset| begin_line_number + 1 + offset);
+            match end_line_number {
+                Some(end_line_number) => {
+                    content.splice(
+                        begin_line_number + 1..end_line_number,
+                        section,
+                    );
+                    true
+                }
+                None => false,
+            }
+        }
+        None => {
+            content.push(begin_marker.to_string());
+            content.extend(section);
+            content.push(end_marker.to_string());
+            true
+        }
+    }
+}
+
 /// Update the content of a README.md with a Scan Result. When the section doesn't exist, it will
 /// be created with an `h2` level header, otherwise it will preserve the level of the existing
 /// header
@@ -415,6 +498,68 @@ mod readme_tests {
         assert_eq!(readme_path_buf, path_buf);
     }
 
+    #[rstest]
+    fn get_readme_path_buf_from_arguments_or_default_test_update_file() {
+        let path_buf = PathBuf::from("/test/SECURITY.md");
+
+        let readme_args = ReadmeArgs {
+            readme_path: Some(PathBuf::from("/test/README.md")),
+            update_file: Some(path_buf.clone()),
+            ..Default::default()
+        };
+
+        let readme_path_buf =
+            get_readme_path_buf_from_arguments_or_default(&readme_args);
+
+        assert_eq!(readme_path_buf, path_buf);
+    }
+
+    #[rstest(
+        input_content,
+        expected_updated,
+        expected_content,
+        case(
+            vec!["# Security", "text"],
+            true,
+            vec!["# Security", "text", "<!-- begin -->", "```", "scan result", "```", "<!-- end -->"]
+        ),
+        case(
+            vec!["# Security", "<!-- begin -->", "old scan result", "<!-- end -->", "text"],
+            true,
+            vec!["# Security", "<!-- begin -->", "```", "scan result", "```", "<!-- end -->", "text"]
+        ),
+        case(
+            vec!["# Security", "  <!-- begin -->", "<!-- end -->"],
+            true,
+            vec!["# Security", "  <!-- begin -->", "```", "scan result", "```", "<!-- end -->"]
+        ),
+        case(
+            vec!["# Security", "<!-- begin -->", "old scan result"],
+            false,
+            vec!["# Security", "<!-- begin -->", "old scan result"]
+        )
+    )]
+    fn update_marked_content_test(
+        input_content: Vec<&str>,
+        expected_updated: bool,
+        expected_content: Vec<&str>,
+    ) {
+        let mut content = input_content
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        let updated = update_marked_content(
+            "<!-- begin -->",
+            "<!-- end -->",
+            &mut content,
+            &[String::from("scan result")],
+        );
+
+        assert_eq!(updated, expected_updated);
+        assert_eq!(content, expected_content);
+    }
+
     #[rstest(
         input_readme_args,
         expected_section_header,
@@ -0,0 +1,351 @@
+use cargo::core::{Dependency, PackageSet, SourceId, SourceMap};
+use cargo::{CliError, Config};
+use cargo_geiger_serde::Source;
+use cargo_metadata::semver::{Version, VersionReq};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::task::Poll;
+use tempfile::TempDir;
+use walkdir::WalkDir;
+
+/// A package of crates.io given with `--package-spec`, such as
+/// `serde_json@1.0.120`, or `serde_json` for its latest version
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PackageSpec {
+    pub name: String,
+    /// A complete version such as `1.0.120` is matched exactly, as
+    /// `=1.0.120` rather than `^1.0.120`
+    pub version_req: Option<String>,
+}
+
+impl FromStr for PackageSpec {
+    type Err = PackageSpecParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, version) = match s.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (s, None),
+        };
+        if name.is_empty() {
+            return Err(PackageSpecParseError);
+        }
+        let version_req = match version {
+            Some(version) if Version::parse(version).is_ok() => {
+                Some(format!("={}", version))
+            }
+            Some(version) if VersionReq::parse(version).is_ok() => {
+                Some(version.to_string())
+            }
+            Some(_) => return Err(PackageSpecParseError),
+            None => None,
+        };
+        Ok(PackageSpec {
+            name: name.to_string(),
+            version_req,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PackageSpecParseError;
+
+impl Error for PackageSpecParseError {}
+
+impl fmt::Display for PackageSpecParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "package spec not valid, expected <NAME> or <NAME>@<VERSION>"
+        )
+    }
+}
+
+/// The names of the registries of the cargo configuration, such as the
+/// `my-registry` of `[registries.my-registry]`, by the URL of their index.
+/// Cargo metadata only gives the URL of the registry of each crate.
+#[derive(Debug, Default)]
+pub struct RegistryNames {
+    index_to_name: HashMap<String, String>,
+}
+
+impl RegistryNames {
+    /// The name of the registry a crate comes from, `crates.io` for
+    /// crates.io, or `None` if it doesn't come from a registry. A registry
+    /// missing from the configuration is named after the URL of its index.
+    pub fn get(&self, source: &Source) -> Option<String> {
+        match source {
+            Source::Registry { .. } if source.is_crates_io() => {
+                Some(String::from("crates.io"))
+            }
+            Source::Registry { url, .. } => Some(
+                self.index_to_name
+                    .get(&index_key(url.as_str()))
+                    .cloned()
+                    .unwrap_or_else(|| url.to_string()),
+            ),
+            Source::Git { .. } | Source::Path(_) => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RegistryConfig {
+    index: Option<String>,
+}
+
+/// Read the names of the registries of the cargo configuration. If it fails
+/// to be read, the registries are named after their index.
+pub fn read_registry_names(config: &Config) -> RegistryNames {
+    let registries = match config
+        .get::<Option<HashMap<String, RegistryConfig>>>("registries")
+    {
+        Ok(registries) => registries.unwrap_or_default(),
+        Err(e) => {
+            eprintln!(
+                "WARNING: Failed to read the registries of the cargo \
+                configuration: {}",
+                e
+            );
+            HashMap::new()
+        }
+    };
+    RegistryNames {
+        index_to_name: registries
+            .into_iter()
+            .filter_map(|(name, registry_config)| {
+                Some((index_key(&registry_config.index?), name))
+            })
+            .collect(),
+    }
+}
+
+/// The index of a registry as configured may differ from the one in the
+/// metadata by its `sparse+` prefix and trailing slash
+fn index_key(index: &str) -> String {
+    index
+        .trim_start_matches("sparse+")
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// A package given with `--package-spec`, copied into a temporary directory
+/// which is removed when this is dropped
+pub struct RegistryPackageDirectory {
+    package_path: PathBuf,
+    // Only held to remove the directory on drop.
+    _temp_dir: TempDir,
+}
+
+impl RegistryPackageDirectory {
+    /// The manifest of the package, which is scanned in place of
+    /// `--manifest-path`
+    pub fn manifest_path(&self) -> PathBuf {
+        self.package_path.join("Cargo.toml")
+    }
+
+    /// The root of the package
+    pub fn package_path(&self) -> &Path {
+        &self.package_path
+    }
+}
+
+/// Download the newest version of the package given with `--package-spec`
+/// matching its version from crates.io, and copy it into a temporary
+/// directory, where it is built without writing into the cache of cargo.
+/// Returns `None` if no package is given.
+pub fn download_registry_package(
+    package_spec: &Option<PackageSpec>,
+    config: &Config,
+) -> Result<Option<RegistryPackageDirectory>, CliError> {
+    let package_spec = match package_spec {
+        Some(package_spec) => package_spec,
+        None => return Ok(None),
+    };
+
+    let source_id = SourceId::crates_io(config)?;
+    let dependency = Dependency::parse(
+        package_spec.name.as_str(),
+        package_spec.version_req.as_deref(),
+        source_id,
+    )?;
+    let package = {
+        let _lock = config.acquire_package_cache_lock()?;
+        let mut source = source_id.load(config, &HashSet::new())?;
+        let summaries = loop {
+            match source.query_vec(&dependency)? {
+                Poll::Ready(summaries) => break summaries,
+                Poll::Pending => source.block_until_ready()?,
+            }
+        };
+        let package_id =
+            match summaries.iter().map(|summary| summary.package_id()).max() {
+                Some(package_id) => package_id,
+                None => {
+                    eprintln!(
+                        "No version of {} found on crates.io matching: {}",
+                        package_spec.name,
+                        package_spec.version_req.as_deref().unwrap_or("*")
+                    );
+                    return Err(CliError::code(1));
+                }
+            };
+        let mut source_map = SourceMap::new();
+        source_map.insert(source);
+        let package_set = PackageSet::new(&[package_id], source_map, config)?;
+        let package = package_set.get_one(package_id).map_err(|e| {
+            eprintln!("Failed to download package: {}", package_id);
+            CliError::new(e, 1)
+        })?;
+        package.clone()
+    };
+
+    let temp_dir = TempDir::new().map_err(|e| {
+        eprintln!("Failed to create a directory to copy the package into");
+        anyhow::Error::from(e)
+    })?;
+    let package_path = temp_dir.path().join(format!(
+        "{}-{}",
+        package.name(),
+        package.version()
+    ));
+    copy_directory(package.root(), &package_path).map_err(|e| {
+        eprintln!(
+            "Failed to copy package: {} into: {}",
+            package.root().display(),
+            package_path.display()
+        );
+        anyhow::Error::from(e)
+    })?;
+
+    Ok(Some(RegistryPackageDirectory {
+        package_path,
+        _temp_dir: temp_dir,
+    }))
+}
+
+fn copy_directory(source: &Path, destination: &Path) -> std::io::Result<()> {
+    for entry in WalkDir::new(source) {
+        let entry = entry?;
+        let relative_path = entry.path().strip_prefix(source).unwrap();
+        let destination_path = destination.join(relative_path);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&destination_path)?;
+        } else {
+            fs::copy(entry.path(), &destination_path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod registry_tests {
+    use super::*;
+
+    use rstest::*;
+    use url::Url;
+
+    #[rstest(
+        input_package_spec,
+        expected_package_spec,
+        case(
+            "serde_json@1.0.120",
+            Ok(PackageSpec {
+                name: String::from("serde_json"),
+                version_req: Some(String::from("=1.0.120")),
+            })
+        ),
+        case(
+            "serde_json@^1.0",
+            Ok(PackageSpec {
+                name: String::from("serde_json"),
+                version_req: Some(String::from("^1.0")),
+            })
+        ),
+        case(
+            "serde_json",
+            Ok(PackageSpec {
+                name: String::from("serde_json"),
+                version_req: None,
+            })
+        ),
+        case("serde_json@one", Err(PackageSpecParseError)),
+        case("@1.0.0", Err(PackageSpecParseError))
+    )]
+    fn package_spec_from_str_test(
+        input_package_spec: &str,
+        expected_package_spec: Result<PackageSpec, PackageSpecParseError>,
+    ) {
+        assert_eq!(
+            PackageSpec::from_str(input_package_spec),
+            expected_package_spec
+        );
+    }
+
+    #[rstest(
+        input_source,
+        expected_registry_name,
+        case(
+            Source::Registry {
+                name: String::from("crates.io"),
+                url: Url::parse("https://index.crates.io/").unwrap()
+            },
+            Some("crates.io")
+        ),
+        case(
+            Source::Registry {
+                name: String::from("registry.example.com"),
+                url: Url::parse("https://registry.example.com/index/").unwrap()
+            },
+            Some("my-registry")
+        ),
+        case(
+            Source::Registry {
+                name: String::from("other.example.com"),
+                url: Url::parse("https://other.example.com/index").unwrap()
+            },
+            Some("https://other.example.com/index")
+        ),
+        case(
+            Source::Path(Url::parse("file:///foo/bar").unwrap()),
+            None
+        )
+    )]
+    fn registry_names_get_test(
+        input_source: Source,
+        expected_registry_name: Option<&str>,
+    ) {
+        let registry_names = RegistryNames {
+            index_to_name: vec![(
+                index_key("sparse+https://registry.example.com/index"),
+                String::from("my-registry"),
+            )]
+            .into_iter()
+            .collect(),
+        };
+
+        assert_eq!(
+            registry_names.get(&input_source).as_deref(),
+            expected_registry_name
+        );
+    }
+
+    #[rstest]
+    fn copy_directory_test() {
+        let source = TempDir::new().unwrap();
+        fs::create_dir_all(source.path().join("src")).unwrap();
+        fs::write(source.path().join("Cargo.toml"), "[package]").unwrap();
+        fs::write(source.path().join("src/lib.rs"), "").unwrap();
+        let destination = TempDir::new().unwrap();
+        let destination_path = destination.path().join("copy");
+
+        copy_directory(source.path(), &destination_path).unwrap();
+
+        assert!(destination_path.join("Cargo.toml").is_file());
+        assert!(destination_path.join("src/lib.rs").is_file());
+    }
+}
@@ -1,31 +1,62 @@
+mod cache;
+mod dangerous_apis;
 mod default;
 mod find;
 mod forbid;
+mod local_targets;
+mod relative_paths;
 mod rs_file;
+mod safety_docs;
+mod send_sync;
+mod snippets;
+mod target_breakdown;
 
+use crate::advisory::{read_advisories, Advisories};
 use crate::args::Args;
-use crate::format::print_config::PrintConfig;
+use crate::audits::{read_audits_file, Audits};
+use crate::baseline::read_baseline;
+use crate::checksum::{find_checksum_statuses, ChecksumStatuses};
+use crate::downloads::{read_downloads, DownloadCounts};
+use crate::expand::{find_expanded_counts, ExpandedCounts};
+use crate::explain::{construct_explanation_lines, find_package, CrateSpec};
+use crate::format::print_config::{OutputFormat, PrintConfig};
+use crate::gate::DetectionOutcome;
 use crate::graph::Graph;
+use crate::history::{read_history, UnsafeHistory};
+use crate::inspect::construct_inspect_lines;
+use crate::license::{
+    package_matches_license_filter, print_license_filter_warnings,
+};
 use crate::mapping::{
     CargoMetadataParameters, ToCargoGeigerDependencyKind,
     ToCargoGeigerPackageId,
 };
+use crate::order::{read_order_file, CrateOrder};
+use crate::percentiles::{read_percentiles, EcosystemStats};
+use crate::security_policy::{find_security_policies, SecurityPolicies};
+use crate::source_kind::package_matches_source_filter;
+use crate::source_map::{resolve_source_map, SourceMap};
 
+pub use cache::remove_watch_entries;
 pub use rs_file::RsFileMetricsWrapper;
+pub use safety_docs::{SafetyDocsForbidOnlyError, UndocumentedUnsafeError};
 
-use default::scan_unsafe;
+use default::{
+    scan_package, scan_to_safety_report, scan_to_workspace, scan_unsafe,
+};
 use forbid::scan_forbid_unsafe;
 
 use cargo::core::Workspace;
 use cargo::{CliError, Config};
 use cargo_geiger_serde::{
-    CounterBlock, DependencyKind, PackageInfo, UnsafeInfo,
+    CounterBlock, DependencyKind, FfiCount, PackageInfo, SafetyReport,
+    ScanWarning, UnsafeInfo,
 };
 use cargo_metadata::PackageId;
 use krates::NodeId;
 use petgraph::prelude::NodeIndex;
 use petgraph::visit::EdgeRef;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
 use std::fmt;
 use std::path::PathBuf;
@@ -43,8 +74,69 @@ impl fmt::Display for FoundWarningsError {
     }
 }
 
+#[derive(Debug)]
+pub struct UnsafeDetectedError {
+    pub crate_count: u64,
+}
+
+impl Error for UnsafeDetectedError {}
+
+impl fmt::Display for UnsafeDetectedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Found {} crates using unsafe code", self.crate_count)
+    }
+}
+
+#[derive(Debug)]
+pub struct FailThresholdError {
+    pub fail_threshold: f32,
+    pub unsafe_ratio: f32,
+}
+
+impl Error for FailThresholdError {}
+
+impl fmt::Display for FailThresholdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The unsafe ratio of {:.2}% is above the threshold of {:.2}%",
+            100.0 * self.unsafe_ratio,
+            100.0 * self.fail_threshold
+        )
+    }
+}
+
+/// A `SafetyReport`, along with the .rs files which were used by the build
+pub struct ScanReport {
+    pub report: SafetyReport,
+    pub rs_files_used: HashSet<PathBuf>,
+}
+
 pub struct ScanResult {
+    /// The number of crates with unsafe usage in a category of the error tier
+    /// of `--category-policy`
+    pub category_policy_error_count: u64,
+    /// The number of crates whose source doesn't match `--verify-checksums`,
+    /// filled in by the scan functions once the output has been rendered
+    pub checksum_mismatch_count: u64,
+    /// What the scan found, for `--exit-code-mode detection`, or `None` for
+    /// the outputs which don't count the unsafe usage of every crate
+    pub detection_outcome: Option<DetectionOutcome>,
+    /// The unsafe ratio of the table footer, when it is above
+    /// `--fail-threshold`
+    pub over_fail_threshold_ratio: Option<f32>,
+    /// The number of crates with a weighted unsafe score above `--max-score`
+    pub over_max_score_count: u64,
     pub scan_output_lines: Vec<String>,
+    /// The number of crates using unsafe code without an audit, counted with
+    /// `--require-audits`
+    pub unaudited_crate_count: u64,
+    pub undocumented_unsafe_count: u64,
+    /// The number of crates using unsafe code, counted with `--deny-unsafe`
+    pub unsafe_crate_count: u64,
+    /// The number of crates with unsafe usage above `--max-unsafe`, and of
+    /// totals above `--max-total-unsafe`
+    pub unsafe_limit_violation_count: u64,
     pub warning_count: u64,
 }
 
@@ -54,6 +146,12 @@ pub struct ScanResult {
 pub struct GeigerContext {
     pub package_id_to_metrics: HashMap<PackageId, PackageMetrics>,
     pub ignored_paths: HashSet<PathBuf>,
+    /// The crates with files which failed to be scanned, and whose unsafe
+    /// usage is only partially counted
+    pub partially_scanned_package_ids: HashSet<PackageId>,
+    /// The files which failed to be scanned, or were scanned lossily, in the
+    /// order they were found. They are only reported with `--lossy`.
+    pub scan_warnings: Vec<ScanWarning>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -73,9 +171,23 @@ pub enum ScanMode {
 }
 
 pub struct ScanParameters<'a> {
+    pub advisories: Option<&'a Advisories>,
     pub args: &'a Args,
+    pub audits: Option<&'a Audits>,
+    /// The `--baseline` report the `PrComment` output is compared to
+    pub baseline: Option<&'a SafetyReport>,
+    /// The statuses of the crates against `--verify-checksums`
+    pub checksum_statuses: Option<&'a ChecksumStatuses>,
     pub config: &'a Config,
+    pub crate_order: Option<&'a CrateOrder>,
+    pub download_counts: Option<&'a DownloadCounts>,
+    /// The `--percentiles` snapshot the unsafe density of crates is compared to
+    pub ecosystem_stats: Option<&'a EcosystemStats>,
+    pub expanded_counts: Option<&'a ExpandedCounts>,
     pub print_config: &'a PrintConfig,
+    pub security_policies: Option<&'a SecurityPolicies>,
+    pub source_map: &'a SourceMap,
+    pub unsafe_history: Option<&'a UnsafeHistory>,
 }
 
 pub fn scan(
@@ -87,14 +199,71 @@ pub fn scan(
     workspace: &Workspace,
 ) -> Result<ScanResult, CliError> {
     let print_config = PrintConfig::new(args)?;
+    print_license_filter_warnings(
+        cargo_metadata_parameters,
+        &print_config.license_filter,
+        graph,
+    );
+    let unsafe_history = read_history(&args.history)?;
+    let download_counts = read_downloads(args.downloads, &args.downloads_file)?;
+    let ecosystem_stats =
+        read_percentiles(args.percentiles, &args.percentiles_file)?;
+    let crate_order = read_order_file(&args.order_file)?;
+    let source_map = resolve_source_map(
+        &args.source_map,
+        cargo_metadata_parameters.metadata,
+    )?;
+    let security_policies = find_security_policies(
+        args.check_security_policy,
+        config,
+        cargo_metadata_parameters.metadata,
+    );
+    let checksum_statuses = find_checksum_statuses(
+        &args.checksum_args,
+        cargo_metadata_parameters.metadata,
+        graph,
+    )?;
+    let advisories = read_advisories(args.audit, &args.advisory_db, config)?;
+    let audits = read_audits_file(&args.audits)?;
+    let expanded_counts = find_expanded_counts(
+        args.expand,
+        &args.cargo_lock_args(),
+        &args.features_args,
+        print_config.include_tests,
+        cargo_metadata_parameters.metadata,
+    );
+    let baseline = read_pr_comment_baseline(args)?;
 
     let scan_parameters = ScanParameters {
+        advisories: advisories.as_ref(),
         args,
+        audits: audits.as_ref(),
+        baseline: baseline.as_ref(),
+        checksum_statuses: checksum_statuses.as_ref(),
         config,
+        crate_order: crate_order.as_ref(),
+        download_counts: download_counts.as_ref(),
+        ecosystem_stats: ecosystem_stats.as_ref(),
+        expanded_counts: expanded_counts.as_ref(),
         print_config: &print_config,
+        security_policies: security_policies.as_ref(),
+        source_map: &source_map,
+        unsafe_history: unsafe_history.as_ref(),
     };
 
-    if args.forbid_only {
+    let scan_result = if args.forbid_only {
+        if !args.also_emit.is_empty() {
+            eprintln!(
+                "NOTE: `--also-emit` doesn't apply to `--forbid-only`. No \
+                report is written."
+            );
+        }
+        if args.log_sink.is_some() {
+            eprintln!(
+                "NOTE: `--log-sink` doesn't apply to `--forbid-only`. No \
+                record is written."
+            );
+        }
         scan_forbid_unsafe(
             cargo_metadata_parameters,
             graph,
@@ -109,6 +278,292 @@ pub fn scan(
             &scan_parameters,
             workspace,
         )
+    };
+    scan_result.map(|scan_result| ScanResult {
+        checksum_mismatch_count: mismatched_crate_count(
+            checksum_statuses.as_ref(),
+        ),
+        ..scan_result
+    })
+}
+
+/// Scan for unsafe usage like `scan`, but return the results as a
+/// `SafetyReport`, regardless of the output format
+pub fn scan_to_report(
+    args: &Args,
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    config: &Config,
+    graph: &Graph,
+    root_package_id: PackageId,
+    workspace: &Workspace,
+) -> Result<ScanReport, CliError> {
+    let print_config = PrintConfig::new(args)?;
+    print_license_filter_warnings(
+        cargo_metadata_parameters,
+        &print_config.license_filter,
+        graph,
+    );
+    let unsafe_history = read_history(&args.history)?;
+    let download_counts = read_downloads(args.downloads, &args.downloads_file)?;
+    let ecosystem_stats =
+        read_percentiles(args.percentiles, &args.percentiles_file)?;
+    let crate_order = read_order_file(&args.order_file)?;
+    let source_map = resolve_source_map(
+        &args.source_map,
+        cargo_metadata_parameters.metadata,
+    )?;
+    let security_policies = find_security_policies(
+        args.check_security_policy,
+        config,
+        cargo_metadata_parameters.metadata,
+    );
+    let checksum_statuses = find_checksum_statuses(
+        &args.checksum_args,
+        cargo_metadata_parameters.metadata,
+        graph,
+    )?;
+    let advisories = read_advisories(args.audit, &args.advisory_db, config)?;
+    let audits = read_audits_file(&args.audits)?;
+    let expanded_counts = find_expanded_counts(
+        args.expand,
+        &args.cargo_lock_args(),
+        &args.features_args,
+        print_config.include_tests,
+        cargo_metadata_parameters.metadata,
+    );
+
+    let scan_parameters = ScanParameters {
+        advisories: advisories.as_ref(),
+        args,
+        audits: audits.as_ref(),
+        baseline: None,
+        checksum_statuses: checksum_statuses.as_ref(),
+        config,
+        crate_order: crate_order.as_ref(),
+        download_counts: download_counts.as_ref(),
+        ecosystem_stats: ecosystem_stats.as_ref(),
+        expanded_counts: expanded_counts.as_ref(),
+        print_config: &print_config,
+        security_policies: security_policies.as_ref(),
+        source_map: &source_map,
+        unsafe_history: unsafe_history.as_ref(),
+    };
+
+    scan_to_safety_report(
+        cargo_metadata_parameters,
+        graph,
+        root_package_id,
+        &scan_parameters,
+        workspace,
+    )
+}
+
+/// Scan every member given with `--workspace` in a single build, and print
+/// one report of the crates of their dependency graphs
+pub fn scan_workspace_members(
+    args: &Args,
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    config: &Config,
+    graph: &Graph,
+    member_package_ids: &[PackageId],
+    workspace: &Workspace,
+) -> Result<ScanResult, CliError> {
+    if args.forbid_only {
+        eprintln!(
+            "NOTE: `--forbid-only` doesn't apply to `--workspace`. All files \
+            are scanned."
+        );
+    }
+    let print_config = PrintConfig::new(args)?;
+    print_license_filter_warnings(
+        cargo_metadata_parameters,
+        &print_config.license_filter,
+        graph,
+    );
+    let unsafe_history = read_history(&args.history)?;
+    let download_counts = read_downloads(args.downloads, &args.downloads_file)?;
+    let ecosystem_stats =
+        read_percentiles(args.percentiles, &args.percentiles_file)?;
+    let crate_order = read_order_file(&args.order_file)?;
+    let source_map = resolve_source_map(
+        &args.source_map,
+        cargo_metadata_parameters.metadata,
+    )?;
+    let security_policies = find_security_policies(
+        args.check_security_policy,
+        config,
+        cargo_metadata_parameters.metadata,
+    );
+    let checksum_statuses = find_checksum_statuses(
+        &args.checksum_args,
+        cargo_metadata_parameters.metadata,
+        graph,
+    )?;
+    let advisories = read_advisories(args.audit, &args.advisory_db, config)?;
+    let audits = read_audits_file(&args.audits)?;
+    let expanded_counts = find_expanded_counts(
+        args.expand,
+        &args.cargo_lock_args(),
+        &args.features_args,
+        print_config.include_tests,
+        cargo_metadata_parameters.metadata,
+    );
+    let baseline = read_pr_comment_baseline(args)?;
+
+    let scan_parameters = ScanParameters {
+        advisories: advisories.as_ref(),
+        args,
+        audits: audits.as_ref(),
+        baseline: baseline.as_ref(),
+        checksum_statuses: checksum_statuses.as_ref(),
+        config,
+        crate_order: crate_order.as_ref(),
+        download_counts: download_counts.as_ref(),
+        ecosystem_stats: ecosystem_stats.as_ref(),
+        expanded_counts: expanded_counts.as_ref(),
+        print_config: &print_config,
+        security_policies: security_policies.as_ref(),
+        source_map: &source_map,
+        unsafe_history: unsafe_history.as_ref(),
+    };
+
+    scan_to_workspace(
+        cargo_metadata_parameters,
+        graph,
+        member_package_ids,
+        &scan_parameters,
+        workspace,
+    )
+    .map(|scan_result| ScanResult {
+        checksum_mismatch_count: mismatched_crate_count(
+            checksum_statuses.as_ref(),
+        ),
+        ..scan_result
+    })
+}
+
+fn mismatched_crate_count(checksum_statuses: Option<&ChecksumStatuses>) -> u64 {
+    checksum_statuses.map_or(0, ChecksumStatuses::mismatched_crate_count)
+}
+
+/// Scan only the files of the crate given with `--explain`, and break down
+/// how its status was determined
+pub fn explain(
+    args: &Args,
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    config: &Config,
+    crate_spec: &CrateSpec,
+    workspace: &Workspace,
+) -> Result<Vec<String>, CliError> {
+    let (package, scan_details) = scan_single_crate(
+        args,
+        cargo_metadata_parameters,
+        config,
+        crate_spec,
+        workspace,
+    )?;
+    let package_metrics = single_crate_metrics(package, &scan_details)?;
+    Ok(construct_explanation_lines(
+        package,
+        package_metrics,
+        &scan_details.rs_files_used,
+    ))
+}
+
+/// Scan only the files of the crate given to `inspect`, and break down its
+/// unsafe code by module
+pub fn inspect(
+    args: &Args,
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    config: &Config,
+    crate_spec: &CrateSpec,
+    workspace: &Workspace,
+) -> Result<Vec<String>, CliError> {
+    let (package, scan_details) = scan_single_crate(
+        args,
+        cargo_metadata_parameters,
+        config,
+        crate_spec,
+        workspace,
+    )?;
+    let package_metrics = single_crate_metrics(package, &scan_details)?;
+    Ok(construct_inspect_lines(
+        package,
+        package_metrics,
+        &scan_details.rs_files_used,
+    ))
+}
+
+/// The single crate matching the spec, and the details of the scan of its
+/// files alone
+fn scan_single_crate<'a>(
+    args: &Args,
+    cargo_metadata_parameters: &CargoMetadataParameters<'a>,
+    config: &Config,
+    crate_spec: &CrateSpec,
+    workspace: &Workspace,
+) -> Result<(&'a cargo_metadata::Package, ScanDetails), CliError> {
+    let package = find_package(crate_spec, cargo_metadata_parameters.metadata)?;
+    let print_config = PrintConfig::new(args)?;
+    let source_map = resolve_source_map(
+        &args.source_map,
+        cargo_metadata_parameters.metadata,
+    )?;
+
+    let scan_parameters = ScanParameters {
+        advisories: None,
+        args,
+        audits: None,
+        baseline: None,
+        checksum_statuses: None,
+        config,
+        crate_order: None,
+        download_counts: None,
+        ecosystem_stats: None,
+        expanded_counts: None,
+        print_config: &print_config,
+        security_policies: None,
+        source_map: &source_map,
+        unsafe_history: None,
+    };
+
+    let scan_details = scan_package(
+        cargo_metadata_parameters,
+        package,
+        &scan_parameters,
+        workspace,
+    )?;
+    Ok((package, scan_details))
+}
+
+fn single_crate_metrics<'a>(
+    package: &cargo_metadata::Package,
+    scan_details: &'a ScanDetails,
+) -> Result<&'a PackageMetrics, CliError> {
+    scan_details
+        .geiger_context
+        .package_id_to_metrics
+        .get(&package.id)
+        .ok_or_else(|| {
+            eprintln!("No metrics found for package: {}", package.id);
+            CliError::code(1)
+        })
+}
+
+/// The `--baseline` report, read when the `PrComment` output, or one written
+/// with `--also-emit`, compares the scan to it
+fn read_pr_comment_baseline(
+    args: &Args,
+) -> Result<Option<SafetyReport>, CliError> {
+    let emits_pr_comment = args.output_format == OutputFormat::PrComment
+        || args.also_emit.iter().any(|emitted_report| {
+            emitted_report.output_format == OutputFormat::PrComment
+        });
+    match &args.baseline_args.baseline {
+        Some(baseline_path) if emits_pr_comment => {
+            read_baseline(baseline_path).map(Some)
+        }
+        _ => Ok(None),
     }
 }
 
@@ -122,21 +577,51 @@ pub fn unsafe_stats(
     // classified as forbidding unsafe code, all entry point source
     // files must declare `forbid(unsafe_code)`. Either a crate
     // forbids all unsafe code or it allows it _to some degree_.
-    let forbids_unsafe = package_metrics
+    // `deny(unsafe_code)` is accepted as well, but is tracked separately
+    // since it can be overridden further down in the crate, and so is
+    // `cfg_attr(<condition>, forbid(unsafe_code))`, since it only applies
+    // under its condition.
+    let entry_points = package_metrics
         .rs_path_to_metrics
         .iter()
         .filter(|(_, v)| v.is_crate_entry_point)
-        .all(|(_, v)| v.metrics.forbids_unsafe);
+        .collect::<Vec<_>>();
+    let forbids_unsafe =
+        entry_points.iter().all(|(_, v)| v.metrics.forbids_unsafe);
+    let denies_unsafe = !forbids_unsafe
+        && entry_points
+            .iter()
+            .all(|(_, v)| v.metrics.forbids_unsafe || v.metrics.denies_unsafe);
+    let conditionally_forbids_unsafe = !forbids_unsafe
+        && !denies_unsafe
+        && entry_points.iter().all(|(_, v)| {
+            v.metrics.forbids_unsafe
+                || v.metrics.denies_unsafe
+                || v.metrics.conditionally_forbids_unsafe
+        });
 
     let mut used = CounterBlock::default();
     let mut unused = CounterBlock::default();
+    let mut ffi = FfiCount::default();
+    let mut detector_findings = BTreeMap::<String, u64>::new();
+    let mut lines_of_code = 0;
 
     for (path_buf, rs_file_metrics_wrapper) in
         &package_metrics.rs_path_to_metrics
     {
+        let ffi_items = rs_file_metrics_wrapper.metrics.ffi_items;
         let target = if rs_files_used.contains(path_buf) {
+            ffi.used += ffi_items;
+            lines_of_code += rs_file_metrics_wrapper.metrics.lines;
+            for (detector_name, findings) in
+                &rs_file_metrics_wrapper.metrics.detector_findings
+            {
+                *detector_findings.entry(detector_name.clone()).or_insert(0) +=
+                    findings;
+            }
             &mut used
         } else {
+            ffi.unused += ffi_items;
             &mut unused
         };
         *target += rs_file_metrics_wrapper.metrics.counters.clone();
@@ -145,12 +630,33 @@ pub fn unsafe_stats(
         used,
         unused,
         forbids_unsafe,
+        denies_unsafe,
+        conditionally_forbids_unsafe,
+        ffi,
+        unsafe_introduced_at: None,
+        downloads: None,
+        has_security_policy: None,
+        checksum_mismatch: None,
+        advisories: None,
+        unsafe_findings: None,
+        dangerous_api_calls: None,
+        expanded: None,
+        source_kind: None,
+        registry: None,
+        detector_findings: (!detector_findings.is_empty())
+            .then_some(detector_findings),
+        unsafe_density_percentile: None,
+        lines_of_code: Some(lines_of_code),
     }
 }
 
 struct ScanDetails {
     rs_files_used: HashSet<PathBuf>,
     geiger_context: GeigerContext,
+    /// The files of the examples and benches of workspace members selected
+    /// with `--scan-examples` and `--scan-benches`, which are left out of
+    /// `rs_files_used`
+    local_target_files_used: HashSet<PathBuf>,
 }
 
 fn construct_rs_files_used_lines(
@@ -196,6 +702,7 @@ fn package_metrics(
     cargo_metadata_parameters: &CargoMetadataParameters,
     geiger_context: &GeigerContext,
     graph: &Graph,
+    print_config: &PrintConfig,
     root_package_id: PackageId,
 ) -> Vec<(PackageInfo, Option<PackageMetrics>)> {
     let mut package_metrics =
@@ -212,7 +719,9 @@ fn package_metrics(
         {
             let mut package_info = PackageInfo::new(package);
 
-            for edge in graph.graph.edges(index) {
+            for edge in graph.graph.edges(index).filter(|edge| {
+                print_config.include_build_deps.allows(*edge.weight())
+            }) {
                 let dep_index = edge.target();
 
                 let dependency_kind_option =
@@ -229,6 +738,20 @@ fn package_metrics(
                 );
             }
 
+            // The dependencies are still walked, so crates with a matching
+            // license are found below the ones without.
+            if !package_matches_license_filter(
+                cargo_metadata_parameters,
+                &print_config.license_filter,
+                &package_id,
+            ) || !package_matches_source_filter(
+                cargo_metadata_parameters,
+                &print_config.source_filter,
+                &package_id,
+            ) {
+                continue;
+            }
+
             match geiger_context.package_id_to_metrics.get(&package_id) {
                 Some(m) => {
                     package_metrics.push((package_info, Some(m.clone())))
@@ -317,6 +840,7 @@ mod scan_tests {
         let mut graph = Graph {
             graph: Default::default(),
             nodes: Default::default(),
+            features: Default::default(),
         };
         graph.graph.add_node(package_id);
 
@@ -454,6 +978,8 @@ mod scan_tests {
             .cloned()
             .collect(),
             ignored_paths: HashSet::new(),
+            partially_scanned_package_ids: HashSet::new(),
+            scan_warnings: vec![],
         };
 
         let rs_files_used = input_rs_files_used_vec.iter().cloned().collect();
@@ -474,6 +1000,11 @@ mod scan_tests {
         let stats = unsafe_stats(&Default::default(), &Default::default());
         let expected = UnsafeInfo {
             forbids_unsafe: true,
+            unsafe_introduced_at: None,
+            downloads: None,
+            has_security_policy: None,
+            advisories: None,
+            lines_of_code: Some(0),
             ..Default::default()
         };
         assert_eq!(stats, expected);
@@ -490,7 +1021,32 @@ mod scan_tests {
                 .build(),
         )]);
         let stats = unsafe_stats(&metrics, &set_of_paths(&["foo.rs"]));
-        assert!(stats.forbids_unsafe)
+        assert!(stats.forbids_unsafe);
+        assert!(!stats.denies_unsafe)
+    }
+
+    #[rstest]
+    fn unsafe_stats_report_denies_unsafe_if_one_entry_point_denies_unsafe() {
+        let metrics = metrics_from_iter(vec![
+            (
+                "foo.rs",
+                MetricsBuilder::default()
+                    .forbids_unsafe(true)
+                    .set_is_crate_entry_point(true)
+                    .build(),
+            ),
+            (
+                "bar.rs",
+                MetricsBuilder::default()
+                    .denies_unsafe(true)
+                    .set_is_crate_entry_point(true)
+                    .build(),
+            ),
+        ]);
+        let stats =
+            unsafe_stats(&metrics, &set_of_paths(&["foo.rs", "bar.rs"]));
+        assert!(!stats.forbids_unsafe);
+        assert!(stats.denies_unsafe)
     }
 
     #[rstest]
@@ -517,6 +1073,43 @@ mod scan_tests {
         assert!(!stats.forbids_unsafe)
     }
 
+    #[rstest(
+        input_bar_conditionally_forbids_unsafe,
+        expected_conditionally_forbids_unsafe,
+        case(true, true),
+        case(false, false)
+    )]
+    fn unsafe_stats_report_conditionally_forbids_unsafe(
+        input_bar_conditionally_forbids_unsafe: bool,
+        expected_conditionally_forbids_unsafe: bool,
+    ) {
+        let metrics = metrics_from_iter(vec![
+            (
+                "foo.rs",
+                MetricsBuilder::default()
+                    .forbids_unsafe(true)
+                    .set_is_crate_entry_point(true)
+                    .build(),
+            ),
+            (
+                "bar.rs",
+                MetricsBuilder::default()
+                    .conditionally_forbids_unsafe(
+                        input_bar_conditionally_forbids_unsafe,
+                    )
+                    .set_is_crate_entry_point(true)
+                    .build(),
+            ),
+        ]);
+        let stats =
+            unsafe_stats(&metrics, &set_of_paths(&["foo.rs", "bar.rs"]));
+        assert!(!stats.forbids_unsafe);
+        assert_eq!(
+            stats.conditionally_forbids_unsafe,
+            expected_conditionally_forbids_unsafe
+        )
+    }
+
     #[rstest]
     fn unsafe_stats_accumulate_counters() {
         let metrics = metrics_from_iter(vec![
@@ -539,6 +1132,18 @@ mod scan_tests {
         assert_eq!(stats.unused.functions.unsafe_, 110);
     }
 
+    #[rstest]
+    fn unsafe_stats_count_lines_of_code_of_used_files() {
+        let metrics = metrics_from_iter(vec![
+            ("foo.rs", MetricsBuilder::default().lines(120).build()),
+            ("bar.rs", MetricsBuilder::default().lines(30).build()),
+            ("baz.rs", MetricsBuilder::default().lines(1000).build()),
+        ]);
+        let stats =
+            unsafe_stats(&metrics, &set_of_paths(&["foo.rs", "bar.rs"]));
+        assert_eq!(stats.lines_of_code, Some(150));
+    }
+
     fn metrics_from_iter<I, P>(it: I) -> PackageMetrics
     where
         I: IntoIterator<Item = (P, RsFileMetricsWrapper)>,
@@ -566,6 +1171,16 @@ mod scan_tests {
     }
 
     impl MetricsBuilder {
+        fn conditionally_forbids_unsafe(mut self, yes: bool) -> Self {
+            self.inner.metrics.conditionally_forbids_unsafe = yes;
+            self
+        }
+
+        fn denies_unsafe(mut self, yes: bool) -> Self {
+            self.inner.metrics.denies_unsafe = yes;
+            self
+        }
+
         fn forbids_unsafe(mut self, yes: bool) -> Self {
             self.inner.metrics.forbids_unsafe = yes;
             self
@@ -576,6 +1191,11 @@ mod scan_tests {
             self
         }
 
+        fn lines(mut self, lines: u64) -> Self {
+            self.inner.metrics.lines = lines;
+            self
+        }
+
         fn set_is_crate_entry_point(mut self, yes: bool) -> Self {
             self.inner.is_crate_entry_point = yes;
             self
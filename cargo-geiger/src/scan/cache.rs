@@ -0,0 +1,392 @@
+use crate::checksum::compute_source_checksum;
+use crate::format::print_config::ScanCacheMode;
+use crate::scan::PackageMetrics;
+
+use cargo_metadata::Package;
+use cargo_util::Sha256;
+use geiger::detector::Detector;
+use geiger::{IncludeTests, RsFileMetrics};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Override the directory of the cache, unless `--cache-dir` is given
+const CACHE_DIR_ENVS: [&str; 2] =
+    ["CARGO_GEIGER_CACHE_DIR", "GEIGER_CACHE_DIR"];
+
+/// The cache entries are only valid for the version of cargo-geiger which
+/// wrote them, as the metrics of a file may change with it.
+const CACHE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The directory of the cache the entries of path packages are kept in with
+/// `--watch`. It is emptied when a session starts, as every edit of a package
+/// leaves an entry behind.
+const WATCH_DIRECTORY: &str = "watch";
+
+/// The metrics of each `.rs` file of a package, by its path relative to the
+/// package root with `/` separators
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CachedPackage {
+    pub rs_files: BTreeMap<String, RsFileMetrics>,
+}
+
+/// The cache entry of a package, and its content if it was read
+pub struct CacheEntry {
+    cached_package: Option<CachedPackage>,
+    entry_path: PathBuf,
+    package_root: PathBuf,
+}
+
+impl CacheEntry {
+    /// Whether the files of the package were found in the cache, in which
+    /// case the entry isn't written again
+    pub fn is_cached(&self) -> bool {
+        self.cached_package.is_some()
+    }
+
+    /// The cached metrics of a file of the package, given by its canonical
+    /// path
+    pub fn rs_file_metrics(&self, path: &Path) -> Option<&RsFileMetrics> {
+        let cached_package = self.cached_package.as_ref()?;
+        cached_package
+            .rs_files
+            .get(&relative_path(&self.package_root, path)?)
+    }
+}
+
+/// The on-disk cache of the scan results of packages from crates.io, whose
+/// published sources never change for a given version. An entry is keyed by
+/// the package id, the checksum of the package sources, whether tests are
+/// included and the version of cargo-geiger, so a changed source is never
+/// read from a stale entry. Each entry is a file of its own, written at once,
+/// so runs sharing a cache never read a partly written entry. Failing to read
+/// or write the cache only costs a scan.
+pub struct ScanCache {
+    directory: PathBuf,
+    mode: ScanCacheMode,
+    /// Path packages are cached too, in the `WATCH_DIRECTORY`
+    caches_path_packages: bool,
+}
+
+impl ScanCache {
+    /// The cache in the directory given with `--cache-dir`, or else in
+    /// `$CARGO_GEIGER_CACHE_DIR` or `$GEIGER_CACHE_DIR`, or else in the
+    /// `cargo-geiger` directory of `$XDG_CACHE_HOME` or `~/.cache`, unless it
+    /// is disabled or no such directory is known
+    pub fn new(
+        mode: ScanCacheMode,
+        cache_dir: Option<&Path>,
+        caches_path_packages: bool,
+    ) -> Option<Self> {
+        if mode == ScanCacheMode::Disabled {
+            return None;
+        }
+        Some(ScanCache {
+            directory: cache_directory(cache_dir, |key| std::env::var_os(key))?,
+            mode,
+            caches_path_packages,
+        })
+    }
+
+    /// The entry of a package from crates.io, or of a path package with
+    /// `--watch`, read unless `--refresh` is set. Other packages, whose
+    /// sources may change, have no entry. Scans with other detectors have
+    /// entries of their own.
+    pub fn entry(
+        &self,
+        package: &Package,
+        include_tests: IncludeTests,
+        detectors: &[Box<dyn Detector>],
+    ) -> Option<CacheEntry> {
+        let directory = match &package.source {
+            Some(source) if source.is_crates_io() => self.directory.clone(),
+            None if self.caches_path_packages => {
+                self.directory.join(WATCH_DIRECTORY)
+            }
+            _ => return None,
+        };
+        let package_root =
+            package.manifest_path.parent()?.canonicalize().ok()?;
+        let checksum = compute_source_checksum(&package_root).ok()?;
+        let entry_path = directory.join(format!(
+            "{}.json",
+            cache_key(
+                &package.id.repr,
+                &checksum,
+                include_tests,
+                &detectors
+                    .iter()
+                    .map(|detector| detector.name())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            )
+        ));
+        let cached_package = match self.mode {
+            ScanCacheMode::Enabled => fs::read_to_string(&entry_path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok()),
+            _ => None,
+        };
+        Some(CacheEntry {
+            cached_package,
+            entry_path,
+            package_root,
+        })
+    }
+
+    /// Write the metrics of every file of the package to its entry. Nothing
+    /// is written if a file is outside of the package root.
+    pub fn write(
+        &self,
+        cache_entry: &CacheEntry,
+        package_metrics: &PackageMetrics,
+    ) {
+        let rs_files = package_metrics
+            .rs_path_to_metrics
+            .iter()
+            .map(|(path_buf, rs_file_metrics_wrapper)| {
+                Some((
+                    relative_path(&cache_entry.package_root, path_buf)?,
+                    rs_file_metrics_wrapper.metrics.clone(),
+                ))
+            })
+            .collect::<Option<BTreeMap<_, _>>>();
+        let rs_files = match rs_files {
+            Some(rs_files) => rs_files,
+            None => return,
+        };
+        let content =
+            serde_json::to_string(&CachedPackage { rs_files }).unwrap();
+        // The entry is written to a file of this process first, and renamed
+        // into place, as another run may be reading or writing it.
+        let temp_path = cache_entry
+            .entry_path
+            .with_extension(format!("json.{}.tmp", process::id()));
+        let written = cache_entry
+            .entry_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&temp_path, content))
+            .and_then(|_| fs::rename(&temp_path, &cache_entry.entry_path));
+        if written.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        if let Err(e) = written {
+            eprintln!(
+                "WARNING: Failed to write scan cache entry: {}, {}",
+                cache_entry.entry_path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Remove the entries of the path packages left by a previous `--watch`
+/// session
+pub fn remove_watch_entries(cache_dir: Option<&Path>) {
+    if let Some(directory) =
+        cache_directory(cache_dir, |key| std::env::var_os(key))
+    {
+        let _ = fs::remove_dir_all(directory.join(WATCH_DIRECTORY));
+    }
+}
+
+fn cache_directory<F>(cache_dir: Option<&Path>, env_var: F) -> Option<PathBuf>
+where
+    F: Fn(&str) -> Option<OsString>,
+{
+    if let Some(cache_dir) = cache_dir {
+        return Some(cache_dir.to_path_buf());
+    }
+    let non_empty = |key| env_var(key).filter(|value| !value.is_empty());
+    if let Some(cache_dir) =
+        CACHE_DIR_ENVS.iter().find_map(|key| non_empty(key))
+    {
+        return Some(PathBuf::from(cache_dir));
+    }
+    let cache_home =
+        non_empty("XDG_CACHE_HOME").map(PathBuf::from).or_else(|| {
+            non_empty("HOME").map(|home| Path::new(&home).join(".cache"))
+        })?;
+    Some(cache_home.join("cargo-geiger"))
+}
+
+fn cache_key(
+    package_id_repr: &str,
+    checksum: &str,
+    include_tests: IncludeTests,
+    detector_names: &str,
+) -> String {
+    let include_tests = match include_tests {
+        IncludeTests::Yes => "tests",
+        IncludeTests::No => "no-tests",
+    };
+    let mut sha256 = Sha256::new();
+    for part in [
+        package_id_repr,
+        checksum,
+        include_tests,
+        detector_names,
+        CACHE_VERSION,
+    ] {
+        sha256.update(part.as_bytes()).update(&[0]);
+    }
+    sha256.finish_hex()
+}
+
+fn relative_path(package_root: &Path, path: &Path) -> Option<String> {
+    Some(
+        path.strip_prefix(package_root)
+            .ok()?
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use super::*;
+
+    use crate::scan::rs_file::RsFileMetricsWrapper;
+
+    use rstest::*;
+
+    #[rstest(
+        input_cache_dir,
+        input_env_vars,
+        expected_cache_directory,
+        case(
+            None,
+            vec![("CARGO_GEIGER_CACHE_DIR", "/cache"), ("HOME", "/home/foo")],
+            Some("/cache")
+        ),
+        case(
+            None,
+            vec![("GEIGER_CACHE_DIR", "/cache"), ("HOME", "/home/foo")],
+            Some("/cache")
+        ),
+        case(
+            Some("/shared"),
+            vec![("CARGO_GEIGER_CACHE_DIR", "/cache")],
+            Some("/shared")
+        ),
+        case(
+            None,
+            vec![("XDG_CACHE_HOME", "/xdg"), ("HOME", "/home/foo")],
+            Some("/xdg/cargo-geiger")
+        ),
+        case(
+            None,
+            vec![("XDG_CACHE_HOME", ""), ("HOME", "/home/foo")],
+            Some("/home/foo/.cache/cargo-geiger")
+        ),
+        case(None, vec![], None)
+    )]
+    fn cache_directory_test(
+        input_cache_dir: Option<&str>,
+        input_env_vars: Vec<(&str, &str)>,
+        expected_cache_directory: Option<&str>,
+    ) {
+        let env_var = |key: &str| {
+            input_env_vars
+                .iter()
+                .find(|(env_key, _)| *env_key == key)
+                .map(|(_, value)| OsString::from(value))
+        };
+
+        assert_eq!(
+            cache_directory(input_cache_dir.map(Path::new), env_var),
+            expected_cache_directory.map(PathBuf::from)
+        );
+    }
+
+    #[rstest]
+    fn cache_key_test() {
+        let cache_key_with = |checksum, include_tests| {
+            cache_key(
+                "foo 0.1.0 (registry+...)",
+                checksum,
+                include_tests,
+                "unsafe",
+            )
+        };
+
+        assert_eq!(
+            cache_key_with("abc", IncludeTests::No),
+            cache_key_with("abc", IncludeTests::No)
+        );
+        assert_ne!(
+            cache_key_with("abc", IncludeTests::No),
+            cache_key_with("abd", IncludeTests::No)
+        );
+        assert_ne!(
+            cache_key_with("abc", IncludeTests::No),
+            cache_key_with("abc", IncludeTests::Yes)
+        );
+        assert_ne!(
+            cache_key_with("abc", IncludeTests::No),
+            cache_key(
+                "foo 0.1.0 (registry+...)",
+                "abc",
+                IncludeTests::No,
+                "unsafe,panic-in-unsafe"
+            )
+        );
+    }
+
+    #[rstest]
+    fn scan_cache_write_test() {
+        let cache_directory = tempfile::tempdir().unwrap();
+        let package_root = tempfile::tempdir().unwrap();
+        let scan_cache = ScanCache {
+            directory: cache_directory.path().join("cargo-geiger"),
+            mode: ScanCacheMode::Enabled,
+            caches_path_packages: true,
+        };
+        let cache_entry = CacheEntry {
+            cached_package: None,
+            entry_path: scan_cache
+                .directory
+                .join(WATCH_DIRECTORY)
+                .join("entry.json"),
+            package_root: package_root.path().to_path_buf(),
+        };
+        let lib_path = package_root.path().join("src").join("lib.rs");
+        let mut rs_file_metrics = RsFileMetrics::default();
+        rs_file_metrics.counters.exprs.unsafe_ = 2;
+        let mut package_metrics = PackageMetrics::default();
+        package_metrics.rs_path_to_metrics.insert(
+            lib_path.clone(),
+            RsFileMetricsWrapper {
+                metrics: rs_file_metrics.clone(),
+                is_crate_entry_point: true,
+            },
+        );
+
+        scan_cache.write(&cache_entry, &package_metrics);
+
+        let cached_package = serde_json::from_str::<CachedPackage>(
+            &fs::read_to_string(&cache_entry.entry_path).unwrap(),
+        )
+        .unwrap();
+        let read_cache_entry = CacheEntry {
+            cached_package: Some(cached_package),
+            ..cache_entry
+        };
+        assert!(read_cache_entry.is_cached());
+        assert_eq!(
+            read_cache_entry.rs_file_metrics(&lib_path),
+            Some(&rs_file_metrics)
+        );
+        assert_eq!(
+            read_cache_entry
+                .rs_file_metrics(&package_root.path().join("src/main.rs")),
+            None
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::args::Args;
+
+use super::{GeigerContext, PackageMetrics};
+
+use cargo_metadata::Metadata;
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+/// The APIs looked for with `--dangerous-apis`, to which `--dangerous-api`
+/// adds. A path is matched by the calls ending with it, or with its last
+/// segments when it is imported, so `mem::transmute` matches both
+/// `std::mem::transmute` and `transmute`. A method is given as `.` and its
+/// name, and casts to raw pointers as `as *const` and `as *mut`.
+pub const DEFAULT_DANGEROUS_APIS: [&str; 14] = [
+    "mem::transmute",
+    "mem::transmute_copy",
+    "mem::zeroed",
+    "mem::uninitialized",
+    "ptr::copy_nonoverlapping",
+    "ptr::copy",
+    "slice::from_raw_parts",
+    "slice::from_raw_parts_mut",
+    ".assume_init",
+    ".get_unchecked",
+    ".get_unchecked_mut",
+    ".set_len",
+    "as *const",
+    "as *mut",
+];
+
+/// The APIs to look for, the default ones followed by those given with
+/// `--dangerous-api`, or `None` if neither it nor `--dangerous-apis`
+/// is given
+pub fn dangerous_apis(args: &Args) -> Option<Vec<String>> {
+    if !args.dangerous_apis && args.dangerous_api.is_empty() {
+        return None;
+    }
+    let mut dangerous_apis = DEFAULT_DANGEROUS_APIS
+        .iter()
+        .map(|api| api.to_string())
+        .collect::<Vec<_>>();
+    for api in &args.dangerous_api {
+        if !dangerous_apis.contains(api) {
+            dangerous_apis.push(api.clone());
+        }
+    }
+    Some(dangerous_apis)
+}
+
+/// The number of calls to each of the APIs in the unsafe code of the files of
+/// a crate used by the build, leaving out the APIs which aren't called
+pub fn dangerous_api_calls(
+    dangerous_apis: &[String],
+    package_metrics: &PackageMetrics,
+    rs_files_used: &HashSet<PathBuf>,
+) -> BTreeMap<String, u64> {
+    let mut dangerous_api_calls = BTreeMap::new();
+    for (_, rs_file_metrics_wrapper) in package_metrics
+        .rs_path_to_metrics
+        .iter()
+        .filter(|(path_buf, _)| rs_files_used.contains(*path_buf))
+    {
+        for (call, count) in &rs_file_metrics_wrapper.metrics.unsafe_calls {
+            if let Some(api) =
+                dangerous_apis.iter().find(|api| is_call_of(call, api))
+            {
+                *dangerous_api_calls.entry(api.clone()).or_insert(0) += count;
+            }
+        }
+    }
+    dangerous_api_calls
+}
+
+/// The lines printed below the tree with `--dangerous-apis`, a line for each
+/// crate calling any of the APIs, sorted by crate name
+pub fn construct_dangerous_api_lines(
+    dangerous_apis: &[String],
+    geiger_context: &GeigerContext,
+    metadata: &Metadata,
+    rs_files_used: &HashSet<PathBuf>,
+) -> Vec<String> {
+    let mut crate_lines = metadata
+        .packages
+        .iter()
+        .filter_map(|package| {
+            let dangerous_api_calls = dangerous_api_calls(
+                dangerous_apis,
+                geiger_context.package_id_to_metrics.get(&package.id)?,
+                rs_files_used,
+            );
+            if dangerous_api_calls.is_empty() {
+                return None;
+            }
+            Some(format!(
+                "    {} {}: {}",
+                package.name,
+                package.version,
+                format_dangerous_api_calls(&dangerous_api_calls)
+            ))
+        })
+        .collect::<Vec<_>>();
+    crate_lines.sort();
+
+    let mut dangerous_api_lines = vec![String::from(
+        "Calls to dangerous APIs in unsafe code, per crate:",
+    )];
+    if crate_lines.is_empty() {
+        dangerous_api_lines.push(String::from("    None"));
+    }
+    dangerous_api_lines.extend(crate_lines);
+    dangerous_api_lines.push(String::new());
+    dangerous_api_lines
+}
+
+fn format_dangerous_api_calls(
+    dangerous_api_calls: &BTreeMap<String, u64>,
+) -> String {
+    dangerous_api_calls
+        .iter()
+        .map(|(api, count)| format!("{} {}", api, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Whether the call, as counted in `RsFileMetrics::unsafe_calls`, is of the
+/// API: the segments of one of them end with those of the other
+fn is_call_of(call: &str, api: &str) -> bool {
+    if api.starts_with('.') || api.starts_with("as ") {
+        return call == api;
+    }
+    let call_segments = call.split("::").collect::<Vec<_>>();
+    let api_segments = api.split("::").collect::<Vec<_>>();
+    call_segments.ends_with(&api_segments)
+        || api_segments.ends_with(&call_segments)
+}
+
+#[cfg(test)]
+mod dangerous_apis_tests {
+    use super::*;
+
+    use crate::scan::RsFileMetricsWrapper;
+
+    use geiger::RsFileMetrics;
+    use rstest::*;
+    use std::collections::HashMap;
+
+    #[rstest(
+        input_call,
+        input_api,
+        expected_is_call_of,
+        case("std::mem::transmute", "mem::transmute", true),
+        case("transmute", "mem::transmute", true),
+        case("mem::transmute", "mem::transmute", true),
+        case("mem::transmute_copy", "mem::transmute", false),
+        case("my_mem::transmute", "mem::transmute", false),
+        case(".get_unchecked", ".get_unchecked", true),
+        case("get_unchecked", ".get_unchecked", false),
+        case("as *mut", "as *mut", true),
+        case("as *const", "as *mut", false)
+    )]
+    fn is_call_of_test(
+        input_call: &str,
+        input_api: &str,
+        expected_is_call_of: bool,
+    ) {
+        assert_eq!(is_call_of(input_call, input_api), expected_is_call_of);
+    }
+
+    #[rstest]
+    fn dangerous_api_calls_test() {
+        let used_path = PathBuf::from("/foo/src/lib.rs");
+        let unused_path = PathBuf::from("/foo/src/unused.rs");
+        let rs_file_metrics_wrapper =
+            |calls: &[(&str, u64)]| RsFileMetricsWrapper {
+                metrics: RsFileMetrics {
+                    unsafe_calls: calls
+                        .iter()
+                        .map(|(call, count)| (call.to_string(), *count))
+                        .collect(),
+                    ..Default::default()
+                },
+                is_crate_entry_point: false,
+            };
+        let package_metrics = PackageMetrics {
+            rs_path_to_metrics: HashMap::from([
+                (
+                    used_path.clone(),
+                    rs_file_metrics_wrapper(&[
+                        ("std::mem::transmute", 2),
+                        ("transmute", 1),
+                        (".get_unchecked", 1),
+                        (".as_ptr", 3),
+                    ]),
+                ),
+                (unused_path, rs_file_metrics_wrapper(&[(".set_len", 1)])),
+            ]),
+        };
+        let dangerous_apis = DEFAULT_DANGEROUS_APIS
+            .iter()
+            .map(|api| api.to_string())
+            .collect::<Vec<_>>();
+
+        let dangerous_api_calls = dangerous_api_calls(
+            &dangerous_apis,
+            &package_metrics,
+            &HashSet::from([used_path]),
+        );
+
+        assert_eq!(
+            dangerous_api_calls,
+            BTreeMap::from([
+                (String::from(".get_unchecked"), 1),
+                (String::from("mem::transmute"), 3),
+            ])
+        );
+        assert_eq!(
+            format_dangerous_api_calls(&dangerous_api_calls),
+            ".get_unchecked 1, mem::transmute 3"
+        );
+    }
+}
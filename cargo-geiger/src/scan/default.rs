@@ -1,26 +1,90 @@
+mod aligned;
+mod feature_report;
+mod folded;
+mod heatmap;
+mod interactive;
+mod license_groups;
+mod source_groups;
+mod summary;
 mod table;
+mod why;
+mod workspace;
 
-use crate::args::FeaturesArgs;
+use crate::allowlist::Allowlist;
+use crate::args::{
+    FeaturesArgs, LocalTargetsArgs, Profile, TargetArgs, WorkspaceArgs,
+};
+use crate::audits::Audits;
+use crate::format::csv::create_csv_lines;
+use crate::format::cyclonedx::create_cyclonedx_line;
+use crate::format::dot::create_dot_lines;
+use crate::format::github_actions::create_github_actions_lines;
+use crate::format::html::{create_html_lines, CrateFiles};
+use crate::format::json::create_json_line;
+use crate::format::json_lines::create_json_lines;
+use crate::format::mermaid::create_mermaid_lines;
+use crate::format::pr_comment::create_pr_comment_lines;
 use crate::format::print_config::OutputFormat;
+use crate::format::prometheus::create_prometheus_lines;
+use crate::format::sarif::{artifact_uri, create_sarif_line, UnsafeLocations};
+use crate::format::shields_io::ShieldsIoEndpoint;
+use crate::format::toml::create_toml_lines;
+use crate::gate::{DetectionOutcome, ExitCodeMode};
 use crate::graph::Graph;
-use crate::mapping::CargoMetadataParameters;
+use crate::limits::find_unsafe_limit_violations;
+use crate::log_sink::{construct_log_records, emit_log_records};
+use crate::mapping::{CargoMetadataParameters, ToCargoGeigerPackageId};
+use crate::policy::{
+    find_crates_in_tier, find_send_sync_crates_in_tier, PolicyTier,
+};
+use crate::registry::read_registry_names;
 use crate::scan::rs_file::resolve_rs_file_deps;
+use crate::score::find_crates_over_max_score;
+use crate::source_kind::source_kind;
 
+use super::dangerous_apis::{dangerous_api_calls, dangerous_apis};
 use super::find::find_unsafe;
+use super::local_targets::{
+    local_targets_compile_filter, split_local_target_files,
+};
+use super::relative_paths::make_report_paths_relative;
+use super::safety_docs::check_safety_docs;
+use super::send_sync::send_sync_impls_per_crate;
 use super::{
     list_files_used_but_not_scanned, package_metrics, unsafe_stats,
-    ScanDetails, ScanMode, ScanParameters, ScanResult,
+    GeigerContext, ScanDetails, ScanMode, ScanParameters, ScanReport,
+    ScanResult,
 };
 
+use aligned::scan_to_aligned;
+use feature_report::scan_to_feature_report;
+use folded::scan_to_folded;
+use heatmap::scan_to_heatmap;
+use interactive::scan_to_interactive;
+use license_groups::scan_to_license_groups;
+use source_groups::scan_to_source_groups;
+use summary::scan_to_summary;
 use table::scan_to_table;
+use why::scan_to_why;
 
-use cargo::core::compiler::CompileMode;
+pub use workspace::scan_to_workspace;
+
+use cargo::core::compiler::{CompileKind, CompileMode};
 use cargo::core::resolver::features::CliFeatures;
 use cargo::core::Workspace;
-use cargo::ops::CompileOptions;
+use cargo::ops::{CompileOptions, Packages};
+use cargo::util::interning::InternedString;
 use cargo::{CliError, Config};
-use cargo_geiger_serde::{ReportEntry, SafetyReport};
-use cargo_metadata::PackageId;
+use cargo_geiger_serde::{
+    CounterBlock, PackageInfo, ReportEntry, SafetyReport, UnsafeFinding,
+    UnsafeInfo,
+};
+use cargo_metadata::semver::Version;
+use cargo_metadata::{Metadata, Package, PackageId};
+use geiger::UnsafeLocation;
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
 
 pub fn scan_unsafe(
     cargo_metadata_parameters: &CargoMetadataParameters,
@@ -29,23 +93,271 @@ pub fn scan_unsafe(
     scan_parameters: &ScanParameters,
     workspace: &Workspace,
 ) -> Result<ScanResult, CliError> {
-    match scan_parameters.args.output_format {
-        OutputFormat::Json => scan_to_report(
+    if scan_parameters.args.collapse_workspace
+        && (scan_parameters.args.output_format.is_machine_readable()
+            || !(scan_parameters.args.heatmap
+                || scan_parameters.args.groups_by_source()))
+    {
+        eprintln!(
+            "NOTE: `--collapse-workspace` only applies to the `--heatmap` and \
+            `--group-by-source` outputs. Workspace members are itemized."
+        );
+    }
+    if !scan_parameters.args.merge.is_empty()
+        && (scan_parameters.args.output_format.is_machine_readable()
+            || !(scan_parameters.args.heatmap
+                || scan_parameters.args.groups_by_source()))
+    {
+        eprintln!(
+            "NOTE: `--merge` only applies to the `--heatmap` and \
+            `--group-by-source` outputs. Crates are counted separately."
+        );
+    }
+    if scan_parameters.args.workspace_only
+        && (scan_parameters.args.output_format.is_machine_readable()
+            || scan_parameters.args.summary
+            || scan_parameters.args.aligned
+            || scan_parameters.args.folded
+            || scan_parameters.args.heatmap
+            || scan_parameters.args.groups_by_source()
+            || scan_parameters.args.group_by_license)
+    {
+        eprintln!(
+            "NOTE: `--workspace-only` only applies to the table output. All \
+            crates are reported."
+        );
+    }
+    if scan_parameters.args.max_depth.is_some()
+        && (scan_parameters.args.output_format.is_machine_readable()
+            || scan_parameters.args.summary
+            || scan_parameters.args.aligned
+            || scan_parameters.args.folded
+            || scan_parameters.args.heatmap
+            || scan_parameters.args.groups_by_source()
+            || scan_parameters.args.group_by_license)
+    {
+        eprintln!(
+            "NOTE: `--max-depth` and `--depth` only apply to the table output. \
+            All crates are reported."
+        );
+    }
+    if scan_parameters.args.fail_threshold.is_some()
+        && (scan_parameters.args.output_format.is_machine_readable()
+            || scan_parameters.args.aligned
+            || scan_parameters.args.folded
+            || scan_parameters.args.heatmap
+            || scan_parameters.args.groups_by_source()
+            || scan_parameters.args.group_by_license)
+    {
+        eprintln!(
+            "NOTE: `--fail-threshold` only applies to the table output, such \
+            as `--output-format Ratio`."
+        );
+    }
+    if scan_parameters.args.group_by_license
+        && scan_parameters.args.output_format.is_machine_readable()
+        && scan_parameters.args.output_format != OutputFormat::Json
+    {
+        eprintln!(
+            "NOTE: `--group-by-license` only applies to the table and Json \
+            outputs. Crates are reported separately."
+        );
+    }
+    if scan_parameters.args.why.is_some()
+        && scan_parameters.args.output_format.is_machine_readable()
+    {
+        eprintln!(
+            "NOTE: `--why` only applies to the table output. The full report \
+            is printed."
+        );
+    }
+    if scan_parameters.args.interactive
+        && scan_parameters.args.output_format.is_machine_readable()
+    {
+        eprintln!(
+            "NOTE: `--interactive` only applies to the table output. The full \
+            report is printed."
+        );
+    }
+    if scan_parameters.args.feature_report
+        && scan_parameters.args.output_format.is_machine_readable()
+    {
+        eprintln!(
+            "NOTE: `--feature-report` only applies to the table output. The \
+            full report is printed."
+        );
+    }
+    if scan_parameters.args.summary
+        && scan_parameters.args.output_format.is_machine_readable()
+    {
+        eprintln!(
+            "NOTE: `--summary` only applies to the table output. The full \
+            report is printed."
+        );
+    }
+    if scan_parameters.args.summary_top.is_some()
+        && !scan_parameters.args.summary
+    {
+        eprintln!("NOTE: `--summary-top` only applies with `--summary`.");
+    }
+    if scan_parameters.crate_order.is_some()
+        && (scan_parameters.args.output_format.is_machine_readable()
+            || !scan_parameters.args.heatmap)
+    {
+        eprintln!(
+            "NOTE: `--order-file` only applies to the `--heatmap` output."
+        );
+    }
+
+    let scan_details =
+        scan(cargo_metadata_parameters, scan_parameters, workspace)?;
+    if !scan_parameters.args.also_emit.is_empty()
+        || scan_parameters.args.log_sink.is_some()
+        || scan_parameters.args.metrics_file.is_some()
+    {
+        let report_root_package_id = root_package_id
+            .to_cargo_geiger_package_id(cargo_metadata_parameters.metadata);
+        let report = safety_report_from_scan_details(
+            cargo_metadata_parameters,
+            graph,
+            root_package_id.clone(),
+            &scan_details,
+            scan_parameters,
+        );
+        write_emitted_reports(
+            cargo_metadata_parameters,
+            &report,
+            report_root_package_id.as_ref(),
+            &scan_details,
+            scan_parameters,
+        )?;
+    }
+    let detection_outcome = (scan_parameters.args.exit_code_mode
+        == ExitCodeMode::Detection)
+        .then(|| {
+            DetectionOutcome::from_report(&safety_report_from_scan_details(
+                cargo_metadata_parameters,
+                graph,
+                root_package_id.clone(),
+                &scan_details,
+                scan_parameters,
+            ))
+        });
+
+    if let Some(crate_spec) = &scan_parameters.args.why {
+        if !scan_parameters.args.output_format.is_machine_readable() {
+            return scan_to_why(
+                cargo_metadata_parameters,
+                crate_spec,
+                graph,
+                root_package_id,
+                scan_parameters,
+                scan_details,
+            )
+            .map(|scan_result| ScanResult {
+                detection_outcome,
+                ..scan_result
+            });
+        }
+    }
+
+    let scan_result = match scan_parameters.args.output_format {
+        OutputFormat::Json if scan_parameters.args.group_by_license => {
+            scan_to_license_groups(
+                cargo_metadata_parameters,
+                graph,
+                root_package_id,
+                scan_parameters,
+                scan_details,
+            )
+        }
+        OutputFormat::Csv
+        | OutputFormat::CycloneDx
+        | OutputFormat::Dot
+        | OutputFormat::GitHubActions
+        | OutputFormat::Html
+        | OutputFormat::Json
+        | OutputFormat::JsonLines
+        | OutputFormat::Mermaid
+        | OutputFormat::PrComment
+        | OutputFormat::Sarif
+        | OutputFormat::ShieldsIo
+        | OutputFormat::Toml => scan_to_report(
             cargo_metadata_parameters,
             graph,
             scan_parameters.args.output_format,
             root_package_id,
             scan_parameters,
-            workspace,
+            scan_details,
+        ),
+        _ if scan_parameters.args.interactive => scan_to_interactive(
+            cargo_metadata_parameters,
+            graph,
+            root_package_id,
+            scan_parameters,
+            scan_details,
+        ),
+        _ if scan_parameters.args.feature_report => scan_to_feature_report(
+            cargo_metadata_parameters,
+            graph,
+            root_package_id,
+            scan_parameters,
+            scan_details,
+        ),
+        _ if scan_parameters.args.summary => scan_to_summary(
+            cargo_metadata_parameters,
+            graph,
+            root_package_id,
+            scan_parameters,
+            scan_details,
+        ),
+        _ if scan_parameters.args.aligned => scan_to_aligned(
+            cargo_metadata_parameters,
+            graph,
+            root_package_id,
+            scan_parameters,
+            scan_details,
+        ),
+        _ if scan_parameters.args.folded => scan_to_folded(
+            cargo_metadata_parameters,
+            graph,
+            root_package_id,
+            scan_parameters,
+            scan_details,
+        ),
+        _ if scan_parameters.args.heatmap => scan_to_heatmap(
+            cargo_metadata_parameters,
+            graph,
+            root_package_id,
+            scan_parameters,
+            scan_details,
+        ),
+        _ if scan_parameters.args.groups_by_source() => scan_to_source_groups(
+            cargo_metadata_parameters,
+            graph,
+            root_package_id,
+            scan_parameters,
+            scan_details,
+        ),
+        _ if scan_parameters.args.group_by_license => scan_to_license_groups(
+            cargo_metadata_parameters,
+            graph,
+            root_package_id,
+            scan_parameters,
+            scan_details,
         ),
         _ => scan_to_table(
             cargo_metadata_parameters,
             graph,
             root_package_id,
             scan_parameters,
-            workspace,
+            scan_details,
         ),
-    }
+    };
+    scan_result.map(|scan_result| ScanResult {
+        detection_outcome,
+        ..scan_result
+    })
 }
 
 /// Based on code from cargo-bloat. It seems weird that `CompileOptions` can be
@@ -55,11 +367,34 @@ pub fn scan_unsafe(
 fn build_compile_options<'a>(
     args: &'a FeaturesArgs,
     config: &'a Config,
+    local_targets_args: &LocalTargetsArgs,
+    profile: Profile,
+    target_args: &TargetArgs,
+    workspace_args: &WorkspaceArgs,
 ) -> CompileOptions {
     let mut compile_options =
         CompileOptions::new(config, CompileMode::Check { test: false })
             .unwrap();
 
+    // The profile decides `debug_assertions`, and with it which source files
+    // are used by the build.
+    compile_options.build_config.requested_profile =
+        InternedString::new(profile.name());
+
+    // The build decides which crates and source files are used, so it has to
+    // be for the platform whose dependencies are in the graph. With
+    // `--all-targets`, or without `--target`, the build is for the host.
+    if let (false, Some(target)) =
+        (target_args.all_targets, &target_args.target)
+    {
+        compile_options.build_config.requested_kinds =
+            CompileKind::from_requested_targets(
+                config,
+                std::slice::from_ref(target),
+            )
+            .unwrap();
+    }
+
     let uses_default_features = !args.no_default_features;
 
     compile_options.cli_features = CliFeatures::from_command_line(
@@ -69,11 +404,24 @@ fn build_compile_options<'a>(
     )
     .unwrap();
 
+    if let Some(compile_filter) =
+        local_targets_compile_filter(local_targets_args)
+    {
+        compile_options.filter = compile_filter;
+    }
+
+    // Without `--workspace`, the package of the current directory is built,
+    // which a virtual manifest doesn't have.
+    if workspace_args.workspace {
+        compile_options.spec =
+            Packages::from_flags(true, workspace_args.exclude.clone(), vec![])
+                .unwrap();
+    }
+
     // TODO: Investigate if this is relevant to cargo-geiger.
     //let mut bins = Vec::new();
     //let mut examples = Vec::new();
     // opt.release = args.release;
-    // opt.target = args.target.clone();
     // if let Some(ref name) = args.bin {
     //     bins.push(name.clone());
     // } else if let Some(ref name) = args.example {
@@ -97,56 +445,470 @@ fn scan(
     cargo_metadata_parameters: &CargoMetadataParameters,
     scan_parameters: &ScanParameters,
     workspace: &Workspace,
+) -> Result<ScanDetails, CliError> {
+    scan_packages(
+        cargo_metadata_parameters,
+        &cargo_metadata_parameters.metadata.packages,
+        scan_parameters,
+        workspace,
+    )
+}
+
+/// Like `scan`, but only the files of the package are scanned. The build still
+/// checks the whole dependency graph, to find the files used by it.
+pub fn scan_package(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    package: &Package,
+    scan_parameters: &ScanParameters,
+    workspace: &Workspace,
+) -> Result<ScanDetails, CliError> {
+    scan_packages(
+        cargo_metadata_parameters,
+        std::slice::from_ref(package),
+        scan_parameters,
+        workspace,
+    )
+}
+
+fn scan_packages(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    packages: &[Package],
+    scan_parameters: &ScanParameters,
+    workspace: &Workspace,
 ) -> Result<ScanDetails, CliError> {
     let compile_options = build_compile_options(
         &scan_parameters.args.features_args,
         scan_parameters.config,
+        &scan_parameters.args.local_targets_args,
+        scan_parameters.args.profile,
+        &scan_parameters.args.target_args,
+        &scan_parameters.args.workspace_args,
     );
 
     match resolve_rs_file_deps(&compile_options, workspace) {
         Ok(rs_files_used) => {
             let geiger_context = find_unsafe(
-                cargo_metadata_parameters,
+                packages,
                 scan_parameters.config,
                 ScanMode::Full,
                 scan_parameters.print_config,
+                scan_parameters.source_map,
             )?;
+            let (local_target_files_used, rs_files_used) =
+                split_local_target_files(
+                    &scan_parameters.args.local_targets_args,
+                    cargo_metadata_parameters.metadata,
+                    scan_parameters
+                        .source_map
+                        .translate_rs_files_used(rs_files_used),
+                );
             Ok(ScanDetails {
                 rs_files_used,
                 geiger_context,
+                local_target_files_used,
             })
         }
         Err(rs_resolve_error) => Err(CliError::new(rs_resolve_error.into(), 1)),
     }
 }
 
-fn scan_to_report(
+/// When `--max-score` is set, print the crates whose weighted unsafe score is
+/// above it, and return their number
+fn check_max_score(
+    scan_parameters: &ScanParameters,
+    rows: &[(String, CounterBlock)],
+) -> u64 {
+    let max_score = match scan_parameters.args.max_score {
+        Some(max_score) => max_score,
+        None => return 0,
+    };
+    let over_max_score_lines = find_crates_over_max_score(
+        &scan_parameters.args.score_weights,
+        max_score,
+        rows,
+    );
+    if !over_max_score_lines.is_empty() {
+        eprintln!("Crates with a weighted unsafe score above the maximum:");
+        for over_max_score_line in &over_max_score_lines {
+            eprintln!("{}", over_max_score_line);
+        }
+    }
+    over_max_score_lines.len() as u64
+}
+
+/// When `--category-policy` is set, print the crates with unsafe usage in the
+/// warn and error tiers, and return the number of crates in the error tier
+fn check_category_policy(
+    scan_parameters: &ScanParameters,
+    rows: &[(String, CounterBlock)],
+) -> u64 {
+    let category_policy = match &scan_parameters.args.category_policy {
+        Some(category_policy) => category_policy,
+        None => return 0,
+    };
+    for (tier, tier_label) in
+        [(PolicyTier::Warn, "warn"), (PolicyTier::Error, "error")]
+    {
+        let tier_lines = find_crates_in_tier(category_policy, tier, rows);
+        if !tier_lines.is_empty() {
+            eprintln!(
+                "Crates with unsafe usage in categories of the {} tier:",
+                tier_label
+            );
+            for tier_line in &tier_lines {
+                eprintln!("{}", tier_line);
+            }
+        }
+    }
+    find_crates_in_tier(category_policy, PolicyTier::Error, rows).len() as u64
+}
+
+/// When `--category-policy` gives a tier to `send-sync-impls`, print the
+/// crates with `unsafe impl`s of `Send` or `Sync`, and return their number if
+/// the tier is the error tier
+fn check_send_sync_policy(
+    scan_parameters: &ScanParameters,
+    metadata: &Metadata,
+    geiger_context: &GeigerContext,
+    rs_files_used: &HashSet<PathBuf>,
+) -> u64 {
+    let category_policy = match &scan_parameters.args.category_policy {
+        Some(category_policy) => category_policy,
+        None => return 0,
+    };
+    let (tier, tier_label) = match category_policy.send_sync_impls {
+        Some(PolicyTier::Error) => (PolicyTier::Error, "error"),
+        Some(PolicyTier::Warn) => (PolicyTier::Warn, "warn"),
+        Some(PolicyTier::Ignore) | None => return 0,
+    };
+    let send_sync_impl_rows = send_sync_impls_per_crate(
+        geiger_context,
+        metadata,
+        rs_files_used,
+        scan_parameters.print_config.workspace_only,
+    );
+    let tier_lines = find_send_sync_crates_in_tier(
+        category_policy,
+        tier,
+        &send_sync_impl_rows,
+    );
+    if !tier_lines.is_empty() {
+        eprintln!(
+            "Crates with unsafe impls of Send or Sync in the {} tier:",
+            tier_label
+        );
+        for tier_line in &tier_lines {
+            eprintln!("{}", tier_line);
+        }
+    }
+    if tier == PolicyTier::Error {
+        tier_lines.len() as u64
+    } else {
+        0
+    }
+}
+
+/// When `--require-safety-docs` is set, print the workspace crates with
+/// `unsafe` blocks without a `// SAFETY:` comment, and return the number of
+/// such blocks. The lines go to stderr, so that the output of the formats
+/// other than the tree stays parseable.
+fn check_required_safety_docs(
+    scan_parameters: &ScanParameters,
+    metadata: &Metadata,
+    geiger_context: &GeigerContext,
+    rs_files_used: &HashSet<PathBuf>,
+) -> u64 {
+    if !scan_parameters.args.require_safety_docs {
+        return 0;
+    }
+    let (undocumented_unsafe_count, undocumented_unsafe_lines) =
+        check_safety_docs(geiger_context, metadata, rs_files_used);
+    for undocumented_unsafe_line in undocumented_unsafe_lines {
+        eprintln!("{}", undocumented_unsafe_line);
+    }
+    undocumented_unsafe_count
+}
+
+/// When `--deny-unsafe` is set, print the crates using unsafe code, and return
+/// their number
+fn check_deny_unsafe(
+    scan_parameters: &ScanParameters,
+    rows: &[(String, CounterBlock)],
+) -> u64 {
+    if !scan_parameters.args.deny_unsafe {
+        return 0;
+    }
+    let allowlist = scan_parameters.print_config.allowlist.as_ref();
+    let mut unsafe_crate_names = rows
+        .iter()
+        .filter(|(_, used)| used.has_unsafe())
+        .filter(|(name, _)| {
+            !allowlist
+                .is_some_and(|allowlist| is_allowlisted_row(allowlist, name))
+        })
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<&str>>();
+    unsafe_crate_names.sort_unstable();
+    unsafe_crate_names.dedup();
+    if !unsafe_crate_names.is_empty() {
+        eprintln!("Crates using unsafe code:");
+        for unsafe_crate_name in &unsafe_crate_names {
+            eprintln!("    {}", unsafe_crate_name);
+        }
+    }
+    unsafe_crate_names.len() as u64
+}
+
+/// When `--require-audits` is set, print the crates using unsafe code without
+/// an audit in `--audits`, and return their number
+fn check_required_audits(
+    scan_parameters: &ScanParameters,
+    rows: &[(String, CounterBlock)],
+) -> u64 {
+    if !scan_parameters.args.require_audits {
+        return 0;
+    }
+    let allowlist = scan_parameters.print_config.allowlist.as_ref();
+    let mut unaudited_crate_names = rows
+        .iter()
+        .filter(|(_, used)| used.has_unsafe())
+        .filter(|(name, _)| {
+            !allowlist
+                .is_some_and(|allowlist| is_allowlisted_row(allowlist, name))
+        })
+        .filter(|(name, _)| {
+            !scan_parameters
+                .audits
+                .is_some_and(|audits| is_audited_row(audits, name))
+        })
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<&str>>();
+    unaudited_crate_names.sort_unstable();
+    unaudited_crate_names.dedup();
+    if !unaudited_crate_names.is_empty() {
+        eprintln!("Crates using unsafe code without an audit:");
+        for unaudited_crate_name in &unaudited_crate_names {
+            eprintln!("    {}", unaudited_crate_name);
+        }
+    }
+    unaudited_crate_names.len() as u64
+}
+
+/// When `--max-unsafe` or `--max-total-unsafe` is set, print the crates and
+/// the total with unsafe usage above the limits, and return their number
+fn check_unsafe_limits(
+    scan_parameters: &ScanParameters,
+    rows: &[(String, CounterBlock)],
+) -> u64 {
+    let violation_lines = find_unsafe_limit_violations(
+        scan_parameters.args.max_unsafe.as_ref(),
+        scan_parameters.args.max_total_unsafe.as_ref(),
+        rows,
+    );
+    if !violation_lines.is_empty() {
+        eprintln!("Unsafe usage above the limits:");
+        for violation_line in &violation_lines {
+            eprintln!("{}", violation_line);
+        }
+    }
+    violation_lines.len() as u64
+}
+
+/// Whether the crate of a row, labelled `<name> <version>`, is on the
+/// allowlist
+fn is_allowlisted_row(allowlist: &Allowlist, row_name: &str) -> bool {
+    row_name_and_version(row_name)
+        .is_some_and(|(name, version)| allowlist.contains(name, &version))
+}
+
+fn is_audited_row(audits: &Audits, row_name: &str) -> bool {
+    row_name_and_version(row_name)
+        .is_some_and(|(name, version)| audits.get(name, &version).is_some())
+}
+
+/// The name and version of a `<name> <version>` row
+fn row_name_and_version(row_name: &str) -> Option<(&str, Version)> {
+    row_name.rsplit_once(' ').and_then(|(name, version)| {
+        Version::parse(version).ok().map(|version| (name, version))
+    })
+}
+
+/// The unsafe usage of all workspace members, summed into the single row
+/// which replaces them with `--collapse-workspace`
+struct WorkspaceRow {
+    crate_count: usize,
+    unused: CounterBlock,
+    used: CounterBlock,
+}
+
+impl WorkspaceRow {
+    fn name(&self) -> String {
+        format!("workspace ({} crates)", self.crate_count)
+    }
+}
+
+/// With `--collapse-workspace`, take the workspace members out of the scanned
+/// crates and sum their unsafe usage into a `WorkspaceRow`
+fn collapse_workspace(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    scan_parameters: &ScanParameters,
+    package_unsafe_infos: Vec<(PackageInfo, UnsafeInfo)>,
+) -> (Vec<(PackageInfo, UnsafeInfo)>, Option<WorkspaceRow>) {
+    if !scan_parameters.args.collapse_workspace {
+        return (package_unsafe_infos, None);
+    }
+    let metadata = cargo_metadata_parameters.metadata;
+    let workspace_member_ids = metadata
+        .workspace_members
+        .iter()
+        .filter_map(|package_id| {
+            package_id.to_cargo_geiger_package_id(metadata)
+        })
+        .collect::<HashSet<_>>();
+    collapse_workspace_members(&workspace_member_ids, package_unsafe_infos)
+}
+
+fn collapse_workspace_members(
+    workspace_member_ids: &HashSet<cargo_geiger_serde::PackageId>,
+    package_unsafe_infos: Vec<(PackageInfo, UnsafeInfo)>,
+) -> (Vec<(PackageInfo, UnsafeInfo)>, Option<WorkspaceRow>) {
+    let (workspace_members, dependencies): (Vec<_>, Vec<_>) =
+        package_unsafe_infos
+            .into_iter()
+            .partition(|(package_info, _)| {
+                workspace_member_ids.contains(&package_info.id)
+            });
+    if workspace_members.is_empty() {
+        return (dependencies, None);
+    }
+
+    let mut workspace_row = WorkspaceRow {
+        crate_count: workspace_members.len(),
+        unused: CounterBlock::default(),
+        used: CounterBlock::default(),
+    };
+    for (_, unsafe_info) in workspace_members {
+        workspace_row.unused += unsafe_info.unused;
+        workspace_row.used += unsafe_info.used;
+    }
+    (dependencies, Some(workspace_row))
+}
+
+/// Scan for unsafe usage, and collect the results into a `SafetyReport`
+pub fn scan_to_safety_report(
     cargo_metadata_parameters: &CargoMetadataParameters,
     graph: &Graph,
-    output_format: OutputFormat,
     root_package_id: PackageId,
     scan_parameters: &ScanParameters,
     workspace: &Workspace,
-) -> Result<ScanResult, CliError> {
+) -> Result<ScanReport, CliError> {
+    let scan_details =
+        scan(cargo_metadata_parameters, scan_parameters, workspace)?;
+    let report = safety_report_from_scan_details(
+        cargo_metadata_parameters,
+        graph,
+        root_package_id,
+        &scan_details,
+        scan_parameters,
+    );
+    Ok(ScanReport {
+        report,
+        rs_files_used: scan_details.rs_files_used,
+    })
+}
+
+fn safety_report_from_scan_details(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    graph: &Graph,
+    root_package_id: PackageId,
+    scan_details: &ScanDetails,
+    scan_parameters: &ScanParameters,
+) -> SafetyReport {
     let ScanDetails {
         rs_files_used,
         geiger_context,
-    } = scan(cargo_metadata_parameters, scan_parameters, workspace)?;
+        ..
+    } = scan_details;
     let mut report = SafetyReport::default();
+    let unsafe_locations = scan_parameters
+        .args
+        .include_locations
+        .then(|| unsafe_locations(cargo_metadata_parameters, scan_details));
+    let dangerous_apis = dangerous_apis(scan_parameters.args);
+    let registry_names = read_registry_names(scan_parameters.config);
     for (package, package_metrics_option) in package_metrics(
         cargo_metadata_parameters,
-        &geiger_context,
+        geiger_context,
         graph,
+        scan_parameters.print_config,
         root_package_id,
     ) {
         let package_metrics = match package_metrics_option {
             Some(m) => m,
+            None if scan_parameters
+                .print_config
+                .ignore_list
+                .is_ignored_crate(&package.id.name) =>
+            {
+                report.ignored_packages.insert(package.id);
+                continue;
+            }
             None => {
                 report.packages_without_metrics.insert(package.id);
                 continue;
             }
         };
-        let unsafe_info = unsafe_stats(&package_metrics, &rs_files_used);
+        let mut unsafe_info = unsafe_stats(&package_metrics, rs_files_used);
+        if let Some(unsafe_history) = scan_parameters.unsafe_history {
+            if unsafe_info.used.has_unsafe() {
+                unsafe_info.unsafe_introduced_at = unsafe_history
+                    .unsafe_introduced_at(
+                        &package.id.name,
+                        &package.id.version,
+                        &unsafe_info.used,
+                    );
+            }
+        }
+        unsafe_info.source_kind =
+            Some(source_kind(cargo_metadata_parameters.metadata, &package.id));
+        unsafe_info.registry = registry_names.get(&package.id.source);
+        if let Some(download_counts) = scan_parameters.download_counts {
+            unsafe_info.downloads = download_counts.get(&package.id.name);
+        }
+        if let Some(security_policies) = scan_parameters.security_policies {
+            unsafe_info.has_security_policy =
+                security_policies.get(&package.id.name, &package.id.version);
+        }
+        if let Some(checksum_statuses) = scan_parameters.checksum_statuses {
+            unsafe_info.checksum_mismatch = checksum_statuses
+                .is_mismatched(&package.id.name, &package.id.version);
+        }
+        if let Some(expanded_counts) = scan_parameters.expanded_counts {
+            unsafe_info.expanded = expanded_counts
+                .get(&package.id.name, &package.id.version)
+                .cloned();
+        }
+        if let Some(advisories) = scan_parameters.advisories {
+            unsafe_info.advisories =
+                Some(advisories.get(&package.id.name, &package.id.version));
+        }
+        if let Some(ecosystem_stats) = scan_parameters.ecosystem_stats {
+            unsafe_info.unsafe_density_percentile = ecosystem_stats
+                .compare_counter_blocks(&unsafe_info.used, &unsafe_info.unused)
+                .map(|density_comparison| density_comparison.percentile);
+        }
+        if let Some(dangerous_apis) = &dangerous_apis {
+            unsafe_info.dangerous_api_calls = Some(dangerous_api_calls(
+                dangerous_apis,
+                &package_metrics,
+                rs_files_used,
+            ));
+        }
+        if let Some(unsafe_locations) = &unsafe_locations {
+            unsafe_info.unsafe_findings = Some(unsafe_findings(
+                unsafe_locations.get(&package.id).map_or(&[], Vec::as_slice),
+            ));
+        }
         let entry = ReportEntry {
             package,
             unsafety: unsafe_info,
@@ -154,18 +916,340 @@ fn scan_to_report(
         report.packages.insert(entry.package.id.clone(), entry);
     }
     report.used_but_not_scanned_files =
-        list_files_used_but_not_scanned(&geiger_context, &rs_files_used)
+        list_files_used_but_not_scanned(geiger_context, rs_files_used)
             .into_iter()
             .collect();
-    let json_string = match output_format {
-        OutputFormat::Json => serde_json::to_string(&report).unwrap(),
-        _ => panic!("Only implemented for OutputFormat::Json"),
+    if scan_parameters.print_config.lossy {
+        report.scan_warnings = geiger_context.scan_warnings.clone();
+        report.scan_warnings.sort();
+    }
+    if scan_parameters.args.relative_paths {
+        make_report_paths_relative(
+            &mut report,
+            &canonical_workspace_root(cargo_metadata_parameters.metadata),
+        );
+    }
+    report
+}
+
+fn scan_to_report(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    graph: &Graph,
+    output_format: OutputFormat,
+    root_package_id: PackageId,
+    scan_parameters: &ScanParameters,
+    scan_details: ScanDetails,
+) -> Result<ScanResult, CliError> {
+    let report_root_package_id = root_package_id
+        .to_cargo_geiger_package_id(cargo_metadata_parameters.metadata);
+    let report = safety_report_from_scan_details(
+        cargo_metadata_parameters,
+        graph,
+        root_package_id,
+        &scan_details,
+        scan_parameters,
+    );
+    let scan_output_lines = report_output_lines(
+        cargo_metadata_parameters,
+        output_format,
+        &report,
+        report_root_package_id.as_ref(),
+        &scan_details,
+        scan_parameters,
+    );
+    Ok(scan_result_from_report(
+        cargo_metadata_parameters,
+        &report,
+        &scan_details,
+        scan_parameters,
+        scan_output_lines,
+    ))
+}
+
+/// Write the report in each of the formats given with `--also-emit` to its
+/// file, its metrics to the file given with `--metrics-file` and its records
+/// to the system log given with `--log-sink`
+fn write_emitted_reports(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    report: &SafetyReport,
+    report_root_package_id: Option<&cargo_geiger_serde::PackageId>,
+    scan_details: &ScanDetails,
+    scan_parameters: &ScanParameters,
+) -> Result<(), CliError> {
+    for emitted_report in &scan_parameters.args.also_emit {
+        let mut emitted_report_content = report_output_lines(
+            cargo_metadata_parameters,
+            emitted_report.output_format,
+            report,
+            report_root_package_id,
+            scan_details,
+            scan_parameters,
+        )
+        .join("\n");
+        emitted_report_content.push('\n');
+        fs::write(&emitted_report.path, emitted_report_content).map_err(
+            |e| {
+                eprintln!(
+                    "Failed to write report to file: {}",
+                    emitted_report.path.display()
+                );
+                CliError::new(anyhow::Error::from(e), 1)
+            },
+        )?;
+    }
+    if let Some(metrics_file) = &scan_parameters.args.metrics_file {
+        let roots = match report_root_package_id {
+            Some(root_package_id) => {
+                vec![root_package_id.clone()].into_iter().collect()
+            }
+            None => workspace_member_ids(cargo_metadata_parameters),
+        };
+        let mut metrics_content =
+            create_prometheus_lines(report, &roots).join("\n");
+        metrics_content.push('\n');
+        fs::write(metrics_file, metrics_content).map_err(|e| {
+            eprintln!(
+                "Failed to write metrics to file: {}",
+                metrics_file.display()
+            );
+            CliError::new(anyhow::Error::from(e), 1)
+        })?;
+    }
+    if let Some(log_sink) = scan_parameters.args.log_sink {
+        emit_log_records(log_sink, &construct_log_records(report))?;
+    }
+    Ok(())
+}
+
+/// Run the checks of the gates on the crates of a report, and return their
+/// results along with the output
+fn scan_result_from_report(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    report: &SafetyReport,
+    scan_details: &ScanDetails,
+    scan_parameters: &ScanParameters,
+    scan_output_lines: Vec<String>,
+) -> ScanResult {
+    let undocumented_unsafe_count = check_required_safety_docs(
+        scan_parameters,
+        cargo_metadata_parameters.metadata,
+        &scan_details.geiger_context,
+        &scan_details.rs_files_used,
+    );
+    // The Json report records what is missing, so it only fails on
+    // warnings when asked to.
+    let warning_count = if scan_parameters.args.deny_warnings {
+        (report.packages_without_metrics.len()
+            + report.used_but_not_scanned_files.len()) as u64
+    } else {
+        0
     };
+    let rows = report
+        .packages
+        .values()
+        .map(|entry| {
+            (
+                format!(
+                    "{} {}",
+                    entry.package.id.name, entry.package.id.version
+                ),
+                entry.unsafety.used.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let over_max_score_count = check_max_score(scan_parameters, &rows);
+    let category_policy_error_count =
+        check_category_policy(scan_parameters, &rows)
+            + check_send_sync_policy(
+                scan_parameters,
+                cargo_metadata_parameters.metadata,
+                &scan_details.geiger_context,
+                &scan_details.rs_files_used,
+            );
+    let unsafe_crate_count = check_deny_unsafe(scan_parameters, &rows);
+    let unaudited_crate_count = check_required_audits(scan_parameters, &rows);
+    let unsafe_limit_violation_count =
+        check_unsafe_limits(scan_parameters, &rows);
 
-    Ok(ScanResult {
-        scan_output_lines: vec![json_string],
-        warning_count: 0,
-    })
+    ScanResult {
+        category_policy_error_count,
+        checksum_mismatch_count: 0,
+        detection_outcome: None,
+        over_fail_threshold_ratio: None,
+        over_max_score_count,
+        scan_output_lines,
+        unaudited_crate_count,
+        undocumented_unsafe_count,
+        unsafe_crate_count,
+        unsafe_limit_violation_count,
+        warning_count,
+    }
+}
+
+/// The lines of a report in one of the machine-readable output formats
+fn report_output_lines(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    output_format: OutputFormat,
+    report: &SafetyReport,
+    report_root_package_id: Option<&cargo_geiger_serde::PackageId>,
+    scan_details: &ScanDetails,
+    scan_parameters: &ScanParameters,
+) -> Vec<String> {
+    match output_format {
+        OutputFormat::Csv => create_csv_lines(report),
+        OutputFormat::CycloneDx => vec![create_cyclonedx_line(report)],
+        OutputFormat::Dot => create_dot_lines(report),
+        OutputFormat::GitHubActions => create_github_actions_lines(
+            report,
+            &unsafe_locations(cargo_metadata_parameters, scan_details),
+            &workspace_member_ids(cargo_metadata_parameters),
+        ),
+        OutputFormat::Html => create_html_lines(
+            report,
+            report_root_package_id,
+            &crate_files(cargo_metadata_parameters, scan_details),
+        ),
+        OutputFormat::Json => vec![create_json_line(
+            report,
+            scan_parameters.args.categories,
+            scan_parameters.args.json_version,
+        )],
+        OutputFormat::JsonLines => create_json_lines(report),
+        OutputFormat::Mermaid => create_mermaid_lines(report),
+        OutputFormat::PrComment => {
+            create_pr_comment_lines(report, scan_parameters.baseline)
+        }
+        OutputFormat::Sarif => vec![create_sarif_line(
+            report,
+            &unsafe_locations(cargo_metadata_parameters, scan_details),
+        )],
+        OutputFormat::ShieldsIo => {
+            vec![
+                serde_json::to_string(&ShieldsIoEndpoint::from_report(report))
+                    .unwrap(),
+            ]
+        }
+        OutputFormat::Toml => create_toml_lines(report),
+        _ => panic!(
+            "Only implemented for OutputFormat::Csv, OutputFormat::CycloneDx, \
+            OutputFormat::Dot, OutputFormat::GitHubActions, \
+            OutputFormat::Html, OutputFormat::Json, OutputFormat::JsonLines, \
+            OutputFormat::Mermaid, OutputFormat::PrComment, \
+            OutputFormat::Sarif, OutputFormat::ShieldsIo and \
+            OutputFormat::Toml"
+        ),
+    }
+}
+
+/// The files of each crate used by the build with their unsafe code, for the
+/// crate sections of the Html output
+fn crate_files(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    scan_details: &ScanDetails,
+) -> CrateFiles {
+    let metadata = cargo_metadata_parameters.metadata;
+    let workspace_root = canonical_workspace_root(metadata);
+    scan_details
+        .geiger_context
+        .package_id_to_metrics
+        .iter()
+        .filter_map(|(package_id, package_metrics)| {
+            let mut package_files = package_metrics
+                .rs_path_to_metrics
+                .iter()
+                .filter(|(path_buf, _)| {
+                    scan_details.rs_files_used.contains(*path_buf)
+                })
+                .map(|(path_buf, rs_file_metrics_wrapper)| {
+                    (
+                        artifact_uri(path_buf, &workspace_root),
+                        rs_file_metrics_wrapper.metrics.counters.clone(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            package_files.sort_by(|a, b| a.0.cmp(&b.0));
+            Some((
+                package_id.to_cargo_geiger_package_id(metadata)?,
+                package_files,
+            ))
+        })
+        .collect()
+}
+
+/// The workspace members, whose files are those of the repository
+fn workspace_member_ids(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+) -> HashSet<cargo_geiger_serde::PackageId> {
+    let metadata = cargo_metadata_parameters.metadata;
+    metadata
+        .workspace_members
+        .iter()
+        .filter_map(|package_id| {
+            package_id.to_cargo_geiger_package_id(metadata)
+        })
+        .collect()
+}
+
+/// The scanned paths are canonicalized, so the root they are displayed
+/// relative to has to be too
+pub(super) fn canonical_workspace_root(metadata: &Metadata) -> PathBuf {
+    metadata
+        .workspace_root
+        .canonicalize()
+        .unwrap_or_else(|_| metadata.workspace_root.clone().into_std_path_buf())
+}
+
+/// The unsafe items of each crate in the files used by the build, for the
+/// results of the Sarif output and the findings of the Json output, sorted by
+/// file and position
+fn unsafe_locations(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    scan_details: &ScanDetails,
+) -> UnsafeLocations {
+    let metadata = cargo_metadata_parameters.metadata;
+    let workspace_root = canonical_workspace_root(metadata);
+    scan_details
+        .geiger_context
+        .package_id_to_metrics
+        .iter()
+        .filter_map(|(package_id, package_metrics)| {
+            let mut package_unsafe_locations = package_metrics
+                .rs_path_to_metrics
+                .iter()
+                .filter(|(path_buf, _)| {
+                    scan_details.rs_files_used.contains(*path_buf)
+                })
+                .flat_map(|(path_buf, rs_file_metrics_wrapper)| {
+                    let uri = artifact_uri(path_buf, &workspace_root);
+                    rs_file_metrics_wrapper.metrics.unsafe_locations.iter().map(
+                        move |unsafe_location| (uri.clone(), *unsafe_location),
+                    )
+                })
+                .collect::<Vec<_>>();
+            package_unsafe_locations.sort();
+            Some((
+                package_id.to_cargo_geiger_package_id(metadata)?,
+                package_unsafe_locations,
+            ))
+        })
+        .collect()
+}
+
+/// The findings of the Json output, from the unsafe locations of a crate
+fn unsafe_findings(
+    package_unsafe_locations: &[(String, UnsafeLocation)],
+) -> Vec<UnsafeFinding> {
+    package_unsafe_locations
+        .iter()
+        .map(|(file, unsafe_location)| UnsafeFinding {
+            file: file.clone(),
+            kind: unsafe_location.kind,
+            start_line: unsafe_location.line,
+            start_column: unsafe_location.column,
+            end_line: unsafe_location.end_line,
+            end_column: unsafe_location.end_column,
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -175,18 +1259,24 @@ mod default_tests {
 
     #[rstest(
         input_features,
+        input_profile,
         case(
             vec![
                 String::from("unit"),
                 String::from("test"),
                 String::from("features")
             ],
+            Profile::Dev
         ),
         case(
             vec![String::from("")],
+            Profile::Release
         )
     )]
-    fn build_compile_options_test(input_features: Vec<String>) {
+    fn build_compile_options_test(
+        input_features: Vec<String>,
+        input_profile: Profile,
+    ) {
         let args = FeaturesArgs {
             all_features: rand::random(),
             features: input_features,
@@ -194,7 +1284,14 @@ mod default_tests {
         };
 
         let config = Config::default().unwrap();
-        let compile_options = build_compile_options(&args, &config);
+        let compile_options = build_compile_options(
+            &args,
+            &config,
+            &LocalTargetsArgs::default(),
+            input_profile,
+            &TargetArgs::default(),
+            &WorkspaceArgs::default(),
+        );
         let expected_cli_features =
             CliFeatures::from_command_line(&args.features, false, false)
                 .unwrap();
@@ -211,5 +1308,161 @@ mod default_tests {
             !compile_options.cli_features.uses_default_features,
             args.no_default_features
         );
+        assert_eq!(
+            compile_options.build_config.requested_profile.as_str(),
+            input_profile.name()
+        );
+    }
+
+    #[rstest(
+        input_all_targets,
+        input_target,
+        expected_is_host,
+        case(false, Some("x86_64-pc-windows-msvc"), false),
+        case(true, Some("x86_64-pc-windows-msvc"), true),
+        case(false, None, true)
+    )]
+    fn build_compile_options_target_test(
+        input_all_targets: bool,
+        input_target: Option<&str>,
+        expected_is_host: bool,
+    ) {
+        let config = Config::default().unwrap();
+        let compile_options = build_compile_options(
+            &FeaturesArgs::default(),
+            &config,
+            &LocalTargetsArgs::default(),
+            Profile::Dev,
+            &TargetArgs {
+                all_targets: input_all_targets,
+                target: input_target.map(String::from),
+            },
+            &WorkspaceArgs::default(),
+        );
+
+        assert_eq!(compile_options.build_config.requested_kinds.len(), 1);
+        assert_eq!(
+            compile_options.build_config.requested_kinds[0].is_host(),
+            expected_is_host
+        );
+    }
+
+    #[rstest(
+        input_row_name,
+        expected_is_allowlisted,
+        case("libc 0.2.132", true),
+        case("winapi 0.3.9", true),
+        case("winapi 0.3.8", false),
+        case("foo 0.1.0", false),
+        case("libc", false)
+    )]
+    fn is_allowlisted_row_test(
+        input_row_name: &str,
+        expected_is_allowlisted: bool,
+    ) {
+        let allowlist = Allowlist::from_lines("libc\nwinapi:0.3.9").unwrap();
+
+        assert_eq!(
+            is_allowlisted_row(&allowlist, input_row_name),
+            expected_is_allowlisted
+        );
+    }
+
+    #[rstest(
+        input_row_name,
+        expected_is_audited,
+        case("libc 0.2.132", true),
+        case("winapi 0.3.9", true),
+        case("winapi 0.3.8", false),
+        case("libc", false)
+    )]
+    fn is_audited_row_test(input_row_name: &str, expected_is_audited: bool) {
+        let audits = Audits::from_toml(
+            "[[audit]]\ncrate = \"libc\"\nauditor = \"Jane Doe\"\n\n\
+            [[audits.winapi]]\nwho = \"John Doe\"\nversion = \"0.3.9\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            is_audited_row(&audits, input_row_name),
+            expected_is_audited
+        );
+    }
+
+    #[rstest]
+    fn collapse_workspace_members_test() {
+        let create_package_unsafe_info = |name: &str, unsafe_exprs| {
+            let package_id = cargo_geiger_serde::PackageId {
+                name: name.to_string(),
+                version: cargo_metadata::semver::Version::new(0, 1, 0),
+                source: cargo_geiger_serde::Source::Path(
+                    url::Url::parse("file:///workspace").unwrap(),
+                ),
+            };
+            let mut unsafe_info = UnsafeInfo::default();
+            unsafe_info.used.exprs.unsafe_ = unsafe_exprs;
+            unsafe_info.unused.exprs.unsafe_ = 1;
+            (PackageInfo::new(package_id), unsafe_info)
+        };
+        let package_unsafe_infos = vec![
+            create_package_unsafe_info("member_a", 2),
+            create_package_unsafe_info("dependency", 4),
+            create_package_unsafe_info("member_b", 3),
+        ];
+        let workspace_member_ids = package_unsafe_infos
+            .iter()
+            .filter(|(package_info, _)| {
+                package_info.id.name.starts_with("member")
+            })
+            .map(|(package_info, _)| package_info.id.clone())
+            .collect::<HashSet<_>>();
+
+        let (dependencies, workspace_row) = collapse_workspace_members(
+            &workspace_member_ids,
+            package_unsafe_infos.clone(),
+        );
+        let workspace_row = workspace_row.unwrap();
+
+        assert_eq!(
+            dependencies
+                .iter()
+                .map(|(package_info, _)| package_info.id.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["dependency"]
+        );
+        assert_eq!(workspace_row.name(), "workspace (2 crates)");
+        assert_eq!(workspace_row.used.exprs.unsafe_, 5);
+        assert_eq!(workspace_row.unused.exprs.unsafe_, 2);
+
+        let (dependencies, workspace_row) =
+            collapse_workspace_members(&HashSet::new(), package_unsafe_infos);
+        assert_eq!(dependencies.len(), 3);
+        assert!(workspace_row.is_none());
+    }
+
+    #[rstest]
+    fn unsafe_findings_test() {
+        let package_unsafe_locations = vec![(
+            String::from("src/lib.rs"),
+            UnsafeLocation {
+                line: 3,
+                column: 5,
+                end_line: 6,
+                end_column: 1,
+                kind: geiger::UnsafeKind::Function,
+            },
+        )];
+
+        assert_eq!(
+            unsafe_findings(&package_unsafe_locations),
+            vec![UnsafeFinding {
+                file: String::from("src/lib.rs"),
+                kind: geiger::UnsafeKind::Function,
+                start_line: 3,
+                start_column: 5,
+                end_line: 6,
+                end_column: 1,
+            }]
+        );
     }
 }
@@ -0,0 +1,133 @@
+use crate::feature_report::{
+    construct_feature_report_lines, feature_impacts, FeatureReportRow,
+};
+use crate::graph::Graph;
+use crate::mapping::{CargoMetadataParameters, ToCargoGeigerPackageId};
+
+use super::super::{
+    package_metrics, unsafe_stats, ScanDetails, ScanParameters, ScanResult,
+};
+use super::{
+    check_category_policy, check_deny_unsafe, check_max_score,
+    check_required_audits, check_required_safety_docs, check_unsafe_limits,
+};
+
+use cargo::CliError;
+use cargo_metadata::PackageId;
+
+pub fn scan_to_feature_report(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    graph: &Graph,
+    root_package_id: PackageId,
+    scan_parameters: &ScanParameters,
+    scan_details: ScanDetails,
+) -> Result<ScanResult, CliError> {
+    let ScanDetails {
+        rs_files_used,
+        geiger_context,
+        ..
+    } = scan_details;
+    let metadata = cargo_metadata_parameters.metadata;
+
+    let used_unsafe = |package_id: &PackageId| {
+        geiger_context.package_id_to_metrics.get(package_id).map_or(
+            0,
+            |package_metrics| {
+                unsafe_stats(package_metrics, &rs_files_used)
+                    .used
+                    .total_unsafe()
+            },
+        )
+    };
+    let rows = feature_impacts(
+        graph,
+        &metadata.packages,
+        std::slice::from_ref(&root_package_id),
+    )
+    .into_iter()
+    .filter_map(|feature_impact| {
+        let package = feature_impact
+            .package_id
+            .to_cargo_geiger_package_id(metadata)?;
+        Some(FeatureReportRow {
+            name: package.name,
+            version: package.version,
+            feature: feature_impact.feature,
+            optional_dependencies: feature_impact.optional_dependencies,
+            dropped_crates: feature_impact
+                .dropped_package_ids
+                .iter()
+                .filter_map(|package_id| {
+                    package_id.to_cargo_geiger_package_id(metadata)
+                })
+                .map(|package| (package.name, package.version))
+                .collect(),
+            dropped_unsafe: feature_impact
+                .dropped_package_ids
+                .iter()
+                .map(used_unsafe)
+                .sum(),
+        })
+    })
+    .collect::<Vec<_>>();
+
+    // The gates apply to the whole tree, not only to the crates printed.
+    let package_metrics = package_metrics(
+        cargo_metadata_parameters,
+        &geiger_context,
+        graph,
+        scan_parameters.print_config,
+        root_package_id,
+    );
+    let warning_count = if scan_parameters.args.deny_warnings {
+        package_metrics
+            .iter()
+            .filter(|(_, package_metrics_option)| {
+                package_metrics_option.is_none()
+            })
+            .count() as u64
+    } else {
+        0
+    };
+    let crate_unsafe_usages = package_metrics
+        .into_iter()
+        .filter_map(|(package_info, package_metrics_option)| {
+            let unsafe_info =
+                unsafe_stats(&package_metrics_option?, &rs_files_used);
+            Some((
+                format!("{} {}", package_info.id.name, package_info.id.version),
+                unsafe_info.used,
+            ))
+        })
+        .collect::<Vec<_>>();
+    let over_max_score_count =
+        check_max_score(scan_parameters, &crate_unsafe_usages);
+    let category_policy_error_count =
+        check_category_policy(scan_parameters, &crate_unsafe_usages);
+    let unsafe_crate_count =
+        check_deny_unsafe(scan_parameters, &crate_unsafe_usages);
+    let unaudited_crate_count =
+        check_required_audits(scan_parameters, &crate_unsafe_usages);
+    let undocumented_unsafe_count = check_required_safety_docs(
+        scan_parameters,
+        cargo_metadata_parameters.metadata,
+        &geiger_context,
+        &rs_files_used,
+    );
+    let unsafe_limit_violation_count =
+        check_unsafe_limits(scan_parameters, &crate_unsafe_usages);
+
+    Ok(ScanResult {
+        category_policy_error_count,
+        checksum_mismatch_count: 0,
+        detection_outcome: None,
+        over_fail_threshold_ratio: None,
+        over_max_score_count,
+        scan_output_lines: construct_feature_report_lines(rows),
+        unaudited_crate_count,
+        undocumented_unsafe_count,
+        unsafe_crate_count,
+        unsafe_limit_violation_count,
+        warning_count,
+    })
+}
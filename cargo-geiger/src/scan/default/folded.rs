@@ -0,0 +1,116 @@
+use crate::format::folded::{create_folded_lines, FoldedRow};
+use crate::graph::Graph;
+use crate::license::package_matches_license_filter;
+use crate::mapping::CargoMetadataParameters;
+use crate::source_kind::package_matches_source_filter;
+
+use super::super::{unsafe_stats, ScanDetails, ScanParameters, ScanResult};
+use super::{
+    check_category_policy, check_deny_unsafe, check_max_score,
+    check_required_audits, check_required_safety_docs, check_unsafe_limits,
+};
+
+use cargo::CliError;
+use cargo_metadata::PackageId;
+use petgraph::visit::EdgeRef;
+use std::collections::{HashMap, VecDeque};
+
+pub fn scan_to_folded(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    graph: &Graph,
+    root_package_id: PackageId,
+    scan_parameters: &ScanParameters,
+    scan_details: ScanDetails,
+) -> Result<ScanResult, CliError> {
+    let ScanDetails {
+        rs_files_used,
+        geiger_context,
+        ..
+    } = scan_details;
+    let metadata = cargo_metadata_parameters.metadata;
+
+    // Walked breadth first, so that a crate depended on by several crates is
+    // represented by one of its shortest dependency chains.
+    let root_index = graph.nodes[&root_package_id];
+    let mut stacks = HashMap::new();
+    stacks.insert(root_index, vec![metadata[&root_package_id].name.clone()]);
+    let mut indices = VecDeque::from(vec![root_index]);
+    let mut rows = vec![];
+    let mut warning_count = 0;
+
+    while let Some(index) = indices.pop_front() {
+        let stack = stacks[&index].clone();
+        for edge in graph.graph.edges(index) {
+            let dependency_index = edge.target();
+            if stacks.contains_key(&dependency_index) {
+                continue;
+            }
+            let mut dependency_stack = stack.clone();
+            dependency_stack
+                .push(metadata[&graph.graph[dependency_index]].name.clone());
+            stacks.insert(dependency_index, dependency_stack);
+            indices.push_back(dependency_index);
+        }
+
+        let package_id = &graph.graph[index];
+        if !package_matches_license_filter(
+            cargo_metadata_parameters,
+            &scan_parameters.print_config.license_filter,
+            package_id,
+        ) || !package_matches_source_filter(
+            cargo_metadata_parameters,
+            &scan_parameters.print_config.source_filter,
+            package_id,
+        ) {
+            continue;
+        }
+        match geiger_context.package_id_to_metrics.get(package_id) {
+            Some(package_metrics) => rows.push(FoldedRow {
+                stack,
+                used: unsafe_stats(package_metrics, &rs_files_used).used,
+            }),
+            None => {
+                eprintln!(
+                    "WARNING: No metrics found for package: {}",
+                    package_id
+                );
+                warning_count += scan_parameters.args.deny_warnings as u64;
+            }
+        }
+    }
+
+    let crate_unsafe_usages = rows
+        .iter()
+        .map(|row| (row.stack.join(";"), row.used.clone()))
+        .collect::<Vec<_>>();
+    let over_max_score_count =
+        check_max_score(scan_parameters, &crate_unsafe_usages);
+    let category_policy_error_count =
+        check_category_policy(scan_parameters, &crate_unsafe_usages);
+    let unsafe_crate_count =
+        check_deny_unsafe(scan_parameters, &crate_unsafe_usages);
+    let unaudited_crate_count =
+        check_required_audits(scan_parameters, &crate_unsafe_usages);
+    let undocumented_unsafe_count = check_required_safety_docs(
+        scan_parameters,
+        cargo_metadata_parameters.metadata,
+        &geiger_context,
+        &rs_files_used,
+    );
+    let unsafe_limit_violation_count =
+        check_unsafe_limits(scan_parameters, &crate_unsafe_usages);
+
+    Ok(ScanResult {
+        category_policy_error_count,
+        checksum_mismatch_count: 0,
+        detection_outcome: None,
+        over_fail_threshold_ratio: None,
+        over_max_score_count,
+        scan_output_lines: create_folded_lines(rows),
+        unaudited_crate_count,
+        undocumented_unsafe_count,
+        unsafe_crate_count,
+        unsafe_limit_violation_count,
+        warning_count,
+    })
+}
@@ -0,0 +1,163 @@
+use crate::format::heatmap::{create_heatmap_lines, HeatmapRow};
+use crate::format::print_config::OutputFormat;
+use crate::format::Charset;
+use crate::graph::Graph;
+use crate::mapping::CargoMetadataParameters;
+use crate::merge::{merge_facades, merged_crate_name};
+
+use super::super::{
+    package_metrics, unsafe_stats, ScanDetails, ScanParameters, ScanResult,
+};
+use super::{
+    check_category_policy, check_deny_unsafe, check_max_score,
+    check_required_audits, check_required_safety_docs, check_unsafe_limits,
+    collapse_workspace,
+};
+
+use cargo::CliError;
+use cargo_metadata::PackageId;
+use std::collections::HashSet;
+
+pub fn scan_to_heatmap(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    graph: &Graph,
+    root_package_id: PackageId,
+    scan_parameters: &ScanParameters,
+    scan_details: ScanDetails,
+) -> Result<ScanResult, CliError> {
+    let ScanDetails {
+        rs_files_used,
+        geiger_context,
+        ..
+    } = scan_details;
+
+    let package_metrics = package_metrics(
+        cargo_metadata_parameters,
+        &geiger_context,
+        graph,
+        scan_parameters.print_config,
+        root_package_id,
+    );
+    let warning_count = if scan_parameters.args.deny_warnings {
+        package_metrics
+            .iter()
+            .filter(|(_, package_metrics_option)| {
+                package_metrics_option.is_none()
+            })
+            .count() as u64
+    } else {
+        0
+    };
+
+    let (package_unsafe_infos, merged_crates) = merge_facades(
+        &scan_parameters.args.merge,
+        package_metrics
+            .into_iter()
+            .filter_map(|(package_info, package_metrics_option)| {
+                let unsafe_info =
+                    unsafe_stats(&package_metrics_option?, &rs_files_used);
+                Some((package_info, unsafe_info))
+            })
+            .collect(),
+    );
+    let (package_unsafe_infos, workspace_row) = collapse_workspace(
+        cargo_metadata_parameters,
+        scan_parameters,
+        package_unsafe_infos,
+    );
+    let mut rows = package_unsafe_infos
+        .into_iter()
+        .map(|(package_info, unsafe_info)| HeatmapRow {
+            crate_name: package_info.id.name.clone(),
+            downloads: scan_parameters.download_counts.and_then(
+                |download_counts| download_counts.get(&package_info.id.name),
+            ),
+            name: merged_crate_name(&merged_crates, &package_info.id),
+            used: unsafe_info.used,
+        })
+        .collect::<Vec<HeatmapRow>>();
+    if let Some(workspace_row) = workspace_row {
+        rows.push(HeatmapRow {
+            crate_name: String::from("workspace"),
+            downloads: None,
+            name: workspace_row.name(),
+            used: workspace_row.used,
+        });
+    }
+    let crate_unsafe_usages = rows
+        .iter()
+        .map(|row| (row.name.clone(), row.used.clone()))
+        .collect::<Vec<_>>();
+    let over_max_score_count =
+        check_max_score(scan_parameters, &crate_unsafe_usages);
+    let category_policy_error_count =
+        check_category_policy(scan_parameters, &crate_unsafe_usages);
+    let unsafe_crate_count =
+        check_deny_unsafe(scan_parameters, &crate_unsafe_usages);
+    let unaudited_crate_count =
+        check_required_audits(scan_parameters, &crate_unsafe_usages);
+    let undocumented_unsafe_count = check_required_safety_docs(
+        scan_parameters,
+        cargo_metadata_parameters.metadata,
+        &geiger_context,
+        &rs_files_used,
+    );
+    let unsafe_limit_violation_count =
+        check_unsafe_limits(scan_parameters, &crate_unsafe_usages);
+
+    if let Some(crate_order) = scan_parameters.crate_order {
+        let metadata = cargo_metadata_parameters.metadata;
+        let crate_names = graph
+            .graph
+            .node_weights()
+            .map(|package_id| metadata[package_id].name.as_str())
+            .collect::<HashSet<&str>>();
+        let missing_crate_names = crate_order.missing_crate_names(&crate_names);
+        if !missing_crate_names.is_empty() {
+            eprintln!(
+                "WARNING: Crates in the order file which are not in the \
+                dependency graph: {}",
+                missing_crate_names.join(", ")
+            );
+        }
+    }
+
+    if scan_parameters.args.sort_by_unpopular_unsafe
+        && scan_parameters.download_counts.is_none()
+    {
+        eprintln!(
+            "NOTE: `--sort-by-unpopular-unsafe` needs `--downloads`, \
+            download counts are not fetched. Sorting by unsafe usage alone."
+        );
+    }
+
+    // Shading relies on block characters, fall back to plain numbers where
+    // only a reduced charset, or no styling, is wanted.
+    let shade = match (
+        scan_parameters.print_config.charset,
+        scan_parameters.print_config.output_format,
+    ) {
+        (Charset::Ascii, _) | (_, OutputFormat::GitHubMarkdown) => false,
+        _ => scan_parameters.print_config.color.is_enabled(),
+    };
+
+    Ok(ScanResult {
+        category_policy_error_count,
+        checksum_mismatch_count: 0,
+        detection_outcome: None,
+        over_fail_threshold_ratio: None,
+        over_max_score_count,
+        scan_output_lines: create_heatmap_lines(
+            rows,
+            shade,
+            scan_parameters.download_counts.is_some(),
+            scan_parameters.args.sort_by_unpopular_unsafe,
+            scan_parameters.crate_order,
+        ),
+        unaudited_crate_count,
+        undocumented_unsafe_count,
+        unsafe_crate_count,
+        unsafe_limit_violation_count,
+        warning_count,
+    })
+}
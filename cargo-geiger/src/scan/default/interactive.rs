@@ -0,0 +1,179 @@
+use crate::format::CrateDetectionStatus;
+use crate::graph::Graph;
+use crate::interactive::{
+    run_interactive, InteractiveCrate, InteractiveTree, UnsafeFile,
+};
+use crate::mapping::CargoMetadataParameters;
+
+use super::super::{
+    package_metrics, unsafe_stats, ScanDetails, ScanParameters, ScanResult,
+};
+use super::{
+    check_category_policy, check_deny_unsafe, check_max_score,
+    check_required_audits, check_required_safety_docs, check_unsafe_limits,
+};
+
+use cargo::CliError;
+use cargo_metadata::PackageId;
+use petgraph::visit::EdgeRef;
+use std::collections::HashMap;
+use std::io;
+
+pub fn scan_to_interactive(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    graph: &Graph,
+    root_package_id: PackageId,
+    scan_parameters: &ScanParameters,
+    scan_details: ScanDetails,
+) -> Result<ScanResult, CliError> {
+    let ScanDetails {
+        rs_files_used,
+        geiger_context,
+        ..
+    } = scan_details;
+    let metadata = cargo_metadata_parameters.metadata;
+
+    // The crates of the tree are those reachable from the root with the
+    // dependency kinds printed.
+    let mut crate_indices = HashMap::from([(root_package_id.clone(), 0)]);
+    let mut package_ids = vec![root_package_id.clone()];
+    let mut dependencies = vec![];
+    let mut index = 0;
+    while index < package_ids.len() {
+        let node_index = graph.nodes[&package_ids[index]];
+        let mut crate_dependencies = vec![];
+        for edge in graph.graph.edges(node_index).filter(|edge| {
+            scan_parameters
+                .print_config
+                .include_build_deps
+                .allows(*edge.weight())
+        }) {
+            let dependency_id = &graph.graph[edge.target()];
+            let dependency_index = *crate_indices
+                .entry(dependency_id.clone())
+                .or_insert_with(|| {
+                    package_ids.push(dependency_id.clone());
+                    package_ids.len() - 1
+                });
+            if !crate_dependencies.contains(&dependency_index) {
+                crate_dependencies.push(dependency_index);
+            }
+        }
+        dependencies.push(crate_dependencies);
+        index += 1;
+    }
+
+    let crates = package_ids
+        .iter()
+        .map(|package_id| {
+            let package_metrics =
+                geiger_context.package_id_to_metrics.get(package_id);
+            let unsafe_counts = package_metrics.map(|package_metrics| {
+                let unsafe_info = unsafe_stats(package_metrics, &rs_files_used);
+                let used = unsafe_info.used.total_unsafe();
+                (
+                    CrateDetectionStatus::from(&unsafe_info),
+                    used,
+                    used + unsafe_info.unused.total_unsafe(),
+                )
+            });
+            let package_root = metadata[package_id]
+                .manifest_path
+                .parent()
+                .map(|path| path.as_std_path().to_path_buf());
+            let mut unsafe_files = package_metrics
+                .into_iter()
+                .flat_map(|package_metrics| &package_metrics.rs_path_to_metrics)
+                .map(|(path, rs_file_metrics_wrapper)| UnsafeFile {
+                    path: package_root
+                        .as_ref()
+                        .and_then(|root| path.strip_prefix(root).ok())
+                        .unwrap_or(path)
+                        .to_path_buf(),
+                    unsafe_count: rs_file_metrics_wrapper
+                        .metrics
+                        .counters
+                        .total_unsafe(),
+                    used_by_build: rs_files_used.contains(path),
+                })
+                .filter(|unsafe_file| unsafe_file.unsafe_count > 0)
+                .collect::<Vec<_>>();
+            unsafe_files.sort_by(|a, b| a.path.cmp(&b.path));
+            InteractiveCrate {
+                name: metadata[package_id].name.clone(),
+                version: metadata[package_id].version.clone(),
+                unsafe_counts,
+                unsafe_files,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut interactive_tree = InteractiveTree::new(crates, dependencies, 0);
+    run_interactive(
+        &mut interactive_tree,
+        scan_parameters.print_config,
+        io::stdin().lock(),
+        io::stdout().lock(),
+    )
+    .map_err(|e| CliError::new(anyhow::Error::from(e), 1))?;
+
+    // The gates apply after the tree is explored, as for the other outputs.
+    let package_metrics = package_metrics(
+        cargo_metadata_parameters,
+        &geiger_context,
+        graph,
+        scan_parameters.print_config,
+        root_package_id,
+    );
+    let warning_count = if scan_parameters.args.deny_warnings {
+        package_metrics
+            .iter()
+            .filter(|(_, package_metrics_option)| {
+                package_metrics_option.is_none()
+            })
+            .count() as u64
+    } else {
+        0
+    };
+    let crate_unsafe_usages = package_metrics
+        .into_iter()
+        .filter_map(|(package_info, package_metrics_option)| {
+            let unsafe_info =
+                unsafe_stats(&package_metrics_option?, &rs_files_used);
+            Some((
+                format!("{} {}", package_info.id.name, package_info.id.version),
+                unsafe_info.used,
+            ))
+        })
+        .collect::<Vec<_>>();
+    let over_max_score_count =
+        check_max_score(scan_parameters, &crate_unsafe_usages);
+    let category_policy_error_count =
+        check_category_policy(scan_parameters, &crate_unsafe_usages);
+    let unsafe_crate_count =
+        check_deny_unsafe(scan_parameters, &crate_unsafe_usages);
+    let unaudited_crate_count =
+        check_required_audits(scan_parameters, &crate_unsafe_usages);
+    let undocumented_unsafe_count = check_required_safety_docs(
+        scan_parameters,
+        cargo_metadata_parameters.metadata,
+        &geiger_context,
+        &rs_files_used,
+    );
+    let unsafe_limit_violation_count =
+        check_unsafe_limits(scan_parameters, &crate_unsafe_usages);
+
+    Ok(ScanResult {
+        category_policy_error_count,
+        checksum_mismatch_count: 0,
+        detection_outcome: None,
+        over_fail_threshold_ratio: None,
+        over_max_score_count,
+        scan_output_lines: vec![],
+        unaudited_crate_count,
+        undocumented_unsafe_count,
+        unsafe_crate_count,
+        unsafe_limit_violation_count,
+        warning_count,
+    })
+}
@@ -0,0 +1,133 @@
+use crate::format::license_groups::{
+    create_license_group_json_line, create_license_group_lines, LicenseGroup,
+    LicenseGroupRow,
+};
+use crate::format::print_config::OutputFormat;
+use crate::graph::Graph;
+use crate::mapping::{CargoMetadataParameters, GetPackageIdInformation};
+
+use super::super::{
+    package_metrics, unsafe_stats, ScanDetails, ScanParameters, ScanResult,
+};
+use super::{
+    check_category_policy, check_deny_unsafe, check_max_score,
+    check_required_audits, check_required_safety_docs, check_unsafe_limits,
+};
+
+use cargo::CliError;
+use cargo_metadata::PackageId;
+
+pub fn scan_to_license_groups(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    graph: &Graph,
+    root_package_id: PackageId,
+    scan_parameters: &ScanParameters,
+    scan_details: ScanDetails,
+) -> Result<ScanResult, CliError> {
+    let ScanDetails {
+        rs_files_used,
+        geiger_context,
+        ..
+    } = scan_details;
+
+    let package_metrics = package_metrics(
+        cargo_metadata_parameters,
+        &geiger_context,
+        graph,
+        scan_parameters.print_config,
+        root_package_id,
+    );
+    let warning_count = if scan_parameters.args.deny_warnings {
+        package_metrics
+            .iter()
+            .filter(|(_, package_metrics_option)| {
+                package_metrics_option.is_none()
+            })
+            .count() as u64
+    } else {
+        0
+    };
+
+    let rows = package_metrics
+        .into_iter()
+        .filter_map(|(package_info, package_metrics_option)| {
+            let unsafe_info =
+                unsafe_stats(&package_metrics_option?, &rs_files_used);
+            Some(LicenseGroupRow {
+                license_group: LicenseGroup::from_license(license(
+                    cargo_metadata_parameters,
+                    &package_info.id,
+                )),
+                name: format!(
+                    "{} {}",
+                    package_info.id.name, package_info.id.version
+                ),
+                unused: unsafe_info.unused,
+                used: unsafe_info.used,
+            })
+        })
+        .collect::<Vec<LicenseGroupRow>>();
+    let crate_unsafe_usages = rows
+        .iter()
+        .map(|row| (row.name.clone(), row.used.clone()))
+        .collect::<Vec<_>>();
+    let over_max_score_count =
+        check_max_score(scan_parameters, &crate_unsafe_usages);
+    let category_policy_error_count =
+        check_category_policy(scan_parameters, &crate_unsafe_usages);
+    let unsafe_crate_count =
+        check_deny_unsafe(scan_parameters, &crate_unsafe_usages);
+    let unaudited_crate_count =
+        check_required_audits(scan_parameters, &crate_unsafe_usages);
+    let undocumented_unsafe_count = check_required_safety_docs(
+        scan_parameters,
+        cargo_metadata_parameters.metadata,
+        &geiger_context,
+        &rs_files_used,
+    );
+    let unsafe_limit_violation_count =
+        check_unsafe_limits(scan_parameters, &crate_unsafe_usages);
+
+    let scan_output_lines = match scan_parameters.print_config.output_format {
+        OutputFormat::Json => vec![create_license_group_json_line(rows)],
+        output_format => create_license_group_lines(
+            rows,
+            scan_parameters.print_config.hide_zeros,
+            output_format,
+        ),
+    };
+
+    Ok(ScanResult {
+        category_policy_error_count,
+        checksum_mismatch_count: 0,
+        detection_outcome: None,
+        over_fail_threshold_ratio: None,
+        over_max_score_count,
+        scan_output_lines,
+        unaudited_crate_count,
+        undocumented_unsafe_count,
+        unsafe_crate_count,
+        unsafe_limit_violation_count,
+        warning_count,
+    })
+}
+
+/// The license of the crate, as printed by `{l}` in `--format`
+fn license(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    package_id: &cargo_geiger_serde::PackageId,
+) -> Option<String> {
+    cargo_metadata_parameters
+        .metadata
+        .packages
+        .iter()
+        .find(|package| {
+            package.name == package_id.name
+                && package.version == package_id.version
+        })
+        .and_then(|package| {
+            package
+                .id
+                .get_package_id_licence(cargo_metadata_parameters.krates)
+        })
+}
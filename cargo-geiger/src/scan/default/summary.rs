@@ -0,0 +1,129 @@
+use crate::format::summary::{
+    create_top_crate_lines, CrateCounts, Summary, SummaryFormat, TopCrateRow,
+};
+use crate::format::table::unsafe_ratio_over_threshold;
+use crate::format::CrateDetectionStatus;
+use crate::graph::Graph;
+use crate::mapping::CargoMetadataParameters;
+
+use super::super::{
+    package_metrics, unsafe_stats, ScanDetails, ScanParameters, ScanResult,
+};
+use super::{
+    check_category_policy, check_deny_unsafe, check_max_score,
+    check_required_audits, check_required_safety_docs, check_unsafe_limits,
+};
+
+use cargo::CliError;
+use cargo_geiger_serde::CounterBlock;
+use cargo_metadata::PackageId;
+
+pub fn scan_to_summary(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    graph: &Graph,
+    root_package_id: PackageId,
+    scan_parameters: &ScanParameters,
+    scan_details: ScanDetails,
+) -> Result<ScanResult, CliError> {
+    let ScanDetails {
+        rs_files_used,
+        geiger_context,
+        ..
+    } = scan_details;
+
+    let package_metrics = package_metrics(
+        cargo_metadata_parameters,
+        &geiger_context,
+        graph,
+        scan_parameters.print_config,
+        root_package_id,
+    );
+    let warning_count = if scan_parameters.args.deny_warnings {
+        package_metrics
+            .iter()
+            .filter(|(_, package_metrics_option)| {
+                package_metrics_option.is_none()
+            })
+            .count() as u64
+    } else {
+        0
+    };
+
+    let mut summary = Summary {
+        used: CounterBlock::default(),
+        unused: CounterBlock::default(),
+        crates: CrateCounts::default(),
+    };
+    let rows = package_metrics
+        .into_iter()
+        .filter_map(|(package_info, package_metrics_option)| {
+            let unsafe_info =
+                unsafe_stats(&package_metrics_option?, &rs_files_used);
+            summary.used += unsafe_info.used.clone();
+            summary.unused += unsafe_info.unused.clone();
+            summary
+                .crates
+                .count(CrateDetectionStatus::from(&unsafe_info));
+            Some(TopCrateRow {
+                name: package_info.id.name,
+                version: package_info.id.version,
+                used: unsafe_info.used,
+                unused: unsafe_info.unused,
+            })
+        })
+        .collect::<Vec<TopCrateRow>>();
+    let crate_unsafe_usages = rows
+        .iter()
+        .map(|row| (format!("{} {}", row.name, row.version), row.used.clone()))
+        .collect::<Vec<_>>();
+    let over_max_score_count =
+        check_max_score(scan_parameters, &crate_unsafe_usages);
+    let category_policy_error_count =
+        check_category_policy(scan_parameters, &crate_unsafe_usages);
+    let unsafe_crate_count =
+        check_deny_unsafe(scan_parameters, &crate_unsafe_usages);
+    let unaudited_crate_count =
+        check_required_audits(scan_parameters, &crate_unsafe_usages);
+    let undocumented_unsafe_count = check_required_safety_docs(
+        scan_parameters,
+        cargo_metadata_parameters.metadata,
+        &geiger_context,
+        &rs_files_used,
+    );
+    let unsafe_limit_violation_count =
+        check_unsafe_limits(scan_parameters, &crate_unsafe_usages);
+    let over_fail_threshold_ratio = scan_parameters
+        .args
+        .fail_threshold
+        .and_then(|fail_threshold| {
+            unsafe_ratio_over_threshold(
+                fail_threshold,
+                &summary.used,
+                &summary.unused,
+            )
+        });
+
+    let mut scan_output_lines = match scan_parameters.args.summary_format {
+        SummaryFormat::Json => vec![summary.json_line()],
+        SummaryFormat::Table | SummaryFormat::Text => summary.text_lines(),
+    };
+    if let Some(summary_top) = scan_parameters.args.summary_top {
+        scan_output_lines.push(String::new());
+        scan_output_lines
+            .append(&mut create_top_crate_lines(rows, summary_top));
+    }
+
+    Ok(ScanResult {
+        category_policy_error_count,
+        checksum_mismatch_count: 0,
+        detection_outcome: None,
+        over_fail_threshold_ratio,
+        over_max_score_count,
+        scan_output_lines,
+        unaudited_crate_count,
+        undocumented_unsafe_count,
+        unsafe_crate_count,
+        unsafe_limit_violation_count,
+        warning_count,
+    })
+}
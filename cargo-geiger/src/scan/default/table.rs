@@ -1,38 +1,55 @@
 use crate::args::Verbosity;
+use crate::format::categories::{category_breakdowns, create_categories_lines};
 use crate::format::emoji_symbols::EmojiSymbols;
-use crate::format::print_config::OutputFormat;
+use crate::format::plain::create_plain_header_line;
+use crate::format::print_config::{OutputFormat, Prefix};
 use crate::format::table::{
-    create_table_from_text_tree_lines, TableParameters, UNSAFE_COUNTERS_HEADER,
+    create_table_from_text_tree_lines, table_header, TableParameters,
 };
 use crate::format::SymbolKind;
 use crate::graph::Graph;
-use crate::mapping::CargoMetadataParameters;
-use crate::tree::traversal::walk_dependency_tree;
+use crate::mapping::{
+    CargoMetadataParameters, GetPackageIdInformation, ToCargoGeigerPackageId,
+};
+use crate::tree::traversal::{walk_dependency_tree, SiblingOrder};
+use crate::tree::{compare_siblings, sort_text_tree_lines, TextTreeLine};
 
+use super::super::dangerous_apis::{
+    construct_dangerous_api_lines, dangerous_apis,
+};
+use super::super::local_targets::construct_local_target_lines;
+use super::super::safety_docs::check_safety_docs;
+use super::super::snippets::construct_snippet_lines;
+use super::super::target_breakdown::construct_target_breakdown_lines;
 use super::super::{
     construct_rs_files_used_lines, list_files_used_but_not_scanned,
-    ScanDetails, ScanParameters, ScanResult,
+    package_metrics, unsafe_stats, ScanDetails, ScanParameters, ScanResult,
+};
+use super::{
+    check_category_policy, check_deny_unsafe, check_max_score,
+    check_required_audits, check_send_sync_policy, check_unsafe_limits,
 };
-use super::scan;
 
-use cargo::core::Workspace;
 use cargo::CliError;
-use cargo_metadata::PackageId;
+use cargo_geiger_serde::{ScanWarning, UnsafeInfo};
+use cargo_metadata::{Metadata, PackageId};
 use colored::Colorize;
+use std::collections::{HashMap, HashSet};
 
 pub fn scan_to_table(
     cargo_metadata_parameters: &CargoMetadataParameters,
     graph: &Graph,
     root_package_id: PackageId,
     scan_parameters: &ScanParameters,
-    workspace: &Workspace,
+    scan_details: ScanDetails,
 ) -> Result<ScanResult, CliError> {
     let mut combined_scan_output_lines = Vec::<String>::new();
 
     let ScanDetails {
         rs_files_used,
         geiger_context,
-    } = scan(cargo_metadata_parameters, scan_parameters, workspace)?;
+        local_target_files_used,
+    } = scan_details;
 
     if scan_parameters.args.verbosity != Verbosity::Quiet {
         let mut rs_files_used_lines =
@@ -40,35 +57,220 @@ pub fn scan_to_table(
         combined_scan_output_lines.append(&mut rs_files_used_lines);
     }
 
-    let emoji_symbols =
-        EmojiSymbols::new(scan_parameters.print_config.output_format);
+    let emoji_symbols = EmojiSymbols::new(
+        scan_parameters.print_config.color,
+        scan_parameters.print_config.color_scheme,
+        scan_parameters.print_config.output_format,
+        scan_parameters.print_config.symbols,
+    );
     let mut output_key_lines = construct_key_lines(
         &emoji_symbols,
+        scan_parameters.print_config.ffi,
         scan_parameters.print_config.output_format,
     );
     combined_scan_output_lines.append(&mut output_key_lines);
 
-    let text_tree_lines = walk_dependency_tree(
+    // The unsafe usage of each crate is only needed by the gates, the table
+    // itself is built while walking the dependency tree.
+    let crate_unsafe_usages = if scan_parameters.args.max_score.is_some()
+        || scan_parameters.args.category_policy.is_some()
+        || scan_parameters.args.deny_unsafe
+        || scan_parameters.args.require_audits
+        || scan_parameters.args.max_unsafe.is_some()
+        || scan_parameters.args.max_total_unsafe.is_some()
+    {
+        let workspace_member_ids = workspace_member_ids(
+            cargo_metadata_parameters.metadata,
+            scan_parameters.print_config.workspace_only,
+        );
+        package_metrics(
+            cargo_metadata_parameters,
+            &geiger_context,
+            graph,
+            scan_parameters.print_config,
+            root_package_id.clone(),
+        )
+        .into_iter()
+        .filter(|(package_info, _)| {
+            workspace_member_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&package_info.id))
+        })
+        .filter_map(|(package_info, package_metrics_option)| {
+            let unsafe_info =
+                unsafe_stats(&package_metrics_option?, &rs_files_used);
+            Some((
+                format!("{} {}", package_info.id.name, package_info.id.version),
+                unsafe_info.used,
+            ))
+        })
+        .collect::<Vec<_>>()
+    } else {
+        vec![]
+    };
+    let over_max_score_count =
+        check_max_score(scan_parameters, &crate_unsafe_usages);
+    let category_policy_error_count =
+        check_category_policy(scan_parameters, &crate_unsafe_usages)
+            + check_send_sync_policy(
+                scan_parameters,
+                cargo_metadata_parameters.metadata,
+                &geiger_context,
+                &rs_files_used,
+            );
+    let unsafe_crate_count =
+        check_deny_unsafe(scan_parameters, &crate_unsafe_usages);
+    let unaudited_crate_count =
+        check_required_audits(scan_parameters, &crate_unsafe_usages);
+    let unsafe_limit_violation_count =
+        check_unsafe_limits(scan_parameters, &crate_unsafe_usages);
+
+    let sibling_order = scan_parameters
+        .print_config
+        .sort
+        .filter(|crate_sort| crate_sort.keeps_tree())
+        .map(|crate_sort| {
+            let unsafe_infos = geiger_context
+                .package_id_to_metrics
+                .iter()
+                .map(|(package_id, package_metrics)| {
+                    (
+                        package_id.clone(),
+                        unsafe_stats(package_metrics, &rs_files_used),
+                    )
+                })
+                .collect::<HashMap<_, _>>();
+            let no_unsafe_info = UnsafeInfo::default();
+            move |a: &PackageId, b: &PackageId| {
+                compare_siblings(
+                    crate_sort,
+                    unsafe_infos.get(a).unwrap_or(&no_unsafe_info),
+                    unsafe_infos.get(b).unwrap_or(&no_unsafe_info),
+                )
+            }
+        });
+    let mut text_tree_lines = walk_dependency_tree(
         cargo_metadata_parameters,
         graph,
         scan_parameters.print_config,
-        root_package_id,
+        root_package_id.clone(),
+        sibling_order
+            .as_ref()
+            .map(|sibling_order| sibling_order as SiblingOrder),
     );
+    if let Some(crate_sort) = scan_parameters
+        .print_config
+        .sort
+        .filter(|crate_sort| !crate_sort.keeps_tree())
+    {
+        let prefix = scan_parameters.print_config.prefix;
+        if prefix == Prefix::Indent {
+            eprintln!(
+                "WARNING: `--sort` can't keep the indentation of the tree, \
+                printing a flat list instead."
+            );
+        }
+        text_tree_lines = sort_text_tree_lines(
+            crate_sort,
+            matches!(prefix, Prefix::Depth | Prefix::DepthColumn),
+            |package_id| {
+                let total_unsafe = geiger_context
+                    .package_id_to_metrics
+                    .get(package_id)
+                    .map_or(0, |package_metrics| {
+                        unsafe_stats(package_metrics, &rs_files_used)
+                            .used
+                            .total_unsafe()
+                    });
+                let name = package_id
+                    .get_package_id_name_and_version(
+                        cargo_metadata_parameters.krates,
+                    )
+                    .map_or_else(|| package_id.repr.clone(), |(name, _)| name);
+                (total_unsafe, name)
+            },
+            text_tree_lines,
+        );
+    }
+    if scan_parameters.print_config.only_unsafe {
+        text_tree_lines = flatten_text_tree_lines(text_tree_lines);
+    }
     let table_parameters = TableParameters {
+        advisories: scan_parameters.advisories,
+        audits: scan_parameters.audits,
+        checksum_statuses: scan_parameters.checksum_statuses,
+        download_counts: scan_parameters.download_counts,
+        ecosystem_stats: scan_parameters.ecosystem_stats,
+        expanded_counts: scan_parameters.expanded_counts,
+        fail_threshold: scan_parameters.args.fail_threshold,
         geiger_context: &geiger_context,
         print_config: scan_parameters.print_config,
+        root_package_id: &root_package_id,
         rs_files_used: &rs_files_used,
+        security_policies: scan_parameters.security_policies,
+        summary_format: scan_parameters.args.summary_format,
+        unsafe_history: scan_parameters.unsafe_history,
     };
 
     let ScanResult {
+        over_fail_threshold_ratio,
         mut scan_output_lines,
         mut warning_count,
+        ..
     } = create_table_from_text_tree_lines(
         cargo_metadata_parameters,
         &table_parameters,
         text_tree_lines,
     );
     combined_scan_output_lines.append(&mut scan_output_lines);
+    combined_scan_output_lines.append(&mut construct_local_target_lines(
+        &scan_parameters.args.local_targets_args,
+        &geiger_context,
+        &local_target_files_used,
+        cargo_metadata_parameters.metadata,
+    ));
+    combined_scan_output_lines.append(&mut construct_target_breakdown_lines(
+        scan_parameters.args.target_breakdown,
+        &geiger_context,
+        scan_parameters.print_config.include_tests,
+        cargo_metadata_parameters.metadata,
+        // The examples and benches of `--scan-examples` and `--scan-benches`
+        // are broken down with the rest.
+        &rs_files_used
+            .union(&local_target_files_used)
+            .cloned()
+            .collect(),
+    ));
+
+    if scan_parameters.args.categories {
+        let crate_unsafe_infos = package_metrics(
+            cargo_metadata_parameters,
+            &geiger_context,
+            graph,
+            scan_parameters.print_config,
+            root_package_id.clone(),
+        )
+        .into_iter()
+        .filter_map(|(package_info, package_metrics_option)| {
+            Some((
+                package_info.id,
+                unsafe_stats(&package_metrics_option?, &rs_files_used),
+            ))
+        })
+        .collect::<Vec<_>>();
+        combined_scan_output_lines.append(&mut create_categories_lines(
+            &category_breakdowns(&crate_unsafe_infos),
+        ));
+    }
+
+    if let Some(dangerous_apis) = dangerous_apis(scan_parameters.args) {
+        combined_scan_output_lines.append(&mut construct_dangerous_api_lines(
+            &dangerous_apis,
+            &geiger_context,
+            cargo_metadata_parameters.metadata,
+            &rs_files_used,
+        ));
+    }
 
     let used_but_not_scanned =
         list_files_used_but_not_scanned(&geiger_context, &rs_files_used);
@@ -80,16 +282,115 @@ pub fn scan_to_table(
         );
     }
 
+    let mut undocumented_unsafe_count = 0;
+    if scan_parameters.args.require_safety_docs {
+        let (count, mut undocumented_unsafe_lines) = check_safety_docs(
+            &geiger_context,
+            cargo_metadata_parameters.metadata,
+            &rs_files_used,
+        );
+        undocumented_unsafe_count = count;
+        combined_scan_output_lines.append(&mut undocumented_unsafe_lines);
+    }
+
+    if scan_parameters.args.show_snippets {
+        combined_scan_output_lines.append(&mut construct_snippet_lines(
+            &geiger_context,
+            cargo_metadata_parameters.metadata,
+            &rs_files_used,
+        ));
+    }
+
+    if scan_parameters.print_config.lossy {
+        combined_scan_output_lines.append(&mut construct_scan_warning_lines(
+            &geiger_context.scan_warnings,
+        ));
+    }
+
     Ok(ScanResult {
+        category_policy_error_count,
+        checksum_mismatch_count: 0,
+        detection_outcome: None,
+        over_fail_threshold_ratio,
+        over_max_score_count,
         scan_output_lines: combined_scan_output_lines,
+        unaudited_crate_count,
+        undocumented_unsafe_count,
+        unsafe_crate_count,
+        unsafe_limit_violation_count,
         warning_count,
     })
 }
 
+/// With `--workspace-only`, the workspace members, the only crates which fail
+/// the gates
+fn workspace_member_ids(
+    metadata: &Metadata,
+    workspace_only: bool,
+) -> Option<HashSet<cargo_geiger_serde::PackageId>> {
+    workspace_only.then(|| {
+        metadata
+            .workspace_members
+            .iter()
+            .filter_map(|package_id| {
+                package_id.to_cargo_geiger_package_id(metadata)
+            })
+            .collect()
+    })
+}
+
+/// With `--lossy`, the files which failed to be scanned or were scanned
+/// lossily, after the table
+fn construct_scan_warning_lines(scan_warnings: &[ScanWarning]) -> Vec<String> {
+    if scan_warnings.is_empty() {
+        return vec![];
+    }
+    let mut scan_warnings = scan_warnings.to_vec();
+    scan_warnings.sort();
+    let mut lines = vec![String::new(), String::from("Scan warnings:")];
+    lines.extend(scan_warnings.iter().map(|scan_warning| {
+        format!(
+            "    {}: {}",
+            scan_warning.path.display(),
+            scan_warning.message
+        )
+    }));
+    lines
+}
+
+/// The package lines without their tree vines, for the flat list of
+/// `--only-unsafe`
+fn flatten_text_tree_lines(
+    text_tree_lines: Vec<TextTreeLine>,
+) -> Vec<TextTreeLine> {
+    text_tree_lines
+        .into_iter()
+        .filter_map(|text_tree_line| match text_tree_line {
+            TextTreeLine::Package {
+                id,
+                pruned_package_ids,
+                ..
+            } => Some(TextTreeLine::Package {
+                id,
+                pruned_package_ids,
+                tree_vines: String::new(),
+            }),
+            TextTreeLine::ExtraDepsGroup { .. } => None,
+        })
+        .collect()
+}
+
 fn construct_key_lines(
     emoji_symbols: &EmojiSymbols,
+    ffi: bool,
     output_format: OutputFormat,
 ) -> Vec<String> {
+    // The columns of the Plain output are the same in each run, so they are
+    // named instead of explained.
+    if output_format == OutputFormat::Plain {
+        return vec![create_plain_header_line()];
+    }
+
     let mut output_key_lines = vec![String::new()];
 
     match output_format {
@@ -114,9 +415,15 @@ fn construct_key_lines(
         }
     }
     output_key_lines.push(String::new());
+    if !emoji_symbols.will_output_symbols() {
+        return push_table_key_lines(ffi, output_format, output_key_lines);
+    }
     output_key_lines.push(String::from("Symbols: "));
 
     let forbids = "No `unsafe` usage found, declares #![forbid(unsafe_code)]";
+    let denies = "No `unsafe` usage found, declares #![deny(unsafe_code)]";
+    let conditionally_forbids = "No `unsafe` usage found, declares \
+        #![forbid(unsafe_code)] only within #![cfg_attr(<condition>, ...)]";
     let unknown = "No `unsafe` usage found, missing #![forbid(unsafe_code)]";
     let guilty = "`unsafe` usage found";
 
@@ -132,6 +439,8 @@ fn construct_key_lines(
 
     let symbol_kinds_to_string_values = vec![
         (SymbolKind::Lock, "", forbids),
+        (SymbolKind::OpenLock, "", denies),
+        (SymbolKind::KeyLock, "", conditionally_forbids),
         (SymbolKind::QuestionMark, "", unknown),
         (SymbolKind::Rads, shift_sequence, guilty),
     ];
@@ -149,11 +458,16 @@ fn construct_key_lines(
 
     output_key_lines.push(String::new());
 
-    let key = UNSAFE_COUNTERS_HEADER
-        .iter()
-        .map(|s| s.to_owned())
-        .collect::<Vec<_>>()
-        .join(" ");
+    push_table_key_lines(ffi, output_format, output_key_lines)
+}
+
+/// The header of the table columns, ending the key lines
+fn push_table_key_lines(
+    ffi: bool,
+    output_format: OutputFormat,
+    mut output_key_lines: Vec<String>,
+) -> Vec<String> {
+    let key = table_header(ffi).join(" ");
 
     match output_format {
         OutputFormat::GitHubMarkdown => output_key_lines.push(key),
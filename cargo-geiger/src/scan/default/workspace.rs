@@ -0,0 +1,186 @@
+use crate::format::json::ReportSummary;
+use crate::format::workspace::{
+    create_member_summary_lines, create_workspace_lines, MemberSummaryRow,
+};
+use crate::gate::DetectionOutcome;
+use crate::graph::Graph;
+use crate::mapping::CargoMetadataParameters;
+
+use super::super::{ScanParameters, ScanResult};
+use super::{
+    report_output_lines, safety_report_from_scan_details, scan,
+    scan_result_from_report, write_emitted_reports,
+};
+
+use cargo::core::Workspace;
+use cargo::CliError;
+use cargo_geiger_serde::SafetyReport;
+use cargo_metadata::PackageId;
+
+/// Scan the members given with `--workspace` in a single build. A report is
+/// made for the dependency graph of each member, and the crates of all of them
+/// are merged into the report which is printed.
+pub fn scan_to_workspace(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    graph: &Graph,
+    member_package_ids: &[PackageId],
+    scan_parameters: &ScanParameters,
+    workspace: &Workspace,
+) -> Result<ScanResult, CliError> {
+    let scan_details =
+        scan(cargo_metadata_parameters, scan_parameters, workspace)?;
+    let metadata = cargo_metadata_parameters.metadata;
+    let member_reports = member_package_ids
+        .iter()
+        .map(|member_package_id| {
+            let member_package = &metadata[member_package_id];
+            (
+                format!("{} {}", member_package.name, member_package.version),
+                safety_report_from_scan_details(
+                    cargo_metadata_parameters,
+                    graph,
+                    member_package_id.clone(),
+                    &scan_details,
+                    scan_parameters,
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+    let report = merge_member_reports(
+        member_reports
+            .iter()
+            .map(|(_, member_report)| member_report),
+    );
+    write_emitted_reports(
+        cargo_metadata_parameters,
+        &report,
+        None,
+        &scan_details,
+        scan_parameters,
+    )?;
+
+    let member_summary_lines =
+        if scan_parameters.args.workspace_args.member_summary {
+            create_member_summary_lines(
+                &member_reports
+                    .iter()
+                    .map(|(name, member_report)| MemberSummaryRow {
+                        name: name.clone(),
+                        summary: ReportSummary::from_report(member_report),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        } else {
+            vec![]
+        };
+    let output_format = scan_parameters.args.output_format;
+    let scan_output_lines = if output_format.is_machine_readable() {
+        // Keep stdout parseable, the table goes to stderr instead.
+        for member_summary_line in &member_summary_lines {
+            eprintln!("{}", member_summary_line);
+        }
+        report_output_lines(
+            cargo_metadata_parameters,
+            output_format,
+            &report,
+            None,
+            &scan_details,
+            scan_parameters,
+        )
+    } else {
+        let member_names = member_reports
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        let mut workspace_lines =
+            create_workspace_lines(&member_names, &report);
+        if !member_summary_lines.is_empty() {
+            workspace_lines.push(String::new());
+            workspace_lines.extend(member_summary_lines);
+        }
+        workspace_lines
+    };
+
+    Ok(ScanResult {
+        detection_outcome: Some(DetectionOutcome::from_report(&report)),
+        ..scan_result_from_report(
+            cargo_metadata_parameters,
+            &report,
+            &scan_details,
+            scan_parameters,
+            scan_output_lines,
+        )
+    })
+}
+
+/// The crates of the reports of all members, a crate depended upon by several
+/// of them having a single entry
+fn merge_member_reports<'a>(
+    member_reports: impl Iterator<Item = &'a SafetyReport>,
+) -> SafetyReport {
+    let mut report = SafetyReport::default();
+    for member_report in member_reports {
+        report.packages.extend(
+            member_report
+                .packages
+                .iter()
+                .map(|(package_id, entry)| (package_id.clone(), entry.clone())),
+        );
+        report
+            .packages_without_metrics
+            .extend(member_report.packages_without_metrics.iter().cloned());
+        report
+            .ignored_packages
+            .extend(member_report.ignored_packages.iter().cloned());
+        report
+            .used_but_not_scanned_files
+            .extend(member_report.used_but_not_scanned_files.iter().cloned());
+        for scan_warning in &member_report.scan_warnings {
+            if !report.scan_warnings.contains(scan_warning) {
+                report.scan_warnings.push(scan_warning.clone());
+            }
+        }
+    }
+    report.scan_warnings.sort();
+    report
+}
+
+#[cfg(test)]
+mod workspace_tests {
+    use super::*;
+
+    use crate::lib_tests::{create_path_package_id, create_safety_report};
+
+    use cargo_geiger_serde::{PackageInfo, UnsafeInfo};
+    use rstest::*;
+
+    fn report_of(names: &[&str]) -> SafetyReport {
+        create_safety_report(
+            names
+                .iter()
+                .map(|name| {
+                    (
+                        PackageInfo::new(create_path_package_id(name)),
+                        UnsafeInfo::default(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[rstest]
+    fn merge_member_reports_test() {
+        let member_reports =
+            [report_of(&["a", "shared"]), report_of(&["b", "shared"])];
+
+        let report = merge_member_reports(member_reports.iter());
+
+        let mut names = report
+            .packages
+            .keys()
+            .map(|package_id| package_id.name.as_str())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(names, ["a", "b", "shared"]);
+    }
+}
@@ -1,154 +1,421 @@
 use crate::format::print_config::PrintConfig;
-use crate::mapping::{CargoMetadataParameters, GetPackageRoot};
+use crate::ignore::IgnoreList;
+use crate::mapping::GetPackageRoot;
+use crate::scan::cache::{CacheEntry, ScanCache};
 use crate::scan::rs_file::{
     into_is_entry_point_and_path_buf, into_rs_code_file, into_target_kind,
     is_file_with_ext, RsFile, RsFileMetricsWrapper,
 };
 use crate::scan::PackageMetrics;
+use crate::source_map::SourceMap;
 
 use super::{GeigerContext, ScanMode};
 
+use cargo::util::{Progress, ProgressStyle};
 use cargo::{CargoResult, CliError, Config};
+use cargo_geiger_serde::ScanWarning;
 use cargo_metadata::PackageId;
-use geiger::find::find_unsafe_in_file;
+use geiger::detector::{detector_pipeline, Detector};
+use geiger::find::find_unsafe_in_file_with_detectors;
 use geiger::{IncludeTests, RsFileMetrics, ScanFileError};
-use rayon::{in_place_scope, prelude::*};
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
 use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::num::NonZeroUsize;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::sync_channel;
-use std::sync::{Arc, Mutex};
 use walkdir::WalkDir;
 
+/// The scan warning of a file decoded lossily with `--lossy`
+const LOSSY_SCAN_WARNING: &str =
+    "the file is not valid UTF-8, and was scanned with the invalid bytes replaced";
+
+/// Scan the files of the packages, which are usually all packages in the
+/// dependency graph
 pub fn find_unsafe(
-    cargo_metadata_parameters: &CargoMetadataParameters,
+    packages: &[cargo_metadata::Package],
     config: &Config,
     mode: ScanMode,
     print_config: &PrintConfig,
+    source_map: &SourceMap,
 ) -> Result<GeigerContext, CliError> {
-    let mut progress = cargo::util::Progress::new("Scanning", config);
+    // The files are scanned by a pool of their own, so that `--jobs` doesn't
+    // depend on whether the global pool was already used.
+    let thread_pool = ThreadPoolBuilder::new()
+        .num_threads(print_config.jobs.map_or(0, NonZeroUsize::get))
+        .build()
+        .map_err(|e| CliError::new(anyhow::Error::new(e), 1))?;
+    let scan_cache = ScanCache::new(
+        print_config.scan_cache_mode,
+        print_config.cache_dir.as_deref(),
+        print_config.caches_path_packages,
+    );
+    let mut progress =
+        Progress::with_style("Scanning", ProgressStyle::Ratio, config);
     let geiger_context = find_unsafe_in_packages_with_progress(
-        print_config.allow_partial_results,
-        cargo_metadata_parameters,
-        print_config.include_tests,
+        print_config,
         mode,
-        |progress_count, count| {
-            progress.tick(progress_count, count, "find_unsafe_tick")
+        packages,
+        source_map,
+        scan_cache.as_ref(),
+        &thread_pool,
+        |scanned_count, count, package_name| {
+            progress.tick(scanned_count, count, &format!(": {}", package_name))
         },
     );
     progress.clear();
     config.shell().status("Scanning", "done")?;
+    check_partial_results(
+        print_config.allow_partial_results,
+        &geiger_context.partially_scanned_package_ids,
+    )?;
     Ok(geiger_context)
 }
 
-fn find_unsafe_in_packages_with_progress<F>(
+#[derive(Debug)]
+pub struct PartialResultsError {
+    pub crate_count: usize,
+}
+
+impl Error for PartialResultsError {}
+
+impl fmt::Display for PartialResultsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Failed to scan files of {} crates, and `--no-partial-results` \
+            is set",
+            self.crate_count
+        )
+    }
+}
+
+/// Fail when files of any crate failed to be scanned, unless partial results
+/// are allowed, in which case the number of crates only partially scanned is
+/// printed
+fn check_partial_results(
     allow_partial_results: bool,
-    cargo_metadata_parameters: &CargoMetadataParameters,
-    include_tests: IncludeTests,
+    partially_scanned_package_ids: &HashSet<PackageId>,
+) -> Result<(), CliError> {
+    let crate_count = partially_scanned_package_ids.len();
+    if crate_count == 0 {
+        return Ok(());
+    }
+    if !allow_partial_results {
+        return Err(CliError::new(
+            anyhow::Error::new(PartialResultsError { crate_count }),
+            1,
+        ));
+    }
+    eprintln!(
+        "WARNING: Skipped the files of {} crates which failed to be scanned, \
+        their unsafe usage is only partially counted",
+        crate_count
+    );
+    Ok(())
+}
+
+fn find_unsafe_in_packages_with_progress<F>(
+    print_config: &PrintConfig,
     mode: ScanMode,
+    packages: &[cargo_metadata::Package],
+    source_map: &SourceMap,
+    scan_cache: Option<&ScanCache>,
+    thread_pool: &ThreadPool,
     mut progress_fn: F,
 ) -> GeigerContext
 where
-    F: FnMut(usize, usize) -> CargoResult<()>,
+    F: FnMut(usize, usize, &str) -> CargoResult<()>,
 {
     let mut res: Option<GeigerContext> = None;
     let (progress_sender, progress_receiver) = sync_channel(0);
-    let on_processed = move |count_processed, count| {
-        progress_sender.send((count_processed, count)).unwrap();
+    let on_processed = move |count_processed, count, package_name: &str| {
+        progress_sender
+            .send((count_processed, count, package_name.to_string()))
+            .unwrap();
     };
-    in_place_scope(|s| {
+    thread_pool.in_place_scope(|s| {
         s.spawn(|_| {
             res = Some(find_unsafe_in_packages(
-                allow_partial_results,
-                cargo_metadata_parameters,
-                include_tests,
+                print_config,
                 mode,
+                packages,
+                source_map,
+                scan_cache,
                 Some(on_processed),
             ))
         });
 
-        while let Ok((progress_counter, count)) = progress_receiver.recv() {
-            let _ = progress_fn(progress_counter, count);
+        while let Ok((progress_counter, count, package_name)) =
+            progress_receiver.recv()
+        {
+            let _ = progress_fn(progress_counter, count, &package_name);
         }
     });
     res.unwrap()
 }
 
+/// The outcome of scanning a single file of a package
+enum ScannedRsFile {
+    /// A build script, or a file left out by `--ignore` or `--ignore-path`,
+    /// which is not counted
+    Ignored(PathBuf),
+    /// A file which is not an entry point, with `--forbid-only`
+    Skipped,
+    Failed(PackageId, PathBuf, ScanFileError),
+    Scanned {
+        is_entry_point: bool,
+        /// Whether the file isn't valid UTF-8, and was decoded lossily
+        is_lossy: bool,
+        package_id: PackageId,
+        path_buf: PathBuf,
+        rs_file_metrics: RsFileMetrics,
+    },
+}
+
+/// The packages are walked and their files scanned on the current thread
+/// pool. The outcome of each file is collected in the order in which the
+/// files were found, and merged on a single thread in that order, so the
+/// results, and the errors printed, don't depend on which thread finishes
+/// first.
+///
+/// The files found in the cache are not scanned again, and the packages which
+/// were not found in it are written to it after a full scan.
+///
+/// `on_processed` is called with the name of each package once all its files
+/// are scanned, along with the number of packages scanned so far and in total.
 fn find_unsafe_in_packages<F>(
-    allow_partial_results: bool,
-    cargo_metadata_parameters: &CargoMetadataParameters,
-    include_tests: IncludeTests,
+    print_config: &PrintConfig,
     mode: ScanMode,
+    packages: &[cargo_metadata::Package],
+    source_map: &SourceMap,
+    scan_cache: Option<&ScanCache>,
     on_processed: Option<F>,
 ) -> GeigerContext
 where
-    F: Fn(usize, usize) + Send + Sync,
+    F: Fn(usize, usize, &str) + Send + Sync,
 {
-    let package_id_to_metrics = Arc::new(Mutex::new(HashMap::new()));
-    let ignored = Arc::new(Mutex::new(HashSet::new()));
-    let packages = cargo_metadata_parameters.metadata.packages.to_vec();
-    let package_code_files: Vec<_> =
-        find_rs_files_in_packages(&packages).collect();
-    let package_code_file_count = package_code_files.len();
+    let detectors = detector_pipeline(&print_config.detectors);
+    let cache_entries = match scan_cache {
+        Some(scan_cache) => find_cache_entries(
+            print_config.include_tests,
+            &detectors,
+            packages,
+            source_map,
+            scan_cache,
+        ),
+        None => HashMap::new(),
+    };
+    let package_code_files = find_rs_files_in_packages(
+        &print_config.ignore_list,
+        packages,
+        source_map,
+    );
+    let package_names = packages
+        .iter()
+        .map(|package| (&package.id, package.name.as_str()))
+        .collect::<HashMap<_, _>>();
+    let remaining_file_counts = remaining_file_counts(&package_code_files);
     let processed_count = AtomicUsize::new(0);
-    package_code_files.into_par_iter().for_each_with(
-        (package_id_to_metrics.clone(), ignored.clone()),
-        |(package_id_to_metrics, ignored), (package_id, rs_code_file)| {
-            if let RsFile::CustomBuildRoot(path_buf) = rs_code_file {
-                let mut ignored = ignored.lock().unwrap();
-                ignored.insert(path_buf);
-                return;
-            }
-            let (is_entry_point, path_buf) =
-                into_is_entry_point_and_path_buf(rs_code_file);
-            if let (false, ScanMode::EntryPointsOnly) = (is_entry_point, &mode)
-            {
-                return;
-            }
-            match find_unsafe_in_file(&path_buf, include_tests) {
-                Err(error) => {
-                    handle_unsafe_in_file_error(
-                        allow_partial_results,
-                        error,
-                        &path_buf,
-                    );
-                }
-                Ok(rs_file_metrics) => {
-                    let package_id_to_metrics =
-                        &mut package_id_to_metrics.lock().unwrap();
-                    update_package_id_to_metrics_with_rs_file_metrics(
-                        is_entry_point,
-                        package_id,
-                        package_id_to_metrics,
-                        path_buf,
-                        rs_file_metrics,
+    let scanned_rs_files = package_code_files
+        .into_par_iter()
+        .map(|(package_id, rs_code_file)| {
+            let cache_entry = cache_entries.get(&package_id);
+            let package_name = package_names[&package_id];
+            let remaining_file_count = &remaining_file_counts[&package_id];
+            let scanned_rs_file = scan_rs_file(
+                print_config.include_tests,
+                print_config.lossy,
+                &detectors,
+                &mode,
+                package_id,
+                rs_code_file,
+                cache_entry,
+            );
+            if let Some(on_processed) = &on_processed {
+                if remaining_file_count.fetch_sub(1, Ordering::Relaxed) == 1 {
+                    on_processed(
+                        processed_count.fetch_add(1, Ordering::Relaxed) + 1,
+                        remaining_file_counts.len(),
+                        package_name,
                     );
                 }
             }
-
-            if let Some(on_processed) = &on_processed {
-                on_processed(
-                    processed_count.fetch_add(1, Ordering::Relaxed),
-                    package_code_file_count,
-                );
+            scanned_rs_file
+        })
+        .collect::<Vec<_>>();
+
+    let lossily_scanned_package_ids =
+        lossily_scanned_package_ids(&scanned_rs_files);
+    let geiger_context = merge_scanned_rs_files(scanned_rs_files);
+    // The entry points alone are not enough to be read back.
+    if let (Some(scan_cache), ScanMode::Full) = (scan_cache, &mode) {
+        for (package_id, cache_entry) in &cache_entries {
+            if cache_entry.is_cached()
+                || geiger_context
+                    .partially_scanned_package_ids
+                    .contains(package_id)
+                || lossily_scanned_package_ids.contains(package_id)
+            {
+                continue;
             }
-        },
-    );
+            if let Some(package_metrics) =
+                geiger_context.package_id_to_metrics.get(package_id)
+            {
+                scan_cache.write(cache_entry, package_metrics);
+            }
+        }
+    }
+    geiger_context
+}
 
-    let cargo_core_package_metrics = package_id_to_metrics
-        .lock()
-        .unwrap()
+/// The packages with a file decoded lossily, which are not written to the
+/// cache, so that a later scan without `--lossy` still fails on the file
+fn lossily_scanned_package_ids(
+    scanned_rs_files: &[ScannedRsFile],
+) -> HashSet<PackageId> {
+    scanned_rs_files
         .iter()
-        .map(|(cargo_metadata_package_id, package_metrics)| {
-            (cargo_metadata_package_id.clone(), package_metrics.clone())
+        .filter_map(|scanned_rs_file| match scanned_rs_file {
+            ScannedRsFile::Scanned {
+                is_lossy: true,
+                package_id,
+                ..
+            } => Some(package_id.clone()),
+            _ => None,
         })
-        .collect::<HashMap<PackageId, PackageMetrics>>();
+        .collect()
+}
 
-    GeigerContext {
-        package_id_to_metrics: cargo_core_package_metrics,
-        ignored_paths: Arc::try_unwrap(ignored).unwrap().into_inner().unwrap(),
+/// The number of files of each package, counted down as they are scanned
+fn remaining_file_counts(
+    package_code_files: &[(PackageId, RsFile)],
+) -> HashMap<PackageId, AtomicUsize> {
+    let mut remaining_file_counts = HashMap::<PackageId, AtomicUsize>::new();
+    for (package_id, _) in package_code_files {
+        *remaining_file_counts
+            .entry(package_id.clone())
+            .or_default()
+            .get_mut() += 1;
     }
+    remaining_file_counts
+}
+
+/// The cache entries of the packages from crates.io, not replaced by a local
+/// directory with `--source-map`
+fn find_cache_entries(
+    include_tests: IncludeTests,
+    detectors: &[Box<dyn Detector>],
+    packages: &[cargo_metadata::Package],
+    source_map: &SourceMap,
+    scan_cache: &ScanCache,
+) -> HashMap<PackageId, CacheEntry> {
+    packages
+        .par_iter()
+        .filter(|package| source_map.local_package(&package.name).is_none())
+        .filter_map(|package| {
+            scan_cache
+                .entry(package, include_tests, detectors)
+                .map(|cache_entry| (package.id.clone(), cache_entry))
+        })
+        .collect()
+}
+
+fn scan_rs_file(
+    include_tests: IncludeTests,
+    lossy: bool,
+    detectors: &[Box<dyn Detector>],
+    mode: &ScanMode,
+    package_id: PackageId,
+    rs_code_file: RsFile,
+    cache_entry: Option<&CacheEntry>,
+) -> ScannedRsFile {
+    if let RsFile::CustomBuildRoot(path_buf) | RsFile::Ignored(path_buf) =
+        rs_code_file
+    {
+        return ScannedRsFile::Ignored(path_buf);
+    }
+    let (is_entry_point, path_buf) =
+        into_is_entry_point_and_path_buf(rs_code_file);
+    if let (false, ScanMode::EntryPointsOnly) = (is_entry_point, mode) {
+        return ScannedRsFile::Skipped;
+    }
+    // Packages with a lossily decoded file are never cached.
+    if let Some(rs_file_metrics) = cache_entry
+        .and_then(|cache_entry| cache_entry.rs_file_metrics(&path_buf))
+    {
+        return ScannedRsFile::Scanned {
+            is_entry_point,
+            is_lossy: false,
+            package_id,
+            path_buf,
+            rs_file_metrics: rs_file_metrics.clone(),
+        };
+    }
+    match find_unsafe_in_file_with_detectors(
+        &path_buf,
+        include_tests,
+        lossy,
+        detectors,
+    ) {
+        Err(error) => ScannedRsFile::Failed(package_id, path_buf, error),
+        Ok((rs_file_metrics, is_lossy)) => ScannedRsFile::Scanned {
+            is_entry_point,
+            is_lossy,
+            package_id,
+            path_buf,
+            rs_file_metrics,
+        },
+    }
+}
+
+fn merge_scanned_rs_files(
+    scanned_rs_files: Vec<ScannedRsFile>,
+) -> GeigerContext {
+    let mut geiger_context = GeigerContext::default();
+    for scanned_rs_file in scanned_rs_files {
+        match scanned_rs_file {
+            ScannedRsFile::Ignored(path_buf) => {
+                geiger_context.ignored_paths.insert(path_buf);
+            }
+            ScannedRsFile::Skipped => {}
+            ScannedRsFile::Failed(package_id, path_buf, error) => {
+                geiger_context.scan_warnings.push(ScanWarning {
+                    path: path_buf.clone(),
+                    message: scan_warning_message(&error),
+                });
+                handle_unsafe_in_file_error(error, &path_buf);
+                geiger_context
+                    .partially_scanned_package_ids
+                    .insert(package_id);
+            }
+            ScannedRsFile::Scanned {
+                is_entry_point,
+                is_lossy,
+                package_id,
+                path_buf,
+                rs_file_metrics,
+            } => {
+                if is_lossy {
+                    geiger_context.scan_warnings.push(ScanWarning {
+                        path: path_buf.clone(),
+                        message: String::from(LOSSY_SCAN_WARNING),
+                    });
+                }
+                update_package_id_to_metrics_with_rs_file_metrics(
+                    is_entry_point,
+                    package_id,
+                    &mut geiger_context.package_id_to_metrics,
+                    path_buf,
+                    rs_file_metrics,
+                )
+            }
+        }
+    }
+    geiger_context
 }
 
 fn find_rs_files_in_dir(dir: &Path) -> impl Iterator<Item = PathBuf> {
@@ -207,28 +474,70 @@ fn find_rs_files_in_package(package: &cargo_metadata::Package) -> Vec<RsFile> {
     rs_files
 }
 
+/// The files of a crate given with `--source-map` are found in its local
+/// directory, but still belong to the crate in the dependency graph. The
+/// directories are walked in parallel, and the files listed in the order of
+/// the packages.
+///
+/// Every file of a crate given with `--ignore`, and the files matching an
+/// `--ignore-path` pattern, are listed as ignored, so that they are not
+/// reported as used by the build without being scanned.
 fn find_rs_files_in_packages(
+    ignore_list: &IgnoreList,
     packages: &[cargo_metadata::Package],
-) -> impl Iterator<Item = (PackageId, RsFile)> + '_ {
-    packages.iter().flat_map(|package| {
-        find_rs_files_in_package(package)
-            .into_iter()
-            .map(move |p| (package.id.clone(), p))
-    })
+    source_map: &SourceMap,
+) -> Vec<(PackageId, RsFile)> {
+    packages
+        .par_iter()
+        .flat_map_iter(|package| {
+            let scanned_package =
+                source_map.local_package(&package.name).unwrap_or(package);
+            let is_ignored_crate = ignore_list.is_ignored_crate(&package.name);
+            let package_root = scanned_package
+                .manifest_path
+                .parent()
+                .and_then(|parent| parent.canonicalize().ok());
+            find_rs_files_in_package(scanned_package).into_iter().map(
+                move |rs_file| {
+                    let (_, path_buf) =
+                        into_is_entry_point_and_path_buf(rs_file.clone());
+                    let is_ignored = is_ignored_crate
+                        || package_root.as_ref().is_some_and(|package_root| {
+                            ignore_list.is_ignored_path(package_root, &path_buf)
+                        });
+                    match is_ignored {
+                        true => (package.id.clone(), RsFile::Ignored(path_buf)),
+                        false => (package.id.clone(), rs_file),
+                    }
+                },
+            )
+        })
+        .collect()
 }
 
-fn handle_unsafe_in_file_error(
-    allow_partial_results: bool,
-    error: ScanFileError,
-    path_buf: &Path,
-) {
-    if allow_partial_results {
-        eprintln!("Failed to parse file: {}, {:?} ", path_buf.display(), error);
-    } else {
-        panic!("Failed to parse file: {}, {:?} ", path_buf.display(), error);
+/// The crate of the file is only partially scanned, which fails the scan
+/// after all files are scanned unless partial results are allowed
+/// The reason a file failed to be scanned, as recorded in the scan warnings
+fn scan_warning_message(error: &ScanFileError) -> String {
+    match error {
+        ScanFileError::Io(e, _) => format!("failed to read the file: {}", e),
+        ScanFileError::Utf8(..) => String::from("the file is not valid UTF-8"),
+        ScanFileError::Syn(e, _) => {
+            let start = e.span().start();
+            format!(
+                "failed to parse the file at line {}, column {}: {}",
+                start.line,
+                start.column + 1,
+                e
+            )
+        }
     }
 }
 
+fn handle_unsafe_in_file_error(error: ScanFileError, path_buf: &Path) {
+    eprintln!("Failed to parse file: {}, {:?} ", path_buf.display(), error);
+}
+
 fn update_package_id_to_metrics_with_rs_file_metrics(
     is_entry_point: bool,
     package_id: PackageId,
@@ -252,6 +561,7 @@ mod find_tests {
     use super::*;
 
     use cargo_metadata::{CargoOpt, MetadataCommand};
+    use geiger::find::find_unsafe_in_file;
     use rstest::*;
     use std::fs::File;
     use std::io;
@@ -305,6 +615,7 @@ mod find_tests {
                 RsFile::CustomBuildRoot(path_buf) => path_buf,
                 RsFile::LibRoot(path_buf) => path_buf,
                 RsFile::Other(path_buf) => path_buf,
+                RsFile::Ignored(path_buf) => path_buf,
             })
             .collect::<Vec<&PathBuf>>();
 
@@ -314,11 +625,38 @@ mod find_tests {
     }
 
     #[rstest]
-    fn handle_unsafe_in_file_error_doesnt_panic_when_allow_partial_results_is_true(
-    ) {
+    fn find_rs_files_in_packages_ignore_path_test() {
+        let package = get_current_workspace_package();
+        let ignore_list = IgnoreList {
+            path_patterns: vec![glob::Pattern::new("src/format/**").unwrap()],
+            ..Default::default()
+        };
+
+        let rs_files = find_rs_files_in_packages(
+            &ignore_list,
+            &[package],
+            &SourceMap::default(),
+        );
+
+        assert!(!rs_files.is_empty());
+        for (_, rs_file) in rs_files {
+            let is_ignored = matches!(rs_file, RsFile::Ignored(_));
+            let (_, path_buf) = into_is_entry_point_and_path_buf(rs_file);
+            let components = path_buf
+                .components()
+                .map(|component| component.as_os_str().to_owned())
+                .collect::<Vec<_>>();
+            let is_in_format = components
+                .windows(2)
+                .any(|window| window[0] == "src" && window[1] == "format");
+            assert_eq!(is_ignored, is_in_format);
+        }
+    }
+
+    #[rstest]
+    fn handle_unsafe_in_file_error_doesnt_panic() {
         let path_buf = PathBuf::from("test_path");
         handle_unsafe_in_file_error(
-            true,
             ScanFileError::Io(
                 io::Error::new(ErrorKind::Other, "test"),
                 path_buf.clone(),
@@ -327,18 +665,170 @@ mod find_tests {
         );
     }
 
+    #[rstest(
+        input_allow_partial_results,
+        input_partially_scanned_count,
+        expected_is_ok,
+        case(true, 0, true),
+        case(true, 2, true),
+        case(false, 0, true),
+        case(false, 2, false)
+    )]
+    fn check_partial_results_test(
+        input_allow_partial_results: bool,
+        input_partially_scanned_count: usize,
+        expected_is_ok: bool,
+    ) {
+        let partially_scanned_package_ids = (0..input_partially_scanned_count)
+            .map(|index| PackageId {
+                repr: format!("package_{}", index),
+            })
+            .collect::<HashSet<PackageId>>();
+
+        assert_eq!(
+            check_partial_results(
+                input_allow_partial_results,
+                &partially_scanned_package_ids
+            )
+            .is_ok(),
+            expected_is_ok
+        );
+    }
+
     #[rstest]
-    #[should_panic]
-    fn handle_unsafe_in_file_error_panics_when_allow_partial_results_is_false()
-    {
-        let path_buf = PathBuf::from("test_path");
-        handle_unsafe_in_file_error(
-            false,
-            ScanFileError::Io(
-                io::Error::new(ErrorKind::Other, "test"),
-                path_buf.clone(),
+    fn find_unsafe_in_packages_test_on_processed() {
+        let package = get_current_workspace_package();
+        let processed = std::sync::Mutex::new(vec![]);
+
+        find_unsafe_in_packages(
+            &PrintConfig::default(),
+            ScanMode::EntryPointsOnly,
+            &[package],
+            &SourceMap::default(),
+            None,
+            Some(|scanned_count, count, package_name: &str| {
+                processed.lock().unwrap().push((
+                    scanned_count,
+                    count,
+                    package_name.to_string(),
+                ))
+            }),
+        );
+
+        assert_eq!(
+            processed.into_inner().unwrap(),
+            vec![(1, 1, String::from("cargo-geiger"))]
+        );
+    }
+
+    #[rstest]
+    fn remaining_file_counts_test() {
+        let package_id = |repr: &str| PackageId {
+            repr: String::from(repr),
+        };
+        let package_code_files = vec![
+            (package_id("foo"), RsFile::LibRoot(PathBuf::from("lib.rs"))),
+            (package_id("foo"), RsFile::Other(PathBuf::from("a.rs"))),
+            (package_id("bar"), RsFile::BinRoot(PathBuf::from("main.rs"))),
+        ];
+
+        let remaining_file_counts = remaining_file_counts(&package_code_files);
+
+        assert_eq!(remaining_file_counts.len(), 2);
+        assert_eq!(
+            remaining_file_counts[&package_id("foo")].load(Ordering::Relaxed),
+            2
+        );
+        assert_eq!(
+            remaining_file_counts[&package_id("bar")].load(Ordering::Relaxed),
+            1
+        );
+    }
+
+    #[rstest]
+    fn lossily_scanned_package_ids_test() {
+        let package_id = |repr: &str| PackageId {
+            repr: String::from(repr),
+        };
+        let create_scanned_rs_file =
+            |repr: &str, is_lossy: bool| ScannedRsFile::Scanned {
+                is_entry_point: false,
+                is_lossy,
+                package_id: package_id(repr),
+                path_buf: PathBuf::from("lib.rs"),
+                rs_file_metrics: RsFileMetrics::default(),
+            };
+
+        assert_eq!(
+            lossily_scanned_package_ids(&[
+                create_scanned_rs_file("foo", false),
+                create_scanned_rs_file("bar", true),
+                create_scanned_rs_file("bar", false),
+                ScannedRsFile::Skipped,
+            ]),
+            vec![package_id("bar")].into_iter().collect()
+        );
+    }
+
+    #[rstest]
+    fn merge_scanned_rs_files_test() {
+        let package_id = PackageId {
+            repr: String::from("package"),
+        };
+        let create_scanned_rs_file = |path: &str, unsafe_exprs: u64| {
+            let mut rs_file_metrics = RsFileMetrics::default();
+            rs_file_metrics.counters.exprs.unsafe_ = unsafe_exprs;
+            ScannedRsFile::Scanned {
+                is_entry_point: false,
+                is_lossy: false,
+                package_id: package_id.clone(),
+                path_buf: PathBuf::from(path),
+                rs_file_metrics,
+            }
+        };
+
+        let geiger_context = merge_scanned_rs_files(vec![
+            ScannedRsFile::Ignored(PathBuf::from("build.rs")),
+            create_scanned_rs_file("lib.rs", 1),
+            ScannedRsFile::Skipped,
+            ScannedRsFile::Failed(
+                package_id.clone(),
+                PathBuf::from("broken.rs"),
+                ScanFileError::Io(
+                    io::Error::new(ErrorKind::Other, "test"),
+                    PathBuf::from("broken.rs"),
+                ),
             ),
-            &path_buf,
+            // A file found again is merged in the order it was found.
+            create_scanned_rs_file("lib.rs", 2),
+        ]);
+
+        assert_eq!(
+            geiger_context.ignored_paths,
+            vec![PathBuf::from("build.rs")].into_iter().collect()
+        );
+        assert_eq!(
+            geiger_context.partially_scanned_package_ids,
+            vec![package_id.clone()].into_iter().collect()
+        );
+        assert_eq!(
+            geiger_context.scan_warnings,
+            vec![ScanWarning {
+                path: PathBuf::from("broken.rs"),
+                message: String::from("failed to read the file: test"),
+            }]
+        );
+        let rs_path_to_metrics = &geiger_context.package_id_to_metrics
+            [&package_id]
+            .rs_path_to_metrics;
+        assert_eq!(rs_path_to_metrics.len(), 1);
+        assert_eq!(
+            rs_path_to_metrics[Path::new("lib.rs")]
+                .metrics
+                .counters
+                .exprs
+                .unsafe_,
+            2
         );
     }
 
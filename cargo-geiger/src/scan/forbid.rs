@@ -1,15 +1,17 @@
 mod table;
 
-use crate::format::print_config::{OutputFormat, PrintConfig};
+use crate::format::print_config::OutputFormat;
 use crate::graph::Graph;
 use crate::mapping::CargoMetadataParameters;
 
+use super::default::canonical_workspace_root;
 use super::find::find_unsafe;
+use super::relative_paths::make_quick_report_paths_relative;
 use super::{package_metrics, ScanMode, ScanParameters, ScanResult};
 
 use table::scan_forbid_to_table;
 
-use cargo::{CliError, Config};
+use cargo::CliError;
 use cargo_geiger_serde::{QuickReportEntry, QuickSafetyReport};
 use cargo_metadata::PackageId;
 
@@ -22,45 +24,73 @@ pub fn scan_forbid_unsafe(
     match scan_parameters.args.output_format {
         OutputFormat::Json => scan_forbid_to_report(
             cargo_metadata_parameters,
-            scan_parameters.config,
             graph,
-            scan_parameters.args.output_format,
-            scan_parameters.print_config,
             root_package_id,
+            scan_parameters,
         ),
+        OutputFormat::Csv
+        | OutputFormat::CycloneDx
+        | OutputFormat::Dot
+        | OutputFormat::GitHubActions
+        | OutputFormat::Html
+        | OutputFormat::Mermaid
+        | OutputFormat::Plain
+        | OutputFormat::PrComment
+        | OutputFormat::Sarif
+        | OutputFormat::ShieldsIo => {
+            eprintln!(
+                "The {:?} output format counts unsafe code, which `--forbid-only` doesn't scan for",
+                scan_parameters.args.output_format
+            );
+            Err(CliError::code(1))
+        }
+        OutputFormat::JsonLines | OutputFormat::Toml => {
+            eprintln!(
+                "The {:?} output format is not supported with `--forbid-only`, use Json instead",
+                scan_parameters.args.output_format
+            );
+            Err(CliError::code(1))
+        }
         _ => scan_forbid_to_table(
             cargo_metadata_parameters,
-            scan_parameters.config,
             graph,
-            scan_parameters.print_config,
             root_package_id,
+            scan_parameters,
         ),
     }
 }
 
 fn scan_forbid_to_report(
     cargo_metadata_parameters: &CargoMetadataParameters,
-    config: &Config,
     graph: &Graph,
-    output_format: OutputFormat,
-    print_config: &PrintConfig,
     root_package_id: PackageId,
+    scan_parameters: &ScanParameters,
 ) -> Result<ScanResult, CliError> {
+    let print_config = scan_parameters.print_config;
     let geiger_context = find_unsafe(
-        cargo_metadata_parameters,
-        config,
+        &cargo_metadata_parameters.metadata.packages,
+        scan_parameters.config,
         ScanMode::EntryPointsOnly,
         print_config,
+        scan_parameters.source_map,
     )?;
     let mut report = QuickSafetyReport::default();
     for (package, package_metrics) in package_metrics(
         cargo_metadata_parameters,
         &geiger_context,
         graph,
+        print_config,
         root_package_id,
     ) {
         let pack_metrics = match package_metrics {
             Some(m) => m,
+            None if print_config
+                .ignore_list
+                .is_ignored_crate(&package.id.name) =>
+            {
+                report.ignored_packages.insert(package.id);
+                continue;
+            }
             None => {
                 report.packages_without_metrics.insert(package.id);
                 continue;
@@ -71,19 +101,59 @@ fn scan_forbid_to_report(
                 rs_file_metrics_wrapper.metrics.forbids_unsafe
             },
         );
+        let denies_unsafe = !forbids_unsafe
+            && pack_metrics.rs_path_to_metrics.iter().all(
+                |(_, rs_file_metrics_wrapper)| {
+                    rs_file_metrics_wrapper.metrics.forbids_unsafe
+                        || rs_file_metrics_wrapper.metrics.denies_unsafe
+                },
+            );
+        let conditionally_forbids_unsafe = !forbids_unsafe
+            && !denies_unsafe
+            && pack_metrics.rs_path_to_metrics.iter().all(
+                |(_, rs_file_metrics_wrapper)| {
+                    rs_file_metrics_wrapper.metrics.forbids_unsafe
+                        || rs_file_metrics_wrapper.metrics.denies_unsafe
+                        || rs_file_metrics_wrapper
+                            .metrics
+                            .conditionally_forbids_unsafe
+                },
+            );
         let entry = QuickReportEntry {
             package,
             forbids_unsafe,
+            denies_unsafe,
+            conditionally_forbids_unsafe,
         };
         report.packages.insert(entry.package.id.clone(), entry);
     }
-    let json_string = match output_format {
+    if scan_parameters.args.relative_paths {
+        make_quick_report_paths_relative(
+            &mut report,
+            &canonical_workspace_root(cargo_metadata_parameters.metadata),
+        );
+    }
+    let warning_count = if scan_parameters.args.deny_warnings {
+        report.packages_without_metrics.len() as u64
+    } else {
+        0
+    };
+    let json_string = match scan_parameters.args.output_format {
         OutputFormat::Json => serde_json::to_string(&report).unwrap(),
         _ => panic!("Only implemented for OutputFormat::Json"),
     };
 
     Ok(ScanResult {
+        category_policy_error_count: 0,
+        checksum_mismatch_count: 0,
+        detection_outcome: None,
+        over_fail_threshold_ratio: None,
+        over_max_score_count: 0,
         scan_output_lines: vec![json_string],
-        warning_count: 0,
+        unaudited_crate_count: 0,
+        undocumented_unsafe_count: 0,
+        unsafe_crate_count: 0,
+        unsafe_limit_violation_count: 0,
+        warning_count,
     })
 }
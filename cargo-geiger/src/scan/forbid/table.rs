@@ -1,29 +1,41 @@
 use crate::format::emoji_symbols::EmojiSymbols;
 use crate::format::pattern::Pattern;
 use crate::format::print_config::PrintConfig;
-use crate::format::{get_kind_group_name, SymbolKind};
+use crate::format::{get_kind_group_name, SymbolKind, Symbols};
 use crate::graph::Graph;
+use crate::license::package_matches_license_filter;
 use crate::mapping::CargoMetadataParameters;
 use crate::scan::GeigerContext;
+use crate::source_kind::package_matches_source_filter;
 use crate::tree::traversal::walk_dependency_tree;
-use crate::tree::TextTreeLine;
+use crate::tree::{is_printed_with_workspace_only, TextTreeLine};
 
 use super::super::find::find_unsafe;
-use super::super::{ScanMode, ScanResult};
+use super::super::{ScanMode, ScanParameters, ScanResult};
 
-use cargo::{CliError, Config};
+use cargo::CliError;
 use cargo_metadata::PackageId;
 use colored::Colorize;
 
 pub fn scan_forbid_to_table(
     cargo_metadata_parameters: &CargoMetadataParameters,
-    config: &Config,
     graph: &Graph,
-    print_config: &PrintConfig,
     root_package_id: PackageId,
+    scan_parameters: &ScanParameters,
 ) -> Result<ScanResult, CliError> {
+    let print_config = scan_parameters.print_config;
     let mut scan_output_lines = Vec::<String>::new();
-    let emoji_symbols = EmojiSymbols::new(print_config.output_format);
+    // The symbols are the only column of this report, so they are kept.
+    let symbols = match print_config.symbols {
+        Symbols::None => Symbols::Ascii,
+        symbols => symbols,
+    };
+    let emoji_symbols = EmojiSymbols::new(
+        print_config.color,
+        print_config.color_scheme,
+        print_config.output_format,
+        symbols,
+    );
 
     let mut output_key_lines = construct_key_lines(&emoji_symbols);
     scan_output_lines.append(&mut output_key_lines);
@@ -32,7 +44,8 @@ pub fn scan_forbid_to_table(
         cargo_metadata_parameters,
         graph,
         print_config,
-        root_package_id,
+        root_package_id.clone(),
+        None,
     );
 
     for tree_line in tree_lines {
@@ -49,12 +62,31 @@ pub fn scan_forbid_to_table(
             TextTreeLine::Package {
                 id: package_id,
                 tree_vines,
+                ..
             } => {
-                let geiger_ctx = find_unsafe(
+                if !package_matches_license_filter(
+                    cargo_metadata_parameters,
+                    &print_config.license_filter,
+                    &package_id,
+                ) || !package_matches_source_filter(
                     cargo_metadata_parameters,
-                    config,
+                    &print_config.source_filter,
+                    &package_id,
+                ) || !is_printed_with_workspace_only(
+                    print_config.workspace_only,
+                    &cargo_metadata_parameters.metadata.workspace_members,
+                    &root_package_id,
+                    &package_id,
+                ) {
+                    continue;
+                }
+
+                let geiger_ctx = find_unsafe(
+                    &cargo_metadata_parameters.metadata.packages,
+                    scan_parameters.config,
                     ScanMode::EntryPointsOnly,
                     print_config,
+                    scan_parameters.source_map,
                 )?;
 
                 handle_package_text_tree_line(
@@ -71,7 +103,16 @@ pub fn scan_forbid_to_table(
     }
 
     Ok(ScanResult {
+        category_policy_error_count: 0,
+        checksum_mismatch_count: 0,
+        detection_outcome: None,
+        over_fail_threshold_ratio: None,
+        over_max_score_count: 0,
         scan_output_lines,
+        unaudited_crate_count: 0,
+        undocumented_unsafe_count: 0,
+        unsafe_crate_count: 0,
+        unsafe_limit_violation_count: 0,
         warning_count: 0,
     })
 }
@@ -80,10 +121,16 @@ fn construct_key_lines(emoji_symbols: &EmojiSymbols) -> Vec<String> {
     let mut output_key_lines = vec![String::new(), String::from("Symbols: ")];
 
     let forbids = "All entry point .rs files declare #![forbid(unsafe_code)].";
+    let denies = "All entry point .rs files declare #![deny(unsafe_code)].";
+    let conditionally_forbids = "All entry point .rs files declare \
+        #![forbid(unsafe_code)], some only within \
+        #![cfg_attr(<condition>, ...)].";
     let unknown = "This crate may use unsafe code.";
 
     let symbol_kinds_to_string_values = vec![
         (SymbolKind::Lock, forbids),
+        (SymbolKind::OpenLock, denies),
+        (SymbolKind::KeyLock, conditionally_forbids),
         (SymbolKind::QuestionMark, unknown),
     ];
 
@@ -104,7 +151,12 @@ fn format_package_name(
     package_id: &PackageId,
     pattern: &Pattern,
 ) -> String {
-    format!("{}", pattern.display(cargo_metadata_parameters, package_id))
+    // Unsafe code isn't counted in this mode, so the unsafe counts are left
+    // out.
+    format!(
+        "{}",
+        pattern.display(cargo_metadata_parameters, package_id, None)
+    )
 }
 
 fn handle_package_text_tree_line(
@@ -117,6 +169,8 @@ fn handle_package_text_tree_line(
     tree_vines: String,
 ) {
     let sym_lock = emoji_symbols.emoji(SymbolKind::Lock);
+    let sym_open_lock = emoji_symbols.emoji(SymbolKind::OpenLock);
+    let sym_key_lock = emoji_symbols.emoji(SymbolKind::KeyLock);
     let sym_qmark = emoji_symbols.emoji(SymbolKind::QuestionMark);
 
     let name = format_package_name(
@@ -125,16 +179,35 @@ fn handle_package_text_tree_line(
         &print_config.format,
     );
     let package_metrics = geiger_ctx.package_id_to_metrics.get(&package_id);
-    let package_forbids_unsafe = match package_metrics {
-        None => false, // no metrics available, .rs parsing failed?
-        Some(package_metric) => package_metric.rs_path_to_metrics.iter().all(
-            |(_k, rs_file_metrics_wrapper)| {
-                rs_file_metrics_wrapper.metrics.forbids_unsafe
-            },
-        ),
+    let (
+        package_forbids_unsafe,
+        package_denies_unsafe,
+        package_conditionally_forbids_unsafe,
+    ) = match package_metrics {
+        None => (false, false, false), // no metrics available, .rs parsing failed?
+        Some(package_metric) => {
+            let mut metrics = package_metric.rs_path_to_metrics.values().map(
+                |rs_file_metrics_wrapper| &rs_file_metrics_wrapper.metrics,
+            );
+            (
+                metrics.clone().all(|metrics| metrics.forbids_unsafe),
+                metrics.clone().all(|metrics| {
+                    metrics.forbids_unsafe || metrics.denies_unsafe
+                }),
+                metrics.all(|metrics| {
+                    metrics.forbids_unsafe
+                        || metrics.denies_unsafe
+                        || metrics.conditionally_forbids_unsafe
+                }),
+            )
+        }
     };
     let (symbol, name) = if package_forbids_unsafe {
         (&sym_lock, name.green())
+    } else if package_denies_unsafe {
+        (&sym_open_lock, name.yellow())
+    } else if package_conditionally_forbids_unsafe {
+        (&sym_key_lock, name.cyan())
     } else {
         (&sym_qmark, name.red())
     };
@@ -149,9 +222,14 @@ mod forbid_tests {
 
     #[rstest]
     fn construct_scan_mode_forbid_only_output_key_lines_test() {
-        let emoji_symbols = EmojiSymbols::new(OutputFormat::Utf8);
+        let emoji_symbols = EmojiSymbols::new(
+            Default::default(),
+            Default::default(),
+            OutputFormat::Utf8,
+            Symbols::Emoji,
+        );
         let output_key_lines = construct_key_lines(&emoji_symbols);
 
-        assert_eq!(output_key_lines.len(), 5);
+        assert_eq!(output_key_lines.len(), 7);
     }
 }
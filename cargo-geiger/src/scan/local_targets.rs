@@ -0,0 +1,280 @@
+use crate::args::LocalTargetsArgs;
+use crate::format::summary::format_category_counts;
+
+use super::GeigerContext;
+
+use cargo::ops::{CompileFilter, FilterRule, LibRule};
+use cargo_geiger_serde::CounterBlock;
+use cargo_metadata::{Metadata, Package};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The targets of workspace members which are only scanned when asked to,
+/// since they are not shipped
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LocalTargetKind {
+    Example,
+    Bench,
+}
+
+impl LocalTargetKind {
+    /// Both the directory of the crate in which cargo finds the targets, and
+    /// the label their unsafe usage is reported under
+    fn directory_name(self) -> &'static str {
+        match self {
+            LocalTargetKind::Example => "examples",
+            LocalTargetKind::Bench => "benches",
+        }
+    }
+
+    /// The target kind in the cargo metadata
+    fn metadata_kind(self) -> &'static str {
+        match self {
+            LocalTargetKind::Example => "example",
+            LocalTargetKind::Bench => "bench",
+        }
+    }
+
+    fn selected(local_targets_args: &LocalTargetsArgs) -> Vec<LocalTargetKind> {
+        let mut local_target_kinds = vec![];
+        if local_targets_args.scan_examples {
+            local_target_kinds.push(LocalTargetKind::Example);
+        }
+        if local_targets_args.scan_benches {
+            local_target_kinds.push(LocalTargetKind::Bench);
+        }
+        local_target_kinds
+    }
+}
+
+/// A workspace member, with its root canonicalized like the paths of the
+/// scanned files
+struct WorkspaceMember<'a> {
+    package: &'a Package,
+    root: PathBuf,
+}
+
+impl<'a> WorkspaceMember<'a> {
+    /// The kind of the selected target the file belongs to: the entry point of
+    /// the target, or any file in the directory of its kind
+    fn local_target_kind(
+        &self,
+        local_target_kinds: &[LocalTargetKind],
+        path: &Path,
+    ) -> Option<LocalTargetKind> {
+        local_target_kinds
+            .iter()
+            .copied()
+            .find(|local_target_kind| {
+                path.starts_with(
+                    self.root.join(local_target_kind.directory_name()),
+                ) || self.package.targets.iter().any(|target| {
+                    target
+                        .kind
+                        .iter()
+                        .any(|kind| kind == local_target_kind.metadata_kind())
+                        && target
+                            .src_path
+                            .canonicalize()
+                            .is_ok_and(|src_path| src_path == path)
+                })
+            })
+    }
+}
+
+fn workspace_members(metadata: &Metadata) -> Vec<WorkspaceMember<'_>> {
+    metadata
+        .packages
+        .iter()
+        .filter(|package| metadata.workspace_members.contains(&package.id))
+        .filter_map(|package| {
+            let root = package.manifest_path.parent()?.canonicalize().ok()?;
+            Some(WorkspaceMember { package, root })
+        })
+        .collect()
+}
+
+/// With `--scan-examples` or `--scan-benches`, the build also checks the
+/// selected targets of the workspace members, alongside the default targets.
+/// Dependencies are built without their examples and benches regardless.
+pub fn local_targets_compile_filter(
+    local_targets_args: &LocalTargetsArgs,
+) -> Option<CompileFilter> {
+    if !local_targets_args.scan_examples && !local_targets_args.scan_benches {
+        return None;
+    }
+    let rule = |selected| {
+        if selected {
+            FilterRule::All
+        } else {
+            FilterRule::none()
+        }
+    };
+    Some(CompileFilter::new(
+        LibRule::Default,
+        FilterRule::All,
+        FilterRule::none(),
+        rule(local_targets_args.scan_examples),
+        rule(local_targets_args.scan_benches),
+    ))
+}
+
+/// Split the files used by the build into the files of the selected targets
+/// of the workspace members, and all other files
+pub fn split_local_target_files(
+    local_targets_args: &LocalTargetsArgs,
+    metadata: &Metadata,
+    rs_files_used: HashSet<PathBuf>,
+) -> (HashSet<PathBuf>, HashSet<PathBuf>) {
+    let local_target_kinds = LocalTargetKind::selected(local_targets_args);
+    if local_target_kinds.is_empty() {
+        return (HashSet::new(), rs_files_used);
+    }
+    let workspace_members = workspace_members(metadata);
+    rs_files_used.into_iter().partition(|rs_file_used| {
+        workspace_members.iter().any(|workspace_member| {
+            workspace_member
+                .local_target_kind(&local_target_kinds, rs_file_used)
+                .is_some()
+        })
+    })
+}
+
+/// A line for each workspace member and selected target kind with source
+/// files, giving the unsafe usage of the targets, which is not counted in
+/// the rest of the report
+pub fn construct_local_target_lines(
+    local_targets_args: &LocalTargetsArgs,
+    geiger_context: &GeigerContext,
+    local_target_files_used: &HashSet<PathBuf>,
+    metadata: &Metadata,
+) -> Vec<String> {
+    let local_target_kinds = LocalTargetKind::selected(local_targets_args);
+    if local_target_kinds.is_empty() {
+        return vec![];
+    }
+
+    let mut workspace_members = workspace_members(metadata);
+    workspace_members.sort_by(|a, b| a.package.name.cmp(&b.package.name));
+
+    let mut local_target_lines = vec![];
+    for workspace_member in &workspace_members {
+        let package_metrics = match geiger_context
+            .package_id_to_metrics
+            .get(&workspace_member.package.id)
+        {
+            Some(package_metrics) => package_metrics,
+            None => continue,
+        };
+        for local_target_kind in &local_target_kinds {
+            let mut used = CounterBlock::default();
+            let mut unused = CounterBlock::default();
+            let mut has_files = false;
+            for (path, rs_file_metrics_wrapper) in
+                &package_metrics.rs_path_to_metrics
+            {
+                if workspace_member
+                    .local_target_kind(&[*local_target_kind], path)
+                    .is_none()
+                {
+                    continue;
+                }
+                has_files = true;
+                let counters = rs_file_metrics_wrapper.metrics.counters.clone();
+                if local_target_files_used.contains(path) {
+                    used += counters;
+                } else {
+                    unused += counters;
+                }
+            }
+            if has_files {
+                local_target_lines.push(format!(
+                    "    {} {} {}: {}",
+                    workspace_member.package.name,
+                    workspace_member.package.version,
+                    local_target_kind.directory_name(),
+                    format_category_counts(&used, &unused)
+                ));
+            }
+        }
+    }
+
+    if local_target_lines.is_empty() {
+        return vec![];
+    }
+    local_target_lines.insert(
+        0,
+        String::from(
+            "Unsafe code used by the build/found in examples and benches of \
+            workspace members, only counted as found above:",
+        ),
+    );
+    local_target_lines.push(String::new());
+    local_target_lines
+}
+
+#[cfg(test)]
+mod local_targets_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest(
+        input_path,
+        input_local_targets_args,
+        expected_local_target_kind,
+        case(
+            "examples/demo.rs",
+            LocalTargetsArgs { scan_benches: false, scan_examples: true },
+            Some(LocalTargetKind::Example)
+        ),
+        case(
+            "examples/demo/helper.rs",
+            LocalTargetsArgs { scan_benches: true, scan_examples: true },
+            Some(LocalTargetKind::Example)
+        ),
+        case(
+            "benches/bench.rs",
+            LocalTargetsArgs { scan_benches: false, scan_examples: true },
+            None
+        ),
+        case(
+            "benches/bench.rs",
+            LocalTargetsArgs { scan_benches: true, scan_examples: false },
+            Some(LocalTargetKind::Bench)
+        ),
+        case(
+            "src/examples.rs",
+            LocalTargetsArgs { scan_benches: true, scan_examples: true },
+            None
+        )
+    )]
+    fn local_target_kind_test(
+        input_path: &str,
+        input_local_targets_args: LocalTargetsArgs,
+        expected_local_target_kind: Option<LocalTargetKind>,
+    ) {
+        let package = serde_json::from_value::<Package>(serde_json::json!({
+            "name": "foo",
+            "version": "0.1.0",
+            "id": "foo 0.1.0 (path+file:///foo)",
+            "dependencies": [],
+            "targets": [],
+            "features": {},
+            "manifest_path": "/foo/Cargo.toml"
+        }))
+        .unwrap();
+        let workspace_member = WorkspaceMember {
+            package: &package,
+            root: PathBuf::from("/foo"),
+        };
+
+        assert_eq!(
+            workspace_member.local_target_kind(
+                &LocalTargetKind::selected(&input_local_targets_args),
+                &Path::new("/foo").join(input_path)
+            ),
+            expected_local_target_kind
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use cargo_geiger_serde::{
+    PackageId, PackageInfo, QuickSafetyReport, SafetyReport, Source,
+};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use url::Url;
+
+/// With `--relative-paths`, the paths under the workspace root are written
+/// relative to it, and the path sources under it as `file:///` URLs from it,
+/// so that a report doesn't change with the directory it was scanned in
+pub(super) fn make_report_paths_relative(
+    report: &mut SafetyReport,
+    workspace_root: &Path,
+) {
+    report.packages = report
+        .packages
+        .drain()
+        .map(|(package_id, mut entry)| {
+            entry.package =
+                relative_package_info(entry.package, workspace_root);
+            (relative_package_id(package_id, workspace_root), entry)
+        })
+        .collect();
+    report.packages_without_metrics = relative_package_ids(
+        &mut report.packages_without_metrics,
+        workspace_root,
+    );
+    report.ignored_packages =
+        relative_package_ids(&mut report.ignored_packages, workspace_root);
+    report.used_but_not_scanned_files = report
+        .used_but_not_scanned_files
+        .drain()
+        .map(|path| relative_path(path, workspace_root))
+        .collect();
+    for scan_warning in &mut report.scan_warnings {
+        scan_warning.path =
+            relative_path(scan_warning.path.clone(), workspace_root);
+    }
+    report.scan_warnings.sort();
+}
+
+/// The same as `make_report_paths_relative`, for the report of
+/// `--forbid-only`
+pub(super) fn make_quick_report_paths_relative(
+    report: &mut QuickSafetyReport,
+    workspace_root: &Path,
+) {
+    report.packages = report
+        .packages
+        .drain()
+        .map(|(package_id, mut entry)| {
+            entry.package =
+                relative_package_info(entry.package, workspace_root);
+            (relative_package_id(package_id, workspace_root), entry)
+        })
+        .collect();
+    report.packages_without_metrics = relative_package_ids(
+        &mut report.packages_without_metrics,
+        workspace_root,
+    );
+    report.ignored_packages =
+        relative_package_ids(&mut report.ignored_packages, workspace_root);
+}
+
+fn relative_package_info(
+    package_info: PackageInfo,
+    workspace_root: &Path,
+) -> PackageInfo {
+    let PackageInfo {
+        id,
+        mut dependencies,
+        mut dev_dependencies,
+        mut build_dependencies,
+    } = package_info;
+    PackageInfo {
+        id: relative_package_id(id, workspace_root),
+        dependencies: relative_package_ids(&mut dependencies, workspace_root),
+        dev_dependencies: relative_package_ids(
+            &mut dev_dependencies,
+            workspace_root,
+        ),
+        build_dependencies: relative_package_ids(
+            &mut build_dependencies,
+            workspace_root,
+        ),
+    }
+}
+
+fn relative_package_ids(
+    package_ids: &mut HashSet<PackageId>,
+    workspace_root: &Path,
+) -> HashSet<PackageId> {
+    package_ids
+        .drain()
+        .map(|package_id| relative_package_id(package_id, workspace_root))
+        .collect()
+}
+
+fn relative_package_id(
+    package_id: PackageId,
+    workspace_root: &Path,
+) -> PackageId {
+    match package_id.source {
+        Source::Path(url) => PackageId {
+            source: Source::Path(relative_url(url, workspace_root)),
+            ..package_id
+        },
+        _ => package_id,
+    }
+}
+
+/// The URL of a path under the workspace root, as if the workspace root were
+/// the root of the file system
+fn relative_url(url: Url, workspace_root: &Path) -> Url {
+    let relative_components = url
+        .to_file_path()
+        .ok()
+        .map(|path| path.canonicalize().unwrap_or(path))
+        .and_then(|path| {
+            path.strip_prefix(workspace_root).ok().map(path_components)
+        });
+    match relative_components {
+        Some(components) => {
+            Url::parse(&format!("file:///{}", components.join("/")))
+                .unwrap_or(url)
+        }
+        None => url,
+    }
+}
+
+fn relative_path(path: PathBuf, workspace_root: &Path) -> PathBuf {
+    let canonical_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+    match canonical_path.strip_prefix(workspace_root) {
+        Ok(relative_path) => relative_path.to_path_buf(),
+        Err(_) => path,
+    }
+}
+
+fn path_components(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => {
+                Some(name.to_string_lossy().into_owned())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod relative_paths_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_registry_package_id,
+        create_safety_report,
+    };
+
+    use cargo_geiger_serde::{DependencyKind, ScanWarning, UnsafeInfo};
+    use rstest::*;
+
+    fn package_id(name: &str, source: Source) -> PackageId {
+        PackageId {
+            source,
+            ..create_path_package_id(name)
+        }
+    }
+
+    fn path_source(url: &str) -> Source {
+        Source::Path(Url::parse(url).unwrap())
+    }
+
+    #[rstest(
+        input_url,
+        expected_url,
+        case("file:///ws/crates/foo", "file:///crates/foo"),
+        case("file:///ws", "file:///"),
+        case("file:///elsewhere/foo", "file:///elsewhere/foo"),
+        case("https://example.com/ws/foo", "https://example.com/ws/foo")
+    )]
+    fn relative_url_test(input_url: &str, expected_url: &str) {
+        assert_eq!(
+            relative_url(Url::parse(input_url).unwrap(), Path::new("/ws"))
+                .as_str(),
+            expected_url
+        );
+    }
+
+    #[rstest]
+    fn make_report_paths_relative_test() {
+        let baz_id = create_registry_package_id("baz", "0.1.0");
+        let mut package_info =
+            PackageInfo::new(package_id("foo", path_source("file:///ws/foo")));
+        package_info.add_dependency(
+            package_id("bar", path_source("file:///ws/bar")),
+            DependencyKind::Normal,
+        );
+        package_info.add_dependency(baz_id.clone(), DependencyKind::Build);
+        let mut report =
+            create_safety_report(vec![(package_info, UnsafeInfo::default())]);
+        report
+            .packages_without_metrics
+            .insert(package_id("qux", path_source("file:///ws/qux")));
+        report
+            .used_but_not_scanned_files
+            .insert(PathBuf::from("/ws/foo/src/generated.rs"));
+        report.scan_warnings.push(ScanWarning {
+            path: PathBuf::from("/elsewhere/lib.rs"),
+            message: String::from("test"),
+        });
+
+        make_report_paths_relative(&mut report, Path::new("/ws"));
+
+        let foo_id = package_id("foo", path_source("file:///foo"));
+        let entry = &report.packages[&foo_id];
+        assert_eq!(entry.package.id, foo_id);
+        assert_eq!(
+            entry.package.dependencies,
+            vec![package_id("bar", path_source("file:///bar"))]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            entry.package.build_dependencies,
+            vec![baz_id].into_iter().collect()
+        );
+        assert_eq!(
+            report.packages_without_metrics,
+            vec![package_id("qux", path_source("file:///qux"))]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            report.used_but_not_scanned_files,
+            vec![PathBuf::from("foo/src/generated.rs")]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            report.scan_warnings[0].path,
+            PathBuf::from("/elsewhere/lib.rs")
+        );
+    }
+}
@@ -7,7 +7,7 @@ use cargo::core::manifest::TargetKind;
 use cargo::core::Workspace;
 use cargo::ops;
 use cargo::ops::{CleanOptions, CompileOptions};
-use cargo::util::{interning::InternedString, CargoResult};
+use cargo::util::CargoResult;
 use cargo::Config;
 use cargo_util::paths;
 use geiger::RsFileMetrics;
@@ -22,7 +22,7 @@ use walkdir::{DirEntry, WalkDir};
 /// Provides information needed to scan for crate root
 /// `#![forbid(unsafe_code)]`.
 /// The wrapped `PathBufs` are canonicalized.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum RsFile {
     /// Executable entry point source file, usually src/main.rs
     BinRoot(PathBuf),
@@ -35,6 +35,9 @@ pub enum RsFile {
 
     /// All other .rs files.
     Other(PathBuf),
+
+    /// Files left out of the scan by `--ignore` or `--ignore-path`.
+    Ignored(PathBuf),
 }
 
 #[derive(Clone, Debug, Default)]
@@ -92,6 +95,7 @@ pub fn into_is_entry_point_and_path_buf(rs_file: RsFile) -> (bool, PathBuf) {
         RsFile::CustomBuildRoot(pb) => (true, pb),
         RsFile::LibRoot(pb) => (true, pb),
         RsFile::Other(pb) => (false, pb),
+        RsFile::Ignored(pb) => (false, pb),
     }
 }
 
@@ -161,14 +165,14 @@ pub fn resolve_rs_file_deps(
     // Need to run a cargo clean to identify all new .d deps files.
     // TODO: Figure out how this can be avoided to improve performance, clean
     // Rust builds are __slow__.
+    // The profile is cleaned along with the build, so its deps files are
+    // written again.
     let clean_options = CleanOptions {
         config,
         spec: packages,
         targets: vec![],
         profile_specified: false,
-        // A temporary hack to get cargo 0.43 to build, TODO: look closer at the updated cargo API
-        // later.
-        requested_profile: InternedString::new("dev"),
+        requested_profile: compile_options.build_config.requested_profile,
         doc: false,
     };
 
@@ -304,7 +308,8 @@ mod rs_file_tests {
         case(RsFile::BinRoot(PathBuf::from("test.txt")), true),
         case(RsFile::CustomBuildRoot(PathBuf::from("test.txt")), true),
         case(RsFile::LibRoot(PathBuf::from("test.txt")), true),
-        case(RsFile::Other(PathBuf::from("test.txt")), false)
+        case(RsFile::Other(PathBuf::from("test.txt")), false),
+        case(RsFile::Ignored(PathBuf::from("test.txt")), false)
     )]
     fn into_is_entry_point_and_path_buf_test(
         input_rs_file: RsFile,
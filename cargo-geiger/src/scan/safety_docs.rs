@@ -0,0 +1,165 @@
+use super::GeigerContext;
+
+use cargo_metadata::Metadata;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub struct UndocumentedUnsafeError {
+    pub undocumented_unsafe_count: u64,
+}
+
+impl Error for UndocumentedUnsafeError {}
+
+impl fmt::Display for UndocumentedUnsafeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Found {} unsafe blocks without a `// SAFETY:` comment",
+            self.undocumented_unsafe_count
+        )
+    }
+}
+
+/// `--require-safety-docs` was given with `--forbid-only`, which doesn't scan
+/// the `unsafe` blocks
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SafetyDocsForbidOnlyError;
+
+impl Error for SafetyDocsForbidOnlyError {}
+
+impl fmt::Display for SafetyDocsForbidOnlyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "`--require-safety-docs` can't be used with `--forbid-only`"
+        )
+    }
+}
+
+/// The number of undocumented `unsafe` blocks, for each workspace crate which
+/// has any, sorted by crate name. Dependencies are left out, since their
+/// source can't be fixed from this workspace.
+fn undocumented_unsafe_blocks_per_workspace_crate(
+    geiger_context: &GeigerContext,
+    metadata: &Metadata,
+    rs_files_used: &HashSet<PathBuf>,
+) -> Vec<(String, u64)> {
+    let mut undocumented_unsafe_blocks = metadata
+        .packages
+        .iter()
+        .filter(|package| metadata.workspace_members.contains(&package.id))
+        .filter_map(|package| {
+            let package_metrics =
+                geiger_context.package_id_to_metrics.get(&package.id)?;
+            let count = package_metrics
+                .rs_path_to_metrics
+                .iter()
+                .filter(|(path_buf, _)| rs_files_used.contains(*path_buf))
+                .map(|(_, rs_file_metrics_wrapper)| {
+                    rs_file_metrics_wrapper.metrics.undocumented_unsafe_blocks
+                })
+                .sum::<u64>();
+            Some((format!("{} {}", package.name, package.version), count))
+        })
+        .filter(|(_, count)| *count > 0)
+        .collect::<Vec<(String, u64)>>();
+
+    undocumented_unsafe_blocks.sort();
+    undocumented_unsafe_blocks
+}
+
+/// Returns the total number of undocumented `unsafe` blocks in workspace
+/// crates, along with report lines listing the count per crate.
+pub fn check_safety_docs(
+    geiger_context: &GeigerContext,
+    metadata: &Metadata,
+    rs_files_used: &HashSet<PathBuf>,
+) -> (u64, Vec<String>) {
+    let undocumented_unsafe_blocks =
+        undocumented_unsafe_blocks_per_workspace_crate(
+            geiger_context,
+            metadata,
+            rs_files_used,
+        );
+    let undocumented_unsafe_count = undocumented_unsafe_blocks
+        .iter()
+        .map(|(_, count)| count)
+        .sum();
+
+    (
+        undocumented_unsafe_count,
+        construct_undocumented_unsafe_lines(&undocumented_unsafe_blocks),
+    )
+}
+
+fn construct_undocumented_unsafe_lines(
+    undocumented_unsafe_blocks: &[(String, u64)],
+) -> Vec<String> {
+    if undocumented_unsafe_blocks.is_empty() {
+        return vec![];
+    }
+
+    let mut undocumented_unsafe_lines = vec![String::from(
+        "Unsafe blocks without a `// SAFETY:` comment, per workspace crate:",
+    )];
+    for (crate_name, count) in undocumented_unsafe_blocks {
+        undocumented_unsafe_lines
+            .push(format!("    {}: {}", crate_name, count));
+    }
+    undocumented_unsafe_lines.push(String::new());
+
+    undocumented_unsafe_lines
+}
+
+#[cfg(test)]
+mod safety_docs_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest(
+        input_undocumented_unsafe_blocks,
+        expected_undocumented_unsafe_lines,
+        case(vec![], vec![]),
+        case(
+            vec![
+                (String::from("bar 0.1.0"), 1),
+                (String::from("foo 1.2.3"), 4)
+            ],
+            vec![
+                String::from(
+                    "Unsafe blocks without a `// SAFETY:` comment, per workspace crate:"
+                ),
+                String::from("    bar 0.1.0: 1"),
+                String::from("    foo 1.2.3: 4"),
+                String::new()
+            ]
+        )
+    )]
+    fn construct_undocumented_unsafe_lines_test(
+        input_undocumented_unsafe_blocks: Vec<(String, u64)>,
+        expected_undocumented_unsafe_lines: Vec<String>,
+    ) {
+        assert_eq!(
+            construct_undocumented_unsafe_lines(
+                &input_undocumented_unsafe_blocks
+            ),
+            expected_undocumented_unsafe_lines
+        );
+    }
+
+    #[rstest]
+    fn undocumented_unsafe_error_display_test() {
+        let error = UndocumentedUnsafeError {
+            undocumented_unsafe_count: 3,
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "Found 3 unsafe blocks without a `// SAFETY:` comment"
+        );
+    }
+}
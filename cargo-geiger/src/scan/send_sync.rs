@@ -0,0 +1,37 @@
+use super::GeigerContext;
+
+use cargo_metadata::Metadata;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// The number of `unsafe impl`s of `Send` and `Sync` in the files used by the
+/// build, for each crate, labelled `<name> <version>` like the rows of the
+/// other gates. With `workspace_only`, only the workspace members are
+/// counted.
+pub fn send_sync_impls_per_crate(
+    geiger_context: &GeigerContext,
+    metadata: &Metadata,
+    rs_files_used: &HashSet<PathBuf>,
+    workspace_only: bool,
+) -> Vec<(String, u64)> {
+    metadata
+        .packages
+        .iter()
+        .filter(|package| {
+            !workspace_only || metadata.workspace_members.contains(&package.id)
+        })
+        .filter_map(|package| {
+            let package_metrics =
+                geiger_context.package_id_to_metrics.get(&package.id)?;
+            let count = package_metrics
+                .rs_path_to_metrics
+                .iter()
+                .filter(|(path_buf, _)| rs_files_used.contains(*path_buf))
+                .map(|(_, rs_file_metrics_wrapper)| {
+                    rs_file_metrics_wrapper.metrics.send_sync_impls
+                })
+                .sum::<u64>();
+            Some((format!("{} {}", package.name, package.version), count))
+        })
+        .collect()
+}
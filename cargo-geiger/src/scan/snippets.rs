@@ -0,0 +1,242 @@
+use super::default::canonical_workspace_root;
+use super::GeigerContext;
+
+use cargo_metadata::Metadata;
+use geiger::UnsafeLocation;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The lines of source printed before and after the first line of each
+/// unsafe item with `--show-snippets`
+const CONTEXT_LINES: usize = 2;
+
+/// The lines printed below the tree with `--show-snippets`: each unsafe item
+/// of the files of the workspace crates used by the build, with the source
+/// around it. Dependencies are left out, since their source can't be fixed
+/// from this workspace.
+pub fn construct_snippet_lines(
+    geiger_context: &GeigerContext,
+    metadata: &Metadata,
+    rs_files_used: &HashSet<PathBuf>,
+) -> Vec<String> {
+    let mut workspace_packages = metadata
+        .packages
+        .iter()
+        .filter(|package| metadata.workspace_members.contains(&package.id))
+        .collect::<Vec<_>>();
+    workspace_packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let workspace_root = canonical_workspace_root(metadata);
+    let mut snippet_lines = vec![];
+    for package in workspace_packages {
+        let package_metrics =
+            match geiger_context.package_id_to_metrics.get(&package.id) {
+                Some(package_metrics) => package_metrics,
+                None => continue,
+            };
+        let mut unsafe_files = package_metrics
+            .rs_path_to_metrics
+            .iter()
+            .filter(|(path_buf, rs_file_metrics_wrapper)| {
+                rs_files_used.contains(*path_buf)
+                    && !rs_file_metrics_wrapper
+                        .metrics
+                        .unsafe_locations
+                        .is_empty()
+            })
+            .collect::<Vec<_>>();
+        unsafe_files.sort_by_key(|(path_buf, _)| *path_buf);
+
+        for (path_buf, rs_file_metrics_wrapper) in unsafe_files {
+            let source = match fs::read_to_string(path_buf) {
+                Ok(source) => source,
+                Err(e) => {
+                    eprintln!(
+                        "WARNING: Failed to read {} for its snippets: {}",
+                        path_buf.display(),
+                        e
+                    );
+                    continue;
+                }
+            };
+            let path = display_path(path_buf, &workspace_root);
+            let mut unsafe_locations =
+                rs_file_metrics_wrapper.metrics.unsafe_locations.clone();
+            unsafe_locations.sort();
+            for unsafe_location in &unsafe_locations {
+                snippet_lines.append(&mut construct_snippet(
+                    &source,
+                    &path,
+                    &format!("{} {}", package.name, package.version),
+                    unsafe_location,
+                ));
+            }
+        }
+    }
+    if snippet_lines.is_empty() {
+        return vec![];
+    }
+
+    let mut lines = vec![String::from("Unsafe code in workspace crates:")];
+    lines.push(String::new());
+    lines.append(&mut snippet_lines);
+    lines
+}
+
+/// An unsafe item with the lines around its first line, with their line
+/// numbers in a gutter and the start of the item marked, as in the
+/// diagnostics of the compiler
+fn construct_snippet(
+    source: &str,
+    path: &str,
+    crate_name: &str,
+    unsafe_location: &UnsafeLocation,
+) -> Vec<String> {
+    let source_lines = source.lines().collect::<Vec<_>>();
+    let first_line = unsafe_location.line.saturating_sub(CONTEXT_LINES).max(1);
+    let last_line = (unsafe_location.line + CONTEXT_LINES)
+        .min(unsafe_location.end_line.max(unsafe_location.line))
+        .min(source_lines.len());
+    let gutter_width = last_line.max(first_line).to_string().len();
+    let empty_gutter = format!("{} |", " ".repeat(gutter_width));
+
+    let mut lines = vec![
+        format!("unsafe {} in {}", unsafe_location.kind, crate_name),
+        format!(
+            "{}--> {}:{}:{}",
+            " ".repeat(gutter_width),
+            path,
+            unsafe_location.line,
+            unsafe_location.column
+        ),
+        empty_gutter.clone(),
+    ];
+    for line_number in first_line..=last_line {
+        lines.push(
+            format!(
+                "{:>width$} | {}",
+                line_number,
+                source_lines[line_number - 1],
+                width = gutter_width
+            )
+            .trim_end()
+            .to_string(),
+        );
+        if line_number == unsafe_location.line {
+            lines.push(format!(
+                "{} {}^",
+                empty_gutter,
+                " ".repeat(unsafe_location.column.saturating_sub(1))
+            ));
+        }
+    }
+    if unsafe_location.end_line > last_line {
+        lines.push(format!("{}...", " ".repeat(gutter_width)));
+    }
+    lines.push(String::new());
+    lines
+}
+
+/// The path of a file relative to the workspace root, when it is in it
+fn display_path(path: &Path, workspace_root: &Path) -> String {
+    path.strip_prefix(workspace_root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+#[cfg(test)]
+mod snippets_tests {
+    use super::*;
+
+    use geiger::UnsafeKind;
+    use rstest::*;
+
+    const SOURCE: &str = "\
+fn f() {}
+
+pub fn g() {
+    let x = 1;
+    unsafe {
+        h(x);
+    }
+}
+";
+
+    #[rstest]
+    fn construct_snippet_test() {
+        let unsafe_location = UnsafeLocation {
+            line: 5,
+            column: 5,
+            end_line: 7,
+            end_column: 5,
+            kind: UnsafeKind::Block,
+        };
+
+        assert_eq!(
+            construct_snippet(
+                SOURCE,
+                "src/lib.rs",
+                "foo 0.1.0",
+                &unsafe_location
+            ),
+            vec![
+                "unsafe block in foo 0.1.0",
+                " --> src/lib.rs:5:5",
+                "  |",
+                "3 | pub fn g() {",
+                "4 |     let x = 1;",
+                "5 |     unsafe {",
+                "  |     ^",
+                "6 |         h(x);",
+                "7 |     }",
+                "",
+            ]
+        );
+    }
+
+    #[rstest]
+    fn construct_snippet_test_long_item() {
+        let unsafe_location = UnsafeLocation {
+            line: 1,
+            column: 1,
+            end_line: 8,
+            end_column: 1,
+            kind: UnsafeKind::Function,
+        };
+
+        assert_eq!(
+            construct_snippet(
+                SOURCE,
+                "src/lib.rs",
+                "foo 0.1.0",
+                &unsafe_location
+            ),
+            vec![
+                "unsafe function in foo 0.1.0",
+                " --> src/lib.rs:1:1",
+                "  |",
+                "1 | fn f() {}",
+                "  | ^",
+                "2 |",
+                "3 | pub fn g() {",
+                " ...",
+                "",
+            ]
+        );
+    }
+
+    #[rstest(
+        input_path,
+        expected_path,
+        case("/workspace/src/lib.rs", "src/lib.rs"),
+        case("/registry/foo/src/lib.rs", "/registry/foo/src/lib.rs")
+    )]
+    fn display_path_test(input_path: &str, expected_path: &str) {
+        assert_eq!(
+            display_path(Path::new(input_path), Path::new("/workspace")),
+            expected_path
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use crate::format::summary::format_category_counts;
+
+use super::GeigerContext;
+
+use cargo_geiger_serde::CounterBlock;
+use cargo_metadata::{Metadata, Package, Target};
+use geiger::{find_unsafe_in_file, IncludeTests};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The kinds of cargo targets the unsafe usage of a crate is broken down by
+/// with `--target-breakdown`, in the order they are printed in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum BreakdownKind {
+    Lib,
+    Bin,
+    Example,
+    Test,
+    Bench,
+    Build,
+    /// Files matching no target, such as those of a crate scanned from
+    /// another directory with `--source-map`
+    Other,
+}
+
+impl BreakdownKind {
+    const ALL: [BreakdownKind; 7] = [
+        BreakdownKind::Lib,
+        BreakdownKind::Bin,
+        BreakdownKind::Example,
+        BreakdownKind::Test,
+        BreakdownKind::Bench,
+        BreakdownKind::Build,
+        BreakdownKind::Other,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            BreakdownKind::Lib => "lib",
+            BreakdownKind::Bin => "bins",
+            BreakdownKind::Example => "examples",
+            BreakdownKind::Test => "tests",
+            BreakdownKind::Bench => "benches",
+            BreakdownKind::Build => "build script",
+            BreakdownKind::Other => "other",
+        }
+    }
+
+    /// The directory of the crate in which cargo finds the targets of the
+    /// kind without them being declared
+    fn directory_name(self) -> Option<&'static str> {
+        match self {
+            BreakdownKind::Bin => Some("src/bin"),
+            BreakdownKind::Example => Some("examples"),
+            BreakdownKind::Test => Some("tests"),
+            BreakdownKind::Bench => Some("benches"),
+            _ => None,
+        }
+    }
+
+    fn from_target(target: &Target) -> BreakdownKind {
+        let is_kind = |kind: &str| target.kind.iter().any(|k| k == kind);
+        if is_kind("custom-build") {
+            BreakdownKind::Build
+        } else if is_kind("bin") {
+            BreakdownKind::Bin
+        } else if is_kind("example") {
+            BreakdownKind::Example
+        } else if is_kind("test") {
+            BreakdownKind::Test
+        } else if is_kind("bench") {
+            BreakdownKind::Bench
+        } else {
+            BreakdownKind::Lib
+        }
+    }
+}
+
+/// The root of a crate and the entry points of its targets, canonicalized
+/// like the paths of the scanned files
+struct BreakdownCrate {
+    root: PathBuf,
+    entry_points: Vec<(PathBuf, BreakdownKind)>,
+}
+
+impl BreakdownCrate {
+    fn new(package: &Package) -> Option<Self> {
+        let root = package.manifest_path.parent()?.canonicalize().ok()?;
+        let entry_points = package
+            .targets
+            .iter()
+            .filter_map(|target| {
+                let src_path = target.src_path.canonicalize().ok()?;
+                Some((src_path, BreakdownKind::from_target(target)))
+            })
+            .collect();
+        Some(BreakdownCrate { root, entry_points })
+    }
+
+    /// The kind of the target a file belongs to: the target it is the entry
+    /// point of, or else the kind whose directory it is in, or else the
+    /// library or binary whose entry point is in the same directory or above
+    /// it. A file shared by a library and a binary is counted for the
+    /// library.
+    fn breakdown_kind(&self, path: &Path) -> BreakdownKind {
+        if let Some((_, kind)) = self
+            .entry_points
+            .iter()
+            .find(|(entry_point, _)| entry_point == path)
+        {
+            return *kind;
+        }
+        if let Some(kind) = BreakdownKind::ALL.iter().find(|kind| {
+            kind.directory_name().is_some_and(|directory_name| {
+                path.starts_with(self.root.join(directory_name))
+            })
+        }) {
+            return *kind;
+        }
+        [BreakdownKind::Lib, BreakdownKind::Bin]
+            .iter()
+            .copied()
+            .find(|kind| {
+                self.entry_points.iter().any(|(entry_point, entry_kind)| {
+                    entry_kind == kind
+                        && entry_point
+                            .parent()
+                            .is_some_and(|parent| path.starts_with(parent))
+                })
+            })
+            .unwrap_or(BreakdownKind::Other)
+    }
+}
+
+/// With `--target-breakdown`, a line for each crate with unsafe code and each
+/// kind of target with source files, giving the unsafe usage of its targets
+/// of the kind. The build scripts, which are left out of the rest of the
+/// report, are scanned for it.
+pub fn construct_target_breakdown_lines(
+    target_breakdown: bool,
+    geiger_context: &GeigerContext,
+    include_tests: IncludeTests,
+    metadata: &Metadata,
+    rs_files_used: &HashSet<PathBuf>,
+) -> Vec<String> {
+    if !target_breakdown {
+        return vec![];
+    }
+
+    let mut packages = metadata
+        .packages
+        .iter()
+        .filter(|package| {
+            geiger_context
+                .package_id_to_metrics
+                .contains_key(&package.id)
+        })
+        .collect::<Vec<_>>();
+    packages.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+
+    let mut target_breakdown_lines = vec![];
+    for package in packages {
+        let breakdown_crate = match BreakdownCrate::new(package) {
+            Some(breakdown_crate) => breakdown_crate,
+            None => continue,
+        };
+        let mut kind_counts = BreakdownKind::ALL
+            .iter()
+            .map(|kind| (*kind, None::<(CounterBlock, CounterBlock)>))
+            .collect::<Vec<_>>();
+        let mut add_counts = |path: &Path, counters: CounterBlock| {
+            let kind = breakdown_crate.breakdown_kind(path);
+            let (_, counts) =
+                kind_counts.iter_mut().find(|(k, _)| *k == kind).unwrap();
+            let (used, unused) = counts.get_or_insert_with(Default::default);
+            if rs_files_used.contains(path) {
+                *used += counters;
+            } else {
+                *unused += counters;
+            }
+        };
+
+        let package_metrics =
+            &geiger_context.package_id_to_metrics[&package.id];
+        for (path, rs_file_metrics_wrapper) in
+            &package_metrics.rs_path_to_metrics
+        {
+            add_counts(path, rs_file_metrics_wrapper.metrics.counters.clone());
+        }
+        for (entry_point, _) in breakdown_crate
+            .entry_points
+            .iter()
+            .filter(|(_, kind)| *kind == BreakdownKind::Build)
+        {
+            match find_unsafe_in_file(entry_point, include_tests) {
+                Ok(rs_file_metrics) => {
+                    add_counts(entry_point, rs_file_metrics.counters)
+                }
+                Err(e) => eprintln!(
+                    "WARNING: Failed to scan build script: {}, {:?}",
+                    entry_point.display(),
+                    e
+                ),
+            }
+        }
+
+        let has_unsafe = kind_counts.iter().any(|(_, counts)| {
+            counts.as_ref().is_some_and(|(used, unused)| {
+                used.has_unsafe() || unused.has_unsafe()
+            })
+        });
+        if !has_unsafe {
+            continue;
+        }
+        for (kind, counts) in kind_counts {
+            if let Some((used, unused)) = counts {
+                target_breakdown_lines.push(format!(
+                    "    {} {} {}: {}",
+                    package.name,
+                    package.version,
+                    kind.label(),
+                    format_category_counts(&used, &unused)
+                ));
+            }
+        }
+    }
+
+    if target_breakdown_lines.is_empty() {
+        return vec![];
+    }
+    target_breakdown_lines.insert(
+        0,
+        String::from(
+            "Unsafe code used by the build/found in each kind of target of \
+            the crates using unsafe code, build scripts included:",
+        ),
+    );
+    target_breakdown_lines.push(String::new());
+    target_breakdown_lines
+}
+
+#[cfg(test)]
+mod target_breakdown_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest(
+        input_path,
+        expected_breakdown_kind,
+        case("src/lib.rs", BreakdownKind::Lib),
+        case("src/parser/mod.rs", BreakdownKind::Lib),
+        case("src/main.rs", BreakdownKind::Bin),
+        case("src/bin/tool.rs", BreakdownKind::Bin),
+        case("examples/demo/helper.rs", BreakdownKind::Example),
+        case("tests/integration.rs", BreakdownKind::Test),
+        case("benches/bench.rs", BreakdownKind::Bench),
+        case("build.rs", BreakdownKind::Build),
+        case("fuzz/fuzz_targets/parse.rs", BreakdownKind::Other)
+    )]
+    fn breakdown_kind_test(
+        input_path: &str,
+        expected_breakdown_kind: BreakdownKind,
+    ) {
+        let breakdown_crate = BreakdownCrate {
+            root: PathBuf::from("/foo"),
+            entry_points: vec![
+                (PathBuf::from("/foo/src/lib.rs"), BreakdownKind::Lib),
+                (PathBuf::from("/foo/src/main.rs"), BreakdownKind::Bin),
+                (PathBuf::from("/foo/build.rs"), BreakdownKind::Build),
+            ],
+        };
+
+        assert_eq!(
+            breakdown_crate.breakdown_kind(&Path::new("/foo").join(input_path)),
+            expected_breakdown_kind
+        );
+    }
+}
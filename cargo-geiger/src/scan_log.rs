@@ -0,0 +1,460 @@
+use cargo::CliError;
+use cargo_geiger_serde::SafetyReport;
+use cargo_metadata::semver::Version;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The store of `history` at the root of the workspace, unless
+/// `--history-store` is given
+pub const DEFAULT_HISTORY_STORE: &str = "geiger-history.jsonl";
+
+/// The width of the bar of the scan using the most unsafe code with
+/// `history show`
+const TREND_BAR_WIDTH: u64 = 40;
+
+/// The `history` subcommand:
+/// `record` appends the summary of a scan to the store, and `show` prints
+/// the trend of the recorded scans
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HistoryCommand {
+    Record,
+    Show,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HistoryArgsError;
+
+impl Error for HistoryArgsError {}
+
+impl fmt::Display for HistoryArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected `history record` or `history show`")
+    }
+}
+
+/// The summary of a scan in the store, a line of Json for each scan
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HistoryRecord {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// The commit checked out when the scan was recorded, or `None` outside
+    /// of a git repository
+    pub commit: Option<String>,
+    /// Ordered by name and version
+    pub crates: Vec<HistoryCrate>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HistoryCrate {
+    pub name: String,
+    pub version: Version,
+    pub used: u64,
+    pub found: u64,
+}
+
+impl HistoryRecord {
+    pub fn from_report(
+        report: &SafetyReport,
+        timestamp: u64,
+        commit: Option<String>,
+    ) -> Self {
+        let mut crates = report
+            .packages
+            .values()
+            .map(|entry| {
+                let used = entry.unsafety.used.total_unsafe();
+                HistoryCrate {
+                    name: entry.package.id.name.clone(),
+                    version: entry.package.id.version.clone(),
+                    used,
+                    found: used + entry.unsafety.unused.total_unsafe(),
+                }
+            })
+            .collect::<Vec<_>>();
+        crates.sort_by(|a, b| {
+            a.name.cmp(&b.name).then_with(|| a.version.cmp(&b.version))
+        });
+        HistoryRecord {
+            timestamp,
+            commit,
+            crates,
+        }
+    }
+
+    fn total_used(&self) -> u64 {
+        self.crates
+            .iter()
+            .map(|history_crate| history_crate.used)
+            .sum()
+    }
+
+    fn total_found(&self) -> u64 {
+        self.crates
+            .iter()
+            .map(|history_crate| history_crate.found)
+            .sum()
+    }
+}
+
+/// The `history` subcommand from the positional arguments, or `None` if they
+/// are not of the subcommand
+pub fn parse_history_args(
+    positional_args: &[String],
+) -> Result<Option<HistoryCommand>, HistoryArgsError> {
+    match positional_args {
+        [subcommand, ..] if subcommand != "history" => Ok(None),
+        [] => Ok(None),
+        [_, action] if action == "record" => Ok(Some(HistoryCommand::Record)),
+        [_, action] if action == "show" => Ok(Some(HistoryCommand::Show)),
+        _ => Err(HistoryArgsError),
+    }
+}
+
+/// The seconds since the Unix epoch of now
+pub fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// The commit checked out in the directory, or `None` if it isn't in a git
+/// repository, or git can't be run
+pub fn current_commit(directory: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(directory)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|commit| commit.trim().to_string())
+        .filter(|commit| !commit.is_empty())
+}
+
+/// Append the record as a line to the store, which is created if missing
+pub fn append_history_record(
+    history_store_path: &Path,
+    history_record: &HistoryRecord,
+) -> Result<(), CliError> {
+    let append_line = || -> std::io::Result<()> {
+        let mut history_store = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(history_store_path)?;
+        writeln!(
+            history_store,
+            "{}",
+            serde_json::to_string(history_record).unwrap()
+        )
+    };
+    append_line().map_err(|e| {
+        eprintln!(
+            "Failed to write history to file: {}",
+            history_store_path.display()
+        );
+        CliError::new(anyhow::Error::from(e), 1)
+    })
+}
+
+/// Read the records of the store, in the order they were recorded
+pub fn read_history_records(
+    history_store_path: &Path,
+) -> Result<Vec<HistoryRecord>, CliError> {
+    let history_content =
+        fs::read_to_string(history_store_path).map_err(|e| {
+            eprintln!(
+                "Failed to read history from file: {}, recorded with \
+                `history record`",
+                history_store_path.display()
+            );
+            anyhow::Error::from(e)
+        })?;
+    history_content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                eprintln!(
+                    "Failed to parse line {} of history file: {}",
+                    index + 1,
+                    history_store_path.display()
+                );
+                CliError::new(anyhow::Error::from(e), 1)
+            })
+        })
+        .collect()
+}
+
+/// The lines printed by `history show`: a row for each recorded scan with
+/// its totals, and a bar of the unsafe code used by the build scaled to the
+/// scan using the most
+pub fn construct_history_lines(
+    history_records: &[HistoryRecord],
+) -> Vec<String> {
+    let mut history_lines = vec![
+        String::from("Unsafe code used by the build over time:"),
+        String::new(),
+    ];
+    if history_records.is_empty() {
+        history_lines.push(String::from("No scans recorded."));
+        history_lines.push(String::new());
+        return history_lines;
+    }
+
+    history_lines.push(format!(
+        "{: <17}{: <10}{: >7}{: >14}{: >8}{: >8}",
+        "Date", "Commit", "Crates", "Unsafe crates", "Used", "Found"
+    ));
+    let max_used = history_records
+        .iter()
+        .map(HistoryRecord::total_used)
+        .max()
+        .unwrap_or(0);
+    for history_record in history_records {
+        let total_used = history_record.total_used();
+        let bar_width = match max_used {
+            0 => 0,
+            // Rounded up, so any unsafe code shows.
+            _ => (total_used * TREND_BAR_WIDTH).div_ceil(max_used),
+        };
+        history_lines.push(format!(
+            "{: <17}{: <10}{: >7}{: >14}{: >8}{: >8}  {}",
+            format_timestamp(history_record.timestamp),
+            history_record
+                .commit
+                .as_deref()
+                .map_or("-", |commit| &commit[..commit.len().min(8)]),
+            history_record.crates.len(),
+            history_record
+                .crates
+                .iter()
+                .filter(|history_crate| history_crate.used > 0)
+                .count(),
+            total_used,
+            history_record.total_found(),
+            "#".repeat(bar_width as usize)
+        ));
+    }
+
+    let first_used = history_records[0].total_used() as i64;
+    let last_used = history_records[history_records.len() - 1].total_used();
+    history_lines.push(String::new());
+    history_lines.push(format!(
+        "Change over {} scans: {:+}",
+        history_records.len(),
+        last_used as i64 - first_used
+    ));
+    history_lines.push(String::new());
+    history_lines
+}
+
+/// The timestamp as a UTC date and time, such as `2022-08-30 14:05`
+fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let seconds_of_day = timestamp % 86_400;
+
+    // The civil date of the days since the Unix epoch, in the proleptic
+    // Gregorian calendar, with eras of 400 years starting on March 1st.
+    let days_since_era_start = days + 719_468;
+    let era = days_since_era_start.div_euclid(146_097);
+    let day_of_era = days_since_era_start.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60
+    )
+}
+
+#[cfg(test)]
+mod scan_log_tests {
+    use super::*;
+
+    use crate::lib_tests::{
+        create_path_package_id, create_safety_report, create_unsafe_info,
+    };
+
+    use cargo_geiger_serde::PackageInfo;
+    use rstest::*;
+
+    fn history_record(
+        timestamp: u64,
+        commit: Option<&str>,
+        crates: &[(&str, u64, u64)],
+    ) -> HistoryRecord {
+        HistoryRecord {
+            timestamp,
+            commit: commit.map(String::from),
+            crates: crates
+                .iter()
+                .map(|(name, used, found)| HistoryCrate {
+                    name: name.to_string(),
+                    version: Version::new(0, 1, 0),
+                    used: *used,
+                    found: *found,
+                })
+                .collect(),
+        }
+    }
+
+    #[rstest(
+        input_positional_args,
+        expected_history_command,
+        case(vec![], Ok(None)),
+        case(vec!["compare", "foo", "0.1.0", "0.2.0"], Ok(None)),
+        case(vec!["history", "record"], Ok(Some(HistoryCommand::Record))),
+        case(vec!["history", "show"], Ok(Some(HistoryCommand::Show))),
+        case(vec!["history"], Err(HistoryArgsError)),
+        case(vec!["history", "show", "all"], Err(HistoryArgsError))
+    )]
+    fn parse_history_args_test(
+        input_positional_args: Vec<&str>,
+        expected_history_command: Result<
+            Option<HistoryCommand>,
+            HistoryArgsError,
+        >,
+    ) {
+        let positional_args = input_positional_args
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            parse_history_args(&positional_args),
+            expected_history_command
+        );
+    }
+
+    #[rstest]
+    fn history_record_from_report_test() {
+        let report = create_safety_report(
+            vec![("foo", 2, 1), ("bar", 0, 3)]
+                .into_iter()
+                .map(|(name, used_exprs, unused_exprs)| {
+                    let mut unsafety = create_unsafe_info(used_exprs);
+                    unsafety.unused.exprs.unsafe_ = unused_exprs;
+                    (PackageInfo::new(create_path_package_id(name)), unsafety)
+                })
+                .collect(),
+        );
+
+        assert_eq!(
+            HistoryRecord::from_report(
+                &report,
+                1_661_868_300,
+                Some(String::from("1a2b3c"))
+            ),
+            history_record(
+                1_661_868_300,
+                Some("1a2b3c"),
+                &[("bar", 0, 3), ("foo", 2, 3)]
+            )
+        );
+    }
+
+    #[rstest]
+    fn append_and_read_history_records_test() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let history_store_path = temp_dir.path().join(DEFAULT_HISTORY_STORE);
+        let history_records = vec![
+            history_record(1, None, &[("foo", 2, 3)]),
+            history_record(2, Some("1a2b3c"), &[]),
+        ];
+
+        for history_record in &history_records {
+            append_history_record(&history_store_path, history_record).unwrap();
+        }
+
+        assert_eq!(
+            read_history_records(&history_store_path).unwrap(),
+            history_records
+        );
+    }
+
+    #[rstest(
+        input_timestamp,
+        expected_date,
+        case(0, "1970-01-01 00:00"),
+        case(951_782_400, "2000-02-29 00:00"),
+        case(1_661_868_300, "2022-08-30 14:05")
+    )]
+    fn format_timestamp_test(input_timestamp: u64, expected_date: &str) {
+        assert_eq!(format_timestamp(input_timestamp), expected_date);
+    }
+
+    #[rstest]
+    fn construct_history_lines_test() {
+        let history_records = vec![
+            history_record(
+                1_661_868_300,
+                Some("1a2b3c4d5e6f"),
+                &[("foo", 8, 10), ("bar", 0, 0)],
+            ),
+            history_record(1_661_954_700, None, &[("foo", 2, 10)]),
+        ];
+
+        assert_eq!(
+            construct_history_lines(&history_records),
+            vec![
+                String::from("Unsafe code used by the build over time:"),
+                String::new(),
+                String::from(
+                    "Date             Commit     Crates Unsafe crates    \
+                    Used   Found"
+                ),
+                format!(
+                    "2022-08-30 14:05 1a2b3c4d        2             1       \
+                    8      10  {}",
+                    "#".repeat(40)
+                ),
+                format!(
+                    "2022-08-31 14:05 -               1             1       \
+                    2      10  {}",
+                    "#".repeat(10)
+                ),
+                String::new(),
+                String::from("Change over 2 scans: -6"),
+                String::new(),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn construct_history_lines_test_empty() {
+        assert_eq!(
+            construct_history_lines(&[]),
+            vec![
+                "Unsafe code used by the build over time:",
+                "",
+                "No scans recorded.",
+                "",
+            ]
+        );
+    }
+}
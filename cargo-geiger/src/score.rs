@@ -0,0 +1,205 @@
+use crate::format::table::UNSAFE_CATEGORY_NAMES;
+
+use cargo_geiger_serde::CounterBlock;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub struct MaxScoreError {
+    pub crate_count: u64,
+    pub max_score: f64,
+}
+
+impl Error for MaxScoreError {}
+
+impl fmt::Display for MaxScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Found {} crates with a weighted unsafe score above {}",
+            self.crate_count, self.max_score
+        )
+    }
+}
+
+/// The weight of each unsafe category in the weighted score of a crate. Every
+/// category weighs 1 by default, so that the score is the total unsafe count.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CategoryWeights {
+    pub functions: f64,
+    pub exprs: f64,
+    pub item_impls: f64,
+    pub item_traits: f64,
+    pub methods: f64,
+}
+
+impl CategoryWeights {
+    /// The sum of the used unsafe counts, each multiplied by the weight of its
+    /// category
+    pub fn weighted_score(&self, used: &CounterBlock) -> f64 {
+        used.functions.unsafe_ as f64 * self.functions
+            + used.exprs.unsafe_ as f64 * self.exprs
+            + used.item_impls.unsafe_ as f64 * self.item_impls
+            + used.item_traits.unsafe_ as f64 * self.item_traits
+            + used.methods.unsafe_ as f64 * self.methods
+    }
+}
+
+impl Default for CategoryWeights {
+    fn default() -> Self {
+        CategoryWeights {
+            functions: 1.0,
+            exprs: 1.0,
+            item_impls: 1.0,
+            item_traits: 1.0,
+            methods: 1.0,
+        }
+    }
+}
+
+impl FromStr for CategoryWeights {
+    type Err = CategoryWeightsParseError;
+
+    /// Parse a comma separated list of `<category>=<weight>`, such as
+    /// `impls=0.5,expressions=3`. Categories which are not listed keep the
+    /// weight 1.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut category_weights = CategoryWeights::default();
+        for category_weight in s.split(',') {
+            let parse_error = CategoryWeightsParseError {
+                category_weight: category_weight.to_string(),
+            };
+            let (category, weight) = match category_weight.split_once('=') {
+                Some((category, weight)) => (category, weight),
+                None => return Err(parse_error),
+            };
+            let weight = match weight.trim().parse::<f64>() {
+                Ok(weight) if weight.is_finite() && weight >= 0.0 => weight,
+                _ => return Err(parse_error),
+            };
+            let target = match category.trim() {
+                "functions" => &mut category_weights.functions,
+                "expressions" => &mut category_weights.exprs,
+                "impls" => &mut category_weights.item_impls,
+                "traits" => &mut category_weights.item_traits,
+                "methods" => &mut category_weights.methods,
+                _ => return Err(parse_error),
+            };
+            *target = weight;
+        }
+        Ok(category_weights)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct CategoryWeightsParseError {
+    pub category_weight: String,
+}
+
+impl Error for CategoryWeightsParseError {}
+
+impl fmt::Display for CategoryWeightsParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse category weight `{}`, expected `<category>=<weight>` with a category of {} and a non-negative weight",
+            self.category_weight,
+            UNSAFE_CATEGORY_NAMES.join(", ")
+        )
+    }
+}
+
+/// A line for each crate whose weighted score is above `max_score`, giving
+/// its score and the maximum, ordered by name
+pub fn find_crates_over_max_score(
+    category_weights: &CategoryWeights,
+    max_score: f64,
+    rows: &[(String, CounterBlock)],
+) -> Vec<String> {
+    let mut over_max_score_lines = rows
+        .iter()
+        .filter_map(|(name, used)| {
+            let score = category_weights.weighted_score(used);
+            (score > max_score).then(|| {
+                format!("    {}: score {} > {}", name, score, max_score)
+            })
+        })
+        .collect::<Vec<String>>();
+    over_max_score_lines.sort();
+    over_max_score_lines.dedup();
+    over_max_score_lines
+}
+
+#[cfg(test)]
+mod score_tests {
+    use super::*;
+
+    use cargo_geiger_serde::Count;
+    use rstest::*;
+
+    #[rstest(
+        input_category_weights,
+        expected_score,
+        case("functions=1", 15.0),
+        case("expressions=3", 19.0),
+        case("impls=0.5,expressions=0", 11.0),
+        case(" traits = 2 , methods=0 ", 13.0)
+    )]
+    fn weighted_score_test(input_category_weights: &str, expected_score: f64) {
+        let category_weights =
+            CategoryWeights::from_str(input_category_weights).unwrap();
+
+        assert_eq!(
+            category_weights.weighted_score(&create_counter_block()),
+            expected_score
+        );
+    }
+
+    #[rstest(
+        input_category_weights,
+        case("functions"),
+        case("exprs=1"),
+        case("methods=-1"),
+        case("methods=many"),
+        case("")
+    )]
+    fn category_weights_from_str_test_invalid(input_category_weights: &str) {
+        assert!(CategoryWeights::from_str(input_category_weights).is_err());
+    }
+
+    #[rstest]
+    fn find_crates_over_max_score_test() {
+        let rows = vec![
+            (String::from("foo 0.1.0"), create_counter_block()),
+            (String::from("bar 0.1.0"), CounterBlock::default()),
+        ];
+
+        assert_eq!(
+            find_crates_over_max_score(
+                &CategoryWeights::default(),
+                10.0,
+                &rows
+            ),
+            vec![String::from("    foo 0.1.0: score 15 > 10")]
+        );
+        assert!(find_crates_over_max_score(
+            &CategoryWeights::default(),
+            15.0,
+            &rows
+        )
+        .is_empty());
+    }
+
+    /// 1 function, 2 expressions, 4 impls, 3 traits and 5 methods
+    fn create_counter_block() -> CounterBlock {
+        let count = |unsafe_| Count { safe: 0, unsafe_ };
+        CounterBlock {
+            functions: count(1),
+            exprs: count(2),
+            item_impls: count(4),
+            item_traits: count(3),
+            methods: count(5),
+        }
+    }
+}
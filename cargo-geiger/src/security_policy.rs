@@ -0,0 +1,226 @@
+use cargo::Config;
+use cargo_metadata::semver::Version;
+use cargo_metadata::Metadata;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The paths, relative to the root of a package or repository, at which a
+/// security policy is looked for
+const SECURITY_POLICY_PATHS: [&str; 5] = [
+    "SECURITY.md",
+    ".github/SECURITY.md",
+    "docs/SECURITY.md",
+    ".well-known/security.txt",
+    "security.txt",
+];
+
+/// Whether each crate publishes a security policy, looked up with
+/// `--check-security-policy`. A crate is only missing if it couldn't be told,
+/// because its repository is on an unsupported host or couldn't be reached.
+#[derive(Debug, Default)]
+pub struct SecurityPolicies {
+    crate_policies: HashMap<(String, Version), bool>,
+}
+
+impl SecurityPolicies {
+    pub fn get(&self, name: &str, version: &Version) -> Option<bool> {
+        self.crate_policies
+            .get(&(name.to_string(), version.clone()))
+            .copied()
+    }
+}
+
+/// The annotation of a crate in the tree, such as ` (security policy)`
+pub fn format_security_policy(has_security_policy: Option<bool>) -> String {
+    match has_security_policy {
+        Some(true) => String::from(" (security policy)"),
+        Some(false) => String::from(" (no security policy)"),
+        None => String::from(" (security policy unknown)"),
+    }
+}
+
+/// Look up the security policy of every crate in the dependency graph, or
+/// return `None` without `--check-security-policy`.
+///
+/// A policy shipped in the sources of a crate is found without the network.
+/// Otherwise, unless the network is disabled with `--offline`, the repository
+/// of the crate is checked, once for all crates sharing it.
+pub fn find_security_policies(
+    check_security_policy: bool,
+    config: &Config,
+    metadata: &Metadata,
+) -> Option<SecurityPolicies> {
+    if !check_security_policy {
+        return None;
+    }
+
+    let mut repository_policies = HashMap::<String, Option<bool>>::new();
+    let mut security_policies = SecurityPolicies::default();
+    for package in &metadata.packages {
+        let ships_security_policy =
+            package.manifest_path.parent().is_some_and(|package_root| {
+                ships_security_policy(package_root.as_std_path())
+            });
+        let has_security_policy = if ships_security_policy {
+            Some(true)
+        } else if config.offline() {
+            None
+        } else {
+            package.repository.as_ref().and_then(|repository| {
+                *repository_policies
+                    .entry(repository.clone())
+                    .or_insert_with(|| {
+                        repository_publishes_security_policy(config, repository)
+                    })
+            })
+        };
+        if let Some(has_security_policy) = has_security_policy {
+            security_policies.crate_policies.insert(
+                (package.name.clone(), package.version.clone()),
+                has_security_policy,
+            );
+        }
+    }
+    Some(security_policies)
+}
+
+fn ships_security_policy(package_root: &Path) -> bool {
+    SECURITY_POLICY_PATHS
+        .iter()
+        .any(|path| package_root.join(path).is_file())
+}
+
+/// The URLs of the raw files of the default branch of a repository at which a
+/// security policy is looked for, or `None` if the host is not supported
+fn security_policy_urls(repository: &str) -> Option<Vec<String>> {
+    let repository = repository
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .trim_start_matches("https://")
+        .trim_start_matches("http://");
+    let (host, project) = repository.split_once('/')?;
+    let raw_url_prefix = match host {
+        "github.com" => {
+            let mut segments = project.split('/');
+            let (owner, name) = (segments.next()?, segments.next()?);
+            format!("https://raw.githubusercontent.com/{}/{}/HEAD", owner, name)
+        }
+        "gitlab.com" => {
+            // Links into a GitLab repository continue after a `-` segment.
+            let project = project.split("/-/").next()?;
+            format!("https://gitlab.com/{}/-/raw/HEAD", project)
+        }
+        _ => return None,
+    };
+    Some(
+        SECURITY_POLICY_PATHS
+            .iter()
+            .map(|path| format!("{}/{}", raw_url_prefix, path))
+            .collect(),
+    )
+}
+
+/// Whether any of the security policy files exist in the repository, or
+/// `None` if the host is not supported or a request fails
+fn repository_publishes_security_policy(
+    config: &Config,
+    repository: &str,
+) -> Option<bool> {
+    let security_policy_urls = security_policy_urls(repository)?;
+    for security_policy_url in security_policy_urls {
+        match url_exists(config, &security_policy_url) {
+            Ok(true) => return Some(true),
+            Ok(false) => continue,
+            Err(e) => {
+                eprintln!(
+                    "WARNING: Failed to check the security policy of {}: {}",
+                    repository, e
+                );
+                return None;
+            }
+        }
+    }
+    Some(false)
+}
+
+fn url_exists(config: &Config, url: &str) -> anyhow::Result<bool> {
+    let mut handle = cargo::ops::http_handle(config)?;
+    handle.url(url)?;
+    handle.nobody(true)?;
+    handle.follow_location(true)?;
+    handle.perform()?;
+    Ok(handle.response_code()? == 200)
+}
+
+#[cfg(test)]
+mod security_policy_tests {
+    use super::*;
+
+    use rstest::*;
+    use std::fs;
+
+    #[rstest(
+        input_security_policy,
+        expected_annotation,
+        case(Some(true), " (security policy)"),
+        case(Some(false), " (no security policy)"),
+        case(None, " (security policy unknown)")
+    )]
+    fn format_security_policy_test(
+        input_security_policy: Option<bool>,
+        expected_annotation: &str,
+    ) {
+        assert_eq!(
+            format_security_policy(input_security_policy),
+            expected_annotation
+        );
+    }
+
+    #[rstest(
+        input_repository,
+        expected_first_url,
+        case(
+            "https://github.com/rust-secure-code/cargo-geiger",
+            Some("https://raw.githubusercontent.com/rust-secure-code/cargo-geiger/HEAD/SECURITY.md")
+        ),
+        case(
+            "https://github.com/rust-lang/cargo.git",
+            Some("https://raw.githubusercontent.com/rust-lang/cargo/HEAD/SECURITY.md")
+        ),
+        case(
+            "https://github.com/rust-lang/cargo/tree/master/crates/cargo-util",
+            Some("https://raw.githubusercontent.com/rust-lang/cargo/HEAD/SECURITY.md")
+        ),
+        case(
+            "https://gitlab.com/group/project/-/tree/main",
+            Some("https://gitlab.com/group/project/-/raw/HEAD/SECURITY.md")
+        ),
+        case("https://example.com/project", None),
+        case("not a url", None)
+    )]
+    fn security_policy_urls_test(
+        input_repository: &str,
+        expected_first_url: Option<&str>,
+    ) {
+        let security_policy_urls = security_policy_urls(input_repository);
+        assert_eq!(
+            security_policy_urls
+                .as_ref()
+                .map(|urls| urls.first().unwrap().as_str()),
+            expected_first_url
+        );
+        if let Some(security_policy_urls) = security_policy_urls {
+            assert_eq!(security_policy_urls.len(), SECURITY_POLICY_PATHS.len());
+        }
+    }
+
+    #[rstest]
+    fn ships_security_policy_test() {
+        let package_root = tempfile::tempdir().unwrap();
+        assert!(!ships_security_policy(package_root.path()));
+
+        fs::create_dir(package_root.path().join(".github")).unwrap();
+        fs::write(package_root.path().join(".github/SECURITY.md"), "").unwrap();
+        assert!(ships_security_policy(package_root.path()));
+    }
+}
@@ -0,0 +1,218 @@
+use crate::mapping::{CargoMetadataParameters, ToCargoGeigerPackageId};
+
+use cargo_geiger_serde::SourceKind;
+use cargo_metadata::{Metadata, PackageId};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The file `cargo vendor` writes next to the manifest of each crate it
+/// copies, which the crates extracted from a registry don't have
+const VENDORED_CHECKSUM_FILE: &str = ".cargo-checksum.json";
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct SourceFilterParseError {
+    pub source_kind: String,
+}
+
+impl Error for SourceFilterParseError {}
+
+impl fmt::Display for SourceFilterParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse source kind `{}`, expected `crates.io`, \
+            `registry`, `git`, `path` or `vendored`",
+            self.source_kind
+        )
+    }
+}
+
+/// The kinds of source given with `--only-source`, such as `git,path`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourceFilter {
+    source_kinds: Vec<SourceKind>,
+}
+
+impl SourceFilter {
+    pub fn matches(&self, source_kind: SourceKind) -> bool {
+        self.source_kinds.contains(&source_kind)
+    }
+}
+
+impl FromStr for SourceFilter {
+    type Err = SourceFilterParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let source_kinds = s
+            .split(',')
+            .map(str::trim)
+            .map(parse_source_kind)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(SourceFilter { source_kinds })
+    }
+}
+
+/// The field of `--group-by`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GroupBy {
+    /// The kind of source of each crate
+    Source,
+}
+
+impl FromStr for GroupBy {
+    type Err = GroupByParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "source" => Ok(GroupBy::Source),
+            _ => Err(GroupByParseError {
+                group_by: s.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct GroupByParseError {
+    pub group_by: String,
+}
+
+impl Error for GroupByParseError {}
+
+impl fmt::Display for GroupByParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse `--group-by {}`, expected `source`",
+            self.group_by
+        )
+    }
+}
+
+/// Where the crate comes from. A crate of a registry is vendored if it was
+/// copied by `cargo vendor`, since cargo reports it as coming from the
+/// registry it replaces.
+pub fn source_kind(
+    metadata: &Metadata,
+    package_id: &cargo_geiger_serde::PackageId,
+) -> SourceKind {
+    let source_kind = package_id.source.kind();
+    if !matches!(source_kind, SourceKind::CratesIo | SourceKind::Registry) {
+        return source_kind;
+    }
+    let is_vendored = metadata
+        .packages
+        .iter()
+        .filter(|package| {
+            package.name == package_id.name
+                && package.version == package_id.version
+                && package.source.is_some()
+        })
+        .any(|package| {
+            package.manifest_path.parent().is_some_and(|package_root| {
+                package_root.join(VENDORED_CHECKSUM_FILE).is_file()
+            })
+        });
+    if is_vendored {
+        SourceKind::Vendored
+    } else {
+        source_kind
+    }
+}
+
+/// Check whether the package should be part of the report, with
+/// `--only-source`
+pub fn package_matches_source_filter(
+    cargo_metadata_parameters: &CargoMetadataParameters,
+    source_filter: &Option<SourceFilter>,
+    package_id: &PackageId,
+) -> bool {
+    let source_filter = match source_filter {
+        Some(source_filter) => source_filter,
+        None => return true,
+    };
+    let metadata = cargo_metadata_parameters.metadata;
+    match package_id.to_cargo_geiger_package_id(metadata) {
+        Some(package_id) => {
+            source_filter.matches(source_kind(metadata, &package_id))
+        }
+        None => true,
+    }
+}
+
+fn parse_source_kind(s: &str) -> Result<SourceKind, SourceFilterParseError> {
+    match s {
+        "crates.io" | "crates-io" => Ok(SourceKind::CratesIo),
+        "registry" => Ok(SourceKind::Registry),
+        "git" => Ok(SourceKind::Git),
+        "path" => Ok(SourceKind::Path),
+        "vendored" => Ok(SourceKind::Vendored),
+        _ => Err(SourceFilterParseError {
+            source_kind: s.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod source_kind_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest(
+        input_source_filter,
+        expected_source_filter,
+        case(
+            "git,path",
+            Ok(SourceFilter {
+                source_kinds: vec![SourceKind::Git, SourceKind::Path]
+            })
+        ),
+        case(
+            "crates-io, vendored",
+            Ok(SourceFilter {
+                source_kinds: vec![SourceKind::CratesIo, SourceKind::Vendored]
+            })
+        ),
+        case(
+            "git,svn",
+            Err(SourceFilterParseError {
+                source_kind: String::from("svn")
+            })
+        )
+    )]
+    fn source_filter_from_str_test(
+        input_source_filter: &str,
+        expected_source_filter: Result<SourceFilter, SourceFilterParseError>,
+    ) {
+        assert_eq!(
+            SourceFilter::from_str(input_source_filter),
+            expected_source_filter
+        );
+    }
+
+    #[rstest]
+    fn source_filter_matches_test() {
+        let source_filter = SourceFilter::from_str("git,path").unwrap();
+
+        assert!(source_filter.matches(SourceKind::Git));
+        assert!(!source_filter.matches(SourceKind::CratesIo));
+    }
+
+    #[rstest(
+        input_group_by,
+        expected_group_by,
+        case("source", Ok(GroupBy::Source)),
+        case(
+            "license",
+            Err(GroupByParseError { group_by: String::from("license") })
+        )
+    )]
+    fn group_by_from_str_test(
+        input_group_by: &str,
+        expected_group_by: Result<GroupBy, GroupByParseError>,
+    ) {
+        assert_eq!(GroupBy::from_str(input_group_by), expected_group_by);
+    }
+}
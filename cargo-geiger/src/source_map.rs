@@ -0,0 +1,289 @@
+use crate::mapping::GetPackageRoot;
+
+use cargo::CliError;
+use cargo_metadata::{Metadata, MetadataCommand, Package};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// A `--source-map` entry, naming a crate and the local directory to scan in
+/// place of its source
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourceMapEntry {
+    pub crate_name: String,
+    pub path: PathBuf,
+}
+
+impl FromStr for SourceMapEntry {
+    type Err = SourceMapEntryParseError;
+
+    /// Parse `<name>=<path>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((crate_name, path))
+                if !crate_name.trim().is_empty() && !path.is_empty() =>
+            {
+                Ok(SourceMapEntry {
+                    crate_name: crate_name.trim().to_string(),
+                    path: PathBuf::from(path),
+                })
+            }
+            _ => Err(SourceMapEntryParseError {
+                entry: s.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct SourceMapEntryParseError {
+    pub entry: String,
+}
+
+impl Error for SourceMapEntryParseError {}
+
+impl fmt::Display for SourceMapEntryParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse source map entry `{}`, expected `<name>=<path>`",
+            self.entry
+        )
+    }
+}
+
+/// A crate whose source files are scanned from a local directory, in place
+/// of the directory cargo resolved the crate to
+#[derive(Debug)]
+struct SourceReplacement {
+    /// The crate found in the local directory
+    local_package: Package,
+    local_root: PathBuf,
+    /// The directories cargo resolved the crate to, one for each version of
+    /// the crate in the dependency graph
+    original_roots: Vec<PathBuf>,
+}
+
+/// The crates given with `--source-map`, keyed by name
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    source_replacements: HashMap<String, SourceReplacement>,
+}
+
+impl SourceMap {
+    /// The crate to scan in place of the crate with the given name, if it is
+    /// mapped to a local directory
+    pub fn local_package(&self, crate_name: &str) -> Option<&Package> {
+        self.source_replacements
+            .get(crate_name)
+            .map(|source_replacement| &source_replacement.local_package)
+    }
+
+    /// The build still compiles the original source of a mapped crate, so
+    /// the files it uses are moved to the same relative path below the local
+    /// directory.
+    pub fn translate_rs_files_used(
+        &self,
+        rs_files_used: HashSet<PathBuf>,
+    ) -> HashSet<PathBuf> {
+        rs_files_used
+            .into_iter()
+            .map(|rs_file_used| {
+                self.source_replacements
+                    .values()
+                    .find_map(|source_replacement| {
+                        source_replacement.original_roots.iter().find_map(
+                            |original_root| {
+                                let relative_path = rs_file_used
+                                    .strip_prefix(original_root)
+                                    .ok()?;
+                                Some(
+                                    source_replacement
+                                        .local_root
+                                        .join(relative_path),
+                                )
+                            },
+                        )
+                    })
+                    .unwrap_or(rs_file_used)
+            })
+            .collect()
+    }
+}
+
+/// Check that each `--source-map` entry names a crate in the dependency graph
+/// and a directory with a valid crate of the same name, and collect them into
+/// a `SourceMap`
+pub fn resolve_source_map(
+    source_map_entries: &[SourceMapEntry],
+    metadata: &Metadata,
+) -> Result<SourceMap, CliError> {
+    let mut source_map = SourceMap::default();
+    for source_map_entry in source_map_entries {
+        let original_roots = metadata
+            .packages
+            .iter()
+            .filter(|package| package.name == source_map_entry.crate_name)
+            .filter_map(|package| package.clone().get_root())
+            .map(|root| canonicalize(&root))
+            .collect::<Result<Vec<PathBuf>, CliError>>()?;
+        if original_roots.is_empty() {
+            eprintln!(
+                "Crate in source map is not in the dependency graph: {}",
+                source_map_entry.crate_name
+            );
+            return Err(CliError::code(1));
+        }
+
+        let local_package = read_local_package(source_map_entry)?;
+        let local_root = match local_package.clone().get_root() {
+            Some(local_root) => canonicalize(&local_root)?,
+            None => return Err(CliError::code(1)),
+        };
+        source_map.source_replacements.insert(
+            source_map_entry.crate_name.clone(),
+            SourceReplacement {
+                local_package,
+                local_root,
+                original_roots,
+            },
+        );
+    }
+    Ok(source_map)
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, CliError> {
+    path.canonicalize().map_err(|e| {
+        eprintln!("Failed to resolve path: {}", path.display());
+        CliError::new(anyhow::Error::from(e), 1)
+    })
+}
+
+/// The crate with the name of the entry in the manifest at the root of its
+/// directory. The manifest may also be the root of a workspace with the crate
+/// as one of its members.
+fn read_local_package(
+    source_map_entry: &SourceMapEntry,
+) -> Result<Package, CliError> {
+    let manifest_path = source_map_entry.path.join("Cargo.toml");
+    if !manifest_path.is_file() {
+        eprintln!(
+            "No Cargo.toml found in source map directory: {}",
+            source_map_entry.path.display()
+        );
+        return Err(CliError::code(1));
+    }
+
+    let local_metadata = MetadataCommand::new()
+        .manifest_path(&manifest_path)
+        .no_deps()
+        .exec()
+        .map_err(|e| {
+            eprintln!(
+                "Failed to read crate in source map directory: {}",
+                source_map_entry.path.display()
+            );
+            CliError::new(anyhow::Error::from(e), 1)
+        })?;
+
+    match local_metadata
+        .packages
+        .into_iter()
+        .find(|package| package.name == source_map_entry.crate_name)
+    {
+        Some(local_package) => Ok(local_package),
+        None => {
+            eprintln!(
+                "No crate named {} found in source map directory: {}",
+                source_map_entry.crate_name,
+                source_map_entry.path.display()
+            );
+            Err(CliError::code(1))
+        }
+    }
+}
+
+#[cfg(test)]
+mod source_map_tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest(
+        input_entry,
+        expected_entry,
+        case(
+            "foo=../foo",
+            Ok(SourceMapEntry {
+                crate_name: String::from("foo"),
+                path: PathBuf::from("../foo"),
+            })
+        ),
+        case(
+            "foo=/forks/a=b",
+            Ok(SourceMapEntry {
+                crate_name: String::from("foo"),
+                path: PathBuf::from("/forks/a=b"),
+            })
+        ),
+        case(
+            "foo",
+            Err(SourceMapEntryParseError {
+                entry: String::from("foo"),
+            })
+        ),
+        case(
+            "=../foo",
+            Err(SourceMapEntryParseError {
+                entry: String::from("=../foo"),
+            })
+        )
+    )]
+    fn source_map_entry_from_str_test(
+        input_entry: &str,
+        expected_entry: Result<SourceMapEntry, SourceMapEntryParseError>,
+    ) {
+        assert_eq!(SourceMapEntry::from_str(input_entry), expected_entry);
+    }
+
+    #[rstest]
+    fn translate_rs_files_used_test() {
+        let mut source_map = SourceMap::default();
+        source_map.source_replacements.insert(
+            String::from("foo"),
+            SourceReplacement {
+                local_package: serde_json::from_value(serde_json::json!({
+                    "name": "foo",
+                    "version": "0.1.0",
+                    "id": "foo 0.1.0 (path+file:///forks/foo)",
+                    "dependencies": [],
+                    "targets": [],
+                    "features": {},
+                    "manifest_path": "/forks/foo/Cargo.toml"
+                }))
+                .unwrap(),
+                local_root: PathBuf::from("/forks/foo"),
+                original_roots: vec![PathBuf::from("/registry/foo-0.1.0")],
+            },
+        );
+
+        let rs_files_used = vec![
+            PathBuf::from("/registry/foo-0.1.0/src/lib.rs"),
+            PathBuf::from("/registry/bar-0.2.0/src/lib.rs"),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            source_map.translate_rs_files_used(rs_files_used),
+            vec![
+                PathBuf::from("/forks/foo/src/lib.rs"),
+                PathBuf::from("/registry/bar-0.2.0/src/lib.rs"),
+            ]
+            .into_iter()
+            .collect::<HashSet<PathBuf>>()
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+const ESCAPE: u8 = 0x1B;
+
+/// Where the writer is within an ANSI escape sequence, kept between writes so
+/// that a sequence split across two writes is still removed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum EscapeState {
+    /// Outside of an escape sequence
+    Text,
+    /// After the escape character
+    Escape,
+    /// Within the parameters of a control sequence, `ESC [ ... <final byte>`
+    ControlSequence,
+}
+
+/// Duplicates everything written to the terminal into a `--tee` file. The
+/// terminal receives the output unchanged, colors included, while ANSI escape
+/// sequences and carriage returns are stripped from the file copy.
+pub struct TeeWriter<W: Write> {
+    escape_state: EscapeState,
+    file: File,
+    terminal: W,
+}
+
+impl<W: Write> TeeWriter<W> {
+    pub fn new(tee_path: &Path, terminal: W) -> io::Result<Self> {
+        Ok(TeeWriter {
+            escape_state: EscapeState::Text,
+            file: File::create(tee_path)?,
+            terminal,
+        })
+    }
+
+    fn strip_escape_sequences(&mut self, buf: &[u8]) -> Vec<u8> {
+        let mut stripped = Vec::with_capacity(buf.len());
+        for byte in buf {
+            self.escape_state = match (self.escape_state, *byte) {
+                (EscapeState::Text, ESCAPE) => EscapeState::Escape,
+                (EscapeState::Text, b'\r') => EscapeState::Text,
+                (EscapeState::Text, byte) => {
+                    stripped.push(byte);
+                    EscapeState::Text
+                }
+                (EscapeState::Escape, b'[') => EscapeState::ControlSequence,
+                // Other escape sequences used by terminals are two bytes long.
+                (EscapeState::Escape, _) => EscapeState::Text,
+                (EscapeState::ControlSequence, 0x40..=0x7E) => {
+                    EscapeState::Text
+                }
+                (EscapeState::ControlSequence, _) => {
+                    EscapeState::ControlSequence
+                }
+            };
+        }
+        stripped
+    }
+}
+
+impl<W: Write> Write for TeeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.terminal.write_all(buf)?;
+        let stripped = self.strip_escape_sequences(buf);
+        self.file.write_all(&stripped)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.terminal.flush()?;
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tee_tests {
+    use super::*;
+
+    use rstest::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[rstest(
+        input_writes,
+        expected_file_content,
+        case(vec!["plain\n"], "plain\n"),
+        case(vec!["\x1B[1;31mred\x1B[0m\n"], "red\n"),
+        case(vec!["\x1B[1;3", "1mred\x1B", "[0m\n"], "red\n"),
+        case(vec!["    ☢️\r\x1B[7C = unsafe\n"], "    ☢️ = unsafe\n")
+    )]
+    fn tee_writer_test(input_writes: Vec<&str>, expected_file_content: &str) {
+        let tee_directory = tempdir().unwrap();
+        let tee_path = tee_directory.path().join("report.txt");
+        let mut terminal = vec![];
+
+        {
+            let mut tee_writer =
+                TeeWriter::new(&tee_path, &mut terminal).unwrap();
+            for write in &input_writes {
+                tee_writer.write_all(write.as_bytes()).unwrap();
+            }
+            tee_writer.flush().unwrap();
+        }
+
+        assert_eq!(String::from_utf8(terminal).unwrap(), input_writes.concat());
+        assert_eq!(
+            fs::read_to_string(&tee_path).unwrap(),
+            expected_file_content
+        );
+    }
+}
@@ -1,16 +1,40 @@
 pub mod traversal;
 
-use crate::format::print_config::{OutputFormat, Prefix, PrintConfig};
+use crate::format::print_config::{CrateSort, Prefix, PrintConfig};
+use crate::format::Charset;
+use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::fmt::Write as _;
 
+use cargo_geiger_serde::{CounterBlock, UnsafeInfo};
 use cargo_metadata::{DependencyKind, PackageId};
 
+/// Whether the crate is printed, which with `--workspace-only` is only the
+/// case for the workspace members and the root of the tree, such as the crate
+/// given to `--invert`
+pub fn is_printed_with_workspace_only(
+    workspace_only: bool,
+    workspace_members: &[PackageId],
+    root_package_id: &PackageId,
+    package_id: &PackageId,
+) -> bool {
+    !workspace_only
+        || package_id == root_package_id
+        || workspace_members.contains(package_id)
+}
+
 /// A step towards decoupling some parts of the table-tree printing from the
 /// dependency graph traversal.
 #[derive(Debug, Eq, PartialEq)]
 pub enum TextTreeLine {
     /// A text line for a package
-    Package { id: PackageId, tree_vines: String },
+    Package {
+        id: PackageId,
+        /// The crates below the package which are left out of the tree by
+        /// `--max-depth`, counted in the row of the package
+        pruned_package_ids: Vec<PackageId>,
+        tree_vines: String,
+    },
     /// There are extra dependencies coming and we should print a group header,
     /// eg. "[build-dependencies]".
     ExtraDepsGroup {
@@ -31,10 +55,11 @@ fn construct_tree_vines_string(
     levels_continue: &mut [bool],
     print_config: &PrintConfig,
 ) -> String {
-    let tree_symbols = get_tree_symbols(print_config.output_format);
+    let tree_symbols = get_tree_symbols(print_config.charset);
 
     match print_config.prefix {
         Prefix::Depth => format!("{} ", levels_continue.len()),
+        Prefix::DepthColumn => format!("{: >3} | ", levels_continue.len()),
         Prefix::Indent => {
             let mut buffer = String::new();
             if let Some((&last_continues, rest)) = levels_continue.split_last()
@@ -56,10 +81,10 @@ fn construct_tree_vines_string(
     }
 }
 
-pub fn get_tree_symbols(output_format: OutputFormat) -> TreeSymbols {
-    match output_format {
-        OutputFormat::Ascii => ASCII_TREE_SYMBOLS,
-        _ => UTF8_TREE_SYMBOLS,
+pub fn get_tree_symbols(charset: Charset) -> TreeSymbols {
+    match charset {
+        Charset::Ascii => ASCII_TREE_SYMBOLS,
+        Charset::Utf8 => UTF8_TREE_SYMBOLS,
     }
 }
 
@@ -77,21 +102,152 @@ const UTF8_TREE_SYMBOLS: TreeSymbols = TreeSymbols {
     right: "─",
 };
 
+/// Flatten the tree into a line for each crate, in the order of `crate_sort`
+/// rather than of the traversal. The `sort_key` of a crate is its total used
+/// unsafe code and its name, which breaks ties.
+///
+/// A crate depended upon in several places is listed once, and the group
+/// headers of the extra dependencies are left out, since the crates no longer
+/// follow them. Without `keep_tree_vines` the crates are printed without a
+/// prefix, as the indentation of the tree would no longer line up.
+pub fn sort_text_tree_lines(
+    crate_sort: CrateSort,
+    keep_tree_vines: bool,
+    sort_key: impl Fn(&PackageId) -> (u64, String),
+    text_tree_lines: Vec<TextTreeLine>,
+) -> Vec<TextTreeLine> {
+    let mut visited_package_ids = HashSet::new();
+    let mut keyed_lines = text_tree_lines
+        .into_iter()
+        .filter_map(|text_tree_line| match text_tree_line {
+            TextTreeLine::Package {
+                id,
+                pruned_package_ids,
+                tree_vines,
+            } => visited_package_ids.insert(id.clone()).then(|| {
+                let tree_vines = match keep_tree_vines {
+                    true => tree_vines,
+                    false => String::new(),
+                };
+                (
+                    sort_key(&id),
+                    TextTreeLine::Package {
+                        id,
+                        pruned_package_ids,
+                        tree_vines,
+                    },
+                )
+            }),
+            TextTreeLine::ExtraDepsGroup { .. } => None,
+        })
+        .collect::<Vec<_>>();
+
+    keyed_lines.sort_by(|((a_unsafe, a_name), _), ((b_unsafe, b_name), _)| {
+        let ordering = match crate_sort {
+            CrateSort::Unsafe => a_unsafe.cmp(b_unsafe),
+            CrateSort::UnsafeDesc => b_unsafe.cmp(a_unsafe),
+            CrateSort::Name
+            | CrateSort::UnsafeCount
+            | CrateSort::UnsafeRatio
+            | CrateSort::TotalUnsafe
+            | CrateSort::Ffi => Ordering::Equal,
+        };
+        ordering.then_with(|| a_name.cmp(b_name))
+    });
+    keyed_lines.into_iter().map(|(_, line)| line).collect()
+}
+
+/// The order of two siblings of the tree with `crate_sort`, the most unsafe
+/// first, from their unsafe code. A crate without any code used by the build
+/// has a ratio of 0. The orders which flatten the tree compare all the
+/// crates equal.
+pub fn compare_siblings(
+    crate_sort: CrateSort,
+    a: &UnsafeInfo,
+    b: &UnsafeInfo,
+) -> Ordering {
+    match crate_sort {
+        CrateSort::UnsafeCount => {
+            b.used.total_unsafe().cmp(&a.used.total_unsafe())
+        }
+        CrateSort::UnsafeRatio => {
+            let (a_unsafe, a_total) = unsafe_ratio(&a.used);
+            let (b_unsafe, b_total) = unsafe_ratio(&b.used);
+            (b_unsafe * a_total).cmp(&(a_unsafe * b_total))
+        }
+        CrateSort::TotalUnsafe => (b.used.total_unsafe()
+            + b.unused.total_unsafe())
+        .cmp(&(a.used.total_unsafe() + a.unused.total_unsafe())),
+        CrateSort::Ffi => b.ffi.used.cmp(&a.ffi.used),
+        CrateSort::Name | CrateSort::Unsafe | CrateSort::UnsafeDesc => {
+            Ordering::Equal
+        }
+    }
+}
+
+/// The unsafe code and all of the code of the counter block, as a fraction
+/// which is compared by cross multiplication
+fn unsafe_ratio(counter_block: &CounterBlock) -> (u128, u128) {
+    let safe_count = counter_block.functions.safe
+        + counter_block.exprs.safe
+        + counter_block.item_impls.safe
+        + counter_block.item_traits.safe
+        + counter_block.methods.safe;
+    let unsafe_count = counter_block.total_unsafe();
+    match safe_count + unsafe_count {
+        0 => (0, 1),
+        total_count => (u128::from(unsafe_count), u128::from(total_count)),
+    }
+}
+
 #[cfg(test)]
 mod tree_tests {
     use super::*;
 
     use crate::format::pattern::Pattern;
-    use crate::format::print_config::OutputFormat;
+    use crate::format::print_config::{
+        IncludeBuildDeps, OutputFormat, ScanCacheMode,
+    };
+    use crate::format::Symbols;
 
     use geiger::IncludeTests;
     use petgraph::EdgeDirection;
     use rstest::*;
 
+    #[rstest(
+        input_workspace_only,
+        input_package_id,
+        expected_is_printed,
+        case(false, "dependency", true),
+        case(true, "dependency", false),
+        case(true, "member", true),
+        case(true, "root", true)
+    )]
+    fn is_printed_with_workspace_only_test(
+        input_workspace_only: bool,
+        input_package_id: &str,
+        expected_is_printed: bool,
+    ) {
+        let package_id = |repr: &str| PackageId {
+            repr: String::from(repr),
+        };
+
+        assert_eq!(
+            is_printed_with_workspace_only(
+                input_workspace_only,
+                &[package_id("member")],
+                &package_id("root"),
+                &package_id(input_package_id)
+            ),
+            expected_is_printed
+        );
+    }
+
     #[rstest(
         input_prefix,
         expected_tree_vines_string,
         case(Prefix::Depth, "3 "),
+        case(Prefix::DepthColumn, "  3 | "),
         case(Prefix::Indent, "|       |-- "),
         case(Prefix::None, "")
     )]
@@ -109,20 +265,153 @@ mod tree_tests {
     }
 
     #[rstest(
-        input_output_format,
+        input_crate_sort,
+        expected_package_names,
+        case(CrateSort::Name, vec!["bar", "baz", "foo"]),
+        case(CrateSort::Unsafe, vec!["bar", "baz", "foo"]),
+        case(CrateSort::UnsafeDesc, vec!["foo", "bar", "baz"])
+    )]
+    fn sort_text_tree_lines_test(
+        input_crate_sort: CrateSort,
+        expected_package_names: Vec<&str>,
+    ) {
+        let package_line =
+            |name: &str, tree_vines: &str| TextTreeLine::Package {
+                id: PackageId {
+                    repr: String::from(name),
+                },
+                pruned_package_ids: vec![],
+                tree_vines: String::from(tree_vines),
+            };
+        let text_tree_lines = vec![
+            package_line("foo", "0 "),
+            package_line("bar", "1 "),
+            TextTreeLine::ExtraDepsGroup {
+                kind: DependencyKind::Build,
+                tree_vines: String::from("1 "),
+            },
+            package_line("baz", "1 "),
+            package_line("bar", "2 "),
+        ];
+        let sort_key = |package_id: &PackageId| {
+            let total_unsafe = match package_id.repr.as_str() {
+                "foo" => 5,
+                _ => 2,
+            };
+            (total_unsafe, package_id.repr.clone())
+        };
+
+        let sorted_text_tree_lines = sort_text_tree_lines(
+            input_crate_sort,
+            false,
+            sort_key,
+            text_tree_lines,
+        );
+
+        assert_eq!(
+            sorted_text_tree_lines,
+            expected_package_names
+                .into_iter()
+                .map(|name| package_line(name, ""))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest(
+        input_crate_sort,
+        expected_names,
+        case(CrateSort::UnsafeCount, vec!["b", "a", "c"]),
+        case(CrateSort::UnsafeRatio, vec!["a", "b", "c"]),
+        case(CrateSort::TotalUnsafe, vec!["a", "c", "b"]),
+        case(CrateSort::Ffi, vec!["c", "a", "b"]),
+        case(CrateSort::Name, vec!["a", "b", "c"])
+    )]
+    fn compare_siblings_test(
+        input_crate_sort: CrateSort,
+        expected_names: Vec<&str>,
+    ) {
+        let unsafe_info = |used_safe, used_unsafe, unused_unsafe| {
+            let mut unsafe_info = UnsafeInfo::default();
+            unsafe_info.used.exprs.safe = used_safe;
+            unsafe_info.used.exprs.unsafe_ = used_unsafe;
+            unsafe_info.unused.exprs.unsafe_ = unused_unsafe;
+            unsafe_info
+        };
+        let mut c_unsafe_info = unsafe_info(0, 0, 4);
+        c_unsafe_info.ffi.used = 2;
+        let mut siblings = [
+            ("a", unsafe_info(1, 1, 5)),
+            ("b", unsafe_info(9, 3, 0)),
+            ("c", c_unsafe_info),
+        ];
+
+        siblings
+            .sort_by(|(_, a), (_, b)| compare_siblings(input_crate_sort, a, b));
+
+        assert_eq!(
+            siblings.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            expected_names
+        );
+    }
+
+    #[rstest]
+    fn sort_text_tree_lines_test_keep_tree_vines() {
+        let text_tree_lines = vec![
+            TextTreeLine::Package {
+                id: PackageId {
+                    repr: String::from("foo"),
+                },
+                pruned_package_ids: vec![],
+                tree_vines: String::from("0 "),
+            },
+            TextTreeLine::Package {
+                id: PackageId {
+                    repr: String::from("bar"),
+                },
+                pruned_package_ids: vec![],
+                tree_vines: String::from("1 "),
+            },
+        ];
+
+        let sorted_text_tree_lines = sort_text_tree_lines(
+            CrateSort::Name,
+            true,
+            |package_id| (0, package_id.repr.clone()),
+            text_tree_lines,
+        );
+
+        assert_eq!(
+            sorted_text_tree_lines,
+            vec![
+                TextTreeLine::Package {
+                    id: PackageId {
+                        repr: String::from("bar"),
+                    },
+                    pruned_package_ids: vec![],
+                    tree_vines: String::from("1 "),
+                },
+                TextTreeLine::Package {
+                    id: PackageId {
+                        repr: String::from("foo"),
+                    },
+                    pruned_package_ids: vec![],
+                    tree_vines: String::from("0 "),
+                },
+            ]
+        );
+    }
+
+    #[rstest(
+        input_charset,
         expected_tree_symbols,
-        case(OutputFormat::Ascii, ASCII_TREE_SYMBOLS),
-        case(OutputFormat::GitHubMarkdown, UTF8_TREE_SYMBOLS),
-        case(OutputFormat::Utf8, UTF8_TREE_SYMBOLS)
+        case(Charset::Ascii, ASCII_TREE_SYMBOLS),
+        case(Charset::Utf8, UTF8_TREE_SYMBOLS)
     )]
     fn get_tree_symbols_test(
-        input_output_format: OutputFormat,
+        input_charset: Charset,
         expected_tree_symbols: TreeSymbols,
     ) {
-        assert_eq!(
-            get_tree_symbols(input_output_format),
-            expected_tree_symbols
-        );
+        assert_eq!(get_tree_symbols(input_charset), expected_tree_symbols);
     }
 
     fn construct_print_config(prefix: Prefix) -> PrintConfig {
@@ -130,11 +419,35 @@ mod tree_tests {
         PrintConfig {
             all: false,
             direction: EdgeDirection::Outgoing,
+            ffi: false,
             prefix,
             format: pattern,
+            hide_zeros: false,
+            ignore_list: Default::default(),
             allow_partial_results: false,
+            allowlist: None,
+            charset: Charset::Ascii,
+            color: Default::default(),
+            color_scheme: Default::default(),
+            detectors: vec![],
+            include_build_deps: IncludeBuildDeps::Yes,
             include_tests: IncludeTests::Yes,
+            jobs: None,
+            license_filter: None,
+            lossy: false,
+            max_depth: None,
+            min_ffi: 0,
+            min_unsafe: 0,
+            only_unsafe: false,
+            output_file: None,
             output_format: OutputFormat::Ascii,
+            scan_cache_mode: ScanCacheMode::Disabled,
+            cache_dir: None,
+            caches_path_packages: false,
+            sort: None,
+            source_filter: None,
+            symbols: Symbols::Ascii,
+            workspace_only: false,
         }
     }
 }
@@ -11,12 +11,18 @@ use dependency_kind::walk_dependency_kind;
 use dependency_node::walk_dependency_node;
 
 use cargo_metadata::PackageId;
+use std::cmp::Ordering;
 use std::collections::HashSet;
 
+/// The order of the dependencies of each crate in the tree, which are
+/// otherwise ordered by package id
+pub type SiblingOrder<'a> = &'a dyn Fn(&PackageId, &PackageId) -> Ordering;
+
 pub struct WalkDependencyParameters<'a> {
     pub graph: &'a Graph,
     pub levels_continue: &'a mut Vec<bool>,
     pub print_config: &'a PrintConfig,
+    pub sibling_order: Option<SiblingOrder<'a>>,
     pub visited_deps: &'a mut HashSet<PackageId>,
 }
 
@@ -31,6 +37,7 @@ pub fn walk_dependency_tree(
     graph: &Graph,
     print_config: &PrintConfig,
     root_package_id: PackageId,
+    sibling_order: Option<SiblingOrder>,
 ) -> Vec<TextTreeLine> {
     let mut visited_deps = HashSet::new();
     let mut levels_continue = vec![];
@@ -39,6 +46,7 @@ pub fn walk_dependency_tree(
         graph,
         levels_continue: &mut levels_continue,
         print_config,
+        sibling_order,
         visited_deps: &mut visited_deps,
     };
 
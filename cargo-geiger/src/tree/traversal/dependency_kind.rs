@@ -22,9 +22,13 @@ pub fn walk_dependency_kind(
 
     // Resolve uses Hash data types internally but we want consistent output ordering
     deps.sort_by_key(|n| n.clone());
+    if let Some(sibling_order) = walk_dependency_parameters.sibling_order {
+        // The sort is stable, so equal siblings stay ordered by package id.
+        deps.sort_by(|a, b| sibling_order(a, b));
+    }
 
     let tree_symbols =
-        get_tree_symbols(walk_dependency_parameters.print_config.output_format);
+        get_tree_symbols(walk_dependency_parameters.print_config.charset);
     let mut text_tree_lines = Vec::new();
     if let Prefix::Indent = walk_dependency_parameters.print_config.prefix {
         push_extra_deps_group_text_tree_line_for_non_normal_dependencies(
@@ -92,7 +96,7 @@ fn push_extra_deps_group_text_tree_line_for_non_normal_dependencies(
 mod traversal_tests {
     use super::*;
 
-    use crate::format::print_config::OutputFormat;
+    use crate::format::Charset;
     use crate::tree::TextTreeLine::ExtraDepsGroup;
 
     use rstest::*;
@@ -119,7 +123,7 @@ mod traversal_tests {
                     kind: DependencyKind::Build,
                     tree_vines: format!(
                     "    {}   ",
-                    get_tree_symbols(OutputFormat::Utf8).down
+                    get_tree_symbols(Charset::Utf8).down
                     )
                 }
             ]
@@ -132,7 +136,7 @@ mod traversal_tests {
                     kind: DependencyKind::Development,
                     tree_vines: format!(
                     "{}   ",
-                    get_tree_symbols(OutputFormat::Utf8).down
+                    get_tree_symbols(Charset::Utf8).down
                     )
                 }
             ]
@@ -159,7 +163,7 @@ mod traversal_tests {
         expected_text_tree_lines: Vec<TextTreeLine>,
     ) {
         let mut text_tree_lines: Vec<TextTreeLine> = vec![];
-        let tree_symbols = get_tree_symbols(OutputFormat::Utf8);
+        let tree_symbols = get_tree_symbols(Charset::Utf8);
 
         push_extra_deps_group_text_tree_line_for_non_normal_dependencies(
             input_dep_kind,
@@ -10,7 +10,7 @@ use super::walk_dependency_kind;
 use cargo_metadata::{DependencyKind, PackageId};
 use petgraph::visit::EdgeRef;
 use petgraph::EdgeDirection;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub fn walk_dependency_node(
     cargo_metadata_parameters: &CargoMetadataParameters,
@@ -21,6 +21,22 @@ pub fn walk_dependency_node(
         || walk_dependency_parameters
             .visited_deps
             .insert(package.clone());
+    // The depth is counted from the root of the tree, which is the crate
+    // given to `--invert` when the tree is inverted.
+    let is_at_max_depth = walk_dependency_parameters
+        .print_config
+        .max_depth
+        .is_some_and(|max_depth| {
+            walk_dependency_parameters.levels_continue.len() >= max_depth
+        });
+    let pruned_package_ids = match new && is_at_max_depth {
+        true => collect_pruned_package_ids(
+            walk_dependency_parameters.graph,
+            package,
+            walk_dependency_parameters.print_config,
+        ),
+        false => vec![],
+    };
     let tree_vines = construct_tree_vines_string(
         walk_dependency_parameters.levels_continue,
         walk_dependency_parameters.print_config,
@@ -28,10 +44,11 @@ pub fn walk_dependency_node(
 
     let mut all_out_text_tree_lines = vec![TextTreeLine::Package {
         id: package.clone(),
+        pruned_package_ids,
         tree_vines,
     }];
 
-    if !new {
+    if !new || is_at_max_depth {
         return all_out_text_tree_lines;
     }
 
@@ -55,6 +72,39 @@ pub fn walk_dependency_node(
     all_out_text_tree_lines
 }
 
+/// Every crate below `package` in the direction of the tree, each listed once,
+/// whatever its dependency kind
+fn collect_pruned_package_ids(
+    graph: &Graph,
+    package: &PackageId,
+    print_config: &PrintConfig,
+) -> Vec<PackageId> {
+    let mut visited_package_ids = HashSet::new();
+    visited_package_ids.insert(package.clone());
+    let mut pruned_package_ids = vec![];
+    let mut package_ids_to_visit = vec![package.clone()];
+
+    while let Some(package_id) = package_ids_to_visit.pop() {
+        for edge in graph
+            .graph
+            .edges_directed(graph.nodes[&package_id], print_config.direction)
+        {
+            let dependency = match print_config.direction {
+                EdgeDirection::Incoming => &graph.graph[edge.source()],
+                EdgeDirection::Outgoing => &graph.graph[edge.target()],
+            };
+            if print_config.include_build_deps.allows(*edge.weight())
+                && visited_package_ids.insert(dependency.clone())
+            {
+                pruned_package_ids.push(dependency.clone());
+                package_ids_to_visit.push(dependency.clone());
+            }
+        }
+    }
+
+    pruned_package_ids
+}
+
 fn construct_dependency_type_nodes_hashmap<'a>(
     graph: &'a Graph,
     package: &PackageId,
@@ -73,6 +123,9 @@ fn construct_dependency_type_nodes_hashmap<'a>(
         .graph
         .edges_directed(graph.nodes[package], print_config.direction)
     {
+        if !print_config.include_build_deps.allows(*edge.weight()) {
+            continue;
+        }
         let dependency = match print_config.direction {
             EdgeDirection::Incoming => &graph.graph[edge.source()],
             EdgeDirection::Outgoing => &graph.graph[edge.target()],
@@ -92,7 +145,10 @@ mod dependency_node_tests {
     use super::*;
 
     use crate::format::pattern::Pattern;
-    use crate::format::print_config::{OutputFormat, Prefix, PrintConfig};
+    use crate::format::print_config::{
+        IncludeBuildDeps, OutputFormat, Prefix, PrintConfig, ScanCacheMode,
+    };
+    use crate::format::{Charset, Symbols};
 
     use cargo_metadata::DependencyKind;
     use geiger::IncludeTests;
@@ -165,6 +221,7 @@ mod dependency_node_tests {
         let graph = Graph {
             graph: inner_graph,
             nodes,
+            features: Default::default(),
         };
 
         let dependency_type_nodes_hashmap =
@@ -188,6 +245,67 @@ mod dependency_node_tests {
         );
     }
 
+    #[rstest(
+        input_edge_direction,
+        input_root_index,
+        expected_pruned_indices,
+        case(EdgeDirection::Outgoing, 0, vec![1, 2, 3]),
+        case(EdgeDirection::Outgoing, 1, vec![3]),
+        case(EdgeDirection::Incoming, 3, vec![0, 1, 2]),
+        case(EdgeDirection::Incoming, 0, vec![])
+    )]
+    fn collect_pruned_package_ids_test(
+        input_edge_direction: EdgeDirection,
+        input_root_index: usize,
+        expected_pruned_indices: Vec<usize>,
+    ) {
+        let mut inner_graph =
+            petgraph::Graph::<PackageId, cargo_metadata::DependencyKind>::new();
+        let mut nodes = HashMap::<PackageId, NodeIndex>::new();
+
+        let package_ids = create_cargo_metadata_package_id_vec(4);
+        for package_id in &package_ids {
+            nodes.insert(
+                package_id.clone(),
+                inner_graph.add_node(package_id.clone()),
+            );
+        }
+
+        // 3 is depended upon by both 1 and 2, and is listed once.
+        add_edges_to_graph(
+            &[
+                (0, 1, DependencyKind::Normal),
+                (0, 2, DependencyKind::Build),
+                (1, 3, DependencyKind::Normal),
+                (2, 3, DependencyKind::Normal),
+            ],
+            &mut inner_graph,
+            &nodes,
+            &package_ids,
+        );
+
+        let graph = Graph {
+            graph: inner_graph,
+            nodes,
+            features: Default::default(),
+        };
+
+        let mut pruned_package_ids = collect_pruned_package_ids(
+            &graph,
+            &package_ids[input_root_index],
+            &create_print_config(input_edge_direction),
+        );
+        pruned_package_ids.sort();
+
+        assert_eq!(
+            pruned_package_ids,
+            expected_pruned_indices
+                .into_iter()
+                .map(|index| package_ids[index].clone())
+                .collect::<Vec<_>>()
+        );
+    }
+
     fn add_edges_to_graph(
         directed_edges: &[(usize, usize, DependencyKind)],
         graph: &mut petgraph::Graph<PackageId, DependencyKind>,
@@ -215,11 +333,35 @@ mod dependency_node_tests {
         PrintConfig {
             all: false,
             allow_partial_results: false,
+            allowlist: None,
+            charset: Charset::Ascii,
+            color: Default::default(),
+            color_scheme: Default::default(),
+            detectors: vec![],
             direction: edge_direction,
+            ffi: false,
             format: Pattern::new(vec![]),
+            hide_zeros: false,
+            ignore_list: Default::default(),
+            include_build_deps: IncludeBuildDeps::Yes,
             include_tests: IncludeTests::Yes,
+            jobs: None,
+            license_filter: None,
+            lossy: false,
+            max_depth: None,
+            min_ffi: 0,
+            min_unsafe: 0,
+            only_unsafe: false,
             prefix: Prefix::Depth,
+            output_file: None,
             output_format: OutputFormat::Ascii,
+            scan_cache_mode: ScanCacheMode::Disabled,
+            cache_dir: None,
+            caches_path_packages: false,
+            sort: None,
+            source_filter: None,
+            symbols: Symbols::Ascii,
+            workspace_only: false,
         }
     }
 }
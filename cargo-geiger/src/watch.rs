@@ -0,0 +1,155 @@
+use cargo_metadata::Metadata;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::{DirEntry, WalkDir};
+
+/// How often the files of the workspace members are checked for changes with
+/// `--watch`
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Directories of a workspace member which are not part of its sources
+const IGNORED_DIRECTORIES: [&str; 2] = [".git", "target"];
+
+/// The modification time of each source file and manifest of each workspace
+/// member, by the name of the member. The files are polled rather than
+/// watched, which keeps `--watch` working on every platform and file system.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct WorkspaceSnapshot {
+    member_files: BTreeMap<String, BTreeMap<PathBuf, SystemTime>>,
+}
+
+impl WorkspaceSnapshot {
+    /// The names of the members whose files were added, removed or modified
+    /// since this snapshot was taken
+    pub fn changed_members(&self, newer: &WorkspaceSnapshot) -> Vec<String> {
+        newer
+            .member_files
+            .iter()
+            .filter(|(name, files)| self.member_files.get(*name) != Some(files))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+/// Take a snapshot of the files of each workspace member. The packages in a
+/// subdirectory of a member, such as the other members of a virtual
+/// workspace, are left to their own snapshot.
+pub fn snapshot_workspace(metadata: &Metadata) -> WorkspaceSnapshot {
+    let member_files = metadata
+        .packages
+        .iter()
+        .filter(|package| metadata.workspace_members.contains(&package.id))
+        .filter_map(|package| {
+            let package_root = package.manifest_path.parent()?;
+            Some((
+                package.name.clone(),
+                snapshot_files(package_root.as_std_path()),
+            ))
+        })
+        .collect();
+    WorkspaceSnapshot { member_files }
+}
+
+fn snapshot_files(package_root: &Path) -> BTreeMap<PathBuf, SystemTime> {
+    WalkDir::new(package_root)
+        .into_iter()
+        .filter_entry(|entry| !is_ignored_entry(entry))
+        .filter_map(Result::ok)
+        .filter(is_watched_file)
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((entry.into_path(), modified))
+        })
+        .collect()
+}
+
+fn is_ignored_entry(entry: &DirEntry) -> bool {
+    entry.depth() > 0
+        && entry.file_type().is_dir()
+        && (IGNORED_DIRECTORIES
+            .contains(&entry.file_name().to_string_lossy().as_ref())
+            || entry.path().join("Cargo.toml").is_file())
+}
+
+fn is_watched_file(entry: &DirEntry) -> bool {
+    entry.file_type().is_file()
+        && (entry.file_name() == "Cargo.toml"
+            || entry
+                .path()
+                .extension()
+                .is_some_and(|extension| extension == "rs"))
+}
+
+#[cfg(test)]
+mod watch_tests {
+    use super::*;
+
+    use rstest::*;
+    use std::fs;
+
+    #[rstest]
+    fn changed_members_test() {
+        let files = |modified_secs: &[(&str, u64)]| {
+            modified_secs
+                .iter()
+                .map(|(path, secs)| {
+                    (
+                        PathBuf::from(path),
+                        SystemTime::UNIX_EPOCH + Duration::from_secs(*secs),
+                    )
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        let older = WorkspaceSnapshot {
+            member_files: vec![
+                (String::from("bar"), files(&[("bar/src/lib.rs", 1)])),
+                (String::from("baz"), files(&[("baz/src/lib.rs", 1)])),
+                (String::from("foo"), files(&[("foo/src/lib.rs", 1)])),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let newer = WorkspaceSnapshot {
+            member_files: vec![
+                (String::from("bar"), files(&[("bar/src/lib.rs", 2)])),
+                (
+                    String::from("baz"),
+                    files(&[("baz/src/lib.rs", 1), ("baz/src/io.rs", 2)]),
+                ),
+                (String::from("foo"), files(&[("foo/src/lib.rs", 1)])),
+                (String::from("qux"), files(&[("qux/src/lib.rs", 2)])),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        assert_eq!(older.changed_members(&newer), vec!["bar", "baz", "qux"]);
+        assert!(newer.changed_members(&newer).is_empty());
+    }
+
+    #[rstest]
+    fn snapshot_files_test() {
+        let package_root = tempfile::tempdir().unwrap();
+        let path =
+            |relative_path: &str| package_root.path().join(relative_path);
+        for relative_path in [
+            "Cargo.toml",
+            "README.md",
+            "src/lib.rs",
+            "target/debug/build/out.rs",
+            "member/Cargo.toml",
+            "member/src/lib.rs",
+        ] {
+            fs::create_dir_all(path(relative_path).parent().unwrap()).unwrap();
+            fs::write(path(relative_path), "").unwrap();
+        }
+
+        assert_eq!(
+            snapshot_files(package_root.path())
+                .into_keys()
+                .collect::<Vec<_>>(),
+            vec![path("Cargo.toml"), path("src/lib.rs")]
+        );
+    }
+}
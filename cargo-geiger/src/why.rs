@@ -0,0 +1,273 @@
+use crate::format::print_config::{colorize, IncludeBuildDeps, PrintConfig};
+use crate::format::CrateDetectionStatus;
+use crate::graph::Graph;
+
+use cargo_metadata::semver::Version;
+use cargo_metadata::PackageId;
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeDirection;
+
+/// The most paths printed with `--why`, as their number can grow
+/// exponentially with the depth of the graph
+pub const MAX_WHY_PATHS: usize = 100;
+
+/// A crate along a path printed with `--why`, with its unsafe code used by
+/// the build and found in total, or none if it wasn't scanned
+pub struct WhyCrate {
+    pub name: String,
+    pub version: Version,
+    pub unsafe_counts: Option<(CrateDetectionStatus, u64, u64)>,
+}
+
+/// The dependency paths from the root to the crate, each starting with the
+/// root and ending with the crate, found by walking the edges of the graph
+/// inverted from the crate, as with `--invert`. A crate is at most once in a
+/// path. At most `max_paths` are returned, with whether more were left out.
+pub fn dependency_paths(
+    graph: &Graph,
+    include_build_deps: IncludeBuildDeps,
+    root_package_id: &PackageId,
+    package_id: &PackageId,
+    max_paths: usize,
+) -> (Vec<Vec<PackageId>>, bool) {
+    let (root_index, package_index) = match (
+        graph.nodes.get(root_package_id),
+        graph.nodes.get(package_id),
+    ) {
+        (Some(root_index), Some(package_index)) => {
+            (*root_index, *package_index)
+        }
+        _ => return (vec![], false),
+    };
+
+    let mut paths = Vec::new();
+    let mut partial_paths = vec![vec![package_index]];
+    while let Some(partial_path) = partial_paths.pop() {
+        let index = *partial_path.last().unwrap();
+        if index == root_index {
+            if paths.len() == max_paths {
+                return (paths, true);
+            }
+            paths.push(
+                partial_path
+                    .iter()
+                    .rev()
+                    .map(|index| graph.graph[*index].clone())
+                    .collect(),
+            );
+            continue;
+        }
+
+        // A crate may depend on another with several kinds of dependency.
+        let mut dependent_indices = graph
+            .graph
+            .edges_directed(index, EdgeDirection::Incoming)
+            .filter(|edge| include_build_deps.allows(*edge.weight()))
+            .map(|edge| edge.source())
+            .filter(|dependent_index| !partial_path.contains(dependent_index))
+            .collect::<Vec<_>>();
+        dependent_indices.sort_by_key(|index| &graph.graph[*index]);
+        dependent_indices.dedup();
+        // Popped last, so the paths are found in the order of the crates.
+        for dependent_index in dependent_indices.into_iter().rev() {
+            let mut dependent_path = partial_path.clone();
+            dependent_path.push(dependent_index);
+            partial_paths.push(dependent_path);
+        }
+    }
+    (paths, false)
+}
+
+/// The lines printed with `--why`, a line for each path from the root to the
+/// crate, such as `foo 0.1.0 (0/0) -> bar 1.0.0 (3/5)`, colored by the status
+/// of each crate
+pub fn construct_why_lines(
+    package_name: &str,
+    package_version: &Version,
+    paths: &[Vec<WhyCrate>],
+    truncated: bool,
+    print_config: &PrintConfig,
+) -> Vec<String> {
+    let mut why_lines = vec![format!(
+        "Dependency paths from the root to {} {}, with the unsafe code used \
+        by the build/found of each crate:",
+        package_name, package_version
+    )];
+    if paths.is_empty() {
+        why_lines.push(String::from("    None"));
+    }
+    for path in paths {
+        why_lines.push(format!(
+            "    {}",
+            path.iter()
+                .map(|why_crate| format_why_crate(why_crate, print_config))
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        ));
+    }
+    if truncated {
+        why_lines.push(format!(
+            "    ... more paths are left out, only the first {} are printed",
+            paths.len()
+        ));
+    }
+    why_lines.push(String::new());
+    why_lines
+}
+
+fn format_why_crate(
+    why_crate: &WhyCrate,
+    print_config: &PrintConfig,
+) -> String {
+    match &why_crate.unsafe_counts {
+        Some((crate_detection_status, used, found)) => colorize(
+            print_config.color,
+            print_config.color_scheme,
+            crate_detection_status,
+            print_config.output_format,
+            format!(
+                "{} {} ({}/{})",
+                why_crate.name, why_crate.version, used, found
+            ),
+        )
+        .to_string(),
+        None => format!("{} {} (?)", why_crate.name, why_crate.version),
+    }
+}
+
+#[cfg(test)]
+mod why_tests {
+    use super::*;
+
+    use crate::format::print_config::ColorWhen;
+
+    use cargo_metadata::DependencyKind;
+    use rstest::*;
+    use std::collections::HashMap;
+
+    fn package_id(name: &str) -> PackageId {
+        PackageId {
+            repr: format!("{} 0.1.0 (path+file:///{})", name, name),
+        }
+    }
+
+    /// root -> a -> c, root -> b -> c with a normal and a build dependency,
+    /// and c -> d
+    fn create_graph() -> Graph {
+        let mut graph = Graph {
+            graph: Default::default(),
+            nodes: HashMap::new(),
+            features: HashMap::new(),
+        };
+        for name in ["root", "a", "b", "c", "d"] {
+            let index = graph.graph.add_node(package_id(name));
+            graph.nodes.insert(package_id(name), index);
+        }
+        for (from, to, dependency_kind) in [
+            ("root", "b", DependencyKind::Normal),
+            ("root", "a", DependencyKind::Normal),
+            ("a", "c", DependencyKind::Normal),
+            ("b", "c", DependencyKind::Normal),
+            ("b", "c", DependencyKind::Build),
+            ("c", "d", DependencyKind::Build),
+        ] {
+            graph.graph.add_edge(
+                graph.nodes[&package_id(from)],
+                graph.nodes[&package_id(to)],
+                dependency_kind,
+            );
+        }
+        graph
+    }
+
+    #[rstest(
+        input_include_build_deps,
+        input_name,
+        input_max_paths,
+        expected_paths,
+        expected_truncated,
+        case(
+            IncludeBuildDeps::Yes,
+            "c",
+            MAX_WHY_PATHS,
+            vec![vec!["root", "a", "c"], vec!["root", "b", "c"]],
+            false
+        ),
+        case(
+            IncludeBuildDeps::Yes,
+            "d",
+            1,
+            vec![vec!["root", "a", "c", "d"]],
+            true
+        ),
+        case(IncludeBuildDeps::No, "d", MAX_WHY_PATHS, vec![], false),
+        case(
+            IncludeBuildDeps::Yes,
+            "root",
+            MAX_WHY_PATHS,
+            vec![vec!["root"]],
+            false
+        )
+    )]
+    fn dependency_paths_test(
+        input_include_build_deps: IncludeBuildDeps,
+        input_name: &str,
+        input_max_paths: usize,
+        expected_paths: Vec<Vec<&str>>,
+        expected_truncated: bool,
+    ) {
+        let expected_paths = expected_paths
+            .iter()
+            .map(|path| path.iter().map(|name| package_id(name)).collect())
+            .collect::<Vec<Vec<_>>>();
+
+        assert_eq!(
+            dependency_paths(
+                &create_graph(),
+                input_include_build_deps,
+                &package_id("root"),
+                &package_id(input_name),
+                input_max_paths
+            ),
+            (expected_paths, expected_truncated)
+        );
+    }
+
+    #[rstest]
+    fn construct_why_lines_test() {
+        let why_crate = |name: &str, unsafe_counts| WhyCrate {
+            name: String::from(name),
+            version: Version::new(0, 1, 0),
+            unsafe_counts,
+        };
+        let paths = vec![vec![
+            why_crate(
+                "root",
+                Some((CrateDetectionStatus::NoneDetectedForbidsUnsafe, 0, 0)),
+            ),
+            why_crate("a", None),
+            why_crate("c", Some((CrateDetectionStatus::UnsafeDetected, 3, 5))),
+        ]];
+        let print_config = PrintConfig {
+            color: ColorWhen::Never,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            construct_why_lines(
+                "c",
+                &Version::new(0, 1, 0),
+                &paths,
+                true,
+                &print_config
+            ),
+            vec![
+                "Dependency paths from the root to c 0.1.0, with the unsafe \
+                code used by the build/found of each crate:",
+                "    root 0.1.0 (0/0) -> a 0.1.0 (?) -> c 0.1.0 (3/5)",
+                "    ... more paths are left out, only the first 1 are printed",
+                "",
+            ]
+        );
+    }
+}
@@ -2,8 +2,8 @@ use crate::context::Context;
 use crate::integration_test::IntegrationTest;
 use crate::report::{merge_test_reports, single_entry_safety_report, to_set};
 use cargo_geiger_serde::{
-    Count, CounterBlock, PackageId, PackageInfo, ReportEntry, SafetyReport,
-    Source, UnsafeInfo,
+    Count, CounterBlock, FfiCount, PackageId, PackageInfo, ReportEntry,
+    SafetyReport, Source, UnsafeInfo,
 };
 use semver::Version;
 use url::Url;
@@ -248,6 +248,22 @@ pub fn generational_arena_safety_report() -> SafetyReport {
                 ..Default::default()
             },
             forbids_unsafe: true,
+            denies_unsafe: false,
+            conditionally_forbids_unsafe: false,
+            ffi: FfiCount::default(),
+            unsafe_introduced_at: None,
+            downloads: None,
+            has_security_policy: None,
+            checksum_mismatch: None,
+            advisories: None,
+            unsafe_findings: None,
+            dangerous_api_calls: None,
+            expanded: None,
+            source_kind: None,
+            registry: None,
+            detector_findings: None,
+            unsafe_density_percentile: None,
+            lines_of_code: None,
         },
     };
     let mut report = single_entry_safety_report(entry);
@@ -34,7 +34,10 @@ pub trait IntegrationTest {
 }
 
 fn run_geiger_json(test_name: &str) -> (Output, Context) {
-    run_geiger_with(test_name, &["--output-format", "Json"])
+    run_geiger_with(
+        test_name,
+        &["--output-format", "Json", "--json-version", "1"],
+    )
 }
 
 fn run_geiger_json_quick(test_name: &str) -> (Output, Context) {
@@ -42,6 +42,10 @@ pub fn to_quick_report(report: SafetyReport) -> QuickSafetyReport {
             let quick_entry = QuickReportEntry {
                 package: entry.package,
                 forbids_unsafe: entry.unsafety.forbids_unsafe,
+                denies_unsafe: entry.unsafety.denies_unsafe,
+                conditionally_forbids_unsafe: entry
+                    .unsafety
+                    .conditionally_forbids_unsafe,
             };
             (id, quick_entry)
         })
@@ -49,6 +53,7 @@ pub fn to_quick_report(report: SafetyReport) -> QuickSafetyReport {
     QuickSafetyReport {
         packages: entries,
         packages_without_metrics: report.packages_without_metrics,
+        ignored_packages: report.ignored_packages,
     }
 }
 
@@ -0,0 +1,308 @@
+use super::{is_test_fn, is_test_mod, IncludeTests, RsFileMetrics};
+
+use crate::geiger_syn_visitor::GeigerSynVisitor;
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use syn::{visit, Expr, ImplItemMethod, ItemFn, ItemMod, Macro};
+
+/// An analysis of the syntax tree of each scanned `.rs` file. The detectors of
+/// a scan are run one after the other on the same file, each recording what
+/// it finds in the metrics of the file, so an analysis is added as a detector
+/// without changing how files are found, parsed or cached.
+pub trait Detector: Send + Sync {
+    /// The name of the detector, under which its findings are recorded in
+    /// `RsFileMetrics::detector_findings`
+    fn name(&self) -> &'static str;
+
+    fn visit_file(
+        &self,
+        file: &syn::File,
+        context: &FileContext,
+        metrics: &mut RsFileMetrics,
+    );
+}
+
+/// What a detector is given besides the syntax tree of a file
+pub struct FileContext<'a> {
+    pub include_tests: IncludeTests,
+    /// The source of the file, for what is not part of the syntax tree, such
+    /// as comments
+    pub src: &'a str,
+}
+
+/// The counter of unsafe functions, expressions, impls, traits and methods,
+/// along with the lint levels, FFI items, unsafe calls and locations of the
+/// file. It is the first detector of every scan.
+pub struct UnsafeCounter;
+
+impl Detector for UnsafeCounter {
+    fn name(&self) -> &'static str {
+        "unsafe"
+    }
+
+    fn visit_file(
+        &self,
+        file: &syn::File,
+        context: &FileContext,
+        metrics: &mut RsFileMetrics,
+    ) {
+        use syn::visit::Visit;
+        GeigerSynVisitor::new(context.include_tests, context.src, metrics)
+            .visit_file(file);
+    }
+}
+
+/// The detectors which can be enabled in addition to the `UnsafeCounter`,
+/// with `--detector <NAME>`
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum BuiltInDetector {
+    PanicInUnsafe,
+}
+
+impl BuiltInDetector {
+    pub fn name(self) -> &'static str {
+        match self {
+            BuiltInDetector::PanicInUnsafe => PanicInUnsafe.name(),
+        }
+    }
+
+    fn detector(self) -> Box<dyn Detector> {
+        match self {
+            BuiltInDetector::PanicInUnsafe => Box::new(PanicInUnsafe),
+        }
+    }
+}
+
+impl FromStr for BuiltInDetector {
+    type Err = UnknownDetectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "panic-in-unsafe" => Ok(BuiltInDetector::PanicInUnsafe),
+            _ => Err(UnknownDetectorError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct UnknownDetectorError(String);
+
+impl Error for UnknownDetectorError {}
+
+impl fmt::Display for UnknownDetectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown detector: {}, expected panic-in-unsafe", self.0)
+    }
+}
+
+/// The `UnsafeCounter` only
+pub fn default_detectors() -> Vec<Box<dyn Detector>> {
+    vec![Box::new(UnsafeCounter)]
+}
+
+/// The `UnsafeCounter`, followed by each of the enabled detectors once
+pub fn detector_pipeline(
+    enabled_detectors: &[BuiltInDetector],
+) -> Vec<Box<dyn Detector>> {
+    let mut enabled_detectors = enabled_detectors.to_vec();
+    enabled_detectors.sort_unstable();
+    enabled_detectors.dedup();
+    let mut detectors = default_detectors();
+    detectors
+        .extend(enabled_detectors.into_iter().map(BuiltInDetector::detector));
+    detectors
+}
+
+/// The panicking macros, such as `unreachable!`, and calls to `unwrap` and
+/// `expect`, within unsafe functions, blocks and methods. A panic unwinding
+/// out of unsafe code can leave the invariants it maintains broken.
+pub struct PanicInUnsafe;
+
+/// The macros which panic, unconditionally or when an assertion fails
+const PANICKING_MACROS: [&str; 7] = [
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "panic",
+    "todo",
+    "unimplemented",
+    "unreachable",
+];
+
+impl Detector for PanicInUnsafe {
+    fn name(&self) -> &'static str {
+        "panic-in-unsafe"
+    }
+
+    fn visit_file(
+        &self,
+        file: &syn::File,
+        context: &FileContext,
+        metrics: &mut RsFileMetrics,
+    ) {
+        use syn::visit::Visit;
+        let mut visitor = PanicInUnsafeVisitor {
+            include_tests: context.include_tests,
+            panics: 0,
+            unsafe_scopes: 0,
+        };
+        visitor.visit_file(file);
+        metrics
+            .detector_findings
+            .insert(self.name().to_string(), visitor.panics);
+    }
+}
+
+struct PanicInUnsafeVisitor {
+    include_tests: IncludeTests,
+    panics: u64,
+    unsafe_scopes: u32,
+}
+
+impl PanicInUnsafeVisitor {
+    fn visit_in_scope<F>(&mut self, is_unsafe: bool, visit_inner: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        self.unsafe_scopes += u32::from(is_unsafe);
+        visit_inner(self);
+        self.unsafe_scopes -= u32::from(is_unsafe);
+    }
+}
+
+impl<'ast> visit::Visit<'ast> for PanicInUnsafeVisitor {
+    fn visit_item_fn(&mut self, item_fn: &ItemFn) {
+        if IncludeTests::No == self.include_tests && is_test_fn(item_fn) {
+            return;
+        }
+        self.visit_in_scope(item_fn.sig.unsafety.is_some(), |visitor| {
+            visit::visit_item_fn(visitor, item_fn)
+        });
+    }
+
+    fn visit_item_mod(&mut self, i: &ItemMod) {
+        if IncludeTests::No == self.include_tests && is_test_mod(i) {
+            return;
+        }
+        visit::visit_item_mod(self, i);
+    }
+
+    fn visit_impl_item_method(&mut self, i: &ImplItemMethod) {
+        self.visit_in_scope(i.sig.unsafety.is_some(), |visitor| {
+            visit::visit_impl_item_method(visitor, i)
+        });
+    }
+
+    fn visit_expr(&mut self, i: &Expr) {
+        match i {
+            Expr::Unsafe(i) => self.visit_in_scope(true, |visitor| {
+                visit::visit_expr_unsafe(visitor, i)
+            }),
+            Expr::MethodCall(method_call)
+                if self.unsafe_scopes > 0
+                    && (method_call.method == "unwrap"
+                        || method_call.method == "expect") =>
+            {
+                self.panics += 1;
+                visit::visit_expr(self, i);
+            }
+            _ => visit::visit_expr(self, i),
+        }
+    }
+
+    fn visit_macro(&mut self, i: &Macro) {
+        let is_panicking = i.path.segments.last().is_some_and(|segment| {
+            PANICKING_MACROS.iter().any(|name| segment.ident == name)
+        });
+        if self.unsafe_scopes > 0 && is_panicking {
+            self.panics += 1;
+        }
+        visit::visit_macro(self, i);
+    }
+}
+
+#[cfg(test)]
+mod detector_tests {
+    use super::*;
+
+    use crate::find::find_unsafe_in_string_with_detectors;
+
+    use rstest::*;
+
+    #[rstest(
+        input_src,
+        expected_panics,
+        case(
+            "pub unsafe fn f(v: &[u8]) -> u8 {\n    \
+            assert!(!v.is_empty());\n    \
+            *v.first().unwrap()\n}",
+            2
+        ),
+        case(
+            "pub fn f(x: Option<u8>) -> u8 {\n    \
+            let y = unsafe { x.expect(\"x\") };\n    \
+            x.unwrap() + y\n}",
+            1
+        ),
+        case(
+            "pub fn f() {\n    unsafe {\n        \
+            std::unreachable!()\n    }\n}",
+            1
+        ),
+        case("pub fn f() {\n    panic!()\n}", 0),
+        case(
+            "#[cfg(test)]\nmod tests {\n    unsafe fn f() {\n        \
+            panic!()\n    }\n}",
+            0
+        )
+    )]
+    fn panic_in_unsafe_test(input_src: &str, expected_panics: u64) {
+        let rs_file_metrics = find_unsafe_in_string_with_detectors(
+            input_src,
+            IncludeTests::No,
+            &detector_pipeline(&[BuiltInDetector::PanicInUnsafe]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            rs_file_metrics.detector_findings.get("panic-in-unsafe"),
+            Some(&expected_panics)
+        );
+    }
+
+    #[rstest(
+        input_enabled_detectors,
+        expected_names,
+        case(vec![], vec!["unsafe"]),
+        case(
+            vec![BuiltInDetector::PanicInUnsafe, BuiltInDetector::PanicInUnsafe],
+            vec!["unsafe", "panic-in-unsafe"]
+        )
+    )]
+    fn detector_pipeline_test(
+        input_enabled_detectors: Vec<BuiltInDetector>,
+        expected_names: Vec<&str>,
+    ) {
+        assert_eq!(
+            detector_pipeline(&input_enabled_detectors)
+                .iter()
+                .map(|detector| detector.name())
+                .collect::<Vec<_>>(),
+            expected_names
+        );
+    }
+
+    #[rstest]
+    fn built_in_detector_from_str_test() {
+        assert_eq!(
+            BuiltInDetector::from_str("panic-in-unsafe"),
+            Ok(BuiltInDetector::PanicInUnsafe)
+        );
+        assert_eq!(
+            BuiltInDetector::from_str("unsafe"),
+            Err(UnknownDetectorError(String::from("unsafe")))
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use super::{IncludeTests, RsFileMetrics, ScanFileError};
 
-use crate::geiger_syn_visitor::GeigerSynVisitor;
+use crate::detector::{default_detectors, Detector, FileContext};
 
 use std::fs::File;
 use std::io::Read;
@@ -11,34 +11,99 @@ pub fn find_unsafe_in_file(
     path: &Path,
     include_tests: IncludeTests,
 ) -> Result<RsFileMetrics, ScanFileError> {
+    find_unsafe_in_file_with_detectors(
+        path,
+        include_tests,
+        false,
+        &default_detectors(),
+    )
+    .map(|(rs_file_metrics, _)| rs_file_metrics)
+}
+
+/// Scan a single file for `unsafe` usage like `find_unsafe_in_file`, but
+/// decode a file which isn't valid UTF-8 with its invalid sequences replaced,
+/// instead of failing. Whether the file had to be decoded so is returned with
+/// its metrics.
+pub fn find_unsafe_in_file_lossy(
+    path: &Path,
+    include_tests: IncludeTests,
+) -> Result<(RsFileMetrics, bool), ScanFileError> {
+    find_unsafe_in_file_with_detectors(
+        path,
+        include_tests,
+        true,
+        &default_detectors(),
+    )
+}
+
+/// Scan a single file with the detectors, in order. A file which isn't valid
+/// UTF-8 fails to be scanned, unless `lossy` is set, in which case it is
+/// decoded like with `find_unsafe_in_file_lossy`.
+pub fn find_unsafe_in_file_with_detectors(
+    path: &Path,
+    include_tests: IncludeTests,
+    lossy: bool,
+    detectors: &[Box<dyn Detector>],
+) -> Result<(RsFileMetrics, bool), ScanFileError> {
+    let (src, is_lossy) = match String::from_utf8(read_file(path)?) {
+        Ok(src) => (src, false),
+        Err(e) if lossy => {
+            (String::from_utf8_lossy(e.as_bytes()).into_owned(), true)
+        }
+        Err(e) => return Err(ScanFileError::Utf8(e, path.to_path_buf())),
+    };
+    find_unsafe_in_string_with_detectors(&src, include_tests, detectors)
+        .map(|rs_file_metrics| (rs_file_metrics, is_lossy))
+        .map_err(|e| ScanFileError::Syn(e, path.to_path_buf()))
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, ScanFileError> {
     let mut file = File::open(path)
         .map_err(|e| ScanFileError::Io(e, path.to_path_buf()))?;
     let mut src = vec![];
     file.read_to_end(&mut src)
         .map_err(|e| ScanFileError::Io(e, path.to_path_buf()))?;
-    let src = String::from_utf8(src)
-        .map_err(|e| ScanFileError::Utf8(e, path.to_path_buf()))?;
-    find_unsafe_in_string(&src, include_tests)
-        .map_err(|e| ScanFileError::Syn(e, path.to_path_buf()))
+    Ok(src)
 }
 
 pub fn find_unsafe_in_string(
     src: &str,
     include_tests: IncludeTests,
 ) -> Result<RsFileMetrics, syn::Error> {
-    use syn::visit::Visit;
+    find_unsafe_in_string_with_detectors(
+        src,
+        include_tests,
+        &default_detectors(),
+    )
+}
+
+/// Parse the source once and run each of the detectors on it, in order
+pub fn find_unsafe_in_string_with_detectors(
+    src: &str,
+    include_tests: IncludeTests,
+    detectors: &[Box<dyn Detector>],
+) -> Result<RsFileMetrics, syn::Error> {
     let syntax = syn::parse_file(src)?;
-    let mut vis = GeigerSynVisitor::new(include_tests);
-    vis.visit_file(&syntax);
-    Ok(vis.metrics)
+    let context = FileContext { include_tests, src };
+    let mut metrics = RsFileMetrics {
+        lines: src.lines().count() as u64,
+        ..Default::default()
+    };
+    for detector in detectors {
+        detector.visit_file(&syntax, &context, &mut metrics);
+    }
+    Ok(metrics)
 }
 
 #[cfg(test)]
 mod find_tests {
     use super::*;
 
+    use crate::{UnsafeKind, UnsafeLocation};
+
     use cargo_geiger_serde::{Count, CounterBlock};
     use rstest::*;
+    use std::collections::BTreeMap;
     use std::io::Write;
     use tempfile::tempdir;
 
@@ -83,6 +148,7 @@ mod tests {
         case(
         IncludeTests::Yes,
         RsFileMetrics {
+            lines: 34,
             counters: CounterBlock {
                 functions: Count {
                     safe: 2,
@@ -105,12 +171,24 @@ mod tests {
                     unsafe_: 0
                 }
             },
-            forbids_unsafe: false
+            forbids_unsafe: false,
+            denies_unsafe: false,
+conditionally_forbids_unsafe: false,
+            undocumented_unsafe_blocks: 2,
+            send_sync_impls: 0,
+            ffi_items: 2,
+            unsafe_calls: BTreeMap::from([(
+                String::from("std::str::from_utf8_unchecked"),
+                1
+            )]),
+            detector_findings: BTreeMap::new(),
+            unsafe_locations: create_unsafe_locations(IncludeTests::Yes)
         }
         ),
         case(
             IncludeTests::No,
             RsFileMetrics {
+                lines: 34,
                 counters: CounterBlock {
                     functions: Count {
                         safe: 1,
@@ -133,7 +211,18 @@ mod tests {
                         unsafe_: 0
                     }
                 },
-                forbids_unsafe: false
+                forbids_unsafe: false,
+                denies_unsafe: false,
+conditionally_forbids_unsafe: false,
+                undocumented_unsafe_blocks: 1,
+                send_sync_impls: 0,
+                ffi_items: 2,
+                unsafe_calls: BTreeMap::from([(
+                    String::from("std::str::from_utf8_unchecked"),
+                    1
+                )]),
+                detector_findings: BTreeMap::new(),
+                unsafe_locations: create_unsafe_locations(IncludeTests::No)
             }
         )
     )]
@@ -157,12 +246,36 @@ mod tests {
         assert_eq!(unsafe_in_file, expected_rs_file_metrics);
     }
 
+    #[rstest]
+    fn find_unsafe_in_file_lossy_test() {
+        let temp_dir = tempdir().unwrap();
+        let lib_file_path = temp_dir.path().join("lib.rs");
+        let mut file = File::create(lib_file_path.clone()).unwrap();
+
+        // A comment in Latin-1, which is not valid UTF-8.
+        file.write_all(b"// caf\xe9\npub unsafe fn f() {}\n")
+            .unwrap();
+
+        assert!(matches!(
+            find_unsafe_in_file(&lib_file_path, IncludeTests::No),
+            Err(ScanFileError::Utf8(..))
+        ));
+
+        let (rs_file_metrics, is_lossy) =
+            find_unsafe_in_file_lossy(&lib_file_path, IncludeTests::No)
+                .unwrap();
+
+        assert!(is_lossy);
+        assert_eq!(rs_file_metrics.counters.functions.unsafe_, 1);
+    }
+
     #[rstest(
         input_include_tests,
         expected_rs_file_metrics,
         case(
             IncludeTests::Yes,
             RsFileMetrics {
+                lines: 33,
                 counters: CounterBlock {
                     functions: Count {
                         safe: 2,
@@ -185,12 +298,24 @@ mod tests {
                         unsafe_: 0
                     }
                 },
-                forbids_unsafe: false
+                forbids_unsafe: false,
+                denies_unsafe: false,
+conditionally_forbids_unsafe: false,
+                undocumented_unsafe_blocks: 2,
+                send_sync_impls: 0,
+                ffi_items: 2,
+                unsafe_calls: BTreeMap::from([(
+                    String::from("std::str::from_utf8_unchecked"),
+                    1
+                )]),
+                detector_findings: BTreeMap::new(),
+                unsafe_locations: create_unsafe_locations(IncludeTests::Yes)
             }
         ),
         case(
             IncludeTests::No,
             RsFileMetrics {
+                lines: 33,
                 counters: CounterBlock {
                     functions: Count {
                         safe: 1,
@@ -213,7 +338,18 @@ mod tests {
                         unsafe_: 0
                     }
                 },
-                forbids_unsafe: false
+                forbids_unsafe: false,
+                denies_unsafe: false,
+conditionally_forbids_unsafe: false,
+                undocumented_unsafe_blocks: 1,
+                send_sync_impls: 0,
+                ffi_items: 2,
+                unsafe_calls: BTreeMap::from([(
+                    String::from("std::str::from_utf8_unchecked"),
+                    1
+                )]),
+                detector_findings: BTreeMap::new(),
+                unsafe_locations: create_unsafe_locations(IncludeTests::No)
             }
         )
     )]
@@ -229,4 +365,261 @@ mod tests {
 
         assert_eq!(unsafe_in_string, expected_rs_file_metrics);
     }
+
+    #[rstest(
+        input_src,
+        expected_undocumented_unsafe_blocks,
+        case(
+            "pub fn f() {
+                // SAFETY: the pointer is never dereferenced.
+                let _ = unsafe { std::ptr::null::<u8>().add(0) };
+            }",
+            0
+        ),
+        case(
+            "pub fn f() {
+                // SAFETY: the pointer is never dereferenced, and
+                // this comment spans multiple lines.
+                let _ = unsafe { std::ptr::null::<u8>().add(0) };
+            }",
+            0
+        ),
+        case(
+            "pub fn f() {
+                let _ = /* SAFETY: in bounds */ unsafe { g() };
+            }",
+            0
+        ),
+        case(
+            "pub fn f() {
+                /*
+                 * SAFETY: the pointer is never dereferenced.
+                 */
+                let _ = unsafe { std::ptr::null::<u8>().add(0) };
+            }",
+            0
+        ),
+        case(
+            "pub fn f(ptr: &mut u8) {
+                // SAFETY: only covers the assignment below.
+                *ptr = 0;
+                let _ = unsafe { std::ptr::null::<u8>().add(0) };
+            }",
+            1
+        ),
+        case(
+            "pub fn f() {
+                // SAFETY: the pointer is never dereferenced.
+                let _ = 1;
+                let _ = unsafe { std::ptr::null::<u8>().add(0) };
+            }",
+            1
+        ),
+        case(
+            "pub fn f() {
+                let _ = unsafe { std::ptr::null::<u8>().add(0) };
+            }",
+            1
+        )
+    )]
+    fn find_unsafe_in_string_undocumented_unsafe_blocks_test(
+        input_src: &str,
+        expected_undocumented_unsafe_blocks: u64,
+    ) {
+        let rs_file_metrics =
+            find_unsafe_in_string(input_src, IncludeTests::Yes).unwrap();
+
+        assert_eq!(
+            rs_file_metrics.undocumented_unsafe_blocks,
+            expected_undocumented_unsafe_blocks
+        );
+    }
+
+    #[rstest(
+        input_src,
+        expected_forbids_unsafe,
+        expected_denies_unsafe,
+        expected_conditionally_forbids_unsafe,
+        case("#![forbid(unsafe_code)]", true, false, false),
+        case("#![deny(unsafe_code)]", false, true, false),
+        case("#![deny(missing_docs, unsafe_code)]", false, true, false),
+        case("#![deny(missing_docs)]", false, false, false),
+        case(
+            "#![cfg_attr(not(feature = \"unsafe\"), forbid(unsafe_code))]",
+            false,
+            false,
+            true
+        ),
+        case(
+            "#![cfg_attr(not(test), deny(missing_docs), deny(unsafe_code))]",
+            false,
+            false,
+            true
+        ),
+        case(
+            "#![cfg_attr(unix, cfg_attr(not(test), forbid(unsafe_code)))]",
+            false,
+            false,
+            true
+        ),
+        case("#![cfg_attr(test, deny(missing_docs))]", false, false, false),
+        case("", false, false, false)
+    )]
+    fn find_unsafe_in_string_lint_level_test(
+        input_src: &str,
+        expected_forbids_unsafe: bool,
+        expected_denies_unsafe: bool,
+        expected_conditionally_forbids_unsafe: bool,
+    ) {
+        let rs_file_metrics =
+            find_unsafe_in_string(input_src, IncludeTests::Yes).unwrap();
+
+        assert_eq!(rs_file_metrics.forbids_unsafe, expected_forbids_unsafe);
+        assert_eq!(rs_file_metrics.denies_unsafe, expected_denies_unsafe);
+        assert_eq!(
+            rs_file_metrics.conditionally_forbids_unsafe,
+            expected_conditionally_forbids_unsafe
+        );
+    }
+
+    #[rstest(
+        input_src,
+        expected_send_sync_impls,
+        case("unsafe impl Send for Foo {}", 1),
+        case("unsafe impl core::marker::Sync for Foo {}", 1),
+        case(
+            "unsafe impl<T> Send for Foo<T> {}\nunsafe impl Bar for Foo {}",
+            1
+        ),
+        case("impl Clone for Foo {}", 0)
+    )]
+    fn find_unsafe_in_string_send_sync_impls_test(
+        input_src: &str,
+        expected_send_sync_impls: u64,
+    ) {
+        let rs_file_metrics =
+            find_unsafe_in_string(input_src, IncludeTests::Yes).unwrap();
+
+        assert_eq!(rs_file_metrics.send_sync_impls, expected_send_sync_impls);
+    }
+
+    #[rstest(
+        input_src,
+        expected_ffi_items,
+        case("extern \"C\" {\n    fn f();\n    static S: u8;\n}", 2),
+        case("pub extern \"C\" fn f() {}", 1),
+        case("#[no_mangle]\npub extern \"C\" fn f() {}", 1),
+        case("#[export_name = \"g\"]\npub fn f() {}", 1),
+        case("struct S;\nimpl S {\n    extern \"C\" fn f() {}\n}", 1),
+        case("pub fn f() {}\nextern crate core;", 0)
+    )]
+    fn find_unsafe_in_string_ffi_items_test(
+        input_src: &str,
+        expected_ffi_items: u64,
+    ) {
+        let rs_file_metrics =
+            find_unsafe_in_string(input_src, IncludeTests::Yes).unwrap();
+
+        assert_eq!(rs_file_metrics.ffi_items, expected_ffi_items);
+    }
+
+    #[rstest(
+        input_src,
+        expected_unsafe_calls,
+        case(
+            "unsafe fn f(v: &[u8]) -> u32 {\n    \
+            let p = v.as_ptr() as *const u32;\n    \
+            std::mem::transmute(*v.get_unchecked(0))\n}",
+            vec![
+                (".as_ptr", 1),
+                (".get_unchecked", 1),
+                ("as *const", 1),
+                ("std::mem::transmute", 1),
+            ]
+        ),
+        case("fn f(v: &[u8]) -> u8 {\n    *v.get(0).unwrap()\n}", vec![])
+    )]
+    fn find_unsafe_in_string_unsafe_calls_test(
+        input_src: &str,
+        expected_unsafe_calls: Vec<(&str, u64)>,
+    ) {
+        let rs_file_metrics =
+            find_unsafe_in_string(input_src, IncludeTests::Yes).unwrap();
+
+        assert_eq!(
+            rs_file_metrics
+                .unsafe_calls
+                .iter()
+                .map(|(call, count)| (call.as_str(), *count))
+                .collect::<Vec<_>>(),
+            expected_unsafe_calls
+        );
+    }
+
+    #[rstest]
+    fn find_unsafe_in_string_test_unsafe_locations() {
+        let rs_file_metrics = find_unsafe_in_string(
+            "unsafe trait Foo {}\n\
+            unsafe impl Foo for () {}\n\
+            struct Bar;\n\
+            impl Bar {\n    unsafe fn baz() {}\n}\n",
+            IncludeTests::Yes,
+        )
+        .unwrap();
+
+        assert_eq!(
+            rs_file_metrics.unsafe_locations,
+            vec![
+                UnsafeLocation {
+                    line: 1,
+                    column: 1,
+                    end_line: 1,
+                    end_column: 19,
+                    kind: UnsafeKind::Trait
+                },
+                UnsafeLocation {
+                    line: 2,
+                    column: 1,
+                    end_line: 2,
+                    end_column: 25,
+                    kind: UnsafeKind::Impl
+                },
+                UnsafeLocation {
+                    line: 5,
+                    column: 5,
+                    end_line: 5,
+                    end_column: 22,
+                    kind: UnsafeKind::Method
+                },
+            ]
+        );
+    }
+
+    /// The unsafe items of `FILE_CONTENT_STRING`, the last one being in its
+    /// test module
+    fn create_unsafe_locations(
+        include_tests: IncludeTests,
+    ) -> Vec<UnsafeLocation> {
+        let mut unsafe_locations = vec![
+            (UnsafeKind::Function, (3, 5), (5, 1)),
+            (UnsafeKind::Block, (8, 33), (10, 5)),
+            (UnsafeKind::Function, (14, 5), (16, 1)),
+            (UnsafeKind::Function, (19, 5), (21, 1)),
+        ];
+        if include_tests == IncludeTests::Yes {
+            unsafe_locations.push((UnsafeKind::Block, (29, 9), (31, 9)));
+        }
+        unsafe_locations
+            .into_iter()
+            .map(|(kind, (line, column), (end_line, end_column))| {
+                UnsafeLocation {
+                    line,
+                    column,
+                    end_line,
+                    end_column,
+                    kind,
+                }
+            })
+            .collect()
+    }
 }
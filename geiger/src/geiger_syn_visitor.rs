@@ -1,16 +1,26 @@
 use super::{
-    file_forbids_unsafe, has_unsafe_attributes, is_test_fn, is_test_mod,
-    IncludeTests, RsFileMetrics,
+    file_conditionally_forbids_unsafe, file_denies_unsafe, file_forbids_unsafe,
+    has_safety_comment, has_unsafe_attributes, is_test_fn, is_test_mod,
+    IncludeTests, RsFileMetrics, UnsafeKind, UnsafeLocation,
 };
 
-use syn::{visit, Expr, ImplItemMethod, ItemFn, ItemImpl, ItemMod, ItemTrait};
+use proc_macro2::LineColumn;
 
-pub struct GeigerSynVisitor {
+use syn::{
+    visit, Expr, ForeignItem, ImplItemMethod, ItemFn, ItemForeignMod, ItemImpl,
+    ItemMod, ItemTrait, Type,
+};
+
+pub struct GeigerSynVisitor<'a, 'm> {
     /// Count unsafe usage inside tests
     include_tests: IncludeTests,
 
+    /// The lines of the scanned source file, used to look for `// SAFETY:`
+    /// comments, which are not part of the syntax tree.
+    source_lines: Vec<&'a str>,
+
     /// The resulting data from a single file scan.
-    pub metrics: RsFileMetrics,
+    metrics: &'m mut RsFileMetrics,
 
     /// The number of nested unsafe scopes that the GeigerSynVisitor are
     /// currently in. For example, if the visitor is inside an unsafe function
@@ -22,11 +32,16 @@ pub struct GeigerSynVisitor {
     unsafe_scopes: u32,
 }
 
-impl GeigerSynVisitor {
-    pub fn new(include_tests: IncludeTests) -> Self {
+impl<'a, 'm> GeigerSynVisitor<'a, 'm> {
+    pub fn new(
+        include_tests: IncludeTests,
+        src: &'a str,
+        metrics: &'m mut RsFileMetrics,
+    ) -> Self {
         GeigerSynVisitor {
             include_tests,
-            metrics: Default::default(),
+            source_lines: src.lines().collect(),
+            metrics,
             unsafe_scopes: 0,
         }
     }
@@ -38,11 +53,25 @@ impl GeigerSynVisitor {
     pub fn exit_unsafe_scope(&mut self) {
         self.unsafe_scopes -= 1;
     }
+
+    fn push_unsafe_location(
+        &mut self,
+        kind: UnsafeKind,
+        start: LineColumn,
+        end: LineColumn,
+    ) {
+        self.metrics
+            .unsafe_locations
+            .push(UnsafeLocation::new(kind, start, end));
+    }
 }
 
-impl<'ast> visit::Visit<'ast> for GeigerSynVisitor {
+impl<'a, 'm, 'ast> visit::Visit<'ast> for GeigerSynVisitor<'a, 'm> {
     fn visit_file(&mut self, i: &'ast syn::File) {
         self.metrics.forbids_unsafe = file_forbids_unsafe(i);
+        self.metrics.denies_unsafe = file_denies_unsafe(i);
+        self.metrics.conditionally_forbids_unsafe =
+            file_conditionally_forbids_unsafe(i);
         syn::visit::visit_file(self, i);
     }
 
@@ -54,9 +83,21 @@ impl<'ast> visit::Visit<'ast> for GeigerSynVisitor {
         let unsafe_fn =
             item_fn.sig.unsafety.is_some() || has_unsafe_attributes(item_fn);
         if unsafe_fn {
-            self.enter_unsafe_scope()
+            self.enter_unsafe_scope();
+            let start = match item_fn.sig.unsafety {
+                Some(unsafety) => unsafety.span.start(),
+                None => item_fn.sig.fn_token.span.start(),
+            };
+            self.push_unsafe_location(
+                UnsafeKind::Function,
+                start,
+                item_fn.block.brace_token.span.end(),
+            );
         }
         self.metrics.counters.functions.count(unsafe_fn);
+        if item_fn.sig.abi.is_some() || has_unsafe_attributes(item_fn) {
+            self.metrics.ffi_items += 1;
+        }
         visit::visit_item_fn(self, item_fn);
         if item_fn.sig.unsafety.is_some() {
             self.exit_unsafe_scope()
@@ -67,6 +108,17 @@ impl<'ast> visit::Visit<'ast> for GeigerSynVisitor {
         // Total number of expressions of any type
         match i {
             Expr::Unsafe(i) => {
+                if !has_safety_comment(
+                    &self.source_lines,
+                    i.unsafe_token.span.start(),
+                ) {
+                    self.metrics.undocumented_unsafe_blocks += 1;
+                }
+                self.push_unsafe_location(
+                    UnsafeKind::Block,
+                    i.unsafe_token.span.start(),
+                    i.block.brace_token.span.end(),
+                );
                 self.enter_unsafe_scope();
                 visit::visit_expr_unsafe(self, i);
                 self.exit_unsafe_scope();
@@ -82,11 +134,27 @@ impl<'ast> visit::Visit<'ast> for GeigerSynVisitor {
                 //     println!("{:#?}", other);
                 // }
                 self.metrics.counters.exprs.count(self.unsafe_scopes > 0);
+                if self.unsafe_scopes > 0 {
+                    if let Some(call) = unsafe_call(other) {
+                        *self.metrics.unsafe_calls.entry(call).or_insert(0) +=
+                            1;
+                    }
+                }
                 visit::visit_expr(self, other);
             }
         }
     }
 
+    /// `extern` blocks, whose items are implemented outside of Rust
+    fn visit_item_foreign_mod(&mut self, i: &ItemForeignMod) {
+        self.metrics.ffi_items += i
+            .items
+            .iter()
+            .filter(|item| !matches!(item, ForeignItem::Macro(_)))
+            .count() as u64;
+        visit::visit_item_foreign_mod(self, i);
+    }
+
     fn visit_item_mod(&mut self, i: &ItemMod) {
         if IncludeTests::No == self.include_tests && is_test_mod(i) {
             return;
@@ -96,12 +164,29 @@ impl<'ast> visit::Visit<'ast> for GeigerSynVisitor {
 
     fn visit_item_impl(&mut self, i: &ItemImpl) {
         // unsafe trait impl's
+        if let Some(unsafety) = i.unsafety {
+            self.push_unsafe_location(
+                UnsafeKind::Impl,
+                unsafety.span.start(),
+                i.brace_token.span.end(),
+            );
+        }
         self.metrics.counters.item_impls.count(i.unsafety.is_some());
+        if i.unsafety.is_some() && implements_send_or_sync(i) {
+            self.metrics.send_sync_impls += 1;
+        }
         visit::visit_item_impl(self, i);
     }
 
     fn visit_item_trait(&mut self, i: &ItemTrait) {
         // Unsafe traits
+        if let Some(unsafety) = i.unsafety {
+            self.push_unsafe_location(
+                UnsafeKind::Trait,
+                unsafety.span.start(),
+                i.brace_token.span.end(),
+            );
+        }
         self.metrics
             .counters
             .item_traits
@@ -110,13 +195,21 @@ impl<'ast> visit::Visit<'ast> for GeigerSynVisitor {
     }
 
     fn visit_impl_item_method(&mut self, i: &ImplItemMethod) {
-        if i.sig.unsafety.is_some() {
-            self.enter_unsafe_scope()
+        if let Some(unsafety) = i.sig.unsafety {
+            self.enter_unsafe_scope();
+            self.push_unsafe_location(
+                UnsafeKind::Method,
+                unsafety.span.start(),
+                i.block.brace_token.span.end(),
+            );
         }
         self.metrics
             .counters
             .methods
             .count(i.sig.unsafety.is_some());
+        if i.sig.abi.is_some() {
+            self.metrics.ffi_items += 1;
+        }
         visit::visit_impl_item_method(self, i);
         if i.sig.unsafety.is_some() {
             self.exit_unsafe_scope()
@@ -128,3 +221,41 @@ impl<'ast> visit::Visit<'ast> for GeigerSynVisitor {
     // TODO: Figure out if there are other visit methods that should be
     // implemented here.
 }
+
+/// Whether the impl is of `Send` or `Sync`, however the trait is spelled out,
+/// such as `core::marker::Send`
+fn implements_send_or_sync(i: &ItemImpl) -> bool {
+    i.trait_
+        .as_ref()
+        .and_then(|(_, path, _)| path.segments.last())
+        .is_some_and(|segment| {
+            segment.ident == "Send" || segment.ident == "Sync"
+        })
+}
+
+/// The called function or method, or the raw pointer type cast to, under
+/// which the expression is counted in `RsFileMetrics::unsafe_calls`
+fn unsafe_call(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Call(i) => match &*i.func {
+            Expr::Path(func) => Some(
+                func.path
+                    .segments
+                    .iter()
+                    .map(|segment| segment.ident.to_string())
+                    .collect::<Vec<_>>()
+                    .join("::"),
+            ),
+            _ => None,
+        },
+        Expr::MethodCall(i) => Some(format!(".{}", i.method)),
+        Expr::Cast(i) => match &*i.ty {
+            Type::Ptr(ptr) if ptr.mutability.is_some() => {
+                Some(String::from("as *mut"))
+            }
+            Type::Ptr(_) => Some(String::from("as *const")),
+            _ => None,
+        },
+        _ => None,
+    }
+}
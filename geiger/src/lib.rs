@@ -7,12 +7,18 @@
 #![forbid(unsafe_code)]
 #![deny(warnings)]
 
+pub mod detector;
 pub mod find;
 pub use find::*; // preserve APIs
 
 mod geiger_syn_visitor;
 
+pub use cargo_geiger_serde::UnsafeKind;
+
 use cargo_geiger_serde::CounterBlock;
+use proc_macro2::LineColumn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fmt;
 use std::io;
@@ -27,13 +33,83 @@ pub enum IncludeTests {
 }
 
 /// Scan result for a single `.rs` file.
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct RsFileMetrics {
     /// Metrics storage.
     pub counters: CounterBlock,
 
+    /// Number of lines of the file, blank and comment lines included.
+    #[serde(default)]
+    pub lines: u64,
+
     /// This file is decorated with `#![forbid(unsafe_code)]`
     pub forbids_unsafe: bool,
+
+    /// This file is decorated with `#![deny(unsafe_code)]`, which unlike
+    /// `forbid` can be overridden by an inner `#[allow(unsafe_code)]`
+    pub denies_unsafe: bool,
+
+    /// This file is decorated with `forbid(unsafe_code)` or
+    /// `deny(unsafe_code)` within `#![cfg_attr(<condition>, ...)]`, which only
+    /// applies when the condition holds, such as when a feature is disabled
+    pub conditionally_forbids_unsafe: bool,
+
+    /// Number of `unsafe` blocks which are not preceded by a `// SAFETY:`
+    /// comment.
+    pub undocumented_unsafe_blocks: u64,
+
+    /// Number of `unsafe impl`s of `Send` or `Sync`, which are also counted
+    /// in `counters.item_impls`.
+    pub send_sync_impls: u64,
+
+    /// Number of items of the FFI surface: each item declared in an `extern`
+    /// block, and each function with an `extern` ABI or exported with
+    /// `#[no_mangle]` or `#[export_name]`.
+    pub ffi_items: u64,
+
+    /// Number of calls made in unsafe scopes, by the path of the called
+    /// function as written, such as `mem::transmute`, or by `.` and the name
+    /// of the called method, such as `.get_unchecked`. Casts to raw pointers
+    /// are counted as `as *const` and `as *mut`.
+    pub unsafe_calls: BTreeMap<String, u64>,
+
+    /// Where each unsafe function, block, impl, trait and method starts, in
+    /// the order they are found in the file.
+    pub unsafe_locations: Vec<UnsafeLocation>,
+
+    /// The number of findings of each detector run besides the
+    /// `UnsafeCounter`, by the name of the detector.
+    #[serde(default)]
+    pub detector_findings: BTreeMap<String, u64>,
+}
+
+/// An unsafe item, from its `unsafe` keyword, or from the `fn` keyword of a
+/// function which is unsafe because of its attributes, to the closing brace of
+/// its body. Lines and columns are 1-based, as printed by the compiler, and
+/// the end is the closing brace itself.
+#[derive(
+    Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize,
+)]
+pub struct UnsafeLocation {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub kind: UnsafeKind,
+}
+
+impl UnsafeLocation {
+    /// From the start of the first token to the end of the last one, whose
+    /// exclusive end column is the inclusive 1-based one
+    fn new(kind: UnsafeKind, start: LineColumn, end: LineColumn) -> Self {
+        UnsafeLocation {
+            line: start.line,
+            column: start.column + 1,
+            end_line: end.line,
+            end_column: end.column,
+            kind,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -53,35 +129,125 @@ impl fmt::Display for ScanFileError {
     }
 }
 
+fn file_denies_unsafe(f: &syn::File) -> bool {
+    file_has_unsafe_code_lint_level(f, "deny")
+}
+
 fn file_forbids_unsafe(f: &syn::File) -> bool {
+    file_has_unsafe_code_lint_level(f, "forbid")
+}
+
+/// Whether the file forbids or denies unsafe code only when a condition
+/// holds, with `#![cfg_attr(<condition>, forbid(unsafe_code))]`
+fn file_conditionally_forbids_unsafe(f: &syn::File) -> bool {
+    inner_attribute_metas(f).any(|meta| is_cfg_attr_of_unsafe_code_lint(&meta))
+}
+
+fn file_has_unsafe_code_lint_level(f: &syn::File, lint_level: &str) -> bool {
+    inner_attribute_metas(f)
+        .any(|meta| is_unsafe_code_lint_level(&meta, lint_level))
+}
+
+fn inner_attribute_metas(
+    f: &syn::File,
+) -> impl Iterator<Item = syn::Meta> + '_ {
     use syn::AttrStyle;
-    use syn::Meta;
-    use syn::MetaList;
-    use syn::NestedMeta;
     f.attrs
         .iter()
         .filter(|a| matches!(a.style, AttrStyle::Inner(_)))
         .filter_map(|a| a.parse_meta().ok())
-        .filter(|meta| match meta {
-            Meta::List(MetaList {
-                path,
-                paren_token: _paren,
-                nested,
-            }) => {
-                if !path.is_ident("forbid") {
-                    return false;
+}
+
+/// Whether the attribute is a `cfg_attr` whose attributes, after the
+/// condition, include a `forbid(unsafe_code)` or `deny(unsafe_code)`, also
+/// within a nested `cfg_attr`
+fn is_cfg_attr_of_unsafe_code_lint(meta: &syn::Meta) -> bool {
+    use syn::Meta;
+    use syn::NestedMeta;
+    match meta {
+        Meta::List(meta_list) if meta_list.path.is_ident("cfg_attr") => {
+            meta_list.nested.iter().skip(1).any(|n| match n {
+                NestedMeta::Meta(meta) => {
+                    is_unsafe_code_lint_level(meta, "forbid")
+                        || is_unsafe_code_lint_level(meta, "deny")
+                        || is_cfg_attr_of_unsafe_code_lint(meta)
                 }
-                nested.iter().any(|n| match n {
-                    NestedMeta::Meta(Meta::Path(p)) => {
-                        p.is_ident("unsafe_code")
-                    }
-                    _ => false,
-                })
+                _ => false,
+            })
+        }
+        _ => false,
+    }
+}
+
+fn is_unsafe_code_lint_level(meta: &syn::Meta, lint_level: &str) -> bool {
+    use syn::Meta;
+    use syn::MetaList;
+    use syn::NestedMeta;
+    match meta {
+        Meta::List(MetaList {
+            path,
+            paren_token: _paren,
+            nested,
+        }) => {
+            if !path.is_ident(lint_level) {
+                return false;
             }
-            _ => false,
-        })
-        .count()
-        > 0
+            nested.iter().any(|n| match n {
+                NestedMeta::Meta(Meta::Path(p)) => p.is_ident("unsafe_code"),
+                _ => false,
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Heuristic for the `// SAFETY:` comment convention. The comment is accepted
+/// if it is placed on the lines directly above the `unsafe` keyword, or before
+/// the keyword on the same line.
+fn has_safety_comment(source_lines: &[&str], unsafe_start: LineColumn) -> bool {
+    const SAFETY_COMMENT_MARKER: &str = "SAFETY:";
+
+    let line_index = unsafe_start.line.saturating_sub(1);
+    if line_index >= source_lines.len() {
+        return false;
+    }
+
+    let same_line_prefix = source_lines[line_index]
+        .chars()
+        .take(unsafe_start.column)
+        .collect::<String>();
+    if same_line_prefix.contains(SAFETY_COMMENT_MARKER) {
+        return true;
+    }
+
+    comment_lines_above(&source_lines[..line_index])
+        .iter()
+        .any(|line| line.contains(SAFETY_COMMENT_MARKER))
+}
+
+/// The trimmed comment lines directly above the end of `source_lines`. A line
+/// of a `/* */` comment is only accepted once the line opening the comment is
+/// reached, so that code such as `*ptr = 0;` isn't taken for a comment.
+fn comment_lines_above<'a>(source_lines: &[&'a str]) -> Vec<&'a str> {
+    let mut comment_lines = Vec::new();
+    let mut open_block_lines = Vec::new();
+    for line in source_lines.iter().rev().map(|line| line.trim()) {
+        if !open_block_lines.is_empty() {
+            open_block_lines.push(line);
+            if line.starts_with("/*") {
+                comment_lines.append(&mut open_block_lines);
+            }
+        } else if line.starts_with("//")
+            || (line.starts_with("/*") && line.ends_with("*/"))
+        {
+            comment_lines.push(line);
+        } else if line.ends_with("*/") {
+            open_block_lines.push(line);
+        } else {
+            break;
+        }
+    }
+    comment_lines
 }
 
 fn is_test_fn(item_fn: &ItemFn) -> bool {